@@ -0,0 +1,7 @@
+//!
+//! The shared library with the C interface of the aes crate, libaes_ffi.so on Linux,
+//! libaes_ffi.dylib on macOS and aes_ffi.dll on Windows. The functions are in
+//! aes/src/ffi.rs and declared in aes/include/aes.h.
+//!
+
+pub use aes::ffi::*;
@@ -0,0 +1,69 @@
+//!
+//! Throughput of the modes and the building blocks with the backend selected by the
+//! features. Compare backends by running the benches once per feature set:
+//! cargo bench -p aes, cargo bench -p aes --features fast-tables and
+//! cargo bench -p aes --features constant-time.
+//!
+
+use aes::cipher::{Cipher, Mode};
+use aes::ctr::Counter;
+use aes::throughput::BACKEND;
+use aes::{Key, sha256};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+
+///
+/// Lengths of the data encrypted at a time, one block and a typical chunk.
+///
+const LENGTHS: [usize; 2] = [16, 64 * 1024];
+
+fn modes(criterion: &mut Criterion) {
+    let cipher = Cipher::new(&Key::from_slice(&[0x2b; 16]).unwrap());
+    let iv = [0x0f; 16];
+    let modes = [
+        ("ecb", Mode::Ecb),
+        ("cbc", Mode::Cbc { iv }),
+        ("ctr", Mode::Ctr { counter: Counter::new(iv) }),
+        ("cfb", Mode::Cfb { iv }),
+        ("ofb", Mode::Ofb { iv }),
+        ("gcm", Mode::Gcm { nonce: &iv[..12], aad: &[] }),
+    ];
+    for length in LENGTHS {
+        let data = vec![0x5a; length];
+        let mut group = criterion.benchmark_group(format!("encrypt/{length}"));
+        group.throughput(Throughput::Bytes(length as u64));
+        for (name, mode) in modes {
+            group.bench_with_input(BenchmarkId::new(name, BACKEND), &data, |bencher, data| bencher.iter(|| cipher.encrypt(mode, black_box(data)).unwrap()));
+        }
+        group.finish();
+    }
+    let data = vec![0x5a; LENGTHS[1]];
+    let encrypted = cipher.encrypt(Mode::Cbc { iv }, &data).unwrap();
+    let mut group = criterion.benchmark_group(format!("decrypt/{}", LENGTHS[1]));
+    group.throughput(Throughput::Bytes(LENGTHS[1] as u64));
+    group.bench_function(BenchmarkId::new("cbc", BACKEND), |bencher| bencher.iter(|| cipher.decrypt(Mode::Cbc { iv }, black_box(&encrypted)).unwrap()));
+    group.finish();
+}
+
+fn key_schedule(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("key_schedule");
+    for length in [16, 24, 32] {
+        let key = Key::from_slice(&vec![0x2b; length]).unwrap();
+        group.bench_function(BenchmarkId::new(format!("aes{}", length * 8), BACKEND), |bencher| bencher.iter(|| black_box(&key).roundkeys()));
+    }
+    group.finish();
+}
+
+///
+/// SHA-256 as a reference point that does not depend on the backend.
+///
+fn reference(criterion: &mut Criterion) {
+    let data = vec![0x5a; LENGTHS[1]];
+    let mut group = criterion.benchmark_group(format!("sha256/{}", LENGTHS[1]));
+    group.throughput(Throughput::Bytes(LENGTHS[1] as u64));
+    group.bench_function("sha256", |bencher| bencher.iter(|| sha256::digest(black_box(&data))));
+    group.finish();
+}
+
+criterion_group!(benches, modes, key_schedule, reference);
+criterion_main!(benches);
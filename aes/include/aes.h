@@ -0,0 +1,228 @@
+/* The C interface of the aes crate. Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#ifndef AES_H
+#define AES_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/*
+
+ AesStatus enum with the result of a call.
+
+ */
+typedef enum AesStatus {
+  /*
+   The call succeeded.
+   */
+  AES_STATUS_OK = 0,
+  /*
+   A pointer that must be set is null.
+   */
+  AES_STATUS_NULL_POINTER = 1,
+  /*
+   The key is not 16, 24 or 32 bytes.
+   */
+  AES_STATUS_INVALID_KEY_LENGTH = 2,
+  /*
+   The iv or nonce has the wrong length for the mode.
+   */
+  AES_STATUS_INVALID_IV_LENGTH = 3,
+  /*
+   The data has the wrong length for the mode.
+   */
+  AES_STATUS_INVALID_DATA_LENGTH = 4,
+  /*
+   The decrypted data does not end with valid padding.
+   */
+  AES_STATUS_PADDING = 5,
+  /*
+   The authentication tag does not match the data.
+   */
+  AES_STATUS_AUTHENTICATION = 6,
+  /*
+   The output buffer is too small. The needed length is written to output_length.
+   */
+  AES_STATUS_BUFFER_TOO_SMALL = 7,
+  /*
+   Any other error.
+   */
+  AES_STATUS_ERROR = 8,
+  /*
+   The mode is not one of the AesMode values.
+   */
+  AES_STATUS_INVALID_MODE = 9,
+} AesStatus;
+
+/*
+
+ AesMode enum with the modes available through the C interface.
+
+ */
+typedef enum AesMode {
+  /*
+   Electronic codebook with PKCS#7 padding, no iv.
+   */
+  AES_MODE_ECB = 0,
+  /*
+   Cipher block chaining with PKCS#7 padding and a 16 byte iv.
+   */
+  AES_MODE_CBC = 1,
+  /*
+   Counter mode with a 16 byte first counter block.
+   */
+  AES_MODE_CTR = 2,
+  /*
+   Cipher feedback with a 16 byte iv.
+   */
+  AES_MODE_CFB = 3,
+  /*
+   Output feedback with a 16 byte iv.
+   */
+  AES_MODE_OFB = 4,
+  /*
+   Galois/Counter mode with a nonce, normally 12 bytes, and associated data. The 16 byte
+   tag follows the ciphertext.
+   */
+  AES_MODE_GCM = 5,
+} AesMode;
+
+/*
+
+ AesContext struct with the cipher of a key. It is opaque to C.
+
+ */
+typedef struct AesContext AesContext;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+
+ Creates a context for the key.
+
+ key: Pointer to the key.
+ key_length: 16, 24 or 32.
+
+ result: The context, or null if the key is null or has an unsupported length. The
+         context must be freed with aes_context_free.
+
+ # Safety
+
+ key must point to key_length readable bytes.
+
+ */
+struct AesContext *aes_context_new(const uint8_t *key, size_t key_length);
+
+/*
+
+ Frees a context. The roundkeys are overwritten. Null is ignored.
+
+ context: A context from aes_context_new.
+
+ # Safety
+
+ context must be null or a context from aes_context_new that has not been freed.
+
+ */
+void aes_context_free(struct AesContext *context);
+
+/*
+
+ Encrypts a buffer.
+
+ context: A context from aes_context_new.
+ mode: The mode, one of the AesMode values.
+ iv: The iv, counter block or nonce. May be null for ECB.
+ iv_length: 0 for ECB, 16 for CBC, CTR, CFB and OFB, and the nonce length for GCM.
+ aad: Associated data for GCM. May be null when aad_length is 0.
+ aad_length: Length of the associated data.
+ input: The plaintext.
+ input_length: Length of the plaintext.
+ output: The buffer for the ciphertext, followed by the tag in GCM.
+ output_length: The capacity of output, replaced by the length of the ciphertext.
+
+ result: The status.
+
+ # Safety
+
+ Each pointer must be null or point to the given number of bytes, readable for the
+ inputs and writable for output. output_length must point to a writable size_t.
+
+ */
+enum AesStatus aes_encrypt(const struct AesContext *context,
+                           uint32_t mode,
+                           const uint8_t *iv,
+                           size_t iv_length,
+                           const uint8_t *aad,
+                           size_t aad_length,
+                           const uint8_t *input,
+                           size_t input_length,
+                           uint8_t *output,
+                           size_t *output_length);
+
+/*
+
+ Decrypts a buffer. Nothing is written to output if the padding or the tag is invalid.
+
+ context: A context from aes_context_new.
+ mode: The mode used when encrypting, one of the AesMode values.
+ iv: The iv, counter block or nonce used when encrypting. May be null for ECB.
+ iv_length: 0 for ECB, 16 for CBC, CTR, CFB and OFB, and the nonce length for GCM.
+ aad: Associated data for GCM. May be null when aad_length is 0.
+ aad_length: Length of the associated data.
+ input: The ciphertext, followed by the tag in GCM.
+ input_length: Length of the ciphertext.
+ output: The buffer for the plaintext.
+ output_length: The capacity of output, replaced by the length of the plaintext.
+
+ result: The status.
+
+ # Safety
+
+ Each pointer must be null or point to the given number of bytes, readable for the
+ inputs and writable for output. output_length must point to a writable size_t.
+
+ */
+enum AesStatus aes_decrypt(const struct AesContext *context,
+                           uint32_t mode,
+                           const uint8_t *iv,
+                           size_t iv_length,
+                           const uint8_t *aad,
+                           size_t aad_length,
+                           const uint8_t *input,
+                           size_t input_length,
+                           uint8_t *output,
+                           size_t *output_length);
+
+/*
+
+ Computes the key schedule, the roundkeys of a key one after the other.
+
+ key: Pointer to the key.
+ key_length: 16, 24 or 32.
+ output: The buffer for the roundkeys, 176, 208 or 240 bytes.
+ output_length: The capacity of output, replaced by the length of the roundkeys.
+
+ result: The status.
+
+ # Safety
+
+ key must point to key_length readable bytes, output to output_length writable bytes
+ and output_length to a writable size_t.
+
+ */
+enum AesStatus aes_key_schedule(const uint8_t *key,
+                                size_t key_length,
+                                uint8_t *output,
+                                size_t *output_length);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* AES_H */
@@ -0,0 +1,294 @@
+//!
+//! Archive format for encrypting a directory as a single stream. The tree is walked in
+//! name order and every directory and file becomes an entry with its path relative to
+//! the root. The archive is read as a stream, so the files are opened one at a time while
+//! it is encrypted and the whole tree is never held in memory.
+//!
+//! Layout: the magic bytes, then the entries, each a kind byte, the length of the path as
+//! a big-endian u16 and the path in UTF-8 with / between the components. A file entry
+//! continues with the length of the contents as a big-endian u64 and the contents. An end
+//! byte follows the last entry. Symbolic links and other special files are not stored.
+//!
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+///
+/// The first bytes of an archive.
+///
+pub const MAGIC: [u8; 4] = *b"AESA";
+
+///
+/// Kinds of the entries.
+///
+const END: u8 = 0;
+const DIRECTORY: u8 = 1;
+const FILE: u8 = 2;
+
+///
+/// Entry enum with a directory or a file found in the tree.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Entry {
+    // A directory with its path in the archive.
+    Directory(String),
+    // A file with its path in the archive and its path on disk.
+    File(String, PathBuf),
+}
+
+///
+/// ArchiveReader struct that gives the archive of a directory as a Read stream.
+///
+pub struct ArchiveReader {
+    // The entries that are not yet read.
+    entries: VecDeque<Entry>,
+    // The bytes of the current entry header that are not yet read.
+    buffer: Vec<u8>,
+    position: usize,
+    // The contents of the current file that are not yet read.
+    file: Option<io::Take<File>>,
+    // The end byte has been added to the buffer.
+    finished: bool,
+    // The length of the archive when the tree was walked.
+    length: u64,
+}
+
+impl ArchiveReader {
+    ///
+    /// Walks the directory and creates a reader for its archive.
+    ///
+    /// root: The directory to archive.
+    ///
+    /// result: An ArchiveReader struct, or an error if the directory can not be read or a
+    ///         path is not valid UTF-8.
+    ///
+    pub fn new(root: &Path) -> io::Result<ArchiveReader> {
+        let mut entries = VecDeque::new();
+        walk(root, "", &mut entries)?;
+        let mut length = (MAGIC.len() + 1) as u64;
+        for entry in &entries {
+            length += match entry {
+                Entry::Directory(name) => 3 + name.len() as u64,
+                Entry::File(name, path) => 11 + name.len() as u64 + fs::metadata(path)?.len(),
+            };
+        }
+        Ok(ArchiveReader { entries, buffer: MAGIC.to_vec(), position: 0, file: None, finished: false, length })
+    }
+
+    ///
+    /// Returns the length of the archive, as long as the files do not change while it is read.
+    ///
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    ///
+    /// Puts the header of the next entry in the buffer and opens its file.
+    ///
+    fn next_entry(&mut self) -> io::Result<()> {
+        self.position = 0;
+        self.buffer = match self.entries.pop_front() {
+            Some(Entry::Directory(name)) => entry_header(DIRECTORY, &name)?,
+            Some(Entry::File(name, path)) => {
+                let file = File::open(&path)?;
+                let length = file.metadata()?.len();
+                self.file = Some(file.take(length));
+                [entry_header(FILE, &name)?, length.to_be_bytes().to_vec()].concat()
+            }
+            None if !self.finished => {
+                self.finished = true;
+                [END].to_vec()
+            }
+            None => Vec::new(),
+        };
+        Ok(())
+    }
+}
+
+impl Read for ArchiveReader {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            if self.position < self.buffer.len() {
+                let count = (self.buffer.len() - self.position).min(buffer.len());
+                buffer[..count].copy_from_slice(&self.buffer[self.position..self.position + count]);
+                self.position += count;
+                return Ok(count);
+            }
+            if let Some(file) = &mut self.file {
+                let count = file.read(buffer)?;
+                if count > 0 {
+                    return Ok(count);
+                }
+                if file.limit() > 0 {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "File became shorter while it was archived"));
+                }
+                self.file = None;
+            }
+            if self.finished && self.entries.is_empty() {
+                return Ok(0);
+            }
+            self.next_entry()?;
+        }
+    }
+}
+
+///
+/// Adds the entries of a directory, sorted by name, and the entries below them.
+///
+fn walk(directory: &Path, prefix: &str, entries: &mut VecDeque<Entry>) -> io::Result<()> {
+    let mut children = fs::read_dir(directory)?.collect::<io::Result<Vec<_>>>()?;
+    children.sort_by_key(|child| child.file_name());
+    for child in children {
+        let name = child.file_name().into_string().map_err(|name| io::Error::new(io::ErrorKind::InvalidData, alloc::format!("Path is not valid UTF-8: {name:?}")))?;
+        let name = if prefix.is_empty() { name } else { alloc::format!("{prefix}/{name}") };
+        let kind = child.file_type()?;
+        if kind.is_dir() {
+            entries.push_back(Entry::Directory(name.clone()));
+            walk(&child.path(), &name, entries)?;
+        } else if kind.is_file() {
+            entries.push_back(Entry::File(name, child.path()));
+        }
+    }
+    Ok(())
+}
+
+///
+/// Returns the kind, the length of the path and the path.
+///
+fn entry_header(kind: u8, name: &str) -> io::Result<Vec<u8>> {
+    let length = u16::try_from(name.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, alloc::format!("Path is too long: {name}")))?;
+    Ok([&[kind][..], &length.to_be_bytes(), name.as_bytes()].concat())
+}
+
+///
+/// Restores the tree of an archive below the root. Paths that would end up outside the
+/// root are rejected.
+///
+/// input: The reader with the archive.
+/// root: The directory to restore the tree in. It is created if it does not exist.
+/// overwrite: Replace files that already exist instead of stopping with an error.
+///
+/// result: Ok, or an error if reading or writing fails, a file already exists and is not
+///         overwritten or the archive is malformed.
+///
+pub fn extract<R: Read>(mut input: R, root: &Path, overwrite: bool) -> io::Result<()> {
+    let mut magic = [0u8; 4];
+    input.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(invalid_archive("Not an archive"));
+    }
+    fs::create_dir_all(root)?;
+    loop {
+        let mut kind = [0u8; 1];
+        input.read_exact(&mut kind)?;
+        if kind[0] == END {
+            return Ok(());
+        }
+        let mut length = [0u8; 2];
+        input.read_exact(&mut length)?;
+        let mut name = alloc::vec![0u8; u16::from_be_bytes(length) as usize];
+        input.read_exact(&mut name)?;
+        let path = root.join(relative_path(&name)?);
+        match kind[0] {
+            DIRECTORY => fs::create_dir_all(&path)?,
+            FILE => {
+                let mut length = [0u8; 8];
+                input.read_exact(&mut length)?;
+                let length = u64::from_be_bytes(length);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let mut file = if overwrite { File::create(&path)? } else { File::create_new(&path)? };
+                if io::copy(&mut (&mut input).take(length), &mut file)? != length {
+                    return Err(invalid_archive("Archive ends inside a file"));
+                }
+            }
+            _ => return Err(invalid_archive("Unknown entry")),
+        }
+    }
+}
+
+///
+/// Converts a path in the archive to a relative path. Empty components, . and .. are
+/// rejected, so an archive can not write outside the root.
+///
+fn relative_path(name: &[u8]) -> io::Result<PathBuf> {
+    let name = core::str::from_utf8(name).map_err(|_| invalid_archive("Path is not valid UTF-8"))?;
+    let mut path = PathBuf::new();
+    for component in name.split('/') {
+        if component.is_empty() || component == "." || component == ".." || component.contains(['\\', ':']) {
+            return Err(invalid_archive("Path leaves the directory"));
+        }
+        path.push(component);
+    }
+    Ok(path)
+}
+
+fn invalid_archive(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///
+    /// Creates an empty directory with a name that no other test uses.
+    ///
+    fn directory(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(alloc::format!("aes-archive-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let source = directory("source");
+        fs::create_dir_all(source.join("a/b")).unwrap();
+        fs::create_dir_all(source.join("empty")).unwrap();
+        fs::write(source.join("top.txt"), b"Attack at dawn").unwrap();
+        fs::write(source.join("a/b/data.bin"), (0..100_000).map(|value| value as u8).collect::<Vec<u8>>()).unwrap();
+        fs::write(source.join("a/none"), b"").unwrap();
+        let mut reader = ArchiveReader::new(&source).unwrap();
+        let length = reader.length();
+        let mut archive = Vec::new();
+        reader.read_to_end(&mut archive).unwrap();
+        assert_eq!(archive.len() as u64, length);
+        let target = directory("target");
+        extract(&archive[..], &target, false).unwrap();
+        assert_eq!(fs::read(target.join("top.txt")).unwrap(), b"Attack at dawn");
+        assert_eq!(fs::read(target.join("a/b/data.bin")).unwrap(), fs::read(source.join("a/b/data.bin")).unwrap());
+        assert_eq!(fs::read(target.join("a/none")).unwrap(), b"");
+        assert!(target.join("empty").is_dir());
+        // Files are only overwritten when asked to.
+        assert_eq!(extract(&archive[..], &target, false).unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        fs::write(target.join("top.txt"), b"Changed").unwrap();
+        extract(&archive[..], &target, true).unwrap();
+        assert_eq!(fs::read(target.join("top.txt")).unwrap(), b"Attack at dawn");
+        let _ = fs::remove_dir_all(&source);
+        let _ = fs::remove_dir_all(&target);
+    }
+
+    #[test]
+    fn test_invalid_archive() {
+        let target = directory("invalid");
+        for name in ["../escape", "/absolute", "a//b", "a/./b", "a\\..\\b"] {
+            let archive = [&MAGIC[..], &entry_header(DIRECTORY, name).unwrap(), &[END]].concat();
+            assert_eq!(extract(&archive[..], &target, false).unwrap_err().kind(), io::ErrorKind::InvalidData, "{name}");
+        }
+        assert_eq!(extract(&b"AESF"[..], &target, false).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let archive = [&MAGIC[..], &entry_header(FILE, "short").unwrap(), &10u64.to_be_bytes(), b"12345"].concat();
+        assert_eq!(extract(&archive[..], &target, false).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let archive = [&MAGIC[..], &entry_header(DIRECTORY, "cut").unwrap()].concat();
+        assert_eq!(extract(&archive[..], &target, false).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        let _ = fs::remove_dir_all(&target);
+    }
+}
@@ -0,0 +1,308 @@
+//!
+//! Argon2id, a memory-hard password hash. A matrix of 1 KiB blocks is filled where each
+//! block depends on the previous block and one earlier block, so guessing a password
+//! needs the whole matrix in memory. The first half of the first pass picks the earlier
+//! blocks independently of the password (Argon2i) and the rest picks them from the
+//! previous block (Argon2d).
+//! Description: https://en.wikipedia.org/wiki/Argon2
+//! Specification: RFC 9106.
+//!
+
+use crate::blake2b::{self, Blake2b, MAX_OUTPUT_LENGTH};
+use alloc::{vec, vec::Vec};
+
+///
+/// Version 1.3 of Argon2.
+///
+const VERSION: u32 = 0x13;
+
+///
+/// The type number of Argon2id.
+///
+const TYPE_ID: u32 = 2;
+
+///
+/// Number of 64 bit words in a 1 KiB block.
+///
+const BLOCK_WORDS: usize = 128;
+
+///
+/// Number of slices in a lane. The lanes are synchronized after each slice.
+///
+const SLICES: usize = 4;
+
+///
+/// Number of reference addresses in an address block.
+///
+const ADDRESSES_PER_BLOCK: usize = BLOCK_WORDS;
+
+type Block = [u64; BLOCK_WORDS];
+
+///
+/// Params struct with the cost parameters.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Params {
+    /// Memory in KiB, at least 8 times the parallelism.
+    pub memory: u32,
+    /// Number of passes over the memory, at least 1.
+    pub iterations: u32,
+    /// Number of lanes, between 1 and 2^24 - 1.
+    pub parallelism: u32,
+}
+
+impl Default for Params {
+    ///
+    /// The second recommended option of RFC 9106, 64 MiB of memory and 3 passes.
+    ///
+    fn default() -> Self {
+        Params { memory: 64 * 1024, iterations: 3, parallelism: 4 }
+    }
+}
+
+///
+/// The variable length hash H' built from BLAKE2b. Hashes up to 64 bytes are a single
+/// BLAKE2b hash, longer hashes are chained BLAKE2b hashes of which the first 32 bytes are used.
+///
+fn variable_hash(length: usize, data: &[&[u8]]) -> Vec<u8> {
+    let hash_of = |output_length: usize, parts: &[&[u8]]| {
+        let mut blake = Blake2b::new(output_length);
+        for part in parts {
+            blake.update(part);
+        }
+        blake.finalize()
+    };
+    let prefix = (length as u32).to_le_bytes();
+    let parts: Vec<&[u8]> = core::iter::once(&prefix[..]).chain(data.iter().copied()).collect();
+    if length <= MAX_OUTPUT_LENGTH {
+        return hash_of(length, &parts);
+    }
+    let mut result = Vec::with_capacity(length);
+    let mut hash = hash_of(MAX_OUTPUT_LENGTH, &parts);
+    while length - result.len() > MAX_OUTPUT_LENGTH {
+        result.extend_from_slice(&hash[..32]);
+        hash = blake2b::digest(MAX_OUTPUT_LENGTH.min(length - result.len()), &hash);
+    }
+    result.extend_from_slice(&hash);
+    result
+}
+
+///
+/// The permutation P from the BLAKE2b round with the multiplication added to the additions,
+/// applied to 16 words given by their indexes in the block.
+///
+fn permute(block: &mut Block, indexes: [usize; 16]) {
+    let mut mix = |a: usize, b: usize, c: usize, d: usize| {
+        let (a, b, c, d) = (indexes[a], indexes[b], indexes[c], indexes[d]);
+        let multiply = |x: u64, y: u64| 2u64.wrapping_mul(x & 0xffff_ffff).wrapping_mul(y & 0xffff_ffff);
+        block[a] = block[a].wrapping_add(block[b]).wrapping_add(multiply(block[a], block[b]));
+        block[d] = (block[d] ^ block[a]).rotate_right(32);
+        block[c] = block[c].wrapping_add(block[d]).wrapping_add(multiply(block[c], block[d]));
+        block[b] = (block[b] ^ block[c]).rotate_right(24);
+        block[a] = block[a].wrapping_add(block[b]).wrapping_add(multiply(block[a], block[b]));
+        block[d] = (block[d] ^ block[a]).rotate_right(16);
+        block[c] = block[c].wrapping_add(block[d]).wrapping_add(multiply(block[c], block[d]));
+        block[b] = (block[b] ^ block[c]).rotate_right(63);
+    };
+    mix(0, 4, 8, 12);
+    mix(1, 5, 9, 13);
+    mix(2, 6, 10, 14);
+    mix(3, 7, 11, 15);
+    mix(0, 5, 10, 15);
+    mix(1, 6, 11, 12);
+    mix(2, 7, 8, 13);
+    mix(3, 4, 9, 14);
+}
+
+///
+/// The compression function G. The xor of the inputs is permuted as 8 rows and then
+/// as 8 columns of 16 byte registers, and xored with itself before the permutation.
+///
+fn compress(x: &Block, y: &Block) -> Block {
+    let mut r = [0u64; BLOCK_WORDS];
+    for (idx, value) in r.iter_mut().enumerate() {
+        *value = x[idx] ^ y[idx];
+    }
+    let mut z = r;
+    for row in 0..8 {
+        permute(&mut z, core::array::from_fn(|idx| 16 * row + idx));
+    }
+    for column in 0..8 {
+        permute(&mut z, core::array::from_fn(|idx| 2 * column + 16 * (idx / 2) + idx % 2));
+    }
+    for (value, word) in z.iter_mut().zip(r) {
+        *value ^= word;
+    }
+    z
+}
+
+///
+/// Converts the bytes from H' into a block.
+///
+fn to_block(bytes: &[u8]) -> Block {
+    core::array::from_fn(|idx| u64::from_le_bytes(bytes[8 * idx..8 * idx + 8].try_into().unwrap()))
+}
+
+///
+/// Hashes the password with all the inputs of Argon2id.
+///
+/// password: The password as bytes.
+/// salt: A random salt of at least 8 bytes, stored with the data.
+/// secret: An optional key that is not stored with the data.
+/// aad: Optional associated data.
+/// params: The cost parameters.
+/// length: The length of the hash in bytes, at least 4.
+///
+/// result: A vector of bytes with the hash.
+///
+/// Panics if a parameter is outside the ranges of RFC 9106.
+///
+pub fn hash(password: &[u8], salt: &[u8], secret: &[u8], aad: &[u8], params: &Params, length: usize) -> Vec<u8> {
+    let Params { memory, iterations, parallelism } = *params;
+    assert!((1..1 << 24).contains(&parallelism), "Parallelism must be between 1 and 2^24 - 1: {parallelism}");
+    assert!(memory >= 8 * parallelism, "Memory must be at least 8 KiB per lane: {memory}");
+    assert!(iterations >= 1, "Iteration count must be at least 1");
+    assert!(salt.len() >= 8, "Salt must be at least 8 bytes: {}", salt.len());
+    assert!(length >= 4, "Hash must be at least 4 bytes: {length}");
+
+    let lanes = parallelism as usize;
+    let lane_length = (memory as usize / (SLICES * lanes)) * SLICES;
+    let segment_length = lane_length / SLICES;
+    let block_count = lane_length * lanes;
+
+    let mut initial = Blake2b::new(MAX_OUTPUT_LENGTH);
+    for value in [parallelism, length as u32, memory, iterations, VERSION, TYPE_ID] {
+        initial.update(&value.to_le_bytes());
+    }
+    for input in [password, salt, secret, aad] {
+        initial.update(&(input.len() as u32).to_le_bytes());
+        initial.update(input);
+    }
+    let initial = initial.finalize();
+
+    let mut blocks: Vec<Block> = vec![[0; BLOCK_WORDS]; block_count];
+    for lane in 0..lanes {
+        for column in 0..2 {
+            blocks[lane * lane_length + column] = to_block(&variable_hash(1024, &[&initial, &(column as u32).to_le_bytes(), &(lane as u32).to_le_bytes()]));
+        }
+    }
+
+    let zero: Block = [0; BLOCK_WORDS];
+    for pass in 0..iterations as usize {
+        for slice in 0..SLICES {
+            for lane in 0..lanes {
+                let independent = pass == 0 && slice < SLICES / 2;
+                let mut input: Block = [0; BLOCK_WORDS];
+                let mut addresses: Block = [0; BLOCK_WORDS];
+                if independent {
+                    input[..6].copy_from_slice(&[pass as u64, lane as u64, slice as u64, block_count as u64, iterations as u64, TYPE_ID as u64]);
+                }
+                let start = if pass == 0 && slice == 0 { 2 } else { 0 };
+                for index in start..segment_length {
+                    let column = slice * segment_length + index;
+                    let current = lane * lane_length + column;
+                    let previous = if column == 0 { current + lane_length - 1 } else { current - 1 };
+
+                    let pseudo_random = if independent {
+                        if index == start || index % ADDRESSES_PER_BLOCK == 0 {
+                            input[6] += 1;
+                            addresses = compress(&zero, &compress(&zero, &input));
+                        }
+                        addresses[index % ADDRESSES_PER_BLOCK]
+                    } else {
+                        blocks[previous][0]
+                    };
+
+                    let reference_lane = if pass == 0 && slice == 0 { lane } else { (pseudo_random >> 32) as usize % lanes };
+                    let same_lane = reference_lane == lane;
+                    let finished = if pass == 0 { slice * segment_length } else { lane_length - segment_length };
+                    let reference_area = if same_lane {
+                        finished + index - 1
+                    } else if index == 0 {
+                        finished - 1
+                    } else {
+                        finished
+                    };
+                    let j1 = pseudo_random & 0xffff_ffff;
+                    let relative = reference_area - 1 - ((reference_area as u64 * ((j1 * j1) >> 32)) >> 32) as usize;
+                    let area_start = if pass == 0 || slice == SLICES - 1 { 0 } else { (slice + 1) * segment_length };
+                    let reference = reference_lane * lane_length + (area_start + relative) % lane_length;
+
+                    let block = compress(&blocks[previous], &blocks[reference]);
+                    if pass == 0 {
+                        blocks[current] = block;
+                    } else {
+                        for (value, word) in blocks[current].iter_mut().zip(block) {
+                            *value ^= word;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut last = blocks[lane_length - 1];
+    for lane in 1..lanes {
+        for (value, word) in last.iter_mut().zip(blocks[lane * lane_length + lane_length - 1]) {
+            *value ^= word;
+        }
+    }
+    let bytes: Vec<u8> = last.iter().flat_map(|word| word.to_le_bytes()).collect();
+    variable_hash(length, &[&bytes])
+}
+
+///
+/// Derives a key from the password.
+///
+/// password: The password as bytes.
+/// salt: A random salt of at least 8 bytes, stored with the data. 16 bytes are recommended.
+/// params: The cost parameters.
+/// length: The length of the derived key in bytes, at least 4.
+///
+/// result: A vector of bytes with the derived key.
+///
+/// Panics if a parameter is outside the ranges of RFC 9106.
+///
+pub fn derive(password: &[u8], salt: &[u8], params: &Params, length: usize) -> Vec<u8> {
+    hash(password, salt, &[], &[], params, length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    ///
+    /// The Argon2id test vector from section 5.3 of RFC 9106.
+    ///
+    #[test]
+    fn test_rfc9106() {
+        let params = Params { memory: 32, iterations: 3, parallelism: 4 };
+        let tag = hash(&[0x01; 32], &[0x02; 16], &[0x03; 8], &[0x04; 12], &params, 32);
+        assert_eq!(tag, hex("0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659"));
+    }
+
+    #[test]
+    fn test_variable_hash() {
+        assert_eq!(variable_hash(16, &[b"abc"]), blake2b::digest(16, &[&16u32.to_le_bytes()[..], b"abc"].concat()));
+        for length in [64, 65, 96, 100, 1024] {
+            assert_eq!(variable_hash(length, &[b"abc"]).len(), length);
+        }
+    }
+
+    #[test]
+    fn test_parameters_change_key() {
+        let params = Params { memory: 64, iterations: 1, parallelism: 2 };
+        let key = derive(b"password", b"saltsalt", &params, 16);
+        assert_eq!(key, derive(b"password", b"saltsalt", &params, 16));
+        assert_ne!(key, derive(b"passwore", b"saltsalt", &params, 16));
+        assert_ne!(key, derive(b"password", b"saltsalu", &params, 16));
+        assert_ne!(key, derive(b"password", b"saltsalt", &Params { memory: 72, ..params }, 16));
+        assert_ne!(key, derive(b"password", b"saltsalt", &Params { iterations: 2, ..params }, 16));
+        assert_ne!(key, derive(b"password", b"saltsalt", &Params { parallelism: 1, ..params }, 16));
+    }
+}
@@ -1,25 +1,342 @@
-use clap::Parser;
+use aes::argon2;
+use aes::format::FileMode;
+use aes::kdf::{Kdf, KdfLimits};
+use aes::{KeySize, pbkdf2, pipeline, scrypt};
+use clap::{Parser, Subcommand, ValueEnum};
+
+/// Key derivation functions that can be selected
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KdfName {
+    Pbkdf2,
+    Argon2id,
+    Scrypt,
+}
+
+/// Key sizes that can be selected, in bits
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeySizeName {
+    #[value(name = "128")]
+    Aes128,
+    #[value(name = "192")]
+    Aes192,
+    #[value(name = "256")]
+    Aes256,
+}
+
+impl KeySizeName {
+    /// The key size with this name
+    pub fn key_size(self) -> KeySize {
+        match self {
+            KeySizeName::Aes128 => KeySize::Aes128,
+            KeySizeName::Aes192 => KeySize::Aes192,
+            KeySizeName::Aes256 => KeySize::Aes256,
+        }
+    }
+}
+
+/// Modes of operation that can be selected
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModeName {
+    Ecb,
+    Cbc,
+    Ctr,
+    Gcm,
+}
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Args {
-    /// File to encrypt or decrypt
+    /// Check the cipher against the known answer vectors before anything else
+    #[arg(long, global = true)]
+    pub selftest: bool,
+
+    /// Profile of the configuration file to take settings from that are not given here. The profile named default is used without it
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// The commands of the program
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Encrypt a file, or a directory with --recursive
+    Encrypt(EncryptArgs),
+
+    /// Decrypt a file, or restore a directory with --recursive
+    Decrypt(FileArgs),
+
+    /// Check that a file decrypts with the password, and in gcm mode that it has not been changed, without writing anything
+    Verify(VerifyArgs),
+
+    /// Print the header of an encrypted file. Needs no password
+    Inspect(InspectArgs),
+
+    /// Generate a random key from the random source of the operating system
+    Keygen(KeygenArgs),
+
+    /// Encrypt a buffer in memory with each mode and print the MB/s of the backend this program is built with
+    Bench {
+        /// Key size in bits
+        #[arg(long, value_enum, default_value_t = KeySizeName::Aes256)]
+        key_size: KeySizeName,
+
+        /// Bytes encrypted at a time
+        #[arg(long, default_value_t = 1 << 20)]
+        length: usize,
+
+        /// Milliseconds spent on each mode
+        #[arg(long, default_value_t = 1000)]
+        milliseconds: u64,
+    },
+}
+
+/// Arguments for reading one file or directory and writing another
+#[derive(clap::Args, Debug)]
+pub struct FileArgs {
+    /// File to encrypt or decrypt, - for standard input
     #[arg(short, long)]
     pub input_file: String,
 
-    /// Output file after encrypt or decrypt
-    #[arg(short, long)]
+    /// Output file after encrypt or decrypt, - for standard output
+    #[arg(short, long, required_unless_present = "in_place", default_value = "")]
     pub output_file: String,
 
-    /// Password
+    #[command(flatten)]
+    pub password: PasswordArgs,
+
+    #[command(flatten)]
+    pub limits: KdfLimitArgs,
+
+    /// Bytes read and encrypted or decrypted at a time, a multiple of 16
+    #[arg(long, default_value_t = pipeline::DEFAULT_CHUNK_LENGTH)]
+    pub chunk_size: usize,
+
+    /// Threads each chunk is split over in ecb and ctr mode, where the blocks are independent
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub jobs: u32,
+
+    /// Encrypt the input directory with everything in it, or restore it in the output directory when decrypting
+    #[arg(short, long)]
+    pub recursive: bool,
+
+    /// Overwrite the output file, or files in the output directory, if they already exist
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Replace the input file with the result, keeping its permissions
+    #[arg(long, conflicts_with_all = ["output_file", "recursive"])]
+    pub in_place: bool,
+
+    /// Show the progress bar also when standard error is not a terminal
+    #[arg(long, conflicts_with = "quiet")]
+    pub progress: bool,
+
+    /// Show no progress bar and no message when the operation succeeds
     #[arg(short, long)]
-    pub password: String,
+    pub quiet: bool,
+}
+
+/// Arguments of the encrypt command
+#[derive(clap::Args, Debug)]
+pub struct EncryptArgs {
+    #[command(flatten)]
+    pub files: FileArgs,
+
+    /// Mode of operation. Decryption reads it from the file
+    #[arg(long, value_enum, default_value_t = ModeName::Gcm)]
+    pub mode: ModeName,
+
+    /// Key size in bits. Decryption reads it from the file
+    #[arg(long, value_enum, default_value_t = KeySizeName::Aes256)]
+    pub key_size: KeySizeName,
+
+    /// Key derivation function. Decryption reads it from the file
+    #[arg(long, value_enum, default_value_t = KdfName::Pbkdf2)]
+    pub kdf: KdfName,
+
+    /// Iterations of the key derivation function. Defaults to 600000 for PBKDF2 and 3 for Argon2id
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub iterations: Option<u32>,
+
+    /// Argon2id memory in KiB
+    #[arg(long, default_value_t = argon2::Params::default().memory)]
+    pub memory: u32,
+
+    /// Parallelism of Argon2id or p of scrypt. Defaults to 4 for Argon2id and 1 for scrypt
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub parallelism: Option<u32>,
+
+    /// scrypt cost N, a power of two
+    #[arg(long, default_value_t = scrypt::Params::default().n)]
+    pub cost: u32,
+
+    /// scrypt block size r
+    #[arg(long, default_value_t = scrypt::Params::default().r, value_parser = clap::value_parser!(u32).range(1..))]
+    pub block_size: u32,
+}
+
+/// Arguments of the keygen command
+#[derive(clap::Args, Debug)]
+pub struct KeygenArgs {
+    /// Key size in bits
+    #[arg(long, value_enum, default_value_t = KeySizeName::Aes256)]
+    pub size: KeySizeName,
+
+    /// File to write the key to, - for standard output. Only the owner can read it on Unix
+    #[arg(long)]
+    pub out: String,
+
+    /// Write the key as hexadecimal text instead of raw bytes
+    #[arg(long)]
+    pub hex: bool,
+
+    /// Overwrite the file if it already exists
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+/// Arguments of the verify command
+#[derive(clap::Args, Debug)]
+pub struct VerifyArgs {
+    /// File to check, - for standard input
+    #[arg(short, long)]
+    pub input_file: String,
+
+    #[command(flatten)]
+    pub password: PasswordArgs,
+
+    #[command(flatten)]
+    pub limits: KdfLimitArgs,
+
+    /// Bytes read and decrypted at a time, a multiple of 16
+    #[arg(long, default_value_t = pipeline::DEFAULT_CHUNK_LENGTH)]
+    pub chunk_size: usize,
+
+    /// Threads each chunk is split over in ecb and ctr mode, where the blocks are independent
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub jobs: u32,
+
+    /// Show the progress bar also when standard error is not a terminal
+    #[arg(long, conflicts_with = "quiet")]
+    pub progress: bool,
+
+    /// Show no progress bar and no message, only the exit code tells the result
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+/// Arguments of the inspect command
+#[derive(clap::Args, Debug)]
+pub struct InspectArgs {
+    /// Encrypted file, - for standard input
+    #[arg(short, long)]
+    pub input_file: String,
+
+    /// Also derive the key from the password and print it with its round keys in hex. They open the file, so keep the output secret
+    #[arg(long)]
+    pub expand_key: bool,
+
+    #[command(flatten)]
+    pub password: PasswordArgs,
+
+    #[command(flatten)]
+    pub limits: KdfLimitArgs,
+}
+
+/// The most the key derivation may cost, so the parameters in a crafted file can not take all memory or run practically forever
+#[derive(clap::Args, Debug)]
+pub struct KdfLimitArgs {
+    /// Most memory in MiB the key derivation may use with Argon2id or scrypt
+    #[arg(long, value_name = "MIB", default_value_t = KdfLimits::default().max_memory >> 20, value_parser = clap::value_parser!(u64).range(1..1 << 44))]
+    pub max_kdf_memory: u64,
+
+    /// Most iterations of PBKDF2
+    #[arg(long, value_name = "N", default_value_t = KdfLimits::default().max_iterations, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_kdf_iterations: u32,
+
+    /// Most passes over the memory, the iterations of Argon2id or the parallelism of scrypt
+    #[arg(long, value_name = "N", default_value_t = KdfLimits::default().max_passes, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_kdf_passes: u32,
+}
+
+/// Where the password comes from. It is asked for without echo when none of these is given
+#[derive(clap::Args, Debug)]
+#[group(multiple = false)]
+pub struct PasswordArgs {
+    /// Password. Other users may see it in the process list, and it ends up in the shell history
+    #[arg(short, long)]
+    pub password: Option<String>,
+
+    /// Read the password from this environment variable
+    #[arg(long, value_name = "VAR")]
+    pub password_env: Option<String>,
+
+    /// Read the password from the first line of this open file descriptor, like 3 with 3<password.txt
+    #[cfg(unix)]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(i32).range(0..))]
+    pub password_fd: Option<std::os::fd::RawFd>,
+}
+
+impl KdfLimitArgs {
+    /// The limits of the key derivation
+    pub fn limits(&self) -> KdfLimits {
+        KdfLimits { max_memory: self.max_kdf_memory << 20, max_iterations: self.max_kdf_iterations, max_passes: self.max_kdf_passes }
+    }
+}
+
+impl EncryptArgs {
+    /// The selected key derivation function with its parameters
+    pub fn kdf(&self) -> Kdf {
+        match self.kdf {
+            KdfName::Pbkdf2 => Kdf::Pbkdf2 { iterations: self.iterations.unwrap_or(pbkdf2::DEFAULT_ITERATIONS) },
+            KdfName::Argon2id => Kdf::Argon2id(argon2::Params {
+                memory: self.memory,
+                iterations: self.iterations.unwrap_or(argon2::Params::default().iterations),
+                parallelism: self.parallelism.unwrap_or(argon2::Params::default().parallelism),
+            }),
+            KdfName::Scrypt => Kdf::Scrypt(scrypt::Params { n: self.cost, r: self.block_size, p: self.parallelism.unwrap_or(scrypt::Params::default().p) }),
+        }
+    }
+
+    /// The selected key size
+    pub fn key_size(&self) -> KeySize {
+        self.key_size.key_size()
+    }
+
+    /// The selected mode of operation
+    pub fn mode(&self) -> FileMode {
+        match self.mode {
+            ModeName::Ecb => FileMode::Ecb,
+            ModeName::Cbc => FileMode::Cbc,
+            ModeName::Ctr => FileMode::Ctr,
+            ModeName::Gcm => FileMode::Gcm,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    /// Encrypt
-    #[arg(short, long, conflicts_with = "decrypt")]
-    pub encrypt: bool,
+    /**
+     * Parses the arguments of an encrypt command line.
+     */
+    fn encrypt_args(options: &[&str]) -> Result<EncryptArgs, clap::Error> {
+        let line = [&["aes", "encrypt", "--input-file", "in", "--output-file", "out"], options].concat();
+        match Args::try_parse_from(line)?.command {
+            Some(Command::Encrypt(arguments)) => Ok(arguments),
+            command => panic!("not encrypt: {command:?}"),
+        }
+    }
 
-    /// Decrypt
-    #[arg(short, long, conflicts_with = "encrypt")]
-    pub decrypt: bool,
+    #[test]
+    fn test_mode() {
+        assert_eq!(encrypt_args(&[]).unwrap().mode(), FileMode::Gcm);
+        for (name, mode) in [("ecb", FileMode::Ecb), ("cbc", FileMode::Cbc), ("ctr", FileMode::Ctr), ("gcm", FileMode::Gcm)] {
+            assert_eq!(encrypt_args(&["--mode", name]).unwrap().mode(), mode);
+        }
+        assert!(encrypt_args(&["--mode", "xts"]).is_err());
+    }
 }
@@ -0,0 +1,330 @@
+//!
+//! Constant-time rounds without table lookups. The block is bitsliced into eight 16 bit
+//! planes, where plane b holds bit b of every byte, so each step works on all 16 bytes at
+//! once with the same operations whatever the data is. The s_box is the Boyar-Peralta
+//! circuit of 113 gates, which computes the inversion in the Galois field and the affine
+//! transformation with xors and ands only. Nothing depends on secret data, so the timing
+//! and the memory access pattern reveal nothing about the key or the data.
+//! Description: https://en.wikipedia.org/wiki/Bit_slicing
+//! Specification: J. Boyar and R. Peralta, A small depth-16 circuit for the AES S-box, 2012.
+//!
+
+use crate::RoundKeys;
+
+///
+/// The bit planes of a block. Bit j of plane b is bit b of byte j.
+///
+type Planes = [u16; 8];
+
+///
+/// Splits up to 16 bytes into bit planes.
+///
+fn planes(data: &[u8]) -> Planes {
+    core::array::from_fn(|bit| data.iter().enumerate().fold(0, |plane, (idx, value)| plane | (((*value as u16 >> bit) & 1) << idx)))
+}
+
+///
+/// Joins bit planes back into bytes.
+///
+fn bytes<const N: usize>(planes: &Planes) -> [u8; N] {
+    core::array::from_fn(|idx| planes.iter().enumerate().fold(0, |value, (bit, plane)| value | ((((plane >> idx) & 1) as u8) << bit)))
+}
+
+///
+/// Moves the bytes of the block. Byte j of the result is byte source(j) of the planes.
+///
+fn permute(planes: &Planes, source: impl Fn(usize) -> usize) -> Planes {
+    planes.map(|plane| (0..16).fold(0, |result, idx| result | (((plane >> source(idx)) & 1) << idx)))
+}
+
+///
+/// The s_box on every byte of the planes.
+///
+fn sub_bytes(q: &mut Planes) {
+    let [x7, x6, x5, x4, x3, x2, x1, x0] = *q;
+    // Top linear transformation.
+    let y14 = x3 ^ x5;
+    let y13 = x0 ^ x6;
+    let y9 = x0 ^ x3;
+    let y8 = x0 ^ x5;
+    let t0 = x1 ^ x2;
+    let y1 = t0 ^ x7;
+    let y4 = y1 ^ x3;
+    let y12 = y13 ^ y14;
+    let y2 = y1 ^ x0;
+    let y5 = y1 ^ x6;
+    let y3 = y5 ^ y8;
+    let t1 = x4 ^ y12;
+    let y15 = t1 ^ x5;
+    let y20 = t1 ^ x1;
+    let y6 = y15 ^ x7;
+    let y10 = y15 ^ t0;
+    let y11 = y20 ^ y9;
+    let y7 = x7 ^ y11;
+    let y17 = y10 ^ y11;
+    let y19 = y10 ^ y8;
+    let y16 = t0 ^ y11;
+    let y21 = y13 ^ y16;
+    let y18 = x0 ^ y16;
+    // Non-linear section, the inversion in the Galois field.
+    let t2 = y12 & y15;
+    let t3 = y3 & y6;
+    let t4 = t3 ^ t2;
+    let t5 = y4 & x7;
+    let t6 = t5 ^ t2;
+    let t7 = y13 & y16;
+    let t8 = y5 & y1;
+    let t9 = t8 ^ t7;
+    let t10 = y2 & y7;
+    let t11 = t10 ^ t7;
+    let t12 = y9 & y11;
+    let t13 = y14 & y17;
+    let t14 = t13 ^ t12;
+    let t15 = y8 & y10;
+    let t16 = t15 ^ t12;
+    let t17 = t4 ^ t14;
+    let t18 = t6 ^ t16;
+    let t19 = t9 ^ t14;
+    let t20 = t11 ^ t16;
+    let t21 = t17 ^ y20;
+    let t22 = t18 ^ y19;
+    let t23 = t19 ^ y21;
+    let t24 = t20 ^ y18;
+    let t25 = t21 ^ t22;
+    let t26 = t21 & t23;
+    let t27 = t24 ^ t26;
+    let t28 = t25 & t27;
+    let t29 = t28 ^ t22;
+    let t30 = t23 ^ t24;
+    let t31 = t22 ^ t26;
+    let t32 = t31 & t30;
+    let t33 = t32 ^ t24;
+    let t34 = t23 ^ t33;
+    let t35 = t27 ^ t33;
+    let t36 = t24 & t35;
+    let t37 = t36 ^ t34;
+    let t38 = t27 ^ t36;
+    let t39 = t29 & t38;
+    let t40 = t25 ^ t39;
+    let t41 = t40 ^ t37;
+    let t42 = t29 ^ t33;
+    let t43 = t29 ^ t40;
+    let t44 = t33 ^ t37;
+    let t45 = t42 ^ t41;
+    let z0 = t44 & y15;
+    let z1 = t37 & y6;
+    let z2 = t33 & x7;
+    let z3 = t43 & y16;
+    let z4 = t40 & y1;
+    let z5 = t29 & y7;
+    let z6 = t42 & y11;
+    let z7 = t45 & y17;
+    let z8 = t41 & y10;
+    let z9 = t44 & y12;
+    let z10 = t37 & y3;
+    let z11 = t33 & y4;
+    let z12 = t43 & y13;
+    let z13 = t40 & y5;
+    let z14 = t29 & y2;
+    let z15 = t42 & y9;
+    let z16 = t45 & y14;
+    let z17 = t41 & y8;
+    // Bottom linear transformation, including the affine transformation of the s_box.
+    let t46 = z15 ^ z16;
+    let t47 = z10 ^ z11;
+    let t48 = z5 ^ z13;
+    let t49 = z9 ^ z10;
+    let t50 = z2 ^ z12;
+    let t51 = z2 ^ z5;
+    let t52 = z7 ^ z8;
+    let t53 = z0 ^ z3;
+    let t54 = z6 ^ z7;
+    let t55 = z16 ^ z17;
+    let t56 = z12 ^ t48;
+    let t57 = t50 ^ t53;
+    let t58 = z4 ^ t46;
+    let t59 = z3 ^ t54;
+    let t60 = t46 ^ t57;
+    let t61 = z14 ^ t57;
+    let t62 = t52 ^ t58;
+    let t63 = t49 ^ t58;
+    let t64 = z4 ^ t59;
+    let t65 = t61 ^ t62;
+    let t66 = z1 ^ t63;
+    let s0 = t59 ^ t63;
+    let s6 = t56 ^ !t62;
+    let s7 = t48 ^ !t60;
+    let t67 = t64 ^ t65;
+    let s3 = t53 ^ t66;
+    let s4 = t51 ^ t66;
+    let s5 = t47 ^ t65;
+    let s1 = t64 ^ !s3;
+    let s2 = t55 ^ !t67;
+    *q = [s7, s6, s5, s4, s3, s2, s1, s0];
+}
+
+///
+/// The inverse of the linear part of the affine transformation, with the constant 0x63
+/// added first. Applied before and after the s_box it gives the inverse s_box.
+///
+fn inverse_affine(q: &mut Planes) {
+    let [q0, q1, q2, q3, q4, q5, q6, q7] = [!q[0], !q[1], q[2], q[3], q[4], !q[5], !q[6], q[7]];
+    *q = [q2 ^ q5 ^ q7, q3 ^ q6 ^ q0, q4 ^ q7 ^ q1, q5 ^ q0 ^ q2, q6 ^ q1 ^ q3, q7 ^ q2 ^ q4, q0 ^ q3 ^ q5, q1 ^ q4 ^ q6];
+}
+
+///
+/// The inverse s_box on every byte of the planes.
+///
+fn inverse_sub_bytes(q: &mut Planes) {
+    inverse_affine(q);
+    sub_bytes(q);
+    inverse_affine(q);
+}
+
+///
+/// Shifts row r of the block r columns to the left. The bytes are in the order of the
+/// input, so byte j is in row j % 4 and column j / 4.
+///
+fn shift_rows(q: &Planes) -> Planes {
+    permute(q, |idx| (idx + 4 * (idx % 4)) % 16)
+}
+
+///
+/// Shifts row r of the block r columns to the right.
+///
+fn inverse_shift_rows(q: &Planes) -> Planes {
+    permute(q, |idx| (idx + 16 - 4 * (idx % 4)) % 16)
+}
+
+///
+/// Rotates each column up by the number of rows.
+///
+fn rotate_rows(q: &Planes, rows: usize) -> Planes {
+    permute(q, |idx| idx - idx % 4 + (idx + rows) % 4)
+}
+
+///
+/// Multiplies every byte by 2 in the Galois field.
+///
+fn xtime(q: &Planes) -> Planes {
+    [q[7], q[0] ^ q[7], q[1], q[2] ^ q[7], q[3] ^ q[7], q[4], q[5], q[6]]
+}
+
+fn xor(a: &Planes, b: &Planes) -> Planes {
+    core::array::from_fn(|bit| a[bit] ^ b[bit])
+}
+
+///
+/// Mixes the columns, each byte becomes 2 * a0 + 3 * a1 + a2 + a3 with the next bytes of its column.
+///
+fn mix_columns(q: &Planes) -> Planes {
+    let q1 = rotate_rows(q, 1);
+    let sum = xor(&xor(&q1, &rotate_rows(q, 2)), &rotate_rows(q, 3));
+    xor(&xtime(&xor(q, &q1)), &sum)
+}
+
+///
+/// Inverse mixes the columns. The inverse is the column mixing after adding 4 * (a0 + a2)
+/// to the even rows and 4 * (a1 + a3) to the odd rows.
+///
+fn inverse_mix_columns(q: &Planes) -> Planes {
+    mix_columns(&xor(q, &xtime(&xtime(&xor(q, &rotate_rows(q, 2))))))
+}
+
+///
+/// Substitutes the bytes of a word in the key schedule.
+///
+/// word: 4 bytes.
+///
+/// result: The 4 bytes substituted.
+///
+pub(crate) fn sub_word(word: [u8; 4]) -> [u8; 4] {
+    let mut q = planes(&word);
+    sub_bytes(&mut q);
+    bytes(&q)
+}
+
+///
+/// Encrypts a single block in constant time.
+///
+/// block: The 16 bytes to encrypt.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+///
+/// result: The 16 bytes encrypted.
+///
+pub(crate) fn encrypt(block: [u8; 16], roundkeys: &RoundKeys) -> [u8; 16] {
+    let rounds = roundkeys.len() - 1;
+    let mut q = xor(&planes(&block), &planes(&roundkeys[0]));
+    for (idx, roundkey) in roundkeys.iter().enumerate().skip(1) {
+        sub_bytes(&mut q);
+        q = shift_rows(&q);
+        // The final round has no column mixing.
+        if idx != rounds {
+            q = mix_columns(&q);
+        }
+        q = xor(&q, &planes(roundkey));
+    }
+    bytes(&q)
+}
+
+///
+/// Decrypts a single block in constant time.
+///
+/// block: The 16 bytes to decrypt.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+///
+/// result: The 16 bytes decrypted.
+///
+pub(crate) fn decrypt(block: [u8; 16], roundkeys: &RoundKeys) -> [u8; 16] {
+    let rounds = roundkeys.len() - 1;
+    let mut q = xor(&planes(&block), &planes(&roundkeys[rounds]));
+    for (idx, roundkey) in roundkeys.iter().rev().enumerate().skip(1) {
+        // The final round of the encryption had no column mixing to undo.
+        if idx != 1 {
+            q = inverse_mix_columns(&q);
+        }
+        q = inverse_shift_rows(&q);
+        inverse_sub_bytes(&mut q);
+        q = xor(&q, &planes(roundkey));
+    }
+    bytes(&q)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AESBlock, DecryptedState, EncryptedState, Key};
+
+    ///
+    /// The circuits give the s_box and the inverse s_box for every byte.
+    ///
+    #[test]
+    fn test_s_box() {
+        for chunk in (0..=255u8).collect::<Vec<u8>>().chunks(16) {
+            let mut q = planes(chunk);
+            sub_bytes(&mut q);
+            let substituted: [u8; 16] = bytes(&q);
+            assert_eq!(substituted.to_vec(), chunk.iter().map(|value| AESBlock::<DecryptedState>::S_BOX[*value as usize]).collect::<Vec<u8>>());
+            inverse_sub_bytes(&mut q);
+            assert_eq!(bytes::<16>(&q).to_vec(), chunk);
+        }
+        assert_eq!(sub_word([0x00, 0x53, 0x10, 0xff]), [0x63, 0xed, 0xca, 0x16]);
+    }
+
+    ///
+    /// The constant-time rounds give the same result as the round functions for all key sizes.
+    ///
+    #[test]
+    fn test_same_as_rounds() {
+        for key in [Key::Aes128([1; 16]), Key::Aes192([2; 24]), Key::Aes256([3; 32])] {
+            let roundkeys = key.roundkeys();
+            for value in 0..32u8 {
+                let block: [u8; 16] = core::array::from_fn(|idx| value.wrapping_mul(37).wrapping_add(idx as u8 * 11));
+                let encrypted = encrypt(block, &roundkeys);
+                assert_eq!(encrypted, AESBlock::<DecryptedState>::new(block).rounds(&roundkeys));
+                assert_eq!(decrypt(encrypted, &roundkeys), block);
+                assert_eq!(AESBlock::<EncryptedState>::new(encrypted).rounds(&roundkeys), block);
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//!
+//! BLAKE2b hash function with a variable output length of 1 to 64 bytes. The 128 byte
+//! blocks are mixed into eight 64 bit words of state with twelve rounds of the G function.
+//! Description: https://en.wikipedia.org/wiki/BLAKE_(hash_function)#BLAKE2
+//! Specification: RFC 7693.
+//!
+
+use alloc::vec::Vec;
+
+///
+/// Length of a block in bytes.
+///
+pub const BLOCK_LENGTH: usize = 128;
+
+///
+/// Longest hash in bytes.
+///
+pub const MAX_OUTPUT_LENGTH: usize = 64;
+
+///
+/// Initialization vector, the same as the initial state of SHA-512.
+///
+const IV: [u64; 8] = [0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1, 0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179];
+
+///
+/// The order of the message words in each round.
+///
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+///
+/// Blake2b struct for hashing data that arrives in parts.
+///
+#[derive(Debug, Clone)]
+pub struct Blake2b {
+    state: [u64; 8],
+    buffer: Vec<u8>,
+    length: u128,
+    output_length: usize,
+}
+
+impl Blake2b {
+    ///
+    /// Creates an unkeyed hash.
+    ///
+    /// output_length: Length of the hash in bytes, between 1 and 64.
+    ///
+    /// result: A Blake2b struct.
+    ///
+    /// Panics if the output length is not supported.
+    ///
+    pub fn new(output_length: usize) -> Blake2b {
+        assert!((1..=MAX_OUTPUT_LENGTH).contains(&output_length), "Unsupported output length: {output_length}");
+        let mut state = IV;
+        state[0] ^= 0x01010000 ^ output_length as u64;
+        Blake2b { state, buffer: Vec::with_capacity(BLOCK_LENGTH), length: 0, output_length }
+    }
+
+    ///
+    /// Adds data to the hash. The last block is kept until finalize, since it is
+    /// compressed with the final flag set.
+    ///
+    /// data: A vector of bytes of any length.
+    ///
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            if self.buffer.len() == BLOCK_LENGTH {
+                self.length += BLOCK_LENGTH as u128;
+                let block = core::mem::take(&mut self.buffer);
+                self.compress(&block, false);
+            }
+            self.buffer.push(byte);
+        }
+    }
+
+    ///
+    /// Compresses the last block and returns the hash.
+    ///
+    pub fn finalize(mut self) -> Vec<u8> {
+        self.length += self.buffer.len() as u128;
+        let mut block = core::mem::take(&mut self.buffer);
+        block.resize(BLOCK_LENGTH, 0);
+        self.compress(&block, true);
+        self.state.iter().flat_map(|word| word.to_le_bytes()).take(self.output_length).collect()
+    }
+
+    ///
+    /// Compresses one 128 byte block into the state.
+    ///
+    fn compress(&mut self, block: &[u8], last: bool) {
+        let mut message = [0u64; 16];
+        for (word, chunk) in message.iter_mut().zip(block.chunks(8)) {
+            *word = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        let mut v = [0u64; 16];
+        v[..8].copy_from_slice(&self.state);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= self.length as u64;
+        v[13] ^= (self.length >> 64) as u64;
+        if last {
+            v[14] = !v[14];
+        }
+        for round in 0..12 {
+            let s = &SIGMA[round % 10];
+            mix(&mut v, 0, 4, 8, 12, message[s[0]], message[s[1]]);
+            mix(&mut v, 1, 5, 9, 13, message[s[2]], message[s[3]]);
+            mix(&mut v, 2, 6, 10, 14, message[s[4]], message[s[5]]);
+            mix(&mut v, 3, 7, 11, 15, message[s[6]], message[s[7]]);
+            mix(&mut v, 0, 5, 10, 15, message[s[8]], message[s[9]]);
+            mix(&mut v, 1, 6, 11, 12, message[s[10]], message[s[11]]);
+            mix(&mut v, 2, 7, 8, 13, message[s[12]], message[s[13]]);
+            mix(&mut v, 3, 4, 9, 14, message[s[14]], message[s[15]]);
+        }
+        for (idx, value) in self.state.iter_mut().enumerate() {
+            *value ^= v[idx] ^ v[idx + 8];
+        }
+    }
+}
+
+///
+/// The G function, mixing two message words into four words of the working vector.
+///
+fn mix(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+///
+/// Hashes the data.
+///
+/// output_length: Length of the hash in bytes, between 1 and 64.
+/// data: A vector of bytes of any length.
+///
+/// result: The hash.
+///
+/// Panics if the output length is not supported.
+///
+pub fn digest(output_length: usize, data: &[u8]) -> Vec<u8> {
+    let mut blake = Blake2b::new(output_length);
+    blake.update(data);
+    blake.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_digest() {
+        assert_eq!(digest(64, b""), hex("786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce"));
+        assert_eq!(digest(64, b"abc"), hex("ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"));
+    }
+
+    #[test]
+    fn test_update_in_parts() {
+        let data: Vec<u8> = (0..=255).cycle().take(600).collect();
+        for split in [0, 1, 127, 128, 129, 256, 600] {
+            let mut blake = Blake2b::new(32);
+            blake.update(&data[..split]);
+            blake.update(&data[split..]);
+            assert_eq!(blake.finalize(), digest(32, &data), "split at {split}");
+        }
+    }
+}
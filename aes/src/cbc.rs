@@ -0,0 +1,89 @@
+//!
+//! Cipher block chaining mode. Each plaintext block is xored with the previous
+//! ciphertext block, or the initialization vector for the first, before it is
+//! encrypted, so equal plaintext blocks give different ciphertext blocks.
+//! Description: https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation#Cipher_block_chaining_(CBC)
+//!
+
+use crate::{RoundKeys, decrypt_block, encrypt_block};
+use alloc::vec::Vec;
+
+///
+/// Encrypts the data. The data must already be padded to whole blocks.
+///
+/// data: A vector of bytes, a multiple of 16 bytes long.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+/// iv: The initialization vector. It should be unpredictable for each message.
+///
+/// result: A vector of bytes with the same length as the data.
+///
+/// Panics if the data is not a multiple of 16 bytes.
+///
+pub fn encrypt(data: &[u8], roundkeys: &RoundKeys, iv: [u8; 16]) -> Vec<u8> {
+    assert!(data.len().is_multiple_of(16), "CBC data must be whole blocks");
+    let mut previous = iv;
+    data.chunks(16)
+        .flat_map(|chunk| {
+            for (value, byte) in previous.iter_mut().zip(chunk) {
+                *value ^= byte;
+            }
+            previous = encrypt_block(previous, roundkeys);
+            previous
+        })
+        .collect()
+}
+
+///
+/// Decrypts the data. The padding is not removed.
+///
+/// data: A vector of bytes, a multiple of 16 bytes long.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+/// iv: The initialization vector used when encrypting.
+///
+/// result: A vector of bytes with the same length as the data.
+///
+/// Panics if the data is not a multiple of 16 bytes.
+///
+pub fn decrypt(data: &[u8], roundkeys: &RoundKeys, iv: [u8; 16]) -> Vec<u8> {
+    assert!(data.len().is_multiple_of(16), "CBC data must be whole blocks");
+    let mut previous = iv;
+    data.chunks(16)
+        .flat_map(|chunk| {
+            let mut block = [0u8; 16];
+            block.copy_from_slice(chunk);
+            let mut decrypted = decrypt_block(block, roundkeys);
+            for (value, byte) in decrypted.iter_mut().zip(previous) {
+                *value ^= byte;
+            }
+            previous = block;
+            decrypted
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AESData, DecryptedState};
+
+    ///
+    /// Example vector F.2.1 from NIST SP 800-38A.
+    ///
+    #[test]
+    fn test_sp800_38a() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c]).unwrap();
+        let iv = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f];
+        let plaintext: Vec<u8> = vec![
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e,
+            0x51, 0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef, 0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c,
+            0x37, 0x10,
+        ];
+        let ciphertext: Vec<u8> = vec![
+            0x76, 0x49, 0xab, 0xac, 0x81, 0x19, 0xb2, 0x46, 0xce, 0xe9, 0x8e, 0x9b, 0x12, 0xe9, 0x19, 0x7d, 0x50, 0x86, 0xcb, 0x9b, 0x50, 0x72, 0x19, 0xee, 0x95, 0xdb, 0x11, 0x3a, 0x91, 0x76, 0x78,
+            0xb2, 0x73, 0xbe, 0xd6, 0xb8, 0xe3, 0xc1, 0x74, 0x3b, 0x71, 0x16, 0xe6, 0x9e, 0x22, 0x22, 0x95, 0x16, 0x3f, 0xf1, 0xca, 0xa1, 0x68, 0x1f, 0xac, 0x09, 0x12, 0x0e, 0xca, 0x30, 0x75, 0x86,
+            0xe1, 0xa7,
+        ];
+        assert_eq!(encrypt(&plaintext, &roundkeys, iv), ciphertext);
+        assert_eq!(decrypt(&ciphertext, &roundkeys, iv), plaintext);
+    }
+}
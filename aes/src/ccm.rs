@@ -0,0 +1,239 @@
+//!
+//! Counter with CBC-MAC mode. The tag is a CBC-MAC over a header block, the associated
+//! data and the plaintext, and the data and the tag are encrypted in counter mode.
+//! Description: https://en.wikipedia.org/wiki/CCM_mode
+//! Specification: RFC 3610.
+//!
+
+use crate::{AesError, RoundKeys, constant_time_eq, encrypt_block};
+use alloc::vec::Vec;
+
+///
+/// Ccm struct with the tag and nonce lengths. The nonce length decides the size of
+/// the length field, 15 - nonce length bytes, and with it the longest message.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ccm {
+    tag_length: usize,
+    nonce_length: usize,
+}
+
+impl Ccm {
+    ///
+    /// Creates the mode with the specified lengths.
+    ///
+    /// tag_length: Length of the tag in bytes, an even number between 4 and 16.
+    /// nonce_length: Length of the nonce in bytes, between 7 and 13.
+    ///
+    /// result: A Ccm struct or an error if a length is not supported.
+    ///
+    pub fn new(tag_length: usize, nonce_length: usize) -> Result<Ccm, AesError> {
+        if !(4..=16).contains(&tag_length) || !tag_length.is_multiple_of(2) {
+            return Err(AesError::InvalidTagLength { length: tag_length });
+        }
+        if !(7..=13).contains(&nonce_length) {
+            return Err(AesError::InvalidNonceLength { length: nonce_length });
+        }
+        Ok(Ccm { tag_length, nonce_length })
+    }
+
+    pub fn tag_length(&self) -> usize {
+        self.tag_length
+    }
+
+    pub fn nonce_length(&self) -> usize {
+        self.nonce_length
+    }
+
+    ///
+    /// Encrypts and authenticates the data.
+    ///
+    /// plaintext: A vector of bytes of any length that fits the length field.
+    /// aad: Associated data that is authenticated but not encrypted.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// nonce: The nonce. The same nonce must never be used twice with the same key.
+    ///
+    /// result: The ciphertext with the same length as the plaintext and the tag, or an
+    ///         error if the nonce has the wrong length or the plaintext is too long.
+    ///
+    pub fn encrypt(&self, plaintext: &[u8], aad: &[u8], roundkeys: &RoundKeys, nonce: &[u8]) -> Result<(Vec<u8>, Vec<u8>), AesError> {
+        self.check(nonce, plaintext.len())?;
+        let tag = self.encrypt_tag(&self.mac(plaintext, aad, roundkeys, nonce), roundkeys, nonce);
+        Ok((self.ctr(plaintext, roundkeys, nonce), tag))
+    }
+
+    ///
+    /// Decrypts and verifies the data. No plaintext is returned if the tag does not match.
+    ///
+    /// ciphertext: A vector of bytes of any length that fits the length field.
+    /// aad: The associated data used when encrypting.
+    /// tag: The tag from the encryption.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// nonce: The nonce used when encrypting.
+    ///
+    /// result: The plaintext, or an error if the data or tag have been changed.
+    ///
+    pub fn decrypt(&self, ciphertext: &[u8], aad: &[u8], tag: &[u8], roundkeys: &RoundKeys, nonce: &[u8]) -> Result<Vec<u8>, AesError> {
+        self.check(nonce, ciphertext.len())?;
+        if tag.len() != self.tag_length {
+            return Err(AesError::Authentication);
+        }
+        let plaintext = self.ctr(ciphertext, roundkeys, nonce);
+        let expected = self.encrypt_tag(&self.mac(&plaintext, aad, roundkeys, nonce), roundkeys, nonce);
+        if !constant_time_eq(&expected, tag) {
+            return Err(AesError::Authentication);
+        }
+        Ok(plaintext)
+    }
+
+    ///
+    /// Returns the size of the length field in bytes.
+    ///
+    fn length_size(&self) -> usize {
+        15 - self.nonce_length
+    }
+
+    ///
+    /// Checks the nonce length and that the message length fits the length field.
+    ///
+    fn check(&self, nonce: &[u8], length: usize) -> Result<(), AesError> {
+        if nonce.len() != self.nonce_length {
+            return Err(AesError::InvalidNonceLength { length: nonce.len() });
+        }
+        if (length as u128) >> (8 * self.length_size()) != 0 {
+            return Err(AesError::MessageTooLong { length });
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns a block with the flags, the nonce and a big-endian number in the length field.
+    ///
+    fn block(&self, flags: u8, nonce: &[u8], value: usize) -> [u8; 16] {
+        let mut block = [0u8; 16];
+        block[0] = flags;
+        block[1..=self.nonce_length].copy_from_slice(nonce);
+        block[1 + self.nonce_length..].copy_from_slice(&(value as u128).to_be_bytes()[16 - self.length_size()..]);
+        block
+    }
+
+    ///
+    /// Computes the CBC-MAC over the first block, the associated data with its length
+    /// and the plaintext. The associated data and the plaintext are padded with zeros.
+    ///
+    fn mac(&self, plaintext: &[u8], aad: &[u8], roundkeys: &RoundKeys, nonce: &[u8]) -> Vec<u8> {
+        let flags = (u8::from(!aad.is_empty()) << 6) | (((self.tag_length as u8 - 2) / 2) << 3) | (self.length_size() as u8 - 1);
+        let mut mac = encrypt_block(self.block(flags, nonce, plaintext.len()), roundkeys);
+        let mut encoded_aad: Vec<u8> = match aad.len() {
+            0 => Vec::new(),
+            length if length < 0xff00 => (length as u16).to_be_bytes().to_vec(),
+            length if length <= u32::MAX as usize => [0xff, 0xfe].into_iter().chain((length as u32).to_be_bytes()).collect(),
+            length => [0xff, 0xff].into_iter().chain((length as u64).to_be_bytes()).collect(),
+        };
+        encoded_aad.extend_from_slice(aad);
+        for chunk in encoded_aad.chunks(16).chain(plaintext.chunks(16)) {
+            for (value, byte) in mac.iter_mut().zip(chunk) {
+                *value ^= byte;
+            }
+            mac = encrypt_block(mac, roundkeys);
+        }
+        mac[..self.tag_length].to_vec()
+    }
+
+    ///
+    /// Encrypts the CBC-MAC with the keystream block for counter 0.
+    ///
+    fn encrypt_tag(&self, mac: &[u8], roundkeys: &RoundKeys, nonce: &[u8]) -> Vec<u8> {
+        let keystream = encrypt_block(self.block(self.length_size() as u8 - 1, nonce, 0), roundkeys);
+        mac.iter().zip(keystream).map(|(value, key)| value ^ key).collect()
+    }
+
+    ///
+    /// Xors the data with the keystream from the counter blocks starting at 1.
+    ///
+    fn ctr(&self, data: &[u8], roundkeys: &RoundKeys, nonce: &[u8]) -> Vec<u8> {
+        data.chunks(16)
+            .enumerate()
+            .flat_map(|(idx, chunk)| {
+                let keystream = encrypt_block(self.block(self.length_size() as u8 - 1, nonce, idx + 1), roundkeys);
+                chunk.iter().zip(keystream).map(|(value, key)| value ^ key).collect::<Vec<u8>>()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AESData, DecryptedState};
+
+    ///
+    /// Checks a packet vector from RFC 3610. The packets have 8 bytes of associated data
+    /// followed by the payload, and the result has the ciphertext followed by the tag.
+    ///
+    fn check(tag_length: usize, nonce: &[u8], packet: &[u8], result: &[u8]) {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[0xc0, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xcb, 0xcc, 0xcd, 0xce, 0xcf]).unwrap();
+        let ccm = Ccm::new(tag_length, nonce.len()).unwrap();
+        let (aad, plaintext) = packet.split_at(8);
+        let (ciphertext, tag) = ccm.encrypt(plaintext, aad, &roundkeys, nonce).unwrap();
+        assert_eq!([&packet[..8], &ciphertext[..], &tag[..]].concat(), result);
+        assert_eq!(ccm.decrypt(&ciphertext, aad, &tag, &roundkeys, nonce), Ok(plaintext.to_vec()));
+    }
+
+    #[test]
+    fn test_rfc3610_packet_vector_1() {
+        let nonce = [0x00, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+        let packet: Vec<u8> = (0x00..=0x1e).collect();
+        let result = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x58, 0x8c, 0x97, 0x9a, 0x61, 0xc6, 0x63, 0xd2, 0xf0, 0x66, 0xd0, 0xc2, 0xc0, 0xf9, 0x89, 0x80, 0x6d, 0x5f, 0x6b, 0x61, 0xda, 0xc3, 0x84,
+            0x17, 0xe8, 0xd1, 0x2c, 0xfd, 0xf9, 0x26, 0xe0,
+        ];
+        check(8, &nonce, &packet, &result);
+    }
+
+    #[test]
+    fn test_rfc3610_packet_vector_2() {
+        let nonce = [0x00, 0x00, 0x00, 0x04, 0x03, 0x02, 0x01, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+        let packet: Vec<u8> = (0x00..=0x1f).collect();
+        let result = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x72, 0xc9, 0x1a, 0x36, 0xe1, 0x35, 0xf8, 0xcf, 0x29, 0x1c, 0xa8, 0x94, 0x08, 0x5c, 0x87, 0xe3, 0xcc, 0x15, 0xc4, 0x39, 0xc9, 0xe4, 0x3a,
+            0x3b, 0xa0, 0x91, 0xd5, 0x6e, 0x10, 0x40, 0x09, 0x16,
+        ];
+        check(8, &nonce, &packet, &result);
+    }
+
+    #[test]
+    fn test_rfc3610_packet_vector_7() {
+        let nonce = [0x00, 0x00, 0x00, 0x09, 0x08, 0x07, 0x06, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+        let packet: Vec<u8> = (0x00..=0x1e).collect();
+        let result = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x01, 0x35, 0xd1, 0xb2, 0xc9, 0x5f, 0x41, 0xd5, 0xd1, 0xd4, 0xfe, 0xc1, 0x85, 0xd1, 0x66, 0xb8, 0x09, 0x4e, 0x99, 0x9d, 0xfe, 0xd9, 0x6c,
+            0x04, 0x8c, 0x56, 0x60, 0x2c, 0x97, 0xac, 0xbb, 0x74, 0x90,
+        ];
+        check(10, &nonce, &packet, &result);
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        assert_eq!(Ccm::new(5, 13), Err(AesError::InvalidTagLength { length: 5 }));
+        assert_eq!(Ccm::new(18, 13), Err(AesError::InvalidTagLength { length: 18 }));
+        assert_eq!(Ccm::new(8, 6), Err(AesError::InvalidNonceLength { length: 6 }));
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[0; 16]).unwrap();
+        let ccm = Ccm::new(16, 13).unwrap();
+        assert_eq!(ccm.encrypt(b"data", b"", &roundkeys, &[0; 12]), Err(AesError::InvalidNonceLength { length: 12 }));
+        assert_eq!(ccm.encrypt(&vec![0; 0x10000], b"", &roundkeys, &[0; 13]), Err(AesError::MessageTooLong { length: 0x10000 }));
+    }
+
+    #[test]
+    fn test_authentication_failure() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[9; 32]).unwrap();
+        let ccm = Ccm::new(4, 7).unwrap();
+        let (ciphertext, tag) = ccm.encrypt(b"Sensor reading 21.5", b"device 7", &roundkeys, &[1; 7]).unwrap();
+        let mut changed = ciphertext.clone();
+        changed[3] ^= 4;
+        assert_eq!(ccm.decrypt(&changed, b"device 7", &tag, &roundkeys, &[1; 7]), Err(AesError::Authentication));
+        assert_eq!(ccm.decrypt(&ciphertext, b"device 8", &tag, &roundkeys, &[1; 7]), Err(AesError::Authentication));
+        assert_eq!(ccm.decrypt(&ciphertext, b"device 7", &tag[..3], &roundkeys, &[1; 7]), Err(AesError::Authentication));
+        assert_eq!(ccm.decrypt(&ciphertext, b"device 7", &tag, &roundkeys, &[1; 7]), Ok(b"Sensor reading 21.5".to_vec()));
+    }
+}
@@ -0,0 +1,89 @@
+//!
+//! Cipher feedback mode with 128 bit segments. Each ciphertext block is encrypted
+//! into the keystream for the next block, so a damaged block only garbles the
+//! following block before decryption synchronises again.
+//! Description: https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation#Cipher_feedback_(CFB)
+//!
+
+use crate::{RoundKeys, encrypt_block};
+use alloc::vec::Vec;
+
+///
+/// Encrypts the data. The data is not padded, a last partial block uses part of the keystream.
+///
+/// data: A vector of bytes of any length.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+/// iv: The initialization vector. It should be unpredictable for each message.
+///
+/// result: A vector of bytes with the same length as the data.
+///
+pub fn encrypt(data: &[u8], roundkeys: &RoundKeys, iv: [u8; 16]) -> Vec<u8> {
+    let mut feedback = iv;
+    data.chunks(16)
+        .flat_map(|chunk| {
+            let keystream = encrypt_block(feedback, roundkeys);
+            let encrypted: Vec<u8> = chunk.iter().zip(keystream).map(|(value, key)| value ^ key).collect();
+            // Only a final partial block is shorter, and its feedback is never used.
+            feedback[..chunk.len()].copy_from_slice(&encrypted);
+            encrypted
+        })
+        .collect()
+}
+
+///
+/// Decrypts the data.
+///
+/// data: A vector of bytes of any length.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+/// iv: The initialization vector used when encrypting.
+///
+/// result: A vector of bytes with the same length as the data.
+///
+pub fn decrypt(data: &[u8], roundkeys: &RoundKeys, iv: [u8; 16]) -> Vec<u8> {
+    let mut feedback = iv;
+    data.chunks(16)
+        .flat_map(|chunk| {
+            let keystream = encrypt_block(feedback, roundkeys);
+            feedback[..chunk.len()].copy_from_slice(chunk);
+            chunk.iter().zip(keystream).map(|(value, key)| value ^ key).collect::<Vec<u8>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AESData, DecryptedState};
+
+    ///
+    /// Example vector F.3.13 from NIST SP 800-38A.
+    ///
+    #[test]
+    fn test_sp800_38a() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c]).unwrap();
+        let iv = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f];
+        let plaintext: Vec<u8> = vec![
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e,
+            0x51, 0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef, 0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c,
+            0x37, 0x10,
+        ];
+        let ciphertext: Vec<u8> = vec![
+            0x3b, 0x3f, 0xd9, 0x2e, 0xb7, 0x2d, 0xad, 0x20, 0x33, 0x34, 0x49, 0xf8, 0xe8, 0x3c, 0xfb, 0x4a, 0xc8, 0xa6, 0x45, 0x37, 0xa0, 0xb3, 0xa9, 0x3f, 0xcd, 0xe3, 0xcd, 0xad, 0x9f, 0x1c, 0xe5,
+            0x8b, 0x26, 0x75, 0x1f, 0x67, 0xa3, 0xcb, 0xb1, 0x40, 0xb1, 0x80, 0x8c, 0xf1, 0x87, 0xa4, 0xf4, 0xdf, 0xc0, 0x4b, 0x05, 0x35, 0x7c, 0x5d, 0x1c, 0x0e, 0xea, 0xc4, 0xc6, 0x6f, 0x9f, 0xf7,
+            0xf2, 0xe6,
+        ];
+        assert_eq!(encrypt(&plaintext, &roundkeys, iv), ciphertext);
+        assert_eq!(decrypt(&ciphertext, &roundkeys, iv), plaintext);
+    }
+
+    #[test]
+    fn test_self_synchronising() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[5; 16]).unwrap();
+        let data: Vec<u8> = (0..64).collect();
+        let mut encrypted = encrypt(&data, &roundkeys, [1; 16]);
+        encrypted[3] ^= 0xff;
+        let decrypted = decrypt(&encrypted, &roundkeys, [1; 16]);
+        assert_ne!(decrypted[..32], data[..32]);
+        assert_eq!(decrypted[32..], data[32..]);
+    }
+}
@@ -0,0 +1,316 @@
+//!
+//! A single entry point for all the modes. The Cipher is created once from a key and
+//! keeps the roundkeys, and the mode with its parameters is chosen for each call.
+//! Authenticated modes append the tag to the ciphertext and expect it there when decrypting.
+//!
+
+use crate::ctr::{self, Counter};
+use crate::padding::{Padding, Pkcs7};
+use crate::{AESData, AesError, DecryptedState, EncryptedState, Key, KeySize, RoundKeys, cbc, ccm, cfb, eax, gcm, gcm_siv, ofb};
+use alloc::{sync::Arc, vec::Vec};
+use zeroize::Zeroizing;
+
+///
+/// Mode enum with the modes of operation and their parameters.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode<'a> {
+    // Electronic codebook. Each block is encrypted alone, so equal blocks give equal ciphertext.
+    Ecb,
+    // Cipher block chaining with an initialization vector.
+    Cbc { iv: [u8; 16] },
+    // Counter mode starting at the counter block.
+    Ctr { counter: Counter },
+    // Cipher feedback with an initialization vector.
+    Cfb { iv: [u8; 16] },
+    // Output feedback with an initialization vector.
+    Ofb { iv: [u8; 16] },
+    // Galois/Counter mode with a nonce, normally 12 bytes, and associated data.
+    Gcm { nonce: &'a [u8], aad: &'a [u8] },
+    // Counter with CBC-MAC with a nonce of 7 to 13 bytes, associated data and the tag length.
+    Ccm { nonce: &'a [u8], aad: &'a [u8], tag_length: usize },
+    // GCM-SIV with a 12 byte nonce and associated data. Only 16 and 32 byte keys.
+    GcmSiv { nonce: [u8; gcm_siv::NONCE_LENGTH], aad: &'a [u8] },
+    // EAX with a nonce of any length and associated data.
+    Eax { nonce: &'a [u8], aad: &'a [u8] },
+}
+
+///
+/// Cipher struct with the roundkeys of a key and the padding used by the ECB and CBC modes.
+/// It is Send and Sync, and a clone shares the roundkeys, so one cipher can be cloned or
+/// put in an Arc and used from many threads at once.
+///
+#[derive(Clone)]
+pub struct Cipher {
+    roundkeys: Arc<RoundKeys>,
+    padding: Arc<dyn Padding + Send + Sync>,
+}
+
+impl Cipher {
+    ///
+    /// Creates a cipher with PKCS#7 padding.
+    ///
+    /// key: The AES key.
+    ///
+    /// result: A Cipher struct.
+    ///
+    pub fn new(key: &Key) -> Cipher {
+        Cipher { roundkeys: Arc::new(key.roundkeys()), padding: Arc::new(Pkcs7) }
+    }
+
+    ///
+    /// Replaces the padding used by the ECB and CBC modes.
+    ///
+    /// padding: The padding scheme.
+    ///
+    /// result: The Cipher struct with the padding.
+    ///
+    pub fn with_padding(mut self, padding: impl Padding + Send + Sync + 'static) -> Cipher {
+        self.padding = Arc::new(padding);
+        self
+    }
+
+    pub fn key_size(&self) -> KeySize {
+        self.roundkeys.key_size()
+    }
+
+    pub fn roundkeys(&self) -> &RoundKeys {
+        &self.roundkeys
+    }
+
+    pub fn padding(&self) -> &dyn Padding {
+        self.padding.as_ref()
+    }
+
+    ///
+    /// Encrypts the data.
+    ///
+    /// mode: The mode with its parameters.
+    /// data: A vector of bytes of any length.
+    ///
+    /// result: The ciphertext followed by the tag in the authenticated modes, or an error
+    ///         if a parameter is not supported by the mode.
+    ///
+    pub fn encrypt(&self, mode: Mode, data: &[u8]) -> Result<Vec<u8>, AesError> {
+        let roundkeys = &self.roundkeys;
+        let result = match mode {
+            Mode::Ecb => {
+                let mut padded = self.padding.pad(data, 16);
+                AESData::<DecryptedState>::encrypt_in_place(&mut padded, roundkeys)?;
+                padded
+            }
+            Mode::Cbc { iv } => cbc::encrypt(&self.padding.pad(data, 16), roundkeys, iv),
+            Mode::Ctr { counter } => ctr::apply_keystream(data, roundkeys, counter),
+            Mode::Cfb { iv } => cfb::encrypt(data, roundkeys, iv),
+            Mode::Ofb { iv } => ofb::apply_keystream(data, roundkeys, iv),
+            Mode::Gcm { nonce, aad } => {
+                check_gcm_nonce(nonce)?;
+                let (ciphertext, tag) = gcm::encrypt(data, aad, roundkeys, nonce);
+                [ciphertext, tag.to_vec()].concat()
+            }
+            Mode::Ccm { nonce, aad, tag_length } => {
+                let (ciphertext, tag) = ccm::Ccm::new(tag_length, nonce.len())?.encrypt(data, aad, roundkeys, nonce)?;
+                [ciphertext, tag].concat()
+            }
+            Mode::GcmSiv { nonce, aad } => {
+                self.check_gcm_siv_key()?;
+                let (ciphertext, tag) = gcm_siv::encrypt(data, aad, roundkeys, &nonce);
+                [ciphertext, tag.to_vec()].concat()
+            }
+            Mode::Eax { nonce, aad } => {
+                let (ciphertext, tag) = eax::encrypt(data, aad, roundkeys, nonce);
+                [ciphertext, tag.to_vec()].concat()
+            }
+        };
+        Ok(result)
+    }
+
+    ///
+    /// Decrypts the data.
+    ///
+    /// mode: The mode with the parameters used when encrypting.
+    /// data: The ciphertext, followed by the tag in the authenticated modes.
+    ///
+    /// result: The plaintext, or an error if the data has the wrong length, the padding
+    ///         is malformed, the tag does not match or a parameter is not supported.
+    ///
+    pub fn decrypt(&self, mode: Mode, data: &[u8]) -> Result<Vec<u8>, AesError> {
+        let roundkeys = &self.roundkeys;
+        match mode {
+            Mode::Ecb => {
+                check_blocks(data)?;
+                let mut decrypted = Zeroizing::new(data.to_vec());
+                AESData::<EncryptedState>::decrypt_in_place(&mut decrypted, roundkeys)?;
+                Ok(self.padding.unpad(&decrypted, 16)?.to_vec())
+            }
+            Mode::Cbc { iv } => {
+                check_blocks(data)?;
+                let decrypted = Zeroizing::new(cbc::decrypt(data, roundkeys, iv));
+                Ok(self.padding.unpad(&decrypted, 16)?.to_vec())
+            }
+            Mode::Ctr { counter } => Ok(ctr::apply_keystream(data, roundkeys, counter)),
+            Mode::Cfb { iv } => Ok(cfb::decrypt(data, roundkeys, iv)),
+            Mode::Ofb { iv } => Ok(ofb::apply_keystream(data, roundkeys, iv)),
+            Mode::Gcm { nonce, aad } => {
+                check_gcm_nonce(nonce)?;
+                let (ciphertext, tag) = split_tag(data, gcm::TAG_LENGTH)?;
+                gcm::decrypt(ciphertext, aad, &to_block(tag), roundkeys, nonce)
+            }
+            Mode::Ccm { nonce, aad, tag_length } => {
+                let ccm = ccm::Ccm::new(tag_length, nonce.len())?;
+                let (ciphertext, tag) = split_tag(data, tag_length)?;
+                ccm.decrypt(ciphertext, aad, tag, roundkeys, nonce)
+            }
+            Mode::GcmSiv { nonce, aad } => {
+                self.check_gcm_siv_key()?;
+                let (ciphertext, tag) = split_tag(data, gcm_siv::TAG_LENGTH)?;
+                gcm_siv::decrypt(ciphertext, aad, &to_block(tag), roundkeys, &nonce)
+            }
+            Mode::Eax { nonce, aad } => {
+                let (ciphertext, tag) = split_tag(data, eax::TAG_LENGTH)?;
+                eax::decrypt(ciphertext, aad, &to_block(tag), roundkeys, nonce)
+            }
+        }
+    }
+
+    ///
+    /// GCM-SIV derives keys of the same length as the key, which is defined for 16 and 32 bytes.
+    ///
+    fn check_gcm_siv_key(&self) -> Result<(), AesError> {
+        match self.key_size() {
+            KeySize::Aes192 => Err(AesError::InvalidKeyLength { length: KeySize::Aes192.length() }),
+            _ => Ok(()),
+        }
+    }
+}
+
+///
+/// Copies a slice of 16 bytes into a block.
+///
+fn to_block(chunk: &[u8]) -> [u8; 16] {
+    let mut block = [0u8; 16];
+    block.copy_from_slice(chunk);
+    block
+}
+
+///
+/// Checks that the data is a whole, non-zero number of blocks.
+///
+fn check_blocks(data: &[u8]) -> Result<(), AesError> {
+    if data.is_empty() || !data.len().is_multiple_of(16) {
+        return Err(AesError::InvalidDataLength { length: data.len() });
+    }
+    Ok(())
+}
+
+///
+/// GCM hashes nonces of any length except zero.
+///
+fn check_gcm_nonce(nonce: &[u8]) -> Result<(), AesError> {
+    if nonce.is_empty() {
+        return Err(AesError::InvalidNonceLength { length: 0 });
+    }
+    Ok(())
+}
+
+///
+/// Splits the tag from the end of the data.
+///
+fn split_tag(data: &[u8], tag_length: usize) -> Result<(&[u8], &[u8]), AesError> {
+    if data.len() < tag_length {
+        return Err(AesError::InvalidDataLength { length: data.len() });
+    }
+    Ok(data.split_at(data.len() - tag_length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::padding::ZeroPadding;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    fn modes<'a>() -> Vec<Mode<'a>> {
+        vec![
+            Mode::Ecb,
+            Mode::Cbc { iv: [1; 16] },
+            Mode::Ctr { counter: Counter::from_nonce(&[2; 12], 1) },
+            Mode::Cfb { iv: [3; 16] },
+            Mode::Ofb { iv: [4; 16] },
+            Mode::Gcm { nonce: &[5; 12], aad: b"header" },
+            Mode::Ccm { nonce: &[6; 13], aad: b"header", tag_length: 8 },
+            Mode::GcmSiv { nonce: [7; 12], aad: b"header" },
+            Mode::Eax { nonce: &[8; 16], aad: b"header" },
+        ]
+    }
+
+    ///
+    /// Example vector F.1.1 from NIST SP 800-38A.
+    ///
+    #[test]
+    fn test_ecb_sp800_38a() {
+        let cipher = Cipher::new(&Key::from_slice(&hex("2b7e151628aed2a6abf7158809cf4f3c")).unwrap()).with_padding(ZeroPadding);
+        let plaintext = hex("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e5130c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710");
+        let ciphertext = hex("3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf43b1cd7f598ece23881b00e3ed0306887b0c785e27e8ad3f8223207104725dd4");
+        assert_eq!(cipher.encrypt(Mode::Ecb, &plaintext), Ok(ciphertext.clone()));
+        assert_eq!(cipher.decrypt(Mode::Ecb, &ciphertext), Ok(plaintext));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for key in [Key::Aes128([9; 16]), Key::Aes256([9; 32])] {
+            let cipher = Cipher::new(&key);
+            for mode in modes() {
+                for length in [0, 1, 15, 16, 17, 40] {
+                    let data: Vec<u8> = (0..length).collect();
+                    let encrypted = cipher.encrypt(mode, &data).unwrap();
+                    assert_eq!(cipher.decrypt(mode, &encrypted), Ok(data), "{mode:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_authentication_failure() {
+        let cipher = Cipher::new(&Key::Aes192([9; 24]));
+        for mode in modes().into_iter().skip(5).filter(|mode| !matches!(mode, Mode::GcmSiv { .. })) {
+            let mut encrypted = cipher.encrypt(mode, b"Attack at dawn").unwrap();
+            encrypted[0] ^= 1;
+            assert_eq!(cipher.decrypt(mode, &encrypted), Err(AesError::Authentication), "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let cipher = Cipher::new(&Key::Aes192([9; 24]));
+        assert_eq!(cipher.key_size(), KeySize::Aes192);
+        assert_eq!(cipher.decrypt(Mode::Ecb, &[0; 20]), Err(AesError::InvalidDataLength { length: 20 }));
+        assert_eq!(cipher.decrypt(Mode::Cbc { iv: [0; 16] }, &[]), Err(AesError::InvalidDataLength { length: 0 }));
+        assert_eq!(cipher.encrypt(Mode::Gcm { nonce: &[], aad: &[] }, b"data"), Err(AesError::InvalidNonceLength { length: 0 }));
+        assert_eq!(cipher.decrypt(Mode::Gcm { nonce: &[1; 12], aad: &[] }, &[0; 15]), Err(AesError::InvalidDataLength { length: 15 }));
+        assert_eq!(cipher.encrypt(Mode::Ccm { nonce: &[1; 6], aad: &[], tag_length: 8 }, b"data"), Err(AesError::InvalidNonceLength { length: 6 }));
+        assert_eq!(cipher.encrypt(Mode::GcmSiv { nonce: [1; 12], aad: &[] }, b"data"), Err(AesError::InvalidKeyLength { length: 24 }));
+    }
+
+    ///
+    /// One cipher shared by several threads gives the same result as a cipher used from one thread.
+    ///
+    #[test]
+    fn test_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Cipher>();
+        let cipher = std::sync::Arc::new(Cipher::new(&Key::Aes256([5; 32])).with_padding(ZeroPadding));
+        let expected = cipher.encrypt(Mode::Cbc { iv: [6; 16] }, b"Attack at dawn").unwrap();
+        let handles: Vec<_> = (0..4)
+            .map(|idx| {
+                let cipher = if idx % 2 == 0 { cipher.clone() } else { std::sync::Arc::new(cipher.as_ref().clone()) };
+                std::thread::spawn(move || cipher.encrypt(Mode::Cbc { iv: [6; 16] }, b"Attack at dawn").unwrap())
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//!
+//! Cipher-based message authentication code, also called OMAC1. A CBC-MAC where the
+//! last block is xored with one of two subkeys derived from the key, depending on
+//! whether it had to be padded, which makes it secure for messages of any length.
+//! Description: https://en.wikipedia.org/wiki/One-key_MAC
+//! Specification: RFC 4493.
+//!
+
+use crate::{AesError, Key, RoundKeys, constant_time_eq, encrypt_block};
+use zeroize::Zeroize;
+
+///
+/// Doubles a block in GF(2^128) with the polynomial x^128 + x^7 + x^2 + x + 1.
+///
+fn double(block: [u8; 16]) -> [u8; 16] {
+    let value = u128::from_be_bytes(block);
+    ((value << 1) ^ (0x87 * (value >> 127))).to_be_bytes()
+}
+
+///
+/// Returns the two subkeys, for a last block that is whole and for a padded last block.
+///
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+///
+/// result: The subkeys K1 and K2.
+///
+pub fn subkeys(roundkeys: &RoundKeys) -> ([u8; 16], [u8; 16]) {
+    let k1 = double(encrypt_block([0; 16], roundkeys));
+    (k1, double(k1))
+}
+
+///
+/// Cmac struct for computing the code of data that arrives in parts. The last block
+/// is held back until finalize, since only then is it known which subkey it needs.
+///
+#[derive(Debug, Clone)]
+pub struct Cmac {
+    roundkeys: RoundKeys,
+    // The subkeys K1 and K2.
+    subkeys: ([u8; 16], [u8; 16]),
+    // The CBC-MAC of the blocks before the buffer.
+    mac: [u8; 16],
+    // The bytes that are not yet added, at most one block.
+    buffer: [u8; 16],
+    // Number of bytes in the buffer.
+    length: usize,
+}
+
+impl Cmac {
+    ///
+    /// Creates a Cmac struct for the key.
+    ///
+    /// key: A 16, 24 or 32 byte key.
+    ///
+    /// result: A Cmac struct.
+    ///
+    pub fn new(key: &Key) -> Cmac {
+        Cmac::from_roundkeys(key.roundkeys())
+    }
+
+    ///
+    /// Creates a Cmac struct for roundkeys that are already generated.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    ///
+    /// result: A Cmac struct.
+    ///
+    pub fn from_roundkeys(roundkeys: RoundKeys) -> Cmac {
+        Cmac { subkeys: subkeys(&roundkeys), roundkeys, mac: [0; 16], buffer: [0; 16], length: 0 }
+    }
+
+    ///
+    /// Adds data to the code. A full buffer is only encrypted when more data arrives.
+    ///
+    /// data: A vector of bytes of any length.
+    ///
+    pub fn update(&mut self, data: &[u8]) {
+        let mut data = data;
+        while !data.is_empty() {
+            if self.length == 16 {
+                self.mac = encrypt_block(xor(self.mac, self.buffer), &self.roundkeys);
+                self.length = 0;
+            }
+            let count = (16 - self.length).min(data.len());
+            self.buffer[self.length..self.length + count].copy_from_slice(&data[..count]);
+            self.length += count;
+            data = &data[count..];
+        }
+    }
+
+    ///
+    /// Finishes the code with the buffered last block.
+    ///
+    /// result: The 16 byte code.
+    ///
+    pub fn finalize(self) -> [u8; 16] {
+        last_block(self.mac, &self.buffer[..self.length], &self.roundkeys, self.subkeys)
+    }
+
+    ///
+    /// Finishes the code and compares it with the expected code in constant time.
+    ///
+    /// tag: The code the data was sent with, all 16 bytes or the first bytes of it.
+    ///
+    /// result: Ok, or an error if the data or the code have been changed.
+    ///
+    pub fn verify(self, tag: &[u8]) -> Result<(), AesError> {
+        if tag.is_empty() || !constant_time_eq(&self.finalize()[..tag.len().min(16)], tag) {
+            return Err(AesError::Authentication);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Computes the code of the data without changing the struct, so it can be reused.
+    ///
+    /// data: A vector of bytes of any length.
+    ///
+    /// result: The 16 byte code.
+    ///
+    pub fn compute(&self, data: &[u8]) -> [u8; 16] {
+        let mut cmac = self.clone();
+        cmac.update(data);
+        cmac.finalize()
+    }
+}
+
+///
+/// The subkeys and the buffered data reveal information about the key and the data.
+///
+impl Drop for Cmac {
+    fn drop(&mut self) {
+        self.subkeys.0.zeroize();
+        self.subkeys.1.zeroize();
+        self.buffer.zeroize();
+    }
+}
+
+///
+/// Computes the message authentication code.
+///
+/// data: A vector of bytes of any length.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+///
+/// result: The 16 byte code.
+///
+pub fn compute(data: &[u8], roundkeys: &RoundKeys) -> [u8; 16] {
+    let last_start = data.len().saturating_sub(1) / 16 * 16;
+    let mut mac = [0u8; 16];
+    for chunk in data[..last_start].chunks(16) {
+        mac = encrypt_block(xor(mac, chunk.try_into().unwrap()), roundkeys);
+    }
+    last_block(mac, &data[last_start..], roundkeys, subkeys(roundkeys))
+}
+
+///
+/// Encrypts the last block, xored with K1 if it is whole, or padded and xored with K2 if not.
+///
+/// mac: The CBC-MAC of the blocks before the last.
+/// last: The last 0 to 16 bytes. Only an empty message has an empty last block.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+/// subkeys: The subkeys K1 and K2.
+///
+/// result: The 16 byte code.
+///
+fn last_block(mac: [u8; 16], last: &[u8], roundkeys: &RoundKeys, subkeys: ([u8; 16], [u8; 16])) -> [u8; 16] {
+    let (k1, k2) = subkeys;
+    let mut block = if last.len() == 16 { k1 } else { k2 };
+    for (idx, value) in block.iter_mut().enumerate() {
+        *value ^= mac[idx] ^ last.get(idx).copied().unwrap_or(if idx == last.len() { 0x80 } else { 0 });
+    }
+    encrypt_block(block, roundkeys)
+}
+
+///
+/// Xors two blocks.
+///
+fn xor(a: [u8; 16], b: [u8; 16]) -> [u8; 16] {
+    core::array::from_fn(|idx| a[idx] ^ b[idx])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AESData, DecryptedState};
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    ///
+    /// Examples from section 4 of RFC 4493.
+    ///
+    #[test]
+    fn test_rfc4493() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&hex("2b7e151628aed2a6abf7158809cf4f3c")).unwrap();
+        let (k1, k2) = subkeys(&roundkeys);
+        assert_eq!(k1.to_vec(), hex("fbeed618357133667c85e08f7236a8de"));
+        assert_eq!(k2.to_vec(), hex("f7ddac306ae266ccf90bc11ee46d513b"));
+        let message = hex(concat!("6bc1bee22e409f96e93d7e117393172a", "ae2d8a571e03ac9c9eb76fac45af8e51", "30c81c46a35ce411e5fbc1191a0a52ef", "f69f2445df4f9b17ad2b417be66c3710"));
+        assert_eq!(compute(&[], &roundkeys).to_vec(), hex("bb1d6929e95937287fa37d129b756746"));
+        assert_eq!(compute(&message[..16], &roundkeys).to_vec(), hex("070a16b46b4d4144f79bdd9dd04a287c"));
+        assert_eq!(compute(&message[..40], &roundkeys).to_vec(), hex("dfa66747de9ae63030ca32611497c827"));
+        assert_eq!(compute(&message, &roundkeys).to_vec(), hex("51f0bebf7e3b9d92fc49741779363cfe"));
+    }
+
+    ///
+    /// The same examples with the data added in parts of different lengths.
+    ///
+    #[test]
+    fn test_update() {
+        let cmac = Cmac::new(&Key::from_slice(&hex("2b7e151628aed2a6abf7158809cf4f3c")).unwrap());
+        let message = hex(concat!("6bc1bee22e409f96e93d7e117393172a", "ae2d8a571e03ac9c9eb76fac45af8e51", "30c81c46a35ce411e5fbc1191a0a52ef", "f69f2445df4f9b17ad2b417be66c3710"));
+        assert_eq!(cmac.compute(&[]).to_vec(), hex("bb1d6929e95937287fa37d129b756746"));
+        assert_eq!(cmac.compute(&message[..16]).to_vec(), hex("070a16b46b4d4144f79bdd9dd04a287c"));
+        for length in [1, 5, 16, 17, 40] {
+            let mut streaming = cmac.clone();
+            for chunk in message.chunks(length) {
+                streaming.update(chunk);
+            }
+            assert_eq!(streaming.finalize().to_vec(), hex("51f0bebf7e3b9d92fc49741779363cfe"));
+        }
+        let mut streaming = cmac.clone();
+        streaming.update(&message[..16]);
+        streaming.update(&[]);
+        streaming.update(&message[16..40]);
+        assert_eq!(streaming.finalize().to_vec(), hex("dfa66747de9ae63030ca32611497c827"));
+    }
+
+    #[test]
+    fn test_verify() {
+        let cmac = Cmac::new(&Key::from_slice(&hex("2b7e151628aed2a6abf7158809cf4f3c")).unwrap());
+        let mut streaming = cmac.clone();
+        streaming.update(&hex("6bc1bee22e409f96e93d7e117393172a"));
+        assert_eq!(streaming.clone().verify(&hex("070a16b46b4d4144f79bdd9dd04a287c")), Ok(()));
+        assert_eq!(streaming.clone().verify(&hex("070a16b46b4d4144")), Ok(()));
+        assert_eq!(streaming.clone().verify(&hex("070a16b46b4d4144f79bdd9dd04a287d")), Err(AesError::Authentication));
+        assert_eq!(streaming.verify(&[]), Err(AesError::Authentication));
+        assert_eq!(cmac.verify(&hex("070a16b46b4d4144f79bdd9dd04a287c")), Err(AesError::Authentication));
+    }
+}
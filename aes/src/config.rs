@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use clap::parser::{ArgMatches, ValueSource};
+use serde::Deserialize;
+
+use crate::args::{Args, Command, EncryptArgs, FileArgs, KdfName, KeySizeName, ModeName, PasswordArgs, VerifyArgs};
+use crate::failure::Failure;
+
+/**
+ * The profile that is used when --profile is not given.
+ */
+const DEFAULT_PROFILE: &str = "default";
+
+/**
+ * The configuration file, with a [profile.NAME] table for each profile.
+ */
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    #[serde(default)]
+    profile: HashMap<String, Profile>,
+}
+
+/**
+ * Settings of a profile. Each one is used when the option with the same name is
+ * not given on the command line.
+ */
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct Profile {
+    mode: Option<String>,
+    key_size: Option<u32>,
+    kdf: Option<String>,
+    iterations: Option<u32>,
+    memory: Option<u32>,
+    parallelism: Option<u32>,
+    cost: Option<u32>,
+    block_size: Option<u32>,
+    chunk_size: Option<usize>,
+    jobs: Option<u32>,
+    password_env: Option<String>,
+}
+
+/**
+ * Fills in the options of the command that are not given on the command line from
+ * the selected profile, or the default profile when there is one.
+ *
+ * # Arguments
+ * * `args`: The parsed arguments, changed in place.
+ * * `matches`: The matches they were parsed from, which tell what was given.
+ *
+ */
+pub fn apply_profile(args: &mut Args, matches: &ArgMatches) -> Result<(), Failure> {
+    match load(config_path().as_deref(), args.profile.as_deref())? {
+        Some((name, profile)) => apply(args, matches, &name, &profile),
+        None => Ok(()),
+    }
+}
+
+/**
+ * Fills in the options of the command that are not given on the command line from a
+ * profile.
+ *
+ * # Arguments
+ * * `args`: The parsed arguments, changed in place.
+ * * `matches`: The matches they were parsed from, which tell what was given.
+ * * `name`: The name of the profile, for the messages.
+ * * `profile`: The profile.
+ *
+ */
+fn apply(args: &mut Args, matches: &ArgMatches, name: &str, profile: &Profile) -> Result<(), Failure> {
+    let Some((_, matches)) = matches.subcommand() else {
+        return Ok(());
+    };
+    match &mut args.command {
+        Some(Command::Encrypt(arguments)) => profile.apply_encrypt(name, arguments, matches),
+        Some(Command::Decrypt(files)) => profile.apply_files(name, files, matches),
+        Some(Command::Verify(arguments)) => profile.apply_verify(name, arguments, matches),
+        Some(Command::Inspect(arguments)) => {
+            profile.apply_password(&mut arguments.password, matches);
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/**
+ * The path of the configuration file, aes/config.toml in $XDG_CONFIG_HOME or else
+ * in ~/.config.
+ */
+fn config_path() -> Option<PathBuf> {
+    let directory = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from).filter(|path| path.is_absolute());
+    let directory = directory.or_else(|| env::var_os("HOME").filter(|home| !home.is_empty()).map(|home| PathBuf::from(home).join(".config")))?;
+    Some(directory.join("aes").join("config.toml"))
+}
+
+/**
+ * Reads the configuration file and returns the profile with the given name, or the
+ * default profile if it exists when no name is given.
+ *
+ * # Arguments
+ * * `path`: The configuration file, None when there is no place for it.
+ * * `name`: The profile given with --profile.
+ *
+ * # Returns
+ * The name and the profile, or None when no profile is used.
+ *
+ */
+fn load(path: Option<&Path>, name: Option<&str>) -> Result<Option<(String, Profile)>, Failure> {
+    let config = match path {
+        None => Config::default(),
+        Some(path) => match fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).map_err(|error| Failure::Usage(format!("Invalid configuration file {}: {}", path.display(), error.to_string().trim_end())))?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Config::default(),
+            Err(error) => return Err(Failure::io(&format!("Failed to read configuration file {}", path.display()), error)),
+        },
+    };
+    let mut profiles = config.profile;
+    match name {
+        None => Ok(profiles.remove(DEFAULT_PROFILE).map(|profile| (DEFAULT_PROFILE.to_string(), profile))),
+        Some(name) => match profiles.remove(name) {
+            Some(profile) => Ok(Some((name.to_string(), profile))),
+            None => {
+                let file = path.map_or_else(|| "the configuration file".to_string(), |path| path.display().to_string());
+                Err(Failure::Usage(format!("Profile {name} is not defined in {file}.")))
+            }
+        },
+    }
+}
+
+/**
+ * Returns true when the option was not given on the command line, so the profile may
+ * set it.
+ */
+fn unset(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) != Some(ValueSource::CommandLine)
+}
+
+/**
+ * Returns the value of the profile for an option that is not given on the command line.
+ */
+fn pick<'a, T>(value: &'a Option<T>, matches: &ArgMatches, id: &str) -> Option<&'a T> {
+    value.as_ref().filter(|_| unset(matches, id))
+}
+
+/**
+ * Parses a value of the profile with the parser of the option, so the same names are
+ * accepted.
+ */
+fn parse<T: ValueEnum>(profile: &str, option: &str, value: &str) -> Result<T, Failure> {
+    T::from_str(value, true).map_err(|_| Failure::Usage(format!("Profile {profile} has an invalid {option}: {value}.")))
+}
+
+/**
+ * Checks that a value of the profile is at least 1, like the options that take it.
+ */
+fn positive(profile: &str, option: &str, value: u32) -> Result<u32, Failure> {
+    if value == 0 { Err(Failure::Usage(format!("Profile {profile} has an invalid {option}: it must be at least 1."))) } else { Ok(value) }
+}
+
+impl Profile {
+    /**
+     * Applies the settings of the encrypt command.
+     */
+    fn apply_encrypt(&self, name: &str, arguments: &mut EncryptArgs, matches: &ArgMatches) -> Result<(), Failure> {
+        if let Some(mode) = pick(&self.mode, matches, "mode") {
+            arguments.mode = parse::<ModeName>(name, "mode", mode)?;
+        }
+        if let Some(key_size) = pick(&self.key_size, matches, "key_size") {
+            arguments.key_size = parse::<KeySizeName>(name, "key-size", &key_size.to_string())?;
+        }
+        if let Some(kdf) = pick(&self.kdf, matches, "kdf") {
+            arguments.kdf = parse::<KdfName>(name, "kdf", kdf)?;
+        }
+        if let Some(iterations) = pick(&self.iterations, matches, "iterations") {
+            arguments.iterations = Some(positive(name, "iterations", *iterations)?);
+        }
+        if let Some(memory) = pick(&self.memory, matches, "memory") {
+            arguments.memory = *memory;
+        }
+        if let Some(parallelism) = pick(&self.parallelism, matches, "parallelism") {
+            arguments.parallelism = Some(positive(name, "parallelism", *parallelism)?);
+        }
+        if let Some(cost) = pick(&self.cost, matches, "cost") {
+            arguments.cost = *cost;
+        }
+        if let Some(block_size) = pick(&self.block_size, matches, "block_size") {
+            arguments.block_size = positive(name, "block-size", *block_size)?;
+        }
+        self.apply_files(name, &mut arguments.files, matches)
+    }
+
+    /**
+     * Applies the settings shared by encrypt and decrypt.
+     */
+    fn apply_files(&self, name: &str, files: &mut FileArgs, matches: &ArgMatches) -> Result<(), Failure> {
+        self.apply_password(&mut files.password, matches);
+        self.apply_chunks(name, &mut files.chunk_size, &mut files.jobs, matches)
+    }
+
+    /**
+     * Applies the settings of the verify command.
+     */
+    fn apply_verify(&self, name: &str, arguments: &mut VerifyArgs, matches: &ArgMatches) -> Result<(), Failure> {
+        self.apply_password(&mut arguments.password, matches);
+        self.apply_chunks(name, &mut arguments.chunk_size, &mut arguments.jobs, matches)
+    }
+
+    /**
+     * Applies the chunk size and the number of threads.
+     */
+    fn apply_chunks(&self, name: &str, chunk_size: &mut usize, jobs: &mut u32, matches: &ArgMatches) -> Result<(), Failure> {
+        if let Some(size) = pick(&self.chunk_size, matches, "chunk_size") {
+            *chunk_size = *size;
+        }
+        if let Some(threads) = pick(&self.jobs, matches, "jobs") {
+            *jobs = positive(name, "jobs", *threads)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Reads the password from the environment variable of the profile, unless the
+     * password is given some other way on the command line.
+     */
+    fn apply_password(&self, password: &mut PasswordArgs, matches: &ArgMatches) {
+        // --password-fd is only an option on Unix.
+        let fd_unset = cfg!(not(unix)) || unset(matches, "password_fd");
+        if self.password_env.is_some() && unset(matches, "password") && unset(matches, "password_env") && fd_unset {
+            password.password_env.clone_from(&self.password_env);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    const SAMPLE: &str = r#"
+[profile.default]
+kdf = "argon2id"
+memory = 262144
+
+[profile.archive]
+mode = "cbc"
+kdf = "scrypt"
+cost = 1048576
+chunk-size = 65536
+jobs = 2
+password-env = "ARCHIVE_PASSWORD"
+"#;
+
+    /**
+     * Parses a command line like main does.
+     */
+    fn parse_args(line: &[&str]) -> (Args, ArgMatches) {
+        let matches = Args::command().try_get_matches_from(line).unwrap();
+        (Args::from_arg_matches(&matches).unwrap(), matches)
+    }
+
+    /**
+     * Writes the configuration to a file of its own in the temporary directory.
+     */
+    fn write_config(name: &str, text: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("aes-config-{}-{name}.toml", std::process::id()));
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config: Config = toml::from_str(SAMPLE).unwrap();
+        assert_eq!(config.profile.len(), 2);
+        let archive = &config.profile["archive"];
+        assert_eq!((archive.mode.as_deref(), archive.kdf.as_deref(), archive.cost), (Some("cbc"), Some("scrypt"), Some(1048576)));
+        assert_eq!((archive.chunk_size, archive.jobs, archive.password_env.as_deref()), (Some(65536), Some(2), Some("ARCHIVE_PASSWORD")));
+        assert_eq!(config.profile["default"].memory, Some(262144));
+        assert!(toml::from_str::<Config>("[profile.default]\ncolour = \"blue\"").is_err());
+    }
+
+    #[test]
+    fn test_command_line_takes_precedence() {
+        let config: Config = toml::from_str(SAMPLE).unwrap();
+        let line = ["aes", "encrypt", "--input-file", "in", "--output-file", "out", "--password", "secret", "--mode", "ctr", "--jobs", "4"];
+        let (mut args, matches) = parse_args(&line);
+        apply(&mut args, &matches, "archive", &config.profile["archive"]).unwrap();
+        let Some(Command::Encrypt(arguments)) = &args.command else { panic!("not encrypt") };
+        // Given on the command line.
+        assert_eq!(arguments.mode, ModeName::Ctr);
+        assert_eq!(arguments.files.jobs, 4);
+        assert_eq!(arguments.files.password.password.as_deref(), Some("secret"));
+        assert_eq!(arguments.files.password.password_env, None);
+        // Taken from the profile, also over the defaults of the options.
+        assert_eq!(arguments.kdf, KdfName::Scrypt);
+        assert_eq!(arguments.cost, 1048576);
+        assert_eq!(arguments.files.chunk_size, 65536);
+        let (mut args, matches) = parse_args(&["aes", "decrypt", "--input-file", "in", "--output-file", "out"]);
+        apply(&mut args, &matches, "archive", &config.profile["archive"]).unwrap();
+        let Some(Command::Decrypt(files)) = &args.command else { panic!("not decrypt") };
+        assert_eq!(files.password.password_env.as_deref(), Some("ARCHIVE_PASSWORD"));
+        let profile = Profile { mode: Some("xts".to_string()), ..Profile::default() };
+        let (mut args, matches) = parse_args(&["aes", "encrypt", "--input-file", "in", "--output-file", "out"]);
+        assert!(matches!(apply(&mut args, &matches, "broken", &profile), Err(Failure::Usage(_))));
+    }
+
+    #[test]
+    fn test_load() {
+        let path = write_config("sample", SAMPLE);
+        let (name, profile) = load(Some(&path), None).unwrap().unwrap();
+        assert_eq!((name.as_str(), profile.kdf.as_deref()), ("default", Some("argon2id")));
+        let (name, profile) = load(Some(&path), Some("archive")).unwrap().unwrap();
+        assert_eq!((name.as_str(), profile.mode.as_deref()), ("archive", Some("cbc")));
+        assert!(matches!(load(Some(&path), Some("unknown")), Err(Failure::Usage(message)) if message.contains("Profile unknown is not defined")));
+        fs::remove_file(&path).unwrap();
+        let path = write_config("invalid", "[profile.default]\nmode = 3");
+        assert!(matches!(load(Some(&path), None), Err(Failure::Usage(message)) if message.starts_with("Invalid configuration file")));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let path = env::temp_dir().join(format!("aes-config-{}-missing.toml", std::process::id()));
+        assert!(load(Some(&path), None).unwrap().is_none());
+        assert!(load(None, None).unwrap().is_none());
+        assert!(matches!(load(Some(&path), Some("archive")), Err(Failure::Usage(_))));
+        assert!(matches!(load(None, Some("archive")), Err(Failure::Usage(message)) if message.contains("the configuration file")));
+    }
+}
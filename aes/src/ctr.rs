@@ -0,0 +1,274 @@
+//!
+//! Counter mode. Successive counter blocks are encrypted into a keystream that is
+//! xored with the data, so encryption and decryption are the same operation and
+//! the data does not need padding.
+//! Description: https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation#Counter_(CTR)
+//!
+
+use crate::{CHUNK_LENGTH, RoundKeys, encrypt_block, for_each_chunk};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::mpsc::{Receiver, sync_channel};
+#[cfg(feature = "std")]
+use zeroize::Zeroizing;
+
+///
+/// Counter struct with a 16 byte counter block. The whole block is treated as one
+/// 128 bit big-endian number when incremented.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counter {
+    block: [u8; 16],
+}
+
+impl Counter {
+    ///
+    /// Creates a counter starting at the specified block.
+    ///
+    /// block: The first counter block.
+    ///
+    /// result: A Counter struct.
+    ///
+    pub fn new(block: [u8; 16]) -> Counter {
+        Counter { block }
+    }
+
+    ///
+    /// Creates a counter from a nonce followed by a big-endian counter value. A 12 byte
+    /// nonce leaves 4 bytes for the counter, which is enough for 64 GB of data.
+    ///
+    /// nonce: Between 0 and 16 bytes placed first in the block.
+    /// counter: The counter value placed in the remaining bytes. Only the lowest
+    ///          16 - nonce length bytes are used.
+    ///
+    /// result: A Counter struct.
+    ///
+    /// Panics if the nonce is longer than 16 bytes.
+    ///
+    pub fn from_nonce(nonce: &[u8], counter: u128) -> Counter {
+        assert!(nonce.len() <= 16, "Nonce longer than 16 bytes: {}", nonce.len());
+        let mut block = counter.to_be_bytes();
+        block[..nonce.len()].copy_from_slice(nonce);
+        Counter { block }
+    }
+
+    ///
+    /// Returns the current counter block.
+    ///
+    pub fn block(&self) -> [u8; 16] {
+        self.block
+    }
+
+    ///
+    /// Increments the counter block by one. The carry runs through all 16 bytes,
+    /// into the nonce if the counter part overflows, and wraps to zero at the end.
+    ///
+    pub fn increment(&mut self) {
+        self.block = u128::from_be_bytes(self.block).wrapping_add(1).to_be_bytes();
+    }
+
+    ///
+    /// Returns the counter a number of blocks later, with the same carry as increment.
+    ///
+    /// count: The number of blocks to skip.
+    ///
+    /// result: A Counter struct.
+    ///
+    pub fn advance(&self, count: u128) -> Counter {
+        Counter { block: u128::from_be_bytes(self.block).wrapping_add(count).to_be_bytes() }
+    }
+
+    ///
+    /// Increments only the last 4 bytes of the counter block by one, wrapping to zero
+    /// within them. This is the increment used by GCM.
+    ///
+    pub fn increment_32(&mut self) {
+        let low = u32::from_be_bytes([self.block[12], self.block[13], self.block[14], self.block[15]]).wrapping_add(1);
+        self.block[12..].copy_from_slice(&low.to_be_bytes());
+    }
+}
+
+///
+/// Xors the data with the keystream from the encrypted counter blocks.
+///
+/// data: A vector of bytes of any length.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+/// counter: The first counter block. The same counter must never be used twice with the same key.
+///
+/// result: A vector of bytes with the same length as the data.
+///
+pub fn apply_keystream(data: &[u8], roundkeys: &RoundKeys, counter: Counter) -> Vec<u8> {
+    let mut result = data.to_vec();
+    for_each_chunk(&mut result, |idx, chunk| {
+        let mut counter = counter.advance((idx * CHUNK_LENGTH / 16) as u128);
+        for block in chunk.chunks_mut(16) {
+            for (value, key) in block.iter_mut().zip(encrypt_block(counter.block(), roundkeys)) {
+                *value ^= key;
+            }
+            counter.increment();
+        }
+    });
+    result
+}
+
+///
+/// Keystream struct that computes the keystream on a worker thread, a batch of blocks ahead
+/// of the data it is xored with. The next batch is computed while the caller xors and writes
+/// the current one, which helps when the caller mostly waits for I/O. With the parallel
+/// feature each batch is also spread over all cores.
+///
+#[cfg(feature = "std")]
+pub struct Keystream {
+    // Batches from the worker. The worker stops when the receiver is dropped.
+    receiver: Receiver<Zeroizing<Vec<u8>>>,
+    // The current batch, used from the position.
+    batch: Zeroizing<Vec<u8>>,
+    position: usize,
+}
+
+#[cfg(feature = "std")]
+impl Keystream {
+    ///
+    /// Starts the worker thread computing the keystream.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// counter: The first counter block. The same counter must never be used twice with the same key.
+    /// blocks: Number of blocks in each batch. One batch is waiting while the next is computed.
+    ///
+    /// result: A Keystream struct.
+    ///
+    /// Panics if blocks is zero.
+    ///
+    pub fn new(roundkeys: RoundKeys, counter: Counter, blocks: usize) -> Keystream {
+        assert!(blocks > 0, "Blocks must be at least 1");
+        let (sender, receiver) = sync_channel(1);
+        std::thread::spawn(move || {
+            let zeros = alloc::vec![0u8; 16 * blocks];
+            let mut counter = counter;
+            loop {
+                let batch = Zeroizing::new(apply_keystream(&zeros, &roundkeys, counter));
+                counter = counter.advance(blocks as u128);
+                if sender.send(batch).is_err() {
+                    break;
+                }
+            }
+        });
+        Keystream { receiver, batch: Zeroizing::new(Vec::new()), position: 0 }
+    }
+
+    ///
+    /// Xors the next bytes of the keystream with the data, so calls with partial blocks
+    /// continue where the previous call stopped.
+    ///
+    /// data: The data, encrypted or decrypted in place.
+    ///
+    pub fn apply(&mut self, data: &mut [u8]) {
+        let mut data = data;
+        while !data.is_empty() {
+            if self.position == self.batch.len() {
+                self.batch = self.receiver.recv().expect("Keystream worker stopped");
+                self.position = 0;
+            }
+            let count = data.len().min(self.batch.len() - self.position);
+            for (value, key) in data[..count].iter_mut().zip(&self.batch[self.position..]) {
+                *value ^= key;
+            }
+            self.position += count;
+            data = &mut data[count..];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AESData, DecryptedState};
+
+    #[test]
+    fn test_from_nonce() {
+        let counter = Counter::from_nonce(&[0xaa; 12], 0x0102);
+        assert_eq!(counter.block(), [0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0x00, 0x00, 0x01, 0x02]);
+        assert_eq!(Counter::from_nonce(&[], 1).block(), Counter::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]).block());
+    }
+
+    #[test]
+    fn test_increment() {
+        let mut counter = Counter::from_nonce(&[0x01; 12], 0xffff_ffff);
+        counter.increment();
+        assert_eq!(counter.block(), [0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00]);
+        let mut counter = Counter::new([0xff; 16]);
+        counter.increment();
+        assert_eq!(counter.block(), [0; 16]);
+        let mut counter = Counter::new([0xff; 16]);
+        counter.increment_32();
+        assert_eq!(counter.block(), [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00]);
+    }
+
+    ///
+    /// Example vector F.5.1 from NIST SP 800-38A. The counter carries from the last byte.
+    ///
+    #[test]
+    fn test_sp800_38a() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c]).unwrap();
+        let counter = Counter::new([0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff]);
+        let plaintext: Vec<u8> = vec![
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e,
+            0x51, 0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef, 0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c,
+            0x37, 0x10,
+        ];
+        let ciphertext: Vec<u8> = vec![
+            0x87, 0x4d, 0x61, 0x91, 0xb6, 0x20, 0xe3, 0x26, 0x1b, 0xef, 0x68, 0x64, 0x99, 0x0d, 0xb6, 0xce, 0x98, 0x06, 0xf6, 0x6b, 0x79, 0x70, 0xfd, 0xff, 0x86, 0x17, 0x18, 0x7b, 0xb9, 0xff, 0xfd,
+            0xff, 0x5a, 0xe4, 0xdf, 0x3e, 0xdb, 0xd5, 0xd3, 0x5e, 0x5b, 0x4f, 0x09, 0x02, 0x0d, 0xb0, 0x3e, 0xab, 0x1e, 0x03, 0x1d, 0xda, 0x2f, 0xbe, 0x03, 0xd1, 0x79, 0x21, 0x70, 0xa0, 0xf3, 0x00,
+            0x9c, 0xee,
+        ];
+        assert_eq!(apply_keystream(&plaintext, &roundkeys, counter), ciphertext);
+        assert_eq!(apply_keystream(&ciphertext, &roundkeys, counter), plaintext);
+    }
+
+    #[test]
+    fn test_partial_block() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[7; 32]).unwrap();
+        let counter = Counter::from_nonce(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12], 1);
+        let data = b"Counter mode needs no padding".to_vec();
+        let encrypted = apply_keystream(&data, &roundkeys, counter);
+        assert_eq!(encrypted.len(), data.len());
+        assert_eq!(apply_keystream(&encrypted, &roundkeys, counter), data);
+    }
+
+    ///
+    /// Data over several chunks gets the same keystream as one counter incremented for each block.
+    ///
+    #[test]
+    fn test_chunks() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[7; 16]).unwrap();
+        let counter = Counter::from_nonce(&[3; 8], u64::MAX as u128 - 1000);
+        let data = vec![0; 2 * CHUNK_LENGTH + 40];
+        let mut expected = Vec::new();
+        let mut next = counter;
+        while expected.len() < data.len() {
+            expected.extend_from_slice(&encrypt_block(next.block(), &roundkeys));
+            next.increment();
+        }
+        expected.truncate(data.len());
+        assert_eq!(apply_keystream(&data, &roundkeys, counter), expected);
+        assert_eq!(counter.advance(data.len().div_ceil(16) as u128), next);
+    }
+
+    ///
+    /// The precomputed keystream is the same as the keystream computed at once, across
+    /// batches and with pieces that are not whole blocks.
+    ///
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_keystream() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[7; 24]).unwrap();
+        let counter = Counter::from_nonce(&[4; 12], 0xffff_fffe);
+        let data: Vec<u8> = (0..1000).map(|value| (value % 251) as u8).collect();
+        let mut keystream = Keystream::new(roundkeys.clone(), counter, 3);
+        let mut encrypted = data.clone();
+        for chunk in encrypted.chunks_mut(37) {
+            keystream.apply(chunk);
+        }
+        assert_eq!(encrypted, apply_keystream(&data, &roundkeys, counter));
+    }
+}
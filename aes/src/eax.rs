@@ -0,0 +1,124 @@
+//!
+//! EAX authenticated mode. The data is encrypted in counter mode, and the nonce, the
+//! associated data and the ciphertext are each authenticated with CMAC under a
+//! different one block prefix. The tag is the xor of the three codes.
+//! Description: https://en.wikipedia.org/wiki/EAX_mode
+//!
+
+use crate::ctr::{self, Counter};
+use crate::{AesError, RoundKeys, cmac, constant_time_eq};
+use alloc::{vec, vec::Vec};
+
+///
+/// Length of the authentication tag in bytes.
+///
+pub const TAG_LENGTH: usize = 16;
+
+///
+/// Computes CMAC over a block ending with the tweak, followed by the data.
+///
+fn omac(tweak: u8, data: &[u8], roundkeys: &RoundKeys) -> [u8; 16] {
+    let mut input = vec![0u8; 16];
+    input[15] = tweak;
+    input.extend_from_slice(data);
+    cmac::compute(&input, roundkeys)
+}
+
+///
+/// Combines the codes of the nonce, the associated data and the ciphertext into the tag.
+///
+fn tag(nonce_mac: [u8; 16], aad: &[u8], ciphertext: &[u8], roundkeys: &RoundKeys) -> [u8; TAG_LENGTH] {
+    let aad_mac = omac(1, aad, roundkeys);
+    let ciphertext_mac = omac(2, ciphertext, roundkeys);
+    let mut tag = [0u8; TAG_LENGTH];
+    for (idx, value) in tag.iter_mut().enumerate() {
+        *value = nonce_mac[idx] ^ aad_mac[idx] ^ ciphertext_mac[idx];
+    }
+    tag
+}
+
+///
+/// Encrypts and authenticates the data.
+///
+/// plaintext: A vector of bytes of any length.
+/// aad: Associated data that is authenticated but not encrypted.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+/// nonce: A nonce of any length. The same nonce must never be used twice with the same key.
+///
+/// result: The ciphertext with the same length as the plaintext, and the tag.
+///
+pub fn encrypt(plaintext: &[u8], aad: &[u8], roundkeys: &RoundKeys, nonce: &[u8]) -> (Vec<u8>, [u8; TAG_LENGTH]) {
+    let nonce_mac = omac(0, nonce, roundkeys);
+    let ciphertext = ctr::apply_keystream(plaintext, roundkeys, Counter::new(nonce_mac));
+    let tag = tag(nonce_mac, aad, &ciphertext, roundkeys);
+    (ciphertext, tag)
+}
+
+///
+/// Verifies the tag and decrypts the data. Nothing is decrypted if the tag does not match.
+///
+/// ciphertext: A vector of bytes of any length.
+/// aad: The associated data used when encrypting.
+/// tag: The tag from the encryption.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+/// nonce: The nonce used when encrypting.
+///
+/// result: The plaintext, or an error if the data or tag have been changed.
+///
+pub fn decrypt(ciphertext: &[u8], aad: &[u8], tag: &[u8; TAG_LENGTH], roundkeys: &RoundKeys, nonce: &[u8]) -> Result<Vec<u8>, AesError> {
+    let nonce_mac = omac(0, nonce, roundkeys);
+    let expected = self::tag(nonce_mac, aad, ciphertext, roundkeys);
+    if !constant_time_eq(&expected, tag) {
+        return Err(AesError::Authentication);
+    }
+    Ok(ctr::apply_keystream(ciphertext, roundkeys, Counter::new(nonce_mac)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AESData, DecryptedState};
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    ///
+    /// Checks one of the test vectors from the EAX paper by Bellare, Rogaway and Wagner.
+    /// The expected result is the ciphertext followed by the tag.
+    ///
+    fn check(message: &str, key: &str, nonce: &str, header: &str, cipher: &str) {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&hex(key)).unwrap();
+        let (ciphertext, tag) = encrypt(&hex(message), &hex(header), &roundkeys, &hex(nonce));
+        assert_eq!([ciphertext.clone(), tag.to_vec()].concat(), hex(cipher));
+        assert_eq!(decrypt(&ciphertext, &hex(header), &tag, &roundkeys, &hex(nonce)), Ok(hex(message)));
+    }
+
+    #[test]
+    fn test_vectors() {
+        check("", "233952dee4d5ed5f9b9c6d6ff80ff478", "62ec67f9c3a4a407fcb2a8c49031a8b3", "6bfb914fd07eae6b", "e037830e8389f27b025a2d6527e79d01");
+        check("f7fb", "91945d3f4dcbee0bf45ef52255f095a4", "becaf043b0a23d843194ba972c66debd", "fa3bfd4806eb53fa", "19dd5c4c9331049d0bdab0277408f67967e5");
+        check("1a47cb4933", "01f74ad64077f2e704c0f60ada3dd523", "70c3db4f0d26368400a10ed05d2bff5e", "234a3463c1264ac6", "d851d5bae03a59f238a23e39199dc9266626c40f80");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[2; 24]).unwrap();
+        for length in [0, 1, 15, 16, 17, 100] {
+            let plaintext: Vec<u8> = (0..length as u8).collect();
+            let (ciphertext, tag) = encrypt(&plaintext, b"associated", &roundkeys, b"any length nonce");
+            assert_eq!(decrypt(&ciphertext, b"associated", &tag, &roundkeys, b"any length nonce"), Ok(plaintext));
+        }
+    }
+
+    #[test]
+    fn test_authentication_failure() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[2; 16]).unwrap();
+        let (ciphertext, tag) = encrypt(b"payload", b"header", &roundkeys, b"nonce");
+        let mut changed = ciphertext.clone();
+        changed[6] ^= 0x10;
+        assert_eq!(decrypt(&changed, b"header", &tag, &roundkeys, b"nonce"), Err(AesError::Authentication));
+        assert_eq!(decrypt(&ciphertext, b"headers", &tag, &roundkeys, b"nonce"), Err(AesError::Authentication));
+        assert_eq!(decrypt(&ciphertext, b"header", &tag, &roundkeys, b"nonce2"), Err(AesError::Authentication));
+    }
+}
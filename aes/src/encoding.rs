@@ -0,0 +1,165 @@
+//!
+//! Hex and Base64 encoding, so ciphertext can be sent in text protocols. Hex is written
+//! in lower case and read in both cases. Base64 uses the standard alphabet with padding.
+//! Description: https://en.wikipedia.org/wiki/Base64
+//! Specification: RFC 4648.
+//!
+
+use crate::AesError;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+///
+/// The hex digits in lower case.
+///
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+///
+/// The standard Base64 alphabet.
+///
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+///
+/// Encodes the data as hex.
+///
+/// data: A vector of bytes of any length.
+///
+/// result: Two lower case hex digits for each byte.
+///
+pub fn to_hex(data: &[u8]) -> String {
+    data.iter().flat_map(|value| [HEX[(value >> 4) as usize] as char, HEX[(value & 0x0f) as usize] as char]).collect()
+}
+
+///
+/// Decodes hex.
+///
+/// text: Hex digits in upper or lower case.
+///
+/// result: The bytes, or an error with the position of the first character that is not a
+///         hex digit. An odd length is reported at the position after the last digit.
+///
+pub fn from_hex(text: &str) -> Result<Vec<u8>, AesError> {
+    let text = text.as_bytes();
+    if !text.len().is_multiple_of(2) {
+        return Err(AesError::InvalidEncoding { position: text.len() });
+    }
+    let digit = |position: usize| hex_value(text[position]).ok_or(AesError::InvalidEncoding { position });
+    (0..text.len()).step_by(2).map(|position| Ok((digit(position)? << 4) | digit(position + 1)?)).collect()
+}
+
+///
+/// Encodes the data as Base64.
+///
+/// data: A vector of bytes of any length.
+///
+/// result: Four characters for each three bytes, padded with '=' to a multiple of four.
+///
+pub fn to_base64(data: &[u8]) -> String {
+    let mut result = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let group = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                result.push(BASE64[(group >> (18 - 6 * idx) & 0x3f) as usize] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
+
+///
+/// Decodes Base64 with padding.
+///
+/// text: Base64 in the standard alphabet, a multiple of four characters.
+///
+/// result: The bytes, or an error with the position of the first character that is not in
+///         the alphabet or is misplaced padding. A length that is not a multiple of four is
+///         reported at the position after the last character.
+///
+pub fn from_base64(text: &str) -> Result<Vec<u8>, AesError> {
+    let text = text.as_bytes();
+    if !text.len().is_multiple_of(4) {
+        return Err(AesError::InvalidEncoding { position: text.len() });
+    }
+    let mut result = Vec::with_capacity(text.len() / 4 * 3);
+    for (start, chunk) in (0..text.len()).step_by(4).zip(text.chunks_exact(4)) {
+        let last = start + 4 == text.len();
+        // Only the last group can have padding, and only in the last two characters.
+        let padding = if last { chunk.iter().rev().take_while(|value| **value == b'=').count().min(2) } else { 0 };
+        let mut group = 0u32;
+        for (idx, value) in chunk[..4 - padding].iter().enumerate() {
+            let value = base64_value(*value).ok_or(AesError::InvalidEncoding { position: start + idx })?;
+            group = group << 6 | value as u32;
+        }
+        group <<= 6 * padding;
+        result.extend_from_slice(&group.to_be_bytes()[1..4 - padding]);
+    }
+    Ok(result)
+}
+
+///
+/// The value of a hex digit.
+///
+fn hex_value(value: u8) -> Option<u8> {
+    match value {
+        b'0'..=b'9' => Some(value - b'0'),
+        b'a'..=b'f' => Some(value - b'a' + 10),
+        b'A'..=b'F' => Some(value - b'A' + 10),
+        _ => None,
+    }
+}
+
+///
+/// The value of a character in the Base64 alphabet.
+///
+fn base64_value(value: u8) -> Option<u8> {
+    match value {
+        b'A'..=b'Z' => Some(value - b'A'),
+        b'a'..=b'z' => Some(value - b'a' + 26),
+        b'0'..=b'9' => Some(value - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(to_hex(&[0x00, 0x1f, 0xa0, 0xff]), "001fa0ff");
+        assert_eq!(from_hex("001FA0ff"), Ok(vec![0x00, 0x1f, 0xa0, 0xff]));
+        assert_eq!(from_hex(""), Ok(vec![]));
+        assert_eq!(from_hex("abc"), Err(AesError::InvalidEncoding { position: 3 }));
+        assert_eq!(from_hex("ab0g"), Err(AesError::InvalidEncoding { position: 3 }));
+    }
+
+    ///
+    /// The test vectors from section 10 of RFC 4648.
+    ///
+    #[test]
+    fn test_base64_rfc4648() {
+        for (text, encoded) in [("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v"), ("foob", "Zm9vYg=="), ("fooba", "Zm9vYmE="), ("foobar", "Zm9vYmFy")] {
+            assert_eq!(to_base64(text.as_bytes()), encoded);
+            assert_eq!(from_base64(encoded), Ok(text.as_bytes().to_vec()));
+        }
+    }
+
+    #[test]
+    fn test_base64_all_bytes() {
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(from_base64(&to_base64(&data)), Ok(data));
+    }
+
+    #[test]
+    fn test_invalid_base64() {
+        assert_eq!(from_base64("Zm9"), Err(AesError::InvalidEncoding { position: 3 }));
+        assert_eq!(from_base64("Zm9v!mFy"), Err(AesError::InvalidEncoding { position: 4 }));
+        assert_eq!(from_base64("Zg==Zm9v"), Err(AesError::InvalidEncoding { position: 2 }));
+        assert_eq!(from_base64("Z==="), Err(AesError::InvalidEncoding { position: 1 }));
+    }
+}
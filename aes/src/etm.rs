@@ -0,0 +1,152 @@
+//!
+//! Encrypt-then-MAC container with AES-CBC and HMAC-SHA-256. The encryption key and the
+//! authentication key are derived from one master key with HKDF, the data is encrypted in
+//! CBC mode with PKCS#7 padding, and the tag covers the header, the ciphertext and the
+//! associated data. The tag is checked before anything is decrypted.
+//! Description: https://en.wikipedia.org/wiki/Authenticated_encryption#Encrypt-then-MAC_(EtM)
+//!
+//! The container is the magic bytes, the version, the iv, the ciphertext and the tag.
+//!
+
+use crate::hmac::{Hmac, TAG_LENGTH};
+use crate::padding::{Padding, Pkcs7};
+use crate::{AesError, Key, cbc, constant_time_eq, hkdf};
+use alloc::vec::Vec;
+use zeroize::Zeroizing;
+
+///
+/// The first bytes of a container.
+///
+pub const MAGIC: [u8; 4] = *b"AETM";
+
+///
+/// The version of the container written by seal.
+///
+pub const VERSION: u8 = 1;
+
+///
+/// Length of the magic bytes, the version and the iv.
+///
+pub const HEADER_LENGTH: usize = MAGIC.len() + 1 + 16;
+
+///
+/// Info for deriving the encryption key.
+///
+const ENCRYPTION_INFO: &[u8] = b"aes-cbc-hmac-sha256 encryption";
+
+///
+/// Info for deriving the authentication key.
+///
+const AUTHENTICATION_INFO: &[u8] = b"aes-cbc-hmac-sha256 authentication";
+
+///
+/// Derives the encryption key, with the same size as the master key, and the authentication key.
+///
+fn derive_keys(key: &Key) -> (Key, Hmac) {
+    let prk = Zeroizing::new(hkdf::hkdf_extract(&[], key.as_bytes()));
+    let encryption = Zeroizing::new(hkdf::hkdf_expand(&prk[..], ENCRYPTION_INFO, key.as_bytes().len()));
+    let authentication = Zeroizing::new(hkdf::hkdf_expand(&prk[..], AUTHENTICATION_INFO, TAG_LENGTH));
+    (Key::from_slice(&encryption).unwrap(), Hmac::new(&authentication))
+}
+
+///
+/// Computes the tag of the header and the ciphertext, followed by the associated data and
+/// its length in bits so the two can not be moved into each other.
+///
+fn tag(hmac: &Hmac, data: &[u8], aad: &[u8]) -> [u8; TAG_LENGTH] {
+    hmac.mac(&[data, aad, &(aad.len() as u64 * 8).to_be_bytes()].concat())
+}
+
+///
+/// Encrypts and authenticates the data into a container.
+///
+/// key: The master key, 16, 24 or 32 bytes.
+/// plaintext: A vector of bytes of any length.
+/// aad: Associated data that is authenticated but not stored in the container.
+/// iv: A random initialization vector. It must be unpredictable and never reused.
+///
+/// result: The container.
+///
+pub fn seal(key: &Key, plaintext: &[u8], aad: &[u8], iv: [u8; 16]) -> Vec<u8> {
+    let (encryption, hmac) = derive_keys(key);
+    let padded = Zeroizing::new(Pkcs7.pad(plaintext, 16));
+    let mut container = [&MAGIC[..], &[VERSION], &iv].concat();
+    container.extend_from_slice(&cbc::encrypt(&padded, &encryption.roundkeys(), iv));
+    let tag = tag(&hmac, &container, aad);
+    container.extend_from_slice(&tag);
+    container
+}
+
+///
+/// Checks the tag of a container and decrypts the data. Nothing is decrypted if the tag
+/// does not match.
+///
+/// key: The master key the container was sealed with.
+/// container: The container from seal.
+/// aad: The associated data used when sealing.
+///
+/// result: The plaintext, or an error if the container is malformed, has another version,
+///         or the container or the associated data have been changed.
+///
+pub fn open(key: &Key, container: &[u8], aad: &[u8]) -> Result<Vec<u8>, AesError> {
+    if container.len() < HEADER_LENGTH + 16 + TAG_LENGTH || !(container.len() - HEADER_LENGTH - TAG_LENGTH).is_multiple_of(16) {
+        return Err(AesError::InvalidDataLength { length: container.len() });
+    }
+    if container[..MAGIC.len()] != MAGIC {
+        return Err(AesError::InvalidContainer);
+    }
+    if container[MAGIC.len()] != VERSION {
+        return Err(AesError::UnsupportedVersion { version: container[MAGIC.len()] });
+    }
+    let (data, expected) = container.split_at(container.len() - TAG_LENGTH);
+    let (encryption, hmac) = derive_keys(key);
+    if !constant_time_eq(&tag(&hmac, data, aad), expected) {
+        return Err(AesError::Authentication);
+    }
+    let iv = data[MAGIC.len() + 1..HEADER_LENGTH].try_into().unwrap();
+    let padded = Zeroizing::new(cbc::decrypt(&data[HEADER_LENGTH..], &encryption.roundkeys(), iv));
+    Ok(Pkcs7.unpad(&padded, 16)?.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_open() {
+        let key = Key::from_slice(&[3; 32]).unwrap();
+        for length in [0, 1, 15, 16, 17, 100] {
+            let plaintext: Vec<u8> = (0..length).map(|value| value as u8).collect();
+            let container = seal(&key, &plaintext, b"header", [9; 16]);
+            assert_eq!(container.len(), HEADER_LENGTH + (length / 16 + 1) * 16 + TAG_LENGTH);
+            assert_eq!(&container[..4], b"AETM");
+            assert_eq!(open(&key, &container, b"header"), Ok(plaintext));
+        }
+    }
+
+    #[test]
+    fn test_authentication_failure() {
+        let key = Key::from_slice(&[3; 16]).unwrap();
+        let container = seal(&key, b"Attack at dawn", b"header", [9; 16]);
+        for idx in [MAGIC.len() + 1, HEADER_LENGTH, container.len() - 1] {
+            let mut changed = container.clone();
+            changed[idx] ^= 1;
+            assert_eq!(open(&key, &changed, b"header"), Err(AesError::Authentication));
+        }
+        assert_eq!(open(&key, &container, b"Header"), Err(AesError::Authentication));
+        assert_eq!(open(&Key::from_slice(&[4; 16]).unwrap(), &container, b"header"), Err(AesError::Authentication));
+    }
+
+    #[test]
+    fn test_invalid_container() {
+        let key = Key::from_slice(&[3; 16]).unwrap();
+        let container = seal(&key, b"Attack at dawn", &[], [9; 16]);
+        assert_eq!(open(&key, &container[..container.len() - 1], &[]), Err(AesError::InvalidDataLength { length: container.len() - 1 }));
+        let mut changed = container.clone();
+        changed[0] = b'X';
+        assert_eq!(open(&key, &changed, &[]), Err(AesError::InvalidContainer));
+        let mut changed = container.clone();
+        changed[MAGIC.len()] = 2;
+        assert_eq!(open(&key, &changed, &[]), Err(AesError::UnsupportedVersion { version: 2 }));
+    }
+}
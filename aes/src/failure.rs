@@ -0,0 +1,124 @@
+use std::fmt;
+use std::io;
+use std::process::ExitCode;
+
+use aes::AesError;
+
+/**
+ * The reason the program stops, with an exit code for each kind so scripts can
+ * tell a mistake in the arguments from a missing file or a wrong password.
+ */
+#[derive(Debug)]
+pub enum Failure {
+    // Arguments that can not be used, exit code 2 like the errors clap reports.
+    Usage(String),
+    // A file or the terminal that can not be read or written, exit code 3.
+    Io(String),
+    // Data that does not decrypt, because of a wrong password or a changed, damaged or
+    // unknown file, or a cipher that fails the self test, exit code 4.
+    Crypto(String),
+}
+
+impl Failure {
+    /**
+     * Returns the exit code of the failure.
+     */
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            Failure::Usage(_) => ExitCode::from(2),
+            Failure::Io(_) => ExitCode::from(3),
+            Failure::Crypto(_) => ExitCode::from(4),
+        }
+    }
+
+    /**
+     * Creates a crypto failure from an error of the library.
+     *
+     * # Arguments
+     * * `context`: What the program was doing, like "Failed to decrypt input file".
+     * * `error`: The error from the library.
+     *
+     */
+    pub fn crypto(context: &str, error: &AesError) -> Failure {
+        Failure::Crypto(format!("{context}: {}.", describe(error)))
+    }
+
+    /**
+     * Creates a usage failure from an error of the library, for arguments the library
+     * rejects.
+     *
+     * # Arguments
+     * * `context`: What is wrong with the arguments, like "Invalid key derivation parameters".
+     * * `error`: The error from the library.
+     *
+     */
+    pub fn usage(context: &str, error: &AesError) -> Failure {
+        Failure::Usage(format!("{context}: {}.", describe(error)))
+    }
+
+    /**
+     * Creates a failure from an I/O error. The library reports errors in the data as
+     * I/O errors with an AesError inside, which are crypto failures.
+     *
+     * # Arguments
+     * * `context`: What the program was doing, like "Failed to write to output file".
+     * * `error`: The error from reading, writing or the library.
+     *
+     */
+    pub fn io(context: &str, error: io::Error) -> Failure {
+        match error.get_ref().and_then(|inner| inner.downcast_ref::<AesError>()) {
+            Some(error) => Failure::crypto(context, error),
+            None => Failure::Io(format!("{context}: {error}.")),
+        }
+    }
+
+    /**
+     * Creates a failure from an error while reading encrypted data. A file that ends
+     * too early or holds malformed data is a problem with the data and not with
+     * reading it, so it is a crypto failure like the errors of the library.
+     *
+     * # Arguments
+     * * `context`: What the program was doing, like "Failed to decrypt input file".
+     * * `error`: The error from reading, writing or the library.
+     *
+     */
+    pub fn data(context: &str, error: io::Error) -> Failure {
+        match error.kind() {
+            io::ErrorKind::UnexpectedEof => Failure::Crypto(format!("{context}: the file is cut short.")),
+            io::ErrorKind::InvalidData if error.get_ref().is_none_or(|inner| !inner.is::<AesError>()) => Failure::Crypto(format!("{context}: {error}.")),
+            _ => Failure::io(context, error),
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Usage(message) | Failure::Io(message) | Failure::Crypto(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+/**
+ * Describes an error of the library in terms of what the user can do about it.
+ */
+fn describe(error: &AesError) -> String {
+    match error {
+        AesError::Authentication | AesError::Padding => "the password is wrong or the file has been changed".to_string(),
+        AesError::IntegrityCheck => "the password does not open any of the keys of the file".to_string(),
+        AesError::InvalidContainer => "the file is not encrypted by this program or it is damaged".to_string(),
+        AesError::UnsupportedVersion { version } => format!("the file has format version {version}, which needs a newer version of this program"),
+        AesError::UnknownKdf { .. } | AesError::UnknownMode { .. } => "the file is damaged or needs a newer version of this program".to_string(),
+        AesError::InvalidDataLength { .. } => "the file is damaged or cut short".to_string(),
+        AesError::InvalidKdfParams { parameter, value, requirement } => format!("the key derivation {parameter} {value} must be {requirement}"),
+        AesError::KdfLimit { parameter, value, limit } if parameter.ends_with("memory") => {
+            format!("the key derivation needs {} MiB of {parameter}, more than the limit of {} MiB set by --max-kdf-memory", value.div_ceil(1 << 20), limit >> 20)
+        }
+        AesError::KdfLimit { parameter, value, limit } => {
+            let option = if parameter.starts_with("PBKDF2") { "--max-kdf-iterations" } else { "--max-kdf-passes" };
+            format!("the key derivation {parameter} {value} is more than the limit of {limit} set by {option}")
+        }
+        AesError::Random => "the operating system could not provide random bytes".to_string(),
+        error => error.to_string(),
+    }
+}
@@ -0,0 +1,414 @@
+//!
+//! C interface, so the library can be called from C, C++ and any language with a C
+//! foreign function interface. A context is created from a key and holds the roundkeys
+//! until it is freed. The functions return a status code instead of panicking, and write
+//! their result into a buffer owned by the caller. The header is include/aes.h.
+//!
+//! Output buffers: the caller passes the capacity in output_length. The length of the
+//! result is always written back, so a call that fails with BufferTooSmall tells how
+//! much room is needed.
+//!
+
+use crate::ctr::Counter;
+use crate::{AesError, Cipher, Key, Mode};
+use std::boxed::Box;
+use std::vec::Vec;
+use zeroize::Zeroizing;
+
+///
+/// AesStatus enum with the result of a call.
+///
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AesStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A pointer that must be set is null.
+    NullPointer = 1,
+    /// The key is not 16, 24 or 32 bytes.
+    InvalidKeyLength = 2,
+    /// The iv or nonce has the wrong length for the mode.
+    InvalidIvLength = 3,
+    /// The data has the wrong length for the mode.
+    InvalidDataLength = 4,
+    /// The decrypted data does not end with valid padding.
+    Padding = 5,
+    /// The authentication tag does not match the data.
+    Authentication = 6,
+    /// The output buffer is too small. The needed length is written to output_length.
+    BufferTooSmall = 7,
+    /// Any other error.
+    Error = 8,
+    /// The mode is not one of the AesMode values.
+    InvalidMode = 9,
+}
+
+impl From<AesError> for AesStatus {
+    fn from(error: AesError) -> AesStatus {
+        match error {
+            AesError::InvalidKeyLength { .. } => AesStatus::InvalidKeyLength,
+            AesError::InvalidNonceLength { .. } => AesStatus::InvalidIvLength,
+            AesError::InvalidDataLength { .. } => AesStatus::InvalidDataLength,
+            AesError::Padding => AesStatus::Padding,
+            AesError::Authentication => AesStatus::Authentication,
+            _ => AesStatus::Error,
+        }
+    }
+}
+
+///
+/// AesMode enum with the modes available through the C interface.
+///
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AesMode {
+    /// Electronic codebook with PKCS#7 padding, no iv.
+    Ecb = 0,
+    /// Cipher block chaining with PKCS#7 padding and a 16 byte iv.
+    Cbc = 1,
+    /// Counter mode with a 16 byte first counter block.
+    Ctr = 2,
+    /// Cipher feedback with a 16 byte iv.
+    Cfb = 3,
+    /// Output feedback with a 16 byte iv.
+    Ofb = 4,
+    /// Galois/Counter mode with a nonce, normally 12 bytes, and associated data. The 16 byte
+    /// tag follows the ciphertext.
+    Gcm = 5,
+}
+
+impl AesMode {
+    ///
+    /// Finds the mode with the value passed from C, which may be any integer.
+    ///
+    /// id: The value of the mode.
+    ///
+    /// result: The mode, or InvalidMode if no mode has the value.
+    ///
+    pub fn from_id(id: u32) -> Result<AesMode, AesStatus> {
+        match id {
+            0 => Ok(AesMode::Ecb),
+            1 => Ok(AesMode::Cbc),
+            2 => Ok(AesMode::Ctr),
+            3 => Ok(AesMode::Cfb),
+            4 => Ok(AesMode::Ofb),
+            5 => Ok(AesMode::Gcm),
+            _ => Err(AesStatus::InvalidMode),
+        }
+    }
+}
+
+///
+/// AesContext struct with the cipher of a key. It is opaque to C.
+///
+pub struct AesContext {
+    cipher: Cipher,
+}
+
+///
+/// Creates a context for the key.
+///
+/// key: Pointer to the key.
+/// key_length: 16, 24 or 32.
+///
+/// result: The context, or null if the key is null or has an unsupported length. The
+///         context must be freed with aes_context_free.
+///
+/// # Safety
+///
+/// key must point to key_length readable bytes.
+///
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn aes_context_new(key: *const u8, key_length: usize) -> *mut AesContext {
+    let Ok(key) = (unsafe { slice(key, key_length) }).and_then(|key| Key::from_slice(key).map_err(AesStatus::from)) else {
+        return core::ptr::null_mut();
+    };
+    Box::into_raw(Box::new(AesContext { cipher: Cipher::new(&key) }))
+}
+
+///
+/// Frees a context. The roundkeys are overwritten. Null is ignored.
+///
+/// context: A context from aes_context_new.
+///
+/// # Safety
+///
+/// context must be null or a context from aes_context_new that has not been freed.
+///
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn aes_context_free(context: *mut AesContext) {
+    if !context.is_null() {
+        drop(unsafe { Box::from_raw(context) });
+    }
+}
+
+///
+/// Encrypts a buffer.
+///
+/// context: A context from aes_context_new.
+/// mode: The mode, one of the AesMode values.
+/// iv: The iv, counter block or nonce. May be null for ECB.
+/// iv_length: 0 for ECB, 16 for CBC, CTR, CFB and OFB, and the nonce length for GCM.
+/// aad: Associated data for GCM. May be null when aad_length is 0.
+/// aad_length: Length of the associated data.
+/// input: The plaintext.
+/// input_length: Length of the plaintext.
+/// output: The buffer for the ciphertext, followed by the tag in GCM.
+/// output_length: The capacity of output, replaced by the length of the ciphertext.
+///
+/// result: The status.
+///
+/// # Safety
+///
+/// Each pointer must be null or point to the given number of bytes, readable for the
+/// inputs and writable for output. output_length must point to a writable size_t.
+///
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn aes_encrypt(
+    context: *const AesContext,
+    mode: u32,
+    iv: *const u8,
+    iv_length: usize,
+    aad: *const u8,
+    aad_length: usize,
+    input: *const u8,
+    input_length: usize,
+    output: *mut u8,
+    output_length: *mut usize,
+) -> AesStatus {
+    unsafe { crypt(context, mode, iv, iv_length, aad, aad_length, input, input_length, output, output_length, Cipher::encrypt) }
+}
+
+///
+/// Decrypts a buffer. Nothing is written to output if the padding or the tag is invalid.
+///
+/// context: A context from aes_context_new.
+/// mode: The mode used when encrypting, one of the AesMode values.
+/// iv: The iv, counter block or nonce used when encrypting. May be null for ECB.
+/// iv_length: 0 for ECB, 16 for CBC, CTR, CFB and OFB, and the nonce length for GCM.
+/// aad: Associated data for GCM. May be null when aad_length is 0.
+/// aad_length: Length of the associated data.
+/// input: The ciphertext, followed by the tag in GCM.
+/// input_length: Length of the ciphertext.
+/// output: The buffer for the plaintext.
+/// output_length: The capacity of output, replaced by the length of the plaintext.
+///
+/// result: The status.
+///
+/// # Safety
+///
+/// Each pointer must be null or point to the given number of bytes, readable for the
+/// inputs and writable for output. output_length must point to a writable size_t.
+///
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn aes_decrypt(
+    context: *const AesContext,
+    mode: u32,
+    iv: *const u8,
+    iv_length: usize,
+    aad: *const u8,
+    aad_length: usize,
+    input: *const u8,
+    input_length: usize,
+    output: *mut u8,
+    output_length: *mut usize,
+) -> AesStatus {
+    unsafe { crypt(context, mode, iv, iv_length, aad, aad_length, input, input_length, output, output_length, Cipher::decrypt) }
+}
+
+///
+/// Computes the key schedule, the roundkeys of a key one after the other.
+///
+/// key: Pointer to the key.
+/// key_length: 16, 24 or 32.
+/// output: The buffer for the roundkeys, 176, 208 or 240 bytes.
+/// output_length: The capacity of output, replaced by the length of the roundkeys.
+///
+/// result: The status.
+///
+/// # Safety
+///
+/// key must point to key_length readable bytes, output to output_length writable bytes
+/// and output_length to a writable size_t.
+///
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn aes_key_schedule(key: *const u8, key_length: usize, output: *mut u8, output_length: *mut usize) -> AesStatus {
+    let key = match unsafe { slice(key, key_length) }.and_then(|key| Key::from_slice(key).map_err(AesStatus::from)) {
+        Ok(key) => key,
+        Err(status) => return status,
+    };
+    let roundkeys = Zeroizing::new(key.roundkeys().concat());
+    unsafe { write(&roundkeys, output, output_length) }
+}
+
+///
+/// Cipher::encrypt or Cipher::decrypt.
+///
+type Operation = fn(&Cipher, Mode, &[u8]) -> Result<Vec<u8>, AesError>;
+
+///
+/// Encrypts or decrypts with the mode built from the parameters. The mode is taken as an
+/// integer and checked, since a C enum may hold any value.
+///
+#[allow(clippy::too_many_arguments)]
+unsafe fn crypt(
+    context: *const AesContext,
+    mode: u32,
+    iv: *const u8,
+    iv_length: usize,
+    aad: *const u8,
+    aad_length: usize,
+    input: *const u8,
+    input_length: usize,
+    output: *mut u8,
+    output_length: *mut usize,
+    operation: Operation,
+) -> AesStatus {
+    let Some(context) = (unsafe { context.as_ref() }) else {
+        return AesStatus::NullPointer;
+    };
+    let result = (|| {
+        let iv = unsafe { slice(iv, iv_length)? };
+        let aad = unsafe { slice(aad, aad_length)? };
+        let input = unsafe { slice(input, input_length)? };
+        let block = || <[u8; 16]>::try_from(iv).map_err(|_| AesStatus::InvalidIvLength);
+        let mode = match AesMode::from_id(mode)? {
+            AesMode::Ecb if iv.is_empty() => Mode::Ecb,
+            AesMode::Ecb => return Err(AesStatus::InvalidIvLength),
+            AesMode::Cbc => Mode::Cbc { iv: block()? },
+            AesMode::Ctr => Mode::Ctr { counter: Counter::new(block()?) },
+            AesMode::Cfb => Mode::Cfb { iv: block()? },
+            AesMode::Ofb => Mode::Ofb { iv: block()? },
+            AesMode::Gcm => Mode::Gcm { nonce: iv, aad },
+        };
+        Ok(Zeroizing::new(operation(&context.cipher, mode, input)?))
+    })();
+    match result {
+        Ok(result) => unsafe { write(&result, output, output_length) },
+        Err(status) => status,
+    }
+}
+
+///
+/// A slice from a pointer and a length. A null pointer is allowed when the length is 0.
+///
+unsafe fn slice<'a>(data: *const u8, length: usize) -> Result<&'a [u8], AesStatus> {
+    match (data.is_null(), length) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(AesStatus::NullPointer),
+        (false, _) => Ok(unsafe { core::slice::from_raw_parts(data, length) }),
+    }
+}
+
+///
+/// Copies the result into the output buffer if it fits, and writes the length of the result.
+///
+unsafe fn write(result: &[u8], output: *mut u8, output_length: *mut usize) -> AesStatus {
+    let Some(output_length) = (unsafe { output_length.as_mut() }) else {
+        return AesStatus::NullPointer;
+    };
+    let capacity = core::mem::replace(output_length, result.len());
+    if capacity < result.len() {
+        return AesStatus::BufferTooSmall;
+    }
+    if result.is_empty() {
+        return AesStatus::Ok;
+    }
+    if output.is_null() {
+        return AesStatus::NullPointer;
+    }
+    unsafe { core::ptr::copy_nonoverlapping(result.as_ptr(), output, result.len()) };
+    AesStatus::Ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    ///
+    /// The FIPS-197 appendix C.1 vector through ECB, where the second block is the padding.
+    ///
+    #[test]
+    fn test_encrypt_decrypt() {
+        let key = hex("000102030405060708090a0b0c0d0e0f");
+        let plaintext = hex("00112233445566778899aabbccddeeff");
+        unsafe {
+            let context = aes_context_new(key.as_ptr(), key.len());
+            assert!(!context.is_null());
+            let mut output = [0u8; 32];
+            let mut length = output.len();
+            let status = aes_encrypt(context, AesMode::Ecb as u32, ptr::null(), 0, ptr::null(), 0, plaintext.as_ptr(), plaintext.len(), output.as_mut_ptr(), &mut length);
+            assert_eq!((status, length), (AesStatus::Ok, 32));
+            assert_eq!(output[..16], hex("69c4e0d86a7b0430d8cdb78070b4c55a"));
+            let mut decrypted = [0u8; 32];
+            let mut decrypted_length = decrypted.len();
+            let status = aes_decrypt(context, AesMode::Ecb as u32, ptr::null(), 0, ptr::null(), 0, output.as_ptr(), length, decrypted.as_mut_ptr(), &mut decrypted_length);
+            assert_eq!((status, &decrypted[..decrypted_length]), (AesStatus::Ok, &plaintext[..]));
+            aes_context_free(context);
+        }
+    }
+
+    #[test]
+    fn test_gcm() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 12];
+        unsafe {
+            let context = aes_context_new(key.as_ptr(), key.len());
+            let mut output = [0u8; 30];
+            let mut length = output.len();
+            let status = aes_encrypt(context, AesMode::Gcm as u32, nonce.as_ptr(), 12, b"header".as_ptr(), 6, b"Attack at dawn".as_ptr(), 14, output.as_mut_ptr(), &mut length);
+            assert_eq!((status, length), (AesStatus::Ok, 30));
+            let mut decrypted = [0u8; 14];
+            let mut decrypted_length = decrypted.len();
+            let status = aes_decrypt(context, AesMode::Gcm as u32, nonce.as_ptr(), 12, b"header".as_ptr(), 6, output.as_ptr(), 30, decrypted.as_mut_ptr(), &mut decrypted_length);
+            assert_eq!((status, &decrypted[..]), (AesStatus::Ok, &b"Attack at dawn"[..]));
+            output[0] ^= 1;
+            let status = aes_decrypt(context, AesMode::Gcm as u32, nonce.as_ptr(), 12, b"header".as_ptr(), 6, output.as_ptr(), 30, decrypted.as_mut_ptr(), &mut decrypted_length);
+            assert_eq!(status, AesStatus::Authentication);
+            aes_context_free(context);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let key = [1u8; 16];
+        unsafe {
+            assert!(aes_context_new(key.as_ptr(), 15).is_null());
+            assert!(aes_context_new(ptr::null(), 16).is_null());
+            aes_context_free(ptr::null_mut());
+            let context = aes_context_new(key.as_ptr(), key.len());
+            let mut output = [0u8; 16];
+            let mut length = 8;
+            let status = aes_encrypt(context, AesMode::Cbc as u32, key.as_ptr(), 16, ptr::null(), 0, key.as_ptr(), 4, output.as_mut_ptr(), &mut length);
+            assert_eq!((status, length), (AesStatus::BufferTooSmall, 16));
+            let status = aes_encrypt(context, AesMode::Cbc as u32, key.as_ptr(), 12, ptr::null(), 0, key.as_ptr(), 4, output.as_mut_ptr(), &mut length);
+            assert_eq!(status, AesStatus::InvalidIvLength);
+            let status = aes_decrypt(context, AesMode::Ecb as u32, ptr::null(), 0, ptr::null(), 0, key.as_ptr(), 4, output.as_mut_ptr(), &mut length);
+            assert_eq!(status, AesStatus::InvalidDataLength);
+            let status = aes_encrypt(ptr::null(), AesMode::Ecb as u32, ptr::null(), 0, ptr::null(), 0, key.as_ptr(), 4, output.as_mut_ptr(), &mut length);
+            assert_eq!(status, AesStatus::NullPointer);
+            let status = aes_encrypt(context, 6, ptr::null(), 0, ptr::null(), 0, key.as_ptr(), 4, output.as_mut_ptr(), &mut length);
+            assert_eq!(status, AesStatus::InvalidMode);
+            let status = aes_decrypt(context, u32::MAX, key.as_ptr(), 16, ptr::null(), 0, key.as_ptr(), 16, output.as_mut_ptr(), &mut length);
+            assert_eq!(status, AesStatus::InvalidMode);
+            aes_context_free(context);
+        }
+    }
+
+    #[test]
+    fn test_key_schedule() {
+        let key = hex("2b7e151628aed2a6abf7158809cf4f3c");
+        let mut output = [0u8; 240];
+        let mut length = output.len();
+        assert_eq!(unsafe { aes_key_schedule(key.as_ptr(), key.len(), output.as_mut_ptr(), &mut length) }, AesStatus::Ok);
+        assert_eq!(length, 176);
+        assert_eq!(output[160..176], hex("d014f9a8c9ee2589e13f0cc8b6630ca6"));
+    }
+}
@@ -0,0 +1,773 @@
+//!
+//! Versioned format of encrypted files. The header before the ciphertext holds everything
+//! except the password that is needed to decrypt: the key derivation function with its
+//! parameters, the salt, the mode, the iv or nonce and the tag. The header starts with
+//! magic bytes and a version, so files from a newer version are rejected with a clear error.
+//!
+//! Layout: the magic bytes, the version, the function id followed by its parameters as
+//! big-endian u32 values, the salt, the mode id, the length and bytes of the iv and the
+//! length and bytes of the tag. In GCM everything before the tag is authenticated.
+//!
+//! Version 2 adds recipients after the tag. The data is encrypted with a random content
+//! key, which is wrapped with AES key wrap once for every key that can open the file,
+//! each stored as a length and the wrapped bytes after a count. The recipients are not
+//! authenticated by GCM, so keys can be added and removed without encrypting the data
+//! again. Key wrap detects changes to a wrapped key by itself.
+//!
+//! Version 3 adds the key length in bytes after the salt, since the key derived from the
+//! password can be 16, 24 or 32 bytes, and always has the count of recipients, which may be
+//! zero. Versions 1 and 2 use 16 byte keys.
+//!
+
+use crate::cipher::{Cipher, Mode};
+use crate::ctr::Counter;
+use crate::kdf::Kdf;
+use crate::{AesError, Key, KeySize, argon2, gcm, keywrap, scrypt};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use zeroize::Zeroizing;
+
+///
+/// The first bytes of an encrypted file.
+///
+pub const MAGIC: [u8; 4] = *b"AESF";
+
+///
+/// The version of the format written by this library. Files with the earlier versions can
+/// still be read, and keep their version when their recipients are changed.
+///
+pub const VERSION: u8 = 3;
+
+///
+/// The first version of the format, with 16 byte keys and no recipients.
+///
+const FIRST_VERSION: u8 = 1;
+
+///
+/// The version of the format that added recipients, with 16 byte keys.
+///
+pub const RECIPIENTS_VERSION: u8 = 2;
+
+///
+/// Length of the random salt in bytes.
+///
+pub const SALT_LENGTH: usize = 16;
+
+///
+/// Lengths of a wrapped 16, 24 or 32 byte content key.
+///
+const WRAPPED_KEY_LENGTHS: [usize; 3] = [24, 32, 40];
+
+///
+/// Identifiers of the key derivation functions.
+///
+const PBKDF2_ID: u8 = 1;
+const ARGON2ID_ID: u8 = 2;
+const SCRYPT_ID: u8 = 3;
+
+///
+/// FileMode enum with the modes an encrypted file can use.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FileMode {
+    /// Electronic codebook with PKCS#7 padding, no iv.
+    Ecb = 1,
+    /// Cipher block chaining with PKCS#7 padding and a 16 byte iv.
+    Cbc = 2,
+    /// Counter mode with a 16 byte first counter block.
+    Ctr = 3,
+    /// Galois/Counter mode with a 12 byte nonce and a 16 byte tag.
+    Gcm = 4,
+}
+
+impl FileMode {
+    ///
+    /// Finds the mode with the identifier.
+    ///
+    /// id: The identifier stored in the header.
+    ///
+    /// result: The mode, or an error if no mode has the identifier.
+    ///
+    pub fn from_id(id: u8) -> Result<FileMode, AesError> {
+        match id {
+            1 => Ok(FileMode::Ecb),
+            2 => Ok(FileMode::Cbc),
+            3 => Ok(FileMode::Ctr),
+            4 => Ok(FileMode::Gcm),
+            _ => Err(AesError::UnknownMode { id }),
+        }
+    }
+
+    ///
+    /// Length of the iv or nonce of the mode in bytes.
+    ///
+    pub fn iv_length(&self) -> usize {
+        match self {
+            FileMode::Ecb => 0,
+            FileMode::Cbc | FileMode::Ctr => 16,
+            FileMode::Gcm => 12,
+        }
+    }
+
+    ///
+    /// Length of the tag of the mode in bytes.
+    ///
+    pub fn tag_length(&self) -> usize {
+        match self {
+            FileMode::Gcm => gcm::TAG_LENGTH,
+            _ => 0,
+        }
+    }
+}
+
+///
+/// Header struct with the contents of the header of an encrypted file.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Header {
+    // The version the header is written with.
+    version: u8,
+    pub kdf: Kdf,
+    pub salt: [u8; SALT_LENGTH],
+    /// The size of the key derived from the password.
+    pub key_size: KeySize,
+    pub mode: FileMode,
+    iv: Vec<u8>,
+    tag: Vec<u8>,
+    // The content key wrapped with the key of each recipient. Empty when the key derived
+    // from the password encrypts the data itself.
+    recipients: Vec<Vec<u8>>,
+}
+
+impl Header {
+    ///
+    /// Creates the header for a file that is about to be encrypted.
+    ///
+    /// kdf: The key derivation function with its parameters.
+    /// salt: The random salt used to derive the key.
+    /// mode: The mode to encrypt with.
+    /// iv: A random iv or nonce with the length of the mode. Empty for ECB.
+    ///
+    /// result: A Header struct, or an error if the iv has the wrong length for the mode.
+    ///
+    pub fn new(kdf: Kdf, salt: [u8; SALT_LENGTH], mode: FileMode, iv: &[u8]) -> Result<Header, AesError> {
+        if iv.len() != mode.iv_length() {
+            return Err(AesError::InvalidNonceLength { length: iv.len() });
+        }
+        Ok(Header { version: VERSION, kdf, salt, key_size: KeySize::Aes256, mode, iv: iv.to_vec(), tag: Vec::new(), recipients: Vec::new() })
+    }
+
+    ///
+    /// Sets the size of the key derived from the password. New headers use 32 byte keys.
+    ///
+    /// key_size: The size of the key.
+    ///
+    /// result: The Header struct with the key size.
+    ///
+    pub fn with_key_size(mut self, key_size: KeySize) -> Header {
+        self.key_size = key_size;
+        self
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn iv(&self) -> &[u8] {
+        &self.iv
+    }
+
+    pub fn tag(&self) -> &[u8] {
+        &self.tag
+    }
+
+    pub fn recipient_count(&self) -> usize {
+        self.recipients.len()
+    }
+
+    ///
+    /// Adds a recipient that can open the file with the key. A file without recipients is
+    /// encrypted with the key derived from the password, so recipients must be added before
+    /// the data is encrypted. To add a recipient to an encrypted file, the content key is
+    /// first unwrapped with the key of an existing recipient.
+    ///
+    /// content_key: The random key the data is encrypted with.
+    /// key: The key of the recipient, usually derived from a password with kdf and salt.
+    ///
+    /// result: Ok, or an error if the header is version 1, which has no recipients, or already
+    ///         has the most recipients the format can hold.
+    ///
+    pub fn add_recipient(&mut self, content_key: &Key, key: &Key) -> Result<(), AesError> {
+        if self.version == FIRST_VERSION {
+            return Err(AesError::UnsupportedVersion { version: FIRST_VERSION });
+        }
+        if self.recipients.len() == u8::MAX as usize {
+            return Err(AesError::InvalidContainer);
+        }
+        self.recipients.push(keywrap::wrap(content_key.as_bytes(), &key.roundkeys())?);
+        Ok(())
+    }
+
+    ///
+    /// Removes the recipients that can open the file with the key, for example after the
+    /// key has been replaced with a new recipient.
+    ///
+    /// key: The key of the recipient.
+    ///
+    /// result: Ok, or an error if no recipient has the key or it is the last recipient.
+    ///
+    pub fn remove_recipient(&mut self, key: &Key) -> Result<(), AesError> {
+        let roundkeys = key.roundkeys();
+        let remaining: Vec<Vec<u8>> = self.recipients.iter().filter(|wrapped| keywrap::unwrap(wrapped, &roundkeys).is_err()).cloned().collect();
+        if remaining.len() == self.recipients.len() {
+            return Err(AesError::Authentication);
+        }
+        if remaining.is_empty() {
+            return Err(AesError::LastRecipient);
+        }
+        self.recipients = remaining;
+        Ok(())
+    }
+
+    ///
+    /// Finds the key the data is encrypted with.
+    ///
+    /// key: The key derived from the password, or the key of a recipient.
+    ///
+    /// result: The content key unwrapped with the key, or the key itself if the header has
+    ///         no recipients. An error if no recipient has the key.
+    ///
+    pub fn content_key(&self, key: &Key) -> Result<Key, AesError> {
+        if self.recipients.is_empty() {
+            return Ok(key.clone());
+        }
+        let roundkeys = key.roundkeys();
+        let unwrapped = self.recipients.iter().find_map(|wrapped| keywrap::unwrap(wrapped, &roundkeys).ok()).ok_or(AesError::Authentication)?;
+        Key::from_slice(&Zeroizing::new(unwrapped))
+    }
+
+    ///
+    /// Serializes the header.
+    ///
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = self.authenticated();
+        result.push(self.tag.len() as u8);
+        result.extend_from_slice(&self.tag);
+        if self.version != FIRST_VERSION {
+            result.push(self.recipients.len() as u8);
+            for wrapped in &self.recipients {
+                result.push(wrapped.len() as u8);
+                result.extend_from_slice(wrapped);
+            }
+        }
+        result
+    }
+
+    ///
+    /// The bytes of the header before the tag, which GCM authenticates.
+    ///
+    fn authenticated(&self) -> Vec<u8> {
+        let (id, params) = match self.kdf {
+            Kdf::Pbkdf2 { iterations } => (PBKDF2_ID, [iterations].to_vec()),
+            Kdf::Argon2id(params) => (ARGON2ID_ID, [params.memory, params.iterations, params.parallelism].to_vec()),
+            Kdf::Scrypt(params) => (SCRYPT_ID, [params.n, params.r, params.p].to_vec()),
+        };
+        let mut result = [&MAGIC[..], &[self.version, id]].concat();
+        result.extend(params.iter().flat_map(|value| value.to_be_bytes()));
+        result.extend_from_slice(&self.salt);
+        if self.version == VERSION {
+            result.push(self.key_size.length() as u8);
+        }
+        result.extend_from_slice(&[self.mode as u8, self.iv.len() as u8]);
+        result.extend_from_slice(&self.iv);
+        result
+    }
+
+    ///
+    /// Reads the header from the start of an encrypted file.
+    ///
+    /// data: The contents of an encrypted file.
+    ///
+    /// result: The header and the ciphertext after it, or an error if the data does not start
+    ///         with a valid header or has a version this library can not read.
+    ///
+    pub fn parse(data: &[u8]) -> Result<(Header, &[u8]), AesError> {
+        let mut reader = Reader { data };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(AesError::InvalidContainer);
+        }
+        let version = reader.byte()?;
+        if !(FIRST_VERSION..=VERSION).contains(&version) {
+            return Err(AesError::UnsupportedVersion { version });
+        }
+        let kdf = match reader.byte()? {
+            PBKDF2_ID => Kdf::Pbkdf2 { iterations: reader.u32()? },
+            ARGON2ID_ID => Kdf::Argon2id(argon2::Params { memory: reader.u32()?, iterations: reader.u32()?, parallelism: reader.u32()? }),
+            SCRYPT_ID => Kdf::Scrypt(scrypt::Params { n: reader.u32()?, r: reader.u32()?, p: reader.u32()? }),
+            id => return Err(AesError::UnknownKdf { id }),
+        };
+        let salt = reader.take(SALT_LENGTH)?.try_into().unwrap();
+        let key_size = if version == VERSION {
+            let length = reader.byte()? as usize;
+            KeySize::from_length(length).ok_or(AesError::InvalidKeyLength { length })?
+        } else {
+            KeySize::Aes128
+        };
+        let mode = FileMode::from_id(reader.byte()?)?;
+        let length = reader.byte()? as usize;
+        if length != mode.iv_length() {
+            return Err(AesError::InvalidNonceLength { length });
+        }
+        let iv = reader.take(length)?.to_vec();
+        let length = reader.byte()? as usize;
+        if length != mode.tag_length() {
+            return Err(AesError::InvalidTagLength { length });
+        }
+        let tag = reader.take(length)?.to_vec();
+        let mut recipients = Vec::new();
+        if version != FIRST_VERSION {
+            let count = reader.byte()?;
+            for _ in 0..count {
+                let length = reader.byte()? as usize;
+                if !WRAPPED_KEY_LENGTHS.contains(&length) {
+                    return Err(AesError::InvalidContainer);
+                }
+                recipients.push(reader.take(length)?.to_vec());
+            }
+            if version == RECIPIENTS_VERSION && recipients.is_empty() {
+                return Err(AesError::InvalidContainer);
+            }
+        }
+        Ok((Header { version, kdf, salt, key_size, mode, iv, tag, recipients }, reader.data))
+    }
+
+    ///
+    /// Encrypts the data and writes the header with the tag before it.
+    ///
+    /// cipher: The cipher with the key derived from the password.
+    /// data: A vector of bytes of any length.
+    ///
+    /// result: The contents of the encrypted file.
+    ///
+    pub fn encrypt(mut self, cipher: &Cipher, data: &[u8]) -> Result<Vec<u8>, AesError> {
+        let aad = self.authenticated();
+        let mut ciphertext = cipher.encrypt(self.cipher_mode(&aad)?, data)?;
+        self.tag = ciphertext.split_off(ciphertext.len() - self.mode.tag_length());
+        Ok([self.to_bytes(), ciphertext].concat())
+    }
+
+    ///
+    /// Decrypts the ciphertext after the header.
+    ///
+    /// cipher: The cipher with the key derived from the password.
+    /// data: The ciphertext returned by parse.
+    ///
+    /// result: The plaintext, or an error if the padding is malformed or the tag does not match.
+    ///
+    pub fn decrypt(&self, cipher: &Cipher, data: &[u8]) -> Result<Vec<u8>, AesError> {
+        let aad = self.authenticated();
+        cipher.decrypt(self.cipher_mode(&aad)?, &[data, &self.tag].concat())
+    }
+
+    ///
+    /// Reads the header from the start of an encrypted file, leaving the reader at the ciphertext.
+    ///
+    /// input: The reader with the encrypted file.
+    ///
+    /// result: The header, or an error if reading fails or the input does not start with a
+    ///         valid header.
+    ///
+    #[cfg(feature = "std")]
+    pub fn read<R: Read>(input: &mut R) -> io::Result<Header> {
+        let mut data = Vec::new();
+        read_more(input, &mut data, MAGIC.len() + 2)?;
+        // An unknown function has no parameters to read, parse reports it below.
+        let params = match data[MAGIC.len() + 1] {
+            PBKDF2_ID => 1,
+            ARGON2ID_ID | SCRYPT_ID => 3,
+            _ => 0,
+        };
+        let key_length = usize::from(data[MAGIC.len()] == VERSION);
+        read_more(input, &mut data, 4 * params + SALT_LENGTH + key_length + 2)?;
+        let iv_length = data[data.len() - 1] as usize;
+        read_more(input, &mut data, iv_length + 1)?;
+        let tag_length = data[data.len() - 1] as usize;
+        read_more(input, &mut data, tag_length)?;
+        if data[MAGIC.len()] != FIRST_VERSION {
+            read_more(input, &mut data, 1)?;
+            for _ in 0..data[data.len() - 1] {
+                read_more(input, &mut data, 1)?;
+                let length = data[data.len() - 1] as usize;
+                read_more(input, &mut data, length)?;
+            }
+        }
+        Header::parse(&data).map(|(header, _)| header).map_err(to_io_error)
+    }
+
+    ///
+    /// Encrypts everything read from the input into the output after the header, a chunk at
+    /// a time. The tag is written into the header when the data is done.
+    ///
+    /// cipher: The cipher with the key derived from the password.
+    /// input: The reader with the plaintext.
+    /// output: The writer that receives the encrypted file, which must be able to seek back to the tag.
+    /// chunk_length: Number of bytes read at a time, a multiple of 16.
+    ///
+    /// result: Ok, or an error if reading or writing fails.
+    ///
+    #[cfg(feature = "std")]
+    pub fn encrypt_chunked<R: Read, W: Write + Seek>(self, cipher: &Cipher, input: R, output: W, chunk_length: usize) -> io::Result<()> {
+        self.encrypt_chunked_parallel(cipher, input, output, chunk_length, 1)
+    }
+
+    ///
+    /// Encrypts like encrypt_chunked, with each chunk split over a number of threads in ECB
+    /// and CTR. The file is the same as from encrypt_chunked.
+    ///
+    /// jobs: Number of threads each chunk is split over. The other modes use one thread.
+    ///
+    #[cfg(feature = "std")]
+    pub fn encrypt_chunked_parallel<R: Read, W: Write + Seek>(mut self, cipher: &Cipher, input: R, mut output: W, chunk_length: usize, jobs: usize) -> io::Result<()> {
+        let aad = self.authenticated();
+        self.tag = vec![0; self.mode.tag_length()];
+        let header = self.to_bytes();
+        let start = output.stream_position()?;
+        output.write_all(&header)?;
+        let tag = crate::pipeline::encrypt_parallel(cipher, self.cipher_mode(&aad).map_err(to_io_error)?, input, &mut output, chunk_length, jobs)?;
+        if !tag.is_empty() {
+            let end = output.stream_position()?;
+            output.seek(SeekFrom::Start(start + aad.len() as u64 + 1))?;
+            output.write_all(&tag)?;
+            output.seek(SeekFrom::Start(end))?;
+        }
+        output.flush()
+    }
+
+    ///
+    /// Decrypts everything read from the input into the output, a chunk at a time. In GCM
+    /// the plaintext is written before the tag is verified, so the output must be discarded
+    /// if this returns an error.
+    ///
+    /// cipher: The cipher with the key derived from the password.
+    /// input: The reader with the ciphertext, after the header returned by read.
+    /// output: The writer that receives the plaintext.
+    /// chunk_length: Number of bytes read at a time, a multiple of 16.
+    ///
+    /// result: Ok, or an error if reading or writing fails, the padding is malformed or the tag does not match.
+    ///
+    #[cfg(feature = "std")]
+    pub fn decrypt_chunked<R: Read, W: Write>(&self, cipher: &Cipher, input: R, output: W, chunk_length: usize) -> io::Result<()> {
+        self.decrypt_chunked_parallel(cipher, input, output, chunk_length, 1)
+    }
+
+    ///
+    /// Decrypts like decrypt_chunked, with each chunk split over a number of threads in ECB
+    /// and CTR.
+    ///
+    /// jobs: Number of threads each chunk is split over. The other modes use one thread.
+    ///
+    #[cfg(feature = "std")]
+    pub fn decrypt_chunked_parallel<R: Read, W: Write>(&self, cipher: &Cipher, input: R, output: W, chunk_length: usize, jobs: usize) -> io::Result<()> {
+        let aad = self.authenticated();
+        crate::pipeline::decrypt_parallel(cipher, self.cipher_mode(&aad).map_err(to_io_error)?, input, output, &self.tag, chunk_length, jobs)
+    }
+
+    ///
+    /// The mode of the cipher with the iv from the header. The lengths are checked again,
+    /// since a deserialized header has not been through new or parse.
+    ///
+    fn cipher_mode<'a>(&'a self, aad: &'a [u8]) -> Result<Mode<'a>, AesError> {
+        if self.iv.len() != self.mode.iv_length() {
+            return Err(AesError::InvalidNonceLength { length: self.iv.len() });
+        }
+        if !self.tag.is_empty() && self.tag.len() != self.mode.tag_length() {
+            return Err(AesError::InvalidTagLength { length: self.tag.len() });
+        }
+        Ok(match self.mode {
+            FileMode::Ecb => Mode::Ecb,
+            FileMode::Cbc => Mode::Cbc { iv: self.iv[..].try_into().unwrap() },
+            FileMode::Ctr => Mode::Ctr { counter: Counter::new(self.iv[..].try_into().unwrap()) },
+            FileMode::Gcm => Mode::Gcm { nonce: &self.iv, aad },
+        })
+    }
+}
+
+///
+/// Reads the fields of the header in order.
+///
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], AesError> {
+        if self.data.len() < length {
+            return Err(AesError::InvalidContainer);
+        }
+        let (result, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(result)
+    }
+
+    fn byte(&mut self) -> Result<u8, AesError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, AesError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+///
+/// Reads the next part of the header onto the end of the data. A file that ends in the
+/// header is an invalid container.
+///
+#[cfg(feature = "std")]
+fn read_more<R: Read>(input: &mut R, data: &mut Vec<u8>, length: usize) -> io::Result<()> {
+    let start = data.len();
+    data.resize(start + length, 0);
+    input.read_exact(&mut data[start..]).map_err(|error| if error.kind() == io::ErrorKind::UnexpectedEof { to_io_error(AesError::InvalidContainer) } else { error })
+}
+
+///
+/// Errors from the header or the cipher are reported as invalid data.
+///
+#[cfg(feature = "std")]
+fn to_io_error(error: AesError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Key;
+    use crate::kdf::KdfLimits;
+
+    fn cipher() -> Cipher {
+        Cipher::new(&Key::from_slice(&[5; 16]).unwrap())
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let kdfs = [Kdf::Pbkdf2 { iterations: 600_000 }, Kdf::Argon2id(argon2::Params::default()), Kdf::Scrypt(scrypt::Params::default())];
+        let modes = [(FileMode::Ecb, &[][..]), (FileMode::Cbc, &[1; 16][..]), (FileMode::Ctr, &[2; 16][..]), (FileMode::Gcm, &[3; 12][..])];
+        for kdf in kdfs {
+            for (mode, iv) in modes {
+                let header = Header::new(kdf, [4; SALT_LENGTH], mode, iv).unwrap();
+                let file = header.clone().encrypt(&cipher(), b"Attack at dawn").unwrap();
+                let (parsed, data) = Header::parse(&file).unwrap();
+                assert_eq!((parsed.kdf, parsed.salt, parsed.mode, parsed.iv()), (kdf, [4; SALT_LENGTH], mode, iv));
+                assert_eq!(parsed.tag().len(), mode.tag_length());
+                assert_eq!(parsed.decrypt(&cipher(), data), Ok(b"Attack at dawn".to_vec()));
+            }
+        }
+    }
+
+    ///
+    /// A header with key derivation parameters that would take terabytes of memory or run
+    /// practically forever is read, but refused before a key is derived.
+    ///
+    #[test]
+    fn test_malicious_kdf_params() {
+        let kdfs = [
+            Kdf::Pbkdf2 { iterations: u32::MAX },
+            Kdf::Argon2id(argon2::Params { memory: u32::MAX, iterations: 1, parallelism: 1 }),
+            Kdf::Argon2id(argon2::Params { memory: 64, iterations: u32::MAX - 1, parallelism: 1 }),
+            Kdf::Scrypt(scrypt::Params { n: 1 << 31, r: 8, p: 1 }),
+            Kdf::Scrypt(scrypt::Params { n: 16, r: 1, p: u32::MAX }),
+        ];
+        for kdf in kdfs {
+            let header = Header::new(kdf, [4; SALT_LENGTH], FileMode::Gcm, &[3; 12]).unwrap();
+            let file = header.encrypt(&cipher(), b"Attack at dawn").unwrap();
+            let (parsed, _) = Header::parse(&file).unwrap();
+            assert_eq!(parsed.kdf, kdf);
+            assert!(matches!(parsed.kdf.check_limits(&KdfLimits::default()), Err(AesError::KdfLimit { .. })));
+        }
+    }
+
+    ///
+    /// The header is authenticated in GCM, so a changed parameter is detected.
+    ///
+    #[test]
+    fn test_authenticated_header() {
+        let header = Header::new(Kdf::Pbkdf2 { iterations: 10 }, [4; SALT_LENGTH], FileMode::Gcm, &[3; 12]).unwrap();
+        let mut file = header.encrypt(&cipher(), b"Attack at dawn").unwrap();
+        file[MAGIC.len() + 5] ^= 1;
+        let (parsed, data) = Header::parse(&file).unwrap();
+        assert_eq!(parsed.decrypt(&cipher(), data), Err(AesError::Authentication));
+    }
+
+    ///
+    /// A file encrypted in chunks is the same as a file encrypted at once, and the header
+    /// can be read from the start of it.
+    ///
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_chunked() {
+        let modes = [(FileMode::Ecb, &[][..]), (FileMode::Cbc, &[1; 16][..]), (FileMode::Ctr, &[2; 16][..]), (FileMode::Gcm, &[3; 12][..])];
+        let data: Vec<u8> = (0..1000).map(|value| (value % 251) as u8).collect();
+        for (mode, iv) in modes {
+            let header = Header::new(Kdf::Scrypt(scrypt::Params::default()), [4; SALT_LENGTH], mode, iv).unwrap();
+            let mut file = io::Cursor::new(Vec::new());
+            header.clone().encrypt_chunked(&cipher(), &data[..], &mut file, 64).unwrap();
+            assert_eq!(file.get_ref(), &header.encrypt(&cipher(), &data).unwrap());
+            let mut input = &file.get_ref()[..];
+            let parsed = Header::read(&mut input).unwrap();
+            assert_eq!(Ok((parsed.clone(), input)), Header::parse(file.get_ref()));
+            let mut decrypted = Vec::new();
+            parsed.decrypt_chunked(&cipher(), input, &mut decrypted, 64).unwrap();
+            assert_eq!(decrypted, data);
+        }
+        let mut header = Header::new(Kdf::Pbkdf2 { iterations: 10 }, [4; SALT_LENGTH], FileMode::Gcm, &[3; 12]).unwrap();
+        header.add_recipient(&Key::from_slice(&[5; 16]).unwrap(), &Key::from_slice(&[1; 16]).unwrap()).unwrap();
+        header.add_recipient(&Key::from_slice(&[5; 16]).unwrap(), &Key::from_slice(&[2; 16]).unwrap()).unwrap();
+        let file = header.encrypt(&cipher(), &data).unwrap();
+        let mut input = &file[..];
+        assert_eq!(Ok((Header::read(&mut input).unwrap(), input)), Header::parse(&file));
+        let error = Header::read(&mut &MAGIC[..]).unwrap_err();
+        assert_eq!(error.get_ref().unwrap().to_string(), AesError::InvalidContainer.to_string());
+    }
+
+    ///
+    /// Every recipient opens the file, and keys can be replaced by changing only the header.
+    ///
+    #[test]
+    fn test_recipients() {
+        let content_key = Key::from_slice(&[7; 32]).unwrap();
+        let (first, second, third) = (Key::from_slice(&[1; 16]).unwrap(), Key::from_slice(&[2; 16]).unwrap(), Key::from_slice(&[3; 32]).unwrap());
+        let mut header = Header::new(Kdf::Pbkdf2 { iterations: 10 }, [4; SALT_LENGTH], FileMode::Gcm, &[3; 12]).unwrap();
+        header.add_recipient(&content_key, &first).unwrap();
+        header.add_recipient(&content_key, &second).unwrap();
+        let file = header.encrypt(&Cipher::new(&content_key), b"Attack at dawn").unwrap();
+        assert_eq!(file[MAGIC.len()], VERSION);
+        let (mut parsed, data) = Header::parse(&file).unwrap();
+        assert_eq!(parsed.recipient_count(), 2);
+        for key in [&first, &second] {
+            assert_eq!(parsed.decrypt(&Cipher::new(&parsed.content_key(key).unwrap()), data), Ok(b"Attack at dawn".to_vec()));
+        }
+        assert_eq!(parsed.content_key(&third), Err(AesError::Authentication));
+        // The first key is replaced without encrypting the data again.
+        parsed.add_recipient(&parsed.content_key(&first).unwrap(), &third).unwrap();
+        parsed.remove_recipient(&first).unwrap();
+        let rekeyed = [parsed.to_bytes(), data.to_vec()].concat();
+        let (parsed, data) = Header::parse(&rekeyed).unwrap();
+        assert_eq!(parsed.content_key(&first), Err(AesError::Authentication));
+        assert_eq!(parsed.decrypt(&Cipher::new(&parsed.content_key(&third).unwrap()), data), Ok(b"Attack at dawn".to_vec()));
+        let mut parsed = parsed;
+        assert_eq!(parsed.remove_recipient(&first), Err(AesError::Authentication));
+        parsed.remove_recipient(&second).unwrap();
+        assert_eq!(parsed.remove_recipient(&third), Err(AesError::LastRecipient));
+        assert_eq!(parsed.recipient_count(), 1);
+    }
+
+    ///
+    /// A header without recipients uses the key itself, and a changed wrapped key or a
+    /// recipient list cut short is rejected.
+    ///
+    #[test]
+    fn test_invalid_recipients() {
+        let header = Header::new(Kdf::Pbkdf2 { iterations: 10 }, [4; SALT_LENGTH], FileMode::Ctr, &[3; 16]).unwrap();
+        assert_eq!(header.content_key(&Key::from_slice(&[1; 16]).unwrap()), Key::from_slice(&[1; 16]));
+        let mut header = header;
+        header.add_recipient(&Key::from_slice(&[7; 16]).unwrap(), &Key::from_slice(&[1; 16]).unwrap()).unwrap();
+        let file = header.encrypt(&cipher(), b"Attack at dawn").unwrap();
+        let (parsed, data) = Header::parse(&file).unwrap();
+        let start = file.len() - data.len() - 24;
+        let mut changed = file.clone();
+        changed[start + 3] ^= 1;
+        assert_eq!(Header::parse(&changed).unwrap().0.content_key(&Key::from_slice(&[1; 16]).unwrap()), Err(AesError::Authentication));
+        let mut changed = file.clone();
+        changed[start - 1] = 20;
+        assert_eq!(Header::parse(&changed), Err(AesError::InvalidContainer));
+        let mut changed = file[..start - 2].to_vec();
+        changed.push(0);
+        assert_eq!(Header::parse(&changed).map(|(header, _)| header.recipient_count()), Ok(0));
+        assert_eq!(Header::parse(&file[..start + 10]), Err(AesError::InvalidContainer));
+        assert_eq!(parsed.content_key(&Key::from_slice(&[1; 16]).unwrap()), Key::from_slice(&[7; 16]));
+    }
+
+    ///
+    /// The key size is stored in the header, and files with the earlier versions, which have
+    /// no key size, are still read and keep their version.
+    ///
+    #[test]
+    fn test_versions() {
+        for key_size in [KeySize::Aes128, KeySize::Aes192, KeySize::Aes256] {
+            let header = Header::new(Kdf::Pbkdf2 { iterations: 10 }, [4; SALT_LENGTH], FileMode::Gcm, &[3; 12]).unwrap().with_key_size(key_size);
+            let file = header.encrypt(&cipher(), b"Attack at dawn").unwrap();
+            assert_eq!(file[MAGIC.len() + 6 + SALT_LENGTH] as usize, key_size.length());
+            assert_eq!(Header::parse(&file).unwrap().0.key_size, key_size);
+        }
+        let header = Header::new(Kdf::Pbkdf2 { iterations: 10 }, [4; SALT_LENGTH], FileMode::Gcm, &[3; 12]).unwrap();
+        assert_eq!((header.version(), header.key_size), (VERSION, KeySize::Aes256));
+        let mut first = Header { version: FIRST_VERSION, key_size: KeySize::Aes128, ..header.clone() };
+        assert_eq!(first.add_recipient(&Key::from_slice(&[7; 16]).unwrap(), &Key::from_slice(&[1; 16]).unwrap()), Err(AesError::UnsupportedVersion { version: 1 }));
+        let file = first.clone().encrypt(&cipher(), b"Attack at dawn").unwrap();
+        // The mode follows the salt directly, and nothing follows the tag.
+        assert_eq!(file[MAGIC.len() + 6 + SALT_LENGTH], FileMode::Gcm as u8);
+        let (parsed, data) = Header::parse(&file).unwrap();
+        assert_eq!(data.len(), 14);
+        assert_eq!((parsed.version(), parsed.key_size), (FIRST_VERSION, KeySize::Aes128));
+        assert_eq!(parsed.decrypt(&cipher(), data), Ok(b"Attack at dawn".to_vec()));
+        let content_key = Key::from_slice(&[7; 16]).unwrap();
+        let mut second = Header { version: RECIPIENTS_VERSION, key_size: KeySize::Aes128, ..header };
+        second.add_recipient(&content_key, &Key::from_slice(&[1; 16]).unwrap()).unwrap();
+        let file = second.encrypt(&Cipher::new(&content_key), b"Attack at dawn").unwrap();
+        let (mut parsed, data) = Header::parse(&file).unwrap();
+        assert_eq!((parsed.version(), parsed.key_size), (RECIPIENTS_VERSION, KeySize::Aes128));
+        parsed.add_recipient(&content_key, &Key::from_slice(&[2; 16]).unwrap()).unwrap();
+        parsed.remove_recipient(&Key::from_slice(&[1; 16]).unwrap()).unwrap();
+        let rekeyed = [parsed.to_bytes(), data.to_vec()].concat();
+        let (parsed, data) = Header::parse(&rekeyed).unwrap();
+        assert_eq!(parsed.version(), RECIPIENTS_VERSION);
+        assert_eq!(parsed.decrypt(&Cipher::new(&parsed.content_key(&Key::from_slice(&[2; 16]).unwrap()).unwrap()), data), Ok(b"Attack at dawn".to_vec()));
+        first.version = RECIPIENTS_VERSION;
+        // Version 2 always has at least one recipient.
+        let file = first.encrypt(&cipher(), b"Attack at dawn").unwrap();
+        assert_eq!(Header::parse(&file), Err(AesError::InvalidContainer));
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let header = Header::new(Kdf::Pbkdf2 { iterations: 10 }, [4; SALT_LENGTH], FileMode::Cbc, &[1; 16]).unwrap();
+        let file = header.encrypt(&cipher(), b"Attack at dawn").unwrap();
+        let mut changed = file.clone();
+        changed[0] = b'X';
+        assert_eq!(Header::parse(&changed), Err(AesError::InvalidContainer));
+        let mut changed = file.clone();
+        changed[MAGIC.len()] = 4;
+        assert_eq!(Header::parse(&changed), Err(AesError::UnsupportedVersion { version: 4 }));
+        let mut changed = file.clone();
+        changed[MAGIC.len() + 1] = 9;
+        assert_eq!(Header::parse(&changed), Err(AesError::UnknownKdf { id: 9 }));
+        let mut changed = file.clone();
+        changed[MAGIC.len() + 6 + SALT_LENGTH] = 20;
+        assert_eq!(Header::parse(&changed), Err(AesError::InvalidKeyLength { length: 20 }));
+        let mut changed = file.clone();
+        changed[MAGIC.len() + 7 + SALT_LENGTH] = 9;
+        assert_eq!(Header::parse(&changed), Err(AesError::UnknownMode { id: 9 }));
+        assert_eq!(Header::parse(&file[..MAGIC.len() + 10]), Err(AesError::InvalidContainer));
+        assert_eq!(Header::new(Kdf::Pbkdf2 { iterations: 10 }, [4; SALT_LENGTH], FileMode::Gcm, &[1; 16]), Err(AesError::InvalidNonceLength { length: 16 }));
+    }
+
+    ///
+    /// A header read back from JSON decrypts the file, and a header with an iv of the wrong
+    /// length for the mode is rejected instead of panicking.
+    ///
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let header = Header::new(Kdf::Argon2id(argon2::Params::default()), [4; SALT_LENGTH], FileMode::Gcm, &[3; 12]).unwrap();
+        let file = header.encrypt(&cipher(), b"Attack at dawn").unwrap();
+        let (parsed, data) = Header::parse(&file).unwrap();
+        let json = serde_json::to_string(&parsed).unwrap();
+        let header: Header = serde_json::from_str(&json).unwrap();
+        assert_eq!(header, parsed);
+        assert_eq!(header.decrypt(&cipher(), data), Ok(b"Attack at dawn".to_vec()));
+        let changed: Header = serde_json::from_str(&json.replace("[3,3,3,3,3,3,3,3,3,3,3,3]", "[3]")).unwrap();
+        assert_eq!(changed.decrypt(&cipher(), data), Err(AesError::InvalidNonceLength { length: 1 }));
+    }
+}
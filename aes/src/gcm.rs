@@ -0,0 +1,354 @@
+//!
+//! Galois/Counter mode. The data is encrypted in counter mode and authenticated
+//! with GHASH, a polynomial hash over GF(2^128) keyed with the encrypted zero block.
+//! Description: https://en.wikipedia.org/wiki/Galois/Counter_Mode
+//! Specification: NIST SP 800-38D.
+//!
+
+use crate::ctr::Counter;
+use crate::gmac::Gmac;
+use crate::{AesError, RoundKeys, constant_time_eq, encrypt_block};
+use alloc::vec::Vec;
+use zeroize::Zeroize;
+
+///
+/// Length of the authentication tag in bytes.
+///
+pub const TAG_LENGTH: usize = 16;
+
+///
+/// The reduction polynomial x^128 + x^7 + x^2 + x + 1 in the reflected bit order of GCM.
+///
+const R: u128 = 0xe1 << 120;
+
+///
+/// Multiplies two elements of GF(2^128). The bits of a block are the coefficients
+/// starting with x^0 in the most significant bit. The loop has no branches on the
+/// values, so the time does not depend on the key.
+///
+/// x: A block as a big-endian number.
+/// y: A block as a big-endian number.
+///
+/// result: The product as a big-endian number.
+///
+pub fn multiply(x: u128, y: u128) -> u128 {
+    let mut result: u128 = 0;
+    let mut value = y;
+    for bit in (0..128).rev() {
+        result ^= value & 0u128.wrapping_sub((x >> bit) & 1);
+        value = (value >> 1) ^ (R & 0u128.wrapping_sub(value & 1));
+    }
+    result
+}
+
+///
+/// Hashes the associated data and the ciphertext. Both are padded with zeros to whole
+/// blocks, followed by a block with their lengths in bits.
+///
+/// h: The hash key, the encrypted zero block.
+/// aad: Associated data that is authenticated but not encrypted.
+/// ciphertext: The encrypted data.
+///
+/// result: The hash as a big-endian number.
+///
+pub fn ghash(h: u128, aad: &[u8], ciphertext: &[u8]) -> u128 {
+    let mut hash: u128 = 0;
+    for chunk in aad.chunks(16).chain(ciphertext.chunks(16)) {
+        let mut block = [0u8; 16];
+        block[..chunk.len()].copy_from_slice(chunk);
+        hash = multiply(hash ^ u128::from_be_bytes(block), h);
+    }
+    let lengths = ((aad.len() as u128 * 8) << 64) | (ciphertext.len() as u128 * 8);
+    multiply(hash ^ lengths, h)
+}
+
+///
+/// Returns the hash key and the pre-counter block. A 12 byte nonce is followed by a
+/// counter starting at 1, other lengths are hashed.
+///
+pub(crate) fn setup(roundkeys: &RoundKeys, nonce: &[u8]) -> (u128, Counter) {
+    assert!(!nonce.is_empty(), "Nonce must not be empty");
+    let h = u128::from_be_bytes(encrypt_block([0; 16], roundkeys));
+    let pre_counter = if nonce.len() == 12 { Counter::from_nonce(nonce, 1) } else { Counter::new(ghash(h, &[], nonce).to_be_bytes()) };
+    (h, pre_counter)
+}
+
+///
+/// Counter mode with the 32 bit increment of GCM, starting after the pre-counter block.
+///
+fn gctr(data: &[u8], roundkeys: &RoundKeys, pre_counter: Counter) -> Vec<u8> {
+    let mut counter = pre_counter;
+    data.chunks(16)
+        .flat_map(|chunk| {
+            counter.increment_32();
+            let keystream = encrypt_block(counter.block(), roundkeys);
+            chunk.iter().zip(keystream).map(|(value, key)| value ^ key).collect::<Vec<u8>>()
+        })
+        .collect()
+}
+
+///
+/// Computes the tag from the hash and the encrypted pre-counter block.
+///
+fn tag(h: u128, pre_counter: Counter, aad: &[u8], ciphertext: &[u8], roundkeys: &RoundKeys) -> [u8; TAG_LENGTH] {
+    (u128::from_be_bytes(encrypt_block(pre_counter.block(), roundkeys)) ^ ghash(h, aad, ciphertext)).to_be_bytes()
+}
+
+///
+/// Encrypts and authenticates the data.
+///
+/// plaintext: A vector of bytes of any length.
+/// aad: Associated data that is authenticated but not encrypted.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+/// nonce: The nonce, normally 12 bytes. The same nonce must never be used twice with the same key.
+///
+/// result: The ciphertext with the same length as the plaintext, and the tag.
+///
+/// Panics if the nonce is empty.
+///
+pub fn encrypt(plaintext: &[u8], aad: &[u8], roundkeys: &RoundKeys, nonce: &[u8]) -> (Vec<u8>, [u8; TAG_LENGTH]) {
+    let (h, pre_counter) = setup(roundkeys, nonce);
+    let ciphertext = gctr(plaintext, roundkeys, pre_counter);
+    let tag = tag(h, pre_counter, aad, &ciphertext, roundkeys);
+    (ciphertext, tag)
+}
+
+///
+/// Verifies the tag and decrypts the data. Nothing is decrypted if the tag does not match.
+///
+/// ciphertext: A vector of bytes of any length.
+/// aad: The associated data used when encrypting.
+/// tag: The tag from the encryption.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+/// nonce: The nonce used when encrypting.
+///
+/// result: The plaintext, or an error if the ciphertext, associated data or tag have been changed.
+///
+/// Panics if the nonce is empty.
+///
+pub fn decrypt(ciphertext: &[u8], aad: &[u8], tag: &[u8; TAG_LENGTH], roundkeys: &RoundKeys, nonce: &[u8]) -> Result<Vec<u8>, AesError> {
+    let (h, pre_counter) = setup(roundkeys, nonce);
+    let expected = self::tag(h, pre_counter, aad, ciphertext, roundkeys);
+    if !constant_time_eq(&expected, tag) {
+        return Err(AesError::Authentication);
+    }
+    Ok(gctr(ciphertext, roundkeys, pre_counter))
+}
+
+///
+/// Gcm struct for encrypting or decrypting data that arrives in parts, so a message can be
+/// processed without holding all of it. When decrypting, the data is released before the
+/// tag is verified, so it must be discarded if verify fails.
+///
+pub struct Gcm<'a> {
+    roundkeys: &'a RoundKeys,
+    // GHASH of the associated data and the ciphertext so far.
+    gmac: Gmac,
+    // Lengths of the associated data and the ciphertext in bytes.
+    aad_length: u64,
+    length: u64,
+    // The counter of the keystream block.
+    counter: Counter,
+    keystream: [u8; 16],
+    // Number of bytes of the keystream block that are used.
+    used: usize,
+}
+
+impl<'a> Gcm<'a> {
+    ///
+    /// Creates a Gcm struct for the key, the nonce and the associated data.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// nonce: The nonce, normally 12 bytes. The same nonce must never be used twice with the same key.
+    /// aad: Associated data that is authenticated but not encrypted.
+    ///
+    /// result: A Gcm struct.
+    ///
+    /// Panics if the nonce is empty.
+    ///
+    pub fn new(roundkeys: &'a RoundKeys, nonce: &[u8], aad: &[u8]) -> Gcm<'a> {
+        let (_, counter) = setup(roundkeys, nonce);
+        let mut gmac = Gmac::new(roundkeys, nonce);
+        gmac.update(aad);
+        gmac.pad();
+        Gcm { roundkeys, gmac, aad_length: aad.len() as u64, length: 0, counter, keystream: [0; 16], used: 16 }
+    }
+
+    ///
+    /// Encrypts the next part of the data in the buffer it is stored in.
+    ///
+    /// data: A vector of bytes of any length.
+    ///
+    pub fn encrypt(&mut self, data: &mut [u8]) {
+        self.apply_keystream(data);
+        self.gmac.update(data);
+        self.length += data.len() as u64;
+    }
+
+    ///
+    /// Decrypts the next part of the data in the buffer it is stored in.
+    ///
+    /// data: A vector of bytes of any length.
+    ///
+    pub fn decrypt(&mut self, data: &mut [u8]) {
+        self.gmac.update(data);
+        self.length += data.len() as u64;
+        self.apply_keystream(data);
+    }
+
+    ///
+    /// Finishes the tag of the data encrypted or decrypted so far.
+    ///
+    /// result: The 16 byte tag.
+    ///
+    pub fn finalize(self) -> [u8; TAG_LENGTH] {
+        let lengths = ((self.aad_length as u128 * 8) << 64) | (self.length as u128 * 8);
+        self.gmac.clone().finish(lengths)
+    }
+
+    ///
+    /// Finishes the tag and compares it with the expected tag.
+    ///
+    /// tag: The tag the data was encrypted with.
+    ///
+    /// result: Ok, or an error if the data, the associated data or the tag have been changed.
+    ///
+    pub fn verify(self, tag: &[u8; TAG_LENGTH]) -> Result<(), AesError> {
+        if !constant_time_eq(&self.finalize(), tag) {
+            return Err(AesError::Authentication);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Xors the data with the keystream, continuing in the block where the last part stopped.
+    ///
+    fn apply_keystream(&mut self, data: &mut [u8]) {
+        for value in data.iter_mut() {
+            if self.used == 16 {
+                self.counter.increment_32();
+                self.keystream = encrypt_block(self.counter.block(), self.roundkeys);
+                self.used = 0;
+            }
+            *value ^= self.keystream[self.used];
+            self.used += 1;
+        }
+    }
+}
+
+///
+/// The keystream reveals the data it is xored with.
+///
+impl Drop for Gcm<'_> {
+    fn drop(&mut self) {
+        self.keystream.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AESData, DecryptedState};
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    ///
+    /// Checks one of the test cases from the GCM specification by McGrew and Viega.
+    ///
+    fn check(key: &str, plaintext: &str, aad: &str, nonce: &str, ciphertext: &str, tag: &str) {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&hex(key)).unwrap();
+        let (encrypted, computed_tag) = encrypt(&hex(plaintext), &hex(aad), &roundkeys, &hex(nonce));
+        assert_eq!(encrypted, hex(ciphertext));
+        assert_eq!(computed_tag.to_vec(), hex(tag));
+        assert_eq!(decrypt(&encrypted, &hex(aad), &computed_tag, &roundkeys, &hex(nonce)), Ok(hex(plaintext)));
+    }
+
+    #[test]
+    fn test_empty() {
+        check("00000000000000000000000000000000", "", "", "000000000000000000000000", "", "58e2fccefa7e3061367f1d57a4e7455a");
+    }
+
+    #[test]
+    fn test_zero_block() {
+        check("00000000000000000000000000000000", "00000000000000000000000000000000", "", "000000000000000000000000", "0388dace60b6a392f328c2b971b2fe78", "ab6e47d42cec13bdf53a67b21257bddf");
+    }
+
+    #[test]
+    fn test_associated_data() {
+        check(
+            "feffe9928665731c6d6a8f9467308308",
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+            "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+            "cafebabefacedbaddecaf888",
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+            "5bc94fbc3221a5db94fae95ae7121a47",
+        );
+    }
+
+    #[test]
+    fn test_short_nonce() {
+        check(
+            "feffe9928665731c6d6a8f9467308308",
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+            "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+            "cafebabefacedbad",
+            "61353b4c2806934a777ff51fa22a4755699b2a714fcdc6f83766e5f97b6c742373806900e49f24b22b097544d4896b424989b5e1ebac0f07c23f4598",
+            "3612d2e79e3b0785561be14aaca2fccb",
+        );
+    }
+
+    #[test]
+    fn test_aes256() {
+        check(
+            "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255",
+            "",
+            "cafebabefacedbaddecaf888",
+            "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662898015ad",
+            "b094dac5d93471bdec1a502270e3cc6c",
+        );
+    }
+
+    #[test]
+    fn test_authentication_failure() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[1; 16]).unwrap();
+        let (ciphertext, tag) = encrypt(b"Attack at dawn", b"header", &roundkeys, &[2; 12]);
+        let mut changed = ciphertext.clone();
+        changed[0] ^= 1;
+        assert_eq!(decrypt(&changed, b"header", &tag, &roundkeys, &[2; 12]), Err(AesError::Authentication));
+        assert_eq!(decrypt(&ciphertext, b"Header", &tag, &roundkeys, &[2; 12]), Err(AesError::Authentication));
+        let mut changed_tag = tag;
+        changed_tag[15] ^= 0x80;
+        assert_eq!(decrypt(&ciphertext, b"header", &changed_tag, &roundkeys, &[2; 12]), Err(AesError::Authentication));
+        assert_eq!(decrypt(&ciphertext, b"header", &tag, &roundkeys, &[3; 12]), Err(AesError::Authentication));
+    }
+
+    ///
+    /// Data in parts that are not whole blocks gives the same ciphertext and tag as the whole data.
+    ///
+    #[test]
+    fn test_parts() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[1; 24]).unwrap();
+        let plaintext: Vec<u8> = (0..100).collect();
+        for nonce in [&[2; 12][..], &[3; 8][..]] {
+            let (ciphertext, tag) = encrypt(&plaintext, b"associated data", &roundkeys, nonce);
+            let mut gcm = Gcm::new(&roundkeys, nonce, b"associated data");
+            let mut data = plaintext.clone();
+            for part in data.chunks_mut(7) {
+                gcm.encrypt(part);
+            }
+            assert_eq!((data.clone(), gcm.finalize()), (ciphertext, tag));
+            let mut gcm = Gcm::new(&roundkeys, nonce, b"associated data");
+            for part in data.chunks_mut(33) {
+                gcm.decrypt(part);
+            }
+            assert_eq!(gcm.verify(&tag), Ok(()));
+            assert_eq!(data, plaintext);
+            let mut gcm = Gcm::new(&roundkeys, nonce, b"associated data");
+            gcm.decrypt(&mut [0; 100]);
+            assert_eq!(gcm.verify(&tag), Err(AesError::Authentication));
+        }
+    }
+}
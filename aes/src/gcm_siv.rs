@@ -0,0 +1,203 @@
+//!
+//! AES-GCM-SIV, an authenticated mode that stays secure if a nonce is repeated. The tag
+//! is computed from the plaintext with POLYVAL and then used as the initial counter,
+//! so a repeated nonce only reveals whether the same message was encrypted twice.
+//! Fresh authentication and encryption keys are derived for every nonce.
+//! Description: https://en.wikipedia.org/wiki/AES-GCM-SIV
+//! Specification: RFC 8452.
+//!
+
+use crate::{AESData, AesError, DecryptedState, KeySize, RoundKeys, constant_time_eq, encrypt_block, gcm};
+use alloc::vec::Vec;
+
+///
+/// Length of the nonce in bytes.
+///
+pub const NONCE_LENGTH: usize = 12;
+
+///
+/// Length of the authentication tag in bytes.
+///
+pub const TAG_LENGTH: usize = 16;
+
+///
+/// Computes POLYVAL, the little-endian relative of GHASH. A POLYVAL block reversed is a
+/// GHASH block, and the key is multiplied by x to remove the extra x^-128 factor of the
+/// POLYVAL product, so the GHASH multiplication can be used.
+///
+/// h: The hash key.
+/// data: Whole 16 byte blocks to hash.
+///
+/// result: The hash.
+///
+/// Panics if the data is not a multiple of 16 bytes.
+///
+pub fn polyval(h: [u8; 16], data: &[u8]) -> [u8; 16] {
+    assert!(data.len().is_multiple_of(16), "POLYVAL input must be whole blocks");
+    let key = u128::from_le_bytes(h);
+    let key = (key >> 1) ^ (if key & 1 == 1 { 0xe1 << 120 } else { 0 });
+    let mut hash: u128 = 0;
+    for chunk in data.chunks(16) {
+        let mut block = [0u8; 16];
+        block.copy_from_slice(chunk);
+        hash = gcm::multiply(hash ^ u128::from_le_bytes(block), key);
+    }
+    hash.to_le_bytes()
+}
+
+///
+/// Derives the authentication key and the roundkeys of the encryption key for a nonce.
+/// Each derived block is the first half of an encrypted block with a counter and the nonce.
+///
+fn derive_keys(roundkeys: &RoundKeys, nonce: &[u8; NONCE_LENGTH]) -> ([u8; 16], RoundKeys) {
+    let key_blocks = if roundkeys.key_size() == KeySize::Aes128 { 2 } else { 4 };
+    let derived: Vec<u8> = (0..2 + key_blocks as u32)
+        .flat_map(|counter| {
+            let mut block = [0u8; 16];
+            block[..4].copy_from_slice(&counter.to_le_bytes());
+            block[4..].copy_from_slice(nonce);
+            encrypt_block(block, roundkeys)[..8].to_vec()
+        })
+        .collect();
+    let mut authentication_key = [0u8; 16];
+    authentication_key.copy_from_slice(&derived[..16]);
+    // The derived key has the same length as the key, so the roundkeys can always be generated.
+    (authentication_key, AESData::<DecryptedState>::generate_roundkeys(&derived[16..]).expect("Derived key of 16 or 32 bytes"))
+}
+
+///
+/// Computes the tag from POLYVAL over the padded associated data, the padded plaintext
+/// and their lengths in bits.
+///
+fn tag(authentication_key: [u8; 16], encryption_roundkeys: &RoundKeys, nonce: &[u8; NONCE_LENGTH], plaintext: &[u8], aad: &[u8]) -> [u8; TAG_LENGTH] {
+    let mut input: Vec<u8> = aad.to_vec();
+    input.resize(aad.len().next_multiple_of(16), 0);
+    input.extend_from_slice(plaintext);
+    input.resize(input.len().next_multiple_of(16), 0);
+    input.extend_from_slice(&(aad.len() as u64 * 8).to_le_bytes());
+    input.extend_from_slice(&(plaintext.len() as u64 * 8).to_le_bytes());
+    let mut hash = polyval(authentication_key, &input);
+    for (value, byte) in hash.iter_mut().zip(nonce) {
+        *value ^= byte;
+    }
+    hash[15] &= 0x7f;
+    encrypt_block(hash, encryption_roundkeys)
+}
+
+///
+/// Counter mode starting with the tag with the top bit set. The first 4 bytes are a
+/// little-endian counter, which wraps without carrying into the rest of the block.
+///
+fn ctr(data: &[u8], encryption_roundkeys: &RoundKeys, tag: &[u8; TAG_LENGTH]) -> Vec<u8> {
+    let mut counter = *tag;
+    counter[15] |= 0x80;
+    data.chunks(16)
+        .flat_map(|chunk| {
+            let keystream = encrypt_block(counter, encryption_roundkeys);
+            let next = u32::from_le_bytes([counter[0], counter[1], counter[2], counter[3]]).wrapping_add(1);
+            counter[..4].copy_from_slice(&next.to_le_bytes());
+            chunk.iter().zip(keystream).map(|(value, key)| value ^ key).collect::<Vec<u8>>()
+        })
+        .collect()
+}
+
+///
+/// Encrypts and authenticates the data.
+///
+/// plaintext: A vector of bytes of any length.
+/// aad: Associated data that is authenticated but not encrypted.
+/// roundkeys: 11 or 15 roundkeys of the key-generating key, a 16 or 32 byte key.
+/// nonce: The nonce. Repeating it with the same key is safe, but reveals equal messages.
+///
+/// result: The ciphertext with the same length as the plaintext, and the tag.
+///
+pub fn encrypt(plaintext: &[u8], aad: &[u8], roundkeys: &RoundKeys, nonce: &[u8; NONCE_LENGTH]) -> (Vec<u8>, [u8; TAG_LENGTH]) {
+    let (authentication_key, encryption_roundkeys) = derive_keys(roundkeys, nonce);
+    let tag = tag(authentication_key, &encryption_roundkeys, nonce, plaintext, aad);
+    (ctr(plaintext, &encryption_roundkeys, &tag), tag)
+}
+
+///
+/// Decrypts and verifies the data. No plaintext is returned if the tag does not match.
+///
+/// ciphertext: A vector of bytes of any length.
+/// aad: The associated data used when encrypting.
+/// tag: The tag from the encryption.
+/// roundkeys: 11 or 15 roundkeys of the key-generating key, a 16 or 32 byte key.
+/// nonce: The nonce used when encrypting.
+///
+/// result: The plaintext, or an error if the data or tag have been changed.
+///
+pub fn decrypt(ciphertext: &[u8], aad: &[u8], tag: &[u8; TAG_LENGTH], roundkeys: &RoundKeys, nonce: &[u8; NONCE_LENGTH]) -> Result<Vec<u8>, AesError> {
+    let (authentication_key, encryption_roundkeys) = derive_keys(roundkeys, nonce);
+    let plaintext = ctr(ciphertext, &encryption_roundkeys, tag);
+    let expected = self::tag(authentication_key, &encryption_roundkeys, nonce, &plaintext, aad);
+    if !constant_time_eq(&expected, tag) {
+        return Err(AesError::Authentication);
+    }
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    ///
+    /// Checks one of the test vectors from appendix C of RFC 8452.
+    ///
+    fn check(key: &str, plaintext: &str, aad: &str, ciphertext: &str, tag: &str) {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&hex(key)).unwrap();
+        let nonce: [u8; NONCE_LENGTH] = hex("030000000000000000000000").try_into().unwrap();
+        let (encrypted, computed_tag) = encrypt(&hex(plaintext), &hex(aad), &roundkeys, &nonce);
+        assert_eq!(encrypted, hex(ciphertext));
+        assert_eq!(computed_tag.to_vec(), hex(tag));
+        assert_eq!(decrypt(&encrypted, &hex(aad), &computed_tag, &roundkeys, &nonce), Ok(hex(plaintext)));
+    }
+
+    #[test]
+    fn test_polyval() {
+        let h: [u8; 16] = hex("25629347589242761d31f826ba4b757b").try_into().unwrap();
+        let data = hex("4f4f95668c83dfb6401762bb2d01a262d1a24ddd2721d006bbe45f20d3c9f362");
+        assert_eq!(polyval(h, &data).to_vec(), hex("f7a3b47b846119fae5b7866cf5e5b77e"));
+    }
+
+    #[test]
+    fn test_aes128() {
+        let key = "01000000000000000000000000000000";
+        check(key, "", "", "", "dc20e2d83f25705bb49e439eca56de25");
+        check(key, "0100000000000000", "", "b5d839330ac7b786", "578782fff6013b815b287c22493a364c");
+        check(key, "010000000000000000000000", "", "7323ea61d05932260047d942", "a4978db357391a0bc4fdec8b0d106639");
+    }
+
+    #[test]
+    fn test_aes256() {
+        let key = "0100000000000000000000000000000000000000000000000000000000000000";
+        check(key, "", "", "", "07f5f4169bbf55a8400cd47ea6fd400f");
+        check(key, "0100000000000000", "", "c2ef328e5c71c83b", "843122130f7364b761e0b97427e3df28");
+    }
+
+    #[test]
+    fn test_nonce_reuse_reveals_only_equality() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[8; 16]).unwrap();
+        let (first, first_tag) = encrypt(b"message one", b"", &roundkeys, &[0; NONCE_LENGTH]);
+        let (second, second_tag) = encrypt(b"message two", b"", &roundkeys, &[0; NONCE_LENGTH]);
+        assert_ne!(first_tag, second_tag);
+        assert_ne!(first.iter().zip(&second).map(|(a, b)| a ^ b).collect::<Vec<u8>>(), b"message one".iter().zip(b"message two").map(|(a, b)| a ^ b).collect::<Vec<u8>>());
+        assert_eq!(encrypt(b"message one", b"", &roundkeys, &[0; NONCE_LENGTH]), (first, first_tag));
+    }
+
+    #[test]
+    fn test_authentication_failure() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[8; 32]).unwrap();
+        let (ciphertext, tag) = encrypt(b"misuse resistant", b"aad", &roundkeys, &[5; NONCE_LENGTH]);
+        let mut changed = ciphertext.clone();
+        changed[0] ^= 1;
+        assert_eq!(decrypt(&changed, b"aad", &tag, &roundkeys, &[5; NONCE_LENGTH]), Err(AesError::Authentication));
+        assert_eq!(decrypt(&ciphertext, b"aae", &tag, &roundkeys, &[5; NONCE_LENGTH]), Err(AesError::Authentication));
+        assert_eq!(decrypt(&ciphertext, b"aad", &tag, &roundkeys, &[6; NONCE_LENGTH]), Err(AesError::Authentication));
+    }
+}
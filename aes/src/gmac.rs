@@ -0,0 +1,195 @@
+//!
+//! Galois message authentication code, GCM without any data to encrypt. The data is
+//! only hashed with GHASH and the tag is the hash xored with the encrypted pre-counter
+//! block, so it is the same tag GCM gives for the data as associated data.
+//! Description: https://en.wikipedia.org/wiki/Galois/Counter_Mode
+//! Specification: NIST SP 800-38D.
+//!
+
+use crate::gcm::{self, TAG_LENGTH};
+use crate::{AesError, RoundKeys, constant_time_eq, encrypt_block};
+use zeroize::Zeroize;
+
+///
+/// Gmac struct for computing the tag of data that arrives in parts.
+///
+#[derive(Debug, Clone)]
+pub struct Gmac {
+    // The hash key, the encrypted zero block.
+    h: u128,
+    // The encrypted pre-counter block.
+    mask: u128,
+    // The hash of the whole blocks so far.
+    hash: u128,
+    // The bytes that are not yet hashed, less than a block.
+    buffer: [u8; 16],
+    // Number of bytes in the buffer.
+    buffered: usize,
+    // Number of bytes added.
+    length: u64,
+}
+
+impl Gmac {
+    ///
+    /// Creates a Gmac struct for the key and the nonce.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// nonce: The nonce, normally 12 bytes. The same nonce must never be used twice with the same key.
+    ///
+    /// result: A Gmac struct.
+    ///
+    /// Panics if the nonce is empty.
+    ///
+    pub fn new(roundkeys: &RoundKeys, nonce: &[u8]) -> Gmac {
+        let (h, pre_counter) = gcm::setup(roundkeys, nonce);
+        let mask = u128::from_be_bytes(encrypt_block(pre_counter.block(), roundkeys));
+        Gmac { h, mask, hash: 0, buffer: [0; 16], buffered: 0, length: 0 }
+    }
+
+    ///
+    /// Adds data to the tag.
+    ///
+    /// data: A vector of bytes of any length.
+    ///
+    pub fn update(&mut self, data: &[u8]) {
+        self.length += data.len() as u64;
+        let mut data = data;
+        while !data.is_empty() {
+            let count = (16 - self.buffered).min(data.len());
+            self.buffer[self.buffered..self.buffered + count].copy_from_slice(&data[..count]);
+            self.buffered += count;
+            data = &data[count..];
+            if self.buffered == 16 {
+                self.hash = gcm::multiply(self.hash ^ u128::from_be_bytes(self.buffer), self.h);
+                self.buffered = 0;
+            }
+        }
+    }
+
+    ///
+    /// Finishes the tag. The last part of a block is padded with zeros and followed by the
+    /// block with the lengths in bits.
+    ///
+    /// result: The 16 byte tag.
+    ///
+    pub fn finalize(self) -> [u8; TAG_LENGTH] {
+        let lengths = (self.length as u128 * 8) << 64;
+        self.finish(lengths)
+    }
+
+    ///
+    /// Pads the data added so far with zeros to a whole block. GCM pads the associated data
+    /// before the ciphertext is added.
+    ///
+    pub(crate) fn pad(&mut self) {
+        if self.buffered > 0 {
+            self.buffer[self.buffered..].fill(0);
+            self.hash = gcm::multiply(self.hash ^ u128::from_be_bytes(self.buffer), self.h);
+            self.buffered = 0;
+        }
+    }
+
+    ///
+    /// Finishes the tag with the block of lengths.
+    ///
+    /// lengths: The lengths in bits of the associated data in the high 64 bits and of the ciphertext in the low 64 bits.
+    ///
+    /// result: The 16 byte tag.
+    ///
+    pub(crate) fn finish(mut self, lengths: u128) -> [u8; TAG_LENGTH] {
+        self.pad();
+        (self.mask ^ gcm::multiply(self.hash ^ lengths, self.h)).to_be_bytes()
+    }
+
+    ///
+    /// Finishes the tag and compares it with the expected tag.
+    ///
+    /// tag: The tag the data was sent with.
+    ///
+    /// result: Ok, or an error if the data or the tag have been changed.
+    ///
+    pub fn verify(self, tag: &[u8; TAG_LENGTH]) -> Result<(), AesError> {
+        if !constant_time_eq(&self.finalize(), tag) {
+            return Err(AesError::Authentication);
+        }
+        Ok(())
+    }
+}
+
+///
+/// The hash key and the mask reveal information about the key, and the buffer about the data.
+///
+impl Drop for Gmac {
+    fn drop(&mut self) {
+        self.h.zeroize();
+        self.mask.zeroize();
+        self.buffer.zeroize();
+    }
+}
+
+///
+/// Computes the tag of the data.
+///
+/// data: A vector of bytes of any length.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+/// nonce: The nonce, normally 12 bytes. The same nonce must never be used twice with the same key.
+///
+/// result: The 16 byte tag.
+///
+/// Panics if the nonce is empty.
+///
+pub fn compute(data: &[u8], roundkeys: &RoundKeys, nonce: &[u8]) -> [u8; TAG_LENGTH] {
+    let mut gmac = Gmac::new(roundkeys, nonce);
+    gmac.update(data);
+    gmac.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Key;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    ///
+    /// A vector with associated data and no plaintext from the NIST GCM test vectors.
+    ///
+    #[test]
+    fn test_nist() {
+        let roundkeys = Key::from_slice(&hex("77be63708971c4e240d1cb79e8d77feb")).unwrap().roundkeys();
+        let tag = compute(&hex("7a43ec1d9c0a5a78a0b16533a6213cab"), &roundkeys, &hex("e0e00f19fed7ba0136a797f3"));
+        assert_eq!(tag.to_vec(), hex("209fcc8d3675ed938e9c7166709dd946"));
+    }
+
+    ///
+    /// The tag is the GCM tag of the data as associated data, however the data is split.
+    ///
+    #[test]
+    fn test_same_as_gcm() {
+        let roundkeys = Key::from_slice(&hex("feffe9928665731c6d6a8f9467308308")).unwrap().roundkeys();
+        let nonce = hex("cafebabefacedbaddecaf888");
+        let data: Vec<u8> = (0..100).collect();
+        let (_, expected) = gcm::encrypt(&[], &data, &roundkeys, &nonce);
+        for length in [1, 7, 16, 33, 100] {
+            let mut gmac = Gmac::new(&roundkeys, &nonce);
+            for chunk in data.chunks(length) {
+                gmac.update(chunk);
+            }
+            assert_eq!(gmac.finalize(), expected);
+        }
+        assert_eq!(compute(&[], &roundkeys, &nonce), gcm::encrypt(&[], &[], &roundkeys, &nonce).1);
+    }
+
+    #[test]
+    fn test_verify() {
+        let roundkeys = Key::from_slice(&[1; 16]).unwrap().roundkeys();
+        let tag = compute(b"header", &roundkeys, &[2; 12]);
+        let mut gmac = Gmac::new(&roundkeys, &[2; 12]);
+        gmac.update(b"header");
+        assert_eq!(gmac.clone().verify(&tag), Ok(()));
+        gmac.update(b"!");
+        assert_eq!(gmac.verify(&tag), Err(AesError::Authentication));
+    }
+}
@@ -0,0 +1,225 @@
+//!
+//! Hash functions built from the block cipher with a compression function in the
+//! Merkle–Damgård construction. The message is padded with a one bit, zeros and its
+//! length in bits, and every 16 byte block is compressed into a 16 byte chaining value.
+//! Davies–Meyer uses the block as the key, Matyas–Meyer–Oseas the chaining value.
+//! The hashes are 128 bits, so collisions can be found with about 2^64 work. They are
+//! meant for checksums, not as a replacement for SHA-256.
+//! Description: https://en.wikipedia.org/wiki/One-way_compression_function
+//! Specification: ISO/IEC 10118-2.
+//!
+
+use crate::{AESData, DecryptedState, encrypt_block};
+use zeroize::Zeroize;
+
+///
+/// Length of a block in bytes.
+///
+pub const BLOCK_LENGTH: usize = 16;
+
+///
+/// Length of the hash in bytes.
+///
+pub const OUTPUT_LENGTH: usize = 16;
+
+///
+/// The chaining value before the first block.
+///
+const INITIAL_VALUE: [u8; 16] = [0; 16];
+
+///
+/// Compression functions that turn the block cipher into a one-way function.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    // H = E(m, H) xor H, with the message block as the key.
+    DaviesMeyer,
+    // H = E(H, m) xor m, with the chaining value as the key.
+    MatyasMeyerOseas,
+}
+
+impl Compression {
+    ///
+    /// Compresses one block into the chaining value.
+    ///
+    /// value: The chaining value.
+    /// block: The 16 byte message block.
+    ///
+    /// result: The next chaining value.
+    ///
+    fn compress(&self, value: [u8; 16], block: [u8; 16]) -> [u8; 16] {
+        let (key, plaintext) = match self {
+            Compression::DaviesMeyer => (block, value),
+            Compression::MatyasMeyerOseas => (value, block),
+        };
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&key).expect("A 16 byte key is supported");
+        let encrypted = encrypt_block(plaintext, &roundkeys);
+        core::array::from_fn(|idx| encrypted[idx] ^ plaintext[idx])
+    }
+}
+
+///
+/// AesHash struct for hashing data that arrives in parts.
+///
+#[derive(Debug, Clone)]
+pub struct AesHash {
+    compression: Compression,
+    // The chaining value of the blocks before the buffer.
+    value: [u8; 16],
+    // The bytes that are not yet compressed, less than one block.
+    buffer: [u8; 16],
+    // Number of bytes in the buffer.
+    buffered: usize,
+    // Number of bytes added in total.
+    length: u64,
+}
+
+impl AesHash {
+    ///
+    /// Creates an AesHash struct.
+    ///
+    /// compression: The compression function.
+    ///
+    /// result: An AesHash struct.
+    ///
+    pub fn new(compression: Compression) -> AesHash {
+        AesHash { compression, value: INITIAL_VALUE, buffer: [0; 16], buffered: 0, length: 0 }
+    }
+
+    ///
+    /// Adds data to the hash. Whole blocks are compressed, the rest is kept until more data arrives.
+    ///
+    /// data: A vector of bytes of any length.
+    ///
+    pub fn update(&mut self, data: &[u8]) {
+        self.length += data.len() as u64;
+        let mut data = data;
+        while !data.is_empty() {
+            let count = (BLOCK_LENGTH - self.buffered).min(data.len());
+            self.buffer[self.buffered..self.buffered + count].copy_from_slice(&data[..count]);
+            self.buffered += count;
+            data = &data[count..];
+            if self.buffered == BLOCK_LENGTH {
+                self.value = self.compression.compress(self.value, self.buffer);
+                self.buffered = 0;
+            }
+        }
+    }
+
+    ///
+    /// Pads the message and returns the hash.
+    ///
+    /// result: The 16 byte hash.
+    ///
+    pub fn finalize(mut self) -> [u8; OUTPUT_LENGTH] {
+        let bits = self.length * 8;
+        let mut padding = [0u8; 2 * BLOCK_LENGTH];
+        padding[0] = 0x80;
+        // The length takes the last 8 bytes, so a block with more than 7 bytes needs another block.
+        let count = if self.buffered < 8 { BLOCK_LENGTH - self.buffered } else { 2 * BLOCK_LENGTH - self.buffered };
+        padding[count - 8..count].copy_from_slice(&bits.to_be_bytes());
+        self.update(&padding[..count]);
+        self.value
+    }
+}
+
+///
+/// The buffered data and the chaining value reveal information about the data.
+///
+impl Drop for AesHash {
+    fn drop(&mut self) {
+        self.value.zeroize();
+        self.buffer.zeroize();
+    }
+}
+
+///
+/// Hashes the data.
+///
+/// data: A vector of bytes of any length.
+/// compression: The compression function.
+///
+/// result: The 16 byte hash.
+///
+pub fn digest(data: &[u8], compression: Compression) -> [u8; OUTPUT_LENGTH] {
+    let mut hash = AesHash::new(compression);
+    hash.update(data);
+    hash.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    const COMPRESSIONS: [Compression; 2] = [Compression::DaviesMeyer, Compression::MatyasMeyerOseas];
+
+    ///
+    /// The padded message written out block by block and compressed with the block cipher.
+    ///
+    #[test]
+    fn test_compression() {
+        let message = *b"abc";
+        let mut block = [0u8; 16];
+        block[..3].copy_from_slice(&message);
+        block[3] = 0x80;
+        block[15] = 24;
+        let encrypt = |key: [u8; 16], data: [u8; 16]| encrypt_block(data, &AESData::<DecryptedState>::generate_roundkeys(&key).unwrap());
+        let davies_meyer: [u8; 16] = core::array::from_fn(|idx| encrypt(block, INITIAL_VALUE)[idx] ^ INITIAL_VALUE[idx]);
+        assert_eq!(digest(&message, Compression::DaviesMeyer), davies_meyer);
+        let matyas_meyer_oseas: [u8; 16] = core::array::from_fn(|idx| encrypt(INITIAL_VALUE, block)[idx] ^ block[idx]);
+        assert_eq!(digest(&message, Compression::MatyasMeyerOseas), matyas_meyer_oseas);
+    }
+
+    ///
+    /// Messages of 8 to 15 bytes have no room for the length and need a second block.
+    ///
+    #[test]
+    fn test_padding() {
+        for compression in COMPRESSIONS {
+            let mut first = [0u8; 16];
+            first[..8].copy_from_slice(&[0xaa; 8]);
+            first[8] = 0x80;
+            let mut second = [0u8; 16];
+            second[15] = 64;
+            let value = compression.compress(compression.compress(INITIAL_VALUE, first), second);
+            assert_eq!(digest(&[0xaa; 8], compression), value);
+            let mut full = [0u8; 16];
+            full[0] = 0x80;
+            full[14] = 0x01;
+            assert_eq!(digest(&[0xaa; 32], compression), compression.compress(compression.compress(compression.compress(INITIAL_VALUE, [0xaa; 16]), [0xaa; 16]), full));
+        }
+    }
+
+    #[test]
+    fn test_update() {
+        let data: Vec<u8> = (0..100).collect();
+        for compression in COMPRESSIONS {
+            let expected = digest(&data, compression);
+            for length in [1, 7, 16, 17, 33] {
+                let mut hash = AesHash::new(compression);
+                for chunk in data.chunks(length) {
+                    hash.update(chunk);
+                }
+                hash.update(&[]);
+                assert_eq!(hash.finalize(), expected);
+            }
+        }
+    }
+
+    ///
+    /// Different messages, including ones that only differ in trailing zeros, give different hashes.
+    ///
+    #[test]
+    fn test_distinct() {
+        let mut hashes = Vec::new();
+        for compression in COMPRESSIONS {
+            for length in 0..40 {
+                hashes.push(digest(&alloc::vec![0; length], compression));
+            }
+        }
+        for (idx, hash) in hashes.iter().enumerate() {
+            assert!(!hashes[idx + 1..].contains(hash));
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//!
+//! HKDF with HMAC-SHA-256 for deriving keys from key material that is already random,
+//! like a master key. Extract concentrates the material into a pseudorandom key and
+//! expand stretches that into as many independent keys as needed, separated by the info.
+//! Description: https://en.wikipedia.org/wiki/HKDF
+//! Specification: RFC 5869.
+//!
+
+use crate::hmac::{self, Hmac, TAG_LENGTH};
+use alloc::vec::Vec;
+
+///
+/// Longest output of expand in bytes.
+///
+pub const MAX_OUTPUT_LENGTH: usize = 255 * TAG_LENGTH;
+
+///
+/// Extracts a pseudorandom key from the input key material.
+///
+/// salt: An optional random value. An empty salt is the same as 32 zero bytes.
+/// ikm: The input key material.
+///
+/// result: The 32 byte pseudorandom key.
+///
+pub fn hkdf_extract(salt: &[u8], ikm: &[u8]) -> [u8; TAG_LENGTH] {
+    hmac::mac(salt, ikm)
+}
+
+///
+/// Expands the pseudorandom key into output key material.
+///
+/// prk: A pseudorandom key of at least 32 bytes, normally from hkdf_extract.
+/// info: Context that separates keys derived from the same pseudorandom key, like "encryption" and "mac".
+/// length: The length of the output in bytes, at most 255 * 32.
+///
+/// result: A vector of bytes with the output key material.
+///
+/// Panics if the length is larger than 255 * 32.
+///
+pub fn hkdf_expand(prk: &[u8], info: &[u8], length: usize) -> Vec<u8> {
+    assert!(length <= MAX_OUTPUT_LENGTH, "HKDF output longer than {MAX_OUTPUT_LENGTH} bytes: {length}");
+    let hmac = Hmac::new(prk);
+    let mut result = Vec::with_capacity(length);
+    let mut block: Vec<u8> = Vec::new();
+    for counter in 1..=length.div_ceil(TAG_LENGTH) as u8 {
+        block = hmac.mac(&[&block[..], info, &[counter]].concat()).to_vec();
+        result.extend_from_slice(&block);
+    }
+    result.truncate(length);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    ///
+    /// Test cases 1 and 3 from RFC 5869.
+    ///
+    #[test]
+    fn test_rfc5869() {
+        let prk = hkdf_extract(&hex("000102030405060708090a0b0c"), &[0x0b; 22]);
+        assert_eq!(prk.to_vec(), hex("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"));
+        assert_eq!(hkdf_expand(&prk, &hex("f0f1f2f3f4f5f6f7f8f9"), 42), hex("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"));
+        let prk = hkdf_extract(&[], &[0x0b; 22]);
+        assert_eq!(prk.to_vec(), hex("19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04"));
+        assert_eq!(hkdf_expand(&prk, &[], 42), hex("8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"));
+    }
+
+    #[test]
+    fn test_separate_keys() {
+        let prk = hkdf_extract(b"salt", b"master key");
+        let encryption = hkdf_expand(&prk, b"encryption", 32);
+        let mac = hkdf_expand(&prk, b"mac", 32);
+        assert_ne!(encryption, mac);
+        assert_eq!(hkdf_expand(&prk, b"encryption", 16), encryption[..16]);
+        assert_eq!(hkdf_expand(&prk, b"mac", MAX_OUTPUT_LENGTH).len(), MAX_OUTPUT_LENGTH);
+    }
+}
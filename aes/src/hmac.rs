@@ -0,0 +1,120 @@
+//!
+//! HMAC with SHA-256. The key is mixed into an inner and an outer hash, so the tag
+//! can not be extended like a plain hash of the key and the message.
+//! Description: https://en.wikipedia.org/wiki/HMAC
+//! Specification: RFC 2104.
+//!
+
+use crate::sha256::{self, BLOCK_LENGTH, OUTPUT_LENGTH, Sha256};
+use crate::{AesError, constant_time_eq};
+
+///
+/// Length of the tag in bytes.
+///
+pub const TAG_LENGTH: usize = OUTPUT_LENGTH;
+
+///
+/// Hmac struct with the inner and outer hashes after the padded key. Computing a tag
+/// only clones them, which matters when the same key is used many times as in PBKDF2.
+///
+#[derive(Debug, Clone)]
+pub struct Hmac {
+    inner: Sha256,
+    outer: Sha256,
+}
+
+impl Hmac {
+    ///
+    /// Creates the hashes for the key. Keys longer than a block are hashed first.
+    ///
+    /// key: A key of any length.
+    ///
+    /// result: A Hmac struct.
+    ///
+    pub fn new(key: &[u8]) -> Hmac {
+        let mut block = [0u8; BLOCK_LENGTH];
+        if key.len() > BLOCK_LENGTH {
+            block[..OUTPUT_LENGTH].copy_from_slice(&sha256::digest(key));
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+        let mut inner = Sha256::new();
+        inner.update(&block.map(|value| value ^ 0x36));
+        let mut outer = Sha256::new();
+        outer.update(&block.map(|value| value ^ 0x5c));
+        Hmac { inner, outer }
+    }
+
+    ///
+    /// Computes the tag of the data.
+    ///
+    /// data: A vector of bytes of any length.
+    ///
+    /// result: The 32 byte tag.
+    ///
+    pub fn mac(&self, data: &[u8]) -> [u8; TAG_LENGTH] {
+        let mut inner = self.inner.clone();
+        inner.update(data);
+        let mut outer = self.outer.clone();
+        outer.update(&inner.finalize());
+        outer.finalize()
+    }
+
+    ///
+    /// Computes the tag of the data and compares it with the expected tag in constant time.
+    ///
+    /// data: A vector of bytes of any length.
+    /// tag: The tag the data was sent with, all 32 bytes or the first bytes of it.
+    ///
+    /// result: Ok, or an error if the data or the tag have been changed.
+    ///
+    pub fn verify(&self, data: &[u8], tag: &[u8]) -> Result<(), AesError> {
+        if tag.is_empty() || !constant_time_eq(&self.mac(data)[..tag.len().min(TAG_LENGTH)], tag) {
+            return Err(AesError::Authentication);
+        }
+        Ok(())
+    }
+}
+
+///
+/// Computes the tag of the data with the key.
+///
+/// key: A key of any length.
+/// data: A vector of bytes of any length.
+///
+/// result: The 32 byte tag.
+///
+pub fn mac(key: &[u8], data: &[u8]) -> [u8; TAG_LENGTH] {
+    Hmac::new(key).mac(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    ///
+    /// Test cases 1, 2 and 6 from RFC 4231.
+    ///
+    #[test]
+    fn test_rfc4231() {
+        assert_eq!(mac(&[0x0b; 20], b"Hi There").to_vec(), hex("b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"));
+        assert_eq!(mac(b"Jefe", b"what do ya want for nothing?").to_vec(), hex("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"));
+        assert_eq!(mac(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First").to_vec(), hex("60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"));
+    }
+
+    ///
+    /// Test case 5 from RFC 4231, with the tag truncated to 128 bits.
+    ///
+    #[test]
+    fn test_verify() {
+        let hmac = Hmac::new(&[0x0c; 20]);
+        assert_eq!(hmac.verify(b"Test With Truncation", &hex("a3b6167473100ee06e0c796c2955552b")), Ok(()));
+        assert_eq!(hmac.verify(b"Test With Truncation", &hex("a3b6167473100ee06e0c796c2955552c")), Err(AesError::Authentication));
+        assert_eq!(hmac.verify(b"Test with truncation", &hex("a3b6167473100ee06e0c796c2955552b")), Err(AesError::Authentication));
+        assert_eq!(hmac.verify(b"Test With Truncation", &[]), Err(AesError::Authentication));
+    }
+}
@@ -0,0 +1,169 @@
+//!
+//! Key derivation functions for turning a password into an AES key. The function and
+//! its cost parameters are chosen together, so they can be stored with the salt and
+//! the same key derived again when decrypting.
+//! Description: https://en.wikipedia.org/wiki/Key_derivation_function
+//!
+
+use crate::{AesError, argon2, pbkdf2, scrypt};
+use alloc::vec::Vec;
+
+///
+/// Kdf enum with the supported key derivation functions and their parameters.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Kdf {
+    /// PBKDF2-HMAC-SHA256 with an iteration count.
+    Pbkdf2 { iterations: u32 },
+    /// Argon2id with memory, iteration and parallelism parameters.
+    Argon2id(argon2::Params),
+    /// scrypt with the N, r and p parameters.
+    Scrypt(scrypt::Params),
+}
+
+///
+/// KdfLimits struct with the most a key derivation may cost, so parameters read from an
+/// untrusted header can not make it allocate more memory than the machine has or run
+/// practically forever.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfLimits {
+    /// Most memory in bytes, for Argon2id and scrypt.
+    pub max_memory: u64,
+    /// Most iterations of PBKDF2.
+    pub max_iterations: u32,
+    /// Most passes over the memory, the iterations of Argon2id and the parallelism of scrypt.
+    pub max_passes: u32,
+}
+
+impl Default for KdfLimits {
+    ///
+    /// 1 GiB of memory, 10 million PBKDF2 iterations and 64 passes, far above the
+    /// recommended parameters.
+    ///
+    fn default() -> Self {
+        KdfLimits { max_memory: 1 << 30, max_iterations: 10_000_000, max_passes: 64 }
+    }
+}
+
+impl Kdf {
+    ///
+    /// Derives a key from the password.
+    ///
+    /// password: The password as bytes.
+    /// salt: A random salt of at least 8 bytes, stored with the data.
+    /// length: The length of the derived key in bytes.
+    ///
+    /// result: A vector of bytes with the derived key.
+    ///
+    /// Panics if a parameter is outside the range of the function.
+    ///
+    pub fn derive(&self, password: &[u8], salt: &[u8], length: usize) -> Vec<u8> {
+        match self {
+            Kdf::Pbkdf2 { iterations } => pbkdf2::derive(password, salt, *iterations, length),
+            Kdf::Argon2id(params) => argon2::derive(password, salt, params, length),
+            Kdf::Scrypt(params) => scrypt::derive(password, salt, params, length),
+        }
+    }
+
+    ///
+    /// Checks that the parameters are in the range of the function, so derive does not
+    /// panic on parameters read from an untrusted header.
+    ///
+    /// result: Ok, or an error with the first parameter that is out of range.
+    ///
+    pub fn check(&self) -> Result<(), AesError> {
+        let invalid = |parameter, value: u32, requirement| Err(AesError::InvalidKdfParams { parameter, value: u64::from(value), requirement });
+        match self {
+            Kdf::Pbkdf2 { iterations: 0 } => invalid("PBKDF2 iterations", 0, "at least 1"),
+            Kdf::Argon2id(params) if params.iterations == 0 => invalid("Argon2id iterations", 0, "at least 1"),
+            Kdf::Argon2id(params) if params.parallelism == 0 || params.parallelism >= 1 << 24 => invalid("Argon2id parallelism", params.parallelism, "from 1 to 2^24 - 1"),
+            Kdf::Argon2id(params) if params.memory < 8 * params.parallelism => invalid("Argon2id memory in KiB", params.memory, "at least 8 per lane"),
+            Kdf::Scrypt(params) if params.n < 2 || !params.n.is_power_of_two() => invalid("scrypt cost", params.n, "a power of two larger than 1"),
+            Kdf::Scrypt(params) if params.r == 0 => invalid("scrypt block size", 0, "at least 1"),
+            Kdf::Scrypt(params) if params.p == 0 => invalid("scrypt parallelism", 0, "at least 1"),
+            Kdf::Scrypt(params) if params.memory().is_none() => invalid("scrypt block size", params.r, "small enough for the memory to be addressable"),
+            _ => Ok(()),
+        }
+    }
+
+    ///
+    /// Checks that the parameters are in the range of the function and that deriving a
+    /// key with them stays within the limits.
+    ///
+    /// limits: The most memory, iterations and passes allowed.
+    ///
+    /// result: Ok, or an error with the first parameter that is out of range or over its
+    ///         limit. Memory is given in bytes.
+    ///
+    pub fn check_limits(&self, limits: &KdfLimits) -> Result<(), AesError> {
+        self.check()?;
+        let over = |parameter, value: u64, limit: u64| if value > limit { Err(AesError::KdfLimit { parameter, value, limit }) } else { Ok(()) };
+        match self {
+            Kdf::Pbkdf2 { iterations } => over("PBKDF2 iterations", u64::from(*iterations), u64::from(limits.max_iterations)),
+            Kdf::Argon2id(params) => {
+                over("Argon2id memory", u64::from(params.memory) * 1024, limits.max_memory)?;
+                over("Argon2id iterations", u64::from(params.iterations), u64::from(limits.max_passes))
+            }
+            Kdf::Scrypt(params) => {
+                over("scrypt memory", params.memory().map_or(u64::MAX, |memory| memory as u64), limits.max_memory)?;
+                over("scrypt parallelism", u64::from(params.p), u64::from(limits.max_passes))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive() {
+        let pbkdf2 = Kdf::Pbkdf2 { iterations: 2 };
+        assert_eq!(pbkdf2.derive(b"password", b"saltsalt", 16), pbkdf2::derive(b"password", b"saltsalt", 2, 16));
+        let params = argon2::Params { memory: 16, iterations: 1, parallelism: 1 };
+        let argon2id = Kdf::Argon2id(params);
+        assert_eq!(argon2id.derive(b"password", b"saltsalt", 16), argon2::derive(b"password", b"saltsalt", &params, 16));
+        let params = scrypt::Params { n: 16, r: 1, p: 1 };
+        let scrypt = Kdf::Scrypt(params);
+        assert_eq!(scrypt.derive(b"password", b"saltsalt", 16), scrypt::derive(b"password", b"saltsalt", &params, 16));
+        assert_ne!(pbkdf2.derive(b"password", b"saltsalt", 16), argon2id.derive(b"password", b"saltsalt", 16));
+        assert_ne!(pbkdf2.derive(b"password", b"saltsalt", 16), scrypt.derive(b"password", b"saltsalt", 16));
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(Kdf::Pbkdf2 { iterations: 1 }.check(), Ok(()));
+        assert_eq!(Kdf::Argon2id(argon2::Params::default()).check(), Ok(()));
+        assert_eq!(Kdf::Scrypt(scrypt::Params::default()).check(), Ok(()));
+        assert_eq!(Kdf::Pbkdf2 { iterations: 0 }.check(), Err(AesError::InvalidKdfParams { parameter: "PBKDF2 iterations", value: 0, requirement: "at least 1" }));
+        assert_eq!(
+            Kdf::Argon2id(argon2::Params { memory: 8, iterations: 1, parallelism: 2 }).check(),
+            Err(AesError::InvalidKdfParams { parameter: "Argon2id memory in KiB", value: 8, requirement: "at least 8 per lane" })
+        );
+        assert_eq!(
+            Kdf::Argon2id(argon2::Params { memory: 8, iterations: 1, parallelism: 1 << 24 }).check(),
+            Err(AesError::InvalidKdfParams { parameter: "Argon2id parallelism", value: 1 << 24, requirement: "from 1 to 2^24 - 1" })
+        );
+        assert_eq!(Kdf::Scrypt(scrypt::Params { n: 24, r: 8, p: 1 }).check(), Err(AesError::InvalidKdfParams { parameter: "scrypt cost", value: 24, requirement: "a power of two larger than 1" }));
+        assert_eq!(Kdf::Scrypt(scrypt::Params { n: 16, r: 8, p: 0 }).check(), Err(AesError::InvalidKdfParams { parameter: "scrypt parallelism", value: 0, requirement: "at least 1" }));
+    }
+
+    #[test]
+    fn test_check_limits() {
+        let limits = KdfLimits::default();
+        assert_eq!(Kdf::Pbkdf2 { iterations: pbkdf2::DEFAULT_ITERATIONS }.check_limits(&limits), Ok(()));
+        assert_eq!(Kdf::Argon2id(argon2::Params::default()).check_limits(&limits), Ok(()));
+        assert_eq!(Kdf::Scrypt(scrypt::Params::default()).check_limits(&limits), Ok(()));
+        let over = |parameter, value, limit| Err(AesError::KdfLimit { parameter, value, limit });
+        assert_eq!(Kdf::Pbkdf2 { iterations: u32::MAX }.check_limits(&limits), over("PBKDF2 iterations", u64::from(u32::MAX), 10_000_000));
+        assert_eq!(Kdf::Argon2id(argon2::Params { memory: u32::MAX, iterations: 1, parallelism: 1 }).check_limits(&limits), over("Argon2id memory", u64::from(u32::MAX) * 1024, 1 << 30));
+        assert_eq!(Kdf::Argon2id(argon2::Params { memory: 64, iterations: u32::MAX, parallelism: 1 }).check_limits(&limits), over("Argon2id iterations", u64::from(u32::MAX), 64));
+        assert_eq!(Kdf::Scrypt(scrypt::Params { n: 1 << 31, r: 8, p: 1 }).check_limits(&limits), over("scrypt memory", 128 * 8 * ((1 << 31) + 1), 1 << 30));
+        assert_eq!(Kdf::Scrypt(scrypt::Params { n: 16, r: 1, p: 65 }).check_limits(&limits), over("scrypt parallelism", 65, 64));
+        assert_eq!(Kdf::Pbkdf2 { iterations: 0 }.check_limits(&limits), Err(AesError::InvalidKdfParams { parameter: "PBKDF2 iterations", value: 0, requirement: "at least 1" }));
+        let raised = KdfLimits { max_iterations: u32::MAX, ..limits };
+        assert_eq!(Kdf::Pbkdf2 { iterations: u32::MAX }.check_limits(&raised), Ok(()));
+    }
+}
@@ -0,0 +1,269 @@
+//!
+//! The Rijndael key schedule as an iterator, so the roundkeys are computed when they are
+//! needed instead of all at once. Each word of the schedule depends on the word before it
+//! and the word one key length before it, so only the last key length of words is kept.
+//! The schedule can be reversed, which gives the roundkeys in the order of decryption
+//! without computing them all first.
+//! Description: https://en.wikipedia.org/wiki/AES_key_schedule
+//!
+
+#[cfg(not(feature = "constant-time"))]
+use crate::{AESBlock, DecryptedState};
+use crate::{Key, KeySize};
+use zeroize::Zeroize;
+
+///
+/// The round constants, the powers of x in GF(2^8).
+///
+const R_CON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+///
+/// KeySchedule struct that yields the roundkeys of a key, from the front in the order of
+/// encryption and from the back in the order of decryption.
+///
+#[derive(Clone)]
+pub struct KeySchedule {
+    key_size: KeySize,
+    // The words of the key, kept until the schedule is first reversed.
+    key: [[u8; 4]; 8],
+    // The last key length of words computed from the front. Word idx is in slot idx % key words.
+    front: [[u8; 4]; 8],
+    // Number of words computed from the front.
+    front_words: usize,
+    // The key length of words starting at back_words, computed from the back.
+    back: Option<[[u8; 4]; 8]>,
+    // Index of the first word computed from the back.
+    back_words: usize,
+    // The next roundkey from the front.
+    start: usize,
+    // One past the next roundkey from the back.
+    end: usize,
+}
+
+impl KeySchedule {
+    ///
+    /// Creates the key schedule of the key. Nothing is computed until a roundkey is taken.
+    ///
+    /// key: The AES key.
+    ///
+    /// result: A KeySchedule with 11, 13 or 15 roundkeys.
+    ///
+    pub fn new(key: &Key) -> KeySchedule {
+        let key_size = key.key_size();
+        let mut words = [[0u8; 4]; 8];
+        for (word, bytes) in words.iter_mut().zip(key.as_bytes().chunks_exact(4)) {
+            word.copy_from_slice(bytes);
+        }
+        KeySchedule { key_size, key: words, front: words, front_words: key_size.length() / 4, back: None, back_words: 0, start: 0, end: key_size.rounds() + 1 }
+    }
+
+    pub fn key_size(&self) -> KeySize {
+        self.key_size
+    }
+
+    ///
+    /// Number of words in the key, 4, 6 or 8.
+    ///
+    fn key_words(&self) -> usize {
+        self.key_size.length() / 4
+    }
+
+    ///
+    /// Number of words in the whole schedule.
+    ///
+    fn total_words(&self) -> usize {
+        4 * (self.key_size.rounds() + 1)
+    }
+
+    ///
+    /// The function applied to the previous word before it is xored into word idx.
+    ///
+    /// word: The word before word idx.
+    /// idx: The index of the word being computed.
+    /// key_words: Number of words in the key.
+    ///
+    /// result: The word rotated and substituted with the round constant on the first word of
+    ///         each key length, substituted in the middle of a 32 byte key, or unchanged.
+    ///
+    fn transform(word: [u8; 4], idx: usize, key_words: usize) -> [u8; 4] {
+        if idx.is_multiple_of(key_words) {
+            let mut result = sub_word([word[1], word[2], word[3], word[0]]);
+            result[0] ^= R_CON[idx / key_words - 1];
+            result
+        } else if key_words > 6 && idx % key_words == 4 {
+            // Longer keys get an extra substitution in the middle of each key.
+            sub_word(word)
+        } else {
+            word
+        }
+    }
+
+    ///
+    /// Computes word idx from the front. It replaces the word one key length before it.
+    ///
+    fn step_forward(words: &mut [[u8; 4]; 8], idx: usize, key_words: usize) {
+        let temp = KeySchedule::transform(words[(idx - 1) % key_words], idx, key_words);
+        for (value, temp) in words[idx % key_words].iter_mut().zip(temp) {
+            *value ^= temp;
+        }
+    }
+
+    ///
+    /// Computes word idx from the back. It replaces the word one key length after it, since
+    /// word idx is that word xored with the same function of the word before it.
+    ///
+    fn step_back(words: &mut [[u8; 4]; 8], idx: usize, key_words: usize) {
+        let temp = KeySchedule::transform(words[(idx + key_words - 1) % key_words], idx + key_words, key_words);
+        for (value, temp) in words[idx % key_words].iter_mut().zip(temp) {
+            *value ^= temp;
+        }
+    }
+
+    ///
+    /// Computes the last key length of words from the key the first time the schedule is reversed.
+    ///
+    fn reverse(&mut self) {
+        if self.back.is_none() {
+            let key_words = self.key_words();
+            let mut words = self.key;
+            for idx in key_words..self.total_words() {
+                KeySchedule::step_forward(&mut words, idx, key_words);
+            }
+            self.back = Some(words);
+            self.back_words = self.total_words() - key_words;
+            self.key.zeroize();
+        }
+    }
+
+    ///
+    /// Copies the roundkey from the words, which must hold its four words.
+    ///
+    fn roundkey(words: &[[u8; 4]; 8], round: usize, key_words: usize) -> [u8; 16] {
+        let mut result = [0u8; 16];
+        for (idx, chunk) in result.chunks_exact_mut(4).enumerate() {
+            chunk.copy_from_slice(&words[(4 * round + idx) % key_words]);
+        }
+        result
+    }
+}
+
+impl Iterator for KeySchedule {
+    type Item = [u8; 16];
+
+    fn next(&mut self) -> Option<[u8; 16]> {
+        if self.start == self.end {
+            return None;
+        }
+        let key_words = self.key_words();
+        let round = self.start;
+        while self.front_words < 4 * (round + 1) {
+            KeySchedule::step_forward(&mut self.front, self.front_words, key_words);
+            self.front_words += 1;
+        }
+        self.start += 1;
+        Some(KeySchedule::roundkey(&self.front, round, key_words))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.end - self.start, Some(self.end - self.start))
+    }
+}
+
+impl DoubleEndedIterator for KeySchedule {
+    fn next_back(&mut self) -> Option<[u8; 16]> {
+        if self.start == self.end {
+            return None;
+        }
+        self.reverse();
+        let key_words = self.key_words();
+        self.end -= 1;
+        let round = self.end;
+        let words = self.back.as_mut().expect("Reversed");
+        while self.back_words > 4 * round {
+            self.back_words -= 1;
+            KeySchedule::step_back(words, self.back_words, key_words);
+        }
+        Some(KeySchedule::roundkey(words, round, key_words))
+    }
+}
+
+impl ExactSizeIterator for KeySchedule {}
+
+///
+/// The words reveal the key, so they are overwritten before the memory is freed.
+///
+impl Drop for KeySchedule {
+    fn drop(&mut self) {
+        self.key.zeroize();
+        self.front.zeroize();
+        if let Some(back) = self.back.as_mut() {
+            back.zeroize();
+        }
+    }
+}
+
+///
+/// Substitutes each byte in the word with the corresponding byte in the s_box.
+///
+#[cfg(not(feature = "constant-time"))]
+pub(crate) fn sub_word(word: [u8; 4]) -> [u8; 4] {
+    word.map(|value| AESBlock::<DecryptedState>::S_BOX[value as usize])
+}
+
+///
+/// Substitutes the bytes of the word in constant time, so the key is not revealed by the timing.
+///
+#[cfg(feature = "constant-time")]
+pub(crate) fn sub_word(word: [u8; 4]) -> [u8; 4] {
+    crate::bitslice::sub_word(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    ///
+    /// The last roundkeys of the key expansion examples in appendix A of FIPS 197.
+    ///
+    #[test]
+    fn test_fips197() {
+        for (key, last) in [
+            ("2b7e151628aed2a6abf7158809cf4f3c", "d014f9a8c9ee2589e13f0cc8b6630ca6"),
+            ("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b", "e98ba06f448c773c8ecc720401002202"),
+            ("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4", "fe4890d1e6188d0b046df344706c631e"),
+        ] {
+            let mut schedule = KeySchedule::new(&Key::from_slice(&hex(key)).unwrap());
+            assert_eq!(schedule.next().unwrap().to_vec(), hex(key)[..16]);
+            assert_eq!(schedule.next_back().unwrap().to_vec(), hex(last));
+            // The same roundkey computed from the front.
+            let key = Key::from_slice(&hex(key)).unwrap();
+            assert_eq!(KeySchedule::new(&key).nth(key.key_size().rounds()).unwrap().to_vec(), hex(last));
+        }
+    }
+
+    ///
+    /// The schedule reversed is the schedule in reverse order, also when both ends are used.
+    ///
+    #[test]
+    fn test_reverse() {
+        for key in [Key::Aes128([1; 16]), Key::Aes192([2; 24]), Key::Aes256([3; 32])] {
+            let forward: Vec<[u8; 16]> = KeySchedule::new(&key).collect();
+            assert_eq!(forward.len(), key.key_size().rounds() + 1);
+            assert_eq!(KeySchedule::new(&key).len(), forward.len());
+            let backward: Vec<[u8; 16]> = KeySchedule::new(&key).rev().collect();
+            assert_eq!(backward.into_iter().rev().collect::<Vec<_>>(), forward);
+            let mut schedule = KeySchedule::new(&key);
+            let (mut first, mut last) = (Vec::new(), Vec::new());
+            while let Some(roundkey) = schedule.next() {
+                first.push(roundkey);
+                last.extend(schedule.next_back());
+            }
+            assert_eq!([first, last.into_iter().rev().collect()].concat(), forward);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//!
+//! AES key wrap, for protecting keys with a key encryption key. The key is split into
+//! 8 byte blocks that are encrypted six times each together with an integrity check
+//! value, so any change to the wrapped key is detected when it is unwrapped. The padded
+//! variant wraps keys of any length by storing the length in the check value.
+//! Description: https://en.wikipedia.org/wiki/Key_Wrap
+//! Specification: RFC 3394 and RFC 5649.
+//!
+
+use crate::{AesError, RoundKeys, constant_time_eq, decrypt_block, encrypt_block};
+use alloc::vec::Vec;
+use zeroize::Zeroize;
+
+///
+/// The default initial value of RFC 3394.
+///
+const IV: [u8; 8] = [0xa6; 8];
+
+///
+/// The first half of the alternative initial value of RFC 5649. The second half is the
+/// length of the key.
+///
+const AIV: [u8; 4] = [0xa6, 0x59, 0x59, 0xa6];
+
+///
+/// Wraps a key with the key encryption key.
+///
+/// key: The key to wrap. A multiple of 8 bytes and at least 16 bytes.
+/// roundkeys: The roundkeys of the key encryption key.
+///
+/// result: The wrapped key, 8 bytes longer than the key, or an error if the length is not supported.
+///
+pub fn wrap(key: &[u8], roundkeys: &RoundKeys) -> Result<Vec<u8>, AesError> {
+    if key.len() < 16 || !key.len().is_multiple_of(8) {
+        return Err(AesError::InvalidDataLength { length: key.len() });
+    }
+    Ok(wrap_blocks(IV, key, roundkeys))
+}
+
+///
+/// Unwraps a key and checks that it has not been changed.
+///
+/// data: The wrapped key. A multiple of 8 bytes and at least 24 bytes.
+/// roundkeys: The roundkeys of the key encryption key.
+///
+/// result: The key, or an error if the length is not supported or the integrity check fails.
+///
+pub fn unwrap(data: &[u8], roundkeys: &RoundKeys) -> Result<Vec<u8>, AesError> {
+    if data.len() < 24 || !data.len().is_multiple_of(8) {
+        return Err(AesError::InvalidDataLength { length: data.len() });
+    }
+    let (check, mut key) = unwrap_blocks(data, roundkeys);
+    if !constant_time_eq(&check, &IV) {
+        key.zeroize();
+        return Err(AesError::IntegrityCheck);
+    }
+    Ok(key)
+}
+
+///
+/// Wraps a key of any length with the key encryption key. The key is padded with zeros
+/// to a multiple of 8 bytes, and a key of at most 8 bytes is encrypted as a single block.
+///
+/// key: The key to wrap. At least 1 byte.
+/// roundkeys: The roundkeys of the key encryption key.
+///
+/// result: The wrapped key, or an error if the key is empty or longer than 2^32 - 1 bytes.
+///
+pub fn wrap_with_padding(key: &[u8], roundkeys: &RoundKeys) -> Result<Vec<u8>, AesError> {
+    let length = u32::try_from(key.len()).ok().filter(|length| *length > 0).ok_or(AesError::InvalidDataLength { length: key.len() })?;
+    let mut check = [0u8; 8];
+    check[..4].copy_from_slice(&AIV);
+    check[4..].copy_from_slice(&length.to_be_bytes());
+    let mut padded = key.to_vec();
+    padded.resize(key.len().div_ceil(8) * 8, 0);
+    let result = if padded.len() == 8 {
+        let mut block = [0u8; 16];
+        block[..8].copy_from_slice(&check);
+        block[8..].copy_from_slice(&padded);
+        let result = encrypt_block(block, roundkeys).to_vec();
+        block.zeroize();
+        result
+    } else {
+        wrap_blocks(check, &padded, roundkeys)
+    };
+    padded.zeroize();
+    Ok(result)
+}
+
+///
+/// Unwraps a key wrapped with padding and checks the length, the padding and that it has
+/// not been changed.
+///
+/// data: The wrapped key. A multiple of 8 bytes and at least 16 bytes.
+/// roundkeys: The roundkeys of the key encryption key.
+///
+/// result: The key without the padding, or an error if the length is not supported or the
+///         integrity check fails.
+///
+pub fn unwrap_with_padding(data: &[u8], roundkeys: &RoundKeys) -> Result<Vec<u8>, AesError> {
+    if data.len() < 16 || !data.len().is_multiple_of(8) {
+        return Err(AesError::InvalidDataLength { length: data.len() });
+    }
+    let (check, mut key) = if data.len() == 16 {
+        let mut block = decrypt_block(data.try_into().unwrap(), roundkeys);
+        let result = (block[..8].try_into().unwrap(), block[8..].to_vec());
+        block.zeroize();
+        result
+    } else {
+        unwrap_blocks(data, roundkeys)
+    };
+    let length = u32::from_be_bytes(check[4..].try_into().unwrap()) as usize;
+    // The length must leave between 0 and 7 bytes of padding, and the padding must be zeros.
+    let valid_length = length <= key.len() && key.len() - length < 8;
+    let valid_padding = valid_length && key[length..].iter().all(|value| *value == 0);
+    if !constant_time_eq(&check[..4], &AIV) || !valid_padding {
+        key.zeroize();
+        return Err(AesError::IntegrityCheck);
+    }
+    key.truncate(length);
+    Ok(key)
+}
+
+///
+/// The wrapping function W of RFC 3394. Each 8 byte block is encrypted six times together
+/// with the check value, which is xored with a counter after each step.
+///
+/// check: The initial value.
+/// key: The key, a multiple of 8 bytes and at least 16 bytes.
+/// roundkeys: The roundkeys of the key encryption key.
+///
+/// result: The check value followed by the encrypted blocks.
+///
+fn wrap_blocks(check: [u8; 8], key: &[u8], roundkeys: &RoundKeys) -> Vec<u8> {
+    let mut result = [&check[..], key].concat();
+    let count = key.len() / 8;
+    let mut block = [0u8; 16];
+    for step in 0..6 {
+        for idx in 1..=count {
+            block[..8].copy_from_slice(&result[..8]);
+            block[8..].copy_from_slice(&result[8 * idx..8 * idx + 8]);
+            block = encrypt_block(block, roundkeys);
+            let counter = ((count * step + idx) as u64).to_be_bytes();
+            for (value, (encrypted, counter)) in result[..8].iter_mut().zip(block[..8].iter().zip(counter)) {
+                *value = encrypted ^ counter;
+            }
+            result[8 * idx..8 * idx + 8].copy_from_slice(&block[8..]);
+        }
+    }
+    block.zeroize();
+    result
+}
+
+///
+/// The unwrapping function W^-1 of RFC 3394, the steps of the wrapping in reverse.
+///
+/// data: The wrapped key, a multiple of 8 bytes and at least 24 bytes.
+/// roundkeys: The roundkeys of the key encryption key.
+///
+/// result: The check value to compare with the initial value, and the key.
+///
+fn unwrap_blocks(data: &[u8], roundkeys: &RoundKeys) -> ([u8; 8], Vec<u8>) {
+    let mut check: [u8; 8] = data[..8].try_into().unwrap();
+    let mut key = data[8..].to_vec();
+    let count = key.len() / 8;
+    let mut block = [0u8; 16];
+    for step in (0..6).rev() {
+        for idx in (1..=count).rev() {
+            let counter = ((count * step + idx) as u64).to_be_bytes();
+            for (value, (check, counter)) in block[..8].iter_mut().zip(check.iter().zip(counter)) {
+                *value = check ^ counter;
+            }
+            block[8..].copy_from_slice(&key[8 * (idx - 1)..8 * idx]);
+            block = decrypt_block(block, roundkeys);
+            check.copy_from_slice(&block[..8]);
+            key[8 * (idx - 1)..8 * idx].copy_from_slice(&block[8..]);
+        }
+    }
+    block.zeroize();
+    (check, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Key;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    ///
+    /// Examples 4.1 and 4.6 from RFC 3394.
+    ///
+    #[test]
+    fn test_rfc3394() {
+        let roundkeys = Key::from_slice(&hex("000102030405060708090a0b0c0d0e0f")).unwrap().roundkeys();
+        let key = hex("00112233445566778899aabbccddeeff");
+        let wrapped = wrap(&key, &roundkeys).unwrap();
+        assert_eq!(wrapped, hex("1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5"));
+        assert_eq!(unwrap(&wrapped, &roundkeys).unwrap(), key);
+
+        let roundkeys = Key::from_slice(&hex("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f")).unwrap().roundkeys();
+        let key = hex("00112233445566778899aabbccddeeff000102030405060708090a0b0c0d0e0f");
+        let wrapped = wrap(&key, &roundkeys).unwrap();
+        assert_eq!(wrapped, hex("28c9f404c4b810f4cbccb35cfb87f8263f5786e2d80ed326cbc7f0e71a99f43bfb988b9b7a02dd21"));
+        assert_eq!(unwrap(&wrapped, &roundkeys).unwrap(), key);
+    }
+
+    ///
+    /// The examples from section 6 of RFC 5649.
+    ///
+    #[test]
+    fn test_rfc5649() {
+        let roundkeys = Key::from_slice(&hex("5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8")).unwrap().roundkeys();
+        let key = hex("c37b7e6492584340bed12207808941155068f738");
+        let wrapped = wrap_with_padding(&key, &roundkeys).unwrap();
+        assert_eq!(wrapped, hex("138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a"));
+        assert_eq!(unwrap_with_padding(&wrapped, &roundkeys).unwrap(), key);
+
+        let key = hex("466f7250617369");
+        let wrapped = wrap_with_padding(&key, &roundkeys).unwrap();
+        assert_eq!(wrapped, hex("afbeb0f07dfbf5419200f2ccb50bb24f"));
+        assert_eq!(unwrap_with_padding(&wrapped, &roundkeys).unwrap(), key);
+    }
+
+    #[test]
+    fn test_integrity_check() {
+        let roundkeys = Key::from_slice(&[7; 16]).unwrap().roundkeys();
+        let mut wrapped = wrap(&[1; 32], &roundkeys).unwrap();
+        wrapped[20] ^= 1;
+        assert_eq!(unwrap(&wrapped, &roundkeys), Err(AesError::IntegrityCheck));
+        // A key wrapped without padding does not have the check value of the padded variant.
+        let wrapped = wrap(&[1; 16], &roundkeys).unwrap();
+        assert_eq!(unwrap_with_padding(&wrapped, &roundkeys), Err(AesError::IntegrityCheck));
+        let mut wrapped = wrap_with_padding(&[1; 5], &roundkeys).unwrap();
+        wrapped[3] ^= 1;
+        assert_eq!(unwrap_with_padding(&wrapped, &roundkeys), Err(AesError::IntegrityCheck));
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        let roundkeys = Key::from_slice(&[7; 16]).unwrap().roundkeys();
+        assert_eq!(wrap(&[1; 8], &roundkeys), Err(AesError::InvalidDataLength { length: 8 }));
+        assert_eq!(wrap(&[1; 20], &roundkeys), Err(AesError::InvalidDataLength { length: 20 }));
+        assert_eq!(unwrap(&[1; 16], &roundkeys), Err(AesError::InvalidDataLength { length: 16 }));
+        assert_eq!(wrap_with_padding(&[], &roundkeys), Err(AesError::InvalidDataLength { length: 0 }));
+        assert_eq!(unwrap_with_padding(&[1; 20], &roundkeys), Err(AesError::InvalidDataLength { length: 20 }));
+    }
+}
@@ -1,4 +1,211 @@
-use std::vec::Vec;
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod archive;
+pub mod argon2;
+#[cfg(feature = "constant-time")]
+mod bitslice;
+pub mod blake2b;
+pub mod cbc;
+pub mod ccm;
+pub mod cfb;
+pub mod cipher;
+pub mod cmac;
+pub mod ctr;
+pub mod eax;
+pub mod encoding;
+pub mod etm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod format;
+pub mod gcm;
+pub mod gcm_siv;
+pub mod gmac;
+pub mod hash;
+pub mod hkdf;
+pub mod hmac;
+pub mod kdf;
+pub mod key_schedule;
+pub mod keywrap;
+pub mod mode;
+pub mod ofb;
+pub mod padding;
+pub mod pbkdf2;
+#[cfg(feature = "std")]
+pub mod pipeline;
+pub mod rand;
+pub mod rijndael;
+pub mod scrypt;
+pub mod sha256;
+#[cfg(feature = "std")]
+pub mod stream;
+#[cfg(all(feature = "fast-tables", not(feature = "constant-time")))]
+mod tables;
+pub mod test_vectors;
+#[cfg(feature = "std")]
+pub mod throughput;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use zeroize::{Zeroize, Zeroizing};
+
+pub use cipher::{Cipher, Mode};
+use ctr::Counter;
+pub use key_schedule::KeySchedule;
+use mode::StreamMode;
+use padding::Padding;
+#[cfg(feature = "std")]
+pub use stream::{AesReader, AesWriter};
+use test_vectors::VectorMode;
+
+///
+/// AesError enum to represent errors when encrypting or decrypting.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AesError {
+    /// The decrypted data does not end with valid padding.
+    Padding,
+    /// The authentication tag does not match the data.
+    Authentication,
+    /// An unwrapped key does not have the expected integrity check value.
+    IntegrityCheck,
+    /// A tag length the mode does not support.
+    InvalidTagLength { length: usize },
+    /// A nonce length the mode does not support, or a nonce with another length than configured.
+    InvalidNonceLength { length: usize },
+    /// The data is too long for the length field of the mode.
+    MessageTooLong { length: usize },
+    /// A key that is not 16, 24 or 32 bytes, or a key size the mode does not support.
+    InvalidKeyLength { length: usize },
+    /// Encrypted data that is not a whole number of blocks.
+    InvalidDataLength { length: usize },
+    /// Roundkeys that are not 11, 13 or 15 keys of 16 bytes.
+    InvalidRoundKeys { count: usize },
+    /// Data that does not start with the magic bytes of the container, a header that is cut short,
+    /// or a header with more recipients than the format can hold.
+    InvalidContainer,
+    /// A container with a version this library can not read.
+    UnsupportedVersion { version: u8 },
+    /// Removing the only recipient of a container, which would leave no key to open it.
+    LastRecipient,
+    /// A key derivation function id that is not known.
+    UnknownKdf { id: u8 },
+    /// A mode id that is not known.
+    UnknownMode { id: u8 },
+    /// A mode that needs the whole message at once, where the data is processed in chunks.
+    UnsupportedMode,
+    /// A key derivation parameter outside the range of the function, with what it must be.
+    InvalidKdfParams { parameter: &'static str, value: u64, requirement: &'static str },
+    /// A key derivation parameter that needs more memory, iterations or passes than its limit.
+    KdfLimit { parameter: &'static str, value: u64, limit: u64 },
+    /// The operating system could not provide random bytes.
+    Random,
+    /// A known answer vector that does not give the expected result.
+    SelfTest { name: &'static str },
+    /// Text that is not valid hex or Base64, with the position of the first invalid character.
+    InvalidEncoding { position: usize },
+}
+
+impl fmt::Display for AesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AesError::Padding => write!(f, "Invalid padding"),
+            AesError::Authentication => write!(f, "Authentication failed"),
+            AesError::IntegrityCheck => write!(f, "Integrity check failed"),
+            AesError::InvalidTagLength { length } => write!(f, "Invalid tag length: {length}"),
+            AesError::InvalidNonceLength { length } => write!(f, "Invalid nonce length: {length}"),
+            AesError::MessageTooLong { length } => write!(f, "Message too long: {length} bytes"),
+            AesError::InvalidKeyLength { length } => write!(f, "Invalid key length: {length}"),
+            AesError::InvalidDataLength { length } => write!(f, "Invalid data length: {length}"),
+            AesError::InvalidRoundKeys { count } => write!(f, "Invalid roundkeys: {count} keys"),
+            AesError::InvalidContainer => write!(f, "Invalid container"),
+            AesError::UnsupportedVersion { version } => write!(f, "Unsupported version: {version}"),
+            AesError::LastRecipient => write!(f, "The last recipient can not be removed"),
+            AesError::UnknownKdf { id } => write!(f, "Unknown key derivation function: {id}"),
+            AesError::UnknownMode { id } => write!(f, "Unknown mode: {id}"),
+            AesError::UnsupportedMode => write!(f, "Mode can not be processed in chunks"),
+            AesError::InvalidKdfParams { parameter, value, requirement } => write!(f, "Invalid key derivation parameter: {parameter} {value} must be {requirement}"),
+            AesError::KdfLimit { parameter, value, limit } => write!(f, "Key derivation parameter over the limit: {parameter} {value} is more than {limit}"),
+            AesError::Random => write!(f, "Random source failed"),
+            AesError::SelfTest { name } => write!(f, "Self test failed: {name}"),
+            AesError::InvalidEncoding { position } => write!(f, "Invalid encoding at position {position}"),
+        }
+    }
+}
+
+impl core::error::Error for AesError {}
+
+///
+/// Compares two byte slices in time that only depends on the length, so a tag can be
+/// checked without revealing how many of the first bytes match. Tags and codes received
+/// from outside should be compared with this instead of ==, which stops at the first
+/// difference. The modes and codes in this library use it when verifying.
+///
+/// a: A vector of bytes.
+/// b: A vector of bytes.
+///
+/// result: True if the slices are equal.
+///
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    // The difference is hidden from the optimizer, so the loop is not turned into an early exit.
+    a.len() == b.len() && core::hint::black_box(a.iter().zip(b).fold(0, |difference, (x, y)| difference | (x ^ y))) == 0
+}
+
+///
+/// Encrypts a single block, as used by the modes built on the block cipher.
+///
+/// block: The 16 bytes to encrypt.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+///
+/// result: The 16 bytes encrypted.
+///
+pub(crate) fn encrypt_block(block: [u8; 16], roundkeys: &RoundKeys) -> [u8; 16] {
+    AESBlock::<DecryptedState>::new(block).encrypt(roundkeys).grid
+}
+
+///
+/// Decrypts a single block, as used by the modes built on the block cipher.
+///
+/// block: The 16 bytes to decrypt.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+///
+/// result: The 16 bytes decrypted.
+///
+pub(crate) fn decrypt_block(block: [u8; 16], roundkeys: &RoundKeys) -> [u8; 16] {
+    AESBlock::<EncryptedState>::new(block).decrypt(roundkeys).grid
+}
+
+///
+/// Length of the chunks the independent blocks of ECB and CTR are split into. With the
+/// parallel feature each chunk is a task for rayon.
+///
+pub(crate) const CHUNK_LENGTH: usize = 16 * 1024;
+
+///
+/// Number of bytes processed between reports of the progress, a whole number of chunks.
+///
+const PROGRESS_LENGTH: usize = 16 * CHUNK_LENGTH;
+
+///
+/// Runs the function on each chunk of the data, in parallel with the parallel feature.
+///
+/// data: The data, split into chunks of CHUNK_LENGTH bytes. The last chunk may be shorter.
+/// function: Called with the index of the chunk and the chunk.
+///
+pub(crate) fn for_each_chunk<F: Fn(usize, &mut [u8]) + Sync + Send>(data: &mut [u8], function: F) {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        data.par_chunks_mut(CHUNK_LENGTH).enumerate().for_each(|(idx, chunk)| function(idx, chunk));
+    }
+    #[cfg(not(feature = "parallel"))]
+    data.chunks_mut(CHUNK_LENGTH).enumerate().for_each(|(idx, chunk)| function(idx, chunk));
+}
 
 ///
 /// DecryptedState is a marker struct used to indicate that the data is decrypted.
@@ -9,6 +216,304 @@ pub struct DecryptedState;
 ///
 pub struct EncryptedState;
 
+///
+/// KeySize enum for the supported key lengths. The key length decides the
+/// number of rounds and the number of roundkeys.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeySize {
+    // 16 byte key, 10 rounds.
+    Aes128,
+    // 24 byte key, 12 rounds.
+    Aes192,
+    // 32 byte key, 14 rounds.
+    Aes256,
+}
+
+impl KeySize {
+    ///
+    /// Finds the key size for a key length.
+    ///
+    /// length: The length of the key in bytes.
+    ///
+    /// result: The key size or None if the length is not supported.
+    ///
+    pub fn from_length(length: usize) -> Option<KeySize> {
+        match length {
+            16 => Some(KeySize::Aes128),
+            24 => Some(KeySize::Aes192),
+            32 => Some(KeySize::Aes256),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Returns the length of the key in bytes.
+    ///
+    pub fn length(&self) -> usize {
+        match self {
+            KeySize::Aes128 => 16,
+            KeySize::Aes192 => 24,
+            KeySize::Aes256 => 32,
+        }
+    }
+
+    ///
+    /// Returns the number of rounds. One more roundkey than rounds is needed.
+    ///
+    pub fn rounds(&self) -> usize {
+        match self {
+            KeySize::Aes128 => 10,
+            KeySize::Aes192 => 12,
+            KeySize::Aes256 => 14,
+        }
+    }
+}
+
+///
+/// Checks the cipher and the block cipher modes against the known answer vectors in
+/// test_vectors, so the implementation can be verified on the platform it runs on.
+///
+/// result: Ok, or an error with the name of the first vector that does not match.
+///
+pub fn self_test() -> Result<(), AesError> {
+    for vector in &test_vectors::FIPS_197 {
+        let roundkeys = Key::from_slice(vector.key)?.roundkeys();
+        let encrypted = AESBlock::<DecryptedState>::new(vector.plaintext).encrypt(&roundkeys);
+        if encrypted.grid != vector.ciphertext || encrypted.decrypt(&roundkeys).grid != vector.plaintext {
+            return Err(AesError::SelfTest { name: vector.name });
+        }
+    }
+    for vector in &test_vectors::SP800_38A {
+        let roundkeys = Key::from_slice(vector.key)?.roundkeys();
+        let (encrypted, decrypted) = match vector.mode {
+            VectorMode::Ecb => {
+                let mut encrypted = vector.plaintext.to_vec();
+                AESData::<DecryptedState>::encrypt_in_place(&mut encrypted, &roundkeys)?;
+                let mut decrypted = vector.ciphertext.to_vec();
+                AESData::<EncryptedState>::decrypt_in_place(&mut decrypted, &roundkeys)?;
+                (encrypted, decrypted)
+            }
+            VectorMode::Cbc => (cbc::encrypt(vector.plaintext, &roundkeys, vector.iv), cbc::decrypt(vector.ciphertext, &roundkeys, vector.iv)),
+            VectorMode::Cfb => (cfb::encrypt(vector.plaintext, &roundkeys, vector.iv), cfb::decrypt(vector.ciphertext, &roundkeys, vector.iv)),
+            VectorMode::Ofb => (ofb::apply_keystream(vector.plaintext, &roundkeys, vector.iv), ofb::apply_keystream(vector.ciphertext, &roundkeys, vector.iv)),
+            VectorMode::Ctr => (ctr::apply_keystream(vector.plaintext, &roundkeys, Counter::new(vector.iv)), ctr::apply_keystream(vector.ciphertext, &roundkeys, Counter::new(vector.iv))),
+        };
+        if encrypted != vector.ciphertext || decrypted != vector.plaintext {
+            return Err(AesError::SelfTest { name: vector.name });
+        }
+    }
+    Ok(())
+}
+
+///
+/// RoundKeys struct with the roundkeys of a key. It can only be created with 11, 13
+/// or 15 roundkeys of 16 bytes each, so the block functions can rely on the layout.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundKeys {
+    keys: Vec<Vec<u8>>,
+    // The roundkeys of the equivalent inverse cipher, derived once from the keys.
+    decryption: DecryptionRoundKeys,
+}
+
+impl RoundKeys {
+    ///
+    /// Creates the roundkeys after checking the count and the lengths.
+    ///
+    /// keys: A vector of 11, 13 or 15 roundkeys. Each roundkey is a vector of 16 bytes.
+    ///
+    /// result: A RoundKeys struct or an error if the count or a length is wrong.
+    ///
+    pub fn new(keys: Vec<Vec<u8>>) -> Result<RoundKeys, AesError> {
+        if ![11, 13, 15].contains(&keys.len()) || keys.iter().any(|key| key.len() != 16) {
+            return Err(AesError::InvalidRoundKeys { count: keys.len() });
+        }
+        Ok(RoundKeys::from_keys(keys))
+    }
+
+    ///
+    /// Creates the roundkeys from keys that are known to be valid.
+    ///
+    fn from_keys(keys: Vec<Vec<u8>>) -> RoundKeys {
+        RoundKeys { decryption: DecryptionRoundKeys::new(&keys), keys }
+    }
+
+    ///
+    /// Returns the roundkeys of the equivalent inverse cipher, in the order they are used.
+    ///
+    pub fn decryption(&self) -> &DecryptionRoundKeys {
+        &self.decryption
+    }
+
+    ///
+    /// Returns the key size the roundkeys were generated from.
+    ///
+    pub fn key_size(&self) -> KeySize {
+        match self.keys.len() {
+            11 => KeySize::Aes128,
+            13 => KeySize::Aes192,
+            _ => KeySize::Aes256,
+        }
+    }
+}
+
+///
+/// Key enum for the three AES key sizes. The length is part of the type, so a key can
+/// not be truncated or padded on the way to the key schedule.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Key {
+    // 16 byte key, 10 rounds.
+    Aes128([u8; 16]),
+    // 24 byte key, 12 rounds.
+    Aes192([u8; 24]),
+    // 32 byte key, 14 rounds.
+    Aes256([u8; 32]),
+}
+
+impl Key {
+    ///
+    /// Creates a key from bytes.
+    ///
+    /// key: A vector of 16, 24 or 32 bytes.
+    ///
+    /// result: A Key or an error if the length is not supported.
+    ///
+    pub fn from_slice(key: &[u8]) -> Result<Key, AesError> {
+        match key.len() {
+            16 => Ok(Key::Aes128(key.try_into().unwrap())),
+            24 => Ok(Key::Aes192(key.try_into().unwrap())),
+            32 => Ok(Key::Aes256(key.try_into().unwrap())),
+            length => Err(AesError::InvalidKeyLength { length }),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Key::Aes128(key) => key,
+            Key::Aes192(key) => key,
+            Key::Aes256(key) => key,
+        }
+    }
+
+    pub fn key_size(&self) -> KeySize {
+        match self {
+            Key::Aes128(_) => KeySize::Aes128,
+            Key::Aes192(_) => KeySize::Aes192,
+            Key::Aes256(_) => KeySize::Aes256,
+        }
+    }
+
+    ///
+    /// Generates the roundkeys with the Rijndael key schedule.
+    ///
+    /// result: The 11, 13 or 15 roundkeys.
+    ///
+    pub fn roundkeys(&self) -> RoundKeys {
+        RoundKeys::from_keys(KeySchedule::new(self).map(|roundkey| roundkey.to_vec()).collect())
+    }
+
+    ///
+    /// Computes the key check value, the first three bytes of an all-zero block encrypted
+    /// with the key. It identifies the key, so a loaded key can be checked against the value
+    /// recorded when it was created, without showing the key itself.
+    ///
+    /// result: The three byte key check value.
+    ///
+    pub fn check_value(&self) -> [u8; 3] {
+        let block = encrypt_block([0; 16], &self.roundkeys());
+        [block[0], block[1], block[2]]
+    }
+}
+
+impl core::ops::Deref for RoundKeys {
+    type Target = [Vec<u8>];
+
+    fn deref(&self) -> &[Vec<u8>] {
+        &self.keys
+    }
+}
+
+///
+/// The roundkeys reveal the key, so they are overwritten before the memory is freed.
+///
+impl Drop for RoundKeys {
+    fn drop(&mut self) {
+        self.keys.iter_mut().for_each(|key| key.zeroize());
+    }
+}
+
+///
+/// DecryptionRoundKeys struct with the roundkeys of the equivalent inverse cipher. They are
+/// derived once from the roundkeys, so the inverse column mixing of the roundkeys is not
+/// repeated for every block that is decrypted.
+/// Specification: FIPS 197, section 5.3.5.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecryptionRoundKeys {
+    keys: Vec<[u8; 16]>,
+}
+
+impl DecryptionRoundKeys {
+    ///
+    /// Creates the roundkeys for the equivalent inverse cipher.
+    ///
+    /// keys: The roundkeys of the key.
+    ///
+    /// result: The last roundkey, the inverse column mixing of the roundkeys in between from the
+    ///         end, and the first roundkey.
+    ///
+    fn new(keys: &[Vec<u8>]) -> DecryptionRoundKeys {
+        let block = AESBlock::<EncryptedState>::new([0; 16]);
+        let rounds = keys.len() - 1;
+        let keys = (0..=rounds)
+            .map(|idx| {
+                let mut key: [u8; 16] = keys[rounds - idx][..].try_into().unwrap();
+                if idx != 0 && idx != rounds {
+                    for column in key.as_chunks_mut::<4>().0 {
+                        *column = block.mix_column(*column);
+                    }
+                }
+                key
+            })
+            .collect();
+        DecryptionRoundKeys { keys }
+    }
+}
+
+impl core::ops::Deref for DecryptionRoundKeys {
+    type Target = [[u8; 16]];
+
+    fn deref(&self) -> &[[u8; 16]] {
+        &self.keys
+    }
+}
+
+///
+/// The decryption roundkeys reveal the key the same way as the roundkeys.
+///
+impl Drop for DecryptionRoundKeys {
+    fn drop(&mut self) {
+        self.keys.zeroize();
+    }
+}
+
+///
+/// The key is overwritten before the memory is freed.
+///
+impl Drop for Key {
+    fn drop(&mut self) {
+        match self {
+            Key::Aes128(key) => key.zeroize(),
+            Key::Aes192(key) => key.zeroize(),
+            Key::Aes256(key) => key.zeroize(),
+        }
+    }
+}
+
 ///
 /// AESBlock is a struct that represents a single 16 byte block of data.
 /// It can be used to encrypt or decrypt the data based on the state the
@@ -17,22 +522,56 @@ pub struct EncryptedState;
 /// The struct is generic over the state. The state can be either DecryptedState
 /// or EncryptedState. This is to ensure that the data is not encrypted or decrypted twice.
 ///
-/// The struct contains a grid of 16 bytes. This grid is considered to be a 4x4 grid with
-/// row-major order. This means that the first 4 bytes are the first row, the next 4 bytes
-/// are the second row and so on.
-///  
+/// The struct contains 16 bytes in the order of the input. AES fills the state column by
+/// column, while the round functions work on a 4x4 grid with row-major order. This means
+/// that the first 4 bytes are the first row, the next 4 bytes are the second row and so on.
+/// The block and the roundkeys are therefore transposed before the rounds and the result
+/// is transposed back. The grid is a fixed array and the rounds change it in place, so
+/// no memory is allocated for a block.
+///
 pub struct AESBlock<State = DecryptedState> {
-    grid: Vec<u8>,
-    state: std::marker::PhantomData<State>,
+    grid: [u8; 16],
+    state: core::marker::PhantomData<State>,
 }
 
 ///
 /// AESData is a struct containg a vector of bytes. This struct can be used to encrypt or decrypt
 /// the included data.
 ///
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(bound = ""))]
 pub struct AESData<State = DecryptedState> {
     pub data: Vec<u8>,
-    state: std::marker::PhantomData<State>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    state: core::marker::PhantomData<State>,
+}
+
+///
+/// Generates the s-box. Each byte is replaced by its inverse in GF(2^8), zero by zero,
+/// followed by the affine transformation that xors the inverse with four rotations of
+/// itself and the constant 0x63.
+///
+const fn s_box() -> [u8; 256] {
+    let mut result = [0u8; 256];
+    let mut idx = 0;
+    while idx < 256 {
+        let inverse = gf256::inverse(idx as u8);
+        result[idx] = inverse ^ inverse.rotate_left(1) ^ inverse.rotate_left(2) ^ inverse.rotate_left(3) ^ inverse.rotate_left(4) ^ 0x63;
+        idx += 1;
+    }
+    result
+}
+
+///
+/// Generates the inverse s-box from the s-box, so the two always match.
+///
+const fn inverse_s_box(s_box: &[u8; 256]) -> [u8; 256] {
+    let mut result = [0u8; 256];
+    let mut idx = 0;
+    while idx < 256 {
+        result[s_box[idx] as usize] = idx as u8;
+        idx += 1;
+    }
+    result
 }
 
 ///
@@ -42,39 +581,68 @@ impl AESBlock<DecryptedState> {
     ///
     /// Substitutes each byte in the data with the corresponding byte in the s_box.
     ///
-    pub const S_BOX: [&'static u8; 256] = [
-        &0x63, &0x7c, &0x77, &0x7b, &0xf2, &0x6b, &0x6f, &0xc5, &0x30, &0x01, &0x67, &0x2b, &0xfe, &0xd7, &0xab, &0x76, &0xca, &0x82, &0xc9, &0x7d, &0xfa, &0x59, &0x47, &0xf0, &0xad, &0xd4, &0xa2,
-        &0xaf, &0x9c, &0xa4, &0x72, &0xc0, &0xb7, &0xfd, &0x93, &0x26, &0x36, &0x3f, &0xf7, &0xcc, &0x34, &0xa5, &0xe5, &0xf1, &0x71, &0xd8, &0x31, &0x15, &0x04, &0xc7, &0x23, &0xc3, &0x18, &0x96,
-        &0x05, &0x9a, &0x07, &0x12, &0x80, &0xe2, &0xeb, &0x27, &0xb2, &0x75, &0x09, &0x83, &0x2c, &0x1a, &0x1b, &0x6e, &0x5a, &0xa0, &0x52, &0x3b, &0xd6, &0xb3, &0x29, &0xe3, &0x2f, &0x84, &0x53,
-        &0xd1, &0x00, &0xed, &0x20, &0xfc, &0xb1, &0x5b, &0x6a, &0xcb, &0xbe, &0x39, &0x4a, &0x4c, &0x58, &0xcf, &0xd0, &0xef, &0xaa, &0xfb, &0x43, &0x4d, &0x33, &0x85, &0x45, &0xf9, &0x02, &0x7f,
-        &0x50, &0x3c, &0x9f, &0xa8, &0x51, &0xa3, &0x40, &0x8f, &0x92, &0x9d, &0x38, &0xf5, &0xbc, &0xb6, &0xda, &0x21, &0x10, &0xff, &0xf3, &0xd2, &0xcd, &0x0c, &0x13, &0xec, &0x5f, &0x97, &0x44,
-        &0x17, &0xc4, &0xa7, &0x7e, &0x3d, &0x64, &0x5d, &0x19, &0x73, &0x60, &0x81, &0x4f, &0xdc, &0x22, &0x2a, &0x90, &0x88, &0x46, &0xee, &0xb8, &0x14, &0xde, &0x5e, &0x0b, &0xdb, &0xe0, &0x32,
-        &0x3a, &0x0a, &0x49, &0x06, &0x24, &0x5c, &0xc2, &0xd3, &0xac, &0x62, &0x91, &0x95, &0xe4, &0x79, &0xe7, &0xc8, &0x37, &0x6d, &0x8d, &0xd5, &0x4e, &0xa9, &0x6c, &0x56, &0xf4, &0xea, &0x65,
-        &0x7a, &0xae, &0x08, &0xba, &0x78, &0x25, &0x2e, &0x1c, &0xa6, &0xb4, &0xc6, &0xe8, &0xdd, &0x74, &0x1f, &0x4b, &0xbd, &0x8b, &0x8a, &0x70, &0x3e, &0xb5, &0x66, &0x48, &0x03, &0xf6, &0x0e,
-        &0x61, &0x35, &0x57, &0xb9, &0x86, &0xc1, &0x1d, &0x9e, &0xe1, &0xf8, &0x98, &0x11, &0x69, &0xd9, &0x8e, &0x94, &0x9b, &0x1e, &0x87, &0xe9, &0xce, &0x55, &0x28, &0xdf, &0x8c, &0xa1, &0x89,
-        &0x0d, &0xbf, &0xe6, &0x42, &0x68, &0x41, &0x99, &0x2d, &0x0f, &0xb0, &0x54, &0xbb, &0x16,
-    ];
+    pub const S_BOX: [u8; 256] = s_box();
 
-    pub fn new(data: Vec<u8>) -> AESBlock<DecryptedState> {
-        AESBlock { grid: data, state: std::marker::PhantomData::<DecryptedState> }
+    ///
+    /// Creates a new AESBlock struct with the specified data.
+    ///
+    /// data: The 16 bytes of the block.
+    ///
+    /// result: A AESBlock struct with the specified data.
+    ///
+    pub fn new(data: [u8; 16]) -> AESBlock<DecryptedState> {
+        AESBlock { grid: data, state: core::marker::PhantomData::<DecryptedState> }
     }
 
     ///
     /// Full encryption of a single 16 byte block.
     ///
-    /// roundkeys: A vector of 11, 13 or 15 roundkeys. Each roundkey is a vector of 16 bytes.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    ///
+    /// result: A block of 16 bytes encrypted.
     ///
-    /// result: A vector of 16 bytes encrypted.
+    pub fn encrypt(&self, roundkeys: &RoundKeys) -> AESBlock<EncryptedState> {
+        #[cfg(feature = "constant-time")]
+        let grid = bitslice::encrypt(self.grid, roundkeys);
+        #[cfg(all(feature = "fast-tables", not(feature = "constant-time")))]
+        let grid = tables::encrypt(self.grid, roundkeys);
+        #[cfg(not(any(feature = "fast-tables", feature = "constant-time")))]
+        let grid = self.rounds(roundkeys);
+        AESBlock { grid, state: core::marker::PhantomData::<EncryptedState> }
+    }
+
     ///
-    pub fn encrypt(&self, roundkeys: &[Vec<u8>]) -> AESBlock<EncryptedState> {
-        let mut result = self.add_roundkey(&self.grid, &roundkeys[0]);
-        for (idx, _) in roundkeys.iter().skip(1).enumerate() {
-            result = self.sub_bytes(&result);
-            result = self.shift_grid(&result);
-            result = if idx != (roundkeys.len() - 1) { self.mix_columns(&result) } else { result };
-            result = self.add_roundkey(&result, &roundkeys[idx + 1]);
+    /// Encrypts the block with the round functions.
+    ///
+    #[cfg_attr(any(feature = "fast-tables", feature = "constant-time"), allow(dead_code))]
+    fn rounds(&self, roundkeys: &RoundKeys) -> [u8; 16] {
+        let rounds = roundkeys.len() - 1;
+        let mut block = AESBlock::<DecryptedState>::new(self.transpose(&self.grid));
+        block.add_roundkey(&self.transpose(&roundkeys[0]));
+        for (idx, roundkey) in roundkeys.iter().enumerate().skip(1) {
+            block.sub_bytes();
+            block.shift_grid();
+            // The final round has no column mixing.
+            if idx != rounds {
+                block.mix_columns();
+            }
+            block.add_roundkey(&self.transpose(roundkey));
         }
-        AESBlock { grid: result.clone(), state: std::marker::PhantomData::<EncryptedState> }
+        self.transpose(&block.grid)
+    }
+
+    ///
+    /// Encrypts a single 16 byte block in the buffer it is stored in.
+    ///
+    /// block: The 16 bytes to encrypt, replaced by the encrypted bytes.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    ///
+    /// result: Ok, or an error if the block is not 16 bytes long.
+    ///
+    pub fn encrypt_in_place(block: &mut [u8], roundkeys: &RoundKeys) -> Result<(), AesError> {
+        let grid: [u8; 16] = (&*block).try_into().map_err(|_| AesError::InvalidDataLength { length: block.len() })?;
+        block.copy_from_slice(&AESBlock::<DecryptedState>::new(grid).encrypt(roundkeys).grid);
+        Ok(())
     }
 
     ///
@@ -84,19 +652,13 @@ impl AESBlock<DecryptedState> {
     /// row 3 shifted to the left twice
     /// row 4 shifted to the left three times
     ///
-    /// data: A vector of 16 bytes. These are considered to be in
-    ///       pattern of a 4x4 grid with row-major order.
+    /// The grid is considered to be in pattern of a 4x4 grid with row-major order.
     ///
-    /// result: A vector of 16 bytes. These are considered to be in
-    ///         pattern of a 4x4 grid with row-major order.
-    ///
-    fn shift_grid(&self, data: &[u8]) -> Vec<u8> {
-        let mut result: Vec<u8> = vec![0; data.len()];
-        data.chunks(4).enumerate().for_each(|(idx, row)| {
-            let shifted_row = self.shift_row(row, &idx);
-            result.splice(idx * 4..idx * 4 + 4, shifted_row);
-        });
-        result
+    fn shift_grid(&mut self) {
+        for idx in 0..4 {
+            let row = self.shift_row([self.grid[idx * 4], self.grid[idx * 4 + 1], self.grid[idx * 4 + 2], self.grid[idx * 4 + 3]], idx);
+            self.grid[idx * 4..idx * 4 + 4].copy_from_slice(&row);
+        }
     }
 
     ///
@@ -104,26 +666,17 @@ impl AESBlock<DecryptedState> {
     /// algorithm. Description of the algorithm can be found here:
     /// https://en.wikipedia.org/wiki/Rijndael_MixColumns
     ///
-    /// data: A vector of 4 bytes for colunm X,
-    ///
-    /// result: A vector of 4 bytes for each row.
-    ///  
-    fn mix_column(&self, data: &[u8]) -> Vec<u8> {
-        let mut result: Vec<u8> = vec![0; 4];
-        let mut a: Vec<u8> = vec![0; 4];
-        let mut b: Vec<u8> = vec![0; 4];
-        let mut h: u8;
-        for c in 0..4 {
-            a[c] = data[c];
-            h = (data[c] >> 7) & 1;
-            b[c] = data[c] << 1;
-            b[c] ^= h * 0x1B;
-        }
-        result[0] = self.multiply(0x02, a[0]) ^ self.multiply(0x03, a[1]) ^ self.multiply(0x01, a[2]) ^ self.multiply(0x01, a[3]);
-        result[1] = self.multiply(0x01, a[0]) ^ self.multiply(0x02, a[1]) ^ self.multiply(0x03, a[2]) ^ self.multiply(0x01, a[3]);
-        result[2] = self.multiply(0x01, a[0]) ^ self.multiply(0x01, a[1]) ^ self.multiply(0x02, a[2]) ^ self.multiply(0x03, a[3]);
-        result[3] = self.multiply(0x03, a[0]) ^ self.multiply(0x01, a[1]) ^ self.multiply(0x01, a[2]) ^ self.multiply(0x02, a[3]);
-        result
+    /// a: The 4 bytes of a column.
+    ///
+    /// result: The 4 bytes of the column mixed.
+    ///
+    fn mix_column(&self, a: [u8; 4]) -> [u8; 4] {
+        [
+            self.multiply(0x02, a[0]) ^ self.multiply(0x03, a[1]) ^ a[2] ^ a[3],
+            a[0] ^ self.multiply(0x02, a[1]) ^ self.multiply(0x03, a[2]) ^ a[3],
+            a[0] ^ a[1] ^ self.multiply(0x02, a[2]) ^ self.multiply(0x03, a[3]),
+            self.multiply(0x03, a[0]) ^ a[1] ^ a[2] ^ self.multiply(0x02, a[3]),
+        ]
     }
 
     ///
@@ -131,34 +684,24 @@ impl AESBlock<DecryptedState> {
     /// algorithm. Description of the algorithm can be found here:
     /// https://en.wikipedia.org/wiki/Rijndael_MixColumns.
     ///
-    /// data: A vector of 16 bytes. These are considered to be in
-    ///      pattern of a 4x4 grid with row-major order.
+    /// The grid is considered to be in pattern of a 4x4 grid with row-major order.
     ///
-    /// result: A vector of 16 bytes. These are considered to be in
-    ///     pattern of a 4x4 grid with row-major order.
-    ///
-    fn mix_columns(&self, data: &[u8]) -> Vec<u8> {
-        let col1: Vec<u8> = self.mix_column(&[data[0], data[4], data[8], data[12]]);
-        let col2: Vec<u8> = self.mix_column(&[data[1], data[5], data[9], data[13]]);
-        let col3: Vec<u8> = self.mix_column(&[data[2], data[6], data[10], data[14]]);
-        let col4: Vec<u8> = self.mix_column(&[data[3], data[7], data[11], data[15]]);
-        vec![col1[0], col2[0], col3[0], col4[0], col1[1], col2[1], col3[1], col4[1], col1[2], col2[2], col3[2], col4[2], col1[3], col2[3], col3[3], col4[3]]
+    fn mix_columns(&mut self) {
+        for column in 0..4 {
+            let mixed = self.mix_column([self.grid[column], self.grid[column + 4], self.grid[column + 8], self.grid[column + 12]]);
+            for (row, value) in mixed.into_iter().enumerate() {
+                self.grid[row * 4 + column] = value;
+            }
+        }
     }
 
     ///
-    /// Substitutes each byte in the data with the corresponding byte in the s_box.
+    /// Substitutes each byte in the grid with the corresponding byte in the s_box.
     ///
-    /// data: A vector of bytes to be exchanged..
-    /// s_box: A vector of bytes containg the substitution values.
-    ///
-    /// result: A vector of bytes with the substituted values.
-    ///
-    fn sub_bytes(&self, data: &[u8]) -> Vec<u8> {
-        let mut result = vec![0; data.len()];
-        for (idx, value) in data.iter().enumerate() {
-            result[idx] = *AESBlock::S_BOX[*value as usize];
+    fn sub_bytes(&mut self) {
+        for value in self.grid.iter_mut() {
+            *value = AESBlock::S_BOX[*value as usize];
         }
-        result
     }
 }
 
@@ -169,75 +712,89 @@ impl AESBlock<EncryptedState> {
     ///
     /// Substitutes each byte in the data with the corresponding byte in the inverse_s_box.
     ///
-    const INVERSE_S_BOX: [&'static u8; 256] = [
-        &0x52, &0x09, &0x6a, &0xd5, &0x30, &0x36, &0xa5, &0x38, &0xbf, &0x40, &0xa3, &0x9e, &0x81, &0xf3, &0xd7, &0xfb, &0x7c, &0xe3, &0x39, &0x82, &0x9b, &0x2f, &0xff, &0x87, &0x34, &0x8e, &0x43,
-        &0x44, &0xc4, &0xde, &0xe9, &0xcb, &0x54, &0x7b, &0x94, &0x32, &0xa6, &0xc2, &0x23, &0x3d, &0xee, &0x4c, &0x95, &0x0b, &0x42, &0xfa, &0xc3, &0x4e, &0x08, &0x2e, &0xa1, &0x66, &0x28, &0xd9,
-        &0x24, &0xb2, &0x76, &0x5b, &0xa2, &0x49, &0x6d, &0x8b, &0xd1, &0x25, &0x72, &0xf8, &0xf6, &0x64, &0x86, &0x68, &0x98, &0x16, &0xd4, &0xa4, &0x5c, &0xcc, &0x5d, &0x65, &0xb6, &0x92, &0x6c,
-        &0x70, &0x48, &0x50, &0xfd, &0xed, &0xb9, &0xda, &0x5e, &0x15, &0x46, &0x57, &0xa7, &0x8d, &0x9d, &0x84, &0x90, &0xd8, &0xab, &0x00, &0x8c, &0xbc, &0xd3, &0x0a, &0xf7, &0xe4, &0x58, &0x05,
-        &0xb8, &0xb3, &0x45, &0x06, &0xd0, &0x2c, &0x1e, &0x8f, &0xca, &0x3f, &0x0f, &0x02, &0xc1, &0xaf, &0xbd, &0x03, &0x01, &0x13, &0x8a, &0x6b, &0x3a, &0x91, &0x11, &0x41, &0x4f, &0x67, &0xdc,
-        &0xea, &0x97, &0xf2, &0xcf, &0xce, &0xf0, &0xb4, &0xe6, &0x73, &0x96, &0xac, &0x74, &0x22, &0xe7, &0xad, &0x35, &0x85, &0xe2, &0xf9, &0x37, &0xe8, &0x1c, &0x75, &0xdf, &0x6e, &0x47, &0xf1,
-        &0x1a, &0x71, &0x1d, &0x29, &0xc5, &0x89, &0x6f, &0xb7, &0x62, &0x0e, &0xaa, &0x18, &0xbe, &0x1b, &0xfc, &0x56, &0x3e, &0x4b, &0xc6, &0xd2, &0x79, &0x20, &0x9a, &0xdb, &0xc0, &0xfe, &0x78,
-        &0xcd, &0x5a, &0xf4, &0x1f, &0xdd, &0xa8, &0x33, &0x88, &0x07, &0xc7, &0x31, &0xb1, &0x12, &0x10, &0x59, &0x27, &0x80, &0xec, &0x5f, &0x60, &0x51, &0x7f, &0xa9, &0x19, &0xb5, &0x4a, &0x0d,
-        &0x2d, &0xe5, &0x7a, &0x9f, &0x93, &0xc9, &0x9c, &0xef, &0xa0, &0xe0, &0x3b, &0x4d, &0xae, &0x2a, &0xf5, &0xb0, &0xc8, &0xeb, &0xbb, &0x3c, &0x83, &0x53, &0x99, &0x61, &0x17, &0x2b, &0x04,
-        &0x7e, &0xba, &0x77, &0xd6, &0x26, &0xe1, &0x69, &0x14, &0x63, &0x55, &0x21, &0x0c, &0x7d,
-    ];
+    const INVERSE_S_BOX: [u8; 256] = inverse_s_box(&AESBlock::<DecryptedState>::S_BOX);
 
     ///
     /// Creates a new AESBlock struct with the specified data.
     ///
-    /// data: A vector of bytes.
+    /// data: The 16 bytes of the block.
     ///
     /// result: A AESBlock struct with the specified data.
-    ///                                                     
-    pub fn new(data: Vec<u8>) -> AESBlock<EncryptedState> {
-        AESBlock { grid: data, state: std::marker::PhantomData::<EncryptedState> }
+    ///
+    pub fn new(data: [u8; 16]) -> AESBlock<EncryptedState> {
+        AESBlock { grid: data, state: core::marker::PhantomData::<EncryptedState> }
     }
 
     ///
     /// Full decryption of a single 16 byte block.
     ///
-    /// roundkeys: A vector of 11, 13 or 15 roundkeys. Each roundkey is a vector of 16 bytes.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
     ///
-    /// result: A vector of 16 bytes decrypted.
+    /// result: A block of 16 bytes decrypted.
+    ///
+    pub fn decrypt(&self, roundkeys: &RoundKeys) -> AESBlock<DecryptedState> {
+        #[cfg(feature = "constant-time")]
+        let grid = bitslice::decrypt(self.grid, roundkeys);
+        #[cfg(all(feature = "fast-tables", not(feature = "constant-time")))]
+        let grid = tables::decrypt(self.grid, roundkeys);
+        #[cfg(not(any(feature = "fast-tables", feature = "constant-time")))]
+        let grid = self.rounds(roundkeys);
+        AESBlock { grid, state: core::marker::PhantomData::<DecryptedState> }
+    }
+
     ///
-    pub fn decrypt(&self, roundkeys: &[Vec<u8>]) -> AESBlock<DecryptedState> {
-        let mut result = self.add_roundkey(&self.grid, &roundkeys[roundkeys.len() - 1]);
-        for (idx, _) in roundkeys.iter().rev().skip(1).enumerate() {
-            result = if idx != (roundkeys.len() - 1) { self.mix_columns(&result) } else { result };
-            result = self.shift_grid(&result);
-            result = self.sub_bytes(&result);
-            result = self.add_roundkey(&result, &roundkeys[roundkeys.len() - idx - 2]);
+    /// Decrypts the block with the round functions in the equivalent inverse cipher. The
+    /// inverse steps are done in the same order as the steps of the encryption, which works
+    /// because the roundkeys in between already have the inverse column mixing applied.
+    ///
+    #[cfg_attr(any(feature = "fast-tables", feature = "constant-time"), allow(dead_code))]
+    fn rounds(&self, roundkeys: &RoundKeys) -> [u8; 16] {
+        let keys = roundkeys.decryption();
+        let rounds = keys.len() - 1;
+        let mut block = AESBlock::<EncryptedState>::new(self.transpose(&self.grid));
+        block.add_roundkey(&self.transpose(&keys[0]));
+        for (idx, key) in keys.iter().enumerate().skip(1) {
+            block.sub_bytes();
+            block.shift_grid();
+            // The first round of the encryption had no column mixing to undo.
+            if idx != rounds {
+                block.mix_columns();
+            }
+            block.add_roundkey(&self.transpose(key));
         }
-        AESBlock { grid: result.clone(), state: std::marker::PhantomData::<DecryptedState> }
+        self.transpose(&block.grid)
     }
 
     ///
-    /// Inverse mixes the columns of the grid by using the  Rijndael MixColumns
+    /// Decrypts a single 16 byte block in the buffer it is stored in.
+    ///
+    /// block: The 16 bytes to decrypt, replaced by the decrypted bytes.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    ///
+    /// result: Ok, or an error if the block is not 16 bytes long.
+    ///
+    pub fn decrypt_in_place(block: &mut [u8], roundkeys: &RoundKeys) -> Result<(), AesError> {
+        let grid: [u8; 16] = (&*block).try_into().map_err(|_| AesError::InvalidDataLength { length: block.len() })?;
+        block.copy_from_slice(&AESBlock::<EncryptedState>::new(grid).decrypt(roundkeys).grid);
+        Ok(())
+    }
+
+    ///
+    /// Inverse mixes a column of the grid by using the  Rijndael MixColumns
     /// algorithm. Description of the algorithm can be found here:
     /// https://en.wikipedia.org/wiki/Rijndael_MixColumns.
     ///
-    /// data: A vector of 16 bytes. These are considered to be in
-    ///      pattern of a 4x4 grid with row-major order.
-    ///
-    /// result: A vector of 16 bytes. These are considered to be in
-    ///     pattern of a 4x4 grid with row-major order.
-    ///
-    fn mix_column(&self, data: &[u8]) -> Vec<u8> {
-        let mut result: Vec<u8> = vec![0; 4];
-        let mut a: Vec<u8> = vec![0; 4];
-        let mut b: Vec<u8> = vec![0; 4];
-        let mut h: u8;
-        for c in 0..4 {
-            a[c] = data[c];
-            h = (data[c] >> 7) & 1;
-            b[c] = data[c] << 1;
-            b[c] ^= h * 0x1B;
-        }
-        result[0] = self.multiply(0x0E, a[0]) ^ self.multiply(0x0B, a[1]) ^ self.multiply(0x0D, a[2]) ^ self.multiply(0x09, a[3]);
-        result[1] = self.multiply(0x09, a[0]) ^ self.multiply(0x0E, a[1]) ^ self.multiply(0x0B, a[2]) ^ self.multiply(0x0D, a[3]);
-        result[2] = self.multiply(0x0D, a[0]) ^ self.multiply(0x09, a[1]) ^ self.multiply(0x0E, a[2]) ^ self.multiply(0x0B, a[3]);
-        result[3] = self.multiply(0x0B, a[0]) ^ self.multiply(0x0D, a[1]) ^ self.multiply(0x09, a[2]) ^ self.multiply(0x0E, a[3]);
-        result
+    /// a: The 4 bytes of a column.
+    ///
+    /// result: The 4 bytes of the column unmixed.
+    ///
+    fn mix_column(&self, a: [u8; 4]) -> [u8; 4] {
+        [
+            self.multiply(0x0E, a[0]) ^ self.multiply(0x0B, a[1]) ^ self.multiply(0x0D, a[2]) ^ self.multiply(0x09, a[3]),
+            self.multiply(0x09, a[0]) ^ self.multiply(0x0E, a[1]) ^ self.multiply(0x0B, a[2]) ^ self.multiply(0x0D, a[3]),
+            self.multiply(0x0D, a[0]) ^ self.multiply(0x09, a[1]) ^ self.multiply(0x0E, a[2]) ^ self.multiply(0x0B, a[3]),
+            self.multiply(0x0B, a[0]) ^ self.multiply(0x0D, a[1]) ^ self.multiply(0x09, a[2]) ^ self.multiply(0x0E, a[3]),
+        ]
     }
 
     ///
@@ -245,18 +802,15 @@ impl AESBlock<EncryptedState> {
     /// algorithm. Description of the algorithm can be found here:
     /// https://en.wikipedia.org/wiki/Rijndael_MixColumns.
     ///
-    /// data: A vector of 16 bytes. These are considered to be in
-    ///      pattern of a 4x4 grid with row-major order.
+    /// The grid is considered to be in pattern of a 4x4 grid with row-major order.
     ///
-    /// result: A vector of 16 bytes. These are considered to be in
-    ///     pattern of a 4x4 grid with row-major order.
-    ///
-    fn mix_columns(&self, data: &[u8]) -> Vec<u8> {
-        let col1: Vec<u8> = self.mix_column(&[data[0], data[4], data[8], data[12]]);
-        let col2: Vec<u8> = self.mix_column(&[data[1], data[5], data[9], data[13]]);
-        let col3: Vec<u8> = self.mix_column(&[data[2], data[6], data[10], data[14]]);
-        let col4: Vec<u8> = self.mix_column(&[data[3], data[7], data[11], data[15]]);
-        vec![col1[0], col2[0], col3[0], col4[0], col1[1], col2[1], col3[1], col4[1], col1[2], col2[2], col3[2], col4[2], col1[3], col2[3], col3[3], col4[3]]
+    fn mix_columns(&mut self) {
+        for column in 0..4 {
+            let mixed = self.mix_column([self.grid[column], self.grid[column + 4], self.grid[column + 8], self.grid[column + 12]]);
+            for (row, value) in mixed.into_iter().enumerate() {
+                self.grid[row * 4 + column] = value;
+            }
+        }
     }
 
     ///
@@ -266,35 +820,31 @@ impl AESBlock<EncryptedState> {
     /// row 3 shifted to the left twice
     /// row 4 shifted to the left once
     ///
-    /// data: A vector of 16 bytes. These are considered to be in
-    ///       pattern of a 4x4 grid with row-major order.
-    ///
-    /// result: A vector of 16 bytes. These are considered to be in
-    ///         pattern of a 4x4 grid with row-major order.
+    /// The grid is considered to be in pattern of a 4x4 grid with row-major order.
     ///
-    fn shift_grid(&self, data: &[u8]) -> Vec<u8> {
-        let mut result: Vec<u8> = vec![0; data.len()];
-        data.chunks(4).enumerate().for_each(|(idx, row)| {
-            let shifted_row = self.shift_row(row, &(4 - idx));
-            result.splice(idx * 4..idx * 4 + 4, shifted_row);
-        });
-        result
+    fn shift_grid(&mut self) {
+        for idx in 0..4 {
+            let row = self.shift_row([self.grid[idx * 4], self.grid[idx * 4 + 1], self.grid[idx * 4 + 2], self.grid[idx * 4 + 3]], (4 - idx) % 4);
+            self.grid[idx * 4..idx * 4 + 4].copy_from_slice(&row);
+        }
     }
 
     ///
-    /// Substitutes each byte in the data with the corresponding byte in the inverse_s_box.
-    ///
-    /// data: A vector of bytes to be exchanged..
-    /// s_box: A vector of bytes containg the substitution values.
+    /// Substitutes each byte in the grid with the corresponding byte in the inverse_s_box.
     ///
-    /// result: A vector of bytes with the substituted values.
-    ///
-    fn sub_bytes(&self, data: &[u8]) -> Vec<u8> {
-        let mut result = vec![0; data.len()];
-        for (idx, value) in data.iter().enumerate() {
-            result[idx] = *AESBlock::INVERSE_S_BOX[*value as usize];
+    fn sub_bytes(&mut self) {
+        for value in self.grid.iter_mut() {
+            *value = AESBlock::INVERSE_S_BOX[*value as usize];
         }
-        result
+    }
+}
+
+///
+/// A decrypted block is plaintext, so the block is overwritten before the memory is freed.
+///
+impl<State> Drop for AESBlock<State> {
+    fn drop(&mut self) {
+        self.grid.zeroize();
     }
 }
 
@@ -308,56 +858,43 @@ impl<State> AESBlock<State> {
     /// result A byte with the result of the multiplication.
     ///
     pub fn multiply(&self, a: u8, b: u8) -> u8 {
-        let mut a = a;
-        let mut b = b;
-        let mut result: u8 = 0;
-        let mut temp: u8;
-        while b > 0 {
-            if (b & 1) > 0 {
-                result ^= a;
-            }
-            temp = a & 0x80;
-            a <<= 1;
-            if temp > 0 {
-                a ^= 0x1B; // Rijndael's Galois field
-            }
-            b >>= 1;
-        }
-        result
+        gf256::multiply(a, b)
     }
 
     ///
     /// Shifts a row of bytes left by the specified amount.
     ///
-    /// row: A vector of 4 bytes.
+    /// row: A row of 4 bytes.
     /// shift: The amount to shift the row by.
     ///
-    /// result: A vector of 4 bytes shifted.
+    /// result: The 4 bytes shifted.
     ///
-    ///
-    fn shift_row(&self, row: &[u8], shift: &usize) -> Vec<u8> {
-        let mut result = vec![0; row.len()];
-        for (idx, value) in row.iter().enumerate() {
-            let new_idx = idx + row.len() - shift;
-            result[(new_idx) % row.len()] = *value;
-        }
+    fn shift_row(&self, row: [u8; 4], shift: usize) -> [u8; 4] {
+        let mut result = row;
+        result.rotate_left(shift);
         result
     }
 
     ///
-    /// Adds the roundkey to the data.
+    /// Transposes the 4x4 grid, turning columns into rows and rows into columns.
     ///
-    /// data: A vector of bytes to be exchanged.
-    /// roundkey: Key to be added to the data.
+    /// data: 16 bytes.
     ///
-    /// result: A vector of bytes with the added values.
+    /// result: The 16 bytes transposed.
     ///
-    fn add_roundkey(&self, data: &[u8], roundkey: &[u8]) -> Vec<u8> {
-        let mut result: Vec<u8> = vec![0; data.len()];
-        for (idx, value) in data.iter().enumerate() {
-            result[idx] = value ^ roundkey[idx];
+    fn transpose(&self, data: &[u8]) -> [u8; 16] {
+        core::array::from_fn(|idx| data[(idx % 4) * 4 + idx / 4])
+    }
+
+    ///
+    /// Adds the roundkey to the grid.
+    ///
+    /// roundkey: Key to be added to the grid.
+    ///
+    fn add_roundkey(&mut self, roundkey: &[u8; 16]) {
+        for (value, key) in self.grid.iter_mut().zip(roundkey) {
+            *value ^= key;
         }
-        result
     }
 }
 
@@ -370,143 +907,294 @@ impl AESData<DecryptedState> {
     /// result: A AESData struct with the specified data.
     ///
     pub fn new(data: Vec<u8>) -> AESData<DecryptedState> {
-        AESData { data, state: std::marker::PhantomData::<DecryptedState> }
+        AESData { data, state: core::marker::PhantomData::<DecryptedState> }
     }
 
     ///
     /// Encrypts the data using AES ithe specified roundkeys.
-    /// Data is padded with a character to make it a multiple of 16 bytes.
-    /// This character is the last character xored with 0x01.
+    /// Data is padded to make it a multiple of 16 bytes.
     ///
-    /// roundkeys: A vector of 11, 13 or 15 roundkeys. Each roundkey is a vector of 16 bytes.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// padding: The padding scheme, for example padding::Pkcs7.
     ///
     /// result: A vector of bytes encrypted.
     ///
-    pub fn encrypt(&self, roundkeys: &[Vec<u8>]) -> AESData<EncryptedState> {
-        let padding_char = self.data[self.data.len() - 1] ^ 0x01;
-        let padded_data: Vec<u8> = self.data.iter().chain(vec![padding_char; 32 - (self.data.len() % 16)].iter()).cloned().collect();
-        let encrypted_data = padded_data
-            .chunks(16)
-            .flat_map(|block: &[u8]| {
-                let block = if block.len() < 16 {
-                    let mut block: Vec<u8> = block.to_vec();
-                    block.resize(16, padding_char);
-                    block
-                } else {
-                    block.to_vec()
-                };
-                let aes_block = AESBlock::<DecryptedState>::new(block);
-                aes_block.encrypt(roundkeys).grid
-            })
-            .collect();
-        AESData { data: encrypted_data, state: std::marker::PhantomData::<EncryptedState> }
+    pub fn encrypt<P: Padding + ?Sized>(&self, roundkeys: &RoundKeys, padding: &P) -> AESData<EncryptedState> {
+        self.encrypt_with_progress(roundkeys, padding, |_, _| {})
+    }
+
+    ///
+    /// Encrypts the data like encrypt, and reports the progress after each part of the data.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// padding: The padding scheme, for example padding::Pkcs7.
+    /// progress: Called with the number of bytes encrypted so far and the length of the padded data.
+    ///
+    /// result: A vector of bytes encrypted.
+    ///
+    pub fn encrypt_with_progress<P: Padding + ?Sized>(&self, roundkeys: &RoundKeys, padding: &P, mut progress: impl FnMut(usize, usize)) -> AESData<EncryptedState> {
+        let mut data: Vec<u8> = padding.pad(&self.data, 16);
+        let total = data.len();
+        let mut done = 0;
+        for part in data.chunks_mut(PROGRESS_LENGTH) {
+            AESData::<DecryptedState>::encrypt_in_place(part, roundkeys).expect("Padded to whole blocks");
+            done += part.len();
+            progress(done, total);
+        }
+        AESData { data, state: core::marker::PhantomData::<EncryptedState> }
+    }
+
+    ///
+    /// Encrypts the data block by block in the buffer it is stored in, without padding.
+    /// Large buffers can be encrypted this way without keeping a copy of the plaintext.
+    ///
+    /// data: The bytes to encrypt, a whole number of 16 byte blocks. They are replaced by the encrypted bytes.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    ///
+    /// result: Ok, or an error if the data is not a whole number of blocks.
+    ///
+    pub fn encrypt_in_place(data: &mut [u8], roundkeys: &RoundKeys) -> Result<(), AesError> {
+        if !data.len().is_multiple_of(16) {
+            return Err(AesError::InvalidDataLength { length: data.len() });
+        }
+        for_each_chunk(data, |_, chunk| {
+            for block in chunk.as_chunks_mut::<16>().0 {
+                *block = encrypt_block(*block, roundkeys);
+            }
+        });
+        Ok(())
+    }
+
+    ///
+    /// Encrypts the data lazily in ECB mode, a block at a time, so the blocks can be hashed
+    /// or written as they are produced. The last block is padded.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// padding: The padding scheme, for example padding::Pkcs7.
+    ///
+    /// result: An iterator over the encrypted blocks.
+    ///
+    pub fn encrypt_blocks<'a, P: Padding + ?Sized>(&'a self, roundkeys: &'a RoundKeys, padding: &P) -> impl Iterator<Item = [u8; 16]> + use<'a, P> {
+        let whole = self.data.len() - self.data.len() % 16;
+        let last: Vec<[u8; 16]> = padding.pad(&self.data[whole..], 16).chunks_exact(16).map(|block| block.try_into().expect("Chunks of 16 bytes")).collect();
+        self.data[..whole].chunks_exact(16).map(|block| block.try_into().expect("Chunks of 16 bytes")).chain(last).map(|block| AESBlock::<DecryptedState>::new(block).encrypt(roundkeys).grid)
+    }
+
+    ///
+    /// Encrypts the data using AES in counter mode. The data is not padded.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// counter: The first counter block. The same counter must never be used twice with the same key.
+    ///
+    /// result: A vector of bytes encrypted, with the same length as the data.
+    ///
+    pub fn encrypt_ctr(&self, roundkeys: &RoundKeys, counter: Counter) -> AESData<EncryptedState> {
+        self.encrypt_stream(roundkeys, StreamMode::Ctr(counter))
+    }
+
+    ///
+    /// Encrypts the data using AES in a stream mode. The data is not padded.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// mode: The stream mode with its counter or initialization vector.
+    ///
+    /// result: A vector of bytes encrypted, with the same length as the data.
+    ///
+    pub fn encrypt_stream(&self, roundkeys: &RoundKeys, mode: StreamMode) -> AESData<EncryptedState> {
+        AESData { data: mode.encrypt(&self.data, roundkeys), state: core::marker::PhantomData::<EncryptedState> }
+    }
+
+    ///
+    /// Encrypts and authenticates the data using AES in Galois/Counter mode. The data is not padded.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// nonce: The nonce, normally 12 bytes. The same nonce must never be used twice with the same key.
+    /// aad: Associated data that is authenticated but not encrypted.
+    ///
+    /// result: A vector of bytes encrypted and the authentication tag.
+    ///
+    pub fn encrypt_gcm(&self, roundkeys: &RoundKeys, nonce: &[u8], aad: &[u8]) -> (AESData<EncryptedState>, [u8; gcm::TAG_LENGTH]) {
+        let (data, tag) = gcm::encrypt(&self.data, aad, roundkeys, nonce);
+        (AESData { data, state: core::marker::PhantomData::<EncryptedState> }, tag)
     }
 }
 
 impl AESData<EncryptedState> {
     pub fn new(data: Vec<u8>) -> AESData<EncryptedState> {
-        AESData { data, state: std::marker::PhantomData::<EncryptedState> }
+        AESData { data, state: core::marker::PhantomData::<EncryptedState> }
     }
 
     ///
-    /// Decrypts the data using AES ithe specified roundkeys.
-    /// Any padded characters are removed.
+    /// Encodes the encrypted data as lower case hex.
     ///
-    /// roundkeys: A vector of 11, 13 or 15 roundkeys. Each roundkey is a vector of 16 bytes.
+    pub fn to_hex(&self) -> String {
+        encoding::to_hex(&self.data)
+    }
+
     ///
-    /// result: A vector of bytes decrypted.
+    /// Reads encrypted data from hex.
     ///
-    pub fn decrypt(&self, roundkeys: &[Vec<u8>]) -> AESData<DecryptedState> {
-        let decrypted_data: Vec<u8> = self
-            .data
-            .chunks(16)
-            .flat_map(|block| {
-                let aes_block = AESBlock::<EncryptedState>::new(block.to_vec());
-                aes_block.decrypt(roundkeys).grid
-            })
-            .collect();
-        let padded_char: u8 = decrypted_data[decrypted_data.len() - 1];
-        let idx = decrypted_data.iter().rev().position(|&x| x != padded_char).unwrap_or(0);
-        AESData { data: decrypted_data[..decrypted_data.len() - idx].to_vec(), state: std::marker::PhantomData::<DecryptedState> }
+    /// text: Hex digits in upper or lower case.
+    ///
+    /// result: The encrypted data, or an error if the text is not valid hex.
+    ///
+    pub fn from_hex(text: &str) -> Result<AESData<EncryptedState>, AesError> {
+        Ok(AESData::<EncryptedState>::new(encoding::from_hex(text)?))
     }
-}
 
-impl<State> AESData<State> {
-    const R_CON: &[u8] = &[0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+    ///
+    /// Encodes the encrypted data as Base64 with padding.
+    ///
+    pub fn to_base64(&self) -> String {
+        encoding::to_base64(&self.data)
+    }
 
     ///
-    /// Generates the roundkeys from the key.
+    /// Reads encrypted data from Base64.
     ///
-    /// key: A vector of bytes used to generate the roundkeys.
+    /// text: Base64 in the standard alphabet with padding.
     ///
-    /// result: A vector of roundkeys. Each roundkey is a vector of 16 bytes.
+    /// result: The encrypted data, or an error if the text is not valid Base64.
     ///
-    pub fn generate_roundkeys(key: &[u8]) -> Vec<Vec<u8>> {
-        let mut roundkeys: Vec<Vec<u8>> = vec![key.to_vec()];
-        let mut roundkey: Vec<u8> = key.to_vec();
-        for idx in 0..10 {
-            roundkey = AESData::<State>::generate_roundkey(&roundkey, idx);
-            roundkeys.push(roundkey.clone());
-        }
-        roundkeys
+    pub fn from_base64(text: &str) -> Result<AESData<EncryptedState>, AesError> {
+        Ok(AESData::<EncryptedState>::new(encoding::from_base64(text)?))
     }
 
     ///
-    /// Generates a roundkey from the previous roundkey.
+    /// Decrypts the data using AES ithe specified roundkeys.
+    /// The padding is checked and removed.
     ///
-    /// roundkey: A vector of bytes to be exchanged.
-    /// iteration: The iteration of the roundkey.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// padding: The padding scheme used when encrypting.
     ///
-    /// result: A vector of with new roundkey.
+    /// result: A vector of bytes decrypted, or an error if the data is empty, not a whole
+    ///         number of blocks or the padding is malformed.
     ///
-    fn generate_roundkey(roundkey: &[u8], iteration: usize) -> Vec<u8> {
-        let mut result: Vec<u8> = vec![0; roundkey.len()];
-        let mut temp: Vec<u8> = vec![0; 4];
-        temp[..4].copy_from_slice(&roundkey[12..16]);
-        temp = AESData::<State>::rotate(&temp);
-        temp = AESData::<State>::sub_word(&temp);
-        temp[0] ^= AESData::<State>::R_CON[iteration];
-        for i in 0..4 {
-            temp[i] ^= roundkey[i];
+    pub fn decrypt<P: Padding + ?Sized>(&self, roundkeys: &RoundKeys, padding: &P) -> Result<AESData<DecryptedState>, AesError> {
+        self.decrypt_with_progress(roundkeys, padding, |_, _| {})
+    }
+
+    ///
+    /// Decrypts the data like decrypt, and reports the progress after each part of the data.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// padding: The padding scheme used when encrypting.
+    /// progress: Called with the number of bytes decrypted so far and the length of the encrypted data.
+    ///
+    /// result: A vector of bytes decrypted, or an error if the data is empty, not a whole
+    ///         number of blocks or the padding is malformed.
+    ///
+    pub fn decrypt_with_progress<P: Padding + ?Sized>(&self, roundkeys: &RoundKeys, padding: &P, mut progress: impl FnMut(usize, usize)) -> Result<AESData<DecryptedState>, AesError> {
+        if self.data.is_empty() || !self.data.len().is_multiple_of(16) {
+            return Err(AesError::InvalidDataLength { length: self.data.len() });
         }
-        result[..4].copy_from_slice(&temp[..4]);
-        for i in 4..16 {
-            result[i] = result[i - 4] ^ roundkey[i];
+        // The plaintext is overwritten also when the padding is malformed.
+        let mut decrypted_data = Zeroizing::new(self.data.clone());
+        let total = decrypted_data.len();
+        let mut done = 0;
+        for part in decrypted_data.chunks_mut(PROGRESS_LENGTH) {
+            AESData::<EncryptedState>::decrypt_in_place(part, roundkeys)?;
+            done += part.len();
+            progress(done, total);
         }
-        result
+        let data = padding.unpad(&decrypted_data, 16)?;
+        Ok(AESData { data: data.to_vec(), state: core::marker::PhantomData::<DecryptedState> })
     }
 
     ///
-    /// Rotates the bytes in the word.
+    /// Decrypts the data block by block in the buffer it is stored in. The padding is not removed.
     ///
-    /// word: A vector of bytes to be rotated.
+    /// data: The bytes to decrypt, a whole number of 16 byte blocks. They are replaced by the decrypted bytes.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
     ///
-    /// result: A vector of bytes with the rotated values.
+    /// result: Ok, or an error if the data is not a whole number of blocks.
     ///
-    fn rotate(word: &[u8]) -> Vec<u8> {
-        let mut result: Vec<u8> = vec![0; word.len()];
-        result[0] = word[1];
-        result[1] = word[2];
-        result[2] = word[3];
-        result[3] = word[0];
-        result
+    pub fn decrypt_in_place(data: &mut [u8], roundkeys: &RoundKeys) -> Result<(), AesError> {
+        if !data.len().is_multiple_of(16) {
+            return Err(AesError::InvalidDataLength { length: data.len() });
+        }
+        for_each_chunk(data, |_, chunk| {
+            for block in chunk.as_chunks_mut::<16>().0 {
+                *block = decrypt_block(*block, roundkeys);
+            }
+        });
+        Ok(())
     }
 
     ///
-    /// Substitutes each byte in the data with the corresponding byte in the s_box.
-    /// This is the same substitution as in the sub_bytes function, but it is used
-    /// for generating the roundkeys.
+    /// Decrypts the data lazily in ECB mode, a block at a time. The padding is not removed
+    /// from the last block.
     ///
-    /// word: A vector of bytes to be exchanged..
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
     ///
-    /// result: A vector of bytes with the substituted values.
+    /// result: An iterator over the decrypted blocks, or an error if the data is not a whole number of blocks.
     ///
-    fn sub_word(word: &[u8]) -> Vec<u8> {
-        let mut result: Vec<u8> = vec![0; word.len()];
-        for i in 0..4 {
-            result[i] = *AESBlock::S_BOX[word[i] as usize]
+    pub fn decrypt_blocks<'a>(&'a self, roundkeys: &'a RoundKeys) -> Result<impl Iterator<Item = [u8; 16]> + 'a, AesError> {
+        if !self.data.len().is_multiple_of(16) {
+            return Err(AesError::InvalidDataLength { length: self.data.len() });
         }
-        result
+        Ok(self.data.chunks_exact(16).map(|block| AESBlock::<EncryptedState>::new(block.try_into().expect("Chunks of 16 bytes")).decrypt(roundkeys).grid))
+    }
+
+    ///
+    /// Decrypts the data using AES in counter mode.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// counter: The first counter block used when encrypting.
+    ///
+    /// result: A vector of bytes decrypted, with the same length as the data.
+    ///
+    pub fn decrypt_ctr(&self, roundkeys: &RoundKeys, counter: Counter) -> AESData<DecryptedState> {
+        self.decrypt_stream(roundkeys, StreamMode::Ctr(counter))
+    }
+
+    ///
+    /// Decrypts the data using AES in a stream mode.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// mode: The stream mode with the counter or initialization vector used when encrypting.
+    ///
+    /// result: A vector of bytes decrypted, with the same length as the data.
+    ///
+    pub fn decrypt_stream(&self, roundkeys: &RoundKeys, mode: StreamMode) -> AESData<DecryptedState> {
+        AESData { data: mode.decrypt(&self.data, roundkeys), state: core::marker::PhantomData::<DecryptedState> }
+    }
+
+    ///
+    /// Verifies and decrypts the data using AES in Galois/Counter mode.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// nonce: The nonce used when encrypting.
+    /// aad: The associated data used when encrypting.
+    /// tag: The authentication tag from the encryption.
+    ///
+    /// result: A vector of bytes decrypted, or an error if the tag does not match.
+    ///
+    pub fn decrypt_gcm(&self, roundkeys: &RoundKeys, nonce: &[u8], aad: &[u8], tag: &[u8; gcm::TAG_LENGTH]) -> Result<AESData<DecryptedState>, AesError> {
+        let data = gcm::decrypt(&self.data, aad, tag, roundkeys, nonce)?;
+        Ok(AESData { data, state: core::marker::PhantomData::<DecryptedState> })
+    }
+}
+
+///
+/// Decrypted data is plaintext, so the data is overwritten before the memory is freed.
+///
+impl<State> Drop for AESData<State> {
+    fn drop(&mut self) {
+        self.data.zeroize();
+    }
+}
+
+impl<State> AESData<State> {
+    ///
+    /// Generates the roundkeys from the key bytes, see Key::roundkeys.
+    ///
+    /// key: A vector of 16, 24 or 32 bytes used to generate the roundkeys.
+    ///
+    /// result: The 11, 13 or 15 roundkeys, or an error if the key length is not supported, see KeySize.
+    ///
+    pub fn generate_roundkeys(key: &[u8]) -> Result<RoundKeys, AesError> {
+        Ok(Key::from_slice(key)?.roundkeys())
     }
 }
 
@@ -514,183 +1202,180 @@ impl<State> AESData<State> {
 mod tests {
 
     use super::*;
+    use padding::Pkcs7;
 
     #[test]
     fn test_add_roundkey() {
-        let aes_block = AESBlock::<DecryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![0, 3, 6, 11, 8, 4, 5, 2, 15, 0, 1, 6, 3, 15, 13, 11];
-        let roundkey: Vec<u8> = vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4];
-        let grid: Vec<u8> = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
-        let result: Vec<u8> = aes_block.add_roundkey(&grid, &roundkey);
-        assert_eq!(expected_result, result);
+        let mut aes_block = AESBlock::<DecryptedState>::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let expected_result: [u8; 16] = [0, 3, 6, 11, 8, 4, 5, 2, 15, 0, 1, 6, 3, 15, 13, 11];
+        let roundkey: [u8; 16] = [0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4];
+        aes_block.add_roundkey(&roundkey);
+        assert_eq!(expected_result, aes_block.grid);
     }
 
     #[test]
     fn test_shift_row0() {
-        let aes_block: AESBlock = AESBlock::<DecryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![1, 2, 3, 4];
-        let row: Vec<u8> = vec![1, 2, 3, 4];
-        let result = aes_block.shift_row(&row, &0);
+        let aes_block = AESBlock::<DecryptedState>::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let expected_result: [u8; 4] = [1, 2, 3, 4];
+        let result = aes_block.shift_row([1, 2, 3, 4], 0);
         assert_eq!(expected_result, result);
     }
 
     #[test]
     fn test_shift_row1() {
-        let aes_block: AESBlock = AESBlock::<DecryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result = vec![2, 3, 4, 1];
-        let row = vec![1, 2, 3, 4];
-        let result = aes_block.shift_row(&row, &1);
+        let aes_block = AESBlock::<DecryptedState>::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let expected_result: [u8; 4] = [2, 3, 4, 1];
+        let result = aes_block.shift_row([1, 2, 3, 4], 1);
         assert_eq!(expected_result, result);
     }
 
     #[test]
     fn test_shift_row2() {
-        let aes_block: AESBlock = AESBlock::<DecryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result = vec![3, 4, 1, 2];
-        let row = vec![1, 2, 3, 4];
-        let result = aes_block.shift_row(&row, &2);
+        let aes_block = AESBlock::<DecryptedState>::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let expected_result: [u8; 4] = [3, 4, 1, 2];
+        let result = aes_block.shift_row([1, 2, 3, 4], 2);
         assert_eq!(expected_result, result);
     }
 
     #[test]
     fn test_shift_row3() {
-        let aes_block: AESBlock = AESBlock::<DecryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result = vec![4, 1, 2, 3];
-        let row = vec![1, 2, 3, 4];
-        let result = aes_block.shift_row(&row, &3);
+        let aes_block = AESBlock::<DecryptedState>::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let expected_result: [u8; 4] = [4, 1, 2, 3];
+        let result = aes_block.shift_row([1, 2, 3, 4], 3);
         assert_eq!(expected_result, result);
     }
 
     #[test]
     fn test_shift_grid() {
-        let aes_block: AESBlock = AESBlock::<DecryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![0, 1, 2, 3, 5, 6, 7, 4, 10, 11, 8, 9, 15, 12, 13, 14];
-        let grid: Vec<u8> = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
-        let result: Vec<u8> = aes_block.shift_grid(&grid);
-        assert_eq!(expected_result, result);
+        let mut aes_block = AESBlock::<DecryptedState>::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let expected_result: [u8; 16] = [0, 1, 2, 3, 5, 6, 7, 4, 10, 11, 8, 9, 15, 12, 13, 14];
+        aes_block.shift_grid();
+        assert_eq!(expected_result, aes_block.grid);
     }
 
     #[test]
     fn test_shift_grid_reverse() {
-        let aes_block: AESBlock<EncryptedState> = AESBlock::<EncryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
-        let grid: Vec<u8> = vec![0, 1, 2, 3, 5, 6, 7, 4, 10, 11, 8, 9, 15, 12, 13, 14];
-        let result: Vec<u8> = aes_block.shift_grid(&grid);
-        assert_eq!(expected_result, result);
+        let mut aes_block = AESBlock::<EncryptedState>::new([0, 1, 2, 3, 5, 6, 7, 4, 10, 11, 8, 9, 15, 12, 13, 14]);
+        let expected_result: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+        aes_block.shift_grid();
+        assert_eq!(expected_result, aes_block.grid);
     }
 
     #[test]
     fn test_mix_column() {
-        let aes_block: AESBlock = AESBlock::<DecryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![1, 1, 1, 1];
-        let data: &[u8] = &[1, 1, 1, 1];
-        let result: Vec<u8> = aes_block.mix_column(data);
+        let aes_block = AESBlock::<DecryptedState>::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let expected_result: [u8; 4] = [1, 1, 1, 1];
+        let result = aes_block.mix_column([1, 1, 1, 1]);
         assert_eq!(expected_result, result);
     }
 
     #[test]
     fn test_inverse_mix_column() {
-        let aes_block: AESBlock<EncryptedState> = AESBlock::<EncryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![1, 1, 1, 1];
-        let data: &[u8] = &[1, 1, 1, 1];
-        let result: Vec<u8> = aes_block.mix_column(data);
+        let aes_block = AESBlock::<EncryptedState>::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let expected_result: [u8; 4] = [1, 1, 1, 1];
+        let result = aes_block.mix_column([1, 1, 1, 1]);
         assert_eq!(expected_result, result);
     }
 
     #[test]
     fn test_mix_column2() {
-        let aes_block: AESBlock = AESBlock::<DecryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![142, 77, 161, 188];
-        let data: &[u8] = &[219, 19, 83, 69];
-        let result: Vec<u8> = aes_block.mix_column(data);
+        let aes_block = AESBlock::<DecryptedState>::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let expected_result: [u8; 4] = [142, 77, 161, 188];
+        let result = aes_block.mix_column([219, 19, 83, 69]);
         assert_eq!(expected_result, result);
     }
 
     #[test]
     fn test_inverse_mix_column2() {
-        let aes_block: AESBlock<EncryptedState> = AESBlock::<EncryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![219, 19, 83, 69];
-        let data: &[u8] = &[142, 77, 161, 188];
-        let result: Vec<u8> = aes_block.mix_column(data);
+        let aes_block = AESBlock::<EncryptedState>::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let expected_result: [u8; 4] = [219, 19, 83, 69];
+        let result = aes_block.mix_column([142, 77, 161, 188]);
         assert_eq!(expected_result, result);
     }
 
     #[test]
     fn test_mix_column3() {
-        let aes_block: AESBlock = AESBlock::<DecryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![159, 220, 88, 157];
-        let data: &[u8] = &[242, 10, 34, 92];
-        let result: Vec<u8> = aes_block.mix_column(data);
+        let aes_block = AESBlock::<DecryptedState>::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let expected_result: [u8; 4] = [159, 220, 88, 157];
+        let result = aes_block.mix_column([242, 10, 34, 92]);
         assert_eq!(expected_result, result);
     }
 
     #[test]
     fn test_inverse_mix_column3() {
-        let aes_block: AESBlock<EncryptedState> = AESBlock::<EncryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![242, 10, 34, 92];
-        let data: &[u8] = &[159, 220, 88, 157];
-        let result: Vec<u8> = aes_block.mix_column(data);
+        let aes_block = AESBlock::<EncryptedState>::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let expected_result: [u8; 4] = [242, 10, 34, 92];
+        let result = aes_block.mix_column([159, 220, 88, 157]);
         assert_eq!(expected_result, result);
     }
 
     #[test]
     fn test_mix_columns() {
-        let aes_block: AESBlock = AESBlock::<DecryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1];
-        let grid: Vec<u8> = vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1];
-        let result: Vec<u8> = aes_block.mix_columns(&grid);
-        assert_eq!(expected_result, result);
+        let mut aes_block = AESBlock::<DecryptedState>::new([1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]);
+        let expected_result: [u8; 16] = [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1];
+        aes_block.mix_columns();
+        assert_eq!(expected_result, aes_block.grid);
     }
 
     #[test]
     fn test_inverse_mix_columns() {
-        let aes_block: AESBlock<EncryptedState> = AESBlock::<EncryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1];
-        let grid: Vec<u8> = vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1];
-        let result: Vec<u8> = aes_block.mix_columns(&grid);
-        assert_eq!(expected_result, result);
+        let mut aes_block = AESBlock::<EncryptedState>::new([1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]);
+        let expected_result: [u8; 16] = [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1];
+        aes_block.mix_columns();
+        assert_eq!(expected_result, aes_block.grid);
     }
 
     #[test]
     fn test_mix_columns2() {
-        let aes_block: AESBlock = AESBlock::<DecryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![142, 159, 1, 198, 77, 220, 1, 198, 161, 88, 1, 198, 188, 157, 1, 198];
-        let grid: Vec<u8> = vec![219, 242, 1, 198, 19, 10, 1, 198, 83, 34, 1, 198, 69, 92, 1, 198];
-        let result: Vec<u8> = aes_block.mix_columns(&grid);
-        assert_eq!(expected_result, result);
+        let mut aes_block = AESBlock::<DecryptedState>::new([219, 242, 1, 198, 19, 10, 1, 198, 83, 34, 1, 198, 69, 92, 1, 198]);
+        let expected_result: [u8; 16] = [142, 159, 1, 198, 77, 220, 1, 198, 161, 88, 1, 198, 188, 157, 1, 198];
+        aes_block.mix_columns();
+        assert_eq!(expected_result, aes_block.grid);
     }
 
     #[test]
     fn test_inverse_mix_columns2() {
-        let aes_block: AESBlock<EncryptedState> = AESBlock::<EncryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![219, 242, 1, 198, 19, 10, 1, 198, 83, 34, 1, 198, 69, 92, 1, 198];
-        let grid: Vec<u8> = vec![142, 159, 1, 198, 77, 220, 1, 198, 161, 88, 1, 198, 188, 157, 1, 198];
-        let result: Vec<u8> = aes_block.mix_columns(&grid);
-        assert_eq!(expected_result, result);
+        let mut aes_block = AESBlock::<EncryptedState>::new([142, 159, 1, 198, 77, 220, 1, 198, 161, 88, 1, 198, 188, 157, 1, 198]);
+        let expected_result: [u8; 16] = [219, 242, 1, 198, 19, 10, 1, 198, 83, 34, 1, 198, 69, 92, 1, 198];
+        aes_block.mix_columns();
+        assert_eq!(expected_result, aes_block.grid);
     }
 
     #[test]
     fn test_sub_bytes() {
-        let aes_block: AESBlock = AESBlock::<DecryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![185, 137, 124, 180, 125, 103, 124, 180, 237, 147, 124, 180, 110, 74, 124, 180];
-        let grid: Vec<u8> = vec![219, 242, 1, 198, 19, 10, 1, 198, 83, 34, 1, 198, 69, 92, 1, 198];
-        let result: Vec<u8> = aes_block.sub_bytes(&grid);
-        assert_eq!(expected_result, result);
+        let mut aes_block = AESBlock::<DecryptedState>::new([219, 242, 1, 198, 19, 10, 1, 198, 83, 34, 1, 198, 69, 92, 1, 198]);
+        let expected_result: [u8; 16] = [185, 137, 124, 180, 125, 103, 124, 180, 237, 147, 124, 180, 110, 74, 124, 180];
+        aes_block.sub_bytes();
+        assert_eq!(expected_result, aes_block.grid);
+    }
+
+    ///
+    /// The first and last rows of the s-box and the first row of the inverse s-box in FIPS 197.
+    ///
+    #[test]
+    fn test_generated_s_box() {
+        let s_box = AESBlock::<DecryptedState>::S_BOX;
+        assert_eq!(s_box[..16], [0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76]);
+        assert_eq!(s_box[240..], [0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16]);
+        let inverse = AESBlock::<EncryptedState>::INVERSE_S_BOX;
+        assert_eq!(inverse[..16], [0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb]);
+        for idx in 0..256 {
+            assert_eq!(inverse[s_box[idx] as usize] as usize, idx);
+        }
     }
 
     #[test]
     fn test_sub_bytes_inverse() {
-        let aes_block: AESBlock<EncryptedState> = AESBlock::<EncryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let expected_result: Vec<u8> = vec![219, 242, 1, 198, 19, 10, 1, 198, 83, 34, 1, 198, 69, 92, 1, 198];
-        let grid: Vec<u8> = vec![185, 137, 124, 180, 125, 103, 124, 180, 237, 147, 124, 180, 110, 74, 124, 180];
-        let result: Vec<u8> = aes_block.sub_bytes(&grid);
-        assert_eq!(expected_result, result);
+        let mut aes_block = AESBlock::<EncryptedState>::new([185, 137, 124, 180, 125, 103, 124, 180, 237, 147, 124, 180, 110, 74, 124, 180]);
+        let expected_result: [u8; 16] = [219, 242, 1, 198, 19, 10, 1, 198, 83, 34, 1, 198, 69, 92, 1, 198];
+        aes_block.sub_bytes();
+        assert_eq!(expected_result, aes_block.grid);
     }
 
     #[test]
     fn test_encrypt() {
-        let aes_block: AESBlock = AESBlock::<DecryptedState>::new(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
-        let roundkeys: Vec<Vec<u8>> = vec![
+        let aes_block: AESBlock = AESBlock::<DecryptedState>::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let roundkeys = RoundKeys::new(vec![
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
@@ -702,16 +1387,17 @@ mod tests {
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
-        ];
+        ])
+        .unwrap();
         let result: AESBlock<EncryptedState> = aes_block.encrypt(&roundkeys);
-        let expected_result: Vec<u8> = vec![128, 249, 176, 188, 201, 213, 195, 110, 192, 161, 230, 165, 31, 182, 33, 44];
+        let expected_result: [u8; 16] = [59, 189, 55, 169, 149, 132, 179, 227, 233, 195, 229, 236, 75, 212, 91, 98];
         assert_eq!(expected_result, result.grid);
     }
 
     #[test]
     fn test_decrypt() {
-        let aes_block: AESBlock<EncryptedState> = AESBlock::<EncryptedState>::new(vec![128, 249, 176, 188, 201, 213, 195, 110, 192, 161, 230, 165, 31, 182, 33, 44]);
-        let roundkeys: Vec<Vec<u8>> = vec![
+        let aes_block: AESBlock<EncryptedState> = AESBlock::<EncryptedState>::new([59, 189, 55, 169, 149, 132, 179, 227, 233, 195, 229, 236, 75, 212, 91, 98]);
+        let roundkeys = RoundKeys::new(vec![
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
@@ -723,9 +1409,10 @@ mod tests {
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
-        ];
+        ])
+        .unwrap();
         let result: AESBlock<DecryptedState> = aes_block.decrypt(&roundkeys);
-        let expected_result: Vec<u8> = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+        let expected_result: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
         assert_eq!(expected_result, result.grid);
     }
 
@@ -733,7 +1420,7 @@ mod tests {
     fn encrypt_decrypt_file() {
         let bytes = &std::fs::read("testdata/testfile.in").unwrap();
         let expected_result = String::from_utf8_lossy(bytes);
-        let roundkeys: Vec<Vec<u8>> = vec![
+        let roundkeys = RoundKeys::new(vec![
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
@@ -745,10 +1432,11 @@ mod tests {
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
-        ];
+        ])
+        .unwrap();
         let aes_data = AESData::<DecryptedState>::new(bytes.to_vec());
-        let encrypted = aes_data.encrypt(&roundkeys);
-        let decrypted = encrypted.decrypt(&roundkeys);
+        let encrypted = aes_data.encrypt(&roundkeys, &Pkcs7);
+        let decrypted = encrypted.decrypt(&roundkeys, &Pkcs7).unwrap();
         let result = String::from_utf8_lossy(&decrypted.data);
         assert_eq!(expected_result, result);
     }
@@ -757,7 +1445,7 @@ mod tests {
     fn encrypt_decrypt_file2() {
         let bytes = &std::fs::read("testdata/large.in").unwrap();
         let expected_result = String::from_utf8_lossy(bytes);
-        let roundkeys: Vec<Vec<u8>> = vec![
+        let roundkeys = RoundKeys::new(vec![
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![1, 3, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![2, 4, 4, 8, 12, 1, 3, 5, 7, 9, 11, 113, 15, 2, 3, 4],
@@ -769,10 +1457,11 @@ mod tests {
             vec![8, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![9, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 143, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
-        ];
+        ])
+        .unwrap();
         let aes_data = AESData::<DecryptedState>::new(bytes.to_vec());
-        let encrypted = aes_data.encrypt(&roundkeys);
-        let decrypted = encrypted.decrypt(&roundkeys);
+        let encrypted = aes_data.encrypt(&roundkeys, &Pkcs7);
+        let decrypted = encrypted.decrypt(&roundkeys, &Pkcs7).unwrap();
         let result = String::from_utf8_lossy(&decrypted.data);
         assert_eq!(expected_result, result);
     }
@@ -781,7 +1470,7 @@ mod tests {
     fn encrypt_decrypt_file3() {
         let bytes = &std::fs::read("testdata/binary.in").unwrap();
         let expected_result = String::from_utf8_lossy(bytes);
-        let roundkeys: Vec<Vec<u8>> = vec![
+        let roundkeys = RoundKeys::new(vec![
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![1, 3, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![2, 4, 4, 8, 12, 1, 3, 5, 7, 9, 11, 113, 15, 2, 3, 4],
@@ -793,10 +1482,11 @@ mod tests {
             vec![8, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
             vec![9, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 143, 15, 2, 3, 4],
             vec![0, 2, 4, 8, 12, 1, 3, 5, 7, 9, 11, 13, 15, 2, 3, 4],
-        ];
+        ])
+        .unwrap();
         let aes_data = AESData::<DecryptedState>::new(bytes.to_vec());
-        let encrypted = aes_data.encrypt(&roundkeys);
-        let decrypted = encrypted.decrypt(&roundkeys);
+        let encrypted = aes_data.encrypt(&roundkeys, &Pkcs7);
+        let decrypted = encrypted.decrypt(&roundkeys, &Pkcs7).unwrap();
         let result = String::from_utf8_lossy(&decrypted.data);
         assert_eq!(expected_result, result);
     }
@@ -804,7 +1494,7 @@ mod tests {
     #[test]
     fn generate_roundkeys() {
         let init_key: Vec<u8> = vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
-        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&init_key);
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&init_key).unwrap();
         let expected_result: Vec<Vec<u8>> = vec![
             vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
             vec![0x62, 0x63, 0x63, 0x63, 0x62, 0x63, 0x63, 0x63, 0x62, 0x63, 0x63, 0x63, 0x62, 0x63, 0x63, 0x63],
@@ -818,17 +1508,313 @@ mod tests {
             vec![0xb1, 0xd4, 0xd8, 0xe2, 0x8a, 0x7d, 0xb9, 0xda, 0x1d, 0x7b, 0xb3, 0xde, 0x4c, 0x66, 0x49, 0x41],
             vec![0xb4, 0xef, 0x5b, 0xcb, 0x3e, 0x92, 0xe2, 0x11, 0x23, 0xe9, 0x51, 0xcf, 0x6f, 0x8f, 0x18, 0x8e],
         ];
-        assert_eq!(expected_result, roundkeys);
+        assert_eq!(expected_result, roundkeys.to_vec());
+    }
+
+    ///
+    /// Key expansion example from FIPS-197 appendix A.1.
+    ///
+    #[test]
+    fn generate_roundkeys_128() {
+        let init_key: Vec<u8> = vec![0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&init_key).unwrap();
+        assert_eq!(roundkeys.len(), 11);
+        assert_eq!(roundkeys[1], vec![0xa0, 0xfa, 0xfe, 0x17, 0x88, 0x54, 0x2c, 0xb1, 0x23, 0xa3, 0x39, 0x39, 0x2a, 0x6c, 0x76, 0x05]);
+        assert_eq!(roundkeys[10], vec![0xd0, 0x14, 0xf9, 0xa8, 0xc9, 0xee, 0x25, 0x89, 0xe1, 0x3f, 0x0c, 0xc8, 0xb6, 0x63, 0x0c, 0xa6]);
+    }
+
+    ///
+    /// The decryption roundkeys are the roundkeys in reverse with the inverse column mixing
+    /// applied to all but the first and the last.
+    ///
+    #[test]
+    fn decryption_roundkeys_128() {
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&[0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c]).unwrap();
+        let decryption = roundkeys.decryption();
+        assert_eq!(decryption.len(), 11);
+        assert_eq!(decryption[0][..], roundkeys[10][..]);
+        assert_eq!(decryption[9], [0x2b, 0x37, 0x08, 0xa7, 0xf2, 0x62, 0xd4, 0x05, 0xbc, 0x3e, 0xbd, 0xbf, 0x4b, 0x61, 0x7d, 0x62]);
+        assert_eq!(decryption[10][..], roundkeys[0][..]);
+    }
+
+    ///
+    /// Key expansion example from FIPS-197 appendix A.3.
+    ///
+    #[test]
+    fn generate_roundkeys_256() {
+        let init_key: Vec<u8> = vec![
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d, 0x77, 0x81, 0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3, 0x09, 0x14, 0xdf,
+            0xf4,
+        ];
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&init_key).unwrap();
+        assert_eq!(roundkeys.len(), 15);
+        assert_eq!(roundkeys[0], init_key[..16].to_vec());
+        assert_eq!(roundkeys[1], init_key[16..].to_vec());
+        assert_eq!(roundkeys[2], vec![0x9b, 0xa3, 0x54, 0x11, 0x8e, 0x69, 0x25, 0xaf, 0xa5, 0x1a, 0x8b, 0x5f, 0x20, 0x67, 0xfc, 0xde]);
+        assert_eq!(roundkeys[3], vec![0xa8, 0xb0, 0x9c, 0x1a, 0x93, 0xd1, 0x94, 0xcd, 0xbe, 0x49, 0x84, 0x6e, 0xb7, 0x5d, 0x5b, 0x9a]);
+        assert_eq!(roundkeys[14], vec![0xfe, 0x48, 0x90, 0xd1, 0xe6, 0x18, 0x8d, 0x0b, 0x04, 0x6d, 0xf3, 0x44, 0x70, 0x6c, 0x63, 0x1e]);
+    }
+
+    ///
+    /// Key expansion example from FIPS-197 appendix A.2.
+    ///
+    #[test]
+    fn generate_roundkeys_192() {
+        let init_key: Vec<u8> = vec![0x8e, 0x73, 0xb0, 0xf7, 0xda, 0x0e, 0x64, 0x52, 0xc8, 0x10, 0xf3, 0x2b, 0x80, 0x90, 0x79, 0xe5, 0x62, 0xf8, 0xea, 0xd2, 0x52, 0x2c, 0x6b, 0x7b];
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&init_key).unwrap();
+        assert_eq!(roundkeys.len(), 13);
+        assert_eq!(roundkeys[1], vec![0x62, 0xf8, 0xea, 0xd2, 0x52, 0x2c, 0x6b, 0x7b, 0xfe, 0x0c, 0x91, 0xf7, 0x24, 0x02, 0xf5, 0xa5]);
+        assert_eq!(roundkeys[12], vec![0xe9, 0x8b, 0xa0, 0x6f, 0x44, 0x8c, 0x77, 0x3c, 0x8e, 0xcc, 0x72, 0x04, 0x01, 0x00, 0x22, 0x02]);
+    }
+
+    ///
+    /// Example vectors from FIPS-197 appendix C for all three key sizes.
+    ///
+    #[test]
+    fn encrypt_decrypt_fips_197() {
+        let plaintext: [u8; 16] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+        let vectors: [(usize, [u8; 16]); 3] = [
+            (16, [0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a]),
+            (24, [0xdd, 0xa9, 0x7c, 0xa4, 0x86, 0x4c, 0xdf, 0xe0, 0x6e, 0xaf, 0x70, 0xa0, 0xec, 0x0d, 0x71, 0x91]),
+            (32, [0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49, 0x60, 0x89]),
+        ];
+        for (key_length, ciphertext) in vectors {
+            let init_key: Vec<u8> = (0..key_length as u8).collect();
+            let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&init_key).unwrap();
+            let encrypted = AESBlock::<DecryptedState>::new(plaintext).encrypt(&roundkeys);
+            assert_eq!(ciphertext, encrypted.grid);
+            let decrypted = AESBlock::<EncryptedState>::new(ciphertext).decrypt(&roundkeys);
+            assert_eq!(plaintext, decrypted.grid);
+        }
+    }
+
+    #[test]
+    fn encrypt_decrypt_in_place() {
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&(0..16).collect::<Vec<u8>>()).unwrap();
+        let plaintext: [u8; 16] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+        let ciphertext: [u8; 16] = [0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a];
+        let mut block = plaintext;
+        AESBlock::<DecryptedState>::encrypt_in_place(&mut block, &roundkeys).unwrap();
+        assert_eq!(block, ciphertext);
+        AESBlock::<EncryptedState>::decrypt_in_place(&mut block, &roundkeys).unwrap();
+        assert_eq!(block, plaintext);
+        let mut data = [plaintext, plaintext, plaintext].concat();
+        AESData::<DecryptedState>::encrypt_in_place(&mut data, &roundkeys).unwrap();
+        assert_eq!(data, [ciphertext, ciphertext, ciphertext].concat());
+        AESData::<EncryptedState>::decrypt_in_place(&mut data, &roundkeys).unwrap();
+        assert_eq!(data, [plaintext, plaintext, plaintext].concat());
+        assert_eq!(AESBlock::<DecryptedState>::encrypt_in_place(&mut [0; 17], &roundkeys), Err(AesError::InvalidDataLength { length: 17 }));
+        assert_eq!(AESData::<EncryptedState>::decrypt_in_place(&mut [0; 20], &roundkeys), Err(AesError::InvalidDataLength { length: 20 }));
+    }
+
+    #[test]
+    fn encrypt_decrypt_blocks() {
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&[3; 24]).unwrap();
+        for length in [0, 1, 16, 40] {
+            let data = AESData::<DecryptedState>::new((0..length).collect());
+            let encrypted = data.encrypt(&roundkeys, &Pkcs7);
+            let blocks: Vec<[u8; 16]> = data.encrypt_blocks(&roundkeys, &Pkcs7).collect();
+            assert_eq!(blocks.concat(), encrypted.data);
+            let decrypted: Vec<[u8; 16]> = encrypted.decrypt_blocks(&roundkeys).unwrap().collect();
+            assert_eq!(decrypted.concat(), Pkcs7.pad(&data.data, 16));
+        }
+        assert!(AESData::<EncryptedState>::new(vec![0; 17]).decrypt_blocks(&roundkeys).is_err());
+    }
+
+    #[test]
+    fn pkcs7_padding() {
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&[0; 16]).unwrap();
+        for length in [0, 1, 15, 16, 17, 32] {
+            let data: Vec<u8> = vec![7; length];
+            let encrypted = AESData::<DecryptedState>::new(data.clone()).encrypt(&roundkeys, &Pkcs7);
+            assert_eq!(encrypted.data.len(), (length / 16 + 1) * 16);
+            assert_eq!(data, encrypted.decrypt(&roundkeys, &Pkcs7).unwrap().data);
+        }
+    }
+
+    #[test]
+    fn encrypt_decrypt_padding_schemes() {
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&[0; 24]).unwrap();
+        let data: Vec<u8> = b"Padded with each of the schemes".to_vec();
+        let padders: Vec<Box<dyn Padding>> = vec![Box::new(Pkcs7), Box::new(padding::AnsiX923), Box::new(padding::Iso7816), Box::new(padding::ZeroPadding)];
+        for padder in padders {
+            let encrypted = AESData::<DecryptedState>::new(data.clone()).encrypt(&roundkeys, padder.as_ref());
+            assert_eq!(encrypted.data.len(), 32);
+            assert_eq!(data, encrypted.decrypt(&roundkeys, padder.as_ref()).unwrap().data);
+        }
+    }
+
+    #[test]
+    fn encrypt_decrypt_ctr_file() {
+        let bytes = std::fs::read("testdata/binary.in").unwrap();
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&[3; 16]).unwrap();
+        let counter = ctr::Counter::from_nonce(&[9; 12], 0);
+        let encrypted = AESData::<DecryptedState>::new(bytes.clone()).encrypt_ctr(&roundkeys, counter);
+        assert_eq!(encrypted.data.len(), bytes.len());
+        assert_ne!(encrypted.data, bytes);
+        assert_eq!(bytes, encrypted.decrypt_ctr(&roundkeys, counter).data);
+    }
+
+    #[test]
+    fn encrypt_decrypt_stream_modes() {
+        let bytes = std::fs::read("testdata/testfile.in").unwrap();
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&[4; 32]).unwrap();
+        let modes = [StreamMode::Ctr(Counter::from_nonce(&[1; 12], 0)), StreamMode::Cfb { iv: [2; 16] }, StreamMode::Ofb { iv: [2; 16] }];
+        for mode in modes {
+            let encrypted = AESData::<DecryptedState>::new(bytes.clone()).encrypt_stream(&roundkeys, mode);
+            assert_eq!(encrypted.data.len(), bytes.len());
+            assert_eq!(bytes, encrypted.decrypt_stream(&roundkeys, mode).data);
+        }
+    }
+
+    #[test]
+    fn encrypt_decrypt_gcm_file() {
+        let bytes = std::fs::read("testdata/binary.in").unwrap();
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&[6; 32]).unwrap();
+        let (encrypted, tag) = AESData::<DecryptedState>::new(bytes.clone()).encrypt_gcm(&roundkeys, &[7; 12], b"binary.in");
+        assert_eq!(bytes, encrypted.decrypt_gcm(&roundkeys, &[7; 12], b"binary.in", &tag).unwrap().data);
+        assert_eq!(encrypted.decrypt_gcm(&roundkeys, &[7; 12], b"other.in", &tag).err(), Some(AesError::Authentication));
+    }
+
+    #[test]
+    fn pkcs7_keeps_trailing_bytes() {
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&[0; 16]).unwrap();
+        let data: Vec<u8> = vec![1, 2, 3, 0, 0, 0, 1, 1];
+        let encrypted = AESData::<DecryptedState>::new(data.clone()).encrypt(&roundkeys, &Pkcs7);
+        assert_eq!(data, encrypted.decrypt(&roundkeys, &Pkcs7).unwrap().data);
+    }
+
+    #[test]
+    fn pkcs7_malformed_padding() {
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&[0; 16]).unwrap();
+        let mut invalid_blocks: Vec<[u8; 16]> = vec![[0; 16], [17; 16]];
+        let mut inconsistent: [u8; 16] = [3; 16];
+        inconsistent[13] = 2;
+        invalid_blocks.push(inconsistent);
+        for block in invalid_blocks {
+            let encrypted = AESData::<EncryptedState>::new(AESBlock::<DecryptedState>::new(block).encrypt(&roundkeys).grid.to_vec());
+            assert_eq!(encrypted.decrypt(&roundkeys, &Pkcs7).err(), Some(AesError::Padding));
+        }
+    }
+
+    #[test]
+    fn decrypt_invalid_length() {
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&[0; 16]).unwrap();
+        assert_eq!(AESData::<EncryptedState>::new(Vec::new()).decrypt(&roundkeys, &Pkcs7).err(), Some(AesError::InvalidDataLength { length: 0 }));
+        assert_eq!(AESData::<EncryptedState>::new(vec![0; 20]).decrypt(&roundkeys, &Pkcs7).err(), Some(AesError::InvalidDataLength { length: 20 }));
+    }
+
+    #[test]
+    fn roundkeys_validation() {
+        assert_eq!(RoundKeys::new(vec![vec![0; 12]; 9]), Err(AesError::InvalidRoundKeys { count: 9 }));
+        assert_eq!(RoundKeys::new(vec![vec![0; 12]; 11]), Err(AesError::InvalidRoundKeys { count: 11 }));
+        let mut keys = vec![vec![0; 16]; 15];
+        keys[7].push(0);
+        assert_eq!(RoundKeys::new(keys), Err(AesError::InvalidRoundKeys { count: 15 }));
+        assert_eq!(RoundKeys::new(vec![vec![0; 16]; 13]).unwrap().key_size(), KeySize::Aes192);
+        assert_eq!(AESData::<EncryptedState>::generate_roundkeys(&[0; 32]).unwrap().key_size(), KeySize::Aes256);
+    }
+
+    #[test]
+    fn key_from_slice() {
+        assert_eq!(Key::from_slice(&[1; 16]), Ok(Key::Aes128([1; 16])));
+        assert_eq!(Key::from_slice(&[2; 24]), Ok(Key::Aes192([2; 24])));
+        assert_eq!(Key::from_slice(&[3; 32]), Ok(Key::Aes256([3; 32])));
+        assert_eq!(Key::from_slice(&[0; 17]), Err(AesError::InvalidKeyLength { length: 17 }));
+        assert_eq!(Key::from_slice(&[]), Err(AesError::InvalidKeyLength { length: 0 }));
+        let key = Key::Aes192([2; 24]);
+        assert_eq!(key.as_bytes(), &[2; 24]);
+        assert_eq!(key.roundkeys(), AESData::<EncryptedState>::generate_roundkeys(&[2; 24]).unwrap());
+    }
+
+    #[test]
+    fn generate_roundkeys_unsupported_length() {
+        assert_eq!(AESData::<EncryptedState>::generate_roundkeys(&[0; 20]), Err(AesError::InvalidKeyLength { length: 20 }));
+    }
+
+    #[test]
+    fn full_crypt_decrypt_256() {
+        let data = b"AES-256 uses fourteen rounds instead of ten.".to_vec();
+        let init_key: Vec<u8> = (0..32).collect();
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&init_key).unwrap();
+        let encrypted = AESData::<DecryptedState>::new(data.clone()).encrypt(&roundkeys, &Pkcs7);
+        assert_ne!(encrypted.data[..16], data[..16]);
+        let decrypted = encrypted.decrypt(&roundkeys, &Pkcs7).unwrap();
+        assert_eq!(data, decrypted.data);
     }
 
     #[test]
     fn full_crypt_decrypt() {
         let data = vec![0x00, 0x00, 0x01, 0x01, 0x03, 0x03, 0x07, 0x07, 0x0f, 0x0f, 0x1f, 0x1f, 0x3f, 0x3f, 0x7f, 0x7f];
         let init_key: Vec<u8> = vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
-        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&init_key);
+        let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&init_key).unwrap();
         let aes_data = AESData::<DecryptedState>::new(data.clone());
-        let encrypted = aes_data.encrypt(&roundkeys);
-        let decrypted = encrypted.decrypt(&roundkeys);
+        let encrypted = aes_data.encrypt(&roundkeys, &Pkcs7);
+        let decrypted = encrypted.decrypt(&roundkeys, &Pkcs7).unwrap();
         assert_eq!(data, decrypted.data);
     }
+
+    #[test]
+    fn self_test_passes() {
+        assert_eq!(self_test(), Ok(()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_key_and_data() {
+        let key = Key::from_slice(&[7; 24]).unwrap();
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(serde_json::from_str::<Key>(&json).unwrap(), key);
+        let roundkeys = key.roundkeys();
+        let encrypted = AESData::<DecryptedState>::new(b"Attack at dawn".to_vec()).encrypt(&roundkeys, &Pkcs7);
+        let json = serde_json::to_string(&encrypted).unwrap();
+        let encrypted: AESData<EncryptedState> = serde_json::from_str(&json).unwrap();
+        assert_eq!(encrypted.decrypt(&roundkeys, &Pkcs7).unwrap().data, b"Attack at dawn");
+    }
+
+    #[test]
+    fn hex_and_base64_armor() {
+        let roundkeys = Key::from_slice(&[7; 16]).unwrap().roundkeys();
+        let encrypted = AESData::<DecryptedState>::new(b"Attack at dawn".to_vec()).encrypt(&roundkeys, &Pkcs7);
+        let hex = encrypted.to_hex();
+        assert_eq!(hex.len(), 32);
+        assert_eq!(AESData::<EncryptedState>::from_hex(&hex.to_uppercase()).unwrap().decrypt(&roundkeys, &Pkcs7).unwrap().data, b"Attack at dawn");
+        let base64 = encrypted.to_base64();
+        assert_eq!(base64.len(), 24);
+        assert_eq!(AESData::<EncryptedState>::from_base64(&base64).unwrap().data, encrypted.data);
+        assert_eq!(AESData::<EncryptedState>::from_base64("####").err(), Some(AesError::InvalidEncoding { position: 0 }));
+    }
+
+    ///
+    /// The zero block encrypted with the all-zero keys starts with 66e94b, aae069 and dc95c0.
+    ///
+    #[test]
+    fn key_check_value() {
+        assert_eq!(Key::Aes128([0; 16]).check_value(), [0x66, 0xe9, 0x4b]);
+        assert_eq!(Key::Aes192([0; 24]).check_value(), [0xaa, 0xe0, 0x69]);
+        assert_eq!(Key::Aes256([0; 32]).check_value(), [0xdc, 0x95, 0xc0]);
+        assert_ne!(Key::Aes128([1; 16]).check_value(), Key::Aes128([0; 16]).check_value());
+    }
+
+    #[test]
+    fn constant_time_comparison() {
+        assert!(constant_time_eq(b"tag", b"tag"));
+        assert!(constant_time_eq(&[], &[]));
+        assert!(!constant_time_eq(b"tag", b"taG"));
+        assert!(!constant_time_eq(b"tag", b"tags"));
+    }
+
+    #[test]
+    fn progress_callback() {
+        let roundkeys = Key::from_slice(&[7; 16]).unwrap().roundkeys();
+        let data = AESData::<DecryptedState>::new(vec![3; PROGRESS_LENGTH + 100]);
+        let mut reports = Vec::new();
+        let encrypted = data.encrypt_with_progress(&roundkeys, &Pkcs7, |done, total| reports.push((done, total)));
+        let total = PROGRESS_LENGTH + 112;
+        assert_eq!(reports, [(PROGRESS_LENGTH, total), (total, total)]);
+        let mut reports = Vec::new();
+        let decrypted = encrypted.decrypt_with_progress(&roundkeys, &Pkcs7, |done, total| reports.push((done, total))).unwrap();
+        assert_eq!(decrypted.data, data.data);
+        assert_eq!(reports.last(), Some(&(total, total)));
+    }
 }
@@ -1,88 +1,725 @@
 mod args;
+mod config;
+mod failure;
+mod progress_bar;
 
-use std::fs;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, IsTerminal, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
 
-use aes::{AESData, DecryptedState, EncryptedState};
-use args::Args;
-use clap::Parser;
+use aes::archive::{self, ArchiveReader};
+use aes::encoding::to_hex;
+use aes::format::{FileMode, Header, SALT_LENGTH};
+use aes::kdf::{Kdf, KdfLimits};
+use aes::pipeline::Progress;
+use aes::{Cipher, Key, KeySchedule, KeySize};
+use args::{Args, Command, EncryptArgs, FileArgs, InspectArgs, KeygenArgs, PasswordArgs, VerifyArgs};
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use failure::Failure;
+use progress_bar::ProgressBar;
+use zeroize::Zeroizing;
 
 /**
- * This is a program for testing the AES encryption and decryption.
- * It reads a sequence of bytes from standard input, encrypts them using AES,
- * and then decrypts them back to verify correctness.
+ * The file name that stands for standard input or standard output.
  */
-fn main() {
-    let args = Args::parse();
-    if args.input_file.is_empty() || args.output_file.is_empty() {
-        eprintln!("Input and output files must be specified.");
-        return;
+const STDIO: &str = "-";
+
+/**
+ * The input file, standard input or the archive of a directory.
+ */
+type Input = Box<dyn Read>;
+
+/**
+ * This is a program for encrypting files with AES and a password. The encrypt and
+ * decrypt commands work on files, standard input and output, or directories with
+ * --recursive. verify checks that a file decrypts without writing anything, inspect
+ * prints the header of a file, keygen prints a random key and bench measures the
+ * speed of each mode.
+ * It exits with 0 on success, 2 for a usage error, 3 for an I/O error and 4 when
+ * the data does not decrypt or the self test fails.
+ */
+fn main() -> ExitCode {
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+    match run(args, &matches) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            eprintln!("{failure}");
+            failure.exit_code()
+        }
+    }
+}
+
+/**
+ * Runs the self test if it is asked for and then the command, with the options
+ * that are not given filled in from the profile.
+ */
+fn run(mut args: Args, matches: &ArgMatches) -> Result<(), Failure> {
+    config::apply_profile(&mut args, matches)?;
+    if args.selftest {
+        aes::self_test().map_err(|error| Failure::crypto("Self test failed", &error))?;
+        let output_file = match &args.command {
+            Some(Command::Encrypt(EncryptArgs { files, .. }) | Command::Decrypt(files)) => files.output_file.as_str(),
+            _ => "",
+        };
+        status(output_file, "Self test passed.");
+    }
+    match args.command {
+        Some(Command::Encrypt(arguments)) => run_encrypt(arguments),
+        Some(Command::Decrypt(files)) => run_decrypt(files),
+        Some(Command::Verify(arguments)) => run_verify(arguments),
+        Some(Command::Inspect(arguments)) => inspect(&arguments),
+        Some(Command::Keygen(arguments)) => keygen(&arguments),
+        Some(Command::Bench { key_size, length, milliseconds }) => bench(key_size.key_size(), length, Duration::from_millis(milliseconds)),
+        None if args.selftest => Ok(()),
+        None => Err(Failure::Usage("Please specify a command, see --help.".to_string())),
     }
-    if args.password.is_empty() {
-        eprintln!("Password must be specified.");
-        return;
+}
+
+/**
+ * Runs the encrypt command.
+ */
+fn run_encrypt(arguments: EncryptArgs) -> Result<(), Failure> {
+    let kdf = arguments.kdf();
+    let (mode, key_size) = (arguments.mode(), arguments.key_size());
+    let files = check_files(arguments.files, false)?;
+    kdf.check_limits(&files.limits.limits()).map_err(|error| Failure::usage("Invalid key derivation parameters", &error))?;
+    let password = read_password(&files.password, true)?;
+    let progress = show_progress(files.progress, files.quiet);
+    encrypt(&files.input_file, &files.output_file, &password, kdf, mode, key_size, files.chunk_size, files.jobs as usize, files.recursive, progress)?;
+    finish(&files.output_file, files.quiet);
+    Ok(())
+}
+
+/**
+ * Runs the decrypt command.
+ */
+fn run_decrypt(files: FileArgs) -> Result<(), Failure> {
+    let files = check_files(files, true)?;
+    let password = read_password(&files.password, false)?;
+    let progress = show_progress(files.progress, files.quiet);
+    decrypt(&files.input_file, &files.output_file, &password, &files.limits.limits(), files.chunk_size, files.jobs as usize, files.recursive, files.force, progress)?;
+    finish(&files.output_file, files.quiet);
+    Ok(())
+}
+
+/**
+ * Runs the verify command.
+ */
+fn run_verify(arguments: VerifyArgs) -> Result<(), Failure> {
+    check_chunk_size(arguments.chunk_size)?;
+    let password = read_password(&arguments.password, false)?;
+    let mode = verify(&arguments.input_file, &password, &arguments.limits.limits(), arguments.chunk_size, arguments.jobs as usize, show_progress(arguments.progress, arguments.quiet))?;
+    if arguments.quiet {
+        return Ok(());
     }
-    if args.encrypt {
-        encrypt(args.input_file, args.output_file, args.password);
-    } else if args.decrypt {
-        decrypt(args.input_file, args.output_file, args.password);
+    if mode.tag_length() > 0 {
+        println!("The password is correct and the file has not been changed.");
     } else {
-        eprintln!("Please specify either --encrypt or --decrypt.");
-        return;
+        println!("The file decrypts, but {} mode has no tag to detect a wrong password or changes.", mode_name(mode));
     }
-    println!("Operation completed successfully.");
+    Ok(())
 }
 
 /**
- * Generates a key from the provided password.
- * This is a simple key generation function that pads or truncates the password
- * to ensure it is 16 bytes long (128 bits), which is the required key size
- * for AES encryption.
+ * Reports that encryption or decryption succeeded, unless --quiet is given.
  */
-fn generate_key(password: &[u8]) -> Vec<u8> {
-    // Simple key generation: pad or truncate to 16 bytes (128 bits)
-    let mut key = vec![0u8; 16];
-    for (i, &b) in password.iter().take(16).enumerate() {
-        key[i] = b;
+fn finish(output_file: &str, quiet: bool) {
+    if !quiet {
+        status(output_file, "Operation completed successfully.");
     }
-    key
+}
+
+/**
+ * Decides whether to show the progress bar. It is shown when standard error is a
+ * terminal, so it does not end up in logs, or when it is asked for.
+ */
+fn show_progress(progress: bool, quiet: bool) -> bool {
+    !quiet && (progress || io::stderr().is_terminal())
+}
+
+/**
+ * Checks the arguments of the encrypt and decrypt commands that clap can not
+ * check on its own.
+ *
+ * # Arguments
+ * * `files`: The arguments of the command.
+ * * `decrypt`: The command is decrypt.
+ *
+ * # Returns
+ * The arguments, with the input file as the output file when it is replaced in place.
+ */
+fn check_files(mut files: FileArgs, decrypt: bool) -> Result<FileArgs, Failure> {
+    check_chunk_size(files.chunk_size)?;
+    if files.in_place {
+        if files.input_file == STDIO {
+            return Err(Failure::Usage("Standard input can not be replaced in place.".to_string()));
+        }
+        // The result is renamed over the input file once it is complete.
+        files.output_file = files.input_file.clone();
+    }
+    if files.recursive && ((!decrypt && files.input_file == STDIO) || (decrypt && files.output_file == STDIO)) {
+        return Err(Failure::Usage("A directory can not be read from standard input or written to standard output.".to_string()));
+    }
+    if !(files.force || files.in_place || files.output_file == STDIO || (decrypt && files.recursive)) && Path::new(&files.output_file).exists() {
+        return Err(Failure::Usage(format!("Output file {} already exists, use --force to overwrite it.", files.output_file)));
+    }
+    if !files.in_place && is_same_file(&files.input_file, &files.output_file) {
+        return Err(Failure::Usage("Input and output files must be different.".to_string()));
+    }
+    Ok(files)
+}
+
+/**
+ * Checks that the chunk size is a positive multiple of the block size.
+ */
+fn check_chunk_size(chunk_size: usize) -> Result<(), Failure> {
+    if chunk_size == 0 || !chunk_size.is_multiple_of(16) {
+        return Err(Failure::Usage("Chunk size must be a positive multiple of 16.".to_string()));
+    }
+    Ok(())
+}
+
+/**
+ * Measures how fast each mode encrypts a buffer in memory and prints the result.
+ * The backend is selected with the features when the program is built, so the
+ * backends are compared by running the builds one after another.
+ *
+ * # Arguments
+ * * `key_size`: The size of the random key.
+ * * `length`: The number of bytes encrypted at a time.
+ * * `duration`: The time spent on each mode.
+ *
+ */
+fn bench(key_size: KeySize, length: usize, duration: Duration) -> Result<(), Failure> {
+    if length == 0 {
+        return Err(Failure::Usage("Length must be at least 1.".to_string()));
+    }
+    println!("Backend {}, {} bit key, {length} bytes at a time.", aes::throughput::BACKEND, key_size.length() * 8);
+    for throughput in aes::throughput::measure_throughput(key_size, length, duration).map_err(|error| Failure::Io(format!("Failed to run benchmark: {error}.")))? {
+        println!("{:<4} {:>10.1} MB/s", throughput.mode, throughput.megabytes_per_second());
+    }
+    Ok(())
+}
+
+/**
+ * Generates a key from the provided password with the key derivation function.
+ * The length of the key is the key size in the header, 16, 24 or 32 bytes.
+ * Files from before the key size was stored use 16 byte keys.
+ */
+fn generate_key(password: &[u8], header: &Header) -> Result<Key, Failure> {
+    let key = Zeroizing::new(header.kdf.derive(password, &header.salt, header.key_size.length()));
+    Key::from_slice(&key).map_err(|error| Failure::crypto("Failed to generate key", &error))
+}
+
+/**
+ * Returns the first line read from the file descriptor given with --password-fd, which
+ * is only available on Unix. The file descriptor is left open, since a number that is
+ * not an open file descriptor only fails to read and must not be closed.
+ *
+ * # Arguments
+ * * `source`: The password arguments.
+ *
+ */
+#[cfg(unix)]
+fn read_password_fd(source: &PasswordArgs) -> Result<Option<Zeroizing<String>>, Failure> {
+    use std::os::fd::FromRawFd;
+    let Some(fd) = source.password_fd else {
+        return Ok(None);
+    };
+    // SAFETY: The caller opened the file descriptor for the program, like 3 with 3<password.txt,
+    // so nothing else in the program uses it.
+    let mut file = std::mem::ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+    let mut text = Zeroizing::new(String::new());
+    file.read_to_string(&mut text).map_err(|error| Failure::io(&format!("Failed to read password from file descriptor {fd}"), error))?;
+    Ok(Some(Zeroizing::new(text.lines().next().unwrap_or_default().to_string())))
+}
+
+/**
+ * Returns no password on platforms without file descriptors, where --password-fd is
+ * not an option and is rejected by the parser as a usage error.
+ */
+#[cfg(not(unix))]
+fn read_password_fd(_source: &PasswordArgs) -> Result<Option<Zeroizing<String>>, Failure> {
+    Ok(None)
+}
+
+/**
+ * Returns the password from the command line, an environment variable or a file
+ * descriptor, or asks for it on the terminal without showing what is typed. The
+ * prompt uses the terminal and not standard input, so it also works when the data
+ * is piped in. A file descriptor is read to the end and the first line is used,
+ * so a password file may end with a newline.
+ *
+ * # Arguments
+ * * `source`: The password given with --password, --password-env or --password-fd, if any.
+ * * `confirm`: Ask for the password twice, so a typing error does not make a file
+ *   that can not be decrypted.
+ *
+ */
+fn read_password(source: &PasswordArgs, confirm: bool) -> Result<Zeroizing<String>, Failure> {
+    let given = if let Some(password) = &source.password {
+        Some(Zeroizing::new(password.clone()))
+    } else if let Some(name) = &source.password_env {
+        Some(Zeroizing::new(std::env::var(name).map_err(|_| Failure::Usage(format!("Environment variable {name} is not set or not valid UTF-8.")))?))
+    } else {
+        read_password_fd(source)?
+    };
+    if let Some(password) = given {
+        if password.is_empty() {
+            return Err(Failure::Usage("Password must be specified.".to_string()));
+        }
+        return Ok(password);
+    }
+    let password = Zeroizing::new(rpassword::prompt_password("Password: ").map_err(|error| Failure::io("Failed to read password", error))?);
+    if password.is_empty() {
+        return Err(Failure::Usage("Password must be specified.".to_string()));
+    }
+    if confirm {
+        let repeated = Zeroizing::new(rpassword::prompt_password("Confirm password: ").map_err(|error| Failure::io("Failed to read password", error))?);
+        if *repeated != *password {
+            return Err(Failure::Usage("Passwords do not match.".to_string()));
+        }
+    }
+    Ok(password)
+}
+
+/**
+ * Reads a random salt from the operating system.
+ */
+fn generate_salt() -> Result<[u8; SALT_LENGTH], Failure> {
+    let mut salt = [0u8; SALT_LENGTH];
+    aes::rand::fill(&mut salt).map_err(|error| Failure::Io(format!("Failed to generate salt: {error}.")))?;
+    Ok(salt)
+}
+
+/**
+ * Generates a random iv or nonce with the length the mode needs.
+ */
+fn generate_iv(mode: FileMode) -> Result<Vec<u8>, Failure> {
+    let mut iv = vec![0u8; mode.iv_length()];
+    aes::rand::fill(&mut iv).map_err(|error| Failure::Io(format!("Failed to generate iv: {error}.")))?;
+    Ok(iv)
 }
 
 /**
  *  Encrypts the contents of the input file using AES encryption.
- *  The encrypted data is written to the output file.
- *  The password is used to generate the key for encryption.
+ *  The encrypted data is written to the output file after a header with the format
+ *  version, the key derivation function, its parameters, the salt used to generate
+ *  the key, the key size, the mode, the iv and the tag. The file is processed a chunk at a time,
+ *  so the memory used does not depend on the size of the file.
  *
  * # Arguments
- * * `input_file`: The path to the file containing the data to encrypt.
- * * `output_file`: The path to the file where the encrypted data will be written.
+ * * `input_file`: The path to the file containing the data to encrypt, or - for standard input.
+ * * `output_file`: The path to the file where the encrypted data will be written, or - for standard output.
  * * `password`: The password used to generate the key for encryption.
+ * * `kdf`: The key derivation function with its parameters.
+ * * `mode`: The mode of operation.
+ * * `key_size`: The size of the key derived from the password.
+ * * `chunk_size`: The number of bytes read at a time.
+ * * `jobs`: The number of threads each chunk is split over in ecb and ctr mode.
+ * * `recursive`: The input is a directory, which is encrypted as an archive.
+ * * `progress`: Draw a progress bar on standard error.
  *
  */
-fn encrypt(input_file: String, output_file: String, password: String) {
-    let password = generate_key(password.as_bytes());
-    let data = fs::read(&input_file).unwrap_or_else(|_| panic!("Failed to read input file: {input_file}"));
-    let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&password);
-    let aes_data = AESData::<DecryptedState>::new(data.clone());
-    let encrypted = aes_data.encrypt(&roundkeys);
-    fs::write(output_file, encrypted.data).unwrap_or_else(|_| panic!("Failed to write to output file"));
+#[allow(clippy::too_many_arguments)]
+fn encrypt(input_file: &str, output_file: &str, password: &str, kdf: Kdf, mode: FileMode, key_size: KeySize, chunk_size: usize, jobs: usize, recursive: bool, progress: bool) -> Result<(), Failure> {
+    let input = open_input(input_file, recursive, progress)?;
+    let header = Header::new(kdf, generate_salt()?, mode, &generate_iv(mode)?).map_err(|error| Failure::crypto("Failed to create header", &error))?.with_key_size(key_size);
+    let cipher = Cipher::new(&generate_key(password.as_bytes(), &header)?);
+    if output_file == STDIO {
+        // The tag is written into the header at the end, which needs an output that can seek.
+        return through_temp_file(
+            |output| header.encrypt_chunked_parallel(&cipher, input, output, chunk_size, jobs).map_err(|error| Failure::io("Failed to encrypt input file", error)),
+            copy_to_stdout,
+        );
+    }
+    write_output(output_file, |output| header.encrypt_chunked_parallel(&cipher, input, output, chunk_size, jobs).map_err(|error| Failure::io("Failed to encrypt input file", error)))
 }
 
 /**
  *  Decrypts the contents of the input file using AES encryption.
- *  The decrypted data is written to the output file.
- *  The password is used to generate the key for decryption.
+ *  The decrypted data is written to the output file a chunk at a time.
+ *  The password is used to generate the key for decryption with the key
+ *  derivation function and the salt from the header. In a file with recipients
+ *  that key unwraps the key the data is encrypted with. The output is written to
+ *  a temporary file that replaces the output file when decryption succeeds, so a
+ *  failed decryption leaves no partial output and an existing output file is
+ *  unchanged. In gcm mode nothing is written to standard output or extracted
+ *  from an archive unless the tag matches.
  *
  * # Arguments
- * * `input_file`: The path to the file containing the encrypted data.
- * * `output_file`: The path to the file where the decrypted data will be written.
+ * * `input_file`: The path to the file containing the encrypted data, or - for standard input.
+ * * `output_file`: The path to the file where the decrypted data will be written, or - for standard output.
  * * `password`: The password used to generate the key for decryption.
+ * * `limits`: The most the key derivation with the parameters of the header may cost.
+ * * `chunk_size`: The number of bytes read at a time.
+ * * `jobs`: The number of threads each chunk is split over in ecb and ctr mode.
+ * * `recursive`: The data is an archive, which is restored in the output directory.
+ * * `force`: Overwrite files that already exist in the output directory.
+ * * `progress`: Draw a progress bar on standard error.
  *
  */
-fn decrypt(input_file: String, output_file: String, password: String) {
-    let password = generate_key(password.as_bytes());
-    let data = fs::read(&input_file).unwrap_or_else(|_| panic!("Failed to read input file: {input_file}"));
-    let roundkeys = AESData::<EncryptedState>::generate_roundkeys(&password);
-    let aes_data = AESData::<EncryptedState>::new(data.clone());
-    let encrypted = aes_data.decrypt(&roundkeys);
-    fs::write(output_file, encrypted.data).unwrap_or_else(|_| panic!("Failed to write to output file"));
+#[allow(clippy::too_many_arguments)]
+fn decrypt(input_file: &str, output_file: &str, password: &str, limits: &KdfLimits, chunk_size: usize, jobs: usize, recursive: bool, force: bool, progress: bool) -> Result<(), Failure> {
+    let mut input = open_input(input_file, false, progress)?;
+    let header = Header::read(&mut input).map_err(|error| Failure::data("Failed to read header of input file", error))?;
+    header.kdf.check_limits(limits).map_err(|error| Failure::crypto("Failed to read header of input file", &error))?;
+    let key = header.content_key(&generate_key(password.as_bytes(), &header)?).map_err(|error| Failure::crypto("Failed to open input file", &error))?;
+    let cipher = Cipher::new(&key);
+    if recursive {
+        // The archive is decrypted and checked completely before any file is extracted.
+        let extract = |file: &mut File| archive::extract(file, Path::new(output_file), force).map_err(|error| Failure::data(&format!("Failed to extract directory {output_file}"), error));
+        return through_temp_file(|output| header.decrypt_chunked_parallel(&cipher, input, output, chunk_size, jobs).map_err(|error| Failure::data("Failed to decrypt input file", error)), extract);
+    }
+    if output_file == STDIO {
+        if header.mode.tag_length() == 0 {
+            return header.decrypt_chunked_parallel(&cipher, input, BufWriter::new(io::stdout().lock()), chunk_size, jobs).map_err(|error| Failure::data("Failed to decrypt input file", error));
+        }
+        // Nothing is written to standard output before the tag has been checked.
+        return through_temp_file(
+            |output| header.decrypt_chunked_parallel(&cipher, input, output, chunk_size, jobs).map_err(|error| Failure::data("Failed to decrypt input file", error)),
+            copy_to_stdout,
+        );
+    }
+    write_output(output_file, |output| header.decrypt_chunked_parallel(&cipher, input, output, chunk_size, jobs).map_err(|error| Failure::data("Failed to decrypt input file", error)))
+}
+
+/**
+ *  Decrypts the input file without writing the result, to check the password and,
+ *  in gcm mode, the tag. Modes without a tag can only fail on the padding, so a
+ *  wrong password or a changed file is not always found in them.
+ *
+ * # Arguments
+ * * `input_file`: The path to the file containing the encrypted data, or - for standard input.
+ * * `password`: The password used to generate the key for decryption.
+ * * `limits`: The most the key derivation with the parameters of the header may cost.
+ * * `chunk_size`: The number of bytes read at a time.
+ * * `jobs`: The number of threads each chunk is split over in ecb and ctr mode.
+ * * `progress`: Draw a progress bar on standard error.
+ *
+ * # Returns
+ * The mode of the file, or a message if it does not decrypt.
+ */
+fn verify(input_file: &str, password: &str, limits: &KdfLimits, chunk_size: usize, jobs: usize, progress: bool) -> Result<FileMode, Failure> {
+    let mut input = open_input(input_file, false, progress)?;
+    let header = Header::read(&mut input).map_err(|error| Failure::data("Failed to read header of input file", error))?;
+    header.kdf.check_limits(limits).map_err(|error| Failure::crypto("Failed to read header of input file", &error))?;
+    let key = header.content_key(&generate_key(password.as_bytes(), &header)?).map_err(|error| Failure::crypto("Failed to open input file", &error))?;
+    header.decrypt_chunked_parallel(&Cipher::new(&key), input, io::sink(), chunk_size, jobs).map_err(|error| Failure::data("Failed to decrypt input file", error))?;
+    Ok(header.mode)
+}
+
+/**
+ * Prints the header of an encrypted file, which is not secret and is read
+ * without the password. With --expand-key the key is derived from the password
+ * and printed with the round keys of the key schedule, to follow the cipher step
+ * by step. In a file with recipients the round keys are those of the content key,
+ * which the data is encrypted with.
+ *
+ * # Arguments
+ * * `arguments`: The path to the encrypted file, or - for standard input, and the password for --expand-key.
+ *
+ */
+fn inspect(arguments: &InspectArgs) -> Result<(), Failure> {
+    let header = Header::read(&mut open_input(&arguments.input_file, false, false)?).map_err(|error| Failure::data("Failed to read header of input file", error))?;
+    let kdf = match header.kdf {
+        Kdf::Pbkdf2 { iterations } => format!("PBKDF2-HMAC-SHA256, {iterations} iterations"),
+        Kdf::Argon2id(params) => format!("Argon2id, {} KiB memory, {} iterations, parallelism {}", params.memory, params.iterations, params.parallelism),
+        Kdf::Scrypt(params) => format!("scrypt, cost {}, block size {}, parallelism {}", params.n, params.r, params.p),
+    };
+    println!("Format version: {}", header.version());
+    println!("Key derivation: {kdf}");
+    println!("Salt:           {}", to_hex(&header.salt));
+    println!("Key size:       {} bits", header.key_size.length() * 8);
+    println!("Mode:           {}", mode_name(header.mode));
+    if !header.iv().is_empty() {
+        println!("Iv:             {}", to_hex(header.iv()));
+    }
+    if !header.tag().is_empty() {
+        println!("Tag:            {}", to_hex(header.tag()));
+    }
+    println!("Recipients:     {}", header.recipient_count());
+    if !arguments.expand_key {
+        return Ok(());
+    }
+    let password = read_password(&arguments.password, false)?;
+    header.kdf.check_limits(&arguments.limits.limits()).map_err(|error| Failure::crypto("Failed to read header of input file", &error))?;
+    let derived = generate_key(password.as_bytes(), &header)?;
+    let key = header.content_key(&derived).map_err(|error| Failure::crypto("Failed to open input file", &error))?;
+    println!("Derived key:    {}", to_hex(derived.as_bytes()));
+    if header.recipient_count() > 0 {
+        println!("Content key:    {}", to_hex(key.as_bytes()));
+    }
+    for (round, roundkey) in KeySchedule::new(&key).enumerate() {
+        println!("Round key {round:>2}:   {}", to_hex(&roundkey));
+    }
+    Ok(())
+}
+
+/**
+ * Generates a random key and writes it to a file that only the owner can read
+ * and write on Unix. An existing file keeps its contents unless it may be
+ * overwritten, and is then also limited to the owner.
+ *
+ * # Arguments
+ * * `arguments`: The key size, the file, the encoding and whether to overwrite.
+ *
+ */
+fn keygen(arguments: &KeygenArgs) -> Result<(), Failure> {
+    let key = aes::rand::generate_key(arguments.size.key_size()).map_err(|error| Failure::Io(format!("Failed to generate key: {error}.")))?;
+    let data = Zeroizing::new(if arguments.hex { format!("{}\n", to_hex(key.as_bytes())).into_bytes() } else { key.as_bytes().to_vec() });
+    if arguments.out == STDIO {
+        let mut output = io::stdout().lock();
+        return output.write_all(&data).and_then(|_| output.flush()).map_err(|error| Failure::io("Failed to write to standard output", error));
+    }
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if arguments.force {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let write = || -> io::Result<()> {
+        let mut file = options.open(&arguments.out)?;
+        // The mode only applies to new files, so an overwritten file is restricted here.
+        #[cfg(unix)]
+        file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+        file.write_all(&data)?;
+        file.sync_all()
+    };
+    write().map_err(|error| match error.kind() {
+        io::ErrorKind::AlreadyExists => Failure::Usage(format!("Output file {} already exists, use --force to overwrite it.", arguments.out)),
+        _ => Failure::io(&format!("Failed to write key to {}", arguments.out), error),
+    })?;
+    println!("Generated a {} bit key in {}.", arguments.size.key_size().length() * 8, arguments.out);
+    Ok(())
+}
+
+/**
+ * Returns the name of the mode as it is given to --mode.
+ */
+fn mode_name(mode: FileMode) -> &'static str {
+    match mode {
+        FileMode::Ecb => "ecb",
+        FileMode::Cbc => "cbc",
+        FileMode::Ctr => "ctr",
+        FileMode::Gcm => "gcm",
+    }
+}
+
+/**
+ * Opens the input file in a reader that reports how much of it has been read.
+ * Standard input has no known length, so the progress is shown in bytes.
+ *
+ * # Arguments
+ * * `input_file`: The path to the input file, or - for standard input.
+ * * `recursive`: The input is a directory, which is read as an archive.
+ * * `progress`: Draw a progress bar with the throughput and the time left on standard error.
+ *
+ */
+fn open_input(input_file: &str, recursive: bool, progress: bool) -> Result<Progress<Input, impl FnMut(u64, u64)>, Failure> {
+    let (input, total): (Input, u64) = if input_file == STDIO {
+        (Box::new(io::stdin().lock()), 0)
+    } else if recursive {
+        let archive = ArchiveReader::new(Path::new(input_file)).map_err(|error| Failure::io(&format!("Failed to read input directory {input_file}"), error))?;
+        let total = archive.length();
+        (Box::new(archive), total)
+    } else {
+        let file = File::open(input_file).map_err(|error| Failure::io(&format!("Failed to read input file {input_file}"), error))?;
+        let total = file.metadata().map_err(|error| Failure::io(&format!("Failed to read input file {input_file}"), error))?.len();
+        (Box::new(file), total)
+    };
+    let mut bar = progress.then(ProgressBar::new);
+    Ok(Progress::new(input, total, move |done, total| {
+        if let Some(bar) = &mut bar {
+            bar.update(done, total);
+        }
+    }))
+}
+
+/**
+ * Writes the output to a temporary file and passes it on when the operation
+ * succeeds. The temporary file can only be read by the owner, and on Unix it is
+ * removed as soon as it is created, so nothing is left behind if the program is
+ * killed. Elsewhere it is removed when the operation is done.
+ *
+ * # Arguments
+ * * `operation`: Writes the output to the file it is given.
+ * * `finish`: Reads the output from the start of the file.
+ *
+ */
+fn through_temp_file(operation: impl FnOnce(&mut File) -> Result<(), Failure>, finish: impl FnOnce(&mut File) -> Result<(), Failure>) -> Result<(), Failure> {
+    let (path, mut file) = create_temp_file(&std::env::temp_dir()).map_err(|error| Failure::io("Failed to create temporary file", error))?;
+    // An open file stays readable and writable after it is removed on Unix.
+    #[cfg(unix)]
+    fs::remove_file(&path).map_err(|error| Failure::io("Failed to create temporary file", error))?;
+    let result = operation(&mut file).and_then(|_| file.seek(SeekFrom::Start(0)).map_err(|error| Failure::io("Failed to read temporary file", error))).and_then(|_| finish(&mut file));
+    #[cfg(not(unix))]
+    let _ = fs::remove_file(&path);
+    result
+}
+
+/**
+ * Copies the file to standard output.
+ */
+fn copy_to_stdout(file: &mut File) -> Result<(), Failure> {
+    let mut output = io::stdout().lock();
+    io::copy(file, &mut output).and_then(|_| output.flush()).map(|_| ()).map_err(|error| Failure::io("Failed to write to standard output", error))
+}
+
+/**
+ * Prints a status message. It goes to standard error when the data is written to
+ * standard output, so the data stream only holds the data.
+ */
+fn status(output_file: &str, message: &str) {
+    if output_file == STDIO {
+        eprintln!("{message}");
+    } else {
+        println!("{message}");
+    }
+}
+
+/**
+ * Writes the output to a temporary file in the directory of the output file and
+ * renames it to the output file when the operation succeeds. An existing output
+ * file is only replaced by a complete result, and no partial or unauthenticated
+ * data is left behind when the operation fails. The temporary file is private,
+ * or gets the permissions of the file it replaces before anything is written to
+ * it, so the input file can be replaced in place and the data is never readable
+ * by more users than the file it replaces.
+ *
+ * # Arguments
+ * * `output_file`: The path to the output file.
+ * * `operation`: Writes the output to the file it is given.
+ *
+ */
+fn write_output(output_file: &str, operation: impl FnOnce(&mut File) -> Result<(), Failure>) -> Result<(), Failure> {
+    let output = Path::new(output_file);
+    let directory = output.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let failed = |error| Failure::io(&format!("Failed to write to output file {output_file}"), error);
+    let (path, mut file) = create_temp_file(directory).map_err(failed)?;
+    let result = match fs::metadata(output) {
+        Ok(metadata) => file.set_permissions(metadata.permissions()).map_err(failed),
+        Err(_) => Ok(()),
+    }
+    .and_then(|_| operation(&mut file))
+    .and_then(|_| file.sync_all().map_err(failed))
+    .and_then(|_| fs::rename(&path, output).map_err(failed));
+    if result.is_err() {
+        let _ = fs::remove_file(&path);
+    }
+    result
+}
+
+/**
+ * Creates a new file with a random name in the directory, open for reading and writing.
+ * On Unix only the owner can read and write it, since it may hold decrypted data.
+ *
+ * # Returns
+ * The path and the file.
+ */
+fn create_temp_file(directory: &Path) -> io::Result<(PathBuf, File)> {
+    let mut suffix = [0u8; 8];
+    aes::rand::fill(&mut suffix).map_err(io::Error::other)?;
+    let path = directory.join(format!(".aes-{}-{}.tmp", std::process::id(), u64::from_be_bytes(suffix)));
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let file = options.open(&path)?;
+    Ok((path, file))
+}
+
+/**
+ * Checks if the input and output are the same file, which would be overwritten
+ * while it is read.
+ */
+fn is_same_file(input_file: &str, output_file: &str) -> bool {
+    match (fs::canonicalize(input_file), fs::canonicalize(output_file)) {
+        (Ok(input), Ok(output)) => input == output,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_temp_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let (path, file) = create_temp_file(&std::env::temp_dir()).unwrap();
+        let mode = file.metadata().unwrap().permissions().mode();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_output_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let (path, _) = create_temp_file(&std::env::temp_dir()).unwrap();
+        let output = path.to_str().unwrap();
+        let mut modes = Vec::new();
+        write_output(output, |file| {
+            modes.push(file.metadata().unwrap().permissions().mode() & 0o777);
+            file.write_all(b"plaintext").map_err(|error| Failure::io("write", error))
+        })
+        .unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        write_output(output, |file| {
+            modes.push(file.metadata().unwrap().permissions().mode() & 0o777);
+            Ok(())
+        })
+        .unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        fs::remove_file(&path).unwrap();
+        assert_eq!(modes, vec![0o600, 0o640]);
+        assert_eq!(mode, 0o640);
+    }
+
+    #[test]
+    fn test_through_temp_file() {
+        let mut read = Vec::new();
+        through_temp_file(|file| file.write_all(b"plaintext").map_err(|error| Failure::io("write", error)), |file| file.read_to_end(&mut read).map(|_| ()).map_err(|error| Failure::io("read", error)))
+            .unwrap();
+        assert_eq!(read, b"plaintext");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_password_fd() {
+        use std::os::fd::AsRawFd;
+        let (path, mut file) = create_temp_file(&std::env::temp_dir()).unwrap();
+        file.write_all(b"secret\nignored\n").unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let source = PasswordArgs { password: None, password_env: None, password_fd: Some(file.as_raw_fd()) };
+        let password = read_password_fd(&source).unwrap();
+        drop(file);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(password.as_deref().map(String::as_str), Some("secret"));
+        let source = PasswordArgs { password: None, password_env: None, password_fd: None };
+        assert!(read_password_fd(&source).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_kdf_limit_failure() {
+        let limits = KdfLimits::default();
+        let failure = |kdf: Kdf| kdf.check_limits(&limits).map_err(|error| Failure::crypto("Failed to read header of input file", &error).to_string());
+        assert_eq!(
+            failure(Kdf::Argon2id(aes::argon2::Params { memory: 1 << 21, iterations: 1, parallelism: 1 })),
+            Err("Failed to read header of input file: the key derivation needs 2048 MiB of Argon2id memory, more than the limit of 1024 MiB set by --max-kdf-memory.".to_string())
+        );
+        assert_eq!(
+            failure(Kdf::Pbkdf2 { iterations: u32::MAX }),
+            Err("Failed to read header of input file: the key derivation PBKDF2 iterations 4294967295 is more than the limit of 10000000 set by --max-kdf-iterations.".to_string())
+        );
+        assert_eq!(failure(Kdf::Pbkdf2 { iterations: 0 }), Err("Failed to read header of input file: the key derivation PBKDF2 iterations 0 must be at least 1.".to_string()));
+    }
 }
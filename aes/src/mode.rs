@@ -0,0 +1,54 @@
+//!
+//! Selection of the stream modes, which turn the block cipher into a stream cipher
+//! and encrypt data of any length without padding.
+//!
+
+use crate::{RoundKeys, cfb, ctr, ofb};
+use alloc::vec::Vec;
+
+///
+/// StreamMode enum for the stream modes and their starting values.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamMode {
+    // Counter mode starting at the counter block.
+    Ctr(ctr::Counter),
+    // Cipher feedback with 128 bit segments, starting with the initialization vector.
+    Cfb { iv: [u8; 16] },
+    // Output feedback, starting with the initialization vector.
+    Ofb { iv: [u8; 16] },
+}
+
+impl StreamMode {
+    ///
+    /// Encrypts the data in the mode.
+    ///
+    /// data: A vector of bytes of any length.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    ///
+    /// result: A vector of bytes with the same length as the data.
+    ///
+    pub fn encrypt(&self, data: &[u8], roundkeys: &RoundKeys) -> Vec<u8> {
+        match self {
+            StreamMode::Ctr(counter) => ctr::apply_keystream(data, roundkeys, *counter),
+            StreamMode::Cfb { iv } => cfb::encrypt(data, roundkeys, *iv),
+            StreamMode::Ofb { iv } => ofb::apply_keystream(data, roundkeys, *iv),
+        }
+    }
+
+    ///
+    /// Decrypts the data in the mode.
+    ///
+    /// data: A vector of bytes of any length.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    ///
+    /// result: A vector of bytes with the same length as the data.
+    ///
+    pub fn decrypt(&self, data: &[u8], roundkeys: &RoundKeys) -> Vec<u8> {
+        match self {
+            StreamMode::Ctr(counter) => ctr::apply_keystream(data, roundkeys, *counter),
+            StreamMode::Cfb { iv } => cfb::decrypt(data, roundkeys, *iv),
+            StreamMode::Ofb { iv } => ofb::apply_keystream(data, roundkeys, *iv),
+        }
+    }
+}
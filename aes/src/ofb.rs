@@ -0,0 +1,55 @@
+//!
+//! Output feedback mode. The initialization vector is encrypted again and again
+//! into a keystream that is xored with the data, so encryption and decryption are
+//! the same operation and a damaged byte only damages the same byte of the output.
+//! Description: https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation#Output_feedback_(OFB)
+//!
+
+use crate::{RoundKeys, encrypt_block};
+use alloc::vec::Vec;
+
+///
+/// Xors the data with the keystream from the repeatedly encrypted initialization vector.
+///
+/// data: A vector of bytes of any length.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+/// iv: The initialization vector. The same vector must never be used twice with the same key.
+///
+/// result: A vector of bytes with the same length as the data.
+///
+pub fn apply_keystream(data: &[u8], roundkeys: &RoundKeys, iv: [u8; 16]) -> Vec<u8> {
+    let mut keystream = iv;
+    data.chunks(16)
+        .flat_map(|chunk| {
+            keystream = encrypt_block(keystream, roundkeys);
+            chunk.iter().zip(&keystream).map(|(value, key)| value ^ key).collect::<Vec<u8>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AESData, DecryptedState};
+
+    ///
+    /// Example vector F.4.1 from NIST SP 800-38A.
+    ///
+    #[test]
+    fn test_sp800_38a() {
+        let roundkeys = AESData::<DecryptedState>::generate_roundkeys(&[0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c]).unwrap();
+        let iv = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f];
+        let plaintext: Vec<u8> = vec![
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e,
+            0x51, 0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef, 0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c,
+            0x37, 0x10,
+        ];
+        let ciphertext: Vec<u8> = vec![
+            0x3b, 0x3f, 0xd9, 0x2e, 0xb7, 0x2d, 0xad, 0x20, 0x33, 0x34, 0x49, 0xf8, 0xe8, 0x3c, 0xfb, 0x4a, 0x77, 0x89, 0x50, 0x8d, 0x16, 0x91, 0x8f, 0x03, 0xf5, 0x3c, 0x52, 0xda, 0xc5, 0x4e, 0xd8,
+            0x25, 0x97, 0x40, 0x05, 0x1e, 0x9c, 0x5f, 0xec, 0xf6, 0x43, 0x44, 0xf7, 0xa8, 0x22, 0x60, 0xed, 0xcc, 0x30, 0x4c, 0x65, 0x28, 0xf6, 0x59, 0xc7, 0x78, 0x66, 0xa5, 0x10, 0xd9, 0xc1, 0xd6,
+            0xae, 0x5e,
+        ];
+        assert_eq!(apply_keystream(&plaintext, &roundkeys, iv), ciphertext);
+        assert_eq!(apply_keystream(&ciphertext, &roundkeys, iv), plaintext);
+    }
+}
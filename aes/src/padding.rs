@@ -0,0 +1,218 @@
+//!
+//! Padding schemes for filling the last block of the data.
+//! Description: https://en.wikipedia.org/wiki/Padding_(cryptography)
+//!
+
+use crate::AesError;
+use alloc::vec::Vec;
+
+///
+/// Padding trait for the schemes that extend the data to a multiple of the block size
+/// before encryption and remove the extension after decryption.
+///
+pub trait Padding {
+    ///
+    /// Pads the data.
+    ///
+    /// data: A vector of bytes to be padded.
+    /// block_size: The size of a block in bytes, between 1 and 255.
+    ///
+    /// result: A vector of bytes with a length that is a multiple of the block size.
+    ///
+    fn pad(&self, data: &[u8], block_size: usize) -> Vec<u8>;
+
+    ///
+    /// Removes the padding. The structure of the padding is checked, so data that was not
+    /// padded with the scheme gives an error instead of losing its last bytes.
+    ///
+    /// data: A vector of padded bytes.
+    /// block_size: The size of a block in bytes, between 1 and 255.
+    ///
+    /// result: The data without the padding, or an error if the data is not a whole number
+    ///         of blocks or the padding is malformed.
+    ///
+    fn unpad<'a>(&self, data: &'a [u8], block_size: usize) -> Result<&'a [u8], AesError>;
+}
+
+///
+/// PKCS#7 padding. Between 1 and block size bytes are added, each with the
+/// number of added bytes as value.
+///
+pub struct Pkcs7;
+
+///
+/// ANSI X9.23 padding. Between 1 and block size bytes are added. These are zero
+/// except the last, which is the number of added bytes.
+///
+pub struct AnsiX923;
+
+///
+/// ISO/IEC 7816-4 padding. A 0x80 byte is added, followed by zero bytes up to
+/// the end of the block.
+///
+pub struct Iso7816;
+
+///
+/// Zero padding. Zero bytes are added up to the end of the block, nothing if the
+/// data already fills the last block. Trailing zero bytes in the data are removed
+/// with the padding, so it should only be used for data that can not end with zero.
+///
+pub struct ZeroPadding;
+
+///
+/// Returns the number of bytes needed to fill the last block. This is a whole block
+/// when the data already fills the last block.
+///
+fn padding_length(data: &[u8], block_size: usize) -> usize {
+    block_size - data.len() % block_size
+}
+
+///
+/// Checks that the padded data is a whole number of blocks.
+///
+fn check_length(data: &[u8], block_size: usize) -> Result<(), AesError> {
+    if !data.len().is_multiple_of(block_size) {
+        return Err(AesError::Padding);
+    }
+    Ok(())
+}
+
+///
+/// Returns the number of padding bytes given by the last byte, after checking that
+/// it is between 1 and the block size and no longer than the data.
+///
+fn counted_padding(data: &[u8], block_size: usize) -> Result<usize, AesError> {
+    check_length(data, block_size)?;
+    let padding = *data.last().ok_or(AesError::Padding)? as usize;
+    if padding == 0 || padding > block_size || padding > data.len() {
+        return Err(AesError::Padding);
+    }
+    Ok(padding)
+}
+
+impl Padding for Pkcs7 {
+    fn pad(&self, data: &[u8], block_size: usize) -> Vec<u8> {
+        let padding = padding_length(data, block_size);
+        data.iter().copied().chain(core::iter::repeat_n(padding as u8, padding)).collect()
+    }
+
+    fn unpad<'a>(&self, data: &'a [u8], block_size: usize) -> Result<&'a [u8], AesError> {
+        let padding = counted_padding(data, block_size)?;
+        let (data, padded) = data.split_at(data.len() - padding);
+        if padded.iter().any(|&value| value as usize != padding) {
+            return Err(AesError::Padding);
+        }
+        Ok(data)
+    }
+}
+
+impl Padding for AnsiX923 {
+    fn pad(&self, data: &[u8], block_size: usize) -> Vec<u8> {
+        let padding = padding_length(data, block_size);
+        data.iter().copied().chain(core::iter::repeat_n(0, padding - 1)).chain(core::iter::once(padding as u8)).collect()
+    }
+
+    fn unpad<'a>(&self, data: &'a [u8], block_size: usize) -> Result<&'a [u8], AesError> {
+        let padding = counted_padding(data, block_size)?;
+        let (data, padded) = data.split_at(data.len() - padding);
+        if padded[..padding - 1].iter().any(|&value| value != 0) {
+            return Err(AesError::Padding);
+        }
+        Ok(data)
+    }
+}
+
+impl Padding for Iso7816 {
+    fn pad(&self, data: &[u8], block_size: usize) -> Vec<u8> {
+        let padding = padding_length(data, block_size);
+        data.iter().copied().chain(core::iter::once(0x80)).chain(core::iter::repeat_n(0, padding - 1)).collect()
+    }
+
+    fn unpad<'a>(&self, data: &'a [u8], block_size: usize) -> Result<&'a [u8], AesError> {
+        check_length(data, block_size)?;
+        let last_block = data.len().saturating_sub(block_size);
+        let marker = data[last_block..].iter().rposition(|&value| value != 0).ok_or(AesError::Padding)?;
+        if data[last_block + marker] != 0x80 {
+            return Err(AesError::Padding);
+        }
+        Ok(&data[..last_block + marker])
+    }
+}
+
+impl Padding for ZeroPadding {
+    fn pad(&self, data: &[u8], block_size: usize) -> Vec<u8> {
+        let padding = padding_length(data, block_size) % block_size;
+        data.iter().copied().chain(core::iter::repeat_n(0, padding)).collect()
+    }
+
+    fn unpad<'a>(&self, data: &'a [u8], block_size: usize) -> Result<&'a [u8], AesError> {
+        check_length(data, block_size)?;
+        let last_block = data.len().saturating_sub(block_size);
+        let end = data[last_block..].iter().rposition(|&value| value != 0).map_or(last_block, |position| last_block + position + 1);
+        Ok(&data[..end])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn padders() -> Vec<Box<dyn Padding>> {
+        vec![Box::new(Pkcs7), Box::new(AnsiX923), Box::new(Iso7816), Box::new(ZeroPadding)]
+    }
+
+    #[test]
+    fn test_pad() {
+        let data = [0xaa, 0xbb, 0xcc];
+        assert_eq!(Pkcs7.pad(&data, 8), vec![0xaa, 0xbb, 0xcc, 5, 5, 5, 5, 5]);
+        assert_eq!(AnsiX923.pad(&data, 8), vec![0xaa, 0xbb, 0xcc, 0, 0, 0, 0, 5]);
+        assert_eq!(Iso7816.pad(&data, 8), vec![0xaa, 0xbb, 0xcc, 0x80, 0, 0, 0, 0]);
+        assert_eq!(ZeroPadding.pad(&data, 8), vec![0xaa, 0xbb, 0xcc, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_full_block() {
+        let data = [0xaa; 4];
+        assert_eq!(Pkcs7.pad(&data, 4), vec![0xaa, 0xaa, 0xaa, 0xaa, 4, 4, 4, 4]);
+        assert_eq!(AnsiX923.pad(&data, 4), vec![0xaa, 0xaa, 0xaa, 0xaa, 0, 0, 0, 4]);
+        assert_eq!(Iso7816.pad(&data, 4), vec![0xaa, 0xaa, 0xaa, 0xaa, 0x80, 0, 0, 0]);
+        assert_eq!(ZeroPadding.pad(&data, 4), data.to_vec());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for padder in padders() {
+            for length in 0..40 {
+                let data: Vec<u8> = (1..=length).collect();
+                let padded = padder.pad(&data, 16);
+                assert_eq!(padded.len() % 16, 0);
+                assert_eq!(padder.unpad(&padded, 16), Ok(&data[..]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert_eq!(Pkcs7.unpad(&[1, 2, 3, 0], 4), Err(AesError::Padding));
+        assert_eq!(Pkcs7.unpad(&[5, 5, 5, 5], 4), Err(AesError::Padding));
+        assert_eq!(Pkcs7.unpad(&[1, 3, 2, 3], 4), Err(AesError::Padding));
+        assert_eq!(Pkcs7.unpad(&[], 4), Err(AesError::Padding));
+        assert_eq!(AnsiX923.unpad(&[1, 1, 0, 3], 4), Err(AesError::Padding));
+        assert_eq!(AnsiX923.unpad(&[1, 2, 3, 0], 4), Err(AesError::Padding));
+        assert_eq!(Iso7816.unpad(&[1, 2, 3, 0], 4), Err(AesError::Padding));
+        assert_eq!(Iso7816.unpad(&[0x80, 0, 0, 0, 0, 0, 0, 0], 4), Err(AesError::Padding));
+        assert_eq!(Iso7816.unpad(&[], 4), Err(AesError::Padding));
+    }
+
+    ///
+    /// Data that is not a whole number of blocks was not padded, even if it ends like padding.
+    ///
+    #[test]
+    fn test_partial_block() {
+        assert_eq!(Pkcs7.unpad(&[1, 2, 3, 4, 1], 4), Err(AesError::Padding));
+        assert_eq!(AnsiX923.unpad(&[1, 2, 3, 4, 0, 2], 4), Err(AesError::Padding));
+        assert_eq!(Iso7816.unpad(&[1, 2, 3, 4, 0x80], 4), Err(AesError::Padding));
+        assert_eq!(ZeroPadding.unpad(&[1, 2, 3, 4, 5, 0], 4), Err(AesError::Padding));
+        assert_eq!(ZeroPadding.unpad(&[], 4), Ok(&[][..]));
+    }
+}
@@ -0,0 +1,72 @@
+//!
+//! PBKDF2 with HMAC-SHA-256 for deriving keys from passwords. Each 32 byte block of
+//! the key is the xor of a chain of HMAC iterations, so guessing a password costs as
+//! many HMAC computations as the iteration count.
+//! Description: https://en.wikipedia.org/wiki/PBKDF2
+//! Specification: RFC 8018.
+//!
+
+use crate::hmac::{Hmac, TAG_LENGTH};
+use alloc::vec::Vec;
+
+///
+/// Iteration count recommended for PBKDF2-HMAC-SHA256 by OWASP.
+///
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+
+///
+/// Derives a key from the password.
+///
+/// password: The password as bytes.
+/// salt: A random salt, stored with the data. At least 16 bytes are recommended.
+/// iterations: The number of HMAC iterations for each block, at least 1.
+/// length: The length of the derived key in bytes.
+///
+/// result: A vector of bytes with the derived key.
+///
+/// Panics if the iteration count is zero.
+///
+pub fn derive(password: &[u8], salt: &[u8], iterations: u32, length: usize) -> Vec<u8> {
+    assert!(iterations > 0, "Iteration count must be at least 1");
+    let hmac = Hmac::new(password);
+    (1..=length.div_ceil(TAG_LENGTH) as u32)
+        .flat_map(|index| {
+            let mut block = hmac.mac(&[salt, &index.to_be_bytes()].concat());
+            let mut result = block;
+            for _ in 1..iterations {
+                block = hmac.mac(&block);
+                for (value, byte) in result.iter_mut().zip(block) {
+                    *value ^= byte;
+                }
+            }
+            result
+        })
+        .take(length)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_iterations() {
+        assert_eq!(derive(b"password", b"salt", 1, 32), hex("120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b"));
+        assert_eq!(derive(b"password", b"salt", 2, 32), hex("ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43"));
+        assert_eq!(derive(b"password", b"salt", 4096, 32), hex("c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"));
+    }
+
+    ///
+    /// The PBKDF2-HMAC-SHA256 test vector from section 11 of RFC 7914, longer than one block.
+    ///
+    #[test]
+    fn test_rfc7914() {
+        let expected = hex("55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783");
+        assert_eq!(derive(b"passwd", b"salt", 1, 64), expected);
+        assert_eq!(derive(b"passwd", b"salt", 1, 20), expected[..20]);
+    }
+}
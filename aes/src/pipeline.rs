@@ -0,0 +1,331 @@
+//!
+//! Encryption and decryption of data of any size with a fixed amount of memory. The input
+//! is read a chunk at a time, and each chunk is processed and written before the next is
+//! read, so a file of many gigabytes only needs a buffer of one chunk.
+//!
+//! GCM is processed in parts with the tag kept apart from the ciphertext, since the tag is
+//! only known at the end. When decrypting, the plaintext is written before the tag is
+//! verified, so the output must be discarded if the tag does not match. The modes that
+//! need the whole message at once are not supported.
+//!
+//! The progress of a long operation is followed by wrapping the input in a Progress reader.
+//!
+
+use std::io::{self, Read, Write};
+
+use crate::cipher::{Cipher, Mode};
+use crate::gcm::{Gcm, TAG_LENGTH};
+use crate::stream::Chain;
+use crate::{AesError, RoundKeys};
+use alloc::vec::Vec;
+use zeroize::Zeroizing;
+
+///
+/// Default length of a chunk in bytes.
+///
+pub const DEFAULT_CHUNK_LENGTH: usize = 1024 * 1024;
+
+///
+/// Engine enum with the state of the mode between chunks.
+///
+enum Engine<'a> {
+    // The block and stream modes.
+    Chain(Chain),
+    // Galois/Counter mode.
+    Gcm(Gcm<'a>),
+}
+
+impl<'a> Engine<'a> {
+    fn new(mode: &Mode, roundkeys: &'a RoundKeys) -> Result<Engine<'a>, AesError> {
+        match *mode {
+            Mode::Gcm { nonce: [], .. } => Err(AesError::InvalidNonceLength { length: 0 }),
+            Mode::Gcm { nonce, aad } => Ok(Engine::Gcm(Gcm::new(roundkeys, nonce, aad))),
+            Mode::Ccm { .. } | Mode::GcmSiv { .. } | Mode::Eax { .. } => Err(AesError::UnsupportedMode),
+            _ => Ok(Engine::Chain(Chain::new(mode))),
+        }
+    }
+
+    fn is_padded(&self) -> bool {
+        matches!(self, Engine::Chain(chain) if chain.is_padded())
+    }
+
+    fn encrypt(&mut self, data: &mut [u8], roundkeys: &RoundKeys, jobs: usize) {
+        match self {
+            Engine::Chain(chain) => chain.split(data, roundkeys, jobs, Chain::encrypt),
+            Engine::Gcm(gcm) => gcm.encrypt(data),
+        }
+    }
+
+    fn decrypt(&mut self, data: &mut [u8], roundkeys: &RoundKeys, jobs: usize) {
+        match self {
+            Engine::Chain(chain) => chain.split(data, roundkeys, jobs, Chain::decrypt),
+            Engine::Gcm(gcm) => gcm.decrypt(data),
+        }
+    }
+}
+
+///
+/// Encrypts everything read from the input into the output a chunk at a time.
+///
+/// cipher: The cipher with the key and the padding.
+/// mode: The mode with its parameters. CCM, GCM-SIV and EAX are not supported.
+/// input: The reader with the plaintext.
+/// output: The writer that receives the ciphertext.
+/// chunk_length: Number of bytes read at a time, a multiple of 16.
+///
+/// result: The tag, empty in the modes without one, or an error if reading or writing fails
+///         or the mode is not supported.
+///
+/// Panics if the chunk length is zero or not a multiple of 16.
+///
+pub fn encrypt<R: Read, W: Write>(cipher: &Cipher, mode: Mode, input: R, output: W, chunk_length: usize) -> io::Result<Vec<u8>> {
+    encrypt_parallel(cipher, mode, input, output, chunk_length, 1)
+}
+
+///
+/// Encrypts like encrypt, with each chunk split over a number of threads in ECB and CTR.
+/// The chunks are still written in order, so the output is the same as from encrypt.
+///
+/// jobs: Number of threads each chunk is split over. The other modes use one thread.
+///
+pub fn encrypt_parallel<R: Read, W: Write>(cipher: &Cipher, mode: Mode, mut input: R, mut output: W, chunk_length: usize, jobs: usize) -> io::Result<Vec<u8>> {
+    assert!(chunk_length > 0 && chunk_length.is_multiple_of(16), "Chunk length must be a positive multiple of 16: {chunk_length}");
+    let roundkeys = cipher.roundkeys();
+    let mut engine = Engine::new(&mode, roundkeys).map_err(to_io_error)?;
+    let mut buffer = Zeroizing::new(vec![0u8; chunk_length]);
+    loop {
+        let count = read_full(&mut input, &mut buffer)?;
+        if count == chunk_length {
+            engine.encrypt(&mut buffer, roundkeys, jobs);
+            output.write_all(&buffer)?;
+            continue;
+        }
+        // The padding of the whole data only depends on the part after the last whole block.
+        let mut last = Zeroizing::new(if engine.is_padded() { cipher.padding().pad(&buffer[..count], 16) } else { buffer[..count].to_vec() });
+        engine.encrypt(&mut last, roundkeys, jobs);
+        output.write_all(&last)?;
+        output.flush()?;
+        return Ok(match engine {
+            Engine::Gcm(gcm) => gcm.finalize().to_vec(),
+            Engine::Chain(_) => Vec::new(),
+        });
+    }
+}
+
+///
+/// Decrypts everything read from the input into the output a chunk at a time. In GCM the
+/// plaintext is written before the tag is verified, so the output must be discarded if
+/// this returns an error.
+///
+/// cipher: The cipher with the key and the padding used when encrypting.
+/// mode: The mode with the parameters used when encrypting.
+/// input: The reader with the ciphertext.
+/// output: The writer that receives the plaintext.
+/// tag: The tag returned by encrypt, empty in the modes without one.
+/// chunk_length: Number of bytes read at a time, a multiple of 16.
+///
+/// result: Ok, or an error if reading or writing fails, the mode is not supported, the
+///         padding is malformed or the tag does not match.
+///
+/// Panics if the chunk length is zero or not a multiple of 16.
+///
+pub fn decrypt<R: Read, W: Write>(cipher: &Cipher, mode: Mode, input: R, output: W, tag: &[u8], chunk_length: usize) -> io::Result<()> {
+    decrypt_parallel(cipher, mode, input, output, tag, chunk_length, 1)
+}
+
+///
+/// Decrypts like decrypt, with each chunk split over a number of threads in ECB and CTR.
+///
+/// jobs: Number of threads each chunk is split over. The other modes use one thread.
+///
+pub fn decrypt_parallel<R: Read, W: Write>(cipher: &Cipher, mode: Mode, mut input: R, mut output: W, tag: &[u8], chunk_length: usize, jobs: usize) -> io::Result<()> {
+    assert!(chunk_length > 0 && chunk_length.is_multiple_of(16), "Chunk length must be a positive multiple of 16: {chunk_length}");
+    let roundkeys = cipher.roundkeys();
+    let mut engine = Engine::new(&mode, roundkeys).map_err(to_io_error)?;
+    let expected = match engine {
+        Engine::Gcm(_) => Some(<[u8; TAG_LENGTH]>::try_from(tag).map_err(|_| to_io_error(AesError::InvalidTagLength { length: tag.len() }))?),
+        Engine::Chain(_) => None,
+    };
+    // One block more than a chunk, since the padded modes keep the last block until the end.
+    let mut buffer = Zeroizing::new(vec![0u8; chunk_length + 16]);
+    let mut kept = 0;
+    let mut total = 0;
+    loop {
+        let count = kept + read_full(&mut input, &mut buffer[kept..])?;
+        total += count - kept;
+        if count < buffer.len() {
+            let last = &mut buffer[..count];
+            if engine.is_padded() {
+                if count == 0 || !count.is_multiple_of(16) {
+                    return Err(to_io_error(AesError::InvalidDataLength { length: total }));
+                }
+                engine.decrypt(last, roundkeys, jobs);
+                output.write_all(cipher.padding().unpad(last, 16).map_err(to_io_error)?)?;
+            } else {
+                engine.decrypt(last, roundkeys, jobs);
+                output.write_all(last)?;
+            }
+            output.flush()?;
+            return match (engine, expected) {
+                (Engine::Gcm(gcm), Some(expected)) => gcm.verify(&expected).map_err(to_io_error),
+                _ => Ok(()),
+            };
+        }
+        let length = if engine.is_padded() { count - 16 } else { count };
+        engine.decrypt(&mut buffer[..length], roundkeys, jobs);
+        output.write_all(&buffer[..length])?;
+        buffer.copy_within(length..count, 0);
+        kept = count - length;
+    }
+}
+
+///
+/// Progress struct that reports the number of bytes read from the inner reader, so the
+/// progress of encrypt and decrypt can be shown.
+///
+pub struct Progress<R: Read, F: FnMut(u64, u64)> {
+    inner: R,
+    // Bytes read so far and the expected total, normally the length of the file.
+    done: u64,
+    total: u64,
+    callback: F,
+}
+
+impl<R: Read, F: FnMut(u64, u64)> Progress<R, F> {
+    ///
+    /// Creates a reader that reports the progress of the inner reader.
+    ///
+    /// inner: The reader with the input.
+    /// total: The number of bytes the inner reader is expected to give.
+    /// callback: Called with the number of bytes read so far and the total after each read.
+    ///
+    /// result: A Progress struct.
+    ///
+    pub fn new(inner: R, total: u64, callback: F) -> Progress<R, F> {
+        Progress { inner, done: 0, total, callback }
+    }
+}
+
+impl<R: Read, F: FnMut(u64, u64)> Read for Progress<R, F> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buffer)?;
+        if count > 0 {
+            self.done += count as u64;
+            (self.callback)(self.done, self.total);
+        }
+        Ok(count)
+    }
+}
+
+///
+/// Reads until the buffer is full or the input has reached the end.
+///
+/// result: The number of bytes read, less than the length of the buffer only at the end.
+///
+fn read_full<R: Read>(input: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut count = 0;
+    while count < buffer.len() {
+        match input.read(&mut buffer[count..]) {
+            Ok(0) => break,
+            Ok(read) => count += read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(count)
+}
+
+///
+/// Errors from the cipher are reported as invalid data.
+///
+fn to_io_error(error: AesError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Key;
+    use crate::ctr::Counter;
+    use crate::padding::ZeroPadding;
+
+    fn modes<'a>() -> Vec<Mode<'a>> {
+        vec![
+            Mode::Ecb,
+            Mode::Cbc { iv: [1; 16] },
+            Mode::Ctr { counter: Counter::from_nonce(&[2; 12], 1) },
+            Mode::Cfb { iv: [3; 16] },
+            Mode::Ofb { iv: [4; 16] },
+            Mode::Gcm { nonce: &[5; 12], aad: b"header" },
+        ]
+    }
+
+    ///
+    /// The chunks give the same ciphertext as the cipher, also when the data ends at a chunk.
+    ///
+    #[test]
+    fn test_same_as_cipher() {
+        for cipher in [Cipher::new(&Key::Aes128([9; 16])), Cipher::new(&Key::Aes256([9; 32])).with_padding(ZeroPadding)] {
+            for mode in modes() {
+                for length in [0, 1, 15, 16, 17, 63, 64, 65, 200] {
+                    let data: Vec<u8> = (0..length).map(|value| (value % 251) as u8 + 1).collect();
+                    let mut encrypted = Vec::new();
+                    let tag = encrypt(&cipher, mode, &data[..], &mut encrypted, 32).unwrap();
+                    assert_eq!(Ok([&encrypted[..], &tag].concat()), cipher.encrypt(mode, &data), "{mode:?}");
+                    // Empty data with zero padding encrypts to nothing, which the cipher also rejects.
+                    let mut decrypted = Vec::new();
+                    let result = decrypt(&cipher, mode, &encrypted[..], &mut decrypted, &tag, 32).map(|_| decrypted).map_err(|error| error.get_ref().unwrap().to_string());
+                    assert_eq!(result, cipher.decrypt(mode, &[&encrypted[..], &tag].concat()).map_err(|error| error.to_string()), "{mode:?}");
+                    assert!(result == Ok(data) || length == 0, "{mode:?}");
+                }
+            }
+        }
+    }
+
+    ///
+    /// Splitting the chunks over threads gives the same result, also with parts of partial blocks.
+    ///
+    #[test]
+    fn test_parallel() {
+        let cipher = Cipher::new(&Key::Aes128([9; 16]));
+        for mode in modes() {
+            for length in [0, 15, 16, 100, 1000, 4097] {
+                let data: Vec<u8> = (0..length).map(|value| (value % 251) as u8).collect();
+                let mut expected = Vec::new();
+                let tag = encrypt(&cipher, mode, &data[..], &mut expected, 1024).unwrap();
+                for jobs in [2, 3, 8] {
+                    let mut encrypted = Vec::new();
+                    assert_eq!(encrypt_parallel(&cipher, mode, &data[..], &mut encrypted, 1024, jobs).unwrap(), tag, "{mode:?}");
+                    assert_eq!(encrypted, expected, "{mode:?} {length} {jobs}");
+                    let mut decrypted = Vec::new();
+                    decrypt_parallel(&cipher, mode, &encrypted[..], &mut decrypted, &tag, 1024, jobs).unwrap();
+                    assert_eq!(decrypted, data, "{mode:?} {length} {jobs}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_progress() {
+        let cipher = Cipher::new(&Key::Aes128([9; 16]));
+        let mut reports = Vec::new();
+        let input = Progress::new(&[1u8; 100][..], 100, |done, total| reports.push((done, total)));
+        encrypt(&cipher, Mode::Ecb, input, &mut Vec::new(), 32).unwrap();
+        assert_eq!(reports, [(32, 100), (64, 100), (96, 100), (100, 100)]);
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        let cipher = Cipher::new(&Key::Aes128([9; 16]));
+        let error = decrypt(&cipher, Mode::Ecb, &[0u8; 20][..], &mut Vec::new(), &[], 16).unwrap_err();
+        assert_eq!(error.get_ref().unwrap().to_string(), AesError::InvalidDataLength { length: 20 }.to_string());
+        let mode = Mode::Gcm { nonce: &[1; 12], aad: &[] };
+        let mut encrypted = Vec::new();
+        let tag = encrypt(&cipher, mode, &b"Attack at dawn"[..], &mut encrypted, 16).unwrap();
+        encrypted[0] ^= 1;
+        let error = decrypt(&cipher, mode, &encrypted[..], &mut Vec::new(), &tag, 16).unwrap_err();
+        assert_eq!(error.get_ref().unwrap().to_string(), AesError::Authentication.to_string());
+        let error = encrypt(&cipher, Mode::Eax { nonce: &[1; 16], aad: &[] }, &b"data"[..], &mut Vec::new(), 16).unwrap_err();
+        assert_eq!(error.get_ref().unwrap().to_string(), AesError::UnsupportedMode.to_string());
+    }
+}
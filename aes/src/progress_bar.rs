@@ -0,0 +1,87 @@
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/**
+ * The number of characters in the bar.
+ */
+const WIDTH: usize = 30;
+
+/**
+ * The time before the bar is drawn the first time, so small files are done
+ * without one.
+ */
+const DELAY: Duration = Duration::from_millis(500);
+
+/**
+ * The shortest time between two redraws.
+ */
+const INTERVAL: Duration = Duration::from_millis(100);
+
+/**
+ * A progress bar on standard error with the percentage done, the throughput and
+ * the estimated time left. The line is ended when the bar is dropped.
+ */
+pub struct ProgressBar {
+    // When the first bytes were read, so the time spent deriving the key is not counted.
+    start: Option<Instant>,
+    // When the bar was last drawn, None before it is drawn the first time.
+    drawn: Option<Instant>,
+}
+
+impl ProgressBar {
+    /**
+     * Creates a progress bar that starts the clock at the first update.
+     */
+    pub fn new() -> ProgressBar {
+        ProgressBar { start: None, drawn: None }
+    }
+
+    /**
+     * Redraws the bar, at most once every interval and always when done.
+     *
+     * # Arguments
+     * * `done`: Bytes processed so far.
+     * * `total`: Bytes in total, or 0 when the length is not known. Only the bytes
+     *   and the throughput are shown then.
+     *
+     */
+    pub fn update(&mut self, done: u64, total: u64) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(*self.start.get_or_insert(now));
+        let finished = total > 0 && done >= total;
+        if elapsed < DELAY || (!finished && self.drawn.is_some_and(|drawn| now.duration_since(drawn) < INTERVAL)) {
+            return;
+        }
+        self.drawn = Some(now);
+        let rate = done as f64 / elapsed.as_secs_f64();
+        let line = if total == 0 {
+            format!("{:.1} MB  {:.1} MB/s", done as f64 / 1e6, rate / 1e6)
+        } else {
+            let fraction = (done as f64 / total as f64).min(1.0);
+            let filled = (fraction * WIDTH as f64) as usize;
+            let left = if rate > 0.0 { format_duration(Duration::from_secs_f64(total.saturating_sub(done) as f64 / rate)) } else { "-".to_string() };
+            format!("[{}{}] {:>3}%  {:.1} MB/s  ETA {left}", "#".repeat(filled), "-".repeat(WIDTH - filled), (fraction * 100.0) as u64, rate / 1e6)
+        };
+        // The spaces clear what is left of a longer previous line.
+        let mut error = io::stderr().lock();
+        let _ = write!(error, "\r{line:<70}");
+        let _ = error.flush();
+    }
+}
+
+impl Drop for ProgressBar {
+    fn drop(&mut self) {
+        if self.drawn.is_some() {
+            // Ends the line the bar was drawn on.
+            eprintln!();
+        }
+    }
+}
+
+/**
+ * Formats a duration as minutes and seconds, with hours when it is an hour or more.
+ */
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 { format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60) } else { format!("{}:{:02}", seconds / 60, seconds % 60) }
+}
@@ -0,0 +1,79 @@
+//!
+//! Random keys, initialization vectors and nonces from the random source of the operating
+//! system, so keys and ivs do not have to come from a home made generator.
+//!
+
+use crate::{AesError, Key, KeySize};
+use zeroize::Zeroizing;
+
+///
+/// Length of a nonce in bytes, the length GCM is designed for.
+///
+pub const NONCE_LENGTH: usize = 12;
+
+///
+/// Fills the buffer with random bytes.
+///
+/// buffer: The bytes to overwrite.
+///
+/// result: Ok, or an error if the operating system could not provide random bytes.
+///
+pub fn fill(buffer: &mut [u8]) -> Result<(), AesError> {
+    getrandom::fill(buffer).map_err(|_| AesError::Random)
+}
+
+///
+/// Generates a random key.
+///
+/// key_size: The size of the key.
+///
+/// result: A Key, or an error if the operating system could not provide random bytes.
+///
+pub fn generate_key(key_size: KeySize) -> Result<Key, AesError> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    fill(&mut key[..key_size.length()])?;
+    Key::from_slice(&key[..key_size.length()])
+}
+
+///
+/// Generates a random initialization vector for CBC, CFB or OFB.
+///
+/// result: 16 random bytes, or an error if the operating system could not provide random bytes.
+///
+pub fn generate_iv() -> Result<[u8; 16], AesError> {
+    let mut iv = [0u8; 16];
+    fill(&mut iv)?;
+    Ok(iv)
+}
+
+///
+/// Generates a random nonce. A random 12 byte nonce should not be used for more than 2^32
+/// messages with the same key.
+///
+/// result: 12 random bytes, or an error if the operating system could not provide random bytes.
+///
+pub fn generate_nonce() -> Result<[u8; NONCE_LENGTH], AesError> {
+    let mut nonce = [0u8; NONCE_LENGTH];
+    fill(&mut nonce)?;
+    Ok(nonce)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_key() {
+        for key_size in [KeySize::Aes128, KeySize::Aes192, KeySize::Aes256] {
+            let key = generate_key(key_size).unwrap();
+            assert_eq!(key.key_size(), key_size);
+            assert_ne!(key, generate_key(key_size).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_generate_iv_nonce() {
+        assert_ne!(generate_iv().unwrap(), generate_iv().unwrap());
+        assert_ne!(generate_nonce().unwrap(), generate_nonce().unwrap());
+    }
+}
@@ -0,0 +1,321 @@
+//!
+//! Rijndael with a block of 4, 6 or 8 columns, 128, 192 or 256 bits. AES is Rijndael with
+//! 4 columns. The state is a grid of 4 rows and a column count given as a generic
+//! parameter. The rows are shifted by offsets that depend on the column count, the number
+//! of rounds depends on the longer of the key and the block, and the key schedule gives a
+//! roundkey with as many words as there are columns. This is an educational extension,
+//! the block sizes other than 128 bits are not part of the AES standard.
+//! Description: https://en.wikipedia.org/wiki/Advanced_Encryption_Standard
+//! Specification: The Design of Rijndael, Daemen and Rijmen, section 3.
+//!
+
+use crate::key_schedule::sub_word;
+use crate::{AESBlock, AesError, DecryptedState, EncryptedState, Key};
+use alloc::vec::Vec;
+use gf256::xtime;
+use zeroize::Zeroize;
+
+///
+/// Rijndael struct with the roundkeys of a key for a block of COLUMNS columns.
+///
+pub struct Rijndael<const COLUMNS: usize> {
+    // The roundkeys as columns of 4 bytes.
+    roundkeys: Vec<[[u8; 4]; COLUMNS]>,
+}
+
+///
+/// Rijndael with a 256 bit block.
+///
+pub type Rijndael256 = Rijndael<8>;
+
+impl<const COLUMNS: usize> Rijndael<COLUMNS> {
+    ///
+    /// Length of a block in bytes.
+    ///
+    pub const BLOCK_LENGTH: usize = 4 * COLUMNS;
+
+    ///
+    /// The column counts Rijndael is specified for.
+    ///
+    const SUPPORTED: () = assert!(COLUMNS == 4 || COLUMNS == 6 || COLUMNS == 8, "Rijndael has 4, 6 or 8 columns");
+
+    ///
+    /// Creates the cipher with the roundkeys of the key.
+    ///
+    /// key: A 16, 24 or 32 byte key.
+    ///
+    /// result: A Rijndael struct.
+    ///
+    pub fn new(key: &Key) -> Rijndael<COLUMNS> {
+        let () = Self::SUPPORTED;
+        Rijndael { roundkeys: Rijndael::<COLUMNS>::key_schedule(key) }
+    }
+
+    ///
+    /// Number of rounds, 6 more than the number of words in the longer of the key and the block.
+    ///
+    pub fn rounds(&self) -> usize {
+        self.roundkeys.len() - 1
+    }
+
+    ///
+    /// Encrypts a single block in the buffer it is stored in.
+    ///
+    /// block: The bytes to encrypt, BLOCK_LENGTH long. They are replaced by the encrypted bytes.
+    ///
+    /// result: Ok, or an error if the block has the wrong length.
+    ///
+    pub fn encrypt_in_place(&self, block: &mut [u8]) -> Result<(), AesError> {
+        let mut state = Rijndael::<COLUMNS>::state(block)?;
+        let rounds = self.rounds();
+        add_roundkey(&mut state, &self.roundkeys[0]);
+        for (idx, roundkey) in self.roundkeys.iter().enumerate().skip(1) {
+            sub_bytes(&mut state);
+            shift_rows(&mut state);
+            // The final round has no column mixing.
+            if idx != rounds {
+                mix_columns(&mut state);
+            }
+            add_roundkey(&mut state, roundkey);
+        }
+        block.copy_from_slice(state.as_flattened());
+        state.zeroize();
+        Ok(())
+    }
+
+    ///
+    /// Decrypts a single block in the buffer it is stored in, with the steps of the
+    /// encryption inverted in the reverse order.
+    ///
+    /// block: The bytes to decrypt, BLOCK_LENGTH long. They are replaced by the decrypted bytes.
+    ///
+    /// result: Ok, or an error if the block has the wrong length.
+    ///
+    pub fn decrypt_in_place(&self, block: &mut [u8]) -> Result<(), AesError> {
+        let mut state = Rijndael::<COLUMNS>::state(block)?;
+        let rounds = self.rounds();
+        add_roundkey(&mut state, &self.roundkeys[rounds]);
+        for idx in (0..rounds).rev() {
+            inverse_shift_rows(&mut state);
+            inverse_sub_bytes(&mut state);
+            add_roundkey(&mut state, &self.roundkeys[idx]);
+            // The first roundkey was added before any column mixing.
+            if idx != 0 {
+                inverse_mix_columns(&mut state);
+            }
+        }
+        block.copy_from_slice(state.as_flattened());
+        state.zeroize();
+        Ok(())
+    }
+
+    ///
+    /// The state of a block, the bytes filling one column after the other.
+    ///
+    fn state(block: &[u8]) -> Result<[[u8; 4]; COLUMNS], AesError> {
+        if block.len() != Self::BLOCK_LENGTH {
+            return Err(AesError::InvalidDataLength { length: block.len() });
+        }
+        Ok(core::array::from_fn(|column| core::array::from_fn(|row| block[4 * column + row])))
+    }
+
+    ///
+    /// The Rijndael key schedule. It is the AES key schedule continued until there is a
+    /// word for every column of every roundkey.
+    ///
+    fn key_schedule(key: &Key) -> Vec<[[u8; 4]; COLUMNS]> {
+        let key_words = key.key_size().length() / 4;
+        let rounds = key_words.max(COLUMNS) + 6;
+        let mut words: Vec<[u8; 4]> = key.as_bytes().chunks_exact(4).map(|word| word.try_into().unwrap()).collect();
+        let mut r_con = 1u8;
+        for idx in key_words..COLUMNS * (rounds + 1) {
+            let mut temp = words[idx - 1];
+            if idx.is_multiple_of(key_words) {
+                temp = sub_word([temp[1], temp[2], temp[3], temp[0]]);
+                temp[0] ^= r_con;
+                r_con = xtime(r_con);
+            } else if key_words > 6 && idx % key_words == 4 {
+                // Longer keys get an extra substitution in the middle of each key.
+                temp = sub_word(temp);
+            }
+            let previous = words[idx - key_words];
+            words.push(core::array::from_fn(|n| previous[n] ^ temp[n]));
+        }
+        let roundkeys = words.chunks_exact(COLUMNS).map(|roundkey| roundkey.try_into().unwrap()).collect();
+        words.zeroize();
+        roundkeys
+    }
+}
+
+///
+/// The roundkeys reveal the key, so they are overwritten before the memory is freed.
+///
+impl<const COLUMNS: usize> Drop for Rijndael<COLUMNS> {
+    fn drop(&mut self) {
+        self.roundkeys.zeroize();
+    }
+}
+
+///
+/// How far each row is shifted to the left. The first row is not shifted, and with 8
+/// columns the last two rows are shifted one more than with 4 or 6.
+///
+fn offsets(columns: usize) -> [usize; 4] {
+    match columns {
+        8 => [0, 1, 3, 4],
+        _ => [0, 1, 2, 3],
+    }
+}
+
+///
+/// Shifts each row to the left by its offset.
+///
+fn shift_rows<const COLUMNS: usize>(state: &mut [[u8; 4]; COLUMNS]) {
+    let original = *state;
+    for (row, offset) in offsets(COLUMNS).into_iter().enumerate() {
+        for column in 0..COLUMNS {
+            state[column][row] = original[(column + offset) % COLUMNS][row];
+        }
+    }
+}
+
+///
+/// Shifts each row back to the right by its offset.
+///
+fn inverse_shift_rows<const COLUMNS: usize>(state: &mut [[u8; 4]; COLUMNS]) {
+    let original = *state;
+    for (row, offset) in offsets(COLUMNS).into_iter().enumerate() {
+        for column in 0..COLUMNS {
+            state[(column + offset) % COLUMNS][row] = original[column][row];
+        }
+    }
+}
+
+///
+/// Mixes each column with the AES column mixing.
+///
+fn mix_columns<const COLUMNS: usize>(state: &mut [[u8; 4]; COLUMNS]) {
+    let block = AESBlock::<DecryptedState>::new([0; 16]);
+    for column in state.iter_mut() {
+        *column = block.mix_column(*column);
+    }
+}
+
+///
+/// Inverts the column mixing of each column.
+///
+fn inverse_mix_columns<const COLUMNS: usize>(state: &mut [[u8; 4]; COLUMNS]) {
+    let block = AESBlock::<EncryptedState>::new([0; 16]);
+    for column in state.iter_mut() {
+        *column = block.mix_column(*column);
+    }
+}
+
+fn sub_bytes<const COLUMNS: usize>(state: &mut [[u8; 4]; COLUMNS]) {
+    for value in state.as_flattened_mut() {
+        *value = AESBlock::<DecryptedState>::S_BOX[*value as usize];
+    }
+}
+
+fn inverse_sub_bytes<const COLUMNS: usize>(state: &mut [[u8; 4]; COLUMNS]) {
+    for value in state.as_flattened_mut() {
+        *value = AESBlock::<EncryptedState>::INVERSE_S_BOX[*value as usize];
+    }
+}
+
+fn add_roundkey<const COLUMNS: usize>(state: &mut [[u8; 4]; COLUMNS], roundkey: &[[u8; 4]; COLUMNS]) {
+    for (value, key) in state.as_flattened_mut().iter_mut().zip(roundkey.as_flattened()) {
+        *value ^= key;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_vectors::FIPS_197;
+    use crate::{Cipher, Mode};
+
+    ///
+    /// With 4 columns Rijndael is AES, for all three key sizes.
+    ///
+    #[test]
+    fn test_same_as_aes() {
+        for vector in FIPS_197 {
+            let rijndael = Rijndael::<4>::new(&Key::from_slice(vector.key).unwrap());
+            let mut block = vector.plaintext.to_vec();
+            rijndael.encrypt_in_place(&mut block).unwrap();
+            assert_eq!(block, vector.ciphertext, "{}", vector.name);
+            rijndael.decrypt_in_place(&mut block).unwrap();
+            assert_eq!(block, vector.plaintext, "{}", vector.name);
+        }
+        let key = Key::from_slice(&[7; 24]).unwrap();
+        let data: Vec<u8> = (0..64).collect();
+        let expected = Cipher::new(&key).encrypt(Mode::Ecb, &data).unwrap();
+        let rijndael = Rijndael::<4>::new(&key);
+        for (chunk, expected) in data.chunks(16).zip(expected.chunks(16)) {
+            let mut block = chunk.to_vec();
+            rijndael.encrypt_in_place(&mut block).unwrap();
+            assert_eq!(block, expected);
+        }
+    }
+
+    #[test]
+    fn test_rounds() {
+        let keys = [Key::Aes128([1; 16]), Key::Aes192([2; 24]), Key::Aes256([3; 32])];
+        assert_eq!(keys.iter().map(|key| Rijndael::<4>::new(key).rounds()).collect::<Vec<_>>(), [10, 12, 14]);
+        assert_eq!(keys.iter().map(|key| Rijndael::<6>::new(key).rounds()).collect::<Vec<_>>(), [12, 12, 14]);
+        assert_eq!(keys.iter().map(|key| Rijndael256::new(key).rounds()).collect::<Vec<_>>(), [14, 14, 14]);
+    }
+
+    ///
+    /// Every block size decrypts what it encrypted, for every key size, and changes every
+    /// part of the block.
+    ///
+    #[test]
+    fn test_round_trip() {
+        fn check<const COLUMNS: usize>(key: &Key) {
+            let rijndael = Rijndael::<COLUMNS>::new(key);
+            let plaintext: Vec<u8> = (0..4 * COLUMNS as u8).collect();
+            let mut block = plaintext.clone();
+            rijndael.encrypt_in_place(&mut block).unwrap();
+            assert!(block.chunks(4).zip(plaintext.chunks(4)).all(|(encrypted, plain)| encrypted != plain));
+            rijndael.decrypt_in_place(&mut block).unwrap();
+            assert_eq!(block, plaintext);
+        }
+        for key in [Key::Aes128([1; 16]), Key::Aes192([2; 24]), Key::Aes256([3; 32])] {
+            check::<4>(&key);
+            check::<6>(&key);
+            check::<8>(&key);
+        }
+    }
+
+    ///
+    /// A 256 bit block encrypted with a 256 bit key. Regression vector, checked against a
+    /// separate implementation of the specification.
+    ///
+    #[test]
+    fn test_rijndael256() {
+        let key: Vec<u8> = (0..32).collect();
+        let mut block: Vec<u8> = (0..32).collect();
+        Rijndael256::new(&Key::from_slice(&key).unwrap()).encrypt_in_place(&mut block).unwrap();
+        let expected = "623d2bd4ca3796dc3d02ecf2f37fb637fd3da58509cebb67ab9265b04db51e7d";
+        assert_eq!(block, (0..64).step_by(2).map(|idx| u8::from_str_radix(&expected[idx..idx + 2], 16).unwrap()).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn test_shift_rows() {
+        let mut state: [[u8; 4]; 8] = core::array::from_fn(|column| core::array::from_fn(|row| (8 * row + column) as u8));
+        shift_rows(&mut state);
+        assert_eq!(state[0], [0, 9, 19, 28]);
+        assert_eq!(state[7], [7, 8, 18, 27]);
+        inverse_shift_rows(&mut state);
+        assert_eq!(state[0], [0, 8, 16, 24]);
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        let rijndael = Rijndael256::new(&Key::Aes128([1; 16]));
+        assert_eq!(rijndael.encrypt_in_place(&mut [0; 16]), Err(AesError::InvalidDataLength { length: 16 }));
+        assert_eq!(rijndael.decrypt_in_place(&mut [0; 33]), Err(AesError::InvalidDataLength { length: 33 }));
+    }
+}
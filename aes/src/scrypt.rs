@@ -0,0 +1,174 @@
+//!
+//! scrypt, a memory-hard key derivation function. PBKDF2 expands the password into
+//! blocks that are each mixed with ROMix, which fills a table of N states with the
+//! Salsa20/8 core and then reads it back in an order that depends on the data.
+//! Description: https://en.wikipedia.org/wiki/Scrypt
+//! Specification: RFC 7914.
+//!
+
+use crate::pbkdf2;
+use alloc::{vec, vec::Vec};
+
+///
+/// Params struct with the cost parameters.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Params {
+    /// CPU and memory cost, a power of two larger than 1. The memory used is 128 * r * n bytes.
+    pub n: u32,
+    /// Block size, at least 1.
+    pub r: u32,
+    /// Parallelization, the number of independent ROMix computations, at least 1.
+    pub p: u32,
+}
+
+impl Default for Params {
+    ///
+    /// The parameters recommended by OWASP, 128 MiB of memory.
+    ///
+    fn default() -> Self {
+        Params { n: 1 << 17, r: 8, p: 1 }
+    }
+}
+
+impl Params {
+    ///
+    /// The memory used by derive in bytes, 128 * r * (n + p) for the table of ROMix and
+    /// the blocks.
+    ///
+    /// result: The number of bytes, or None if it does not fit in a usize.
+    ///
+    pub fn memory(&self) -> Option<usize> {
+        let block_length = 128usize.checked_mul(usize::try_from(self.r).ok()?)?;
+        block_length.checked_mul(usize::try_from(self.n).ok()?.checked_add(usize::try_from(self.p).ok()?)?)
+    }
+}
+
+///
+/// The Salsa20/8 core, four double rounds over sixteen 32 bit words.
+///
+fn salsa20_8(block: &mut [u32; 16]) {
+    let mut x = *block;
+    let mut quarter = |a: usize, b: usize, c: usize, d: usize| {
+        x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+        x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+        x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+        x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+    };
+    for _ in 0..4 {
+        quarter(0, 4, 8, 12);
+        quarter(5, 9, 13, 1);
+        quarter(10, 14, 2, 6);
+        quarter(15, 3, 7, 11);
+        quarter(0, 1, 2, 3);
+        quarter(5, 6, 7, 4);
+        quarter(10, 11, 8, 9);
+        quarter(15, 12, 13, 14);
+    }
+    for (value, word) in block.iter_mut().zip(x) {
+        *value = value.wrapping_add(word);
+    }
+}
+
+///
+/// BlockMix with Salsa20/8 over 2 * r blocks of 16 words. The even blocks of the
+/// result are placed first, followed by the odd blocks.
+///
+fn block_mix(input: &[u32], output: &mut [u32]) {
+    let count = input.len() / 16;
+    let mut x: [u32; 16] = input[input.len() - 16..].try_into().unwrap();
+    for idx in 0..count {
+        for (value, word) in x.iter_mut().zip(&input[16 * idx..16 * idx + 16]) {
+            *value ^= word;
+        }
+        salsa20_8(&mut x);
+        let position = (idx / 2) + (idx % 2) * (count / 2);
+        output[16 * position..16 * position + 16].copy_from_slice(&x);
+    }
+}
+
+///
+/// ROMix, the sequential memory-hard mixing of one 128 * r byte block.
+///
+fn ro_mix(block: &mut [u32], n: usize) {
+    let length = block.len();
+    let mut table = vec![0u32; n.checked_mul(length).expect("The scrypt table does not fit in memory")];
+    let mut x = block.to_vec();
+    let mut y = vec![0u32; length];
+    for idx in 0..n {
+        table[idx * length..(idx + 1) * length].copy_from_slice(&x);
+        block_mix(&x, &mut y);
+        core::mem::swap(&mut x, &mut y);
+    }
+    for _ in 0..n {
+        let j = x[length - 16] as usize & (n - 1);
+        for (value, word) in x.iter_mut().zip(&table[j * length..(j + 1) * length]) {
+            *value ^= word;
+        }
+        block_mix(&x, &mut y);
+        core::mem::swap(&mut x, &mut y);
+    }
+    block.copy_from_slice(&x);
+}
+
+///
+/// Derives a key from the password.
+///
+/// password: The password as bytes.
+/// salt: A random salt, stored with the data. At least 16 bytes are recommended.
+/// params: The cost parameters.
+/// length: The length of the derived key in bytes.
+///
+/// result: A vector of bytes with the derived key.
+///
+/// Panics if n is not a power of two larger than 1, r or p is zero, or the memory
+/// does not fit in a usize.
+///
+pub fn derive(password: &[u8], salt: &[u8], params: &Params, length: usize) -> Vec<u8> {
+    let Params { n, r, p } = *params;
+    assert!(n > 1 && n.is_power_of_two(), "N must be a power of two larger than 1: {n}");
+    assert!(r >= 1 && p >= 1, "r and p must be at least 1: {r}, {p}");
+    assert!(params.memory().is_some(), "The memory of N {n}, r {r} and p {p} does not fit in a usize");
+    let block_length = 128 * r as usize;
+    let mut blocks = pbkdf2::derive(password, salt, 1, block_length.checked_mul(p as usize).expect("The scrypt blocks do not fit in memory"));
+    for chunk in blocks.chunks_mut(block_length) {
+        let mut words: Vec<u32> = chunk.chunks(4).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])).collect();
+        ro_mix(&mut words, n as usize);
+        for (bytes, word) in chunk.chunks_mut(4).zip(words) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+    }
+    pbkdf2::derive(password, &blocks, 1, length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    ///
+    /// Test vectors from section 12 of RFC 7914.
+    ///
+    #[test]
+    fn test_memory() {
+        assert_eq!(Params::default().memory(), Some(128 * 8 * ((1 << 17) + 1)));
+        assert_eq!(Params { n: 16, r: 1, p: 1 }.memory(), Some(128 * 17));
+        assert_eq!(Params { n: 1 << 31, r: u32::MAX, p: u32::MAX }.memory(), None);
+    }
+
+    #[test]
+    fn test_rfc7914() {
+        assert_eq!(
+            derive(b"", b"", &Params { n: 16, r: 1, p: 1 }, 64),
+            hex("77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906")
+        );
+        assert_eq!(
+            derive(b"password", b"NaCl", &Params { n: 1024, r: 8, p: 16 }, 64),
+            hex("fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b3731622eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640")
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//!
+//! SHA-256 hash function. The message is padded with a one bit, zeros and its length
+//! in bits, and the 64 byte blocks are compressed into eight 32 bit words of state.
+//! Description: https://en.wikipedia.org/wiki/SHA-2
+//! Specification: FIPS 180-4.
+//!
+
+use alloc::{vec, vec::Vec};
+
+///
+/// Length of a block in bytes.
+///
+pub const BLOCK_LENGTH: usize = 64;
+
+///
+/// Length of the hash in bytes.
+///
+pub const OUTPUT_LENGTH: usize = 32;
+
+///
+/// The first 32 bits of the fractional parts of the cube roots of the first 64 primes.
+///
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3, 0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+///
+/// The first 32 bits of the fractional parts of the square roots of the first 8 primes.
+///
+const INITIAL_STATE: [u32; 8] = [0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19];
+
+///
+/// Sha256 struct for hashing data that arrives in parts.
+///
+#[derive(Debug, Clone)]
+pub struct Sha256 {
+    state: [u32; 8],
+    buffer: Vec<u8>,
+    length: u64,
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha256 {
+    pub fn new() -> Sha256 {
+        Sha256 { state: INITIAL_STATE, buffer: Vec::with_capacity(BLOCK_LENGTH), length: 0 }
+    }
+
+    ///
+    /// Adds data to the hash. Whole blocks are compressed, the rest is kept until more data arrives.
+    ///
+    /// data: A vector of bytes of any length.
+    ///
+    pub fn update(&mut self, data: &[u8]) {
+        self.length += data.len() as u64;
+        let mut data = data;
+        if !self.buffer.is_empty() {
+            let take = (BLOCK_LENGTH - self.buffer.len()).min(data.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.buffer.len() < BLOCK_LENGTH {
+                return;
+            }
+            let block = core::mem::take(&mut self.buffer);
+            self.compress(&block);
+        }
+        let mut chunks = data.chunks_exact(BLOCK_LENGTH);
+        for block in &mut chunks {
+            self.compress(block);
+        }
+        self.buffer.extend_from_slice(chunks.remainder());
+    }
+
+    ///
+    /// Pads the message and returns the hash.
+    ///
+    pub fn finalize(mut self) -> [u8; OUTPUT_LENGTH] {
+        let bits = self.length * 8;
+        let mut padding = vec![0x80];
+        padding.resize((BLOCK_LENGTH + 55 - self.buffer.len()) % BLOCK_LENGTH + 1, 0);
+        padding.extend_from_slice(&bits.to_be_bytes());
+        self.update(&padding);
+        let mut hash = [0u8; OUTPUT_LENGTH];
+        for (chunk, word) in hash.chunks_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        hash
+    }
+
+    ///
+    /// Compresses one 64 byte block into the state.
+    ///
+    fn compress(&mut self, block: &[u8]) {
+        let mut schedule = [0u32; 64];
+        for (word, chunk) in schedule.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        for idx in 16..64 {
+            let s0 = schedule[idx - 15].rotate_right(7) ^ schedule[idx - 15].rotate_right(18) ^ (schedule[idx - 15] >> 3);
+            let s1 = schedule[idx - 2].rotate_right(17) ^ schedule[idx - 2].rotate_right(19) ^ (schedule[idx - 2] >> 10);
+            schedule[idx] = schedule[idx - 16].wrapping_add(s0).wrapping_add(schedule[idx - 7]).wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for idx in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let temp1 = h.wrapping_add(s1).wrapping_add(choice).wrapping_add(K[idx]).wrapping_add(schedule[idx]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(majority);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+        for (value, word) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *value = value.wrapping_add(word);
+        }
+    }
+}
+
+///
+/// Hashes the data.
+///
+/// data: A vector of bytes of any length.
+///
+/// result: The 32 byte hash.
+///
+pub fn digest(data: &[u8]) -> [u8; OUTPUT_LENGTH] {
+    let mut sha = Sha256::new();
+    sha.update(data);
+    sha.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_digest() {
+        assert_eq!(digest(b"").to_vec(), hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"));
+        assert_eq!(digest(b"abc").to_vec(), hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+        assert_eq!(digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq").to_vec(), hex("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"));
+    }
+
+    #[test]
+    fn test_update_in_parts() {
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        for split in [0, 1, 55, 56, 63, 64, 65, 500, 1000] {
+            let mut sha = Sha256::new();
+            sha.update(&data[..split]);
+            sha.update(&data[split..]);
+            assert_eq!(sha.finalize(), digest(&data), "split at {split}");
+        }
+    }
+}
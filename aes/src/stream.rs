@@ -0,0 +1,447 @@
+//!
+//! Adapters that encrypt into any writer and decrypt from any reader, like a file or a
+//! network stream. The data is processed a block at a time in the block and stream modes.
+//! The authenticated modes need the whole message to compute or check the tag, so they
+//! buffer it and process it at the end. Decryption never releases unauthenticated data.
+//!
+
+use std::io::{self, Read, Write};
+
+use crate::ctr::{Counter, Keystream};
+use crate::{AesError, Cipher, Mode, RoundKeys, decrypt_block, encrypt_block};
+
+///
+/// Number of bytes read from the inner reader at a time.
+///
+const READ_LENGTH: usize = 4096;
+
+///
+/// Chain enum with the state carried from one block to the next.
+///
+pub(crate) enum Chain {
+    // Electronic codebook, no state.
+    Ecb,
+    // Cipher block chaining with the previous ciphertext block.
+    Cbc { previous: [u8; 16] },
+    // Counter mode with the next counter block.
+    Ctr { counter: Counter },
+    // Counter mode with the keystream computed ahead on a worker thread.
+    Prefetched { keystream: Keystream },
+    // Cipher feedback with the previous ciphertext block.
+    Cfb { feedback: [u8; 16] },
+    // Output feedback with the previous keystream block.
+    Ofb { keystream: [u8; 16] },
+    // The authenticated modes, which process the whole message at the end.
+    Buffered,
+}
+
+impl Chain {
+    pub(crate) fn new(mode: &Mode) -> Chain {
+        match *mode {
+            Mode::Ecb => Chain::Ecb,
+            Mode::Cbc { iv } => Chain::Cbc { previous: iv },
+            Mode::Ctr { counter } => Chain::Ctr { counter },
+            Mode::Cfb { iv } => Chain::Cfb { feedback: iv },
+            Mode::Ofb { iv } => Chain::Ofb { keystream: iv },
+            Mode::Gcm { .. } | Mode::Ccm { .. } | Mode::GcmSiv { .. } | Mode::Eax { .. } => Chain::Buffered,
+        }
+    }
+
+    ///
+    /// Computes the keystream of counter mode ahead on a worker thread. The other modes are unchanged.
+    ///
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// blocks: Number of blocks computed ahead at a time.
+    ///
+    fn prefetch(self, roundkeys: &RoundKeys, blocks: usize) -> Chain {
+        match self {
+            Chain::Ctr { counter } => Chain::Prefetched { keystream: Keystream::new(roundkeys.clone(), counter, blocks) },
+            chain => chain,
+        }
+    }
+
+    ///
+    /// ECB and CBC pad the last block, so the data is a whole number of blocks.
+    ///
+    pub(crate) fn is_padded(&self) -> bool {
+        matches!(self, Chain::Ecb | Chain::Cbc { .. })
+    }
+
+    ///
+    /// Encrypts the data in the buffer it is stored in.
+    ///
+    /// data: Whole blocks, except for a last partial block in the stream modes.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    ///
+    pub(crate) fn encrypt(&mut self, data: &mut [u8], roundkeys: &RoundKeys) {
+        if let Chain::Prefetched { keystream } = self {
+            keystream.apply(data);
+            return;
+        }
+        for chunk in data.chunks_mut(16) {
+            match self {
+                Chain::Ecb => chunk.copy_from_slice(&encrypt_block(to_block(chunk), roundkeys)),
+                Chain::Cbc { previous } => {
+                    xor(chunk, previous);
+                    *previous = encrypt_block(to_block(chunk), roundkeys);
+                    chunk.copy_from_slice(previous);
+                }
+                Chain::Ctr { counter } => {
+                    xor(chunk, &encrypt_block(counter.block(), roundkeys));
+                    counter.increment();
+                }
+                Chain::Cfb { feedback } => {
+                    xor(chunk, &encrypt_block(*feedback, roundkeys));
+                    feedback[..chunk.len()].copy_from_slice(chunk);
+                }
+                Chain::Ofb { keystream } => {
+                    *keystream = encrypt_block(*keystream, roundkeys);
+                    xor(chunk, keystream);
+                }
+                Chain::Prefetched { .. } => unreachable!("The keystream is applied to all the data at once"),
+                Chain::Buffered => unreachable!("The authenticated modes are processed by the cipher"),
+            }
+        }
+    }
+
+    ///
+    /// Decrypts the data in the buffer it is stored in.
+    ///
+    /// data: Whole blocks, except for a last partial block in the stream modes.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    ///
+    pub(crate) fn decrypt(&mut self, data: &mut [u8], roundkeys: &RoundKeys) {
+        for chunk in data.chunks_mut(16) {
+            match self {
+                Chain::Ecb => chunk.copy_from_slice(&decrypt_block(to_block(chunk), roundkeys)),
+                Chain::Cbc { previous } => {
+                    let ciphertext = to_block(chunk);
+                    chunk.copy_from_slice(&decrypt_block(ciphertext, roundkeys));
+                    xor(chunk, previous);
+                    *previous = ciphertext;
+                }
+                Chain::Cfb { feedback } => {
+                    let keystream = encrypt_block(*feedback, roundkeys);
+                    feedback[..chunk.len()].copy_from_slice(chunk);
+                    xor(chunk, &keystream);
+                }
+                Chain::Ctr { .. } | Chain::Prefetched { .. } | Chain::Ofb { .. } => self.encrypt(chunk, roundkeys),
+                Chain::Buffered => unreachable!("The authenticated modes are processed by the cipher"),
+            }
+        }
+    }
+
+    ///
+    /// Encrypts or decrypts the data split into one part per job, each on its own thread, in
+    /// ECB and CTR where the blocks do not depend on each other. The other modes process the
+    /// data on the current thread.
+    ///
+    /// data: Whole blocks, except for a last partial block in counter mode.
+    /// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+    /// jobs: Number of threads to use.
+    /// function: Chain::encrypt or Chain::decrypt.
+    ///
+    pub(crate) fn split(&mut self, data: &mut [u8], roundkeys: &RoundKeys, jobs: usize, function: fn(&mut Chain, &mut [u8], &RoundKeys)) {
+        let first = match self {
+            Chain::Ecb => None,
+            Chain::Ctr { counter } => Some(*counter),
+            _ => return function(self, data, roundkeys),
+        };
+        if jobs < 2 || data.len() <= 16 {
+            return function(self, data, roundkeys);
+        }
+        let length = data.len().div_ceil(16).div_ceil(jobs) * 16;
+        std::thread::scope(|scope| {
+            for (index, part) in data.chunks_mut(length).enumerate() {
+                let mut chain = match first {
+                    Some(counter) => Chain::Ctr { counter: counter.advance((index * length / 16) as u128) },
+                    None => Chain::Ecb,
+                };
+                scope.spawn(move || function(&mut chain, part, roundkeys));
+            }
+        });
+        if let Some(counter) = first {
+            *self = Chain::Ctr { counter: counter.advance(data.len().div_ceil(16) as u128) };
+        }
+    }
+}
+
+///
+/// AesWriter struct that encrypts everything written to it into the inner writer.
+/// Call finish when all data is written, it writes the last block and the tag.
+///
+pub struct AesWriter<'a, W: Write> {
+    inner: W,
+    cipher: &'a Cipher,
+    mode: Mode<'a>,
+    chain: Chain,
+    // Data that is not yet a whole block, or the whole message in the authenticated modes.
+    buffer: Vec<u8>,
+}
+
+impl<'a, W: Write> AesWriter<'a, W> {
+    ///
+    /// Creates a writer that encrypts into the inner writer.
+    ///
+    /// inner: The writer that receives the ciphertext.
+    /// cipher: The cipher with the key and the padding.
+    /// mode: The mode with its parameters.
+    ///
+    /// result: An AesWriter struct.
+    ///
+    pub fn new(inner: W, cipher: &'a Cipher, mode: Mode<'a>) -> AesWriter<'a, W> {
+        AesWriter { inner, cipher, mode, chain: Chain::new(&mode), buffer: Vec::new() }
+    }
+
+    ///
+    /// Computes the keystream of counter mode on a worker thread while the data is written.
+    /// The other modes are not changed.
+    ///
+    /// blocks: Number of blocks computed ahead at a time. Panics if zero in counter mode.
+    ///
+    /// result: The AesWriter struct.
+    ///
+    pub fn with_prefetch(mut self, blocks: usize) -> AesWriter<'a, W> {
+        self.chain = self.chain.prefetch(self.cipher.roundkeys(), blocks);
+        self
+    }
+
+    ///
+    /// Encrypts the rest of the data, with padding or the tag, and flushes the inner writer.
+    ///
+    /// result: The inner writer, or an error if writing fails or a parameter is not supported by the mode.
+    ///
+    pub fn finish(mut self) -> io::Result<W> {
+        let last = if let Chain::Buffered = self.chain {
+            self.cipher.encrypt(self.mode, &self.buffer).map_err(to_io_error)?
+        } else {
+            let mut last = if self.chain.is_padded() { self.cipher.padding().pad(&self.buffer, 16) } else { std::mem::take(&mut self.buffer) };
+            self.chain.encrypt(&mut last, self.cipher.roundkeys());
+            last
+        };
+        self.inner.write_all(&last)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for AesWriter<'_, W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(data);
+        if !matches!(self.chain, Chain::Buffered) {
+            let length = self.buffer.len() - self.buffer.len() % 16;
+            self.chain.encrypt(&mut self.buffer[..length], self.cipher.roundkeys());
+            self.inner.write_all(&self.buffer[..length])?;
+            self.buffer.drain(..length);
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+///
+/// AesReader struct that decrypts the data read from the inner reader.
+///
+pub struct AesReader<'a, R: Read> {
+    inner: R,
+    cipher: &'a Cipher,
+    mode: Mode<'a>,
+    chain: Chain,
+    // Ciphertext read but not yet decrypted.
+    input: Vec<u8>,
+    // Plaintext decrypted but not yet returned, from the position.
+    output: Vec<u8>,
+    position: usize,
+    // Set when the inner reader has reached the end.
+    finished: bool,
+}
+
+impl<'a, R: Read> AesReader<'a, R> {
+    ///
+    /// Creates a reader that decrypts from the inner reader.
+    ///
+    /// inner: The reader with the ciphertext.
+    /// cipher: The cipher with the key and the padding used when encrypting.
+    /// mode: The mode with the parameters used when encrypting.
+    ///
+    /// result: An AesReader struct.
+    ///
+    pub fn new(inner: R, cipher: &'a Cipher, mode: Mode<'a>) -> AesReader<'a, R> {
+        AesReader { inner, cipher, mode, chain: Chain::new(&mode), input: Vec::new(), output: Vec::new(), position: 0, finished: false }
+    }
+
+    ///
+    /// Computes the keystream of counter mode on a worker thread while the data is read.
+    /// The other modes are not changed.
+    ///
+    /// blocks: Number of blocks computed ahead at a time. Panics if zero in counter mode.
+    ///
+    /// result: The AesReader struct.
+    ///
+    pub fn with_prefetch(mut self, blocks: usize) -> AesReader<'a, R> {
+        self.chain = self.chain.prefetch(self.cipher.roundkeys(), blocks);
+        self
+    }
+
+    ///
+    /// Reads more ciphertext and decrypts what can be decrypted. The padded modes keep
+    /// the last block until the end, where the padding is removed.
+    ///
+    fn fill(&mut self) -> io::Result<()> {
+        let start = self.input.len();
+        self.input.resize(start + READ_LENGTH, 0);
+        let count = self.inner.read(&mut self.input[start..])?;
+        self.input.truncate(start + count);
+        self.finished = count == 0;
+        let roundkeys = self.cipher.roundkeys();
+        self.output = match (&self.chain, self.finished) {
+            (Chain::Buffered, true) => self.cipher.decrypt(self.mode, &self.input).map_err(to_io_error)?,
+            (Chain::Buffered, false) => return Ok(()),
+            (chain, true) if chain.is_padded() => {
+                if self.input.is_empty() || !self.input.len().is_multiple_of(16) {
+                    return Err(to_io_error(AesError::InvalidDataLength { length: self.input.len() }));
+                }
+                let mut last = std::mem::take(&mut self.input);
+                self.chain.decrypt(&mut last, roundkeys);
+                self.cipher.padding().unpad(&last, 16).map_err(to_io_error)?.to_vec()
+            }
+            (chain, _) => {
+                let mut length = if self.finished { self.input.len() } else { self.input.len() - self.input.len() % 16 };
+                // The last whole block may be the padded one until more data follows it.
+                if chain.is_padded() && length == self.input.len() {
+                    length = length.saturating_sub(16);
+                }
+                let mut data: Vec<u8> = self.input.drain(..length).collect();
+                self.chain.decrypt(&mut data, roundkeys);
+                data
+            }
+        };
+        self.position = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for AesReader<'_, R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.output.len() && !self.finished {
+            self.fill()?;
+        }
+        let count = buffer.len().min(self.output.len() - self.position);
+        buffer[..count].copy_from_slice(&self.output[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+///
+/// Copies a slice of 16 bytes into a block.
+///
+fn to_block(chunk: &[u8]) -> [u8; 16] {
+    let mut block = [0u8; 16];
+    block.copy_from_slice(chunk);
+    block
+}
+
+///
+/// Xors the keystream into the data, which may be shorter than the keystream.
+///
+fn xor(data: &mut [u8], keystream: &[u8; 16]) {
+    for (value, key) in data.iter_mut().zip(keystream) {
+        *value ^= key;
+    }
+}
+
+///
+/// Errors from the cipher are reported as invalid data.
+///
+fn to_io_error(error: AesError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Key;
+    use crate::padding::Iso7816;
+
+    fn modes<'a>() -> Vec<Mode<'a>> {
+        vec![
+            Mode::Ecb,
+            Mode::Cbc { iv: [1; 16] },
+            Mode::Ctr { counter: Counter::from_nonce(&[2; 12], 1) },
+            Mode::Cfb { iv: [3; 16] },
+            Mode::Ofb { iv: [4; 16] },
+            Mode::Gcm { nonce: &[5; 12], aad: b"header" },
+            Mode::Ccm { nonce: &[6; 13], aad: b"header", tag_length: 8 },
+            Mode::GcmSiv { nonce: [7; 12], aad: b"header" },
+            Mode::Eax { nonce: &[8; 16], aad: b"header" },
+        ]
+    }
+
+    ///
+    /// The streams give the same ciphertext as the cipher, written and read in pieces of 7 bytes.
+    ///
+    #[test]
+    fn test_same_as_cipher() {
+        for cipher in [Cipher::new(&Key::Aes128([9; 16])), Cipher::new(&Key::Aes256([9; 32])).with_padding(Iso7816)] {
+            for mode in modes() {
+                for length in [0, 1, 15, 16, 17, 40, 5000] {
+                    let data: Vec<u8> = (0..length).map(|value| (value % 251) as u8 + 1).collect();
+                    let mut writer = AesWriter::new(Vec::new(), &cipher, mode);
+                    for chunk in data.chunks(7) {
+                        writer.write_all(chunk).unwrap();
+                    }
+                    let encrypted = writer.finish().unwrap();
+                    assert_eq!(Ok(encrypted.clone()), cipher.encrypt(mode, &data), "{mode:?}");
+                    let mut reader = AesReader::new(&encrypted[..], &cipher, mode);
+                    let mut decrypted = Vec::new();
+                    let mut buffer = [0u8; 7];
+                    loop {
+                        let count = reader.read(&mut buffer).unwrap();
+                        if count == 0 {
+                            break;
+                        }
+                        decrypted.extend_from_slice(&buffer[..count]);
+                    }
+                    assert_eq!(decrypted, data, "{mode:?}");
+                }
+            }
+        }
+    }
+
+    ///
+    /// Counter mode with the keystream computed ahead gives the same result in pieces of any length.
+    ///
+    #[test]
+    fn test_prefetch() {
+        let cipher = Cipher::new(&Key::Aes192([9; 24]));
+        let mode = Mode::Ctr { counter: Counter::from_nonce(&[2; 12], 1) };
+        let data: Vec<u8> = (0..5000).map(|value| (value % 251) as u8).collect();
+        let mut writer = AesWriter::new(Vec::new(), &cipher, mode).with_prefetch(5);
+        for chunk in data.chunks(7) {
+            writer.write_all(chunk).unwrap();
+        }
+        let encrypted = writer.finish().unwrap();
+        assert_eq!(Ok(encrypted.clone()), cipher.encrypt(mode, &data));
+        let mut decrypted = Vec::new();
+        AesReader::new(&encrypted[..], &cipher, mode).with_prefetch(64).read_to_end(&mut decrypted).unwrap();
+        assert_eq!(decrypted, data);
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        let cipher = Cipher::new(&Key::Aes128([9; 16]));
+        let mut decrypted = Vec::new();
+        let error = AesReader::new(&[0u8; 20][..], &cipher, Mode::Ecb).read_to_end(&mut decrypted).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let mut encrypted = cipher.encrypt(Mode::Eax { nonce: &[1; 16], aad: &[] }, b"Attack at dawn").unwrap();
+        encrypted[0] ^= 1;
+        let mut decrypted = Vec::new();
+        let error = AesReader::new(&encrypted[..], &cipher, Mode::Eax { nonce: &[1; 16], aad: &[] }).read_to_end(&mut decrypted).unwrap_err();
+        assert_eq!(error.get_ref().unwrap().to_string(), AesError::Authentication.to_string());
+        assert!(decrypted.is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+//!
+//! Table based rounds. SubBytes, ShiftRows and MixColumns of a column are combined into
+//! four lookups in precomputed tables and xors, with the state held as four 32 bit columns.
+//! Decryption uses the equivalent inverse cipher, where the inverse column mixing is applied
+//! to the roundkeys once instead of to the state in every round.
+//! Description: https://en.wikipedia.org/wiki/Advanced_Encryption_Standard#Optimization_of_the_cipher
+//! Specification: FIPS 197, section 5.3.5.
+//!
+
+use crate::{AESBlock, DecryptedState, EncryptedState, RoundKeys};
+use gf256::multiply;
+
+///
+/// The encryption tables. The first holds the column (2, 1, 1, 3) times the substituted byte,
+/// the others the same column rotated one, two and three bytes down.
+///
+const ENCRYPTION: [[u32; 256]; 4] = tables(&encryption_column());
+
+///
+/// The decryption tables. The first holds the column (14, 9, 13, 11) times the inverse
+/// substituted byte, the others the same column rotated one, two and three bytes down.
+///
+const DECRYPTION: [[u32; 256]; 4] = tables(&decryption_column());
+
+const fn encryption_column() -> [u32; 256] {
+    let mut result = [0; 256];
+    let mut idx = 0;
+    while idx < 256 {
+        let value = AESBlock::<DecryptedState>::S_BOX[idx];
+        result[idx] = u32::from_be_bytes([multiply(value, 2), value, value, multiply(value, 3)]);
+        idx += 1;
+    }
+    result
+}
+
+const fn decryption_column() -> [u32; 256] {
+    let mut result = [0; 256];
+    let mut idx = 0;
+    while idx < 256 {
+        let value = AESBlock::<EncryptedState>::INVERSE_S_BOX[idx];
+        result[idx] = u32::from_be_bytes([multiply(value, 14), multiply(value, 9), multiply(value, 13), multiply(value, 11)]);
+        idx += 1;
+    }
+    result
+}
+
+///
+/// The four tables of a column and its rotations.
+///
+const fn tables(column: &[u32; 256]) -> [[u32; 256]; 4] {
+    let mut result = [[0; 256]; 4];
+    let mut idx = 0;
+    while idx < 256 {
+        result[0][idx] = column[idx];
+        result[1][idx] = column[idx].rotate_right(8);
+        result[2][idx] = column[idx].rotate_right(16);
+        result[3][idx] = column[idx].rotate_right(24);
+        idx += 1;
+    }
+    result
+}
+
+///
+/// Byte n of the column, counted from the top.
+///
+fn byte(column: u32, n: usize) -> usize {
+    (column >> (24 - 8 * n)) as usize & 0xFF
+}
+
+///
+/// Splits 16 bytes into four big-endian columns.
+///
+fn columns(data: &[u8]) -> [u32; 4] {
+    core::array::from_fn(|idx| u32::from_be_bytes([data[4 * idx], data[4 * idx + 1], data[4 * idx + 2], data[4 * idx + 3]]))
+}
+
+///
+/// Joins four columns into 16 bytes.
+///
+fn bytes(columns: [u32; 4]) -> [u8; 16] {
+    let mut result = [0; 16];
+    for (chunk, column) in result.chunks_mut(4).zip(columns) {
+        chunk.copy_from_slice(&column.to_be_bytes());
+    }
+    result
+}
+
+///
+/// Encrypts a single block with the tables.
+///
+/// block: The 16 bytes to encrypt.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+///
+/// result: The 16 bytes encrypted.
+///
+pub(crate) fn encrypt(block: [u8; 16], roundkeys: &RoundKeys) -> [u8; 16] {
+    let rounds = roundkeys.len() - 1;
+    let first = columns(&roundkeys[0]);
+    let mut state: [u32; 4] = core::array::from_fn(|idx| columns(&block)[idx] ^ first[idx]);
+    for roundkey in &roundkeys[1..rounds] {
+        let key = columns(roundkey);
+        state = core::array::from_fn(|idx| {
+            ENCRYPTION[0][byte(state[idx], 0)] ^ ENCRYPTION[1][byte(state[(idx + 1) % 4], 1)] ^ ENCRYPTION[2][byte(state[(idx + 2) % 4], 2)] ^ ENCRYPTION[3][byte(state[(idx + 3) % 4], 3)] ^ key[idx]
+        });
+    }
+    // The final round has no column mixing, only the substitution and the shifted rows.
+    let key = columns(&roundkeys[rounds]);
+    bytes(core::array::from_fn(|idx| u32::from_be_bytes(core::array::from_fn(|n| AESBlock::<DecryptedState>::S_BOX[byte(state[(idx + n) % 4], n)])) ^ key[idx]))
+}
+
+///
+/// Decrypts a single block with the tables.
+///
+/// block: The 16 bytes to decrypt.
+/// roundkeys: The roundkeys of a 16, 24 or 32 byte key.
+///
+/// result: The 16 bytes decrypted.
+///
+pub(crate) fn decrypt(block: [u8; 16], roundkeys: &RoundKeys) -> [u8; 16] {
+    let keys = roundkeys.decryption();
+    let rounds = keys.len() - 1;
+    let first = columns(&keys[0]);
+    let mut state: [u32; 4] = core::array::from_fn(|idx| columns(&block)[idx] ^ first[idx]);
+    for key in &keys[1..rounds] {
+        let key = columns(key);
+        state = core::array::from_fn(|idx| {
+            DECRYPTION[0][byte(state[idx], 0)] ^ DECRYPTION[1][byte(state[(idx + 3) % 4], 1)] ^ DECRYPTION[2][byte(state[(idx + 2) % 4], 2)] ^ DECRYPTION[3][byte(state[(idx + 1) % 4], 3)] ^ key[idx]
+        });
+    }
+    // The first round of the encryption had no column mixing to undo.
+    let key = columns(&keys[rounds]);
+    bytes(core::array::from_fn(|idx| u32::from_be_bytes(core::array::from_fn(|n| AESBlock::<EncryptedState>::INVERSE_S_BOX[byte(state[(idx + 4 - n) % 4], n)])) ^ key[idx]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Key;
+
+    ///
+    /// The tables give the same result as the round functions for all key sizes.
+    ///
+    #[test]
+    fn test_same_as_rounds() {
+        for key in [Key::Aes128([1; 16]), Key::Aes192([2; 24]), Key::Aes256([3; 32])] {
+            let roundkeys = key.roundkeys();
+            for value in 0..32u8 {
+                let block: [u8; 16] = core::array::from_fn(|idx| value.wrapping_mul(37).wrapping_add(idx as u8 * 11));
+                let encrypted = encrypt(block, &roundkeys);
+                assert_eq!(encrypted, AESBlock::<DecryptedState>::new(block).rounds(&roundkeys));
+                assert_eq!(decrypt(encrypted, &roundkeys), block);
+                assert_eq!(AESBlock::<EncryptedState>::new(encrypted).rounds(&roundkeys), block);
+            }
+        }
+    }
+}
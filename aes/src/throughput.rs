@@ -0,0 +1,104 @@
+//!
+//! Measures how fast the modes encrypt with the backend selected by the features, so the
+//! effect of an optimization can be compared between builds. The benches in the benches
+//! directory give more stable numbers with Criterion, this gives a quick measurement from
+//! within a program.
+//!
+
+use crate::cipher::{Cipher, Mode};
+use crate::ctr::Counter;
+use crate::{AesError, KeySize, rand};
+use alloc::vec::Vec;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+///
+/// The name of the implementation of the rounds selected by the features.
+///
+#[cfg(feature = "constant-time")]
+pub const BACKEND: &str = "constant-time";
+#[cfg(all(feature = "fast-tables", not(feature = "constant-time")))]
+pub const BACKEND: &str = "fast-tables";
+#[cfg(not(any(feature = "fast-tables", feature = "constant-time")))]
+pub const BACKEND: &str = "reference";
+
+///
+/// The modes that are measured, with the names they are reported with.
+///
+pub const MODES: [&str; 6] = ["ecb", "cbc", "ctr", "cfb", "ofb", "gcm"];
+
+///
+/// Throughput struct with the result of measuring one mode.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Throughput {
+    /// The name of the mode.
+    pub mode: &'static str,
+    /// The name of the backend.
+    pub backend: &'static str,
+    /// Number of bytes encrypted in total.
+    pub bytes: u64,
+    /// The time the encryption took.
+    pub elapsed: Duration,
+}
+
+impl Throughput {
+    ///
+    /// Returns the throughput in megabytes, 10^6 bytes, per second.
+    ///
+    pub fn megabytes_per_second(&self) -> f64 {
+        self.bytes as f64 / 1e6 / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+///
+/// Encrypts a buffer with every mode in MODES again and again, until each mode has run for
+/// the duration, and reports the throughput.
+///
+/// key_size: The size of the random key.
+/// length: The length of the buffer encrypted at a time in bytes.
+/// duration: The time spent on each mode. Each mode encrypts the buffer at least once.
+///
+/// result: The throughput of each mode in the order of MODES, or an error if the random
+///         key can not be generated.
+///
+pub fn measure_throughput(key_size: KeySize, length: usize, duration: Duration) -> Result<Vec<Throughput>, AesError> {
+    let cipher = Cipher::new(&rand::generate_key(key_size)?);
+    let data = alloc::vec![0x5a; length];
+    let iv = [0x0f; 16];
+    let mut result = Vec::with_capacity(MODES.len());
+    for name in MODES {
+        let mode = match name {
+            "ecb" => Mode::Ecb,
+            "cbc" => Mode::Cbc { iv },
+            "ctr" => Mode::Ctr { counter: Counter::new(iv) },
+            "cfb" => Mode::Cfb { iv },
+            "ofb" => Mode::Ofb { iv },
+            _ => Mode::Gcm { nonce: &iv[..12], aad: &[] },
+        };
+        let start = Instant::now();
+        let mut bytes = 0;
+        while bytes == 0 || start.elapsed() < duration {
+            black_box(cipher.encrypt(mode, black_box(&data))?);
+            bytes += length as u64;
+        }
+        result.push(Throughput { mode: name, backend: BACKEND, bytes, elapsed: start.elapsed() });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_throughput() {
+        let result = measure_throughput(KeySize::Aes128, 256, Duration::from_millis(1)).unwrap();
+        assert_eq!(result.iter().map(|throughput| throughput.mode).collect::<Vec<_>>(), MODES);
+        for throughput in result {
+            assert_eq!(throughput.backend, BACKEND);
+            assert!(throughput.bytes >= 256 && throughput.bytes % 256 == 0);
+            assert!(throughput.megabytes_per_second() > 0.0 && throughput.megabytes_per_second().is_finite());
+        }
+    }
+}
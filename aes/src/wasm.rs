@@ -0,0 +1,84 @@
+//!
+//! WebAssembly bindings for encrypting files in the browser with a password. The data is
+//! written in the same format as the command line program, with a header holding the key
+//! derivation function, the salt, the mode and the nonce, so files can be exchanged
+//! between the two.
+//!
+//! Built with cargo rustc -p aes --lib --release --target wasm32-unknown-unknown --features wasm
+//! --crate-type cdylib, followed by wasm-bindgen --target web on target/wasm32-unknown-unknown/release/aes.wasm.
+//!
+
+use crate::format::{FileMode, Header, SALT_LENGTH};
+use crate::kdf::{Kdf, KdfLimits};
+use crate::{AesError, Cipher, Key, pbkdf2, rand};
+use alloc::vec::Vec;
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
+
+///
+/// Encrypts the data with a key derived from the password with PBKDF2 in GCM mode.
+///
+/// data: The contents of a file.
+/// password: The password.
+///
+/// result: The encrypted file, or an error if the random source failed.
+///
+#[wasm_bindgen]
+pub fn encrypt(data: &[u8], password: &str) -> Result<Vec<u8>, JsError> {
+    seal(data, password, Kdf::Pbkdf2 { iterations: pbkdf2::DEFAULT_ITERATIONS }).map_err(JsError::from)
+}
+
+///
+/// Decrypts a file encrypted with encrypt or the command line program.
+///
+/// data: The contents of an encrypted file.
+/// password: The password used when encrypting.
+///
+/// result: The contents of the file, or an error if the header is invalid, the password
+///         is wrong or the file has been changed.
+///
+#[wasm_bindgen]
+pub fn decrypt(data: &[u8], password: &str) -> Result<Vec<u8>, JsError> {
+    open(data, password).map_err(JsError::from)
+}
+
+fn seal(data: &[u8], password: &str, kdf: Kdf) -> Result<Vec<u8>, AesError> {
+    let mut salt = [0u8; SALT_LENGTH];
+    rand::fill(&mut salt)?;
+    let header = Header::new(kdf, salt, FileMode::Gcm, &rand::generate_nonce()?)?;
+    let cipher = derive_cipher(&header, password)?;
+    header.encrypt(&cipher, data)
+}
+
+fn open(data: &[u8], password: &str) -> Result<Vec<u8>, AesError> {
+    let (header, data) = Header::parse(data)?;
+    header.kdf.check_limits(&KdfLimits::default())?;
+    header.decrypt(&derive_cipher(&header, password)?, data)
+}
+
+fn derive_cipher(header: &Header, password: &str) -> Result<Cipher, AesError> {
+    let key = Zeroizing::new(header.kdf.derive(password.as_bytes(), &header.salt, header.key_size.length()));
+    Ok(Cipher::new(&header.content_key(&Key::from_slice(&key)?)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_open() {
+        let file = seal(b"Attack at dawn", "password", Kdf::Pbkdf2 { iterations: 10 }).unwrap();
+        assert_eq!(open(&file, "password"), Ok(b"Attack at dawn".to_vec()));
+        assert_eq!(open(&file, "Password"), Err(AesError::Authentication));
+    }
+
+    ///
+    /// A header with parameters out of range is rejected before the key is derived.
+    ///
+    #[test]
+    fn test_invalid_kdf() {
+        let header = Header::new(Kdf::Pbkdf2 { iterations: 0 }, [1; SALT_LENGTH], FileMode::Gcm, &[2; 12]).unwrap();
+        let file = header.encrypt(&Cipher::new(&Key::from_slice(&[3; 16]).unwrap()), b"Attack at dawn").unwrap();
+        assert_eq!(open(&file, "password"), Err(AesError::InvalidKdfParams { parameter: "PBKDF2 iterations", value: 0, requirement: "at least 1" }));
+    }
+}
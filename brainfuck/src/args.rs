@@ -0,0 +1,47 @@
+use clap::{Parser, ValueEnum};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// File with the Brainfuck program. The program reads its input from stdin
+    #[arg(short, long)]
+    pub program_file: String,
+
+    /// How the program is run
+    #[arg(short, long, value_enum, default_value_t = Mode::Interpret)]
+    pub mode: Mode,
+
+    /// Do not coalesce runs of + - < > and replace clear loops
+    #[arg(short, long)]
+    pub no_optimize: bool,
+
+    /// Value of the current cell when reading at the end of the input
+    #[arg(short, long, value_enum, default_value_t = EofArg::Unchanged)]
+    pub eof: EofArg,
+
+    /// Stop the interpreter after this number of operations
+    #[arg(short = 's', long)]
+    pub max_steps: Option<u64>,
+
+    /// Run the program in every mode, discard the output and print the time taken by each
+    #[arg(short, long)]
+    pub compare: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Run one operation at a time
+    Interpret,
+    /// Compile into Rust closures before running
+    Compile,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EofArg {
+    /// The cell keeps its value
+    Unchanged,
+    /// The cell is set to 0
+    Zero,
+    /// The cell is set to 255
+    Max,
+}
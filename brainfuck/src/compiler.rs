@@ -0,0 +1,112 @@
+use crate::interpreter::Tape;
+use crate::io::{Eof, Io};
+use crate::{Op, Program};
+
+///
+/// Machine struct with the state the compiled closures work on.
+///
+pub struct Machine<'a> {
+    tape: Tape,
+    io: &'a mut dyn Io,
+    eof: Eof,
+}
+
+///
+/// Compiled operation.
+///
+type Closure = Box<dyn Fn(&mut Machine<'_>)>;
+
+///
+/// Compiled struct containing the program as nested closures. Each loop becomes
+/// a closure running the closures of its body, so no jump targets are looked up
+/// while running.
+///
+pub struct Compiled {
+    body: Vec<Closure>,
+    eof: Eof,
+}
+
+impl Compiled {
+    ///
+    /// Compiles the program.
+    ///
+    /// eof: What happens to the current cell when reading at the end of the input.
+    ///
+    pub fn new(program: &Program, eof: Eof) -> Compiled {
+        Compiled { body: compile_block(program.ops(), 0, program.ops().len()), eof }
+    }
+
+    ///
+    /// Runs the program. There is no step limit, so the call does not return if the program loops forever.
+    ///
+    /// result: The tape when the program finished.
+    ///
+    pub fn run(&self, io: &mut dyn Io) -> Tape {
+        let mut machine = Machine { tape: Tape::new(), io, eof: self.eof };
+        self.body.iter().for_each(|closure| closure(&mut machine));
+        machine.tape
+    }
+}
+
+///
+/// Compiles the operations from start to end into closures.
+///
+fn compile_block(ops: &[Op], start: usize, end: usize) -> Vec<Closure> {
+    let mut closures: Vec<Closure> = Vec::new();
+    let mut index = start;
+    while index < end {
+        let closure: Closure = match ops[index] {
+            Op::Add(value) => Box::new(move |machine| machine.tape.add(value)),
+            Op::Move(offset) => Box::new(move |machine| machine.tape.move_by(offset)),
+            Op::Input => Box::new(|machine| {
+                let value = machine.eof.read(machine.io, machine.tape.get());
+                machine.tape.set(value);
+            }),
+            Op::Output => Box::new(|machine| machine.io.write(machine.tape.get())),
+            Op::Clear => Box::new(|machine| machine.tape.set(0)),
+            Op::JumpIfZero(loop_end) => {
+                let body = compile_block(ops, index + 1, loop_end);
+                index = loop_end;
+                Box::new(move |machine| {
+                    while machine.tape.get() != 0 {
+                        body.iter().for_each(|closure| closure(machine));
+                    }
+                })
+            }
+            Op::JumpIfNonZero(_) => unreachable!("Loop ends are consumed with the loop start"),
+        };
+        closures.push(closure);
+        index += 1;
+    }
+    closures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+    use crate::io::BufferIo;
+
+    #[test]
+    fn test_hello_world() {
+        let source = std::fs::read_to_string("testdata/hello.b").unwrap();
+        for optimize in [false, true] {
+            let mut io = BufferIo::default();
+            Compiled::new(&Program::parse(&source, optimize).unwrap(), Eof::Unchanged).run(&mut io);
+            assert_eq!(io.output_text(), "Hello World!\n");
+        }
+    }
+
+    #[test]
+    fn test_matches_interpreter() {
+        // Reverses the input.
+        let program = Program::parse(">,[>,]<[.<]", true).unwrap();
+        let mut compiled_io = BufferIo::new(b"stressed");
+        let tape = Compiled::new(&program, Eof::Zero).run(&mut compiled_io);
+        let mut interpreted_io = BufferIo::new(b"stressed");
+        let expected = Interpreter::new(Eof::Zero, None).run(&program, &mut interpreted_io).unwrap();
+        assert_eq!(compiled_io.output_text(), "desserts");
+        assert_eq!(compiled_io.output, interpreted_io.output);
+        assert_eq!(tape, expected);
+    }
+}
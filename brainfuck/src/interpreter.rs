@@ -0,0 +1,186 @@
+use crate::io::{Eof, Io};
+use crate::{BrainfuckError, Op, Program};
+
+///
+/// Tape struct with cells that grow in both directions when the pointer moves
+/// outside, so the tape is unbounded.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tape {
+    cells: Vec<u8>,
+    pointer: usize,
+    // Index of the start cell in cells. Grows when cells are added to the left.
+    origin: usize,
+}
+
+impl Default for Tape {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tape {
+    ///
+    /// Creates a tape with the pointer on the start cell.
+    ///
+    pub fn new() -> Tape {
+        Tape { cells: vec![0; 1024], pointer: 0, origin: 0 }
+    }
+
+    pub fn get(&self) -> u8 {
+        self.cells[self.pointer]
+    }
+
+    pub fn set(&mut self, value: u8) {
+        self.cells[self.pointer] = value;
+    }
+
+    pub fn add(&mut self, value: u8) {
+        self.cells[self.pointer] = self.cells[self.pointer].wrapping_add(value);
+    }
+
+    ///
+    /// Moves the pointer, adding cells if it moves outside the tape.
+    ///
+    pub fn move_by(&mut self, offset: isize) {
+        let target = self.pointer as isize + offset;
+        if target < 0 {
+            let grow = (-target as usize).max(self.cells.len());
+            self.cells.splice(0..0, std::iter::repeat_n(0, grow));
+            self.origin += grow;
+            self.pointer = (target + grow as isize) as usize;
+        } else {
+            self.pointer = target as usize;
+            if self.pointer >= self.cells.len() {
+                self.cells.resize((self.pointer + 1).max(self.cells.len() * 2), 0);
+            }
+        }
+    }
+
+    ///
+    /// Returns the position of the pointer relative to the start cell.
+    ///
+    pub fn position(&self) -> isize {
+        self.pointer as isize - self.origin as isize
+    }
+
+    ///
+    /// Returns the value of the cell at the position relative to the start cell.
+    ///
+    pub fn cell(&self, position: isize) -> u8 {
+        usize::try_from(self.origin as isize + position).ok().and_then(|index| self.cells.get(index)).copied().unwrap_or(0)
+    }
+}
+
+///
+/// Interpreter struct running programs one operation at a time.
+///
+#[derive(Debug, Clone, Default)]
+pub struct Interpreter {
+    eof: Eof,
+    max_steps: Option<u64>,
+}
+
+impl Interpreter {
+    ///
+    /// Creates an interpreter.
+    ///
+    /// eof: What happens to the current cell when reading at the end of the input.
+    /// max_steps: Stop with an error after this number of operations. Unlimited if None.
+    ///
+    pub fn new(eof: Eof, max_steps: Option<u64>) -> Interpreter {
+        Interpreter { eof, max_steps }
+    }
+
+    ///
+    /// Runs the program.
+    ///
+    /// result: The tape when the program finished, or an error if the step limit was reached.
+    ///
+    pub fn run(&self, program: &Program, io: &mut dyn Io) -> Result<Tape, BrainfuckError> {
+        let ops = program.ops();
+        let max_steps = self.max_steps.unwrap_or(u64::MAX);
+        let mut tape = Tape::new();
+        let mut index = 0;
+        let mut steps = 0u64;
+        while index < ops.len() {
+            if steps == max_steps {
+                return Err(BrainfuckError::StepLimit { steps });
+            }
+            steps += 1;
+            match ops[index] {
+                Op::Add(value) => tape.add(value),
+                Op::Move(offset) => tape.move_by(offset),
+                Op::Input => {
+                    let value = self.eof.read(io, tape.get());
+                    tape.set(value);
+                }
+                Op::Output => io.write(tape.get()),
+                Op::JumpIfZero(end) if tape.get() == 0 => index = end,
+                Op::JumpIfNonZero(start) if tape.get() != 0 => index = start,
+                Op::JumpIfZero(_) | Op::JumpIfNonZero(_) => {}
+                Op::Clear => tape.set(0),
+            }
+            index += 1;
+        }
+        Ok(tape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::BufferIo;
+
+    fn run(source: &str, input: &[u8], optimize: bool) -> (Tape, String) {
+        let program = Program::parse(source, optimize).unwrap();
+        let mut io = BufferIo::new(input);
+        let tape = Interpreter::default().run(&program, &mut io).unwrap();
+        (tape, io.output_text())
+    }
+
+    #[test]
+    fn test_hello_world() {
+        let source = std::fs::read_to_string("testdata/hello.b").unwrap();
+        assert_eq!(run(&source, b"", false).1, "Hello World!\n");
+        assert_eq!(run(&source, b"", true).1, "Hello World!\n");
+    }
+
+    #[test]
+    fn test_echo_and_eof() {
+        let source = ",[.,]";
+        let program = Program::parse(source, true).unwrap();
+        let mut io = BufferIo::new(b"abc");
+        Interpreter::new(Eof::Zero, None).run(&program, &mut io).unwrap();
+        assert_eq!(io.output_text(), "abc");
+
+        let mut io = BufferIo::new(b"");
+        let tape = Interpreter::new(Eof::Max, Some(1)).run(&Program::parse(",", false).unwrap(), &mut io).unwrap();
+        assert_eq!(tape.get(), 255);
+    }
+
+    #[test]
+    fn test_tape_grows_left() {
+        let (tape, _) = run("<<<<+>>>>++", b"", false);
+        assert_eq!(tape.position(), 0);
+        assert_eq!(tape.cell(-4), 1);
+        assert_eq!(tape.cell(0), 2);
+        let (tape, _) = run(&">".repeat(5000), b"", true);
+        assert_eq!(tape.position(), 5000);
+    }
+
+    #[test]
+    fn test_wrapping_cells() {
+        let (tape, _) = run("-", b"", false);
+        assert_eq!(tape.get(), 255);
+        let (tape, _) = run(&"+".repeat(257), b"", true);
+        assert_eq!(tape.get(), 1);
+    }
+
+    #[test]
+    fn test_step_limit() {
+        let program = Program::parse("+[]", false).unwrap();
+        let result = Interpreter::new(Eof::Unchanged, Some(1000)).run(&program, &mut BufferIo::default());
+        assert_eq!(result, Err(BrainfuckError::StepLimit { steps: 1000 }));
+    }
+}
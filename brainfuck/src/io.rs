@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+use std::io::{BufWriter, Bytes, Read, StdinLock, StdoutLock, Write};
+
+///
+/// Io trait used by the programs to read and write bytes. Implement it to
+/// connect programs to something else than stdin and stdout.
+///
+pub trait Io {
+    ///
+    /// Returns the next input byte or None at the end of the input.
+    ///
+    fn read(&mut self) -> Option<u8>;
+
+    ///
+    /// Writes an output byte.
+    ///
+    fn write(&mut self, byte: u8);
+}
+
+///
+/// Eof enum for what happens to the current cell when reading at the end of the input.
+/// Implementations of Brainfuck differ, so programs expect different behaviour.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Eof {
+    // The cell keeps its value.
+    #[default]
+    Unchanged,
+    // The cell is set to 0.
+    Zero,
+    // The cell is set to 255, which is -1 for implementations with signed cells.
+    Max,
+}
+
+impl Eof {
+    ///
+    /// Reads a byte and returns the new value of the current cell.
+    ///
+    pub fn read(&self, io: &mut dyn Io, current: u8) -> u8 {
+        match (io.read(), self) {
+            (Some(byte), _) => byte,
+            (None, Eof::Unchanged) => current,
+            (None, Eof::Zero) => 0,
+            (None, Eof::Max) => u8::MAX,
+        }
+    }
+}
+
+///
+/// BufferIo struct reading from a byte buffer and collecting the output.
+///
+#[derive(Debug, Clone, Default)]
+pub struct BufferIo {
+    input: VecDeque<u8>,
+    pub output: Vec<u8>,
+}
+
+impl BufferIo {
+    pub fn new(input: &[u8]) -> BufferIo {
+        BufferIo { input: input.iter().copied().collect(), output: Vec::new() }
+    }
+
+    ///
+    /// Returns the output as text, replacing invalid UTF-8.
+    ///
+    pub fn output_text(&self) -> String {
+        String::from_utf8_lossy(&self.output).into_owned()
+    }
+}
+
+impl Io for BufferIo {
+    fn read(&mut self) -> Option<u8> {
+        self.input.pop_front()
+    }
+
+    fn write(&mut self, byte: u8) {
+        self.output.push(byte);
+    }
+}
+
+///
+/// StdIo struct reading from stdin and writing to stdout. The output is
+/// buffered and flushed at each newline and when reading.
+///
+pub struct StdIo {
+    input: Bytes<StdinLock<'static>>,
+    output: BufWriter<StdoutLock<'static>>,
+}
+
+impl Default for StdIo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StdIo {
+    pub fn new() -> StdIo {
+        StdIo { input: std::io::stdin().lock().bytes(), output: BufWriter::new(std::io::stdout().lock()) }
+    }
+}
+
+impl Io for StdIo {
+    fn read(&mut self) -> Option<u8> {
+        let _ = self.output.flush();
+        self.input.next().and_then(|byte| byte.ok())
+    }
+
+    fn write(&mut self, byte: u8) {
+        let _ = self.output.write_all(&[byte]);
+        if byte == b'\n' {
+            let _ = self.output.flush();
+        }
+    }
+}
+
+impl Drop for StdIo {
+    fn drop(&mut self) {
+        let _ = self.output.flush();
+    }
+}
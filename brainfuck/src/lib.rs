@@ -0,0 +1,175 @@
+///
+/// Brainfuck interpreter and closure compiler.
+/// Description: https://en.wikipedia.org/wiki/Brainfuck
+///
+/// The program is parsed into operations where the matching brackets are
+/// resolved up front, so loops jump directly. Optionally runs of + - < > are
+/// coalesced into single operations and [-] loops are replaced with a clear.
+/// The operations can be run by the interpreter, or compiled into nested Rust
+/// closures which avoids dispatching on every operation.
+///
+pub mod compiler;
+pub mod interpreter;
+pub mod io;
+
+use std::fmt;
+
+///
+/// BrainfuckError enum to represent errors when parsing and running programs.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BrainfuckError {
+    // A [ without a matching ]. Position is the character index in the source.
+    UnmatchedOpen { position: usize },
+    // A ] without a matching [.
+    UnmatchedClose { position: usize },
+    // The program did not finish within the step limit.
+    StepLimit { steps: u64 },
+}
+
+impl fmt::Display for BrainfuckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BrainfuckError::UnmatchedOpen { position } => write!(f, "Unmatched [ at position {position}"),
+            BrainfuckError::UnmatchedClose { position } => write!(f, "Unmatched ] at position {position}"),
+            BrainfuckError::StepLimit { steps } => write!(f, "Program did not finish within {steps} steps"),
+        }
+    }
+}
+
+impl std::error::Error for BrainfuckError {}
+
+///
+/// Op enum for the operations of a parsed program.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    // Adds the value to the current cell, wrapping around.
+    Add(u8),
+    // Moves the pointer by the offset.
+    Move(isize),
+    // Reads a byte into the current cell.
+    Input,
+    // Writes the current cell.
+    Output,
+    // Jumps past the matching JumpIfNonZero if the current cell is zero.
+    JumpIfZero(usize),
+    // Jumps back past the matching JumpIfZero if the current cell is not zero.
+    JumpIfNonZero(usize),
+    // Sets the current cell to zero, replaces [-] and [+].
+    Clear,
+}
+
+///
+/// Program struct containing the operations with resolved jumps.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    ops: Vec<Op>,
+}
+
+impl Program {
+    ///
+    /// Parses the source. Characters other than the 8 commands are comments.
+    ///
+    /// source: The program source.
+    /// optimize: Coalesce runs of + - < > and replace clear loops.
+    ///
+    /// result: The program or an error if the brackets do not match.
+    ///
+    pub fn parse(source: &str, optimize: bool) -> Result<Program, BrainfuckError> {
+        // Operations with the source position of each bracket, jump targets are resolved afterwards.
+        let mut ops: Vec<(Op, usize)> = Vec::new();
+        for (position, character) in source.chars().enumerate() {
+            let op = match character {
+                '+' => Op::Add(1),
+                '-' => Op::Add(u8::MAX),
+                '>' => Op::Move(1),
+                '<' => Op::Move(-1),
+                ',' => Op::Input,
+                '.' => Op::Output,
+                '[' => Op::JumpIfZero(0),
+                ']' => Op::JumpIfNonZero(0),
+                _ => continue,
+            };
+            if optimize {
+                // [-] and [+], or any loop adding an odd value, always end with a zero cell.
+                let is_clear_loop = matches!(op, Op::JumpIfNonZero(_)) && matches!(ops.as_slice(), [.., (Op::JumpIfZero(_), _), (Op::Add(value), _)] if value % 2 == 1);
+                if is_clear_loop {
+                    ops.truncate(ops.len() - 2);
+                    ops.push((Op::Clear, position));
+                    continue;
+                }
+                match (ops.last_mut(), op) {
+                    (Some((Op::Add(total), _)), Op::Add(value)) => {
+                        *total = total.wrapping_add(value);
+                        continue;
+                    }
+                    (Some((Op::Move(total), _)), Op::Move(offset)) => {
+                        *total += offset;
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            ops.push((op, position));
+        }
+        if optimize {
+            ops.retain(|(op, _)| !matches!(op, Op::Add(0) | Op::Move(0)));
+        }
+        Ok(Program { ops: link(ops)? })
+    }
+
+    pub fn ops(&self) -> &[Op] {
+        &self.ops
+    }
+}
+
+///
+/// Resolves the jump targets of the brackets.
+///
+fn link(ops: Vec<(Op, usize)>) -> Result<Vec<Op>, BrainfuckError> {
+    let mut linked: Vec<Op> = Vec::with_capacity(ops.len());
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    for (index, (op, position)) in ops.into_iter().enumerate() {
+        match op {
+            Op::JumpIfZero(_) => {
+                open.push((index, position));
+                linked.push(op);
+            }
+            Op::JumpIfNonZero(_) => {
+                let (start, _) = open.pop().ok_or(BrainfuckError::UnmatchedClose { position })?;
+                linked[start] = Op::JumpIfZero(index);
+                linked.push(Op::JumpIfNonZero(start));
+            }
+            _ => linked.push(op),
+        }
+    }
+    match open.pop() {
+        Some((_, position)) => Err(BrainfuckError::UnmatchedOpen { position }),
+        None => Ok(linked),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_links_brackets() {
+        let program = Program::parse("+[>[-]<]", false).unwrap();
+        assert_eq!(program.ops(), &[Op::Add(1), Op::JumpIfZero(7), Op::Move(1), Op::JumpIfZero(5), Op::Add(255), Op::JumpIfNonZero(3), Op::Move(-1), Op::JumpIfNonZero(1)]);
+    }
+
+    #[test]
+    fn test_parse_optimized() {
+        let program = Program::parse("+++--x>><<<[-]+-,.", true).unwrap();
+        assert_eq!(program.ops(), &[Op::Add(1), Op::Move(-1), Op::Clear, Op::Input, Op::Output]);
+    }
+
+    #[test]
+    fn test_unmatched_brackets() {
+        assert_eq!(Program::parse("+[[]", false), Err(BrainfuckError::UnmatchedOpen { position: 1 }));
+        assert_eq!(Program::parse("ab]", true), Err(BrainfuckError::UnmatchedClose { position: 2 }));
+    }
+}
@@ -0,0 +1,87 @@
+mod args;
+
+use std::fs;
+use std::io::Read;
+use std::time::Instant;
+
+use args::{Args, EofArg, Mode};
+use brainfuck::compiler::Compiled;
+use brainfuck::interpreter::Interpreter;
+use brainfuck::io::{BufferIo, Eof, StdIo};
+use brainfuck::{BrainfuckError, Program};
+use clap::Parser;
+
+/**
+ * Brainfuck interpreter.
+ * Runs a program with stdin and stdout, either interpreted or compiled into
+ * closures, or compares the time taken by the different modes.
+ */
+fn main() {
+    let args = Args::parse();
+    let source = match fs::read_to_string(&args.program_file) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("Failed to read program {}: {err}", args.program_file);
+            return;
+        }
+    };
+    let eof = match args.eof {
+        EofArg::Unchanged => Eof::Unchanged,
+        EofArg::Zero => Eof::Zero,
+        EofArg::Max => Eof::Max,
+    };
+    let result = if args.compare { compare(&source, eof, args.max_steps) } else { run(&source, args.mode, !args.no_optimize, eof, args.max_steps) };
+    if let Err(err) = result {
+        eprintln!("{err}");
+    }
+}
+
+/**
+ * Runs the program with stdin and stdout.
+ *
+ * # Arguments
+ * * `source`: The program source.
+ * * `mode`: Interpret or compile the program.
+ * * `optimize`: Coalesce runs and replace clear loops.
+ * * `eof`: Value of the current cell when reading at the end of the input.
+ * * `max_steps`: Step limit for the interpreter.
+ */
+fn run(source: &str, mode: Mode, optimize: bool, eof: Eof, max_steps: Option<u64>) -> Result<(), BrainfuckError> {
+    let program = Program::parse(source, optimize)?;
+    let mut io = StdIo::new();
+    match mode {
+        Mode::Interpret => {
+            Interpreter::new(eof, max_steps).run(&program, &mut io)?;
+        }
+        Mode::Compile => {
+            Compiled::new(&program, eof).run(&mut io);
+        }
+    }
+    Ok(())
+}
+
+/**
+ * Runs the program interpreted and compiled, with and without optimizations,
+ * and prints the time taken by each. The input is read from stdin once and
+ * given to every run.
+ */
+fn compare(source: &str, eof: Eof, max_steps: Option<u64>) -> Result<(), BrainfuckError> {
+    let mut input = Vec::new();
+    if let Err(err) = std::io::stdin().read_to_end(&mut input) {
+        eprintln!("Failed to read input: {err}");
+    }
+    for optimize in [false, true] {
+        let program = Program::parse(source, optimize)?;
+        let label = if optimize { "optimized" } else { "plain" };
+
+        let start = Instant::now();
+        Interpreter::new(eof, max_steps).run(&program, &mut BufferIo::new(&input))?;
+        println!("Interpreted, {label}: {:?}", start.elapsed());
+
+        let start = Instant::now();
+        let compiled = Compiled::new(&program, eof);
+        compiled.run(&mut BufferIo::new(&input));
+        println!("Compiled, {label}: {:?}", start.elapsed());
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//!
+//! Steps per second of programs that grow the tape to the left, to the right and both
+//! ways. Adding a cell before the first one of the tape takes amortized constant time,
+//! so the programs that move left take about as long as those that move right:
+//! cargo bench -p busy_beaver.
+//!
+
+use busy_beaver::ZanyZoo;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+
+///
+/// Numbers of steps the programs run for.
+///
+const STEPS: [usize; 2] = [10_000, 100_000];
+
+fn tape(criterion: &mut Criterion) {
+    let programs = [("left", "1LA---"), ("right", "1RA---"), ("both", "1LB0RA_1RA1LB")];
+    for steps in STEPS {
+        let mut group = criterion.benchmark_group(format!("tape/{steps}"));
+        group.throughput(Throughput::Elements(steps as u64));
+        for (name, program) in programs {
+            group.bench_with_input(BenchmarkId::from_parameter(name), &program, |bencher, program| bencher.iter(|| ZanyZoo::run_program(black_box(program), steps).unwrap()));
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, tape);
+criterion_main!(benches);
@@ -0,0 +1,145 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Configuration file in TOML with max_iterations and programs. Reads stdin if neither this, --program nor --enumerate is given
+    #[arg(short, long)]
+    pub config: Option<String>,
+
+    /// Program to run, like 1RB1LB_1LA1RZ. Can be repeated, and is run after the programs of the configuration
+    #[arg(short, long)]
+    pub program: Vec<String>,
+
+    /// Maximum number of iterations for each program. Overrides max_iterations of the configuration
+    #[arg(short, long)]
+    pub max_iterations: Option<usize>,
+
+    /// Run all programs with --states and --symbols in Tree Normal Form instead of a list of programs
+    #[arg(short, long, conflicts_with_all = ["config", "program"])]
+    pub enumerate: bool,
+
+    /// Number of states of the enumerated programs
+    #[arg(long, default_value_t = 2, requires = "enumerate", value_parser = clap::value_parser!(u32).range(1..=25))]
+    pub states: u32,
+
+    /// Number of symbols of the enumerated programs
+    #[arg(long, default_value_t = 2, requires = "enumerate", value_parser = clap::value_parser!(u32).range(2..=10))]
+    pub symbols: u32,
+
+    /// Detect programs that repeat a configuration and stop them as non-halting, keeping at most this many MB of configurations for each thread
+    #[arg(long, value_name = "MB")]
+    pub cycle_memory: Option<usize>,
+
+    /// Detect programs that repeat the same steps shifted along the tape and stop them as non-halting, keeping at most this many MB of tapes for each thread
+    #[arg(long, value_name = "MB")]
+    pub translated_cycle_memory: Option<usize>,
+
+    /// Run Lin's recurrence check and stop programs that repeat the same steps, in place or shifted along the tape, with a period of at most this many steps as non-halting
+    #[arg(long, value_name = "STEPS")]
+    pub lin_recurrence: Option<usize>,
+
+    /// Run the programs on blocks of this many tape cells at a time, caching what the program does on each block, which speeds up long runs. Cannot be combined with the deciders
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=64), conflicts_with_all = ["cycle_memory", "translated_cycle_memory", "lin_recurrence"])]
+    pub block_size: u32,
+
+    /// How the tape is kept while a program runs. Overrides tape of the configuration. The run-length tape prints the tape as runs like 1^3 0 1^2, and cannot be combined with the deciders or --block-size
+    #[arg(short, long, value_enum)]
+    pub tape: Option<Tape>,
+
+    /// Number of threads the programs are run on
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub jobs: u32,
+
+    /// Only print the programs that set a new record for the most ones (sigma) or the most steps (shift) as they are found, and the champions at the end
+    #[arg(long, conflicts_with = "output")]
+    pub champions: bool,
+
+    /// Print a status line on stderr with the programs completed, the champions so far, the steps per second and the estimated time left, every N seconds while the programs run
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "10", value_parser = clap::value_parser!(u64).range(1..))]
+    pub progress: Option<u64>,
+
+    /// Print the step, the state, the head position and the tape around the head before the first step and after each step, or only after every Nth step. The programs are run one at a time, and cannot be run on blocks or a run-length tape
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "1", value_parser = clap::value_parser!(u64).range(1..), conflicts_with_all = ["champions", "output", "jobs", "block_size"])]
+    pub trace: Option<u64>,
+
+    /// Write a space-time diagram of each program to an image file, with a row of pixels for the tape after each step and the cell under the head in red. PNG, or PGM/PPM by extension. With more than one program the number of the program is added to the file name, like diagram-2.png
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["champions", "output", "jobs", "block_size"])]
+    pub diagram: Option<String>,
+
+    /// Only draw a row in the diagram for every Nth step and the step the program halts on
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "diagram", value_parser = clap::value_parser!(u64).range(1..))]
+    pub diagram_every: u64,
+
+    /// Size of a cell in pixels in the diagram
+    #[arg(long, default_value_t = 1, requires = "diagram", value_parser = clap::value_parser!(u64).range(1..=64))]
+    pub diagram_scale: u64,
+
+    /// Redraw the tape around the head in the terminal while the programs run one at a time, with the head marked under the cell it is on
+    #[arg(long, conflicts_with_all = ["champions", "output", "jobs", "block_size", "trace"])]
+    pub watch: bool,
+
+    /// Frames per second of --watch
+    #[arg(long, default_value_t = 10, requires = "watch", value_parser = clap::value_parser!(u64).range(1..=1000))]
+    pub fps: u64,
+
+    /// Number of steps between the frames of --watch
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "watch", value_parser = clap::value_parser!(u64).range(1..))]
+    pub watch_every: u64,
+
+    /// Number of cells shown by --watch
+    #[arg(long, default_value_t = 80, requires = "watch", value_parser = clap::value_parser!(u64).range(1..))]
+    pub watch_width: u64,
+
+    /// Write a checkpoint of the running program to this file every --checkpoint-every steps, to continue the run later with --resume. With more than one program the number of the program is added to the file name, like checkpoint-2.json
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["champions", "output", "jobs", "block_size"])]
+    pub checkpoint: Option<String>,
+
+    /// Number of steps between the checkpoints
+    #[arg(long, value_name = "N", default_value_t = 100_000_000, requires = "checkpoint", value_parser = clap::value_parser!(u64).range(1..))]
+    pub checkpoint_every: u64,
+
+    /// Continue the program of a checkpoint written with --checkpoint, up to the max iterations of the checkpoint unless --max-iterations is given
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["config", "program", "enumerate", "champions", "output", "jobs", "block_size"])]
+    pub resume: Option<String>,
+
+    /// Format of the results
+    #[arg(short, long, value_enum, default_value_t = Output::Text)]
+    pub output: Output,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Step through a program interactively. Type help at the prompt for the commands
+    Debug {
+        /// Program to debug, like 1RB1LB_1LA1RZ
+        program: String,
+
+        /// Most steps taken by one command
+        #[arg(short, long, default_value_t = 1_000_000)]
+        max_steps: usize,
+    },
+}
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Tape {
+    /// A cell for each position the head visited
+    Cells,
+    /// Runs of the same symbol with the number of cells, sweeping over a run in one step
+    Rle,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Output {
+    /// One line per program, with errors on stderr
+    Text,
+    /// A JSON array with an object per program, with the error of programs that fail
+    Json,
+    /// A header and a CSV row per program, with the error of programs that fail
+    Csv,
+}
@@ -0,0 +1,105 @@
+/**
+ * Records of a batch of Busy Beaver programs. The sigma of a program is the number of
+ * cells with a symbol other than 0 when it halts, and the shift is the number of steps
+ * it takes to halt. Programs that do not halt within the iterations hold no record.
+ */
+use crate::BusyBeaverResult;
+
+/**
+ * Record enum to represent the two records a program can hold.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Record {
+    // The most cells with a symbol other than 0.
+    Sigma,
+    // The most steps.
+    Shift,
+}
+
+/**
+ * Champion struct to represent the program that holds a record.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Champion {
+    // The program string.
+    pub program: String,
+    // The index of the program in the batch.
+    pub index: usize,
+    // The number of ones or steps of the record.
+    pub value: usize,
+}
+
+/**
+ * Champions struct to track the best sigma and the best shift seen so far.
+ * A record is only taken by a program that beats it, so the first of equal programs keeps it.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Champions {
+    // The program with the most ones, None before a program has halted.
+    pub sigma: Option<Champion>,
+    // The program with the most steps, None before a program has halted.
+    pub shift: Option<Champion>,
+}
+
+impl Champions {
+    /**
+     * Creates a new Champions instance without any records.
+     *
+     * # Returns
+     * A new instance of Champions.
+     */
+    pub fn new() -> Self {
+        Champions::default()
+    }
+
+    /**
+     * Updates the records with the result of a program.
+     *
+     * # Arguments
+     * `program` - The program string.
+     * `index` - The index of the program in the batch.
+     * `result` - The result of running the program.
+     *
+     * # Returns
+     * The records the program has taken, empty if it took none.
+     */
+    pub fn update(&mut self, program: &str, index: usize, result: &BusyBeaverResult) -> Vec<Record> {
+        let mut records = Vec::new();
+        if !result.halted {
+            return records;
+        }
+        if Self::beats(&self.sigma, result.values) {
+            self.sigma = Some(Champion { program: program.to_string(), index, value: result.values });
+            records.push(Record::Sigma);
+        }
+        if Self::beats(&self.shift, result.iterations) {
+            self.shift = Some(Champion { program: program.to_string(), index, value: result.iterations });
+            records.push(Record::Shift);
+        }
+        records
+    }
+
+    /**
+     * Checks if a value beats the record, which any value does when there is no record yet.
+     */
+    fn beats(champion: &Option<Champion>, value: usize) -> bool {
+        champion.as_ref().is_none_or(|champion| value > champion.value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_champions() {
+        let mut champions = Champions::new();
+        assert_eq!(champions.update("first", 0, &BusyBeaverResult::new(6, vec![1, 1, 1, 1], 4, true)), vec![Record::Sigma, Record::Shift]);
+        assert_eq!(champions.update("second", 1, &BusyBeaverResult::new(100, vec![1; 9], 9, false)), vec![]);
+        assert_eq!(champions.update("third", 2, &BusyBeaverResult::new(6, vec![1, 1, 1, 1], 4, true)), vec![]);
+        assert_eq!(champions.update("fourth", 3, &BusyBeaverResult::new(21, vec![1; 5], 5, true)), vec![Record::Sigma, Record::Shift]);
+        assert_eq!(champions.update("fifth", 4, &BusyBeaverResult::new(30, vec![1; 4], 4, true)), vec![Record::Shift]);
+        assert_eq!(champions.sigma, Some(Champion { program: "fourth".to_string(), index: 3, value: 5 }));
+        assert_eq!(champions.shift, Some(Champion { program: "fifth".to_string(), index: 4, value: 30 }));
+    }
+}
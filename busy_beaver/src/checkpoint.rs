@@ -0,0 +1,134 @@
+/**
+ * Checkpoints of a running program, so a long run can be stopped and continued later. A
+ * checkpoint has everything the machine needs to take the next step: the state, the head,
+ * the tape and the number of steps taken, and the program and the step budget it was run with.
+ */
+use serde::{Deserialize, Serialize};
+
+use crate::{BusyBeaverError, HALTED, Machine, STATES, State, Symbol};
+
+/**
+ * Checkpoint struct with the configuration of a program after a number of steps.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    // The program, like "1RB1LB_1LA1RZ".
+    pub program: String,
+    // The maximum number of iterations the program was run with.
+    pub max_iterations: usize,
+    // The number of steps taken.
+    pub iteration: usize,
+    // The current state.
+    pub state: State,
+    // The position of the head, 0 is where it started.
+    pub position: i64,
+    // The position of the first cell of the tape.
+    pub origin: i64,
+    // The cells of the tape the head has visited, from the position of origin.
+    pub tape: Vec<Symbol>,
+    // Flag to indicate if the program has halted.
+    pub halted: bool,
+}
+
+impl Checkpoint {
+    /**
+     * Creates a checkpoint of a running program.
+     *
+     * # Arguments
+     * `program` - The program, like "1RB1LB_1LA1RZ".
+     * `max_iterations` - The maximum number of iterations the program is run with.
+     * `machine` - The running program.
+     *
+     * # Returns
+     * A new instance of Checkpoint.
+     */
+    pub fn new(program: &str, max_iterations: usize, machine: &Machine) -> Self {
+        Checkpoint {
+            program: program.to_string(),
+            max_iterations,
+            iteration: machine.iteration(),
+            state: machine.state(),
+            position: machine.position(),
+            origin: machine.origin(),
+            tape: machine.tape().to_vec(),
+            halted: machine.halted(),
+        }
+    }
+
+    /**
+     * Checks that the checkpoint is a configuration a program can be in, so a machine can be
+     * resumed from it.
+     *
+     * # Returns
+     * The index of the head on the tape, or an error with what is wrong with the checkpoint.
+     */
+    pub(crate) fn validate(&self) -> Result<usize, BusyBeaverError> {
+        let invalid = |message: String| Err(BusyBeaverError::InvalidCheckpoint { message });
+        if !STATES.contains(&self.state) {
+            return invalid(format!("Unknown state {}", self.state));
+        }
+        if self.halted != (self.state == HALTED) {
+            return invalid(format!("State {} does not match halted {}", self.state, self.halted));
+        }
+        if self.tape.is_empty() {
+            return invalid("The tape is empty".to_string());
+        }
+        // The positions of the tape and the head must fit in an i64 as the program keeps running.
+        let end = i64::try_from(self.tape.len()).ok().and_then(|length| self.origin.checked_add(length));
+        let index = self.position.checked_sub(self.origin).and_then(|offset| usize::try_from(offset).ok());
+        match (end, index) {
+            (Some(_), Some(index)) if index <= self.tape.len() => Ok(index),
+            _ => invalid(format!("Position {} is outside the tape from {} with {} cells", self.position, self.origin, self.tape.len())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::BusyBeaver;
+    use crate::parser::parse_program;
+
+    #[test]
+    fn test_checkpoint_resumes_the_run() {
+        let program = parse_program("1RB1LC_0LA1RE_0LD0LB_1RA1RZ_1LA0RE").unwrap();
+        let expected = BusyBeaver::new(program.clone()).run(2_000).unwrap();
+        let mut checkpoints = Vec::new();
+        BusyBeaver::new(program.clone())
+            .run_with(500, |machine| {
+                if machine.iteration() % 100 == 0 {
+                    checkpoints.push(Checkpoint::new("1RB1LC_0LA1RE_0LD0LB_1RA1RZ_1LA0RE", 2_000, machine));
+                }
+            })
+            .unwrap();
+        assert_eq!(checkpoints.len(), 6);
+        let json = serde_json::to_string(&checkpoints[4]).unwrap();
+        let checkpoint: Checkpoint = serde_json::from_str(&json).unwrap();
+        assert_eq!(checkpoint, checkpoints[4]);
+        assert_eq!(checkpoint.iteration, 400);
+        let result = BusyBeaver::new(program).with_checkpoint(checkpoint).run(2_000).unwrap();
+        assert_eq!(result.iterations, expected.iterations);
+        assert_eq!(result.tape, expected.tape);
+        assert_eq!(result.values, expected.values);
+        assert!(result.halted);
+    }
+
+    #[test]
+    fn test_invalid_checkpoint() {
+        let program = parse_program("1RB1LB_1LA1RZ").unwrap();
+        let checkpoint = Checkpoint { program: "1RB1LB_1LA1RZ".to_string(), max_iterations: 30, iteration: 2, state: 'A', position: 0, origin: 0, tape: vec![1, 1], halted: false };
+        assert!(checkpoint.validate().is_ok());
+        for invalid in [
+            Checkpoint { state: 'a', ..checkpoint.clone() },
+            Checkpoint { halted: true, ..checkpoint.clone() },
+            Checkpoint { tape: Vec::new(), ..checkpoint.clone() },
+            Checkpoint { position: 3, ..checkpoint.clone() },
+            Checkpoint { origin: 1, ..checkpoint.clone() },
+            Checkpoint { position: i64::MAX, origin: i64::MIN, ..checkpoint.clone() },
+            Checkpoint { position: i64::MIN, origin: i64::MAX, ..checkpoint.clone() },
+            Checkpoint { position: i64::MAX, origin: i64::MAX, ..checkpoint.clone() },
+        ] {
+            assert!(matches!(BusyBeaver::new(program.clone()).with_checkpoint(invalid).run(30), Err(BusyBeaverError::InvalidCheckpoint { .. })));
+        }
+    }
+}
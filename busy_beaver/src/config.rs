@@ -1,9 +1,13 @@
 use serde::Deserialize;
 
+use crate::args::Tape;
+
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub max_iterations: usize,
     pub programs: Vec<String>,
+    #[serde(default)]
+    pub tape: Option<Tape>,
 }
 
 #[cfg(test)]
@@ -34,5 +38,13 @@ mod test {
         assert_eq!(config.programs.len(), 10);
         assert_eq!(config.programs[0], "1RB1RZ_1LB0RC_1LC1LA");
         assert_eq!(config.programs[1], "1RB1RZ_0LC0RC_1LC1LA");
+        assert_eq!(config.tape, None);
+    }
+
+    #[test]
+    fn test_config_tape() {
+        let config: Config = toml::from_str("max_iterations = 100\nprograms = []\ntape = \"rle\"").unwrap();
+        assert_eq!(config.tape, Some(Tape::Rle));
+        assert!(toml::from_str::<Config>("max_iterations = 100\nprograms = []\ntape = \"list\"").is_err());
     }
 }
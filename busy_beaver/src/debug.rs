@@ -0,0 +1,203 @@
+/**
+ * Interactive debugger for a single program. Commands are read a line at a time and step
+ * the machine, run it until a state or a breakpoint, or print the tape.
+ */
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+
+use busy_beaver::{Machine, ProgramKey, State, Symbol, Transition};
+
+use crate::output;
+
+/**
+ * Debugger struct with the machine and the breakpoints kept between lines.
+ */
+pub struct Debugger<'a> {
+    // The program transitions.
+    program: &'a HashMap<ProgramKey, Transition>,
+    // The running program.
+    machine: Machine<'a>,
+    // The state and the symbol under the head before a step that stops a run.
+    breakpoints: HashSet<(State, Symbol)>,
+    // The most steps taken by one command.
+    max_steps: usize,
+}
+
+impl<'a> Debugger<'a> {
+    /**
+     * Creates a new Debugger with the program before its first step.
+     *
+     * # Arguments
+     * `program` - The program transitions.
+     * `max_steps` - The most steps taken by one command.
+     *
+     * # Returns
+     * A new instance of Debugger.
+     */
+    pub fn new(program: &'a HashMap<ProgramKey, Transition>, max_steps: usize) -> Self {
+        Debugger { program, machine: Machine::new(program), breakpoints: HashSet::new(), max_steps }
+    }
+
+    /**
+     * Reads commands from stdin until quit or the end of the input.
+     */
+    pub fn repl(&mut self) {
+        println!("Busy Beaver debugger. Type help for commands.");
+        println!("{}", output::trace_line(&self.machine));
+        let mut lines = std::io::stdin().lock().lines();
+        loop {
+            print!("> ");
+            let _ = std::io::stdout().flush();
+            match lines.next() {
+                Some(Ok(line)) if matches!(line.trim(), "quit" | "q") => break,
+                Some(Ok(line)) => self.handle(&line),
+                _ => break,
+            }
+        }
+    }
+
+    /**
+     * Runs a command. Empty lines are ignored.
+     */
+    fn handle(&mut self, line: &str) {
+        let line = line.trim();
+        let (name, argument) = line.split_once(' ').map_or((line, ""), |(name, argument)| (name, argument.trim()));
+        match name {
+            "" => {}
+            "step" | "s" if argument.is_empty() => self.run(1, None),
+            "step" | "s" => match argument.parse() {
+                Ok(steps) => self.run(steps, None),
+                Err(_) => eprintln!("Invalid number of steps: {argument}"),
+            },
+            "run-until-state" | "until" => match parse_state(argument) {
+                Some(state) => self.run(self.max_steps, Some(state)),
+                None => eprintln!("Invalid state: {argument}"),
+            },
+            "continue" | "c" => self.run(self.max_steps, None),
+            "print" | "p" => {
+                println!("{}", output::trace_line(&self.machine));
+                let tape: Vec<String> = (0..self.machine.tape().len() as i64)
+                    .map(|index| self.machine.origin() + index)
+                    .map(|position| if position == self.machine.position() { format!("[{}]", self.machine.symbol(position)) } else { self.machine.symbol(position).to_string() })
+                    .collect();
+                println!("Tape from position {}: {}", self.machine.origin(), tape.join(" "));
+            }
+            "breakpoint" | "b" if argument.is_empty() => {
+                let mut breakpoints: Vec<_> = self.breakpoints.iter().collect();
+                breakpoints.sort();
+                breakpoints.iter().for_each(|(state, symbol)| println!("Breakpoint ({state},{symbol})"));
+            }
+            "breakpoint" | "b" => match parse_breakpoint(argument) {
+                Some(breakpoint) if self.breakpoints.remove(&breakpoint) => println!("Breakpoint ({},{}) removed", breakpoint.0, breakpoint.1),
+                Some(breakpoint) => {
+                    self.breakpoints.insert(breakpoint);
+                    println!("Breakpoint ({},{}) set", breakpoint.0, breakpoint.1);
+                }
+                None => eprintln!("Invalid breakpoint, expected a state and a symbol like (B,1): {argument}"),
+            },
+            "reset" => {
+                self.machine = Machine::new(self.program);
+                println!("{}", output::trace_line(&self.machine));
+            }
+            _ => {
+                println!("step [N]             Take one step or N steps");
+                println!("run-until-state S    Run until the program is in state S");
+                println!("continue             Run until a breakpoint or the program halts");
+                println!("print tape           Show the step, the state, the head and the whole tape");
+                println!("breakpoint (S,N)     Stop before a step in state S on symbol N, again to remove it");
+                println!("breakpoint           Show the breakpoints");
+                println!("reset                Go back to the blank tape before the first step");
+                println!("quit                 Exit");
+                println!("Each command takes at most {} steps.", self.max_steps);
+            }
+        }
+    }
+
+    /**
+     * Takes steps until the number of steps is taken, the program is in a state, a breakpoint
+     * is reached or the program halts, and prints where it stopped. A breakpoint does not
+     * stop the first step, so a run can go on from a breakpoint.
+     *
+     * # Arguments
+     * `steps` - The most steps to take.
+     * `until` - The state to stop in.
+     */
+    fn run(&mut self, steps: usize, until: Option<State>) {
+        for taken in 0..steps {
+            if self.machine.halted() {
+                println!("The program has halted");
+                break;
+            }
+            let breakpoint = (self.machine.state(), self.machine.symbol(self.machine.position()));
+            if taken > 0 && self.breakpoints.contains(&breakpoint) {
+                println!("Breakpoint ({},{})", breakpoint.0, breakpoint.1);
+                break;
+            }
+            if let Err(err) = self.machine.step() {
+                eprintln!("{err}");
+                break;
+            }
+            if until == Some(self.machine.state()) {
+                break;
+            }
+        }
+        println!("{}", output::trace_line(&self.machine));
+    }
+}
+
+/**
+ * Parses a state letter, like B.
+ */
+fn parse_state(text: &str) -> Option<State> {
+    let mut chars = text.trim().chars();
+    match (chars.next(), chars.next()) {
+        (Some(state), None) if state.is_ascii_uppercase() => Some(state),
+        _ => None,
+    }
+}
+
+/**
+ * Parses a breakpoint as a state and a symbol, like (B,1), B,1 or B 1.
+ */
+fn parse_breakpoint(text: &str) -> Option<(State, Symbol)> {
+    let text = text.trim().trim_start_matches('(').trim_end_matches(')');
+    let (state, symbol) = text.split_once(',').or_else(|| text.split_once(' '))?;
+    Some((parse_state(state)?, symbol.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use busy_beaver::HALTED;
+    use busy_beaver::parser::parse_program;
+
+    #[test]
+    fn test_debugger_commands() {
+        let program = parse_program("1RB1LB_1LA0LC_1RZ1LD_1RD0RA").unwrap();
+        let mut debugger = Debugger::new(&program, 1000);
+        debugger.handle("step");
+        debugger.handle("step 2");
+        assert_eq!(debugger.machine.iteration(), 3);
+        debugger.handle("run-until-state C");
+        assert_eq!(debugger.machine.state(), 'C');
+        debugger.handle("reset");
+        assert_eq!(debugger.machine.iteration(), 0);
+        debugger.handle("breakpoint (D,0)");
+        debugger.handle("continue");
+        assert_eq!((debugger.machine.state(), debugger.machine.symbol(debugger.machine.position())), ('D', 0));
+        let stopped = debugger.machine.iteration();
+        debugger.handle("continue");
+        assert!(debugger.machine.iteration() > stopped);
+        debugger.handle("breakpoint D 0");
+        debugger.handle("continue");
+        assert_eq!((debugger.machine.state(), debugger.machine.iteration()), (HALTED, 107));
+    }
+
+    #[test]
+    fn test_parse_breakpoint() {
+        assert_eq!(parse_breakpoint("(B,1)"), Some(('B', 1)));
+        assert_eq!(parse_breakpoint("B 1"), Some(('B', 1)));
+        assert_eq!(parse_breakpoint("b,1"), None);
+        assert_eq!(parse_breakpoint("B"), None);
+    }
+}
@@ -0,0 +1,485 @@
+/**
+ * Deciders that prove that a Busy Beaver program never halts, so it can be stopped
+ * before the maximum number of iterations.
+ */
+use std::collections::HashMap;
+use std::fmt;
+use std::mem::size_of;
+
+use crate::{State, Symbol};
+
+/**
+ * Deciders struct to select the deciders that are run along with a program.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Deciders {
+    // Bytes the cycle decider may use for the configurations it keeps, None to not detect cycles.
+    pub cycle_memory: Option<usize>,
+    // Bytes the translated cycle decider may use for the tapes it keeps, None to not detect translated cycles.
+    pub translated_cycle_memory: Option<usize>,
+    // Most steps between a configuration and its repeat for Lin's recurrence check, None to not check.
+    pub lin_recurrence_window: Option<usize>,
+}
+
+/**
+ * NonHalting enum to represent the proof that a program never halts.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonHalting {
+    // The configuration after `start` steps came back after `period` more steps, so the
+    // program repeats the same steps forever.
+    Cycle { start: usize, period: usize },
+    // The head reached a new end of the tape after `start` steps and again after `period`
+    // more steps, `shift` cells further, in the same state and with the same cells behind
+    // it as far as it went back in between. So the program repeats the same steps shifted
+    // along the tape forever.
+    TranslatedCycle { start: usize, period: usize, shift: i64 },
+    // The program was in the same state after `start` steps and after `period` more steps,
+    // `offset` cells further, with the same cells from as far back as it went in between,
+    // so it repeats the same steps forever, in place when `offset` is 0.
+    LinRecurrence { start: usize, period: usize, offset: i64 },
+}
+
+impl fmt::Display for NonHalting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NonHalting::Cycle { start, period } => write!(f, "cycle of period {period} from step {start}"),
+            NonHalting::TranslatedCycle { start, period, shift } => write!(f, "translated cycle of period {period} and shift {shift} from step {start}"),
+            NonHalting::LinRecurrence { start, period, offset } => write!(f, "Lin recurrence of period {period} and offset {offset} from step {start}"),
+        }
+    }
+}
+
+/**
+ * The tapes seen in a state with the head at a position, and the step each was seen at.
+ * The tapes are looked up by the borrowed tape, so it is only copied when it is kept.
+ */
+type Tapes = HashMap<Vec<Symbol>, usize>;
+
+/**
+ * CycleDecider struct to detect a configuration, the state, the position of the head and
+ * the tape, that repeats. The configurations are kept until the memory budget is used,
+ * after which a repeat of one of the kept configurations is still detected.
+ */
+pub(crate) struct CycleDecider {
+    // The tapes seen by the state and the position of the head.
+    seen: HashMap<(State, usize), Tapes>,
+    // Bytes used by the configurations kept.
+    used: usize,
+    // Bytes that may be used.
+    budget: usize,
+}
+
+impl CycleDecider {
+    /**
+     * Creates a new CycleDecider instance.
+     *
+     * # Arguments
+     * `budget` - Bytes that may be used for the configurations.
+     *
+     * # Returns
+     * A new instance of CycleDecider.
+     */
+    pub(crate) fn new(budget: usize) -> Self {
+        CycleDecider { seen: HashMap::new(), used: 0, budget }
+    }
+
+    /**
+     * Checks the configuration before a step.
+     *
+     * # Arguments
+     * `step` - The number of steps taken so far.
+     * `state` - The current state.
+     * `position` - The position of the head on the tape.
+     * `tape` - The tape, which only grows when the head moves past its ends.
+     *
+     * # Returns
+     * The cycle if the configuration was seen before, otherwise None.
+     */
+    pub(crate) fn check(&mut self, step: usize, state: State, position: usize, tape: &[Symbol]) -> Option<NonHalting> {
+        let tapes = self.seen.get(&(state, position));
+        if let Some(&start) = tapes.and_then(|tapes| tapes.get(tape)) {
+            return Some(NonHalting::Cycle { start, period: step - start });
+        }
+        let entry = if tapes.is_some() { 0 } else { size_of::<((State, usize), Tapes)>() };
+        let size = entry + size_of::<(Vec<Symbol>, usize)>() + size_of_val(tape);
+        if self.used + size <= self.budget {
+            self.used += size;
+            self.seen.entry((state, position)).or_default().insert(tape.to_vec(), step);
+        }
+        None
+    }
+}
+
+/**
+ * A time the head reached a new end of the tape.
+ */
+struct Record {
+    // The number of steps taken.
+    step: usize,
+    // The current state.
+    state: State,
+    // The position of the head, 0 is where it started.
+    position: i64,
+    // The position furthest from this end the head reached since the previous record at this end.
+    back: i64,
+    // The tape.
+    tape: Vec<Symbol>,
+    // The position of the first cell of the tape.
+    origin: i64,
+}
+
+impl Record {
+    /**
+     * Returns the symbol at a position, 0 outside the tape since the cell was not visited.
+     */
+    fn symbol(&self, position: i64) -> Symbol {
+        symbol(&self.tape, self.origin, position)
+    }
+}
+
+/**
+ * Returns the symbol at a position of a tape, 0 outside the tape.
+ */
+fn symbol(tape: &[Symbol], origin: i64, position: i64) -> Symbol {
+    usize::try_from(position - origin).ok().and_then(|index| tape.get(index)).copied().unwrap_or(0)
+}
+
+/**
+ * TranslatedCycleDecider struct to detect programs that repeat the same steps shifted
+ * along the tape. It keeps the tape each time the head reaches a new end of the tape,
+ * and compares the new end with the earlier ones at the same end. The cells past an end
+ * have never been visited, so they are blank both times, and only the cells as far back
+ * as the head went in between can change what the program does. Tapes are kept until
+ * the memory budget is used, after which a repeat of one of the kept tapes is still detected.
+ */
+pub(crate) struct TranslatedCycleDecider {
+    // The records at the right end and the left end.
+    right: Vec<Record>,
+    left: Vec<Record>,
+    // The leftmost position since the last kept record at the right end.
+    low: i64,
+    // The rightmost position since the last kept record at the left end.
+    high: i64,
+    // The rightmost and leftmost positions so far.
+    max: i64,
+    min: i64,
+    // Bytes used by the records kept.
+    used: usize,
+    // Bytes that may be used.
+    budget: usize,
+}
+
+impl TranslatedCycleDecider {
+    /**
+     * Creates a new TranslatedCycleDecider instance.
+     *
+     * # Arguments
+     * `budget` - Bytes that may be used for the tapes.
+     *
+     * # Returns
+     * A new instance of TranslatedCycleDecider.
+     */
+    pub(crate) fn new(budget: usize) -> Self {
+        TranslatedCycleDecider { right: Vec::new(), left: Vec::new(), low: 0, high: 0, max: 0, min: 0, used: 0, budget }
+    }
+
+    /**
+     * Checks the configuration before a step.
+     *
+     * # Arguments
+     * `step` - The number of steps taken so far.
+     * `state` - The current state.
+     * `position` - The position of the head, 0 is where it started.
+     * `tape` - The tape.
+     * `origin` - The position of the first cell of the tape.
+     *
+     * # Returns
+     * The translated cycle if the head is at a new end of the tape that repeats an earlier one, otherwise None.
+     */
+    pub(crate) fn check(&mut self, step: usize, state: State, position: i64, tape: &[Symbol], origin: i64) -> Option<NonHalting> {
+        self.low = self.low.min(position);
+        self.high = self.high.max(position);
+        if position > self.max {
+            self.max = position;
+            let found = Self::find(&self.right, state, position, self.low, tape, origin, step);
+            if found.is_none() && self.keep(tape) {
+                self.right.push(Record { step, state, position, back: self.low, tape: tape.to_vec(), origin });
+                self.low = position;
+            }
+            return found;
+        }
+        if position < self.min {
+            self.min = position;
+            let found = Self::find(&self.left, state, position, self.high, tape, origin, step);
+            if found.is_none() && self.keep(tape) {
+                self.left.push(Record { step, state, position, back: self.high, tape: tape.to_vec(), origin });
+                self.high = position;
+            }
+            return found;
+        }
+        None
+    }
+
+    /**
+     * Looks for an earlier record at the same end that the current configuration repeats.
+     *
+     * # Arguments
+     * `records` - The earlier records at the end, the oldest first.
+     * `state` - The current state.
+     * `position` - The position of the head.
+     * `back` - The position furthest from the end the head reached since the last record.
+     * `tape` - The tape.
+     * `origin` - The position of the first cell of the tape.
+     * `step` - The number of steps taken so far.
+     *
+     * # Returns
+     * The translated cycle from the latest record that matches, or None.
+     */
+    fn find(records: &[Record], state: State, position: i64, back: i64, tape: &[Symbol], origin: i64, step: usize) -> Option<NonHalting> {
+        let mut back = back;
+        for record in records.iter().rev() {
+            // The cells behind the end at the record that the head visited between the record and now.
+            let direction = (position - record.position).signum();
+            let depth = ((record.position - back) * direction).max(0);
+            if record.state == state && (0..=depth).all(|distance| record.symbol(record.position - direction * distance) == symbol(tape, origin, position - direction * distance)) {
+                return Some(NonHalting::TranslatedCycle { start: record.step, period: step - record.step, shift: position - record.position });
+            }
+            back = if direction > 0 { back.min(record.back) } else { back.max(record.back) };
+        }
+        None
+    }
+
+    /**
+     * Checks if the memory budget allows another tape to be kept, and counts it if it does.
+     */
+    fn keep(&mut self, tape: &[Symbol]) -> bool {
+        let size = size_of::<Record>() + size_of_val(tape);
+        if self.used + size > self.budget {
+            return false;
+        }
+        self.used += size;
+        true
+    }
+}
+
+/**
+ * A configuration kept by the Lin recurrence decider.
+ */
+struct Snapshot {
+    // The number of steps taken.
+    step: usize,
+    // The current state.
+    state: State,
+    // The position of the head, 0 is where it started.
+    position: i64,
+    // The tape.
+    tape: Vec<Symbol>,
+    // The position of the first cell of the tape.
+    origin: i64,
+}
+
+impl Snapshot {
+    /**
+     * Checks if the tape repeats the tape of the snapshot around the head.
+     *
+     * # Arguments
+     * `position` - The position of the head.
+     * `tape` - The tape.
+     * `origin` - The position of the first cell of the tape.
+     * `low` - The leftmost position since the snapshot.
+     * `high` - The rightmost position since the snapshot.
+     *
+     * # Returns
+     * True if the cells from low to high, and every cell ahead of them in the direction the head moved, are the same relative to the head.
+     */
+    fn repeats(&self, position: i64, tape: &[Symbol], origin: i64, low: i64, high: i64) -> bool {
+        let offset = position - self.position;
+        // Past the ends of both tapes every cell is blank.
+        let (first, last) = match offset.signum() {
+            1 => (low, (self.origin + self.tape.len() as i64).max(origin + tape.len() as i64 - offset)),
+            -1 => (self.origin.min(origin - offset), high),
+            _ => (low, high),
+        };
+        (first..=last).all(|cell| symbol(&self.tape, self.origin, cell) == symbol(tape, origin, cell + offset))
+    }
+}
+
+/**
+ * LinRecurrenceDecider struct to run Lin's recurrence check. A configuration is kept and
+ * every later configuration in the same state is compared with it. The program repeats
+ * when the cells the head visited in between are the same, and when the head moved, also
+ * every cell ahead of them, since the head reaches those cells next time. The kept
+ * configuration is replaced after a number of steps that doubles each time up to the
+ * window, so a recurrence with a period up to the window is found once the program is in it.
+ */
+pub(crate) struct LinRecurrenceDecider {
+    // The configuration compared with.
+    snapshot: Option<Snapshot>,
+    // The leftmost and rightmost positions since the snapshot.
+    low: i64,
+    high: i64,
+    // Steps before the snapshot is replaced.
+    length: usize,
+    // Most steps before the snapshot is replaced.
+    window: usize,
+}
+
+impl LinRecurrenceDecider {
+    /**
+     * Creates a new LinRecurrenceDecider instance.
+     *
+     * # Arguments
+     * `window` - Most steps between a configuration and its repeat.
+     *
+     * # Returns
+     * A new instance of LinRecurrenceDecider.
+     */
+    pub(crate) fn new(window: usize) -> Self {
+        LinRecurrenceDecider { snapshot: None, low: 0, high: 0, length: 1, window: window.max(1) }
+    }
+
+    /**
+     * Checks the configuration before a step.
+     *
+     * # Arguments
+     * `step` - The number of steps taken so far.
+     * `state` - The current state.
+     * `position` - The position of the head, 0 is where it started.
+     * `tape` - The tape.
+     * `origin` - The position of the first cell of the tape.
+     *
+     * # Returns
+     * The recurrence if the configuration repeats the kept one, otherwise None.
+     */
+    pub(crate) fn check(&mut self, step: usize, state: State, position: i64, tape: &[Symbol], origin: i64) -> Option<NonHalting> {
+        if let Some(snapshot) = &self.snapshot {
+            self.low = self.low.min(position);
+            self.high = self.high.max(position);
+            if snapshot.state == state && snapshot.repeats(position, tape, origin, self.low, self.high) {
+                return Some(NonHalting::LinRecurrence { start: snapshot.step, period: step - snapshot.step, offset: position - snapshot.position });
+            }
+            if step - snapshot.step < self.length {
+                return None;
+            }
+            self.length = (self.length * 2).min(self.window);
+        }
+        self.snapshot = Some(Snapshot { step, state, position, tape: tape.to_vec(), origin });
+        self.low = position;
+        self.high = position;
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ZanyZoo;
+    use crate::enumerate::enumerate;
+
+    #[test]
+    fn test_cycle_decider() {
+        let mut decider = CycleDecider::new(1 << 20);
+        assert_eq!(decider.check(0, 'A', 0, &[0, 0]), None);
+        assert_eq!(decider.check(1, 'B', 1, &[1, 0]), None);
+        assert_eq!(decider.check(2, 'A', 0, &[1, 0]), None);
+        assert_eq!(decider.check(3, 'B', 1, &[1, 0]), Some(NonHalting::Cycle { start: 1, period: 2 }));
+    }
+
+    #[test]
+    fn test_translated_cycle_decider() {
+        // The head goes back to 0 after the record at 1, so cells 0 and 1 must repeat as cells 1 and 2.
+        for (tape, expected) in [([1, 0, 0], None), ([1, 1, 0], Some(NonHalting::TranslatedCycle { start: 1, period: 2, shift: 1 }))] {
+            let mut decider = TranslatedCycleDecider::new(1 << 20);
+            assert_eq!(decider.check(0, 'A', 0, &[0, 0], 0), None);
+            assert_eq!(decider.check(1, 'B', 1, &[1, 0], 0), None);
+            assert_eq!(decider.check(2, 'A', 0, &[1, 0], 0), None);
+            assert_eq!(decider.check(3, 'B', 2, &tape, 0), expected);
+        }
+    }
+
+    #[test]
+    fn test_translated_cycle_decider_left() {
+        let mut decider = TranslatedCycleDecider::new(1 << 20);
+        assert_eq!(decider.check(0, 'A', 0, &[0, 0], 0), None);
+        assert_eq!(decider.check(1, 'A', -1, &[0, 0, 0], -1), None);
+        assert_eq!(decider.check(2, 'A', -2, &[0, 0, 0, 0], -2), Some(NonHalting::TranslatedCycle { start: 1, period: 1, shift: -1 }));
+    }
+
+    #[test]
+    fn test_translated_cycles_do_not_halt() {
+        let programs = enumerate(3, 2, 100);
+        let deciders = Deciders { translated_cycle_memory: Some(1 << 20), ..Deciders::default() };
+        let results = ZanyZoo::new(programs.clone()).with_deciders(deciders).run_parallel(100, 1);
+        let mut translated = 0;
+        for (program, result) in programs.iter().zip(&results) {
+            let result = result.as_ref().unwrap();
+            if let Some(NonHalting::TranslatedCycle { .. }) = result.non_halting {
+                translated += 1;
+                assert!(!ZanyZoo::run_program(program, 1000).unwrap().halted, "{program} halts");
+            }
+        }
+        assert!(translated > 0);
+        let halted: Vec<_> = results.iter().flatten().filter(|result| result.halted).collect();
+        assert_eq!(halted.iter().map(|result| result.values).max(), Some(6));
+        assert_eq!(halted.iter().map(|result| result.iterations).max(), Some(21));
+    }
+
+    #[test]
+    fn test_lin_recurrence_decider() {
+        // The snapshot is replaced at step 1, and cells 0 and 1 are visited before the state repeats.
+        for (tape, expected) in [([1, 1], None), ([1, 0], Some(NonHalting::LinRecurrence { start: 1, period: 2, offset: 0 }))] {
+            let mut decider = LinRecurrenceDecider::new(100);
+            assert_eq!(decider.check(0, 'A', 0, &[0, 0], 0), None);
+            assert_eq!(decider.check(1, 'B', 1, &[1, 0], 0), None);
+            assert_eq!(decider.check(2, 'A', 0, &[1, 0], 0), None);
+            assert_eq!(decider.check(3, 'B', 1, &tape, 0), expected);
+        }
+    }
+
+    #[test]
+    fn test_lin_recurrence_decider_shifted() {
+        // The cells ahead of the head must be the same as well.
+        for (tape, expected) in [([1, 1], None), ([1, 0], Some(NonHalting::LinRecurrence { start: 0, period: 1, offset: 1 }))] {
+            let mut decider = LinRecurrenceDecider::new(100);
+            assert_eq!(decider.check(0, 'A', 0, &[0, 0], 0), None);
+            assert_eq!(decider.check(1, 'A', 1, &tape, 0), expected);
+        }
+        let mut decider = LinRecurrenceDecider::new(100);
+        assert_eq!(decider.check(0, 'A', 0, &[0, 0], 0), None);
+        assert_eq!(decider.check(1, 'A', -1, &[0, 0, 0], -1), Some(NonHalting::LinRecurrence { start: 0, period: 1, offset: -1 }));
+    }
+
+    #[test]
+    fn test_lin_recurrences_do_not_halt() {
+        let programs = enumerate(3, 2, 100);
+        let deciders = Deciders { lin_recurrence_window: Some(100), ..Deciders::default() };
+        let results = ZanyZoo::new(programs.clone()).with_deciders(deciders).run_parallel(100, 1);
+        let mut offsets = Vec::new();
+        for (program, result) in programs.iter().zip(&results) {
+            if let Some(NonHalting::LinRecurrence { offset, .. }) = result.as_ref().unwrap().non_halting {
+                offsets.push(offset);
+                assert!(!ZanyZoo::run_program(program, 1000).unwrap().halted, "{program} halts");
+            }
+        }
+        assert!(offsets.contains(&0));
+        assert!(offsets.iter().any(|&offset| offset != 0));
+        let halted: Vec<_> = results.iter().flatten().filter(|result| result.halted).collect();
+        assert_eq!(halted.iter().map(|result| result.values).max(), Some(6));
+        assert_eq!(halted.iter().map(|result| result.iterations).max(), Some(21));
+    }
+
+    #[test]
+    fn test_cycle_decider_budget() {
+        let mut decider = CycleDecider::new(0);
+        assert_eq!(decider.check(0, 'A', 0, &[0, 0]), None);
+        assert_eq!(decider.check(1, 'A', 0, &[0, 0]), None);
+        // Only the first configuration fits, and its repeat is still found after the budget is used.
+        let mut decider = CycleDecider::new(size_of::<((State, usize), Tapes)>() + size_of::<(Vec<Symbol>, usize)>() + 2 * size_of::<Symbol>());
+        assert_eq!(decider.check(0, 'A', 0, &[0, 0]), None);
+        assert_eq!(decider.check(1, 'A', 0, &[1, 0]), None);
+        assert_eq!(decider.check(2, 'A', 0, &[1, 0]), None);
+        assert_eq!(decider.check(3, 'A', 0, &[0, 0]), Some(NonHalting::Cycle { start: 0, period: 3 }));
+        assert_eq!(decider.seen.values().map(|tapes| tapes.len()).sum::<usize>(), 1);
+    }
+}
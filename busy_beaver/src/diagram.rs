@@ -0,0 +1,172 @@
+/**
+ * Space-time diagram of a run, the usual picture of what a Busy Beaver program does. Each
+ * recorded step is a row of pixels with the tape after the step, from the first step at the
+ * top, and each position of the tape is a column. The cell under the head is marked.
+ */
+use busy_beaver::{Machine, Symbol};
+use render::{Color, Image, palette};
+
+/**
+ * Color of the cell under the head.
+ */
+const HEAD_COLOR: Color = [255, 0, 0];
+
+/**
+ * A recorded step: the cells that changed since the previous recorded step, with their
+ * position and new symbol, and the position of the head. Only the changes are kept, so a
+ * long run does not keep a copy of the tape for every row.
+ */
+type Row = (Vec<(i64, Symbol)>, i64);
+
+/**
+ * Diagram struct that records the tape of a run.
+ */
+pub struct Diagram {
+    // The number of steps between the recorded rows.
+    every: usize,
+    // The recorded steps, the first step first.
+    rows: Vec<Row>,
+    // The tape at the last recorded step, and the position of its first cell.
+    tape: Vec<Symbol>,
+    origin: i64,
+    // The leftmost and rightmost positions of the tape or the head in any row.
+    low: i64,
+    high: i64,
+    // The highest symbol in any row.
+    max_symbol: Symbol,
+}
+
+impl Diagram {
+    /**
+     * Creates a new Diagram without rows.
+     *
+     * # Arguments
+     * `every` - The number of steps between the recorded rows, 1 to record each step.
+     *
+     * # Returns
+     * A new instance of Diagram.
+     */
+    pub fn new(every: usize) -> Self {
+        Diagram { every: every.max(1), rows: Vec::new(), tape: Vec::new(), origin: 0, low: 0, high: 0, max_symbol: 1 }
+    }
+
+    /**
+     * Records the cells that changed since the last recorded step when the step is one of
+     * every Nth steps or the program has halted.
+     *
+     * # Arguments
+     * `machine` - The running program.
+     */
+    pub fn record(&mut self, machine: &Machine) {
+        if machine.iteration() % self.every != 0 && !machine.halted() {
+            return;
+        }
+        let (origin, tape, head) = (machine.origin(), machine.tape(), machine.position());
+        let changes: Vec<(i64, Symbol)> = tape
+            .iter()
+            .enumerate()
+            .map(|(index, &symbol)| (origin + index as i64, symbol))
+            .filter(|&(position, symbol)| symbol != usize::try_from(position - self.origin).ok().and_then(|index| self.tape.get(index)).copied().unwrap_or(0))
+            .collect();
+        if self.rows.is_empty() {
+            (self.low, self.high) = (origin.min(head), head);
+        }
+        self.low = self.low.min(origin).min(head);
+        self.high = self.high.max(origin + tape.len() as i64 - 1).max(head);
+        self.max_symbol = changes.iter().map(|change| change.1).fold(self.max_symbol, Symbol::max);
+        self.tape.clear();
+        self.tape.extend_from_slice(tape);
+        self.origin = origin;
+        self.rows.push((changes, head));
+    }
+
+    /**
+     * Draws the diagram with a column for each position any row reached, by applying the
+     * changes of each row to the tape of the row above it. Symbol 0 is black, 1 is white
+     * and higher symbols have their own colors.
+     *
+     * # Arguments
+     * `scale` - The size of a cell in pixels.
+     *
+     * # Returns
+     * The image.
+     */
+    pub fn image(&self, scale: usize) -> Image {
+        let scale = scale.max(1);
+        let colors = palette(self.max_symbol + 1);
+        let width = (self.high - self.low + 1) as usize;
+        let mut image = Image::new(width * scale, self.rows.len() * scale, colors[0]);
+        let mut cells: Vec<Symbol> = vec![0; width];
+        for (y, (changes, head)) in self.rows.iter().enumerate() {
+            for &(position, symbol) in changes {
+                cells[(position - self.low) as usize] = symbol;
+            }
+            for (x, &symbol) in cells.iter().enumerate() {
+                let color = if self.low + x as i64 == *head { HEAD_COLOR } else { colors[symbol] };
+                image.fill_rect(x * scale, y * scale, scale, scale, color);
+            }
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use busy_beaver::parser::parse_program;
+    use render::{BLACK, WHITE};
+
+    #[test]
+    fn test_diagram() {
+        let program = parse_program("1RB1LB_1LA1RZ").unwrap();
+        let mut machine = Machine::new(&program);
+        let mut diagram = Diagram::new(2);
+        diagram.record(&machine);
+        while !machine.halted() {
+            machine.step().unwrap();
+            diagram.record(&machine);
+        }
+        // Steps 0, 2, 4 and 6 from position -2 to 1.
+        let image = diagram.image(1);
+        assert_eq!((image.width(), image.height()), (4, 4));
+        assert_eq!(image.rows().next().unwrap(), &[BLACK, BLACK, HEAD_COLOR, BLACK]);
+        assert_eq!(image.rows().nth(2).unwrap(), &[HEAD_COLOR, WHITE, WHITE, WHITE]);
+        assert_eq!(image.rows().nth(3).unwrap(), &[WHITE, HEAD_COLOR, WHITE, WHITE]);
+        assert_eq!(diagram.image(3).width(), 12);
+    }
+
+    #[test]
+    fn test_diagram_keeps_the_changes() {
+        let program = parse_program("1RB1LC_0LA1RE_0LD0LB_1RA1RZ_1LA0RE").unwrap();
+        let mut machine = Machine::new(&program);
+        let mut diagram = Diagram::new(1);
+        let mut tapes = Vec::new();
+        diagram.record(&machine);
+        tapes.push((machine.origin(), machine.tape().to_vec(), machine.position()));
+        while !machine.halted() {
+            machine.step().unwrap();
+            diagram.record(&machine);
+            tapes.push((machine.origin(), machine.tape().to_vec(), machine.position()));
+        }
+        // A step writes one cell, so each row holds at most one change.
+        assert!(diagram.rows.iter().all(|(changes, _)| changes.len() <= 1));
+        let low = tapes.iter().map(|(origin, _, head)| (*origin).min(*head)).min().unwrap();
+        let image = diagram.image(1);
+        for ((origin, cells, head), row) in tapes.iter().zip(image.rows()) {
+            for (x, &color) in row.iter().enumerate() {
+                let position = low + x as i64;
+                let symbol = usize::try_from(position - origin).ok().and_then(|index| cells.get(index)).copied().unwrap_or(0);
+                assert_eq!(
+                    color,
+                    if position == *head {
+                        HEAD_COLOR
+                    } else if symbol == 0 {
+                        BLACK
+                    } else {
+                        WHITE
+                    }
+                );
+            }
+        }
+    }
+}
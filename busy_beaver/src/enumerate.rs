@@ -0,0 +1,213 @@
+/**
+ * Enumeration of all Busy Beaver programs with a number of states and symbols in Tree
+ * Normal Form. The enumeration starts with a program without transitions and runs it
+ * on a blank tape. When it reaches a transition that is not defined, the program is
+ * split into one program for each way to define it. So only transitions that are used
+ * are defined, and the others are left as "---".
+ *
+ * Programs that only differ by the names of the states or the symbols, or that are
+ * mirror images of each other, are only enumerated once. A state or a symbol that is
+ * not used yet is only introduced as the lowest unused one, and the first move is
+ * always to the right. The last undefined transition is always the halt, since a
+ * program without one can not halt.
+ */
+use crate::{Direction, HALTED, STATES, Symbol, UNSPECIFICED};
+
+/**
+ * Largest number of symbols, since a symbol is written as one digit.
+ */
+pub const MAX_SYMBOLS: usize = 10;
+
+/**
+ * The transition that halts, as in the standard notation.
+ */
+const HALT_TRANSITION: Action = Action { symbol: 1, direction: Direction::Right, state: None };
+
+/**
+ * A defined transition, with None as the state that halts.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Action {
+    // The symbol to write on the tape
+    symbol: Symbol,
+    // The direction to move the tape head
+    direction: Direction,
+    // The index of the state to transition to, None to halt
+    state: Option<usize>,
+}
+
+/**
+ * Machine struct to represent a program of the enumeration where only some transitions are defined.
+ */
+#[derive(Debug, Clone)]
+struct Machine {
+    // The number of states of the program.
+    states: usize,
+    // The number of symbols of the program.
+    symbols: usize,
+    // The transitions by state and then symbol, None when not defined.
+    table: Vec<Option<Action>>,
+}
+
+/**
+ * Enumerates the programs with the number of states and symbols.
+ *
+ * # Arguments
+ * `states` - The number of states, from 1 to 25.
+ * `symbols` - The number of symbols, from 2 to 10.
+ * `max_iterations` - The maximum number of iterations each program is run to find its next
+ *   undefined transition. A program that runs longer is not split further.
+ *
+ * # Returns
+ * The programs in the standard format, like "1RB1LB_1LA1RZ".
+ *
+ * # Panics
+ * If the number of states or symbols is out of range.
+ */
+pub fn enumerate(states: usize, symbols: usize, max_iterations: usize) -> Vec<String> {
+    assert!((1..STATES.len()).contains(&states), "The number of states must be from 1 to {}", STATES.len() - 1);
+    assert!((2..=MAX_SYMBOLS).contains(&symbols), "The number of symbols must be from 2 to {MAX_SYMBOLS}");
+    let mut programs = Vec::new();
+    let mut pending = vec![Machine { states, symbols, table: vec![None; states * symbols] }];
+    while let Some(machine) = pending.pop() {
+        match machine.undefined_transition(max_iterations) {
+            Some(index) => {
+                // Reversed so the programs come out in the order the children are created.
+                pending.extend(machine.children(index).into_iter().rev());
+            }
+            None => programs.push(machine.to_string()),
+        }
+    }
+    programs
+}
+
+impl Machine {
+    /**
+     * Runs the program on a blank tape until it halts, reaches an undefined transition or
+     * runs for the maximum number of iterations.
+     *
+     * # Returns
+     * The index in the table of the undefined transition that was reached, or None.
+     */
+    fn undefined_transition(&self, max_iterations: usize) -> Option<usize> {
+        // The cells at position 0 and to the right, and the cells to the left.
+        let (mut right, mut left): (Vec<Symbol>, Vec<Symbol>) = (Vec::new(), Vec::new());
+        let (mut position, mut state) = (0_i64, 0_usize);
+        for _ in 0..max_iterations {
+            let (cells, cell) = if position >= 0 { (&mut right, position as usize) } else { (&mut left, (-position - 1) as usize) };
+            if cell >= cells.len() {
+                cells.resize(cell + 1, 0);
+            }
+            let index = state * self.symbols + cells[cell];
+            let Some(action) = self.table[index] else {
+                return Some(index);
+            };
+            cells[cell] = action.symbol;
+            position += if action.direction == Direction::Left { -1 } else { 1 };
+            state = action.state?;
+        }
+        None
+    }
+
+    /**
+     * Creates a program for each way to define an undefined transition.
+     *
+     * # Arguments
+     * `index` - The index in the table of the transition.
+     *
+     * # Returns
+     * The programs with the transition defined.
+     */
+    fn children(&self, index: usize) -> Vec<Machine> {
+        let defined = self.table.iter().filter(|action| action.is_some()).count();
+        let halt = self.with(index, HALT_TRANSITION);
+        if defined + 1 == self.table.len() {
+            return vec![halt];
+        }
+        // States and symbols that are used so far, the first state and the blank symbol are always used.
+        let used_states = self.table.iter().flatten().filter_map(|action| action.state).max().unwrap_or(0).max(index / self.symbols);
+        let used_symbols = self.table.iter().flatten().map(|action| action.symbol).max().unwrap_or(0);
+        let directions: &[Direction] = if defined == 0 { &[Direction::Right] } else { &[Direction::Left, Direction::Right] };
+        let mut children = vec![halt];
+        for symbol in 0..=(used_symbols + 1).min(self.symbols - 1) {
+            for &direction in directions {
+                for state in 0..=(used_states + 1).min(self.states - 1) {
+                    children.push(self.with(index, Action { symbol, direction, state: Some(state) }));
+                }
+            }
+        }
+        children
+    }
+
+    /**
+     * Returns a copy of the program with a transition defined.
+     */
+    fn with(&self, index: usize, action: Action) -> Machine {
+        let mut machine = self.clone();
+        machine.table[index] = Some(action);
+        machine
+    }
+}
+
+impl std::fmt::Display for Machine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (state, actions) in self.table.chunks(self.symbols).enumerate() {
+            if state > 0 {
+                write!(f, "_")?;
+            }
+            for action in actions {
+                match action {
+                    Some(action) => {
+                        let direction = if action.direction == Direction::Left { 'L' } else { 'R' };
+                        write!(f, "{}{direction}{}", action.symbol, action.state.map_or(HALTED, |state| STATES[state]))?;
+                    }
+                    None => write!(f, "{UNSPECIFICED}{UNSPECIFICED}{UNSPECIFICED}")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ZanyZoo;
+
+    /**
+     * Runs all programs and returns the most ones and the most steps of the programs that halt.
+     */
+    fn records(programs: Vec<String>, max_iterations: usize) -> (usize, usize) {
+        let results = ZanyZoo::new(programs).run_parallel(max_iterations, 1);
+        let halted: Vec<_> = results.iter().map(|result| result.as_ref().unwrap()).filter(|result| result.halted).collect();
+        (halted.iter().map(|result| result.values).max().unwrap(), halted.iter().map(|result| result.iterations).max().unwrap())
+    }
+
+    #[test]
+    fn test_enumerate_1x2() {
+        assert_eq!(enumerate(1, 2, 10), vec!["1RZ---", "0RA---", "1RA---"]);
+    }
+
+    #[test]
+    fn test_enumerate_first_transition() {
+        let programs = enumerate(2, 2, 10);
+        assert!(programs.iter().all(|program| program.starts_with("1RZ") || program.as_bytes()[1] == b'R'));
+        assert!(programs.contains(&"1RB1LB_1LA1RZ".to_string()));
+        assert!(!programs.contains(&"1LB1RB_1RA1LZ".to_string()));
+    }
+
+    #[test]
+    fn test_enumerate_2x2_records() {
+        assert_eq!(records(enumerate(2, 2, 100), 100), (4, 6));
+    }
+
+    #[test]
+    fn test_enumerate_3x2_records() {
+        assert_eq!(records(enumerate(3, 2, 100), 100), (6, 21));
+    }
+
+    #[test]
+    fn test_enumerate_2x3_records() {
+        assert_eq!(records(enumerate(2, 3, 100), 100), (9, 38));
+    }
+}
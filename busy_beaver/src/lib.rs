@@ -0,0 +1,1116 @@
+/**
+ * Busy Beaver program in Rust.
+ * Description: https://en.wikipedia.org/wiki/Busy_beaver
+ *
+ * A Busy Beaver is a Turing machine that starts on a blank tape. Programs are read
+ * with the parser and run by BusyBeaver, or in batches by ZanyZoo, where Champions
+ * keeps the records.
+ */
+pub mod champions;
+pub mod checkpoint;
+pub mod decider;
+pub mod enumerate;
+mod macro_machine;
+pub mod parser;
+pub mod progress;
+pub mod run_length;
+mod tape;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use checkpoint::Checkpoint;
+use decider::{CycleDecider, Deciders, LinRecurrenceDecider, NonHalting, TranslatedCycleDecider};
+use macro_machine::MacroMachine;
+use run_length::{Run, RunLengthMachine};
+use tape::Tape;
+
+/**
+ * State to halt program.
+ */
+pub const HALTED: char = 'Z';
+
+/**
+ * Unspecified state, used to indicate that a transition does not specify a symbol or state.
+ * This is used in the transitions to indicate that there is no specific action for that transition.
+ */
+pub const UNSPECIFICED: char = '-';
+
+/**
+ * Symbol and state types.
+ * These are used to represent the symbols on the tape and the states of the Busy Beaver program.
+ *
+ * Symbols are by convention represented as usize, and states are represented as char.
+ */
+pub type Symbol = usize;
+pub type State = char;
+
+/**
+ * Array of states used in the Busy Beaver program.
+ * This array contains the states from 'A' to 'Y', and the HALTED state 'Z'.
+ *
+ * The states are used to represent the different states of the Busy Beaver program.
+ * This can be calculated, but this is just easier.
+ */
+pub const STATES: [State; 26] = ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', HALTED];
+
+/**
+ * Direction enum to represent the direction of movement on the tape. This is
+ * used in the transitions.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    // Represents moving the tape head to the left
+    Left,
+    // Represents moving the tape head to the right
+    Right,
+}
+
+/**
+ * TapeKind enum to select how the tape is kept while a program runs.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TapeKind {
+    // A cell for each position the head visited.
+    #[default]
+    Cells,
+    // Runs of the same symbol with the number of cells, which sweeps over a run in one step.
+    RunLength,
+}
+
+/**
+ * BusyBeaverTransitionResult enum to represent the result of a transition in the Busy Beaver program.
+ * It indicates whether to continue processing, break the loop, or if the transition failed.
+ *
+ * Continue: Indicates that the program should continue processing the next transition.
+ * Break: Indicates that the program should stop processing further transitions.
+ * Failed: Indicates that the transition failed, which could happen if the program encounters an unexpected state or symbol.
+ */
+enum BusyBeaverTransitionResult {
+    // Indicates that the program should continue processing the next transition
+    Continue,
+    // Indicates that the program should stop processing further transitions
+    Break,
+}
+
+/**
+ * BusyBeaverError enum to represent errors that can occur in the Busy Beaver program.
+ */
+#[derive(Debug)]
+pub enum BusyBeaverError {
+    // A transition without a symbol, state or direction was used.
+    InvalidTransition { message: String, transition: Transition },
+    // The program has no transition for the symbol under the head in the current state.
+    TransitionNotFound { key: ProgramKey, transition: Transition },
+    // The program text is not a valid program.
+    ProgramReadError { message: String },
+    // The checkpoint is not a configuration the program can be resumed from.
+    InvalidCheckpoint { message: String },
+}
+
+impl fmt::Display for BusyBeaverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BusyBeaverError::InvalidTransition { message, transition } => write!(f, "Invalid Transition: {message} - {transition:?}"),
+            BusyBeaverError::TransitionNotFound { key, transition } => write!(f, "Transition Not Found: {key:?} - {transition:?}"),
+            BusyBeaverError::ProgramReadError { message } => write!(f, "Program Read Error: {message}"),
+            BusyBeaverError::InvalidCheckpoint { message } => write!(f, "Invalid Checkpoint: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for BusyBeaverError {}
+
+/**
+ * Transition struct to represent a transition in the Busy Beaver program.
+ */
+#[derive(Debug, Clone)]
+pub struct Transition {
+    // The symbol to write on the tape
+    pub symbol: Option<Symbol>,
+    // The state to transition to
+    pub state: Option<State>,
+    // The direction to move the tape head
+    pub direction: Option<Direction>,
+}
+
+impl Transition {
+    /**
+     * Creates a new Transition instance.
+     *
+     * # Arguments
+     * `symbol` - The symbol to write on the tape.
+     * `state` - The state to transition to.
+     * `direction` - The direction to move the tape head (Left or Right).
+     *
+     * # Returns
+     * A new instance of Transition.
+     */
+    pub fn new(symbol: Option<Symbol>, state: Option<State>, direction: Option<Direction>) -> Self {
+        Transition { symbol, state, direction }
+    }
+}
+
+/**
+ * ProgramKey struct to represent a unique key for the Busy Beaver program.
+ * It combines a symbol and a state to uniquely identify a transition
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProgramKey {
+    // The symbol associated with key.
+    pub symbol: Symbol,
+    // The state associated with key.
+    pub state: State,
+}
+
+impl ProgramKey {
+    /**
+     * Creates a new ProgramKey instance.
+     *
+     * # Arguments
+     * `symbol` - The symbol associated with the key.
+     * `state` - The state associated with the key.
+     *
+     * # Returns
+     * A new instance of ProgramKey.
+     */
+    pub fn new(symbol: Symbol, state: State) -> Self {
+        ProgramKey { symbol, state }
+    }
+}
+
+/**
+ * ZanyZoo struct to represent the Zany Zoo program.
+ * It contains the number of states and symbols, and it runs the Busy Beaver program.
+ */
+pub struct ZanyZoo {
+    // A vector of strings representing the Busy Beaver programs.
+    // Each string is a Busy Beaver program in the format "1RB1LB_1LA1RZ",
+    // where the first part represents the transitions for state A, and the
+    // second part represents the transitions for state B, and so on.
+    programs: Vec<String>,
+    // The deciders run along with each program.
+    deciders: Deciders,
+    // The number of tape cells the programs are run on at a time.
+    block_size: usize,
+    // How the tape of the programs is kept.
+    tape: TapeKind,
+}
+
+impl ZanyZoo {
+    /**
+     * Creates a new ZanyZoo instance.
+     *
+     * # Arguments
+     * `programs` - A vector of strings representing the Busy Beaver programs.
+     *
+     * # Returns
+     * A new instance of ZanyZoo.
+     */
+    pub fn new(programs: Vec<String>) -> Self {
+        ZanyZoo { programs, deciders: Deciders::default(), block_size: 1, tape: TapeKind::Cells }
+    }
+
+    /**
+     * Sets the deciders that are run along with each program. No deciders are run by default.
+     *
+     * # Arguments
+     * `deciders` - The deciders to run.
+     *
+     * # Returns
+     * The ZanyZoo with the deciders.
+     */
+    pub fn with_deciders(mut self, deciders: Deciders) -> Self {
+        self.deciders = deciders;
+        self
+    }
+
+    /**
+     * Sets the number of tape cells each program is run on at a time, see BusyBeaver::with_block_size.
+     *
+     * # Arguments
+     * `block_size` - The number of cells in a block, 1 to run the programs one cell at a time.
+     *
+     * # Returns
+     * The ZanyZoo with the block size.
+     */
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size;
+        self
+    }
+
+    /**
+     * Sets how the tape of each program is kept, see BusyBeaver::with_tape.
+     *
+     * # Arguments
+     * `tape` - The kind of tape.
+     *
+     * # Returns
+     * The ZanyZoo with the kind of tape.
+     */
+    pub fn with_tape(mut self, tape: TapeKind) -> Self {
+        self.tape = tape;
+        self
+    }
+
+    /**
+     * Returns the programs of the Zany Zoo, in the order they are run.
+     */
+    pub fn programs(&self) -> &[String] {
+        &self.programs
+    }
+
+    /**
+     * Runs the Busy Beaver program for each program in the Zany Zoo.
+     * It initializes the program transitions and runs the Busy Beaver program.
+     * Programs that fail are reported on stderr and left out of the results.
+     *
+     * # Arguments
+     * `max_iterations` - The maximum number of iterations to run the Busy Beaver program.
+     *
+     * # Returns
+     * A vector of BusyBeaverResult containing the results of running each program.
+     */
+    pub fn run(&self, max_iterations: usize) -> Vec<BusyBeaverResult> {
+        let mut results: Vec<BusyBeaverResult> = Vec::new();
+        for (index, program_str) in self.programs.iter().enumerate() {
+            match self.run_one(program_str, index, max_iterations, &|_, _| {}) {
+                Ok(result) => results.push(result),
+                Err(err) => {
+                    Self::print_error(err);
+                }
+            }
+        }
+        results
+    }
+
+    /**
+     * Runs the Busy Beaver program for each program in the Zany Zoo on a number of threads.
+     *
+     * # Arguments
+     * `max_iterations` - The maximum number of iterations to run the Busy Beaver program.
+     * `jobs` - The number of threads. One runs the programs on the calling thread.
+     *
+     * # Returns
+     * The result or the error of each program, in the order of the programs.
+     */
+    pub fn run_parallel(&self, max_iterations: usize, jobs: usize) -> Vec<Result<BusyBeaverResult, BusyBeaverError>> {
+        let mut results: Vec<(usize, Result<BusyBeaverResult, BusyBeaverError>)> = Vec::with_capacity(self.programs.len());
+        self.run_with(max_iterations, jobs, |index, result| results.push((index, result)));
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /**
+     * Runs the Busy Beaver program for each program in the Zany Zoo on a number of threads,
+     * and calls a function on the calling thread with each result as soon as it is done.
+     * Each thread takes the next program that has not been started, so long and short
+     * programs are spread evenly over the threads.
+     *
+     * # Arguments
+     * `max_iterations` - The maximum number of iterations to run the Busy Beaver program.
+     * `jobs` - The number of threads. One runs the programs on the calling thread.
+     * `on_result` - Called with the index of the program and its result or error, in the
+     *   order the programs finish.
+     */
+    pub fn run_with(&self, max_iterations: usize, jobs: usize, on_result: impl FnMut(usize, Result<BusyBeaverResult, BusyBeaverError>)) {
+        self.run_with_steps(max_iterations, jobs, |_, _| {}, on_result);
+    }
+
+    /**
+     * Runs the programs like run_with, and also calls a function with the machine before the
+     * first step and after each step of a program, on the thread that runs the program, so a
+     * long program can be followed while it runs.
+     *
+     * # Arguments
+     * `max_iterations` - The maximum number of iterations to run the Busy Beaver program.
+     * `jobs` - The number of threads. One runs the programs on the calling thread.
+     * `on_step` - Called with the index of the program and the machine, like BusyBeaver::run_with.
+     * `on_result` - Called with the index of the program and its result or error, in the
+     *   order the programs finish.
+     */
+    pub fn run_with_steps(&self, max_iterations: usize, jobs: usize, on_step: impl Fn(usize, &Machine) + Sync, mut on_result: impl FnMut(usize, Result<BusyBeaverResult, BusyBeaverError>)) {
+        let jobs = jobs.min(self.programs.len());
+        if jobs <= 1 {
+            for (index, program_str) in self.programs.iter().enumerate() {
+                on_result(index, self.run_one(program_str, index, max_iterations, &on_step));
+            }
+            return;
+        }
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+        let on_step = &on_step;
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                let (sender, next) = (sender.clone(), &next);
+                scope.spawn(move || {
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(program_str) = self.programs.get(index) else {
+                            break;
+                        };
+                        if sender.send((index, self.run_one(program_str, index, max_iterations, on_step))).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            // The receiver ends when the last thread drops its sender.
+            drop(sender);
+            for (index, result) in receiver {
+                on_result(index, result);
+            }
+        });
+    }
+
+    /**
+     * Runs a program of the Zany Zoo with its deciders and block size, and calls on_step with its index and the machine after each step.
+     */
+    fn run_one(&self, program_str: &str, index: usize, max_iterations: usize, on_step: &impl Fn(usize, &Machine)) -> Result<BusyBeaverResult, BusyBeaverError> {
+        let program = parser::parse_program(program_str)?;
+        BusyBeaver::new(program).with_deciders(self.deciders).with_block_size(self.block_size).with_tape(self.tape).run_with(max_iterations, |machine| on_step(index, machine))
+    }
+
+    /**
+     * Prints the error message for a BusyBeaverError.
+     * This is used to print the error messages in a user-friendly way.
+     *
+     * # Arguments
+     * `err` - The BusyBeaverError to print.
+     */
+    fn print_error(err: BusyBeaverError) {
+        eprintln!("{err}");
+    }
+
+    /**
+     * Runs a single Busy Beaver program.
+     * It prepares the program transitions and runs the Busy Beaver program.
+     *
+     * # Arguments
+     * `program_str` - A string representing the Busy Beaver program in the format "1RB1LB_1LA1RZ".
+     * `max_iterations` - The maximum number of iterations to run the Busy Beaver program.
+     *
+     * # Returns
+     * A BusyBeaverResult containing the result of running the program.
+     */
+    pub fn run_program(program_str: &str, max_iterations: usize) -> Result<BusyBeaverResult, BusyBeaverError> {
+        Self::run_program_with(program_str, max_iterations, Deciders::default())
+    }
+
+    /**
+     * Runs a single Busy Beaver program with deciders that stop it when it is proven to never halt.
+     *
+     * # Arguments
+     * `program_str` - A string representing the Busy Beaver program in the format "1RB1LB_1LA1RZ".
+     * `max_iterations` - The maximum number of iterations to run the Busy Beaver program.
+     * `deciders` - The deciders to run.
+     *
+     * # Returns
+     * A BusyBeaverResult containing the result of running the program.
+     */
+    pub fn run_program_with(program_str: &str, max_iterations: usize, deciders: Deciders) -> Result<BusyBeaverResult, BusyBeaverError> {
+        let program = parser::parse_program(program_str)?;
+        let busy_beaver = BusyBeaver::new(program).with_deciders(deciders);
+        busy_beaver.run(max_iterations)
+    }
+}
+
+/**
+ * BusyBeaver struct to represent the Busy Beaver program.
+ * It contains the program transitions, a flag to indicate if it has halted, and a tape to store the symbols.
+ */
+pub struct BusyBeaver {
+    // The program transitions, mapping ProgramKey to Transition
+    program: HashMap<ProgramKey, Transition>,
+    // The deciders run along with the program
+    deciders: Deciders,
+    // The number of tape cells the program is run on at a time
+    block_size: usize,
+    // How the tape is kept
+    tape: TapeKind,
+    // The configuration the program is resumed from, None to start on a blank tape
+    checkpoint: Option<Checkpoint>,
+}
+
+impl BusyBeaver {
+    /**
+     * Creates a new BusyBeaver instance.
+     *
+     * # Arguments
+     * `program` - A HashMap containing the program transitions.
+     *
+     * # Returns
+     * A new instance of BusyBeaver.
+     */
+    pub fn new(program: HashMap<ProgramKey, Transition>) -> Self {
+        BusyBeaver { program, deciders: Deciders::default(), block_size: 1, tape: TapeKind::Cells, checkpoint: None }
+    }
+
+    /**
+     * Sets the deciders that are run along with the program to stop it when it is proven
+     * to never halt. No deciders are run by default.
+     *
+     * # Arguments
+     * `deciders` - The deciders to run.
+     *
+     * # Returns
+     * The BusyBeaver with the deciders.
+     */
+    pub fn with_deciders(mut self, deciders: Deciders) -> Self {
+        self.deciders = deciders;
+        self
+    }
+
+    /**
+     * Sets the number of tape cells the program is run on at a time. The outcome of running
+     * the program on a block of cells until the head leaves it is cached, so long runs that
+     * come back to the same blocks take far fewer steps. The result is the same, but the
+     * deciders are not run with blocks of more than one cell.
+     *
+     * # Arguments
+     * `block_size` - The number of cells in a block, 1 to run the program one cell at a time.
+     *
+     * # Returns
+     * The BusyBeaver with the block size.
+     */
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size;
+        self
+    }
+
+    /**
+     * Sets how the tape is kept while the program runs. A run-length tape uses little
+     * memory for long runs of the same symbol and sweeps over them in one step. The result
+     * is the same, but like with blocks the deciders are not run, and blocks of more than
+     * one cell are used instead when both are set.
+     *
+     * # Arguments
+     * `tape` - The kind of tape, cells by default.
+     *
+     * # Returns
+     * The BusyBeaver with the kind of tape.
+     */
+    pub fn with_tape(mut self, tape: TapeKind) -> Self {
+        self.tape = tape;
+        self
+    }
+
+    /**
+     * Sets the checkpoint the program is resumed from, to continue a run that was stopped.
+     * The steps taken before the checkpoint count towards the maximum number of iterations,
+     * and the deciders only see the steps after it. A resumed program is always run one cell
+     * at a time on a tape of cells.
+     *
+     * # Arguments
+     * `checkpoint` - The checkpoint of the program, see Checkpoint::new.
+     *
+     * # Returns
+     * The BusyBeaver with the checkpoint.
+     */
+    pub fn with_checkpoint(mut self, checkpoint: Checkpoint) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /**
+     * Runs the Busy Beaver program.
+     * It initializes the tape, current position, current state, and iteration count.
+     * It processes transitions until it either halts, a decider proves that it never halts, or it reaches the maximum number of iterations.
+     *
+     * # Arguments
+     * `max_iterations` - The maximum number of iterations to run the Busy Beaver program.
+     *
+     * # Returns
+     * A BusyBeaverResult containing the number of iterations, final tape state, number of values written, a flag indicating if it halted and the proof if it never halts.
+     */
+    pub fn run(self, max_iterations: usize) -> Result<BusyBeaverResult, BusyBeaverError> {
+        self.run_with(max_iterations, |_| {})
+    }
+
+    /**
+     * Runs the Busy Beaver program like run, and calls a function with the machine before the
+     * first step and after each step, to trace or draw the run. The function is not called
+     * when the program is run on blocks or a run-length tape, which skip steps.
+     *
+     * # Arguments
+     * `max_iterations` - The maximum number of iterations to run the Busy Beaver program.
+     * `on_step` - Called with the machine, which tells the step, the state, the head and the tape.
+     *
+     * # Returns
+     * A BusyBeaverResult like run.
+     */
+    pub fn run_with(self, max_iterations: usize, on_step: impl FnMut(&Machine)) -> Result<BusyBeaverResult, BusyBeaverError> {
+        let start = Instant::now();
+        if let Some(checkpoint) = &self.checkpoint {
+            let machine = Machine::resume(&self.program, checkpoint)?;
+            return self.run_machine(machine, max_iterations, on_step, start);
+        }
+        if self.block_size > 1 {
+            let result = MacroMachine::new(&self.program, self.block_size).run(max_iterations)?;
+            return Ok(BusyBeaverResult { runtime: start.elapsed(), ..result });
+        }
+        if self.tape == TapeKind::RunLength {
+            let result = RunLengthMachine::new(&self.program).run(max_iterations)?;
+            return Ok(BusyBeaverResult { runtime: start.elapsed(), ..result });
+        }
+        self.run_machine(Machine::new(&self.program), max_iterations, on_step, start)
+    }
+
+    /**
+     * Runs a machine one cell at a time with the deciders until it halts, a decider proves
+     * that it never halts or it has taken the maximum number of iterations.
+     *
+     * # Arguments
+     * `machine` - The machine, on a blank tape or resumed from a checkpoint.
+     * `max_iterations` - The maximum number of iterations, including the steps the machine has taken.
+     * `on_step` - Called with the machine before the first step and after each step.
+     * `start` - When the run started.
+     *
+     * # Returns
+     * A BusyBeaverResult like run.
+     */
+    fn run_machine(&self, mut machine: Machine, max_iterations: usize, mut on_step: impl FnMut(&Machine), start: Instant) -> Result<BusyBeaverResult, BusyBeaverError> {
+        let mut cycles = self.deciders.cycle_memory.map(CycleDecider::new);
+        let mut translated_cycles = self.deciders.translated_cycle_memory.map(TranslatedCycleDecider::new);
+        let mut lin_recurrences = self.deciders.lin_recurrence_window.map(LinRecurrenceDecider::new);
+        let mut non_halting = None;
+        on_step(&machine);
+        while machine.iteration < max_iterations && !machine.halted {
+            let (iteration, state, position, origin) = (machine.iteration, machine.current_state, machine.position(), machine.origin);
+            if let Some(cycles) = &mut cycles {
+                non_halting = cycles.check(iteration, state, machine.current_pos, machine.tape());
+            }
+            if let Some(translated_cycles) = &mut translated_cycles {
+                non_halting = non_halting.or_else(|| translated_cycles.check(iteration, state, position, machine.tape(), origin));
+            }
+            if let Some(lin_recurrences) = &mut lin_recurrences {
+                non_halting = non_halting.or_else(|| lin_recurrences.check(iteration, state, position, machine.tape(), origin));
+            }
+            if non_halting.is_some() {
+                break;
+            }
+            machine.step()?;
+            on_step(&machine);
+        }
+        Ok(BusyBeaverResult { runtime: start.elapsed(), non_halting, ..machine.into_result() })
+    }
+
+    /**
+     * Handles the transition for the Busy Beaver program.
+     *
+     * 1. It writes the transition symbol to the tape at the current position.
+     * 2. It updates the current state to the transition state.
+     * 3. It checks if the transition state is HALTED, and if so, it sets the halted flag to true and returns ControlFlow::Break.
+     * 4. It moves the tape head left or right based on the transition direction.
+     *
+     * # Arguments
+     * `tape` - A mutable reference to the tape where symbols are written.
+     * `halted` - A mutable reference to a boolean indicating if the program has halted.
+     * `current_pos` - A mutable reference to the current position of the tape head.
+     * `current_state` - A mutable reference to the current state of the program.
+     * `current_symbol` - The current symbol being processed.
+     * `transition` - An reference to the Transition to be applied.    
+     *
+     * # Returns
+     * A BusyBeaverTransitionResult indicating whether to continue processing, break the loop, or if the transition failed.
+     */
+    fn handle_transition(tape: &mut Tape, halted: &mut bool, current_pos: &mut usize, current_state: &mut State, transition: &Transition) -> Result<BusyBeaverTransitionResult, BusyBeaverError> {
+        // Ensure the tape has enough space. This is a special tape that can grow dynamically.
+        if *current_pos >= tape.len() {
+            tape.push_back();
+        }
+        // Write the transition symbol to the tape
+        if let Some(symbol) = &transition.symbol {
+            tape.set(*current_pos, *symbol);
+        } else {
+            return Err(BusyBeaverError::InvalidTransition { message: "Transition symbol was None".to_string(), transition: transition.clone() });
+        }
+        // Set the current state to the transition state
+        if let Some(state) = &transition.state {
+            *current_state = *state;
+            // Check if the program has halted
+            if Self::check_for_halt(halted, state) {
+                return Ok(BusyBeaverTransitionResult::Break);
+            }
+        } else {
+            return Err(BusyBeaverError::InvalidTransition { message: "Transition state was None".to_string(), transition: transition.clone() });
+        }
+        // Move the tape head left or right based on the transition direction
+        if let Some(direction) = &transition.direction {
+            match direction {
+                Direction::Left => Self::move_tape_left(tape, current_pos),
+                Direction::Right => Self::move_tape_right(tape, current_pos),
+            }
+        } else {
+            return Err(BusyBeaverError::InvalidTransition { message: "Transition direction was None".to_string(), transition: transition.clone() });
+        }
+        Ok(BusyBeaverTransitionResult::Continue)
+    }
+
+    /**
+     * Checks if the program has halted.
+     * If the transition state is HALTED, it sets the halted flag to true and returns a ControlFlow::Break.
+     * Otherwise, it returns None.
+     */
+    fn check_for_halt(halted: &mut bool, state: &State) -> bool {
+        if *state == HALTED {
+            *halted = true;
+            return true;
+        }
+        false
+    }
+
+    /**
+     * Moves the tape head to the left.
+     * If the current position is at the start of the tape, it adds a new symbol (0) at the beginning of the tape.
+     * Otherwise, it decrements the current position.
+     */
+    fn move_tape_left(tape: &mut Tape, current_pos: &mut usize) {
+        if *current_pos == 0 {
+            tape.push_front();
+        } else {
+            *current_pos -= 1;
+        }
+    }
+
+    /**
+     * Moves the tape head to the right.
+     * If the current position is at the end of the tape, it appends a new symbol (0) to the tape.
+     * Otherwise, it increments the current position.
+     */
+    fn move_tape_right(tape: &mut Tape, current_pos: &mut usize) {
+        if *current_pos >= tape.len() {
+            tape.push_back();
+        }
+        *current_pos += 1;
+    }
+}
+
+/**
+ * Machine struct with the configuration of a Busy Beaver program while it runs: the tape,
+ * the head, the state and the number of steps taken. It takes one step at a time, so the
+ * run can be followed or debugged between the steps.
+ */
+pub struct Machine<'a> {
+    // The program transitions
+    program: &'a HashMap<ProgramKey, Transition>,
+    // The tape, which grows when the head moves past its ends
+    tape: Tape,
+    // The index of the cell under the head in the tape
+    current_pos: usize,
+    // The current state
+    current_state: State,
+    // The number of steps taken
+    iteration: usize,
+    // Flag to indicate if the program has halted
+    halted: bool,
+    // The position of the first cell of the tape, which moves left when a cell is added before it
+    origin: i64,
+}
+
+impl<'a> Machine<'a> {
+    /**
+     * Creates a new Machine in state A on a blank tape.
+     *
+     * # Arguments
+     * `program` - The program transitions.
+     *
+     * # Returns
+     * A new instance of Machine.
+     */
+    pub fn new(program: &'a HashMap<ProgramKey, Transition>) -> Self {
+        Machine { program, tape: Tape::new(2), current_pos: 0, current_state: 'A', iteration: 0, halted: false, origin: 0 }
+    }
+
+    /**
+     * Creates a Machine in the configuration of a checkpoint, to continue the run.
+     *
+     * # Arguments
+     * `program` - The program transitions.
+     * `checkpoint` - The checkpoint of the program.
+     *
+     * # Returns
+     * A new instance of Machine, or an error if the checkpoint is not a configuration the program can be in.
+     */
+    pub fn resume(program: &'a HashMap<ProgramKey, Transition>, checkpoint: &Checkpoint) -> Result<Self, BusyBeaverError> {
+        let current_pos = checkpoint.validate()?;
+        Ok(Machine {
+            program,
+            tape: Tape::from_vec(checkpoint.tape.clone()),
+            current_pos,
+            current_state: checkpoint.state,
+            iteration: checkpoint.iteration,
+            halted: checkpoint.halted,
+            origin: checkpoint.origin,
+        })
+    }
+
+    /**
+     * Takes a step: writes the symbol of the transition for the symbol under the head and the
+     * current state, changes the state and moves the head. Does nothing when the program has halted.
+     *
+     * # Returns
+     * An error if the program has no valid transition for the symbol and the state.
+     */
+    pub fn step(&mut self) -> Result<(), BusyBeaverError> {
+        if self.halted {
+            return Ok(());
+        }
+        let current_symbol = self.tape.get(self.current_pos).unwrap_or(0);
+        let program_key: ProgramKey = ProgramKey::new(current_symbol, self.current_state);
+        let transition = match self.program.get(&program_key) {
+            Some(transition) => transition,
+            None => {
+                return Err(BusyBeaverError::TransitionNotFound { key: program_key, transition: Transition::new(None, None, None) });
+            }
+        };
+        self.iteration += 1;
+        let grows_left = self.current_pos == 0 && transition.direction == Some(Direction::Left);
+        match BusyBeaver::handle_transition(&mut self.tape, &mut self.halted, &mut self.current_pos, &mut self.current_state, transition)? {
+            BusyBeaverTransitionResult::Continue if grows_left => self.origin -= 1,
+            BusyBeaverTransitionResult::Continue | BusyBeaverTransitionResult::Break => {}
+        }
+        Ok(())
+    }
+
+    /**
+     * Returns the number of steps taken.
+     */
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /**
+     * Returns the current state, HALTED when the program has halted.
+     */
+    pub fn state(&self) -> State {
+        self.current_state
+    }
+
+    /**
+     * Returns true when the program has halted.
+     */
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    /**
+     * Returns the position of the head, 0 is where it started and negative positions are to the left.
+     */
+    pub fn position(&self) -> i64 {
+        self.origin + self.current_pos as i64
+    }
+
+    /**
+     * Returns the position of the first cell of the tape.
+     */
+    pub fn origin(&self) -> i64 {
+        self.origin
+    }
+
+    /**
+     * Returns the cells of the tape the head has visited, from the position of origin.
+     */
+    pub fn tape(&self) -> &[Symbol] {
+        self.tape.as_slice()
+    }
+
+    /**
+     * Returns the symbol at a position, 0 for cells the head has not visited.
+     */
+    pub fn symbol(&self, position: i64) -> Symbol {
+        usize::try_from(position - self.origin).ok().and_then(|index| self.tape.get(index)).unwrap_or(0)
+    }
+
+    /**
+     * Returns the result of the run so far.
+     */
+    pub fn into_result(self) -> BusyBeaverResult {
+        let tape = self.tape.into_vec();
+        let values = tape.iter().filter(|&&x| x >= 1).count();
+        BusyBeaverResult::new(self.iteration, tape, values, self.halted)
+    }
+}
+
+/**
+ * BusyBeaverResult struct to represent the result of running a Busy Beaver program.
+ * It contains the number of iterations, the final state of the tape, the number of symbols with value more than one written, a flag indicating if it completed
+ * and the time the run took.
+ */
+#[derive(Debug, Clone)]
+pub struct BusyBeaverResult {
+    // The number of iterations taken by the Busy Beaver program.
+    pub iterations: usize,
+    // The final state of the tape, empty when the tape is given as runs.
+    pub tape: Vec<usize>,
+    // The final state of the tape as runs of the same symbol, from the run-length tape, so long runs are not expanded.
+    pub runs: Option<Vec<Run>>,
+    // The number of symbols with value more than one written on the tape.
+    pub values: usize,
+    // Flag to indicate if the program has halted.
+    pub halted: bool,
+    // The time taken by the run, zero if the result was not created by running a program.
+    pub runtime: Duration,
+    // The proof that the program never halts if a decider found one.
+    pub non_halting: Option<NonHalting>,
+}
+
+impl BusyBeaverResult {
+    /**
+     * Creates a new BusyBeaverResult instance.
+     *
+     * # Arguments
+     * `iterations` - The number of iterations taken by the Busy Beaver program.
+     * `tape` - The final state of the tape.
+     * `values` - The number of tape with a value higher than one.
+     * `halted` - Flag to indicate if the program has halted.
+     *
+     * # Returns
+     * A new instance of BusyBeaverResult with a runtime of zero and no proof that it never halts.
+     */
+    pub fn new(iterations: usize, tape: Vec<usize>, values: usize, halted: bool) -> Self {
+        BusyBeaverResult { iterations, tape, runs: None, values, halted, runtime: Duration::ZERO, non_halting: None }
+    }
+
+    /**
+     * Returns the number of cells of the tape, also when it is given as runs.
+     */
+    pub fn tape_length(&self) -> usize {
+        self.runs.as_ref().map_or(self.tape.len(), |runs| runs.iter().map(|run| run.1).sum())
+    }
+
+    /**
+     * Returns the cells of the tape, expanding the runs when the tape is given as runs.
+     */
+    pub fn cells(&self) -> Cow<'_, [Symbol]> {
+        match &self.runs {
+            Some(runs) => Cow::Owned(runs.iter().flat_map(|&(symbol, count)| std::iter::repeat_n(symbol, count)).collect()),
+            None => Cow::Borrowed(&self.tape),
+        }
+    }
+
+    /**
+     * Returns the tape as runs of the same symbol.
+     */
+    pub fn tape_runs(&self) -> Cow<'_, [Run]> {
+        match &self.runs {
+            Some(runs) => Cow::Borrowed(runs),
+            None => Cow::Owned(run_length::to_runs(&self.tape)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_busy_beaver_1x1_halted() {
+        let mut program = HashMap::new();
+        program.insert(ProgramKey::new(0, 'A'), Transition::new(Some(1), Some(HALTED), Some(Direction::Right)));
+        let bb = BusyBeaver::new(program);
+        let result = bb.run(30).unwrap();
+        assert_eq!(result.iterations, 1);
+        assert_eq!(result.values, 1);
+        assert!(result.halted);
+    }
+
+    #[test]
+    fn test_busy_beaver_1x1_failed() {
+        let mut program = HashMap::new();
+        program.insert(ProgramKey::new(0, 'A'), Transition::new(Some(1), Some('B'), Some(Direction::Right)));
+        let bb = BusyBeaver::new(program);
+        match bb.run(30) {
+            Ok(_) => panic!("Expected an error, but got a result"),
+            Err(BusyBeaverError::TransitionNotFound { key, transition }) => {
+                assert_eq!(key.symbol, 0);
+                assert_eq!(key.state, 'B');
+                assert_eq!(transition.symbol, None);
+                assert_eq!(transition.state, None);
+                assert_eq!(transition.direction, None);
+            }
+            _ => panic!("Expected a TransitionNotFound error"),
+        }
+    }
+
+    #[test]
+    fn test_busy_beaver_1x1_continue_forever() {
+        let mut program = HashMap::new();
+        program.insert(ProgramKey::new(0, 'A'), Transition::new(Some(0), Some('A'), Some(Direction::Right)));
+        let bb = BusyBeaver::new(program);
+        let result = bb.run(30).unwrap();
+        assert_eq!(result.iterations, 30);
+        assert_eq!(result.values, 0);
+        assert!(!result.halted);
+    }
+
+    #[test]
+    fn test_busy_beaver_2x2_halted() {
+        let mut program = HashMap::new();
+        program.insert(ProgramKey::new(0, 'A'), Transition::new(Some(1), Some('B'), Some(Direction::Right)));
+        program.insert(ProgramKey::new(0, 'B'), Transition::new(Some(1), Some('A'), Some(Direction::Left)));
+        program.insert(ProgramKey::new(1, 'A'), Transition::new(Some(1), Some('B'), Some(Direction::Left)));
+        program.insert(ProgramKey::new(1, 'B'), Transition::new(Some(1), Some(HALTED), Some(Direction::Right)));
+        let bb = BusyBeaver::new(program);
+        let result = bb.run(30).unwrap();
+        assert_eq!(result.iterations, 6);
+        assert_eq!(result.values, 4);
+        assert!(result.halted);
+    }
+
+    #[test]
+    fn test_busy_beaver_3x2_halted() {
+        let mut program = HashMap::new();
+        program.insert(ProgramKey::new(0, 'A'), Transition::new(Some(1), Some('B'), Some(Direction::Right)));
+        program.insert(ProgramKey::new(0, 'B'), Transition::new(Some(1), Some('B'), Some(Direction::Left)));
+        program.insert(ProgramKey::new(0, 'C'), Transition::new(Some(1), Some('C'), Some(Direction::Left)));
+        program.insert(ProgramKey::new(1, 'A'), Transition::new(Some(1), Some(HALTED), Some(Direction::Right)));
+        program.insert(ProgramKey::new(1, 'B'), Transition::new(Some(0), Some('C'), Some(Direction::Right)));
+        program.insert(ProgramKey::new(1, 'C'), Transition::new(Some(1), Some('A'), Some(Direction::Left)));
+        let bb = BusyBeaver::new(program);
+        let result = bb.run(30).unwrap();
+        assert_eq!(result.iterations, 21);
+        assert_eq!(result.values, 5);
+        assert!(result.halted);
+    }
+
+    #[test]
+    fn test_busy_beaver_cycle() {
+        let mut program = HashMap::new();
+        program.insert(ProgramKey::new(0, 'A'), Transition::new(Some(1), Some('B'), Some(Direction::Right)));
+        program.insert(ProgramKey::new(0, 'B'), Transition::new(Some(0), Some('A'), Some(Direction::Left)));
+        program.insert(ProgramKey::new(1, 'A'), Transition::new(Some(1), Some('B'), Some(Direction::Right)));
+        let bb = BusyBeaver::new(program).with_deciders(Deciders { cycle_memory: Some(1 << 20), ..Deciders::default() });
+        let result = bb.run(1000).unwrap();
+        assert_eq!(result.non_halting, Some(NonHalting::Cycle { start: 1, period: 2 }));
+        assert_eq!(result.iterations, 3);
+        assert!(!result.halted);
+    }
+
+    #[test]
+    fn test_busy_beaver_cycle_without_decider() {
+        let mut program = HashMap::new();
+        program.insert(ProgramKey::new(0, 'A'), Transition::new(Some(0), Some('B'), Some(Direction::Right)));
+        program.insert(ProgramKey::new(0, 'B'), Transition::new(Some(0), Some('A'), Some(Direction::Left)));
+        let result = BusyBeaver::new(program).run(1000).unwrap();
+        assert_eq!(result.non_halting, None);
+        assert_eq!(result.iterations, 1000);
+    }
+
+    #[test]
+    fn test_machine_steps() {
+        let program = parser::parse_program("1RB1LB_1LA1RZ").unwrap();
+        let mut machine = Machine::new(&program);
+        let mut positions = Vec::new();
+        while !machine.halted() {
+            machine.step().unwrap();
+            positions.push(machine.position());
+        }
+        assert_eq!(positions, vec![1, 0, -1, -2, -1, -1]);
+        assert_eq!(machine.state(), HALTED);
+        assert_eq!((machine.origin(), machine.tape()), (-2, &[1, 1, 1, 1][..]));
+        machine.step().unwrap();
+        assert_eq!(machine.iteration(), 6);
+        let mut steps = Vec::new();
+        let result = BusyBeaver::new(program.clone()).run_with(30, |machine| steps.push(machine.iteration())).unwrap();
+        assert_eq!(steps, (0..=6).collect::<Vec<_>>());
+        assert_eq!(result.tape, machine.into_result().tape);
+    }
+
+    #[test]
+    fn test_zany_zoo_2x2() {
+        let programs = vec!["1RB1LB_1LA1RZ".to_string()];
+        let zany_zoo = ZanyZoo::new(programs);
+        let results = zany_zoo.run(30);
+        let result = &results[0];
+        assert_eq!(result.iterations, 6);
+        assert_eq!(result.values, 4);
+        assert!(result.halted);
+    }
+
+    #[test]
+    fn test_zany_zoo_2x3() {
+        let programs = vec!["1RB2LB1RZ_2LA2RB1LB".to_string()];
+        let zany_zoo = ZanyZoo::new(programs);
+        let results = zany_zoo.run(80);
+        let result = &results[0];
+        assert_eq!(result.iterations, 38);
+        assert_eq!(result.values, 9);
+        assert!(result.halted);
+    }
+
+    #[test]
+    fn test_zany_zoo_3x2() {
+        let programs = vec!["1RB1RZ_1LB0RC_1LC1LA".to_string()];
+        let zany_zoo = ZanyZoo::new(programs);
+        let results = zany_zoo.run(40);
+        let result = &results[0];
+        assert_eq!(result.iterations, 21);
+        assert_eq!(result.values, 5);
+        assert!(result.halted);
+    }
+
+    #[test]
+    fn test_zany_zoo_5x2() {
+        let programs = vec!["1RB1LC_0LA1RE_0LD0LB_1RA1RZ_1LA0RE".to_string()];
+        let zany_zoo = ZanyZoo::new(programs);
+        let results = zany_zoo.run(2000);
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.iterations, 1035);
+        assert_eq!(result.values, 35);
+        assert!(result.halted);
+    }
+
+    #[test]
+    fn test_zany_zoo_with_unused_states() {
+        let programs = vec!["1RB---_1RC1RZ_0LB---".to_string()];
+        let zany_zoo = ZanyZoo::new(programs);
+        let results = zany_zoo.run(20);
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.iterations, 4);
+        assert_eq!(result.values, 2);
+        assert!(result.halted);
+    }
+
+    #[test]
+    fn test_zany_zoo_with_invalid_program() {
+        let programs = vec!["1RB---_------_------".to_string()];
+        let zany_zoo = ZanyZoo::new(programs);
+        let results = zany_zoo.run(20);
+        assert_eq!(results.len(), 0);
+    }
+
+    #[test]
+    fn test_zany_zoo_parallel() {
+        let programs = vec![
+            "1RB1LC_0LA1RE_0LD0LB_1RA1RZ_1LA0RE".to_string(),
+            "1RB---_------_------".to_string(),
+            "1RB1LB_1LA1RZ".to_string(),
+            "1RB1RZ_1LB0RC_1LC1LA".to_string(),
+            "1RB2LB1RZ_2LA2RB1LB".to_string(),
+        ];
+        let zany_zoo = ZanyZoo::new(programs);
+        let results = zany_zoo.run_parallel(2000, 3);
+        assert_eq!(results.len(), 5);
+        assert!(matches!(results[1], Err(BusyBeaverError::InvalidTransition { .. })));
+        let iterations: Vec<Option<usize>> = results.iter().map(|result| result.as_ref().ok().map(|result| result.iterations)).collect();
+        assert_eq!(iterations, vec![Some(1035), None, Some(6), Some(21), Some(38)]);
+        let sequential: Vec<Option<usize>> = zany_zoo.run_parallel(2000, 1).iter().map(|result| result.as_ref().ok().map(|result| result.iterations)).collect();
+        assert_eq!(iterations, sequential);
+    }
+
+    #[test]
+    fn test_zany_zoo_with_multiple_programs() {
+        let programs = vec!["1RB---_1RC1RZ_0LB---".to_string(), "1RB1LC_0LA1RE_0LD0LB_1RA1RZ_1LA0RE".to_string()];
+        let zany_zoo = ZanyZoo::new(programs);
+        let results = zany_zoo.run(20);
+        assert_eq!(results.len(), 2);
+    }
+}
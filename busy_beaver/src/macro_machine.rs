@@ -0,0 +1,283 @@
+/**
+ * Macro machine that runs a Busy Beaver program on blocks of tape cells. A block is
+ * entered at one of its edges, and the program is run on it until the head leaves it.
+ * The outcome only depends on the state, the edge and the cells of the block, so it is
+ * cached, and a block that is entered the same way again takes a single step. The
+ * result is the same as running the program one cell at a time.
+ */
+use std::collections::{HashMap, HashSet};
+
+use crate::{BusyBeaverError, BusyBeaverResult, Direction, HALTED, ProgramKey, State, Symbol, Transition};
+
+/**
+ * The state, true when the head enters at the left edge, and the cells of a block.
+ */
+type Key = (State, bool, Vec<Symbol>);
+
+/**
+ * Why a run of the program on a block stopped.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stop {
+    // The head left the block in the direction.
+    Exit(Direction),
+    // The program halted.
+    Halted,
+    // The program took the most steps it was allowed.
+    Limit,
+}
+
+/**
+ * The head on a block, and what it did while it was on it.
+ */
+#[derive(Debug, Clone, Copy)]
+struct Head {
+    // The current state.
+    state: State,
+    // The index of the cell under the head.
+    index: usize,
+    // Steps taken on the block.
+    steps: usize,
+    // The lowest and highest index a step was taken at.
+    low: usize,
+    high: usize,
+}
+
+/**
+ * The cached outcome of entering a block.
+ */
+enum Outcome {
+    // The head left the block or the program halted, with the cells and the head after it.
+    Done { cells: Vec<Symbol>, head: Head, stop: Stop },
+    // The head never leaves the block.
+    Loop,
+}
+
+/**
+ * MacroMachine struct to run a program on blocks of cells.
+ */
+pub(crate) struct MacroMachine<'a> {
+    // The program transitions.
+    program: &'a HashMap<ProgramKey, Transition>,
+    // The number of cells in a block.
+    size: usize,
+    // Steps on a block after which the head is known to never leave it, since a configuration of the block has repeated.
+    bound: usize,
+    // The outcome of each way a block was entered.
+    cache: HashMap<Key, Outcome>,
+}
+
+impl<'a> MacroMachine<'a> {
+    /**
+     * Creates a new MacroMachine instance.
+     *
+     * # Arguments
+     * `program` - The program transitions.
+     * `size` - The number of cells in a block, at least 1.
+     *
+     * # Returns
+     * A new instance of MacroMachine.
+     */
+    pub(crate) fn new(program: &'a HashMap<ProgramKey, Transition>, size: usize) -> Self {
+        let size = size.max(1);
+        let states = program.keys().map(|key| key.state).collect::<HashSet<_>>().len().max(1);
+        let symbols = program.values().filter_map(|transition| transition.symbol).chain(program.keys().map(|key| key.symbol)).max().unwrap_or(0) + 1;
+        let bound = u32::try_from(size).ok().and_then(|exponent| symbols.checked_pow(exponent)).and_then(|blocks| blocks.checked_mul(states * size)).unwrap_or(usize::MAX);
+        MacroMachine { program, size, bound, cache: HashMap::new() }
+    }
+
+    /**
+     * Runs the program.
+     *
+     * # Arguments
+     * `max_iterations` - The maximum number of iterations to run the program.
+     *
+     * # Returns
+     * A BusyBeaverResult with the same iterations, tape and values as running the program one cell at a time.
+     */
+    pub(crate) fn run(&mut self, max_iterations: usize) -> Result<BusyBeaverResult, BusyBeaverError> {
+        let size = self.size as i64;
+        let mut tape: Vec<Symbol> = vec![0; self.size];
+        // The number of the first block of the tape and of the block under the head, 0 is where the head started.
+        let mut first = 0_i64;
+        let mut block = 0_i64;
+        let mut at_left = true;
+        let mut state: State = 'A';
+        let mut iteration = 0_usize;
+        let mut halted = false;
+        // The leftmost position the head was at and the rightmost position a step was taken at, like the tape of BusyBeaver.
+        let mut low = 0_i64;
+        let mut high = 1_i64;
+        let mut position = 0_i64;
+        while iteration < max_iterations {
+            if block < first {
+                tape.splice(0..0, std::iter::repeat_n(0, self.size));
+                first -= 1;
+            } else if (block - first) * size >= tape.len() as i64 {
+                tape.resize(tape.len() + self.size, 0);
+            }
+            let start = ((block - first) * size) as usize;
+            let cells = &mut tape[start..start + self.size];
+            let (head, stop) = self.enter(cells, state, at_left, max_iterations - iteration)?;
+            iteration += head.steps;
+            low = low.min(block * size + head.low as i64);
+            high = high.max(block * size + head.high as i64);
+            state = head.state;
+            position = block * size + head.index as i64;
+            match stop {
+                Stop::Exit(Direction::Left) => {
+                    block -= 1;
+                    at_left = false;
+                    position -= 1;
+                }
+                Stop::Exit(Direction::Right) => {
+                    block += 1;
+                    at_left = true;
+                    position += 1;
+                }
+                Stop::Halted => {
+                    halted = true;
+                    break;
+                }
+                Stop::Limit => break,
+            }
+        }
+        low = low.min(position);
+        let tape: Vec<Symbol> = (low..=high).map(|cell| usize::try_from(cell - first * size).ok().and_then(|index| tape.get(index)).copied().unwrap_or(0)).collect();
+        let values = tape.iter().filter(|&&symbol| symbol >= 1).count();
+        Ok(BusyBeaverResult::new(iteration, tape, values, halted))
+    }
+
+    /**
+     * Runs the program on a block from one of its edges, from the cache when the outcome is known.
+     *
+     * # Arguments
+     * `cells` - The cells of the block, changed in place.
+     * `state` - The state the head enters in.
+     * `at_left` - True when the head enters at the left edge.
+     * `remaining` - The most steps that may be taken.
+     *
+     * # Returns
+     * The head after it and why it stopped.
+     */
+    fn enter(&mut self, cells: &mut [Symbol], state: State, at_left: bool, remaining: usize) -> Result<(Head, Stop), BusyBeaverError> {
+        let index = if at_left { 0 } else { self.size - 1 };
+        let mut head = Head { state, index, steps: 0, low: index, high: index };
+        let key = (state, at_left, cells.to_vec());
+        match self.cache.get(&key) {
+            Some(Outcome::Done { cells: done, head: done_head, stop }) if done_head.steps <= remaining => {
+                cells.copy_from_slice(done);
+                return Ok((*done_head, *stop));
+            }
+            Some(_) => {
+                let stop = self.simulate(cells, &mut head, remaining)?;
+                return Ok((head, stop));
+            }
+            None => {}
+        }
+        let stop = self.simulate(cells, &mut head, remaining.min(self.bound))?;
+        match stop {
+            Stop::Limit if head.steps < remaining => {
+                self.cache.insert(key, Outcome::Loop);
+                let stop = self.simulate(cells, &mut head, remaining)?;
+                Ok((head, stop))
+            }
+            Stop::Limit => Ok((head, stop)),
+            _ => {
+                self.cache.insert(key, Outcome::Done { cells: cells.to_vec(), head, stop });
+                Ok((head, stop))
+            }
+        }
+    }
+
+    /**
+     * Runs the program one cell at a time on a block until the head leaves it, the program halts or the limit is reached.
+     *
+     * # Arguments
+     * `cells` - The cells of the block, changed in place.
+     * `head` - The head on the block, changed in place.
+     * `limit` - The most steps the head may have taken on the block.
+     *
+     * # Returns
+     * Why it stopped.
+     */
+    fn simulate(&self, cells: &mut [Symbol], head: &mut Head, limit: usize) -> Result<Stop, BusyBeaverError> {
+        while head.steps < limit {
+            let key = ProgramKey::new(cells[head.index], head.state);
+            let Some(transition) = self.program.get(&key) else {
+                return Err(BusyBeaverError::TransitionNotFound { key, transition: Transition::new(None, None, None) });
+            };
+            head.steps += 1;
+            head.low = head.low.min(head.index);
+            head.high = head.high.max(head.index);
+            let Some(symbol) = transition.symbol else {
+                return Err(BusyBeaverError::InvalidTransition { message: "Transition symbol was None".to_string(), transition: transition.clone() });
+            };
+            cells[head.index] = symbol;
+            let Some(state) = transition.state else {
+                return Err(BusyBeaverError::InvalidTransition { message: "Transition state was None".to_string(), transition: transition.clone() });
+            };
+            head.state = state;
+            if state == HALTED {
+                return Ok(Stop::Halted);
+            }
+            match transition.direction {
+                Some(Direction::Left) if head.index == 0 => return Ok(Stop::Exit(Direction::Left)),
+                Some(Direction::Left) => head.index -= 1,
+                Some(Direction::Right) if head.index + 1 == self.size => return Ok(Stop::Exit(Direction::Right)),
+                Some(Direction::Right) => head.index += 1,
+                None => return Err(BusyBeaverError::InvalidTransition { message: "Transition direction was None".to_string(), transition: transition.clone() }),
+            }
+        }
+        Ok(Stop::Limit)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::parse_program;
+    use crate::{BusyBeaver, ZanyZoo};
+
+    /**
+     * Checks that the macro machine gives the same result as running the program one cell at a time.
+     */
+    fn assert_same(program: &str, max_iterations: usize) {
+        let expected = ZanyZoo::run_program(program, max_iterations).unwrap();
+        let transitions = parse_program(program).unwrap();
+        for size in 1..=5 {
+            let result = MacroMachine::new(&transitions, size).run(max_iterations).unwrap();
+            assert_eq!(result.iterations, expected.iterations, "{program} with blocks of {size}");
+            assert_eq!(result.tape, expected.tape, "{program} with blocks of {size}");
+            assert_eq!(result.values, expected.values, "{program} with blocks of {size}");
+            assert_eq!(result.halted, expected.halted, "{program} with blocks of {size}");
+        }
+    }
+
+    #[test]
+    fn test_macro_machine_halts() {
+        assert_same("1RB1LB_1LA1RZ", 30);
+        assert_same("1RB1RZ_1LB0RC_1LC1LA", 40);
+        assert_same("1RB2LB1RZ_2LA2RB1LB", 80);
+        assert_same("1RB1LC_0LA1RE_0LD0LB_1RA1RZ_1LA0RE", 2000);
+        assert_same("1RB1LB_1LA0LC_1RZ1LD_1RD0RA", 200);
+    }
+
+    #[test]
+    fn test_macro_machine_max_iterations() {
+        for max_iterations in 1..120 {
+            assert_same("1RB1LB_1LA0LC_1RZ1LD_1RD0RA", max_iterations);
+        }
+        assert_same("0RB---_0LA---", 1001);
+        assert_same("1LA---", 1001);
+    }
+
+    #[test]
+    fn test_macro_machine_errors() {
+        let transitions = parse_program("1RB---_1RC1RZ_0LB---").unwrap();
+        assert!(MacroMachine::new(&transitions, 3).run(20).unwrap().halted);
+        let transitions = parse_program("1RB---_1LC1RZ_0LB---").unwrap();
+        assert!(BusyBeaver::new(transitions.clone()).run(20).is_err());
+        assert!(MacroMachine::new(&transitions, 3).run(20).is_err());
+    }
+}
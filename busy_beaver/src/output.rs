@@ -0,0 +1,205 @@
+use busy_beaver::champions::{Champions, Record};
+use busy_beaver::run_length::format_runs;
+use busy_beaver::{BusyBeaverError, BusyBeaverResult, Machine};
+use serde::Serialize;
+
+/**
+ * The result of one program in the JSON output. The fields of the result are null
+ * when the program fails, and the error is null when it does not.
+ */
+#[derive(Serialize)]
+struct Report<'a> {
+    // The program as it was given.
+    program: &'a str,
+    // The number of iterations taken by the program.
+    iterations: Option<usize>,
+    // The number of cells with a symbol other than 0 on the tape.
+    ones: Option<usize>,
+    // Flag to indicate if the program has halted.
+    halted: Option<bool>,
+    // The number of cells of the tape that were visited.
+    tape_length: Option<usize>,
+    // The symbols of the tape as digits, like "1101", or as runs like "1^2 0 1".
+    tape: Option<String>,
+    // The error if the program could not be read or run.
+    error: Option<String>,
+    // The proof that the program never halts, only present if a decider found one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    non_halting: Option<String>,
+}
+
+impl<'a> Report<'a> {
+    /**
+     * Creates the report of a program from its result.
+     *
+     * # Arguments
+     * `program` - The program string.
+     * `result` - The result or the error of running it.
+     * `runs` - True to give the tape as runs.
+     *
+     * # Returns
+     * A new instance of Report.
+     */
+    fn new(program: &'a str, result: &Result<BusyBeaverResult, BusyBeaverError>, runs: bool) -> Self {
+        match result {
+            Ok(result) => Report {
+                program,
+                iterations: Some(result.iterations),
+                ones: Some(result.values),
+                halted: Some(result.halted),
+                tape_length: Some(result.tape_length()),
+                tape: Some(if runs { format_runs(&result.tape_runs()) } else { result.cells().iter().map(|symbol| symbol.to_string()).collect() }),
+                error: None,
+                non_halting: result.non_halting.map(|proof| proof.to_string()),
+            },
+            Err(err) => Report { program, iterations: None, ones: None, halted: None, tape_length: None, tape: None, error: Some(err.to_string()), non_halting: None },
+        }
+    }
+}
+
+/**
+ * Prints a line for each program, and the errors of the programs that fail on stderr.
+ *
+ * # Arguments
+ * `results` - The result or the error of each program.
+ * `runs` - True to print the tape as runs in exponent style, like 1^2 0 1.
+ */
+pub fn print_text(results: &[Result<BusyBeaverResult, BusyBeaverError>], runs: bool) {
+    for (index, result) in results.iter().enumerate() {
+        match result {
+            Ok(result) => {
+                let non_halting = result.non_halting.map(|proof| format!(", Non-halting: {proof}")).unwrap_or_default();
+                let tape = if runs { format_runs(&result.tape_runs()) } else { format!("{:?}", result.cells()) };
+                println!("Program {}: Iterations: {}, Values: {}, Halted: {}{non_halting}, Tape: {tape}", index + 1, result.iterations, result.values, result.halted)
+            }
+            Err(err) => eprintln!("Program {}: {err}", index + 1),
+        }
+    }
+    println!("Total halted programs: {}", results.iter().filter(|result| result.is_ok()).count());
+}
+
+/**
+ * Prints the results as a JSON array with an object for each program.
+ *
+ * # Arguments
+ * `programs` - The programs in the order they were run.
+ * `results` - The result or the error of each program.
+ * `runs` - True to give the tape as runs in exponent style.
+ */
+pub fn print_json(programs: &[String], results: &[Result<BusyBeaverResult, BusyBeaverError>], runs: bool) {
+    let reports: Vec<Report> = programs.iter().zip(results).map(|(program, result)| Report::new(program, result, runs)).collect();
+    println!("{}", serde_json::to_string_pretty(&reports).expect("Failed to serialize the results"));
+}
+
+/**
+ * Number of cells shown on each side of the head in a trace line.
+ */
+const TRACE_WINDOW: i64 = 10;
+
+/**
+ * Describes the configuration of a running program, like
+ * "Step 2: State A, Position 0, Tape: 0 0 [1] 1 0" with the cell under the head in brackets.
+ *
+ * # Arguments
+ * `machine` - The running program.
+ *
+ * # Returns
+ * The step, the state, the position of the head and the cells around it.
+ */
+pub fn trace_line(machine: &Machine) -> String {
+    let position = machine.position();
+    let cells: Vec<String> =
+        (position - TRACE_WINDOW..=position + TRACE_WINDOW).map(|cell| if cell == position { format!("[{}]", machine.symbol(cell)) } else { machine.symbol(cell).to_string() }).collect();
+    format!("Step {}: State {}, Position {position}, Tape: {}", machine.iteration(), machine.state(), cells.join(" "))
+}
+
+/**
+ * Describes the holder of a record, like "sigma champion: 4 ones by program 1 1RB1LB_1LA1RZ".
+ *
+ * # Arguments
+ * `record` - The record to describe.
+ * `champions` - The records so far.
+ *
+ * # Returns
+ * The description, which tells that there is no champion before a program has halted.
+ */
+pub fn champion_line(record: Record, champions: &Champions) -> String {
+    let (champion, name, unit) = match record {
+        Record::Sigma => (&champions.sigma, "sigma", "ones"),
+        Record::Shift => (&champions.shift, "shift", "steps"),
+    };
+    match champion {
+        Some(champion) => format!("{name} champion: {} {unit} by program {} {}", champion.value, champion.index + 1, champion.program),
+        None => format!("{name} champion: none, no program halted"),
+    }
+}
+
+/**
+ * Prints the results as CSV with a header and a row for each program. The score is
+ * the number of cells with a symbol other than 0, and the runtime is in milliseconds.
+ *
+ * # Arguments
+ * `programs` - The programs in the order they were run.
+ * `results` - The result or the error of each program.
+ */
+pub fn print_csv(programs: &[String], results: &[Result<BusyBeaverResult, BusyBeaverError>]) {
+    println!("program,iterations,score,halted,tape_length,runtime_ms,error");
+    for (program, result) in programs.iter().zip(results) {
+        println!("{}", csv_row(program, result));
+    }
+}
+
+/**
+ * Creates the CSV row of a program. The fields of the result are empty when the
+ * program fails.
+ */
+fn csv_row(program: &str, result: &Result<BusyBeaverResult, BusyBeaverError>) -> String {
+    match result {
+        Ok(result) => format!("{},{},{},{},{},{:.3},", csv_field(program), result.iterations, result.values, result.halted, result.tape_length(), result.runtime.as_secs_f64() * 1000.0),
+        Err(err) => format!("{},,,,,,{}", csv_field(program), csv_field(&err.to_string())),
+    }
+}
+
+/**
+ * Quotes a CSV field if it holds a comma, a quote or a line break, doubling the quotes in it.
+ */
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) { format!("\"{}\"", field.replace('"', "\"\"")) } else { field.to_string() }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use busy_beaver::parser::parse_program;
+    use std::time::Duration;
+
+    #[test]
+    fn test_report_serialization() {
+        let result = Ok(BusyBeaverResult::new(6, vec![1, 1, 0, 1], 3, true));
+        let json = serde_json::to_string(&Report::new("1RB1LB_1LA1RZ", &result, false)).unwrap();
+        assert_eq!(json, r#"{"program":"1RB1LB_1LA1RZ","iterations":6,"ones":3,"halted":true,"tape_length":4,"tape":"1101","error":null}"#);
+        let json = serde_json::to_string(&Report::new("1RB1LB_1LA1RZ", &result, true)).unwrap();
+        assert_eq!(json, r#"{"program":"1RB1LB_1LA1RZ","iterations":6,"ones":3,"halted":true,"tape_length":4,"tape":"1^2 0 1","error":null}"#);
+        let result = Err(BusyBeaverError::ProgramReadError { message: "No parts found in the program".to_string() });
+        let json = serde_json::to_string(&Report::new("", &result, false)).unwrap();
+        assert_eq!(json, r#"{"program":"","iterations":null,"ones":null,"halted":null,"tape_length":null,"tape":null,"error":"Program Read Error: No parts found in the program"}"#);
+    }
+
+    #[test]
+    fn test_trace_line() {
+        let program = parse_program("1RB1LB_1LA1RZ").unwrap();
+        let mut machine = Machine::new(&program);
+        machine.step().unwrap();
+        machine.step().unwrap();
+        let tape = format!("{} [1] 1 {}", ["0"; 10].join(" "), ["0"; 9].join(" "));
+        assert_eq!(trace_line(&machine), format!("Step 2: State A, Position 0, Tape: {tape}"));
+    }
+
+    #[test]
+    fn test_csv_row() {
+        let result = Ok(BusyBeaverResult { runtime: Duration::from_micros(1500), ..BusyBeaverResult::new(6, vec![1, 1, 0, 1], 3, true) });
+        assert_eq!(csv_row("1RB1LB_1LA1RZ", &result), "1RB1LB_1LA1RZ,6,3,true,4,1.500,");
+        let result = Err(BusyBeaverError::ProgramReadError { message: "Invalid part, \"A\"".to_string() });
+        assert_eq!(csv_row("1XB", &result), r#"1XB,,,,,,"Program Read Error: Invalid part, ""A""""#);
+    }
+}
@@ -0,0 +1,227 @@
+/**
+ * Parser for Busy Beaver programs in the standard text format, like "1RB1LB_1LA1RZ".
+ * Each part between underscores is a state, starting with A, and holds one transition
+ * of three characters for each symbol: the symbol to write, the direction L or R and
+ * the next state. The state Z halts, and "---" is a transition that is never used.
+ */
+use std::collections::HashMap;
+
+use crate::{BusyBeaverError, Direction, ProgramKey, STATES, State, Transition, UNSPECIFICED};
+
+/**
+ * Parses a Busy Beaver program.
+ *
+ * # Arguments
+ * `program_str` - A string representing the Busy Beaver program in the format "1RB1LB_1LA1RZ".
+ *
+ * # Returns
+ * A HashMap mapping each ProgramKey of the program to its Transition, or a ProgramReadError
+ * if the program can not be read.
+ */
+pub fn parse_program(program_str: &str) -> Result<HashMap<ProgramKey, Transition>, BusyBeaverError> {
+    let parts: Vec<&str> = program_str.split("_").collect();
+    let num_symbols = get_number_of_states(&parts)?;
+    let num_states = parts.len();
+    prepare_program(parts, num_symbols, num_states)
+}
+
+/**
+ * Gets the number of states from the parts vector.
+ * This is calculated by taking the length of the first part and dividing it by 3,
+ * since each transition is represented by 3 characters (symbol, direction, state).
+ *
+ * # Arguments
+ * `parts` - A vector of strings representing the Busy Beaver program.
+ *
+ * # Returns
+ * The number of states in the program.
+ */
+fn get_number_of_states(parts: &Vec<&str>) -> Result<usize, BusyBeaverError> {
+    match parts.first() {
+        // Each transition is 3 characters long (symbol, direction, state)
+        Some(first_part) => Ok(first_part.len() / 3),
+        None => Err(BusyBeaverError::ProgramReadError { message: "No parts found in the program".to_string() }),
+    }
+}
+
+/**
+ * Prepares the Busy Beaver program from the parts vector.
+ * It creates a HashMap of ProgramKeys to Transition.
+ *
+ * # Arguments
+ * `parts` - A vector of strings representing the Busy Beaver program.
+ * `symbols` - The number of symbols in the program.
+ * `states` - The number of states in the program.
+ *
+ * # Returns
+ * A HashMap mapping ProgramKey to Transition.
+ * Each ProgramKey is a combination of a symbol and a state, and each Transition contains
+ * the symbol to write, the state to transition to, and the direction to move the
+ */
+fn prepare_program(parts: Vec<&str>, num_symbols: usize, num_states: usize) -> Result<HashMap<ProgramKey, Transition>, BusyBeaverError> {
+    let mut program: HashMap<ProgramKey, Transition> = HashMap::new();
+    for symbol_index in 0..num_symbols {
+        for (state_index, state_key) in STATES.iter().enumerate().take(num_states) {
+            let transition_state = get_transition_state(&parts, symbol_index, state_index)?;
+            let transition_symbol = get_transition_symbol(&parts, symbol_index, state_index)?;
+            let transition_direction = get_transition_direction(&parts, symbol_index, state_index)?;
+            let transition = get_transition(transition_state, transition_symbol, transition_direction)?;
+            program.insert(ProgramKey::new(symbol_index, *state_key), transition);
+        }
+    }
+    Ok(program)
+}
+
+/**
+ * Gets the transition symbol from the parts vector.
+ * This is a number that represents the symbol to write on the tape.
+ *
+ * # Arguments
+ * `parts` - A vector of strings representing the Busy Beaver program.
+ * `symbol_index` - The index of the symbol in the transition.
+ * `state_index` - The index of the state in the transition.
+ *
+ * # Returns
+ * The transition symbol as a char.
+ */
+fn get_transition_symbol(parts: &Vec<&str>, symbol_index: usize, state_index: usize) -> Result<Option<usize>, BusyBeaverError> {
+    let part = match parts.get(state_index) {
+        Some(state) => state,
+        None => return Err(BusyBeaverError::ProgramReadError { message: format!("Invalid part for symbol index state index {state_index} and parts {parts:?}") }),
+    };
+    let symbol_as_str = match part.chars().nth(symbol_index * 3) {
+        Some(value) => value,
+        None => return Err(BusyBeaverError::ProgramReadError { message: format!("Invalid symbol in part part {part:?}") }),
+    };
+    if symbol_as_str == UNSPECIFICED {
+        return Ok(None);
+    }
+    let symbol_value = match symbol_as_str.to_digit(10) {
+        Some(value) => value as usize,
+        None => return Err(BusyBeaverError::ProgramReadError { message: format!("Symbol must be a number in part part {part:?} value {symbol_as_str:?}") }),
+    };
+    Ok(Some(symbol_value))
+}
+
+/**
+ * Gets the transition state from the parts vector.
+ * This is a character that represents the state to transition to.
+ *
+ * # Arguments
+ * `parts` - A vector of strings representing the Busy Beaver program.
+ * `symbol_index` - The index of the symbol in the transition.
+ * `state_index` - The index of the state in the transition.
+ *
+ * # Returns
+ * The transition state as a char.
+ */
+fn get_transition_state(parts: &Vec<&str>, symbol_index: usize, state_index: usize) -> Result<Option<State>, BusyBeaverError> {
+    let part = match parts.get(state_index) {
+        Some(state) => state,
+        None => return Err(BusyBeaverError::ProgramReadError { message: format!("Invalid part for state index {state_index} and parts {parts:?}") }),
+    };
+    match part.chars().nth(symbol_index * 3 + 2) {
+        Some(value) => {
+            if value == UNSPECIFICED {
+                return Ok(None);
+            }
+            Ok(Some(value))
+        }
+        None => Err(BusyBeaverError::ProgramReadError { message: format!("Invalid transition state for symbol index {symbol_index} and state index {state_index} in part: {part}") }),
+    }
+}
+
+/**
+ * Gets the transition direction from the parts vector.
+ * This is a character that represents the direction to move the tape head (L for Left, R for Right).
+ *
+ * # Arguments
+ * `parts` - A vector of strings representing the Busy Beaver program. Each part corresponds to a state.
+ * `symbol_index` - The index of the symbol in the transition.
+ * `state_index` - The index of the state in the transition.
+ *
+ * # Returns
+ * The transition direction as a char.
+ */
+fn get_transition_direction(parts: &Vec<&str>, symbol_index: usize, state_index: usize) -> Result<Option<char>, BusyBeaverError> {
+    let part = match parts.get(state_index) {
+        Some(state) => state,
+        None => return Err(BusyBeaverError::ProgramReadError { message: format!("Invalid part for state index {state_index} and parts {parts:?}") }),
+    };
+    match part.chars().nth(symbol_index * 3 + 1) {
+        Some(value) => {
+            if value == UNSPECIFICED {
+                return Ok(None);
+            }
+            Ok(Some(value))
+        }
+        None => Err(BusyBeaverError::ProgramReadError { message: format!("Invalid transition direction for symbol index {symbol_index} and state index {state_index} in part: {part}") }),
+    }
+}
+
+/**
+ * Creates a Transition from the transition state, symbol, and direction.
+ *
+ * # Arguments
+ * `transition_state` - The state to transition to.
+ * `transition_symbol` - The symbol to write on the tape.
+ * `transition_direction` - The direction to move the tape head (L for Left, R for Right).
+ *
+ * # Returns
+ * A new instance of Transition.
+ */
+fn get_transition(transition_state: Option<char>, transition_symbol: Option<usize>, transition_direction: Option<char>) -> Result<Transition, BusyBeaverError> {
+    let direction = match transition_direction {
+        Some(dir) => {
+            if dir == 'L' {
+                Some(Direction::Left)
+            } else if dir == 'R' {
+                Some(Direction::Right)
+            } else if dir == UNSPECIFICED {
+                None
+            } else {
+                return Err(BusyBeaverError::ProgramReadError { message: format!("Invalid transition direction: {dir}") });
+            }
+        }
+        None => None,
+    };
+    Ok(Transition::new(transition_symbol, transition_state, direction))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_program_2x2() {
+        let program = parse_program("1RB1LB_1LA1RZ").unwrap();
+        assert_eq!(program.len(), 4);
+        let transition = &program[&ProgramKey::new(0, 'A')];
+        assert_eq!(transition.symbol, Some(1));
+        assert_eq!(transition.direction, Some(Direction::Right));
+        assert_eq!(transition.state, Some('B'));
+        let transition = &program[&ProgramKey::new(1, 'B')];
+        assert_eq!(transition.symbol, Some(1));
+        assert_eq!(transition.direction, Some(Direction::Right));
+        assert_eq!(transition.state, Some('Z'));
+    }
+
+    #[test]
+    fn test_parse_program_unused_transition() {
+        let program = parse_program("1RB---_1LA1RZ").unwrap();
+        let transition = &program[&ProgramKey::new(1, 'A')];
+        assert_eq!(transition.symbol, None);
+        assert_eq!(transition.direction, None);
+        assert_eq!(transition.state, None);
+    }
+
+    #[test]
+    fn test_parse_program_invalid_direction() {
+        assert!(matches!(parse_program("1XB1LB_1LA1RZ"), Err(BusyBeaverError::ProgramReadError { .. })));
+    }
+
+    #[test]
+    fn test_parse_program_short_part() {
+        assert!(matches!(parse_program("1RB1LB_1LA"), Err(BusyBeaverError::ProgramReadError { .. })));
+    }
+}
@@ -0,0 +1,232 @@
+/**
+ * Progress of a batch of Busy Beaver programs. The results are counted as the programs
+ * finish, and a status with the programs completed, the champions so far, the steps per
+ * second and the estimated time left is given at an interval, so long batches can be
+ * followed while they run. The steps of the programs that are still running are counted
+ * too, so a status is also given while a single long program runs.
+ */
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::champions::{Champion, Champions};
+use crate::{BusyBeaverError, BusyBeaverResult};
+
+/**
+ * Number of steps a running program takes between the checks if a status is due, so the
+ * clock is not read on every step.
+ */
+pub const CHECK_STEPS: usize = 1 << 20;
+
+/**
+ * Status struct with the progress of a batch at a point in time.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Status {
+    // The number of programs that have finished, with a result or an error.
+    pub completed: usize,
+    // The number of programs in the batch.
+    pub total: usize,
+    // The steps taken by the programs that have finished and the programs that are running.
+    pub steps: usize,
+    // The time since the batch started.
+    pub elapsed: Duration,
+    // The program with the most ones so far, None before a program has halted.
+    pub sigma: Option<Champion>,
+    // The program with the most steps so far, None before a program has halted.
+    pub shift: Option<Champion>,
+}
+
+impl Status {
+    /**
+     * Returns the steps per second of the programs that have finished.
+     */
+    pub fn steps_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 { self.steps as f64 / seconds } else { 0.0 }
+    }
+
+    /**
+     * Returns the estimated time left, from the time the programs so far took on average,
+     * or None before a program has finished.
+     */
+    pub fn remaining(&self) -> Option<Duration> {
+        if self.completed == 0 {
+            return None;
+        }
+        Some(self.elapsed.mul_f64(self.total.saturating_sub(self.completed) as f64 / self.completed as f64))
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = if self.total == 0 { 100.0 } else { self.completed as f64 * 100.0 / self.total as f64 };
+        write!(f, "Progress: {}/{} programs ({percent:.1}%)", self.completed, self.total)?;
+        if let Some(sigma) = &self.sigma {
+            write!(f, ", sigma {} by program {}", sigma.value, sigma.index + 1)?;
+        }
+        if let Some(shift) = &self.shift {
+            write!(f, ", shift {} by program {}", shift.value, shift.index + 1)?;
+        }
+        write!(f, ", {:.0} steps/s, elapsed {}", self.steps_per_second(), format_duration(self.elapsed))?;
+        match self.remaining() {
+            Some(remaining) => write!(f, ", remaining {}", format_duration(remaining)),
+            None => write!(f, ", remaining unknown"),
+        }
+    }
+}
+
+/**
+ * Progress struct to count the results of a batch and tell when a status is due.
+ */
+pub struct Progress {
+    // The number of programs in the batch.
+    total: usize,
+    // The number of programs that have finished.
+    completed: usize,
+    // The steps taken by the programs that have finished.
+    steps: usize,
+    // The steps taken so far by the programs that are running, by the index of the program.
+    running: HashMap<usize, usize>,
+    // The records of the programs that have finished.
+    champions: Champions,
+    // The time between the statuses.
+    interval: Duration,
+    // When the batch started.
+    started: Instant,
+    // When the last status was given.
+    reported: Instant,
+}
+
+impl Progress {
+    /**
+     * Creates a new Progress for a batch that starts now.
+     *
+     * # Arguments
+     * `total` - The number of programs in the batch.
+     * `interval` - The time between the statuses.
+     *
+     * # Returns
+     * A new instance of Progress.
+     */
+    pub fn new(total: usize, interval: Duration) -> Self {
+        let started = Instant::now();
+        Progress { total, completed: 0, steps: 0, running: HashMap::new(), champions: Champions::new(), interval, started, reported: started }
+    }
+
+    /**
+     * Counts the result of a program that has finished.
+     *
+     * # Arguments
+     * `program` - The program string.
+     * `index` - The index of the program in the batch.
+     * `result` - The result or the error of the program.
+     *
+     * # Returns
+     * The status when the interval has passed since the last one or the last program has finished, otherwise None.
+     */
+    pub fn update(&mut self, program: &str, index: usize, result: &Result<BusyBeaverResult, BusyBeaverError>) -> Option<Status> {
+        self.completed += 1;
+        self.running.remove(&index);
+        if let Ok(result) = result {
+            self.steps += result.iterations;
+            self.champions.update(program, index, result);
+        }
+        if self.reported.elapsed() < self.interval && self.completed < self.total {
+            return None;
+        }
+        self.reported = Instant::now();
+        Some(self.status())
+    }
+
+    /**
+     * Counts the steps a running program has taken so far. Called every CHECK_STEPS steps.
+     *
+     * # Arguments
+     * `index` - The index of the program in the batch.
+     * `steps` - The steps the program has taken.
+     *
+     * # Returns
+     * The status when the interval has passed since the last one, otherwise None.
+     */
+    pub fn step(&mut self, index: usize, steps: usize) -> Option<Status> {
+        self.running.insert(index, steps);
+        if self.reported.elapsed() < self.interval {
+            return None;
+        }
+        self.reported = Instant::now();
+        Some(self.status())
+    }
+
+    /**
+     * Returns the status of the batch now.
+     */
+    pub fn status(&self) -> Status {
+        let steps = self.steps + self.running.values().sum::<usize>();
+        Status { completed: self.completed, total: self.total, steps, elapsed: self.started.elapsed(), sigma: self.champions.sigma.clone(), shift: self.champions.shift.clone() }
+    }
+}
+
+/**
+ * Formats a duration in whole seconds, minutes and hours, like 1h 2m 3s.
+ */
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m {}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {}m {}s", seconds / 3600, seconds / 60 % 60, seconds % 60),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_progress() {
+        let mut progress = Progress::new(4, Duration::from_secs(3600));
+        assert_eq!(progress.update("first", 0, &Ok(BusyBeaverResult::new(6, vec![1, 1, 1, 1], 4, true))), None);
+        assert_eq!(progress.update("second", 1, &Err(BusyBeaverError::ProgramReadError { message: "Invalid".to_string() })), None);
+        assert_eq!(progress.update("third", 2, &Ok(BusyBeaverResult::new(100, vec![1; 9], 9, false))), None);
+        let status = progress.status();
+        assert_eq!((status.completed, status.total, status.steps), (3, 4, 106));
+        assert!(status.remaining().is_some());
+        let status = progress.update("fourth", 3, &Ok(BusyBeaverResult::new(21, vec![1; 5], 5, true))).unwrap();
+        assert_eq!((status.completed, status.steps), (4, 127));
+        assert_eq!(status.sigma, Some(Champion { program: "fourth".to_string(), index: 3, value: 5 }));
+        assert_eq!(status.shift.as_ref().map(|shift| shift.value), Some(21));
+        assert_eq!(status.remaining(), Some(Duration::ZERO));
+        let mut progress = Progress::new(10, Duration::ZERO);
+        assert!(progress.update("first", 0, &Ok(BusyBeaverResult::new(6, vec![1, 1, 1, 1], 4, true))).is_some());
+    }
+
+    #[test]
+    fn test_progress_while_running() {
+        let mut progress = Progress::new(2, Duration::from_secs(3600));
+        assert_eq!(progress.step(0, CHECK_STEPS), None);
+        assert_eq!(progress.step(1, CHECK_STEPS), None);
+        assert_eq!(progress.step(0, 2 * CHECK_STEPS), None);
+        assert_eq!((progress.status().completed, progress.status().steps), (0, 3 * CHECK_STEPS));
+        progress.update("first", 0, &Ok(BusyBeaverResult::new(2 * CHECK_STEPS + 5, vec![1], 1, false)));
+        assert_eq!((progress.status().completed, progress.status().steps), (1, 3 * CHECK_STEPS + 5));
+        let mut progress = Progress::new(1, Duration::ZERO);
+        let status = progress.step(0, CHECK_STEPS).unwrap();
+        assert_eq!((status.completed, status.steps), (0, CHECK_STEPS));
+    }
+
+    #[test]
+    fn test_status_line() {
+        let status = Status {
+            completed: 25,
+            total: 100,
+            steps: 5_000,
+            elapsed: Duration::from_secs(10),
+            sigma: Some(Champion { program: "1RB1LB_1LA1RZ".to_string(), index: 4, value: 4 }),
+            shift: Some(Champion { program: "1RB1LB_1LA1RZ".to_string(), index: 4, value: 6 }),
+        };
+        assert_eq!(status.to_string(), "Progress: 25/100 programs (25.0%), sigma 4 by program 5, shift 6 by program 5, 500 steps/s, elapsed 10s, remaining 30s");
+        let status = Status { completed: 0, sigma: None, shift: None, elapsed: Duration::from_secs(3725), ..status };
+        assert_eq!(status.to_string(), "Progress: 0/100 programs (0.0%), 1 steps/s, elapsed 1h 2m 5s, remaining unknown");
+    }
+}
@@ -0,0 +1,235 @@
+/**
+ * Run-length encoded tape, where the tape is kept as runs of the same symbol with a count.
+ * The cells on each side of the head are stacks of runs with the run next to the head on
+ * top, so moving the head only changes the top runs. When the program stays in the same
+ * state and moves in the same direction over the symbol it reads, it sweeps over the whole
+ * run of that symbol, which is done in a single step. The result is the same as running
+ * the program one cell at a time.
+ */
+use std::collections::HashMap;
+
+use crate::{BusyBeaverError, BusyBeaverResult, Direction, HALTED, ProgramKey, State, Symbol, Transition};
+
+/**
+ * A run of the same symbol, and the number of cells in it.
+ */
+pub type Run = (Symbol, usize);
+
+/**
+ * RunLengthMachine struct to run a program on a run-length encoded tape.
+ */
+pub(crate) struct RunLengthMachine<'a> {
+    // The program transitions.
+    program: &'a HashMap<ProgramKey, Transition>,
+}
+
+impl<'a> RunLengthMachine<'a> {
+    /**
+     * Creates a new RunLengthMachine instance.
+     *
+     * # Arguments
+     * `program` - The program transitions.
+     *
+     * # Returns
+     * A new instance of RunLengthMachine.
+     */
+    pub(crate) fn new(program: &'a HashMap<ProgramKey, Transition>) -> Self {
+        RunLengthMachine { program }
+    }
+
+    /**
+     * Runs the program.
+     *
+     * # Arguments
+     * `max_iterations` - The maximum number of iterations to run the program.
+     *
+     * # Returns
+     * A BusyBeaverResult with the same iterations, cells and values as running the program one cell at a time, with the tape as runs.
+     */
+    pub(crate) fn run(&self, max_iterations: usize) -> Result<BusyBeaverResult, BusyBeaverError> {
+        // The runs left and right of the head, the run next to the head last.
+        let mut left: Vec<Run> = Vec::new();
+        let mut right: Vec<Run> = Vec::new();
+        let mut symbol: Symbol = 0;
+        let mut state: State = 'A';
+        let mut iteration = 0_usize;
+        let mut halted = false;
+        // The position of the head, the leftmost position it was at and the rightmost position a step was taken at, like the tape of BusyBeaver.
+        let mut position = 0_i64;
+        let mut low = 0_i64;
+        let mut high = 1_i64;
+        while iteration < max_iterations {
+            let key = ProgramKey::new(symbol, state);
+            let Some(transition) = self.program.get(&key) else {
+                return Err(BusyBeaverError::TransitionNotFound { key, transition: Transition::new(None, None, None) });
+            };
+            let Some(write) = transition.symbol else {
+                return Err(BusyBeaverError::InvalidTransition { message: "Transition symbol was None".to_string(), transition: transition.clone() });
+            };
+            let Some(next) = transition.state else {
+                return Err(BusyBeaverError::InvalidTransition { message: "Transition state was None".to_string(), transition: transition.clone() });
+            };
+            low = low.min(position);
+            high = high.max(position);
+            if next == HALTED {
+                symbol = write;
+                iteration += 1;
+                halted = true;
+                break;
+            }
+            let (behind, ahead, sign) = match transition.direction {
+                Some(Direction::Left) => (&mut right, &mut left, -1),
+                Some(Direction::Right) => (&mut left, &mut right, 1),
+                None => return Err(BusyBeaverError::InvalidTransition { message: "Transition direction was None".to_string(), transition: transition.clone() }),
+            };
+            let remaining = max_iterations - iteration;
+            let count = if next == state { Self::sweep(ahead, symbol).min(remaining) } else { 1 };
+            push(behind, write, count);
+            take(ahead, count - 1);
+            symbol = ahead.last().map_or(0, |run| run.0);
+            take(ahead, 1);
+            let last = position + sign * (count as i64 - 1);
+            low = low.min(last);
+            high = high.max(last);
+            position = last + sign;
+            iteration += count;
+            state = next;
+        }
+        low = low.min(position);
+        // The runs from the leftmost cell of the runs to the rightmost one, cut to the cells from low to high with blank cells added on the sides.
+        let mut start = position - left.iter().map(|run| run.1 as i64).sum::<i64>();
+        let mut runs: Vec<Run> = Vec::new();
+        if low < start {
+            push(&mut runs, 0, (start - low) as usize);
+        }
+        for &(symbol, count) in left.iter().chain(&[(symbol, 1)]).chain(right.iter().rev()) {
+            let (first, last) = (start.max(low), (start + count as i64 - 1).min(high));
+            if first <= last {
+                push(&mut runs, symbol, (last - first + 1) as usize);
+            }
+            start += count as i64;
+        }
+        if high >= start {
+            push(&mut runs, 0, (high - start + 1) as usize);
+        }
+        let values = runs.iter().filter(|run| run.0 >= 1).map(|run| run.1).sum();
+        Ok(BusyBeaverResult { runs: Some(runs), ..BusyBeaverResult::new(iteration, Vec::new(), values, halted) })
+    }
+
+    /**
+     * Returns the number of cells the head sweeps over when it keeps moving over the symbol
+     * it reads, usize::MAX when the symbol is 0 and only blank cells are ahead.
+     *
+     * # Arguments
+     * `ahead` - The runs ahead of the head.
+     * `symbol` - The symbol under the head.
+     */
+    fn sweep(ahead: &[Run], symbol: Symbol) -> usize {
+        let (count, rest) = match ahead.split_last() {
+            Some((&(top, count), rest)) if top == symbol => (count + 1, rest),
+            _ => (1, ahead),
+        };
+        if symbol == 0 && rest.is_empty() { usize::MAX } else { count }
+    }
+}
+
+/**
+ * Puts cells of a symbol on top of a stack of runs, adding them to the top run if it has the same symbol.
+ */
+fn push(runs: &mut Vec<Run>, symbol: Symbol, count: usize) {
+    match runs.last_mut() {
+        Some(top) if top.0 == symbol => top.1 += count,
+        _ => runs.push((symbol, count)),
+    }
+}
+
+/**
+ * Takes cells from the top of a stack of runs. Cells past the bottom are blank.
+ */
+fn take(runs: &mut Vec<Run>, mut count: usize) {
+    while count > 0 {
+        let Some(top) = runs.last_mut() else {
+            return;
+        };
+        if top.1 > count {
+            top.1 -= count;
+            return;
+        }
+        count -= top.1;
+        runs.pop();
+    }
+}
+
+/**
+ * Returns the runs of the same symbol in the cells of a tape.
+ */
+pub fn to_runs(tape: &[Symbol]) -> Vec<Run> {
+    let mut runs: Vec<Run> = Vec::new();
+    for &symbol in tape {
+        push(&mut runs, symbol, 1);
+    }
+    runs
+}
+
+/**
+ * Formats a tape in exponent style, with each run of a symbol as the symbol and the
+ * number of cells, like "1^3 0 1^2" for 1110 11.
+ *
+ * # Arguments
+ * `runs` - The runs of the tape.
+ *
+ * # Returns
+ * The runs separated by spaces, with the count left out of runs of one cell.
+ */
+pub fn format_runs(runs: &[Run]) -> String {
+    runs.iter().map(|&(symbol, count)| if count == 1 { symbol.to_string() } else { format!("{symbol}^{count}") }).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ZanyZoo;
+    use crate::parser::parse_program;
+
+    /**
+     * Checks that the run-length tape gives the same result as running the program one cell at a time.
+     */
+    fn assert_same(program: &str, max_iterations: usize) {
+        let expected = ZanyZoo::run_program(program, max_iterations).unwrap();
+        let transitions = parse_program(program).unwrap();
+        let result = RunLengthMachine::new(&transitions).run(max_iterations).unwrap();
+        assert_eq!(result.iterations, expected.iterations, "{program}");
+        assert_eq!(result.cells(), expected.tape, "{program}");
+        assert_eq!(result.tape_length(), expected.tape.len(), "{program}");
+        assert_eq!(result.values, expected.values, "{program}");
+        assert_eq!(result.halted, expected.halted, "{program}");
+    }
+
+    #[test]
+    fn test_run_length_halts() {
+        assert_same("1RB1LB_1LA1RZ", 30);
+        assert_same("1RB1RZ_1LB0RC_1LC1LA", 40);
+        assert_same("1RB2LB1RZ_2LA2RB1LB", 80);
+        assert_same("1RB1LC_0LA1RE_0LD0LB_1RA1RZ_1LA0RE", 2000);
+        assert_same("1RB1LB_1LA0LC_1RZ1LD_1RD0RA", 200);
+    }
+
+    #[test]
+    fn test_run_length_max_iterations() {
+        for max_iterations in 1..120 {
+            assert_same("1RB1LB_1LA0LC_1RZ1LD_1RD0RA", max_iterations);
+            assert_same("1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA", max_iterations * 17);
+        }
+        assert_same("0RB---_0LA---", 1001);
+        assert_same("1LA---", 1001);
+        assert_same("0RA---", 1001);
+    }
+
+    #[test]
+    fn test_format_runs() {
+        assert_eq!(format_runs(&to_runs(&[1, 1, 1, 0, 1, 1])), "1^3 0 1^2");
+        assert_eq!(format_runs(&to_runs(&[2])), "2");
+        assert_eq!(format_runs(&[]), "");
+        assert_eq!(format_runs(&[(1, 10_000_000_000)]), "1^10000000000");
+    }
+}
@@ -0,0 +1,122 @@
+/**
+ * Tape that grows at both ends. The cells are kept in a vector with room before the first
+ * cell, and the room is doubled when it runs out, so adding a cell at either end takes
+ * amortized constant time while the cells stay in one slice for the deciders.
+ */
+use crate::Symbol;
+
+/**
+ * Tape struct with the cells the head has visited.
+ */
+#[derive(Debug, Clone)]
+pub(crate) struct Tape {
+    // The cells, after the room for cells added at the start.
+    cells: Vec<Symbol>,
+    // The index of the first cell of the tape in cells.
+    start: usize,
+}
+
+impl Tape {
+    /**
+     * Creates a new blank Tape with a number of cells.
+     *
+     * # Arguments
+     * `len` - The number of cells.
+     *
+     * # Returns
+     * A new instance of Tape.
+     */
+    pub(crate) fn new(len: usize) -> Self {
+        Tape { cells: vec![0; len], start: 0 }
+    }
+
+    /**
+     * Creates a Tape with the cells of a vector.
+     *
+     * # Arguments
+     * `cells` - The cells, from the first one.
+     *
+     * # Returns
+     * A new instance of Tape.
+     */
+    pub(crate) fn from_vec(cells: Vec<Symbol>) -> Self {
+        Tape { cells, start: 0 }
+    }
+
+    /**
+     * Returns the number of cells of the tape.
+     */
+    pub(crate) fn len(&self) -> usize {
+        self.cells.len() - self.start
+    }
+
+    /**
+     * Returns the cells of the tape.
+     */
+    pub(crate) fn as_slice(&self) -> &[Symbol] {
+        &self.cells[self.start..]
+    }
+
+    /**
+     * Returns the cell at an index, or None past the end of the tape.
+     */
+    pub(crate) fn get(&self, index: usize) -> Option<Symbol> {
+        self.as_slice().get(index).copied()
+    }
+
+    /**
+     * Writes a symbol to the cell at an index.
+     */
+    pub(crate) fn set(&mut self, index: usize, symbol: Symbol) {
+        self.cells[self.start + index] = symbol;
+    }
+
+    /**
+     * Adds a blank cell after the last one.
+     */
+    pub(crate) fn push_back(&mut self) {
+        self.cells.push(0);
+    }
+
+    /**
+     * Adds a blank cell before the first one, so the indexes of the cells move up by one.
+     */
+    pub(crate) fn push_front(&mut self) {
+        if self.start == 0 {
+            let room = self.cells.len().max(1);
+            self.cells.splice(0..0, std::iter::repeat_n(0, room));
+            self.start = room;
+        }
+        self.start -= 1;
+    }
+
+    /**
+     * Returns the cells of the tape as a vector.
+     */
+    pub(crate) fn into_vec(mut self) -> Vec<Symbol> {
+        self.cells.drain(..self.start);
+        self.cells
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tape_grows_at_both_ends() {
+        let mut tape = Tape::new(2);
+        tape.set(1, 1);
+        for _ in 0..5 {
+            tape.push_front();
+        }
+        tape.set(0, 2);
+        tape.push_back();
+        assert_eq!(tape.len(), 8);
+        assert_eq!(tape.get(0), Some(2));
+        assert_eq!(tape.get(6), Some(1));
+        assert_eq!(tape.get(8), None);
+        assert_eq!(tape.as_slice(), &[2, 0, 0, 0, 0, 0, 1, 0]);
+        assert_eq!(tape.into_vec(), vec![2, 0, 0, 0, 0, 0, 1, 0]);
+    }
+}
@@ -0,0 +1,92 @@
+/**
+ * Live view of a run in the terminal. The tape around the head is redrawn at a frame rate
+ * while the program runs, with the head marked under the cell it is on. The view stays in
+ * place and jumps to put the head in the middle when the head leaves it.
+ */
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+use busy_beaver::Machine;
+use render::terminal::CLEAR_SCREEN;
+
+/**
+ * Watch struct with the view of the tape.
+ */
+pub struct Watch {
+    // The number of cells shown.
+    width: usize,
+    // The number of steps between the frames.
+    every: usize,
+    // The time between the frames.
+    delay: Duration,
+    // The position of the leftmost cell shown.
+    left: i64,
+}
+
+impl Watch {
+    /**
+     * Creates a new Watch with the start of the tape in the middle of the view.
+     *
+     * # Arguments
+     * `width` - The number of cells shown.
+     * `every` - The number of steps between the frames.
+     * `fps` - The number of frames per second.
+     *
+     * # Returns
+     * A new instance of Watch.
+     */
+    pub fn new(width: usize, every: usize, fps: u64) -> Self {
+        let width = width.max(1);
+        Watch { width, every: every.max(1), delay: Duration::from_secs(1) / fps.max(1) as u32, left: -(width as i64 / 2) }
+    }
+
+    /**
+     * Draws a frame and waits for the next one when the step is one of every Nth steps or the program has halted.
+     *
+     * # Arguments
+     * `program` - The program, shown above the tape.
+     * `machine` - The running program.
+     */
+    pub fn show(&mut self, program: &str, machine: &Machine) {
+        if machine.iteration() % self.every != 0 && !machine.halted() {
+            return;
+        }
+        print!("{CLEAR_SCREEN}{}", self.frame(program, machine));
+        let _ = std::io::stdout().flush();
+        thread::sleep(self.delay);
+    }
+
+    /**
+     * Returns the frame with the program, the step, the state and the position, the cells in
+     * the view and a line with the head under the cell it is on.
+     */
+    fn frame(&mut self, program: &str, machine: &Machine) -> String {
+        let position = machine.position();
+        if position < self.left || position >= self.left + self.width as i64 {
+            self.left = position - self.width as i64 / 2;
+        }
+        let cells: String = (self.left..self.left + self.width as i64).map(|cell| char::from_digit(machine.symbol(cell) as u32, 36).unwrap_or('?')).collect();
+        let head = format!("{}^", " ".repeat((position - self.left) as usize));
+        format!("{program}\nStep {}, State {}, Position {position}\n{cells}\n{head}\n", machine.iteration(), machine.state())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use busy_beaver::parser::parse_program;
+
+    #[test]
+    fn test_frame() {
+        let program = parse_program("1LA---").unwrap();
+        let mut machine = Machine::new(&program);
+        let mut watch = Watch::new(4, 1, 10);
+        assert_eq!(watch.frame("1LA---", &machine), "1LA---\nStep 0, State A, Position 0\n0000\n  ^\n");
+        machine.step().unwrap();
+        machine.step().unwrap();
+        assert_eq!(watch.frame("1LA---", &machine), "1LA---\nStep 2, State A, Position -2\n0110\n^\n");
+        machine.step().unwrap();
+        assert_eq!(watch.frame("1LA---", &machine), "1LA---\nStep 3, State A, Position -3\n0001\n  ^\n");
+    }
+}
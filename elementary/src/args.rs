@@ -0,0 +1,67 @@
+use clap::{Parser, ValueEnum};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Wolfram rule number 0-255
+    #[arg(short, long, default_value_t = 30)]
+    pub rule: u8,
+
+    /// Number of cells in a row
+    #[arg(short, long, default_value_t = 79)]
+    pub width: usize,
+
+    /// Number of generations to compute
+    #[arg(short, long, default_value_t = 40)]
+    pub generations: usize,
+
+    /// State of the cells outside the row
+    #[arg(short, long, value_enum, default_value_t = BoundaryArg::Periodic)]
+    pub boundary: BoundaryArg,
+
+    /// Initial state of the row
+    #[arg(short, long, value_enum, default_value_t = Initial::Single)]
+    pub initial: Initial,
+
+    /// Cells placed in the center of the row with the pattern initial state, for example 1101 or ##.#
+    #[arg(short, long)]
+    pub pattern: Option<String>,
+
+    /// Probability of a live cell with the random initial state
+    #[arg(short, long, default_value_t = 0.5)]
+    pub density: f64,
+
+    /// Seed for the random initial state
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// Write the diagram to an image file instead of the terminal. PNG, or PPM/PGM by extension
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// Size of a cell in pixels in the image
+    #[arg(long, default_value_t = 2)]
+    pub scale: usize,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoundaryArg {
+    /// The row wraps around
+    Periodic,
+    /// Cells outside the row are dead
+    Dead,
+    /// Cells outside the row are alive
+    Alive,
+    /// Cells outside the row copy the edge cell
+    Reflect,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Initial {
+    /// A single live cell in the center
+    Single,
+    /// Random cells
+    Random,
+    /// The cells given with --pattern
+    Pattern,
+}
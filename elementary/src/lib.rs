@@ -0,0 +1,284 @@
+//! Elementary cellular automata.
+//! Description: https://mathworld.wolfram.com/ElementaryCellularAutomaton.html
+//!
+//! Each cell in a row is either dead or alive and the next state depends on the
+//! cell and its two neighbours. The 8 possible neighbourhoods give 256 rules,
+//! numbered by Wolfram's scheme where bit n of the rule number is the next state
+//! for the neighbourhood with the value n (left * 4 + center * 2 + right).
+
+use std::fmt;
+
+use prng::Rng;
+
+///
+/// AutomatonError enum to represent errors when reading an initial state.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutomatonError {
+    // The initial state contained a character that is not a cell.
+    InvalidCharacter { character: char },
+    // The initial state has more cells than the row.
+    TooWide { length: usize, width: usize },
+}
+
+impl fmt::Display for AutomatonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AutomatonError::InvalidCharacter { character } => write!(f, "Invalid character {character:?}, expected 0, 1, '.' or '#'"),
+            AutomatonError::TooWide { length, width } => write!(f, "Initial state has {length} cells but the width is {width}"),
+        }
+    }
+}
+
+impl std::error::Error for AutomatonError {}
+
+///
+/// Rule struct with the Wolfram rule number.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rule(pub u8);
+
+impl Rule {
+    ///
+    /// Returns the next state of the center cell.
+    ///
+    pub fn next_state(&self, left: bool, center: bool, right: bool) -> bool {
+        let neighbourhood = (left as u8) << 2 | (center as u8) << 1 | right as u8;
+        self.0 & (1 << neighbourhood) != 0
+    }
+
+    ///
+    /// Returns the rule with left and right swapped. The mirrored rule
+    /// draws the mirror image of the diagram.
+    ///
+    pub fn mirrored(&self) -> Rule {
+        Rule((0..8u8).fold(0, |rule, neighbourhood| {
+            let mirrored = (neighbourhood & 1) << 2 | (neighbourhood & 2) | (neighbourhood >> 2);
+            rule | ((self.0 >> mirrored) & 1) << neighbourhood
+        }))
+    }
+
+    ///
+    /// Returns the rule with dead and alive swapped. The complemented rule
+    /// draws the diagram with the colors inverted.
+    ///
+    pub fn complemented(&self) -> Rule {
+        Rule((0..8u8).fold(0, |rule, neighbourhood| rule | (!(self.0 >> (7 - neighbourhood)) & 1) << neighbourhood))
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rule {}", self.0)
+    }
+}
+
+///
+/// Boundary enum for the cells outside the row.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    // The row wraps around, so the first and last cell are neighbours.
+    Periodic,
+    // Cells outside the row are always dead.
+    Dead,
+    // Cells outside the row are always alive.
+    Alive,
+    // Cells outside the row have the same state as the edge cell.
+    Reflect,
+}
+
+///
+/// Automaton struct containing the current row of cells.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Automaton {
+    rule: Rule,
+    boundary: Boundary,
+    cells: Vec<bool>,
+    generation: u64,
+}
+
+impl Automaton {
+    ///
+    /// Creates an automaton with the cells as the first row.
+    ///
+    pub fn new(rule: Rule, boundary: Boundary, cells: Vec<bool>) -> Automaton {
+        Automaton { rule, boundary, cells, generation: 0 }
+    }
+
+    ///
+    /// Creates an automaton where only the center cell is alive.
+    ///
+    pub fn single_cell(rule: Rule, boundary: Boundary, width: usize) -> Automaton {
+        let mut cells = vec![false; width];
+        if width > 0 {
+            cells[width / 2] = true;
+        }
+        Automaton::new(rule, boundary, cells)
+    }
+
+    ///
+    /// Creates an automaton where each cell is alive with the probability.
+    ///
+    pub fn random(rule: Rule, boundary: Boundary, width: usize, density: f64, rng: &mut impl Rng) -> Automaton {
+        Automaton::new(rule, boundary, (0..width).map(|_| rng.gen_bool(density)).collect())
+    }
+
+    ///
+    /// Creates an automaton with the pattern in the center of the row.
+    ///
+    /// pattern: Cells where '1' or '#' is alive and '0' or '.' is dead.
+    ///
+    /// result: The automaton or an error if the pattern is invalid or wider than the row.
+    ///
+    pub fn from_pattern(rule: Rule, boundary: Boundary, width: usize, pattern: &str) -> Result<Automaton, AutomatonError> {
+        let pattern = pattern
+            .chars()
+            .map(|character| match character {
+                '1' | '#' => Ok(true),
+                '0' | '.' => Ok(false),
+                _ => Err(AutomatonError::InvalidCharacter { character }),
+            })
+            .collect::<Result<Vec<bool>, AutomatonError>>()?;
+        if pattern.len() > width {
+            return Err(AutomatonError::TooWide { length: pattern.len(), width });
+        }
+        let mut cells = vec![false; width];
+        let start = (width - pattern.len()) / 2;
+        cells[start..start + pattern.len()].copy_from_slice(&pattern);
+        Ok(Automaton::new(rule, boundary, cells))
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn cells(&self) -> &[bool] {
+        &self.cells
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    ///
+    /// Returns the state of the cell at the position, following the boundary
+    /// condition for positions outside the row.
+    ///
+    fn cell(&self, position: isize) -> bool {
+        let width = self.cells.len() as isize;
+        if (0..width).contains(&position) {
+            return self.cells[position as usize];
+        }
+        match self.boundary {
+            Boundary::Periodic => self.cells[position.rem_euclid(width) as usize],
+            Boundary::Dead => false,
+            Boundary::Alive => true,
+            Boundary::Reflect => self.cells[position.clamp(0, width - 1) as usize],
+        }
+    }
+
+    ///
+    /// Computes the next generation.
+    ///
+    pub fn step(&mut self) {
+        self.cells = (0..self.cells.len() as isize).map(|position| self.rule.next_state(self.cell(position - 1), self.cell(position), self.cell(position + 1))).collect();
+        self.generation += 1;
+    }
+
+    ///
+    /// Computes the generations and returns the space-time diagram.
+    ///
+    /// generations: Number of generations to compute.
+    ///
+    /// result: The rows from the current generation to the last, generations + 1 rows in total.
+    ///
+    pub fn run(&mut self, generations: usize) -> Vec<Vec<bool>> {
+        let mut rows = Vec::with_capacity(generations + 1);
+        rows.push(self.cells.clone());
+        for _ in 0..generations {
+            self.step();
+            rows.push(self.cells.clone());
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prng::Xoshiro256StarStar;
+
+    #[test]
+    fn test_rule_table() {
+        // Rule 30: 111 110 101 100 011 010 001 000 -> 0 0 0 1 1 1 1 0
+        let rule = Rule(30);
+        assert!(!rule.next_state(true, true, true));
+        assert!(rule.next_state(true, false, false));
+        assert!(rule.next_state(false, false, true));
+        assert!(!rule.next_state(false, false, false));
+    }
+
+    #[test]
+    fn test_rule_30_center_column() {
+        // OEIS A051023
+        let expected = [1, 1, 0, 1, 1, 1, 0, 0, 1, 1, 0, 0, 0, 1, 0, 1, 1, 0, 0, 1];
+        let mut automaton = Automaton::single_cell(Rule(30), Boundary::Dead, 101);
+        let column: Vec<u8> = automaton.run(expected.len() - 1).iter().map(|row| row[50] as u8).collect();
+        assert_eq!(column, expected);
+    }
+
+    #[test]
+    fn test_rule_90_sierpinski() {
+        // Row n of the Sierpinski triangle has 2^(number of ones in n) cells.
+        let mut automaton = Automaton::single_cell(Rule(90), Boundary::Dead, 129);
+        for (generation, row) in automaton.run(63).iter().enumerate() {
+            assert_eq!(row.iter().filter(|&&alive| alive).count(), 1 << (generation as u32).count_ones());
+        }
+    }
+
+    #[test]
+    fn test_rule_184_conserves_cells() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let mut automaton = Automaton::random(Rule(184), Boundary::Periodic, 64, 0.4, &mut rng);
+        let population = automaton.cells().iter().filter(|&&alive| alive).count();
+        for row in automaton.run(50) {
+            assert_eq!(row.iter().filter(|&&alive| alive).count(), population);
+        }
+    }
+
+    #[test]
+    fn test_boundaries() {
+        // Rule 2 moves live cells one step to the left.
+        let cells = vec![true, false, false, false];
+        let mut periodic = Automaton::new(Rule(2), Boundary::Periodic, cells.clone());
+        periodic.step();
+        assert_eq!(periodic.cells(), &[false, false, false, true]);
+        let mut dead = Automaton::new(Rule(2), Boundary::Dead, cells);
+        dead.step();
+        assert_eq!(dead.cells(), &[false; 4]);
+        let mut alive = Automaton::new(Rule(2), Boundary::Alive, vec![false; 4]);
+        alive.step();
+        assert_eq!(alive.cells(), &[false, false, false, true]);
+        let mut reflect = Automaton::new(Rule(2), Boundary::Reflect, vec![false, false, false, true]);
+        reflect.step();
+        assert_eq!(reflect.cells(), &[false, false, true, false]);
+    }
+
+    #[test]
+    fn test_from_pattern() {
+        let automaton = Automaton::from_pattern(Rule(110), Boundary::Dead, 7, "1.#").unwrap();
+        assert_eq!(automaton.cells(), &[false, false, true, false, true, false, false]);
+        assert_eq!(Automaton::from_pattern(Rule(110), Boundary::Dead, 2, "101"), Err(AutomatonError::TooWide { length: 3, width: 2 }));
+        assert_eq!(Automaton::from_pattern(Rule(110), Boundary::Dead, 5, "1x1"), Err(AutomatonError::InvalidCharacter { character: 'x' }));
+    }
+
+    #[test]
+    fn test_equivalent_rules() {
+        assert_eq!(Rule(30).mirrored(), Rule(86));
+        assert_eq!(Rule(30).complemented(), Rule(135));
+        assert_eq!(Rule(110).mirrored(), Rule(124));
+        assert_eq!(Rule(90).mirrored(), Rule(90));
+    }
+}
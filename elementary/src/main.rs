@@ -0,0 +1,55 @@
+mod args;
+
+use args::{Args, BoundaryArg, Initial};
+use clap::Parser;
+use elementary::{Automaton, Boundary, Rule};
+use prng::Xoshiro256StarStar;
+use render::terminal::render_cells;
+use render::{BLACK, Image, WHITE};
+
+/**
+ * Elementary cellular automata.
+ * Computes the generations of one of the 256 rules and draws the
+ * space-time diagram in the terminal or as an image.
+ */
+fn main() {
+    let args = Args::parse();
+    let rule = Rule(args.rule);
+    let boundary = match args.boundary {
+        BoundaryArg::Periodic => Boundary::Periodic,
+        BoundaryArg::Dead => Boundary::Dead,
+        BoundaryArg::Alive => Boundary::Alive,
+        BoundaryArg::Reflect => Boundary::Reflect,
+    };
+    let mut automaton = match args.initial {
+        Initial::Single => Automaton::single_cell(rule, boundary, args.width),
+        Initial::Random => {
+            let mut rng = match args.seed {
+                Some(seed) => Xoshiro256StarStar::seed_from_u64(seed),
+                None => Xoshiro256StarStar::from_entropy(),
+            };
+            Automaton::random(rule, boundary, args.width, args.density, &mut rng)
+        }
+        Initial::Pattern => match Automaton::from_pattern(rule, boundary, args.width, args.pattern.as_deref().unwrap_or("1")) {
+            Ok(automaton) => automaton,
+            Err(err) => {
+                eprintln!("Invalid pattern: {err}");
+                return;
+            }
+        },
+    };
+
+    let rows = automaton.run(args.generations);
+    match args.output {
+        Some(output) => {
+            let image = Image::from_cells(args.width, rows.len(), args.scale, |x, y| if rows[y][x] { BLACK } else { WHITE });
+            if let Err(err) = image.save(&output) {
+                eprintln!("Failed to write image {output}: {err}");
+            }
+        }
+        None => {
+            println!("{rule}");
+            print!("{}", render_cells(args.width, rows.len(), |x, y| rows[y][x]));
+        }
+    }
+}
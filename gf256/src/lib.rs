@@ -0,0 +1,216 @@
+//!
+//! Arithmetic in the Galois field GF(2^8) with the reduction polynomial
+//! x^8 + x^4 + x^3 + x + 1 used by AES. The elements are bytes, where bit i is the
+//! coefficient of x^i. Addition is xor, and multiplication is polynomial multiplication
+//! reduced by the polynomial, so every byte except zero has an inverse.
+//! Description: https://en.wikipedia.org/wiki/Finite_field_arithmetic
+//! Specification: FIPS 197, section 4.
+//!
+//! The functions are const, so tables built from them are computed by the compiler.
+//! multiply and inverse run in the same time for every value. The exp and log tables
+//! are faster, but the lookups depend on the values.
+//!
+
+#![no_std]
+
+///
+/// The reduction polynomial x^8 + x^4 + x^3 + x + 1.
+///
+pub const POLYNOMIAL: u16 = 0x11b;
+
+///
+/// The generator of the multiplicative group used for the exp and log tables, x + 1.
+///
+pub const GENERATOR: u8 = 0x03;
+
+///
+/// The powers of the generator. EXP[n] is GENERATOR^n, and EXP[255] is 1 again, so
+/// EXP[(LOG[a] + LOG[b]) % 255] is the product of a and b.
+///
+pub const EXP: [u8; 256] = exp_table();
+
+///
+/// The logarithms to the base of the generator. LOG[EXP[n]] is n for n below 255. Zero
+/// has no logarithm and LOG[0] is 0.
+///
+pub const LOG: [u8; 256] = log_table();
+
+///
+/// Adds two elements. Addition and subtraction are both xor.
+///
+/// a: An element.
+/// b: An element.
+///
+/// result: The sum.
+///
+pub const fn add(a: u8, b: u8) -> u8 {
+    a ^ b
+}
+
+///
+/// Multiplies an element by x, shifting it left and reducing the bit that falls out.
+///
+/// a: An element.
+///
+/// result: The element times x.
+///
+pub const fn xtime(a: u8) -> u8 {
+    (a << 1) ^ ((a >> 7) * (POLYNOMIAL as u8))
+}
+
+///
+/// Multiplies two elements. The loop always runs eight times and has no branches on the
+/// values, so the time does not depend on them.
+///
+/// a: An element.
+/// b: An element.
+///
+/// result: The product.
+///
+pub const fn multiply(a: u8, b: u8) -> u8 {
+    let mut a = a;
+    let mut result = 0;
+    let mut bit = 0;
+    while bit < 8 {
+        result ^= a & 0u8.wrapping_sub((b >> bit) & 1);
+        a = xtime(a);
+        bit += 1;
+    }
+    result
+}
+
+///
+/// Raises an element to a power by repeated squaring.
+///
+/// a: An element.
+/// exponent: The power.
+///
+/// result: a to the power. Zero to the power zero is one.
+///
+pub const fn pow(a: u8, exponent: u32) -> u8 {
+    let mut base = a;
+    let mut exponent = exponent;
+    let mut result = 1;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = multiply(result, base);
+        }
+        base = multiply(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+///
+/// Finds the multiplicative inverse. The group of the nonzero elements has 255 elements,
+/// so the inverse is a^254.
+///
+/// a: An element.
+///
+/// result: The inverse, or zero for zero, as in the AES s-box.
+///
+pub const fn inverse(a: u8) -> u8 {
+    pow(a, 254)
+}
+
+///
+/// Divides one element by another.
+///
+/// a: The dividend.
+/// b: The divisor.
+///
+/// result: The quotient, or None if the divisor is zero.
+///
+pub const fn divide(a: u8, b: u8) -> Option<u8> {
+    if b == 0 {
+        return None;
+    }
+    Some(multiply(a, inverse(b)))
+}
+
+const fn exp_table() -> [u8; 256] {
+    let mut result = [0u8; 256];
+    let mut value = 1;
+    let mut idx = 0;
+    while idx < 256 {
+        result[idx] = value;
+        value = multiply(value, GENERATOR);
+        idx += 1;
+    }
+    result
+}
+
+const fn log_table() -> [u8; 256] {
+    let exp = exp_table();
+    let mut result = [0u8; 256];
+    let mut idx = 0;
+    while idx < 255 {
+        result[exp[idx] as usize] = idx as u8;
+        idx += 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///
+    /// The examples in sections 4.1 and 4.2 of FIPS 197.
+    ///
+    #[test]
+    fn test_fips197() {
+        assert_eq!(add(0x57, 0x83), 0xd4);
+        assert_eq!(multiply(0x57, 0x83), 0xc1);
+        assert_eq!(multiply(0x57, 0x13), 0xfe);
+        assert_eq!([xtime(0x57), xtime(0xae), xtime(0x47), xtime(0x8e)], [0xae, 0x47, 0x8e, 0x07]);
+    }
+
+    #[test]
+    fn test_multiply() {
+        for a in 0..=255u8 {
+            assert_eq!(multiply(a, 0), 0);
+            assert_eq!(multiply(a, 1), a);
+            assert_eq!(multiply(a, 2), xtime(a));
+            for b in 0..=255u8 {
+                assert_eq!(multiply(a, b), multiply(b, a));
+                // Multiplication distributes over addition.
+                assert_eq!(multiply(a, add(b, 0x1f)), add(multiply(a, b), multiply(a, 0x1f)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_inverse() {
+        // The inverse of 0x53 is the example of the s-box construction.
+        assert_eq!(inverse(0x53), 0xca);
+        assert_eq!(inverse(0), 0);
+        for a in 1..=255u8 {
+            assert_eq!(multiply(a, inverse(a)), 1);
+            assert_eq!(divide(a, a), Some(1));
+        }
+        assert_eq!(divide(5, 0), None);
+        assert_eq!(pow(0x57, 0), 1);
+        assert_eq!(pow(0x57, 255), 1);
+    }
+
+    ///
+    /// The generator reaches every nonzero element, and the tables multiply like multiply.
+    ///
+    #[test]
+    fn test_tables() {
+        let mut seen = [false; 256];
+        for &value in &EXP[..255] {
+            assert!(!seen[value as usize]);
+            seen[value as usize] = true;
+        }
+        assert!(!seen[0]);
+        assert_eq!(EXP[255], 1);
+        for a in 1..=255u8 {
+            assert_eq!(EXP[LOG[a as usize] as usize], a);
+            for b in 1..=255u8 {
+                assert_eq!(EXP[(LOG[a as usize] as usize + LOG[b as usize] as usize) % 255], multiply(a, b));
+            }
+        }
+    }
+}
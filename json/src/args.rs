@@ -0,0 +1,29 @@
+use clap::{Parser, ValueEnum};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// JSON file to read. Reads stdin if not specified
+    #[arg(short, long)]
+    pub input_file: Option<String>,
+
+    /// What to do with the document
+    #[arg(short, long, value_enum, default_value_t = Mode::Pretty)]
+    pub mode: Mode,
+
+    /// Number of spaces per level in pretty output
+    #[arg(short = 'n', long, default_value_t = 2)]
+    pub indent: usize,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Only check that the document is valid
+    Validate,
+    /// Print the document without whitespace
+    Compact,
+    /// Print the document indented
+    Pretty,
+    /// Print the events of the streaming reader, one per line
+    Events,
+}
@@ -0,0 +1,222 @@
+//!
+//! Streaming reader that returns a document as a sequence of events, like a
+//! SAX parser. The nesting is kept on an explicit stack, so memory use only
+//! depends on the depth and the largest string, not on the document size.
+//!
+
+use crate::JsonError;
+use crate::parser::MAX_DEPTH;
+use crate::scanner::Scanner;
+
+///
+/// Event enum for the parts of a document in the order they appear.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    // An object key. The next event is its value.
+    Key(String),
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+///
+/// Container enum for the open arrays and objects.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Array,
+    Object,
+}
+
+///
+/// Expect enum for what the reader expects next.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    // A value, at the start of the document, after a key or after a comma in an array.
+    Value,
+    // A value or ], after [.
+    FirstValue,
+    // A key or }, after {.
+    FirstKey,
+    // A key, after a comma in an object.
+    Key,
+    // A comma or the end of the container, or the end of the input after the top level value.
+    Separator,
+    // Nothing, the document has been read or an error was returned.
+    Done,
+}
+
+///
+/// Reader struct for reading a document event by event.
+///
+pub struct Reader<'a> {
+    scanner: Scanner<'a>,
+    stack: Vec<Container>,
+    expect: Expect,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(input: &'a [u8]) -> Reader<'a> {
+        Reader { scanner: Scanner::new(input), stack: Vec::new(), expect: Expect::Value }
+    }
+
+    ///
+    /// Returns the current nesting depth.
+    ///
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    ///
+    /// Reads the next event.
+    ///
+    /// result: The event, None after the whole document has been read, or an
+    /// error. After an error the reader returns None.
+    ///
+    pub fn next_event(&mut self) -> Result<Option<Event>, JsonError> {
+        let result = self.read();
+        if result.is_err() {
+            self.expect = Expect::Done;
+        }
+        result
+    }
+
+    fn read(&mut self) -> Result<Option<Event>, JsonError> {
+        loop {
+            self.scanner.skip_whitespace();
+            match self.expect {
+                Expect::Done => return Ok(None),
+                Expect::Value => return self.value().map(Some),
+                Expect::FirstValue if self.scanner.peek() == Some(b']') => return Ok(Some(self.close())),
+                Expect::FirstValue => self.expect = Expect::Value,
+                Expect::FirstKey if self.scanner.peek() == Some(b'}') => return Ok(Some(self.close())),
+                Expect::FirstKey | Expect::Key => {
+                    let key = self.scanner.string()?;
+                    self.scanner.skip_whitespace();
+                    self.scanner.expect(b':')?;
+                    self.expect = Expect::Value;
+                    return Ok(Some(Event::Key(key)));
+                }
+                Expect::Separator => match (self.stack.last(), self.scanner.peek()) {
+                    (None, None) => {
+                        self.expect = Expect::Done;
+                        return Ok(None);
+                    }
+                    (Some(container), Some(b',')) => {
+                        self.scanner.advance();
+                        self.expect = if *container == Container::Array { Expect::Value } else { Expect::Key };
+                    }
+                    (Some(Container::Array), Some(b']')) | (Some(Container::Object), Some(b'}')) => return Ok(Some(self.close())),
+                    _ => return Err(self.scanner.unexpected()),
+                },
+            }
+        }
+    }
+
+    ///
+    /// Reads a value, or the start of an array or object.
+    ///
+    fn value(&mut self) -> Result<Event, JsonError> {
+        let container = match self.scanner.peek() {
+            Some(b'[') => Some((Container::Array, Event::StartArray, Expect::FirstValue)),
+            Some(b'{') => Some((Container::Object, Event::StartObject, Expect::FirstKey)),
+            _ => None,
+        };
+        if let Some((container, event, expect)) = container {
+            if self.stack.len() >= MAX_DEPTH {
+                return Err(JsonError::DepthLimit { position: self.scanner.position() });
+            }
+            self.scanner.advance();
+            self.stack.push(container);
+            self.expect = expect;
+            return Ok(event);
+        }
+        let event = match self.scanner.peek() {
+            Some(b'"') => Event::String(self.scanner.string()?),
+            Some(b't') => self.scanner.literal(b"true").map(|_| Event::Bool(true))?,
+            Some(b'f') => self.scanner.literal(b"false").map(|_| Event::Bool(false))?,
+            Some(b'n') => self.scanner.literal(b"null").map(|_| Event::Null)?,
+            Some(b'-' | b'0'..=b'9') => Event::Number(self.scanner.number()?),
+            _ => return Err(self.scanner.unexpected()),
+        };
+        self.expect = Expect::Separator;
+        Ok(event)
+    }
+
+    ///
+    /// Consumes the closing bracket of the innermost container.
+    ///
+    fn close(&mut self) -> Event {
+        self.scanner.advance();
+        self.expect = Expect::Separator;
+        match self.stack.pop() {
+            Some(Container::Array) => Event::EndArray,
+            _ => Event::EndObject,
+        }
+    }
+}
+
+impl Iterator for Reader<'_> {
+    type Item = Result<Event, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_bytes;
+    use std::fs;
+
+    #[test]
+    fn test_events() {
+        let events: Result<Vec<Event>, JsonError> = Reader::new(br#"{"a": [1, "x", null], "b": {}}"#).collect();
+        let expected = vec![
+            Event::StartObject,
+            Event::Key("a".to_string()),
+            Event::StartArray,
+            Event::Number(1.0),
+            Event::String("x".to_string()),
+            Event::Null,
+            Event::EndArray,
+            Event::Key("b".to_string()),
+            Event::StartObject,
+            Event::EndObject,
+            Event::EndObject,
+        ];
+        assert_eq!(events, Ok(expected));
+    }
+
+    #[test]
+    fn test_error_stops_reader() {
+        let mut reader = Reader::new(b"[true, fals]");
+        assert_eq!(reader.next_event(), Ok(Some(Event::StartArray)));
+        assert_eq!(reader.next_event(), Ok(Some(Event::Bool(true))));
+        assert!(reader.next_event().is_err());
+        assert_eq!(reader.next_event(), Ok(None));
+    }
+
+    ///
+    /// The reader must accept and reject the same documents as the parser.
+    ///
+    #[test]
+    fn test_agrees_with_parser() {
+        for entry in fs::read_dir("testdata/test_parsing").unwrap() {
+            let path = entry.unwrap().path();
+            let input = fs::read(&path).unwrap();
+            let events: Result<Vec<Event>, JsonError> = Reader::new(&input).collect();
+            assert_eq!(events.err(), parse_bytes(&input).err(), "{}", path.display());
+        }
+        let deep = "[".repeat(100_000);
+        assert_eq!(Reader::new(deep.as_bytes()).find_map(Result::err), parse_bytes(deep.as_bytes()).err());
+    }
+}
@@ -0,0 +1,195 @@
+///
+/// JSON parser and serializer.
+/// Description: https://www.rfc-editor.org/rfc/rfc8259
+///
+/// Documents are parsed with a recursive descent parser into a Value tree, or
+/// read one event at a time with the Reader, which keeps its own stack instead
+/// of recursing and does not build the tree. Both report errors with the line
+/// and column where the problem was found. Values are serialized compactly or
+/// indented.
+///
+pub mod events;
+mod parser;
+mod scanner;
+pub mod serializer;
+
+use std::fmt;
+
+pub use parser::{MAX_DEPTH, parse, parse_bytes};
+
+///
+/// Position struct with a location in the input. Line and column start at 1
+/// and the column counts characters, while the offset counts bytes.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} column {}", self.line, self.column)
+    }
+}
+
+///
+/// JsonError enum to represent errors when parsing documents.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonError {
+    // The input ended in the middle of a value.
+    UnexpectedEnd { position: Position },
+    // A character that is not allowed at the position. Bytes that are not valid UTF-8 are reported as U+FFFD.
+    UnexpectedCharacter { character: char, position: Position },
+    // A number not following the JSON grammar, like 01, 1. or .5.
+    InvalidNumber { position: Position },
+    // A backslash followed by a character that is not an escape.
+    InvalidEscape { position: Position },
+    // A \u escape with a lone surrogate, which can not be represented in a string.
+    InvalidUnicode { position: Position },
+    // Bytes in a string that are not valid UTF-8.
+    InvalidUtf8 { position: Position },
+    // A control character in a string. They have to be escaped.
+    ControlCharacter { position: Position },
+    // Arrays and objects are nested deeper than MAX_DEPTH.
+    DepthLimit { position: Position },
+}
+
+impl JsonError {
+    ///
+    /// Returns where the error was found.
+    ///
+    pub fn position(&self) -> Position {
+        match self {
+            JsonError::UnexpectedEnd { position }
+            | JsonError::UnexpectedCharacter { position, .. }
+            | JsonError::InvalidNumber { position }
+            | JsonError::InvalidEscape { position }
+            | JsonError::InvalidUnicode { position }
+            | JsonError::InvalidUtf8 { position }
+            | JsonError::ControlCharacter { position }
+            | JsonError::DepthLimit { position } => *position,
+        }
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::UnexpectedEnd { position } => write!(f, "Unexpected end of input at {position}"),
+            JsonError::UnexpectedCharacter { character, position } => write!(f, "Unexpected character {character:?} at {position}"),
+            JsonError::InvalidNumber { position } => write!(f, "Invalid number at {position}"),
+            JsonError::InvalidEscape { position } => write!(f, "Invalid escape at {position}"),
+            JsonError::InvalidUnicode { position } => write!(f, "Unpaired surrogate in unicode escape at {position}"),
+            JsonError::InvalidUtf8 { position } => write!(f, "Invalid UTF-8 at {position}"),
+            JsonError::ControlCharacter { position } => write!(f, "Unescaped control character at {position}"),
+            JsonError::DepthLimit { position } => write!(f, "Nesting deeper than {MAX_DEPTH} at {position}"),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+///
+/// Value enum for a parsed document. Object members keep the order and
+/// duplicates from the input.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    ///
+    /// Returns the value of the last member with the key, like most parsers do
+    /// with duplicate keys. None if this is not an object or the key is missing.
+    ///
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.iter().rev().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Returns the array element at the index. None if this is not an array or the index is out of range.
+    ///
+    pub fn index(&self, index: usize) -> Option<&Value> {
+        match self {
+            Value::Array(elements) => elements.get(index),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == Value::Null
+    }
+}
+
+impl fmt::Display for Value {
+    ///
+    /// Writes the value as compact JSON.
+    ///
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serializer::to_string(self))
+    }
+}
+
+impl std::str::FromStr for Value {
+    type Err = JsonError;
+
+    fn from_str(text: &str) -> Result<Value, JsonError> {
+        parse(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accessors() {
+        let value = parse(r#"{"a": [1, true, "x", null], "a": {"b": 2}}"#).unwrap();
+        assert_eq!(value.get("a").and_then(|a| a.get("b")).and_then(Value::as_f64), Some(2.0));
+        assert_eq!(value.index(0), None);
+        let array = Value::Array(vec![Value::Number(1.0), Value::Bool(true), Value::String("x".to_string()), Value::Null]);
+        assert_eq!(array.index(1).and_then(Value::as_bool), Some(true));
+        assert_eq!(array.index(2).and_then(Value::as_str), Some("x"));
+        assert!(array.index(3).is_some_and(Value::is_null));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let text = r#"{"name":"Ada","tags":["a","b"],"age":36,"ratio":0.25,"nested":{"empty":[]}}"#;
+        let value: Value = text.parse().unwrap();
+        assert_eq!(value.to_string(), text);
+    }
+}
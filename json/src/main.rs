@@ -0,0 +1,89 @@
+mod args;
+
+use std::fs;
+use std::io::Read;
+use std::process::ExitCode;
+
+use args::{Args, Mode};
+use clap::Parser;
+use json::events::{Event, Reader};
+use json::serializer::{format_number, to_string, to_string_pretty, write_string};
+use json::{JsonError, parse_bytes};
+
+/**
+ * Validates, formats or streams a JSON document. Exits with an error code if
+ * the document is invalid.
+ */
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let input = match read_input(&args.input_file) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("Failed to read input: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let result = match args.mode {
+        Mode::Validate => parse_bytes(&input).map(|_| println!("Valid")),
+        Mode::Compact => parse_bytes(&input).map(|value| println!("{}", to_string(&value))),
+        Mode::Pretty => parse_bytes(&input).map(|value| println!("{}", to_string_pretty(&value, args.indent))),
+        Mode::Events => print_events(&input, args.indent),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/**
+ * Reads the whole input file, or stdin if no file is given.
+ */
+fn read_input(input_file: &Option<String>) -> std::io::Result<Vec<u8>> {
+    match input_file {
+        Some(path) => fs::read(path),
+        None => {
+            let mut input = Vec::new();
+            std::io::stdin().read_to_end(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+/**
+ * Prints the events of the document indented by depth.
+ *
+ * # Arguments
+ * * `input`: The document.
+ * * `indent`: Number of spaces per level.
+ */
+fn print_events(input: &[u8], indent: usize) -> Result<(), JsonError> {
+    let mut reader = Reader::new(input);
+    while let Some(event) = reader.next_event()? {
+        let depth = match event {
+            Event::StartObject | Event::StartArray => reader.depth() - 1,
+            _ => reader.depth(),
+        };
+        let text = match event {
+            Event::StartObject => "StartObject".to_string(),
+            Event::EndObject => "EndObject".to_string(),
+            Event::StartArray => "StartArray".to_string(),
+            Event::EndArray => "EndArray".to_string(),
+            Event::Key(key) => quoted("Key", &key),
+            Event::String(text) => quoted("String", &text),
+            Event::Number(number) => format!("Number {}", format_number(number)),
+            Event::Bool(value) => format!("Bool {value}"),
+            Event::Null => "Null".to_string(),
+        };
+        println!("{}{text}", " ".repeat(depth * indent));
+    }
+    Ok(())
+}
+
+fn quoted(name: &str, text: &str) -> String {
+    let mut output = format!("{name} ");
+    write_string(&mut output, text);
+    output
+}
@@ -0,0 +1,193 @@
+use crate::scanner::Scanner;
+use crate::{JsonError, Value};
+
+///
+/// Maximum nesting of arrays and objects. Limits the recursion depth of the
+/// parser, so deeply nested input gives an error instead of a stack overflow.
+///
+pub const MAX_DEPTH: usize = 512;
+
+///
+/// Parses a JSON document.
+///
+/// result: The value or an error with the position of the problem.
+///
+pub fn parse(text: &str) -> Result<Value, JsonError> {
+    parse_bytes(text.as_bytes())
+}
+
+///
+/// Parses a JSON document from bytes, which must be UTF-8 without a byte order mark.
+///
+pub fn parse_bytes(input: &[u8]) -> Result<Value, JsonError> {
+    let mut scanner = Scanner::new(input);
+    scanner.skip_whitespace();
+    let value = value(&mut scanner, 0)?;
+    scanner.skip_whitespace();
+    if !scanner.at_end() {
+        return Err(scanner.unexpected());
+    }
+    Ok(value)
+}
+
+///
+/// value := object | array | string | number | true | false | null
+///
+/// depth: Number of arrays and objects the value is inside.
+///
+fn value(scanner: &mut Scanner, depth: usize) -> Result<Value, JsonError> {
+    match scanner.peek() {
+        Some(b'{') => object(scanner, depth + 1),
+        Some(b'[') => array(scanner, depth + 1),
+        Some(b'"') => Ok(Value::String(scanner.string()?)),
+        Some(b't') => scanner.literal(b"true").map(|_| Value::Bool(true)),
+        Some(b'f') => scanner.literal(b"false").map(|_| Value::Bool(false)),
+        Some(b'n') => scanner.literal(b"null").map(|_| Value::Null),
+        Some(b'-' | b'0'..=b'9') => Ok(Value::Number(scanner.number()?)),
+        _ => Err(scanner.unexpected()),
+    }
+}
+
+///
+/// array := [ ] | [ value ( , value )* ]
+///
+fn array(scanner: &mut Scanner, depth: usize) -> Result<Value, JsonError> {
+    if depth > MAX_DEPTH {
+        return Err(JsonError::DepthLimit { position: scanner.position() });
+    }
+    scanner.expect(b'[')?;
+    scanner.skip_whitespace();
+    let mut elements = Vec::new();
+    if scanner.peek() == Some(b']') {
+        scanner.advance();
+        return Ok(Value::Array(elements));
+    }
+    loop {
+        elements.push(value(scanner, depth)?);
+        scanner.skip_whitespace();
+        match scanner.peek() {
+            Some(b',') => {
+                scanner.advance();
+                scanner.skip_whitespace();
+            }
+            Some(b']') => {
+                scanner.advance();
+                return Ok(Value::Array(elements));
+            }
+            _ => return Err(scanner.unexpected()),
+        }
+    }
+}
+
+///
+/// object := { } | { string : value ( , string : value )* }
+///
+fn object(scanner: &mut Scanner, depth: usize) -> Result<Value, JsonError> {
+    if depth > MAX_DEPTH {
+        return Err(JsonError::DepthLimit { position: scanner.position() });
+    }
+    scanner.expect(b'{')?;
+    scanner.skip_whitespace();
+    let mut members = Vec::new();
+    if scanner.peek() == Some(b'}') {
+        scanner.advance();
+        return Ok(Value::Object(members));
+    }
+    loop {
+        let key = scanner.string()?;
+        scanner.skip_whitespace();
+        scanner.expect(b':')?;
+        scanner.skip_whitespace();
+        members.push((key, value(scanner, depth)?));
+        scanner.skip_whitespace();
+        match scanner.peek() {
+            Some(b',') => {
+                scanner.advance();
+                scanner.skip_whitespace();
+            }
+            Some(b'}') => {
+                scanner.advance();
+                return Ok(Value::Object(members));
+            }
+            _ => return Err(scanner.unexpected()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Position;
+    use std::fs;
+
+    fn position(offset: usize, line: usize, column: usize) -> Position {
+        Position { offset, line, column }
+    }
+
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(parse(" null "), Ok(Value::Null));
+        assert_eq!(parse("[-1.5e2, true]"), Ok(Value::Array(vec![Value::Number(-150.0), Value::Bool(true)])));
+        assert_eq!(parse(r#"{"a":"æ😀\n"}"#), Ok(Value::Object(vec![("a".to_string(), Value::String("æ😀\n".to_string()))])));
+    }
+
+    #[test]
+    fn test_error_positions() {
+        assert_eq!(parse("[1,\n 2,\n ]"), Err(JsonError::UnexpectedCharacter { character: ']', position: position(9, 3, 2) }));
+        assert_eq!(parse(r#"{"æø": tru}"#), Err(JsonError::UnexpectedCharacter { character: '}', position: position(12, 1, 11) }));
+        assert_eq!(parse("[01]"), Err(JsonError::InvalidNumber { position: position(2, 1, 3) }));
+        assert_eq!(parse("[1."), Err(JsonError::InvalidNumber { position: position(3, 1, 4) }));
+        assert_eq!(parse(r#"["a\qb"]"#), Err(JsonError::InvalidEscape { position: position(3, 1, 4) }));
+        assert_eq!(parse(r#"["\ud800x"]"#), Err(JsonError::InvalidUnicode { position: position(2, 1, 3) }));
+        assert_eq!(parse("[\"a\tb\"]"), Err(JsonError::ControlCharacter { position: position(3, 1, 4) }));
+        assert_eq!(parse_bytes(b"[\"\xff\"]"), Err(JsonError::InvalidUtf8 { position: position(2, 1, 3) }));
+        assert_eq!(parse("[1"), Err(JsonError::UnexpectedEnd { position: position(2, 1, 3) }));
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let nested = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(parse(&nested).is_ok());
+        let too_deep = format!("{}{}", "[".repeat(MAX_DEPTH + 1), "]".repeat(MAX_DEPTH + 1));
+        assert_eq!(parse(&too_deep), Err(JsonError::DepthLimit { position: position(MAX_DEPTH, 1, MAX_DEPTH + 1) }));
+        // The opening arrays test of JSONTestSuite.
+        assert!(parse(&"[".repeat(100_000)).is_err());
+    }
+
+    ///
+    /// Files of test_parsing that must be present, the deep nesting cases that
+    /// overflow the stack of a recursive parser without a depth limit.
+    ///
+    const REQUIRED_FILES: [&str; 2] = ["i_structure_500_nested_arrays.json", "n_structure_100000_opening_arrays.json"];
+
+    ///
+    /// Runs the test_parsing files of JSONTestSuite
+    /// (https://github.com/nst/JSONTestSuite). Files starting with y_ must be
+    /// accepted, n_ must be rejected, and i_ may be either but must not panic.
+    /// The y_ documents must be unchanged after serializing and parsing again.
+    /// No file is excluded, but the directory holds 148 of the 318 files of
+    /// test_parsing; the others are still to be copied from the suite.
+    ///
+    #[test]
+    fn test_json_test_suite() {
+        let mut entries: Vec<_> = fs::read_dir("testdata/test_parsing").unwrap().map(|entry| entry.unwrap().path()).collect();
+        entries.sort();
+        assert!(!entries.is_empty());
+        for file in REQUIRED_FILES {
+            assert!(entries.iter().any(|path| path.ends_with(file)), "{file} is missing");
+        }
+        for path in entries {
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            let input = fs::read(&path).unwrap();
+            let result = parse_bytes(&input);
+            match &name[..2] {
+                "y_" => {
+                    let value = result.unwrap_or_else(|err| panic!("{name}: {err}"));
+                    assert_eq!(parse(&value.to_string()), Ok(value), "{name}");
+                }
+                "n_" => assert!(result.is_err(), "{name}: {result:?}"),
+                _ => {}
+            }
+        }
+    }
+}
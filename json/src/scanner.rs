@@ -0,0 +1,230 @@
+//!
+//! Scanner for the tokens shared by the parser and the event reader: whitespace,
+//! literals, strings and numbers, with the position in the input kept up to date.
+//!
+
+use crate::{JsonError, Position};
+
+///
+/// Scanner struct over the input bytes.
+///
+pub struct Scanner<'a> {
+    input: &'a [u8],
+    position: Position,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(input: &'a [u8]) -> Scanner<'a> {
+        Scanner { input, position: Position { offset: 0, line: 1, column: 1 } }
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.input.get(self.position.offset).copied()
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.position.offset >= self.input.len()
+    }
+
+    ///
+    /// Moves past the current byte. UTF-8 continuation bytes do not count as columns.
+    ///
+    pub fn advance(&mut self) {
+        match self.peek() {
+            Some(b'\n') => {
+                self.position.line += 1;
+                self.position.column = 1;
+            }
+            Some(0x80..=0xBF) | None => {}
+            Some(_) => self.position.column += 1,
+        }
+        self.position.offset += 1;
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.advance();
+        }
+    }
+
+    ///
+    /// Returns the error for the current byte, which is not allowed here.
+    ///
+    pub fn unexpected(&self) -> JsonError {
+        if self.at_end() {
+            return JsonError::UnexpectedEnd { position: self.position };
+        }
+        let character = decode_char(&self.input[self.position.offset..]).map_or(char::REPLACEMENT_CHARACTER, |(character, _)| character);
+        JsonError::UnexpectedCharacter { character, position: self.position }
+    }
+
+    ///
+    /// Consumes the byte, or returns an error if another byte is found.
+    ///
+    pub fn expect(&mut self, expected: u8) -> Result<(), JsonError> {
+        if self.peek() != Some(expected) {
+            return Err(self.unexpected());
+        }
+        self.advance();
+        Ok(())
+    }
+
+    ///
+    /// Consumes the literal true, false or null.
+    ///
+    pub fn literal(&mut self, literal: &[u8]) -> Result<(), JsonError> {
+        literal.iter().try_for_each(|&expected| self.expect(expected))
+    }
+
+    ///
+    /// Reads a string starting at the opening quote.
+    ///
+    pub fn string(&mut self) -> Result<String, JsonError> {
+        self.expect(b'"')?;
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.unexpected()),
+                Some(b'"') => {
+                    self.advance();
+                    return Ok(value);
+                }
+                Some(b'\\') => value.push(self.escape()?),
+                Some(0x00..=0x1F) => return Err(JsonError::ControlCharacter { position: self.position }),
+                Some(byte) if byte.is_ascii() => {
+                    value.push(byte as char);
+                    self.advance();
+                }
+                Some(_) => {
+                    let (character, length) = decode_char(&self.input[self.position.offset..]).ok_or(JsonError::InvalidUtf8 { position: self.position })?;
+                    value.push(character);
+                    (0..length).for_each(|_| self.advance());
+                }
+            }
+        }
+    }
+
+    ///
+    /// Reads an escape starting at the backslash. A \u escape with a high
+    /// surrogate must be followed by one with a low surrogate.
+    ///
+    fn escape(&mut self) -> Result<char, JsonError> {
+        let start = self.position;
+        self.advance();
+        let character = match self.peek() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{C}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                self.advance();
+                let high = self.hex()?;
+                return match high {
+                    0xD800..=0xDBFF => {
+                        if self.peek() != Some(b'\\') || self.input.get(self.position.offset + 1) != Some(&b'u') {
+                            return Err(JsonError::InvalidUnicode { position: start });
+                        }
+                        self.advance();
+                        self.advance();
+                        let low = self.hex()?;
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            return Err(JsonError::InvalidUnicode { position: start });
+                        }
+                        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).ok_or(JsonError::InvalidUnicode { position: start })
+                    }
+                    _ => char::from_u32(high).ok_or(JsonError::InvalidUnicode { position: start }),
+                };
+            }
+            None => return Err(self.unexpected()),
+            Some(_) => return Err(JsonError::InvalidEscape { position: start }),
+        };
+        self.advance();
+        Ok(character)
+    }
+
+    ///
+    /// Reads the four hex digits of a \u escape.
+    ///
+    fn hex(&mut self) -> Result<u32, JsonError> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let digit = self.peek().and_then(|byte| (byte as char).to_digit(16)).ok_or_else(|| self.unexpected())?;
+            value = value * 16 + digit;
+            self.advance();
+        }
+        Ok(value)
+    }
+
+    ///
+    /// Reads a number: an optional minus, an integer part without leading zeros,
+    /// and optional fraction and exponent parts with at least one digit each.
+    ///
+    pub fn number(&mut self) -> Result<f64, JsonError> {
+        let start = self.position.offset;
+        if self.peek() == Some(b'-') {
+            self.advance();
+        }
+        match self.peek() {
+            Some(b'0') => {
+                self.advance();
+                if self.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+                    return Err(JsonError::InvalidNumber { position: self.position });
+                }
+            }
+            Some(b'1'..=b'9') => self.digits(),
+            _ => return Err(JsonError::InvalidNumber { position: self.position }),
+        }
+        if self.peek() == Some(b'.') {
+            self.advance();
+            self.required_digits()?;
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.advance();
+            if let Some(b'+' | b'-') = self.peek() {
+                self.advance();
+            }
+            self.required_digits()?;
+        }
+        let text = std::str::from_utf8(&self.input[start..self.position.offset]).expect("Numbers are ASCII");
+        Ok(text.parse().expect("Numbers follow the float syntax"))
+    }
+
+    fn digits(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+            self.advance();
+        }
+    }
+
+    fn required_digits(&mut self) -> Result<(), JsonError> {
+        if !self.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+            return Err(JsonError::InvalidNumber { position: self.position });
+        }
+        self.digits();
+        Ok(())
+    }
+}
+
+///
+/// Decodes the UTF-8 character at the start of the bytes.
+///
+/// result: The character and its length in bytes, or None if the bytes are not valid UTF-8.
+///
+fn decode_char(bytes: &[u8]) -> Option<(char, usize)> {
+    let length = match bytes.first()? {
+        0x00..=0x7F => 1,
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return None,
+    };
+    let text = std::str::from_utf8(bytes.get(..length)?).ok()?;
+    text.chars().next().map(|character| (character, length))
+}
@@ -0,0 +1,152 @@
+//!
+//! Serialization of values to compact or indented JSON.
+//!
+
+use crate::Value;
+
+///
+/// Returns the value as JSON without whitespace.
+///
+pub fn to_string(value: &Value) -> String {
+    let mut output = String::new();
+    write_value(&mut output, value, None, 0);
+    output
+}
+
+///
+/// Returns the value as JSON with one member or element per line.
+///
+/// indent: Number of spaces per nesting level.
+///
+pub fn to_string_pretty(value: &Value, indent: usize) -> String {
+    let mut output = String::new();
+    write_value(&mut output, value, Some(indent), 0);
+    output
+}
+
+///
+/// Writes the value.
+///
+/// indent: Spaces per level, or None for compact output.
+/// level: The nesting level of the value.
+///
+fn write_value(output: &mut String, value: &Value, indent: Option<usize>, level: usize) {
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Bool(value) => output.push_str(if *value { "true" } else { "false" }),
+        Value::Number(number) => output.push_str(&format_number(*number)),
+        Value::String(text) => write_string(output, text),
+        Value::Array(elements) => {
+            output.push('[');
+            for (index, element) in elements.iter().enumerate() {
+                separator(output, index, indent, level + 1);
+                write_value(output, element, indent, level + 1);
+            }
+            close(output, elements.is_empty(), indent, level);
+            output.push(']');
+        }
+        Value::Object(members) => {
+            output.push('{');
+            for (index, (key, member)) in members.iter().enumerate() {
+                separator(output, index, indent, level + 1);
+                write_string(output, key);
+                output.push(':');
+                if indent.is_some() {
+                    output.push(' ');
+                }
+                write_value(output, member, indent, level + 1);
+            }
+            close(output, members.is_empty(), indent, level);
+            output.push('}');
+        }
+    }
+}
+
+///
+/// Writes the comma before all but the first element, and the line break and indentation.
+///
+fn separator(output: &mut String, index: usize, indent: Option<usize>, level: usize) {
+    if index > 0 {
+        output.push(',');
+    }
+    if let Some(indent) = indent {
+        output.push('\n');
+        output.push_str(&" ".repeat(indent * level));
+    }
+}
+
+///
+/// Writes the line break before the closing bracket of a non empty container.
+///
+fn close(output: &mut String, empty: bool, indent: Option<usize>, level: usize) {
+    if let Some(indent) = indent.filter(|_| !empty) {
+        output.push('\n');
+        output.push_str(&" ".repeat(indent * level));
+    }
+}
+
+///
+/// Writes the string with quotes, escaping quotes, backslashes and control characters.
+///
+pub fn write_string(output: &mut String, text: &str) {
+    output.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{C}' => output.push_str("\\f"),
+            '\0'..='\u{1F}' => output.push_str(&format!("\\u{:04x}", character as u32)),
+            _ => output.push(character),
+        }
+    }
+    output.push('"');
+}
+
+///
+/// Formats the number with the shortest text that parses back to it. Integers
+/// are written without a fraction and very large or small numbers with an
+/// exponent. JSON has no infinity or NaN, so they are written as null.
+///
+pub fn format_number(number: f64) -> String {
+    if !number.is_finite() {
+        "null".to_string()
+    } else if number.fract() == 0.0 && number.abs() < 1e16 {
+        format!("{}", number as i64)
+    } else if number.abs() >= 1e16 || number.abs() < 1e-5 {
+        format!("{number:e}")
+    } else {
+        format!("{number}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(42.0), "42");
+        assert_eq!(format_number(-0.5), "-0.5");
+        assert_eq!(format_number(1e300), "1e300");
+        assert_eq!(format_number(1.5e-7), "1.5e-7");
+        assert_eq!(format_number(f64::INFINITY), "null");
+    }
+
+    #[test]
+    fn test_escapes() {
+        let mut output = String::new();
+        write_string(&mut output, "a\"\\\n\u{1}é");
+        assert_eq!(output, r#""a\"\\\n\u0001é""#);
+    }
+
+    #[test]
+    fn test_pretty() {
+        let value = parse(r#"{"a":[1,{}],"b":[]}"#).unwrap();
+        assert_eq!(to_string_pretty(&value, 2), "{\n  \"a\": [\n    1,\n    {}\n  ],\n  \"b\": []\n}");
+    }
+}
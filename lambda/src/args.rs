@@ -0,0 +1,29 @@
+use clap::{Parser, ValueEnum};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// File with definitions and terms to evaluate. Starts the REPL on stdin if not specified
+    #[arg(short, long)]
+    pub input_file: Option<String>,
+
+    /// Reduction order
+    #[arg(short, long, value_enum, default_value_t = OrderArg::Normal)]
+    pub order: OrderArg,
+
+    /// Maximum number of beta reductions per term
+    #[arg(short, long, default_value_t = 1_000_000)]
+    pub max_steps: u64,
+
+    /// Start without the predefined combinators and Church encodings
+    #[arg(short, long)]
+    pub no_prelude: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrderArg {
+    /// Leftmost outermost redex first
+    Normal,
+    /// Leftmost innermost redex first
+    Applicative,
+}
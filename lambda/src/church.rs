@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use crate::debruijn::DeBruijn;
+use crate::{LambdaError, Term, parse};
+
+///
+/// Definitions available in the REPL by default.
+/// Description: https://en.wikipedia.org/wiki/Church_encoding
+///
+pub const PRELUDE: &[(&str, &str)] = &[
+    ("I", r"\x. x"),
+    ("K", r"\x y. x"),
+    ("S", r"\x y z. x z (y z)"),
+    ("TRUE", r"\t f. t"),
+    ("FALSE", r"\t f. f"),
+    ("AND", r"\p q. p q p"),
+    ("OR", r"\p q. p p q"),
+    ("NOT", r"\p. p FALSE TRUE"),
+    ("IF", r"\p a b. p a b"),
+    ("SUCC", r"\n f x. f (n f x)"),
+    ("PLUS", r"\m n f x. m f (n f x)"),
+    ("MULT", r"\m n f. m (n f)"),
+    ("POW", r"\b e. e b"),
+    ("PRED", r"\n f x. n (\g h. h (g f)) (\u. x) (\u. u)"),
+    ("SUB", r"\m n. n PRED m"),
+    ("ISZERO", r"\n. n (\x. FALSE) TRUE"),
+    ("LEQ", r"\m n. ISZERO (SUB m n)"),
+    ("PAIR", r"\x y f. f x y"),
+    ("FST", r"\p. p TRUE"),
+    ("SND", r"\p. p FALSE"),
+    ("Y", r"\f. (\x. f (x x)) (\x. f (x x))"),
+    ("Z", r"\f. (\x. f (\v. x x v)) (\x. f (\v. x x v))"),
+    ("FACT", r"Y (\r n. ISZERO n 1 (MULT n (r (PRED n))))"),
+    ("OMEGA", r"(\x. x x) (\x. x x)"),
+];
+
+///
+/// Returns the Church numeral for the number: λf.λx.f (f (... x)) with n applications of f.
+///
+pub fn numeral(number: u64) -> Term {
+    let body = (0..number).fold(Term::var("x"), |body, _| Term::app(Term::var("f"), body));
+    Term::abs("f", Term::abs("x", body))
+}
+
+///
+/// Returns the number if the term is a Church numeral in normal form.
+///
+pub fn to_number(term: &Term) -> Option<u64> {
+    let (DeBruijn::Abs(_, body), _) = DeBruijn::from_term(term) else { return None };
+    let DeBruijn::Abs(_, mut body) = *body else { return None };
+    let mut count = 0;
+    loop {
+        match *body {
+            DeBruijn::Var(0) => return Some(count),
+            DeBruijn::App(function, argument) if *function == DeBruijn::Var(1) => {
+                body = argument;
+                count += 1;
+            }
+            _ => return None,
+        }
+    }
+}
+
+///
+/// Returns the Church boolean: λt.λf.t for true and λt.λf.f for false.
+///
+pub fn boolean(value: bool) -> Term {
+    Term::abs("t", Term::abs("f", Term::var(if value { "t" } else { "f" })))
+}
+
+///
+/// Returns the value if the term is a Church boolean in normal form. Note that
+/// false is the same term as the numeral 0.
+///
+pub fn to_boolean(term: &Term) -> Option<bool> {
+    match DeBruijn::from_term(term).0 {
+        DeBruijn::Abs(_, body) => match *body {
+            DeBruijn::Abs(_, body) => match *body {
+                DeBruijn::Var(1) => Some(true),
+                DeBruijn::Var(0) => Some(false),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+///
+/// Environment struct with named definitions. Free variables of a term with a
+/// definition are replaced by the definition, and free variables made of digits
+/// by Church numerals.
+///
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    definitions: HashMap<String, Term>,
+}
+
+impl Environment {
+    ///
+    /// Creates an environment with the prelude definitions.
+    ///
+    pub fn with_prelude() -> Environment {
+        let mut environment = Environment::default();
+        for (name, text) in PRELUDE {
+            environment.define(name, &parse(text).expect("Prelude is valid"));
+        }
+        environment
+    }
+
+    ///
+    /// Adds or replaces a definition. The term is expanded first, so later
+    /// changes to other definitions do not change it.
+    ///
+    pub fn define(&mut self, name: &str, term: &Term) {
+        let expanded = self.expand(term);
+        self.definitions.insert(name.to_string(), expanded);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Term> {
+        self.definitions.get(name)
+    }
+
+    ///
+    /// Returns the names of the definitions in alphabetical order.
+    ///
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.definitions.keys().map(|name| name.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    ///
+    /// Replaces the free variables that have a definition or are numbers.
+    ///
+    pub fn expand(&self, term: &Term) -> Term {
+        let mut free: Vec<String> = term.free_variables().into_iter().collect();
+        free.sort();
+        free.iter().fold(term.clone(), |expanded, name| match self.definitions.get(name) {
+            Some(definition) => expanded.substitute(name, definition),
+            None => match name.parse::<u64>() {
+                Ok(number) => expanded.substitute(name, &numeral(number)),
+                Err(_) => expanded,
+            },
+        })
+    }
+
+    ///
+    /// Parses a line of the form `name = term` and adds the definition.
+    ///
+    /// result: The name if the line is a definition, None if it is not, or an error if the term is invalid.
+    ///
+    pub fn define_line(&mut self, line: &str) -> Result<Option<String>, LambdaError> {
+        let Some((name, text)) = line.split_once('=') else { return Ok(None) };
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|character| character.is_alphanumeric() || character == '_') {
+            return Ok(None);
+        }
+        let term = parse(text)?;
+        self.define(name, &term);
+        Ok(Some(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::{Order, normalize};
+
+    fn evaluate(environment: &Environment, text: &str) -> Term {
+        normalize(&environment.expand(&parse(text).unwrap()), Order::Normal, 1_000_000).unwrap().term
+    }
+
+    #[test]
+    fn test_numerals() {
+        assert_eq!(numeral(2).to_string(), "λf.λx.f (f x)");
+        assert_eq!(to_number(&numeral(7)), Some(7));
+        assert_eq!(to_number(&parse(r"\a b. a (a b)").unwrap()), Some(2));
+        assert_eq!(to_number(&parse(r"\f x. x f").unwrap()), None);
+    }
+
+    #[test]
+    fn test_booleans() {
+        assert_eq!(to_boolean(&boolean(true)), Some(true));
+        assert_eq!(to_boolean(&boolean(false)), Some(false));
+        assert_eq!(to_boolean(&numeral(1)), None);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let environment = Environment::with_prelude();
+        assert_eq!(to_number(&evaluate(&environment, "PLUS 2 3")), Some(5));
+        assert_eq!(to_number(&evaluate(&environment, "MULT 3 4")), Some(12));
+        assert_eq!(to_number(&evaluate(&environment, "POW 2 5")), Some(32));
+        assert_eq!(to_number(&evaluate(&environment, "PRED 5")), Some(4));
+        assert_eq!(to_number(&evaluate(&environment, "SUB 7 3")), Some(4));
+        assert_eq!(to_boolean(&evaluate(&environment, "ISZERO 0")), Some(true));
+        assert_eq!(to_boolean(&evaluate(&environment, "LEQ 4 3")), Some(false));
+    }
+
+    #[test]
+    fn test_factorial_with_y_combinator() {
+        let environment = Environment::with_prelude();
+        assert_eq!(to_number(&evaluate(&environment, "FACT 4")), Some(24));
+    }
+
+    #[test]
+    fn test_define_line() {
+        let mut environment = Environment::with_prelude();
+        assert_eq!(environment.define_line("DOUBLE = \\n. PLUS n n"), Ok(Some("DOUBLE".to_string())));
+        assert_eq!(environment.define_line("PLUS 1 2"), Ok(None));
+        assert_eq!(to_number(&evaluate(&environment, "DOUBLE 21")), Some(42));
+    }
+}
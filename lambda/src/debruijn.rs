@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::{Term, fresh_name};
+
+///
+/// DeBruijn enum for lambda terms using De Bruijn indices.
+/// Description: https://en.wikipedia.org/wiki/De_Bruijn_index
+///
+/// Index 0 refers to the nearest enclosing abstraction. Free variables have
+/// indices past all enclosing abstractions, and index depth + n refers to
+/// the n-th name in the list of free variables.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DeBruijn {
+    // A variable with its index.
+    Var(usize),
+    // An abstraction. The name is only kept to print the term with readable names.
+    Abs(String, Box<DeBruijn>),
+    // An application of a function to an argument.
+    App(Box<DeBruijn>, Box<DeBruijn>),
+}
+
+impl DeBruijn {
+    ///
+    /// Converts a named term.
+    ///
+    /// result: The term and the names of its free variables, in the order of their indices.
+    ///
+    pub fn from_term(term: &Term) -> (DeBruijn, Vec<String>) {
+        let mut free = Vec::new();
+        let converted = convert(term, &mut Vec::new(), &mut free);
+        (converted, free)
+    }
+
+    ///
+    /// Converts back to a named term. Bound variables get the names they had,
+    /// with primes added where a name would otherwise capture another variable.
+    ///
+    /// free: The names of the free variables.
+    ///
+    pub fn to_term(&self, free: &[String]) -> Term {
+        let mut used: HashSet<String> = free.iter().cloned().collect();
+        name(self, &mut Vec::new(), free, &mut used)
+    }
+
+    ///
+    /// Adds the amount to the indices of the variables at or above the cutoff,
+    /// which are the variables free in the term at this depth.
+    ///
+    pub fn shift(&self, amount: isize, cutoff: usize) -> DeBruijn {
+        match self {
+            DeBruijn::Var(index) if *index >= cutoff => DeBruijn::Var((*index as isize + amount) as usize),
+            DeBruijn::Var(_) => self.clone(),
+            DeBruijn::Abs(name, body) => DeBruijn::Abs(name.clone(), Box::new(body.shift(amount, cutoff + 1))),
+            DeBruijn::App(function, argument) => DeBruijn::App(Box::new(function.shift(amount, cutoff)), Box::new(argument.shift(amount, cutoff))),
+        }
+    }
+
+    ///
+    /// Replaces the variable with the index with the value. The value is
+    /// shifted when moved below abstractions.
+    ///
+    pub fn substitute(&self, index: usize, value: &DeBruijn) -> DeBruijn {
+        match self {
+            DeBruijn::Var(variable) if *variable == index => value.clone(),
+            DeBruijn::Var(_) => self.clone(),
+            DeBruijn::Abs(name, body) => DeBruijn::Abs(name.clone(), Box::new(body.substitute(index + 1, &value.shift(1, 0)))),
+            DeBruijn::App(function, argument) => DeBruijn::App(Box::new(function.substitute(index, value)), Box::new(argument.substitute(index, value))),
+        }
+    }
+
+    ///
+    /// Applies the abstraction body to the argument: (λ.body) argument.
+    ///
+    pub fn beta(body: &DeBruijn, argument: &DeBruijn) -> DeBruijn {
+        body.substitute(0, &argument.shift(1, 0)).shift(-1, 0)
+    }
+
+    ///
+    /// Returns the number of nodes in the term.
+    ///
+    pub fn size(&self) -> usize {
+        match self {
+            DeBruijn::Var(_) => 1,
+            DeBruijn::Abs(_, body) => 1 + body.size(),
+            DeBruijn::App(function, argument) => 1 + function.size() + argument.size(),
+        }
+    }
+}
+
+///
+/// Converts the named term. bound holds the names of the enclosing abstractions, innermost last.
+///
+fn convert(term: &Term, bound: &mut Vec<String>, free: &mut Vec<String>) -> DeBruijn {
+    match term {
+        Term::Var(name) => match bound.iter().rev().position(|bound_name| bound_name == name) {
+            Some(index) => DeBruijn::Var(index),
+            None => {
+                let position = free.iter().position(|free_name| free_name == name).unwrap_or_else(|| {
+                    free.push(name.clone());
+                    free.len() - 1
+                });
+                DeBruijn::Var(bound.len() + position)
+            }
+        },
+        Term::Abs(name, body) => {
+            bound.push(name.clone());
+            let body = convert(body, bound, free);
+            bound.pop();
+            DeBruijn::Abs(name.clone(), Box::new(body))
+        }
+        Term::App(function, argument) => DeBruijn::App(Box::new(convert(function, bound, free)), Box::new(convert(argument, bound, free))),
+    }
+}
+
+///
+/// Converts back to names. bound holds the names chosen for the enclosing abstractions, innermost last.
+///
+fn name(term: &DeBruijn, bound: &mut Vec<String>, free: &[String], used: &mut HashSet<String>) -> Term {
+    match term {
+        DeBruijn::Var(index) if *index < bound.len() => Term::Var(bound[bound.len() - 1 - index].clone()),
+        DeBruijn::Var(index) => Term::Var(free.get(index - bound.len()).cloned().unwrap_or_else(|| format!("#{}", index - bound.len()))),
+        DeBruijn::Abs(hint, body) => {
+            let fresh = fresh_name(hint, used);
+            used.insert(fresh.clone());
+            bound.push(fresh.clone());
+            let body = name(body, bound, free, used);
+            bound.pop();
+            used.remove(&fresh);
+            Term::Abs(fresh, Box::new(body))
+        }
+        DeBruijn::App(function, argument) => Term::app(name(function, bound, free, used), name(argument, bound, free, used)),
+    }
+}
+
+impl fmt::Display for DeBruijn {
+    ///
+    /// Writes the term with indices, for example λ λ 1 (1 0).
+    ///
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeBruijn::Var(index) => write!(f, "{index}"),
+            DeBruijn::Abs(_, body) => write!(f, "λ {body}"),
+            DeBruijn::App(function, argument) => {
+                match function.as_ref() {
+                    DeBruijn::Abs(..) => write!(f, "({function})")?,
+                    _ => write!(f, "{function}")?,
+                }
+                match argument.as_ref() {
+                    DeBruijn::Var(index) => write!(f, " {index}"),
+                    _ => write!(f, " ({argument})"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_from_term() {
+        let (term, free) = DeBruijn::from_term(&parse(r"\f x. f (f x)").unwrap());
+        assert_eq!(term.to_string(), "λ λ 1 (1 0)");
+        assert!(free.is_empty());
+    }
+
+    #[test]
+    fn test_free_variables() {
+        let (term, free) = DeBruijn::from_term(&parse(r"\x. y x z y").unwrap());
+        assert_eq!(term.to_string(), "λ 1 0 2 1");
+        assert_eq!(free, vec!["y".to_string(), "z".to_string()]);
+        assert_eq!(term.to_term(&free).to_string(), "λx.y x z y");
+    }
+
+    #[test]
+    fn test_alpha_equivalent_terms_are_equal() {
+        let (first, _) = DeBruijn::from_term(&parse(r"\x y. x").unwrap());
+        let (second, _) = DeBruijn::from_term(&parse(r"\a b. a").unwrap());
+        // The names are only hints, so compare the printed indices.
+        assert_eq!(first.to_string(), second.to_string());
+    }
+
+    #[test]
+    fn test_beta_avoids_capture() {
+        // (λx.λy.x) y reduces to λy'.y with the free y kept.
+        let (term, free) = DeBruijn::from_term(&parse(r"(\x y. x) y").unwrap());
+        let DeBruijn::App(function, argument) = term else { panic!("Expected application") };
+        let DeBruijn::Abs(_, body) = *function else { panic!("Expected abstraction") };
+        let result = DeBruijn::beta(&body, &argument);
+        assert_eq!(result.to_term(&free).to_string(), "λy'.y");
+    }
+
+    #[test]
+    fn test_size() {
+        let (term, _) = DeBruijn::from_term(&parse(r"\x. x x").unwrap());
+        assert_eq!(term.size(), 4);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::debruijn::DeBruijn;
+use crate::{LambdaError, Term};
+
+///
+/// Order enum for the reduction strategies.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    // Reduce the leftmost outermost redex first. Finds the normal form whenever one exists.
+    Normal,
+    // Reduce the leftmost innermost redex first, so arguments are reduced before they are
+    // passed. Like call by value, and may loop forever where normal order does not.
+    Applicative,
+}
+
+///
+/// Evaluation struct with the result of a reduction.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evaluation {
+    // The normal form.
+    pub term: Term,
+    // Number of beta reductions made.
+    pub steps: u64,
+}
+
+///
+/// Reduces the term to its normal form.
+///
+/// term: The term to reduce.
+/// order: The reduction strategy.
+/// max_steps: Maximum number of beta reductions.
+///
+/// result: The normal form or an error if it was not reached within the step limit.
+///
+pub fn normalize(term: &Term, order: Order, max_steps: u64) -> Result<Evaluation, LambdaError> {
+    let (mut current, free) = DeBruijn::from_term(term);
+    let mut steps = 0;
+    loop {
+        let next = match order {
+            Order::Normal => step_normal(&current),
+            Order::Applicative => step_applicative(&current),
+        };
+        match next {
+            Some(_) if steps == max_steps => return Err(LambdaError::StepLimit { steps }),
+            Some(next) => {
+                current = next;
+                steps += 1;
+            }
+            None => return Ok(Evaluation { term: current.to_term(&free), steps }),
+        }
+    }
+}
+
+///
+/// Makes one normal order reduction step.
+///
+/// result: The reduced term or None if the term is in normal form.
+///
+pub fn step_normal(term: &DeBruijn) -> Option<DeBruijn> {
+    match term {
+        DeBruijn::Var(_) => None,
+        DeBruijn::Abs(name, body) => step_normal(body).map(|body| DeBruijn::Abs(name.clone(), Box::new(body))),
+        DeBruijn::App(function, argument) => {
+            if let DeBruijn::Abs(_, body) = function.as_ref() {
+                return Some(DeBruijn::beta(body, argument));
+            }
+            if let Some(function) = step_normal(function) {
+                return Some(DeBruijn::App(Box::new(function), argument.clone()));
+            }
+            step_normal(argument).map(|argument| DeBruijn::App(function.clone(), Box::new(argument)))
+        }
+    }
+}
+
+///
+/// Makes one applicative order reduction step.
+///
+/// result: The reduced term or None if the term is in normal form.
+///
+pub fn step_applicative(term: &DeBruijn) -> Option<DeBruijn> {
+    match term {
+        DeBruijn::Var(_) => None,
+        DeBruijn::Abs(name, body) => step_applicative(body).map(|body| DeBruijn::Abs(name.clone(), Box::new(body))),
+        DeBruijn::App(function, argument) => {
+            if let Some(function) = step_applicative(function) {
+                return Some(DeBruijn::App(Box::new(function), argument.clone()));
+            }
+            if let Some(argument) = step_applicative(argument) {
+                return Some(DeBruijn::App(function.clone(), Box::new(argument)));
+            }
+            match function.as_ref() {
+                DeBruijn::Abs(_, body) => Some(DeBruijn::beta(body, argument)),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn evaluate(text: &str, order: Order) -> Result<Evaluation, LambdaError> {
+        normalize(&parse(text).unwrap(), order, 10_000)
+    }
+
+    #[test]
+    fn test_identity() {
+        let result = evaluate(r"(\x. x) (\y. y)", Order::Normal).unwrap();
+        assert_eq!(result.term.to_string(), "λy.y");
+        assert_eq!(result.steps, 1);
+    }
+
+    #[test]
+    fn test_reduces_under_abstraction() {
+        let result = evaluate(r"\z. (\x. x) z", Order::Applicative).unwrap();
+        assert_eq!(result.term.to_string(), "λz.z");
+    }
+
+    #[test]
+    fn test_normal_order_skips_divergent_argument() {
+        let term = r"(\x y. y) ((\x. x x) (\x. x x))";
+        assert_eq!(evaluate(term, Order::Normal).unwrap().term.to_string(), "λy.y");
+        assert_eq!(evaluate(term, Order::Applicative), Err(LambdaError::StepLimit { steps: 10_000 }));
+    }
+
+    #[test]
+    fn test_orders_count_steps_differently() {
+        // The argument is reduced once in applicative order but twice in normal order.
+        let term = r"(\x. x x) ((\y. y) (\z. z))";
+        assert_eq!(evaluate(term, Order::Applicative).unwrap().steps, 3);
+        assert_eq!(evaluate(term, Order::Normal).unwrap().steps, 4);
+    }
+
+    #[test]
+    fn test_free_variables_are_kept() {
+        let result = evaluate(r"(\x y. x y) y", Order::Normal).unwrap();
+        assert_eq!(result.term.to_string(), "λy'.y y'");
+    }
+}
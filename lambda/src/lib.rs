@@ -0,0 +1,184 @@
+///
+/// Untyped lambda calculus.
+/// Description: https://en.wikipedia.org/wiki/Lambda_calculus
+///
+/// Terms are parsed with named variables and converted to De Bruijn indices
+/// for reduction, where variables refer to their binder by the number of
+/// binders in between. This makes substitution free of variable capture.
+/// Terms are reduced in normal order (leftmost outermost) or applicative
+/// order (leftmost innermost).
+///
+pub mod church;
+pub mod debruijn;
+pub mod eval;
+mod parser;
+
+use std::collections::HashSet;
+use std::fmt;
+
+pub use parser::parse;
+
+///
+/// LambdaError enum to represent errors when parsing and evaluating terms.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LambdaError {
+    // A character that is not part of the syntax. Position is the character index.
+    InvalidCharacter { character: char, position: usize },
+    // A token in a place where it is not allowed.
+    UnexpectedToken { token: String, position: usize },
+    // The input ended in the middle of a term.
+    UnexpectedEnd,
+    // The term did not reach a normal form within the step limit.
+    StepLimit { steps: u64 },
+}
+
+impl fmt::Display for LambdaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LambdaError::InvalidCharacter { character, position } => write!(f, "Invalid character {character:?} at position {position}"),
+            LambdaError::UnexpectedToken { token, position } => write!(f, "Unexpected {token:?} at position {position}"),
+            LambdaError::UnexpectedEnd => write!(f, "Unexpected end of input"),
+            LambdaError::StepLimit { steps } => write!(f, "No normal form found within {steps} steps"),
+        }
+    }
+}
+
+impl std::error::Error for LambdaError {}
+
+///
+/// Term enum for lambda terms with named variables.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Term {
+    // A variable.
+    Var(String),
+    // An abstraction with the bound variable and the body.
+    Abs(String, Box<Term>),
+    // An application of a function to an argument.
+    App(Box<Term>, Box<Term>),
+}
+
+impl Term {
+    pub fn var(name: &str) -> Term {
+        Term::Var(name.to_string())
+    }
+
+    pub fn abs(name: &str, body: Term) -> Term {
+        Term::Abs(name.to_string(), Box::new(body))
+    }
+
+    pub fn app(function: Term, argument: Term) -> Term {
+        Term::App(Box::new(function), Box::new(argument))
+    }
+
+    ///
+    /// Returns the variables that are not bound by an abstraction in the term.
+    ///
+    pub fn free_variables(&self) -> HashSet<String> {
+        match self {
+            Term::Var(name) => HashSet::from([name.clone()]),
+            Term::Abs(name, body) => {
+                let mut free = body.free_variables();
+                free.remove(name);
+                free
+            }
+            Term::App(function, argument) => {
+                let mut free = function.free_variables();
+                free.extend(argument.free_variables());
+                free
+            }
+        }
+    }
+
+    ///
+    /// Replaces the free occurrences of the variable with the value. Bound
+    /// variables are renamed when needed so free variables in the value are
+    /// not captured.
+    ///
+    pub fn substitute(&self, name: &str, value: &Term) -> Term {
+        match self {
+            Term::Var(variable) if variable == name => value.clone(),
+            Term::Var(_) => self.clone(),
+            Term::Abs(variable, _) if variable == name => self.clone(),
+            Term::Abs(variable, body) => {
+                let value_free = value.free_variables();
+                if value_free.contains(variable) && body.free_variables().contains(name) {
+                    let mut used = value_free;
+                    used.extend(body.free_variables());
+                    let fresh = fresh_name(variable, &used);
+                    let renamed = body.substitute(variable, &Term::Var(fresh.clone()));
+                    Term::Abs(fresh, Box::new(renamed.substitute(name, value)))
+                } else {
+                    Term::Abs(variable.clone(), Box::new(body.substitute(name, value)))
+                }
+            }
+            Term::App(function, argument) => Term::app(function.substitute(name, value), argument.substitute(name, value)),
+        }
+    }
+}
+
+///
+/// Returns the name with primes added until it is not in the used names.
+///
+pub fn fresh_name(name: &str, used: &HashSet<String>) -> String {
+    let mut fresh = name.to_string();
+    while used.contains(&fresh) {
+        fresh.push('\'');
+    }
+    fresh
+}
+
+impl fmt::Display for Term {
+    ///
+    /// Writes the term with as few parentheses as possible. Application is left
+    /// associative and the body of an abstraction extends as far right as possible.
+    ///
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Term::Var(name) => write!(f, "{name}"),
+            Term::Abs(name, body) => write!(f, "λ{name}.{body}"),
+            Term::App(function, argument) => {
+                match function.as_ref() {
+                    Term::Abs(..) => write!(f, "({function})")?,
+                    _ => write!(f, "{function}")?,
+                }
+                match argument.as_ref() {
+                    Term::Var(name) => write!(f, " {name}"),
+                    _ => write!(f, " ({argument})"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let term = Term::app(Term::app(Term::abs("x", Term::var("x")), Term::var("y")), Term::app(Term::var("a"), Term::var("b")));
+        assert_eq!(term.to_string(), "(λx.x) y (a b)");
+        assert_eq!(parse(&term.to_string()).unwrap(), term);
+    }
+
+    #[test]
+    fn test_free_variables() {
+        let term = parse(r"\x. x y (\y. y z)").unwrap();
+        assert_eq!(term.free_variables(), HashSet::from(["y".to_string(), "z".to_string()]));
+    }
+
+    #[test]
+    fn test_substitute_avoids_capture() {
+        let term = parse(r"\y. x y").unwrap();
+        let result = term.substitute("x", &Term::var("y"));
+        assert_eq!(result.to_string(), "λy'.y y'");
+    }
+
+    #[test]
+    fn test_substitute_stops_at_binder() {
+        let term = parse(r"x (\x. x)").unwrap();
+        assert_eq!(term.substitute("x", &Term::var("z")).to_string(), "z (λx.x)");
+    }
+}
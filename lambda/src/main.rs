@@ -0,0 +1,145 @@
+mod args;
+
+use std::fs;
+use std::io::{BufRead, Write};
+
+use args::{Args, OrderArg};
+use clap::Parser;
+use lambda::church::{Environment, to_boolean, to_number};
+use lambda::debruijn::DeBruijn;
+use lambda::eval::{Order, normalize};
+use lambda::parse;
+
+/**
+ * Lambda calculus evaluator.
+ * Reads definitions and terms from a file or an interactive REPL and prints
+ * the normal form of each term.
+ */
+fn main() {
+    let args = Args::parse();
+    let mut repl = Repl {
+        environment: if args.no_prelude { Environment::default() } else { Environment::with_prelude() },
+        order: match args.order {
+            OrderArg::Normal => Order::Normal,
+            OrderArg::Applicative => Order::Applicative,
+        },
+        max_steps: args.max_steps,
+    };
+    match args.input_file {
+        Some(path) => match fs::read_to_string(&path) {
+            Ok(text) => text.lines().for_each(|line| repl.handle(line)),
+            Err(err) => eprintln!("Failed to read {path}: {err}"),
+        },
+        None => {
+            println!("Lambda calculus REPL. Type :help for commands.");
+            let mut lines = std::io::stdin().lock().lines();
+            loop {
+                print!("> ");
+                let _ = std::io::stdout().flush();
+                match lines.next() {
+                    Some(Ok(line)) if line.trim() == ":quit" => break,
+                    Some(Ok(line)) => repl.handle(&line),
+                    _ => break,
+                }
+            }
+        }
+    }
+}
+
+/**
+ * Repl struct with the state kept between lines.
+ */
+struct Repl {
+    environment: Environment,
+    order: Order,
+    max_steps: u64,
+}
+
+impl Repl {
+    /**
+     * Handles a line with a command, a definition or a term. Empty lines and
+     * lines starting with # are ignored.
+     */
+    fn handle(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+        if let Some(command) = line.strip_prefix(':') {
+            self.command(command);
+            return;
+        }
+        match self.environment.define_line(line) {
+            Ok(Some(name)) => println!("{name} defined"),
+            Ok(None) => self.evaluate(line),
+            Err(err) => eprintln!("{err}"),
+        }
+    }
+
+    /**
+     * Parses and reduces the term, and prints the normal form with the number
+     * or boolean it encodes.
+     */
+    fn evaluate(&self, text: &str) {
+        let term = match parse(text) {
+            Ok(term) => self.environment.expand(&term),
+            Err(err) => {
+                eprintln!("{err}");
+                return;
+            }
+        };
+        match normalize(&term, self.order, self.max_steps) {
+            Ok(evaluation) => {
+                let mut meaning = Vec::new();
+                if let Some(number) = to_number(&evaluation.term) {
+                    meaning.push(format!("number {number}"));
+                }
+                if let Some(value) = to_boolean(&evaluation.term) {
+                    meaning.push(format!("boolean {value}"));
+                }
+                let meaning = if meaning.is_empty() { String::new() } else { format!(", {}", meaning.join(", ")) };
+                println!("{} ({} steps{meaning})", evaluation.term, evaluation.steps);
+            }
+            Err(err) => eprintln!("{err}"),
+        }
+    }
+
+    /**
+     * Runs a REPL command.
+     */
+    fn command(&mut self, command: &str) {
+        let (name, argument) = command.split_once(' ').map_or((command, ""), |(name, argument)| (name, argument.trim()));
+        match name {
+            "order" => match argument {
+                "normal" => self.order = Order::Normal,
+                "applicative" => self.order = Order::Applicative,
+                _ => println!("Order: {:?}", self.order),
+            },
+            "steps" => match argument.parse() {
+                Ok(max_steps) => self.max_steps = max_steps,
+                Err(_) => println!("Maximum steps: {}", self.max_steps),
+            },
+            "debruijn" => match parse(argument) {
+                Ok(term) => {
+                    let (converted, free) = DeBruijn::from_term(&self.environment.expand(&term));
+                    if free.is_empty() {
+                        println!("{converted}");
+                    } else {
+                        println!("{converted} (free: {})", free.join(" "));
+                    }
+                }
+                Err(err) => eprintln!("{err}"),
+            },
+            "defs" => self.environment.names().iter().for_each(|name| println!("{name} = {}", self.environment.get(name).expect("Name is defined"))),
+            _ => {
+                println!("name = term          Define a name");
+                println!("term                 Reduce the term to normal form");
+                println!(":order [normal|applicative]  Show or set the reduction order");
+                println!(":steps [count]       Show or set the maximum number of reductions");
+                println!(":debruijn term       Show the term with De Bruijn indices");
+                println!(":defs                Show the definitions");
+                println!(":quit                Exit");
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{LambdaError, Term};
+
+///
+/// Token enum for the lexical elements of a term.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Lambda,
+    Dot,
+    Open,
+    Close,
+    Name(String),
+}
+
+impl Token {
+    fn text(&self) -> String {
+        match self {
+            Token::Lambda => "λ".to_string(),
+            Token::Dot => ".".to_string(),
+            Token::Open => "(".to_string(),
+            Token::Close => ")".to_string(),
+            Token::Name(name) => name.clone(),
+        }
+    }
+}
+
+///
+/// Parses a term.
+///
+/// Abstractions are written \x. body or λx. body, and \x y. body is short for
+/// \x. \y. body. Application is written by juxtaposition and is left associative.
+/// Names consist of letters, digits, _ and '.
+///
+/// result: The term or an error with the position of the problem.
+///
+pub fn parse(text: &str) -> Result<Term, LambdaError> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser { tokens, index: 0 };
+    let term = parser.term()?;
+    match parser.tokens.get(parser.index) {
+        Some((token, position)) => Err(LambdaError::UnexpectedToken { token: token.text(), position: *position }),
+        None => Ok(term),
+    }
+}
+
+///
+/// Splits the text into tokens with their character positions.
+///
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, LambdaError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().enumerate().peekable();
+    while let Some((position, character)) = chars.next() {
+        let token = match character {
+            '\\' | 'λ' => Token::Lambda,
+            '.' => Token::Dot,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            character if character.is_whitespace() => continue,
+            character if is_name_character(character) => {
+                let mut name = character.to_string();
+                while let Some(&(_, next)) = chars.peek().filter(|&&(_, next)| is_name_character(next)) {
+                    name.push(next);
+                    chars.next();
+                }
+                Token::Name(name)
+            }
+            character => return Err(LambdaError::InvalidCharacter { character, position }),
+        };
+        tokens.push((token, position));
+    }
+    Ok(tokens)
+}
+
+fn is_name_character(character: char) -> bool {
+    character.is_alphanumeric() && character != 'λ' || character == '_' || character == '\''
+}
+
+///
+/// Parser struct for a recursive descent parser over the tokens.
+///
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(token, _)| token)
+    }
+
+    ///
+    /// Consumes the next token, which must be the expected token.
+    ///
+    fn expect(&mut self, expected: Token) -> Result<(), LambdaError> {
+        match self.tokens.get(self.index) {
+            Some((token, _)) if *token == expected => {
+                self.index += 1;
+                Ok(())
+            }
+            Some((token, position)) => Err(LambdaError::UnexpectedToken { token: token.text(), position: *position }),
+            None => Err(LambdaError::UnexpectedEnd),
+        }
+    }
+
+    ///
+    /// term := abstraction | application
+    ///
+    fn term(&mut self) -> Result<Term, LambdaError> {
+        if self.peek() == Some(&Token::Lambda) { self.abstraction() } else { self.application() }
+    }
+
+    ///
+    /// abstraction := λ name+ . term
+    ///
+    fn abstraction(&mut self) -> Result<Term, LambdaError> {
+        self.expect(Token::Lambda)?;
+        let mut names = Vec::new();
+        while let Some(Token::Name(name)) = self.peek() {
+            names.push(name.clone());
+            self.index += 1;
+        }
+        if names.is_empty() {
+            return match self.tokens.get(self.index) {
+                Some((token, position)) => Err(LambdaError::UnexpectedToken { token: token.text(), position: *position }),
+                None => Err(LambdaError::UnexpectedEnd),
+            };
+        }
+        self.expect(Token::Dot)?;
+        let body = self.term()?;
+        Ok(names.iter().rev().fold(body, |body, name| Term::abs(name, body)))
+    }
+
+    ///
+    /// application := atom+ abstraction?
+    ///
+    fn application(&mut self) -> Result<Term, LambdaError> {
+        let mut term = self.atom()?;
+        loop {
+            match self.peek() {
+                Some(Token::Name(_)) | Some(Token::Open) => term = Term::app(term, self.atom()?),
+                Some(Token::Lambda) => return Ok(Term::app(term, self.abstraction()?)),
+                _ => return Ok(term),
+            }
+        }
+    }
+
+    ///
+    /// atom := name | ( term )
+    ///
+    fn atom(&mut self) -> Result<Term, LambdaError> {
+        match self.tokens.get(self.index).cloned() {
+            Some((Token::Name(name), _)) => {
+                self.index += 1;
+                Ok(Term::Var(name))
+            }
+            Some((Token::Open, _)) => {
+                self.index += 1;
+                let term = self.term()?;
+                self.expect(Token::Close)?;
+                Ok(term)
+            }
+            Some((token, position)) => Err(LambdaError::UnexpectedToken { token: token.text(), position }),
+            None => Err(LambdaError::UnexpectedEnd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_abstraction() {
+        assert_eq!(parse(r"\x y. x").unwrap(), Term::abs("x", Term::abs("y", Term::var("x"))));
+        assert_eq!(parse("λx.x").unwrap(), Term::abs("x", Term::var("x")));
+    }
+
+    #[test]
+    fn test_application_is_left_associative() {
+        assert_eq!(parse("a b c").unwrap(), Term::app(Term::app(Term::var("a"), Term::var("b")), Term::var("c")));
+        assert_eq!(parse("a (b c)").unwrap(), Term::app(Term::var("a"), Term::app(Term::var("b"), Term::var("c"))));
+    }
+
+    #[test]
+    fn test_trailing_abstraction() {
+        assert_eq!(parse(r"f \x. x y").unwrap(), Term::app(Term::var("f"), Term::abs("x", Term::app(Term::var("x"), Term::var("y")))));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse(r"\x x"), Err(LambdaError::UnexpectedEnd));
+        assert_eq!(parse("(a b"), Err(LambdaError::UnexpectedEnd));
+        assert_eq!(parse("a b)"), Err(LambdaError::UnexpectedToken { token: ")".to_string(), position: 3 }));
+        assert_eq!(parse(r"\. x"), Err(LambdaError::UnexpectedToken { token: ".".to_string(), position: 1 }));
+        assert_eq!(parse("a + b"), Err(LambdaError::InvalidCharacter { character: '+', position: 2 }));
+    }
+}
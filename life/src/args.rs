@@ -0,0 +1,61 @@
+use clap::{Parser, ValueEnum};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Pattern file in RLE format. Reads from stdin if not specified
+    #[arg(short, long)]
+    pub input_file: Option<String>,
+
+    /// Algorithm used to compute the generations
+    #[arg(short, long, value_enum, default_value_t = Algorithm::Hashlife)]
+    pub algorithm: Algorithm,
+
+    /// Number of generations to compute
+    #[arg(short, long, default_value_t = 0)]
+    pub generations: u64,
+
+    /// Width of the shown area in cells. Also the size of the grid for the grid algorithm
+    #[arg(long, default_value_t = 80)]
+    pub width: usize,
+
+    /// Height of the shown area in cells. Also the size of the grid for the grid algorithm
+    #[arg(long, default_value_t = 48)]
+    pub height: usize,
+
+    /// Join the edges of the grid. Only used by the grid algorithm
+    #[arg(short, long)]
+    pub wrap: bool,
+
+    /// Print every generation to the terminal instead of only the last one
+    #[arg(long)]
+    pub animate: bool,
+
+    /// Number of generations between the frames of the animation
+    #[arg(long, default_value_t = 1)]
+    pub step: u64,
+
+    /// Delay between the frames of the animation in milliseconds
+    #[arg(short, long, default_value_t = 100)]
+    pub delay_ms: u64,
+
+    /// Write the last generation to an image file. PNG, or PPM/PGM by extension
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// Size of a cell in pixels in the image
+    #[arg(short, long, default_value_t = 4)]
+    pub scale: usize,
+
+    /// Print the last generation as RLE instead of drawing it
+    #[arg(short, long)]
+    pub rle: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Compute one generation at a time on a fixed size grid
+    Grid,
+    /// Quadtree with memoization, can skip ahead exponentially
+    Hashlife,
+}
@@ -0,0 +1,199 @@
+use crate::{Pattern, Rule, Universe};
+
+///
+/// Grid struct computing one generation at a time on a fixed size grid.
+///
+/// The grid covers the cells from (left, top) to (left + width - 1, top + height - 1).
+/// Cells outside are always dead, unless wrap is set, in which case the edges
+/// are joined and the grid becomes a torus.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    left: i64,
+    top: i64,
+    wrap: bool,
+    rule: Rule,
+    cells: Vec<bool>,
+    // Buffer for the next generation, kept to avoid an allocation per step.
+    next: Vec<bool>,
+    generation: u64,
+}
+
+impl Grid {
+    ///
+    /// Creates an empty grid.
+    ///
+    /// left: x coordinate of the leftmost column.
+    /// top: y coordinate of the top row.
+    /// wrap: Join the edges of the grid.
+    ///
+    pub fn new(width: usize, height: usize, left: i64, top: i64, wrap: bool, rule: Rule) -> Grid {
+        Grid { width, height, left, top, wrap, rule, cells: vec![false; width * height], next: vec![false; width * height], generation: 0 }
+    }
+
+    ///
+    /// Creates a grid with the pattern in the center. Cells of the pattern
+    /// outside the grid are dropped.
+    ///
+    pub fn from_pattern(pattern: &Pattern, width: usize, height: usize, wrap: bool) -> Grid {
+        let (left, top) = match pattern.bounding_box() {
+            Some((min_x, min_y, max_x, max_y)) => ((min_x + max_x) / 2 - width as i64 / 2, (min_y + max_y) / 2 - height as i64 / 2),
+            None => (0, 0),
+        };
+        let mut grid = Grid::new(width, height, left, top, wrap, pattern.rule);
+        pattern.cells.iter().for_each(|&(x, y)| grid.set(x, y, true));
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    ///
+    /// Returns the coordinates of the top left cell.
+    ///
+    pub fn origin(&self) -> (i64, i64) {
+        (self.left, self.top)
+    }
+
+    ///
+    /// Sets the state of a cell. Cells outside the grid are ignored.
+    ///
+    pub fn set(&mut self, x: i64, y: i64, alive: bool) {
+        if let Some(index) = self.index(x, y) {
+            self.cells[index] = alive;
+        }
+    }
+
+    ///
+    /// Returns the index of the cell or None if it is outside the grid.
+    ///
+    fn index(&self, x: i64, y: i64) -> Option<usize> {
+        let column = usize::try_from(x - self.left).ok().filter(|&column| column < self.width)?;
+        let row = usize::try_from(y - self.top).ok().filter(|&row| row < self.height)?;
+        Some(row * self.width + column)
+    }
+
+    ///
+    /// Counts the live neighbours of the cell at the column and row.
+    ///
+    fn neighbours(&self, column: usize, row: usize) -> u32 {
+        let mut count = 0;
+        for row_offset in -1..=1isize {
+            for column_offset in -1..=1isize {
+                if row_offset == 0 && column_offset == 0 {
+                    continue;
+                }
+                let (mut neighbour_column, mut neighbour_row) = (column as isize + column_offset, row as isize + row_offset);
+                if self.wrap {
+                    neighbour_column = neighbour_column.rem_euclid(self.width as isize);
+                    neighbour_row = neighbour_row.rem_euclid(self.height as isize);
+                } else if !(0..self.width as isize).contains(&neighbour_column) || !(0..self.height as isize).contains(&neighbour_row) {
+                    continue;
+                }
+                count += self.cells[neighbour_row as usize * self.width + neighbour_column as usize] as u32;
+            }
+        }
+        count
+    }
+
+    ///
+    /// Computes the next generation.
+    ///
+    fn step_once(&mut self) {
+        for row in 0..self.height {
+            for column in 0..self.width {
+                let index = row * self.width + column;
+                self.next[index] = self.rule.next_state(self.cells[index], self.neighbours(column, row));
+            }
+        }
+        std::mem::swap(&mut self.cells, &mut self.next);
+        self.generation += 1;
+    }
+}
+
+impl Universe for Grid {
+    fn step(&mut self, generations: u64) {
+        for _ in 0..generations {
+            self.step_once();
+        }
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn population(&self) -> u64 {
+        self.cells.iter().filter(|&&alive| alive).count() as u64
+    }
+
+    fn is_alive(&self, x: i64, y: i64) -> bool {
+        self.index(x, y).is_some_and(|index| self.cells[index])
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        self.cells.iter().enumerate().filter(|&(_, &alive)| alive).map(|(index, _)| (self.left + (index % self.width) as i64, self.top + (index / self.width) as i64)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(cells: &[(i64, i64)]) -> Pattern {
+        Pattern { cells: cells.to_vec(), rule: Rule::CONWAY }
+    }
+
+    #[test]
+    fn test_blinker() {
+        let mut grid = Grid::from_pattern(&pattern(&[(0, 1), (1, 1), (2, 1)]), 5, 5, false);
+        grid.step(1);
+        let mut cells = grid.live_cells();
+        cells.sort();
+        assert_eq!(cells, vec![(1, 0), (1, 1), (1, 2)]);
+        grid.step(1);
+        assert!(grid.is_alive(0, 1) && grid.is_alive(2, 1));
+        assert_eq!(grid.generation(), 2);
+    }
+
+    #[test]
+    fn test_glider_moves_diagonally() {
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let mut grid = Grid::from_pattern(&pattern(&glider), 20, 20, false);
+        grid.step(4);
+        let mut cells = grid.live_cells();
+        cells.sort();
+        let mut expected: Vec<(i64, i64)> = glider.iter().map(|&(x, y)| (x + 1, y + 1)).collect();
+        expected.sort();
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn test_bounded_edges_kill_glider() {
+        let mut grid = Grid::new(4, 4, 0, 0, false, Rule::CONWAY);
+        [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].iter().for_each(|&(x, y)| grid.set(x, y, true));
+        grid.step(20);
+        // The glider turns into a block in the corner.
+        assert_eq!(grid.population(), 4);
+    }
+
+    #[test]
+    fn test_wrapping_glider_returns() {
+        let mut grid = Grid::new(8, 8, 0, 0, true, Rule::CONWAY);
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        glider.iter().for_each(|&(x, y)| grid.set(x, y, true));
+        // The glider moves one cell diagonally every 4 generations.
+        grid.step(32);
+        let mut cells = grid.live_cells();
+        cells.sort();
+        let mut expected = glider.to_vec();
+        expected.sort();
+        assert_eq!(cells, expected);
+    }
+}
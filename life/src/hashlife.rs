@@ -0,0 +1,422 @@
+use std::collections::HashMap;
+
+use crate::{Pattern, Rule, Universe};
+
+///
+/// Id of the dead and the live leaf.
+///
+const DEAD: u32 = 0;
+const ALIVE: u32 = 1;
+
+///
+/// Smallest level of the root. A level 3 node covers 8x8 cells.
+///
+const MIN_LEVEL: u8 = 3;
+
+///
+/// Node struct for a square of 2^level x 2^level cells.
+/// Leaves have level 0 and are either the dead or the live cell.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Node {
+    level: u8,
+    // Quadrants in the order north west, north east, south west, south east.
+    children: [u32; 4],
+    population: u64,
+}
+
+///
+/// Hashlife struct storing the universe as a quadtree.
+///
+/// Equal subtrees are only stored once, so a node is identified by its id.
+/// For a node of level k the successor function returns the center node of
+/// level k - 1 advanced up to 2^(k-2) generations. The results are memoized,
+/// so repeated structure in space and time is only computed once.
+///
+/// The root covers the cells from -2^(level-1) to 2^(level-1) - 1 on both axes
+/// and is expanded when the pattern grows.
+///
+#[derive(Debug, Clone)]
+pub struct Hashlife {
+    rule: Rule,
+    nodes: Vec<Node>,
+    // Canonical id of the node with the children.
+    intern: HashMap<[u32; 4], u32>,
+    // Empty node for each level.
+    empty: Vec<u32>,
+    // Memoized successors keyed by node and log2 of the number of generations.
+    successors: HashMap<(u32, u8), u32>,
+    root: u32,
+    generation: u64,
+}
+
+impl Hashlife {
+    ///
+    /// Creates an empty universe.
+    ///
+    pub fn new(rule: Rule) -> Hashlife {
+        let leaves = vec![Node { level: 0, children: [DEAD; 4], population: 0 }, Node { level: 0, children: [DEAD; 4], population: 1 }];
+        let mut hashlife = Hashlife { rule, nodes: leaves, intern: HashMap::new(), empty: vec![DEAD], successors: HashMap::new(), root: DEAD, generation: 0 };
+        hashlife.root = hashlife.empty_node(MIN_LEVEL);
+        hashlife
+    }
+
+    ///
+    /// Creates a universe with the live cells of the pattern.
+    ///
+    pub fn from_pattern(pattern: &Pattern) -> Hashlife {
+        let mut hashlife = Hashlife::new(pattern.rule);
+        pattern.cells.iter().for_each(|&(x, y)| hashlife.set(x, y, true));
+        hashlife
+    }
+
+    ///
+    /// Returns the level of the root node.
+    ///
+    pub fn level(&self) -> u8 {
+        self.nodes[self.root as usize].level
+    }
+
+    ///
+    /// Returns the number of distinct nodes created so far.
+    ///
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    ///
+    /// Sets the state of a cell, expanding the universe if needed.
+    ///
+    pub fn set(&mut self, x: i64, y: i64, alive: bool) {
+        while !self.contains(x, y) {
+            self.expand();
+        }
+        let half = 1i64 << (self.level() - 1);
+        self.root = self.set_in(self.root, x + half, y + half, alive);
+    }
+
+    ///
+    /// Returns true if the cell is within the root node.
+    ///
+    fn contains(&self, x: i64, y: i64) -> bool {
+        let half = 1i64 << (self.level() - 1);
+        (-half..half).contains(&x) && (-half..half).contains(&y)
+    }
+
+    ///
+    /// Returns the node with the cell set. Coordinates are relative to the top left of the node.
+    ///
+    fn set_in(&mut self, node: u32, x: i64, y: i64, alive: bool) -> u32 {
+        let Node { level, mut children, .. } = self.nodes[node as usize];
+        if level == 0 {
+            return if alive { ALIVE } else { DEAD };
+        }
+        let half = 1i64 << (level - 1);
+        let quadrant = (x >= half) as usize + 2 * (y >= half) as usize;
+        children[quadrant] = self.set_in(children[quadrant], x % half, y % half, alive);
+        self.join(children)
+    }
+
+    ///
+    /// Returns the canonical node with the children, creating it if needed.
+    ///
+    fn join(&mut self, children: [u32; 4]) -> u32 {
+        if let Some(&id) = self.intern.get(&children) {
+            return id;
+        }
+        let level = self.nodes[children[0] as usize].level + 1;
+        let population = children.iter().map(|&child| self.nodes[child as usize].population).sum();
+        let id = self.nodes.len() as u32;
+        self.nodes.push(Node { level, children, population });
+        self.intern.insert(children, id);
+        id
+    }
+
+    ///
+    /// Returns the empty node of the level.
+    ///
+    fn empty_node(&mut self, level: u8) -> u32 {
+        while self.empty.len() <= level as usize {
+            let below = *self.empty.last().expect("Empty leaf exists");
+            let node = self.join([below; 4]);
+            self.empty.push(node);
+        }
+        self.empty[level as usize]
+    }
+
+    fn children(&self, node: u32) -> [u32; 4] {
+        self.nodes[node as usize].children
+    }
+
+    fn population_of(&self, node: u32) -> u64 {
+        self.nodes[node as usize].population
+    }
+
+    ///
+    /// Doubles the size of the root and keeps the old root in the center.
+    ///
+    fn expand(&mut self) {
+        let [nw, ne, sw, se] = self.children(self.root);
+        let empty = self.empty_node(self.level() - 1);
+        let new_nw = self.join([empty, empty, empty, nw]);
+        let new_ne = self.join([empty, empty, ne, empty]);
+        let new_sw = self.join([empty, sw, empty, empty]);
+        let new_se = self.join([se, empty, empty, empty]);
+        self.root = self.join([new_nw, new_ne, new_sw, new_se]);
+    }
+
+    ///
+    /// Returns the center node of half the size.
+    ///
+    fn center(&mut self, node: u32) -> u32 {
+        let [nw, ne, sw, se] = self.children(node);
+        let children = [self.children(nw)[3], self.children(ne)[2], self.children(sw)[1], self.children(se)[0]];
+        self.join(children)
+    }
+
+    ///
+    /// Returns the node between the west and the east node, half of each.
+    ///
+    fn horizontal_center(&mut self, west: u32, east: u32) -> u32 {
+        let ([_, west_ne, _, west_se], [east_nw, _, east_sw, _]) = (self.children(west), self.children(east));
+        self.join([west_ne, east_nw, west_se, east_sw])
+    }
+
+    ///
+    /// Returns the node between the north and the south node, half of each.
+    ///
+    fn vertical_center(&mut self, north: u32, south: u32) -> u32 {
+        let ([_, _, north_sw, north_se], [south_nw, south_ne, _, _]) = (self.children(north), self.children(south));
+        self.join([north_sw, north_se, south_nw, south_ne])
+    }
+
+    ///
+    /// Computes one generation of the center 2x2 cells of a level 2 node.
+    ///
+    fn step_level_two(&mut self, node: u32) -> u32 {
+        let mut cells = [[false; 4]; 4];
+        for (quadrant, &child) in self.children(node).iter().enumerate() {
+            for (index, &leaf) in self.children(child).iter().enumerate() {
+                cells[2 * (quadrant / 2) + index / 2][2 * (quadrant % 2) + index % 2] = leaf == ALIVE;
+            }
+        }
+        let mut result = [DEAD; 4];
+        for (index, cell) in result.iter_mut().enumerate() {
+            let (row, column) = (1 + index / 2, 1 + index % 2);
+            let block = cells[row - 1..=row + 1].iter().flat_map(|line| &line[column - 1..=column + 1]).filter(|&&alive| alive).count() as u32;
+            let neighbours = block - cells[row][column] as u32;
+            *cell = if self.rule.next_state(cells[row][column], neighbours) { ALIVE } else { DEAD };
+        }
+        self.join(result)
+    }
+
+    ///
+    /// Returns the center of the node advanced 2^step generations.
+    ///
+    /// node: Node of level k >= 2.
+    /// step: log2 of the number of generations, at most k - 2.
+    ///
+    /// result: Node of level k - 1.
+    ///
+    fn successor(&mut self, node: u32, step: u8) -> u32 {
+        let level = self.nodes[node as usize].level;
+        debug_assert!(level >= 2 && step <= level - 2);
+        if self.population_of(node) == 0 {
+            return self.empty_node(level - 1);
+        }
+        if let Some(&result) = self.successors.get(&(node, step)) {
+            return result;
+        }
+        let result = if level == 2 {
+            self.step_level_two(node)
+        } else {
+            let [nw, ne, sw, se] = self.children(node);
+            // Nine overlapping nodes of level k - 1 covering the node.
+            let parts = [nw, self.horizontal_center(nw, ne), ne, self.vertical_center(nw, sw), self.center(node), self.vertical_center(ne, se), sw, self.horizontal_center(sw, se), se];
+            // At full speed both halves advance 2^(step-1) generations, otherwise
+            // the first half advances 2^step and the second only takes the center.
+            let full_speed = step == level - 2;
+            let first_step = if full_speed { step - 1 } else { step };
+            let mut advanced = [DEAD; 9];
+            for (index, &part) in parts.iter().enumerate() {
+                advanced[index] = self.successor(part, first_step);
+            }
+            let mut quadrants = [DEAD; 4];
+            for (index, quadrant) in quadrants.iter_mut().enumerate() {
+                let corner = 3 * (index / 2) + index % 2;
+                let combined = self.join([advanced[corner], advanced[corner + 1], advanced[corner + 3], advanced[corner + 4]]);
+                *quadrant = if full_speed { self.successor(combined, step - 1) } else { self.center(combined) };
+            }
+            self.join(quadrants)
+        };
+        self.successors.insert((node, step), result);
+        result
+    }
+
+    ///
+    /// Advances the universe 2^step generations.
+    ///
+    fn advance_power_of_two(&mut self, step: u8) {
+        // The pattern must be inside the center quarter of the root and the root large enough
+        // that the pattern can not reach the edge of the result, which is the center half.
+        loop {
+            let inner = self.center(self.root);
+            let inner = self.center(inner);
+            if self.level() >= step + MIN_LEVEL && self.population_of(inner) == self.population_of(self.root) {
+                break;
+            }
+            self.expand();
+        }
+        self.root = self.successor(self.root, step);
+        self.generation += 1 << step;
+        if self.level() < MIN_LEVEL {
+            self.expand();
+        }
+    }
+
+    ///
+    /// Appends the live cells of the node. (left, top) is the position of the top left cell.
+    ///
+    fn collect_cells(&self, node: u32, left: i64, top: i64, cells: &mut Vec<(i64, i64)>) {
+        let Node { level, children, population } = self.nodes[node as usize];
+        if population == 0 {
+            return;
+        }
+        if level == 0 {
+            cells.push((left, top));
+            return;
+        }
+        let half = 1i64 << (level - 1);
+        for (quadrant, &child) in children.iter().enumerate() {
+            self.collect_cells(child, left + half * (quadrant % 2) as i64, top + half * (quadrant / 2) as i64, cells);
+        }
+    }
+}
+
+impl Universe for Hashlife {
+    ///
+    /// Advances the universe by splitting the generations into powers of two.
+    ///
+    fn step(&mut self, generations: u64) {
+        for step in 0..u64::BITS as u8 {
+            if generations & (1 << step) != 0 {
+                self.advance_power_of_two(step);
+            }
+        }
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn population(&self) -> u64 {
+        self.population_of(self.root)
+    }
+
+    fn is_alive(&self, x: i64, y: i64) -> bool {
+        if !self.contains(x, y) {
+            return false;
+        }
+        let half = 1i64 << (self.level() - 1);
+        let (mut x, mut y) = (x + half, y + half);
+        let mut node = self.root;
+        loop {
+            let Node { level, children, .. } = self.nodes[node as usize];
+            if level == 0 {
+                return node == ALIVE;
+            }
+            let half = 1i64 << (level - 1);
+            node = children[(x >= half) as usize + 2 * (y >= half) as usize];
+            (x, y) = (x % half, y % half);
+        }
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        let half = 1i64 << (self.level() - 1);
+        let mut cells = Vec::new();
+        self.collect_cells(self.root, -half, -half, &mut cells);
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+    use crate::rle;
+
+    const GLIDER: [(i64, i64); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+    fn sorted(mut cells: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn test_set_and_get() {
+        let mut hashlife = Hashlife::new(Rule::CONWAY);
+        hashlife.set(-100, 37, true);
+        hashlife.set(5, 5, true);
+        hashlife.set(5, 5, false);
+        assert!(hashlife.is_alive(-100, 37));
+        assert!(!hashlife.is_alive(5, 5));
+        assert_eq!(hashlife.population(), 1);
+        assert_eq!(hashlife.live_cells(), vec![(-100, 37)]);
+    }
+
+    #[test]
+    fn test_equal_nodes_are_shared() {
+        let mut hashlife = Hashlife::new(Rule::CONWAY);
+        hashlife.set(-4, -4, true);
+        let (root, count) = (hashlife.root, hashlife.node_count());
+        hashlife.set(3, 3, true);
+        hashlife.set(3, 3, false);
+        assert_eq!(hashlife.root, root);
+        assert!(hashlife.node_count() > count);
+    }
+
+    #[test]
+    fn test_matches_grid() {
+        let pattern = Pattern { cells: vec![(0, 0), (1, 0), (2, 0), (1, 1), (5, 3), (5, 4), (6, 4), (-3, 2)], rule: Rule::CONWAY };
+        let mut grid = Grid::from_pattern(&pattern, 100, 100, false);
+        let mut hashlife = Hashlife::from_pattern(&pattern);
+        for generations in [1, 1, 2, 3, 5, 8, 13] {
+            grid.step(generations);
+            hashlife.step(generations);
+            assert_eq!(sorted(hashlife.live_cells()), sorted(grid.live_cells()));
+        }
+        assert_eq!(hashlife.generation(), 33);
+    }
+
+    #[test]
+    fn test_glider_far_future() {
+        let mut hashlife = Hashlife::from_pattern(&Pattern { cells: GLIDER.to_vec(), rule: Rule::CONWAY });
+        let generations = 1u64 << 40;
+        hashlife.step(generations);
+        let offset = (generations / 4) as i64;
+        let expected: Vec<(i64, i64)> = GLIDER.iter().map(|&(x, y)| (x + offset, y + offset)).collect();
+        assert_eq!(sorted(hashlife.live_cells()), sorted(expected));
+        assert_eq!(hashlife.generation(), generations);
+    }
+
+    #[test]
+    fn test_gosper_glider_gun() {
+        let text = std::fs::read_to_string("testdata/gosper_glider_gun.rle").unwrap();
+        let pattern = rle::parse(&text).unwrap();
+        let mut hashlife = Hashlife::from_pattern(&pattern);
+        // The gun has period 30 and emits one glider per period.
+        hashlife.step(300);
+        let mut grid = Grid::from_pattern(&pattern, 200, 200, false);
+        grid.step(300);
+        assert_eq!(sorted(hashlife.live_cells()), sorted(grid.live_cells()));
+        assert!(hashlife.population() > pattern.cells.len() as u64);
+    }
+
+    #[test]
+    fn test_r_pentomino_stabilizes() {
+        let text = std::fs::read_to_string("testdata/r_pentomino.rle").unwrap();
+        let mut hashlife = Hashlife::from_pattern(&rle::parse(&text).unwrap());
+        hashlife.step(1103);
+        assert_eq!(hashlife.population(), 116);
+    }
+}
@@ -0,0 +1,200 @@
+///
+/// Conway's Game of Life and other Life-like cellular automata.
+/// Description: https://conwaylife.com/wiki/Conway%27s_Game_of_Life
+///
+/// Two implementations are provided. The grid stepper computes one generation
+/// at a time on a bounded or toroidal grid. Hashlife stores the universe as a
+/// quadtree of canonical nodes and memoizes the future of each node, which makes
+/// it possible to jump exponentially far ahead for regular patterns.
+/// Description: https://en.wikipedia.org/wiki/Hashlife
+///
+pub mod grid;
+pub mod hashlife;
+pub mod rle;
+
+use std::fmt;
+use std::str::FromStr;
+
+///
+/// LifeError enum to represent errors when reading patterns and rules.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifeError {
+    // The rule is not in the B/S notation, for example B3/S23.
+    InvalidRule { rule: String },
+    // The RLE header line could not be parsed.
+    InvalidHeader { header: String },
+    // The RLE data contained an unexpected character.
+    InvalidCharacter { character: char, line: usize },
+}
+
+impl fmt::Display for LifeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LifeError::InvalidRule { rule } => write!(f, "Invalid rule {rule:?}, expected for example B3/S23"),
+            LifeError::InvalidHeader { header } => write!(f, "Invalid header {header:?}"),
+            LifeError::InvalidCharacter { character, line } => write!(f, "Invalid character {character:?} on line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for LifeError {}
+
+///
+/// Rule struct for Life-like automata. Bit n of birth is set if a dead cell
+/// with n live neighbours becomes alive, and bit n of survival if a live cell
+/// with n live neighbours stays alive.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rule {
+    birth: u16,
+    survival: u16,
+}
+
+impl Rule {
+    ///
+    /// Conway's Game of Life, B3/S23.
+    ///
+    pub const CONWAY: Rule = Rule { birth: 1 << 3, survival: (1 << 2) | (1 << 3) };
+
+    ///
+    /// Returns the next state of a cell.
+    ///
+    /// alive: The current state of the cell.
+    /// neighbours: Number of live cells among the 8 neighbours.
+    ///
+    pub fn next_state(&self, alive: bool, neighbours: u32) -> bool {
+        let mask = if alive { self.survival } else { self.birth };
+        mask & (1 << neighbours) != 0
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::CONWAY
+    }
+}
+
+impl FromStr for Rule {
+    type Err = LifeError;
+
+    ///
+    /// Parses a rule in B/S notation, for example B3/S23 or B36/S23.
+    /// The letters are case insensitive.
+    ///
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || LifeError::InvalidRule { rule: text.to_string() };
+        let upper = text.trim().to_ascii_uppercase();
+        let (birth, survival) = upper.split_once('/').ok_or_else(invalid)?;
+        let digits = |part: &str, prefix: char| -> Result<u16, LifeError> {
+            let part = part.strip_prefix(prefix).ok_or_else(invalid)?;
+            part.chars().try_fold(0u16, |mask, digit| match digit.to_digit(10) {
+                Some(count) if count <= 8 => Ok(mask | (1 << count)),
+                _ => Err(invalid()),
+            })
+        };
+        Ok(Rule { birth: digits(birth, 'B')?, survival: digits(survival, 'S')? })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = |mask: u16| (0..=8).filter(|count| mask & (1 << count) != 0).map(|count| count.to_string()).collect::<String>();
+        write!(f, "B{}/S{}", digits(self.birth), digits(self.survival))
+    }
+}
+
+///
+/// Pattern struct containing the live cells and the rule they evolve under.
+/// The y axis points down, as in RLE files and images.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Pattern {
+    pub cells: Vec<(i64, i64)>,
+    pub rule: Rule,
+}
+
+impl Pattern {
+    ///
+    /// Returns the smallest rectangle containing all live cells.
+    ///
+    /// result: (min x, min y, max x, max y) or None if there are no live cells.
+    ///
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        bounding_box(&self.cells)
+    }
+}
+
+///
+/// Returns the smallest rectangle containing all cells as (min x, min y, max x, max y).
+///
+pub fn bounding_box(cells: &[(i64, i64)]) -> Option<(i64, i64, i64, i64)> {
+    let (&(first_x, first_y), rest) = cells.split_first()?;
+    Some(rest.iter().fold((first_x, first_y, first_x, first_y), |(min_x, min_y, max_x, max_y), &(x, y)| (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))))
+}
+
+///
+/// Universe trait implemented by the different Life algorithms.
+///
+pub trait Universe {
+    ///
+    /// Advances the universe by the number of generations.
+    ///
+    fn step(&mut self, generations: u64);
+
+    ///
+    /// Returns the number of generations computed so far.
+    ///
+    fn generation(&self) -> u64;
+
+    ///
+    /// Returns the number of live cells.
+    ///
+    fn population(&self) -> u64;
+
+    ///
+    /// Returns true if the cell is alive.
+    ///
+    fn is_alive(&self, x: i64, y: i64) -> bool;
+
+    ///
+    /// Returns the coordinates of all live cells.
+    ///
+    fn live_cells(&self) -> Vec<(i64, i64)>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rule() {
+        assert_eq!("B3/S23".parse::<Rule>().unwrap(), Rule::CONWAY);
+        assert_eq!("b3/s23".parse::<Rule>().unwrap(), Rule::CONWAY);
+        let highlife: Rule = "B36/S23".parse().unwrap();
+        assert!(highlife.next_state(false, 6));
+        assert!(!highlife.next_state(true, 6));
+        assert_eq!(highlife.to_string(), "B36/S23");
+    }
+
+    #[test]
+    fn test_parse_invalid_rule() {
+        assert!("23/3".parse::<Rule>().is_err());
+        assert!("B9/S23".parse::<Rule>().is_err());
+        assert!("B3S23".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn test_conway_rule() {
+        assert!(Rule::CONWAY.next_state(false, 3));
+        assert!(!Rule::CONWAY.next_state(false, 2));
+        assert!(Rule::CONWAY.next_state(true, 2));
+        assert!(!Rule::CONWAY.next_state(true, 4));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        assert_eq!(bounding_box(&[]), None);
+        assert_eq!(bounding_box(&[(1, 5), (-2, 3), (4, 0)]), Some((-2, 0, 4, 5)));
+    }
+}
@@ -0,0 +1,132 @@
+mod args;
+
+use std::io::{Read, Write};
+use std::time::Duration;
+use std::{fs, thread};
+
+use args::{Algorithm, Args};
+use clap::Parser;
+use life::grid::Grid;
+use life::hashlife::Hashlife;
+use life::{Pattern, Universe, rle};
+use render::terminal::{CLEAR_SCREEN, render_cells};
+use render::{BLACK, Image, WHITE};
+
+/**
+ * Conway's Game of Life.
+ * Reads a pattern in RLE format, computes the generations with a simple grid
+ * or with Hashlife and shows the result in the terminal or as an image.
+ */
+fn main() {
+    let args = Args::parse();
+    let pattern = match read_input(args.input_file.clone()).map(|text| rle::parse(&text)) {
+        Ok(Ok(pattern)) => pattern,
+        Ok(Err(err)) => {
+            eprintln!("Failed to parse pattern: {err}");
+            return;
+        }
+        Err(err) => {
+            eprintln!("Failed to read input: {err}");
+            return;
+        }
+    };
+    let mut universe: Box<dyn Universe> = match args.algorithm {
+        Algorithm::Grid => Box::new(Grid::from_pattern(&pattern, args.width, args.height, args.wrap)),
+        Algorithm::Hashlife => Box::new(Hashlife::from_pattern(&pattern)),
+    };
+    let viewport = Viewport::centered_on(&pattern, args.width, args.height);
+
+    if args.animate {
+        animate(universe.as_mut(), &viewport, args.generations, args.step.max(1), Duration::from_millis(args.delay_ms));
+    } else {
+        universe.step(args.generations);
+        if args.rle {
+            print!("{}", rle::write(&Pattern { cells: universe.live_cells(), rule: pattern.rule }));
+        } else if args.output.is_none() {
+            print_frame(universe.as_ref(), &viewport);
+        }
+    }
+
+    if let Some(output) = args.output {
+        let image = Image::from_cells(viewport.width, viewport.height, args.scale, |x, y| if viewport.is_alive(universe.as_ref(), x, y) { WHITE } else { BLACK });
+        if let Err(err) = image.save(&output) {
+            eprintln!("Failed to write image {output}: {err}");
+        }
+    }
+}
+
+/**
+ * Viewport struct for the area of the universe that is shown.
+ */
+struct Viewport {
+    left: i64,
+    top: i64,
+    width: usize,
+    height: usize,
+}
+
+impl Viewport {
+    /**
+     * Creates a viewport with the center of the pattern in the middle,
+     * covering the same cells as the grid algorithm.
+     */
+    fn centered_on(pattern: &Pattern, width: usize, height: usize) -> Viewport {
+        let (left, top) = match pattern.bounding_box() {
+            Some((min_x, min_y, max_x, max_y)) => ((min_x + max_x) / 2 - width as i64 / 2, (min_y + max_y) / 2 - height as i64 / 2),
+            None => (0, 0),
+        };
+        Viewport { left, top, width, height }
+    }
+
+    /**
+     * Returns true if the cell at the position within the viewport is alive.
+     */
+    fn is_alive(&self, universe: &dyn Universe, x: usize, y: usize) -> bool {
+        universe.is_alive(self.left + x as i64, self.top + y as i64)
+    }
+}
+
+/**
+ * Shows the generations in the terminal until the number of generations is reached.
+ *
+ * # Arguments
+ * * `universe`: The universe to advance.
+ * * `viewport`: The area to show.
+ * * `generations`: Total number of generations.
+ * * `step`: Number of generations between the frames.
+ * * `delay`: Time to wait between the frames.
+ */
+fn animate(universe: &mut dyn Universe, viewport: &Viewport, generations: u64, step: u64, delay: Duration) {
+    loop {
+        print!("{CLEAR_SCREEN}");
+        print_frame(universe, viewport);
+        let _ = std::io::stdout().flush();
+        if universe.generation() >= generations {
+            break;
+        }
+        thread::sleep(delay);
+        universe.step(step.min(generations - universe.generation()));
+    }
+}
+
+/**
+ * Prints the generation, the population and the cells in the viewport.
+ */
+fn print_frame(universe: &dyn Universe, viewport: &Viewport) {
+    println!("Generation: {}, population: {}", universe.generation(), universe.population());
+    print!("{}", render_cells(viewport.width, viewport.height, |x, y| viewport.is_alive(universe, x, y)));
+}
+
+/**
+ * Reads the whole input file, or stdin if no file is specified.
+ */
+fn read_input(input_file: Option<String>) -> std::io::Result<String> {
+    match input_file {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            Ok(text)
+        }
+    }
+}
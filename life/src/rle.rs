@@ -0,0 +1,173 @@
+use crate::{LifeError, Pattern, Rule, bounding_box};
+
+///
+/// Maximum length of the lines written by `write`, as recommended by the format.
+///
+const LINE_LENGTH: usize = 70;
+
+///
+/// Parses a pattern in the run length encoded format.
+/// Description: https://conwaylife.com/wiki/Run_Length_Encoded
+///
+/// Lines starting with # are comments. The header line gives the size and
+/// optionally the rule, for example `x = 3, y = 3, rule = B3/S23`. The data uses
+/// b for dead cells, o for live cells, $ for the end of a row and ! for the end
+/// of the pattern, each optionally preceded by a repeat count.
+///
+/// result: The pattern with the top left cell of the header at (0, 0).
+///
+pub fn parse(text: &str) -> Result<Pattern, LifeError> {
+    let mut pattern = Pattern::default();
+    let mut header_read = false;
+    let (mut x, mut y) = (0i64, 0i64);
+    let mut count: Option<i64> = None;
+    for (line_index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if !header_read && line.starts_with('x') {
+            pattern.rule = parse_header(line)?;
+            header_read = true;
+            continue;
+        }
+        header_read = true;
+        for character in line.chars() {
+            if let Some(digit) = character.to_digit(10) {
+                count = Some(count.unwrap_or(0) * 10 + digit as i64);
+                continue;
+            }
+            if character.is_whitespace() {
+                continue;
+            }
+            let run = count.take().unwrap_or(1);
+            match character {
+                'b' | '.' => x += run,
+                'o' | 'A' => {
+                    pattern.cells.extend((x..x + run).map(|cell_x| (cell_x, y)));
+                    x += run;
+                }
+                '$' => {
+                    y += run;
+                    x = 0;
+                }
+                '!' => return Ok(pattern),
+                _ => return Err(LifeError::InvalidCharacter { character, line: line_index + 1 }),
+            }
+        }
+    }
+    Ok(pattern)
+}
+
+///
+/// Parses the header line and returns the rule, Conway's rule if not given.
+///
+fn parse_header(line: &str) -> Result<Rule, LifeError> {
+    let mut rule = Rule::CONWAY;
+    for part in line.split(',') {
+        let (key, value) = part.split_once('=').ok_or_else(|| LifeError::InvalidHeader { header: line.to_string() })?;
+        match key.trim() {
+            "x" | "y" => {
+                value.trim().parse::<u64>().map_err(|_| LifeError::InvalidHeader { header: line.to_string() })?;
+            }
+            "rule" => rule = value.parse()?,
+            _ => return Err(LifeError::InvalidHeader { header: line.to_string() }),
+        }
+    }
+    Ok(rule)
+}
+
+///
+/// Writes the pattern in the run length encoded format, moved so the top left
+/// of the bounding box is at (0, 0).
+///
+pub fn write(pattern: &Pattern) -> String {
+    let Some((min_x, min_y, max_x, max_y)) = bounding_box(&pattern.cells) else {
+        return format!("x = 0, y = 0, rule = {}\n!\n", pattern.rule);
+    };
+    let mut cells = pattern.cells.clone();
+    cells.sort_by_key(|&(x, y)| (y, x));
+    cells.dedup();
+
+    // Runs of (count, tag) for the whole pattern. Dead cells at the end of a row are left out.
+    let mut runs: Vec<(i64, char)> = Vec::new();
+    let mut push = |count: i64, tag: char| match runs.last_mut() {
+        Some((last_count, last_tag)) if *last_tag == tag => *last_count += count,
+        _ if count > 0 => runs.push((count, tag)),
+        _ => {}
+    };
+    let (mut x, mut y) = (min_x, min_y);
+    for (cell_x, cell_y) in cells {
+        if cell_y > y {
+            push(cell_y - y, '$');
+            (x, y) = (min_x, cell_y);
+        }
+        push(cell_x - x, 'b');
+        push(1, 'o');
+        x = cell_x + 1;
+    }
+
+    let mut text = format!("x = {}, y = {}, rule = {}\n", max_x - min_x + 1, max_y - min_y + 1, pattern.rule);
+    let mut line = String::new();
+    for item in runs.iter().map(|&(count, tag)| if count == 1 { tag.to_string() } else { format!("{count}{tag}") }).chain(std::iter::once("!".to_string())) {
+        if line.len() + item.len() > LINE_LENGTH {
+            text.push_str(&line);
+            text.push('\n');
+            line.clear();
+        }
+        line.push_str(&item);
+    }
+    text.push_str(&line);
+    text.push('\n');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_glider() {
+        let pattern = parse("#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!\n").unwrap();
+        assert_eq!(pattern.cells, vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        assert_eq!(pattern.rule, Rule::CONWAY);
+    }
+
+    #[test]
+    fn test_parse_multiple_rows_and_lines() {
+        let pattern = parse("x = 2, y = 4\no2$\nbo!").unwrap();
+        assert_eq!(pattern.cells, vec![(0, 0), (1, 2)]);
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        let pattern = parse("x = 1, y = 1, rule = B36/S23\no!").unwrap();
+        assert_eq!(pattern.rule, "B36/S23".parse().unwrap());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("x = 1, y = 1\nbxo!"), Err(LifeError::InvalidCharacter { character: 'x', line: 2 }));
+        assert!(matches!(parse("x = a, y = 1\no!"), Err(LifeError::InvalidHeader { .. })));
+        assert!(matches!(parse("x = 1, y = 1, rule = 23/3\no!"), Err(LifeError::InvalidRule { .. })));
+    }
+
+    #[test]
+    fn test_write_roundtrip() {
+        let text = std::fs::read_to_string("testdata/gosper_glider_gun.rle").unwrap();
+        let pattern = parse(&text).unwrap();
+        let written = write(&pattern);
+        assert!(written.lines().all(|line| line.len() <= LINE_LENGTH));
+        let mut cells = parse(&written).unwrap().cells;
+        let mut expected = pattern.cells.clone();
+        cells.sort();
+        expected.sort();
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn test_write_glider() {
+        let pattern = Pattern { cells: vec![(11, 10), (12, 11), (10, 12), (11, 12), (12, 12)], rule: Rule::CONWAY };
+        assert_eq!(write(&pattern), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+    }
+}
@@ -0,0 +1,49 @@
+use clap::{Parser, ValueEnum};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Text files to train on. Reads stdin if neither input files nor a model are specified
+    #[arg(short, long)]
+    pub input_file: Vec<String>,
+
+    /// Train on each line as a separate text instead of each file
+    #[arg(short = 'L', long)]
+    pub lines: bool,
+
+    /// Number of previous tokens the next token depends on
+    #[arg(short, long, default_value_t = 2)]
+    pub order: usize,
+
+    /// How the text is split into tokens
+    #[arg(short, long, value_enum, default_value_t = Tokens::Words)]
+    pub tokens: Tokens,
+
+    /// Model file to load instead of training
+    #[arg(short, long)]
+    pub load_model: Option<String>,
+
+    /// File to save the model to
+    #[arg(short = 'w', long)]
+    pub save_model: Option<String>,
+
+    /// Number of texts to generate
+    #[arg(short, long, default_value_t = 1)]
+    pub count: usize,
+
+    /// Maximum number of tokens in each generated text
+    #[arg(short, long, default_value_t = 100)]
+    pub max_tokens: usize,
+
+    /// Seed for the random number generator
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tokens {
+    /// Words separated by whitespace
+    Words,
+    /// Single bytes
+    Bytes,
+}
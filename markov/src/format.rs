@@ -0,0 +1,184 @@
+//!
+//! Saving and loading models as text. The format is:
+//!
+//! ```text
+//! markov 1
+//! order 2
+//! tokens words
+//! vocabulary 3
+//! the
+//! cat
+//! sat\x0a
+//! transitions 2
+//! 0 0 : 1:4 2:1
+//! 1 2 : 3:1 0:2
+//! ```
+//!
+//! Vocabulary lines are the tokens with id 1 and up, with backslashes and
+//! bytes outside printable ASCII written as \xHH. Transition lines have the
+//! context ids, a colon, and the following ids with their counts. Id 0 is the
+//! boundary.
+//!
+
+use std::fmt::Write;
+
+use crate::model::{MAX_ORDER, Model};
+use crate::{MarkovError, TokenKind};
+
+///
+/// Version of the format written by save.
+///
+const VERSION: u32 = 1;
+
+///
+/// Returns the model as text. Contexts are sorted so saving is deterministic.
+///
+pub fn save(model: &Model) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "markov {VERSION}");
+    let _ = writeln!(output, "order {}", model.order());
+    let _ = writeln!(output, "tokens {}", model.kind().name());
+    let _ = writeln!(output, "vocabulary {}", model.vocabulary_size());
+    for token in &model.vocabulary()[1..] {
+        let _ = writeln!(output, "{}", escape(token));
+    }
+    let mut contexts: Vec<_> = model.transitions().iter().collect();
+    contexts.sort_by(|first, second| first.0.cmp(second.0));
+    let _ = writeln!(output, "transitions {}", contexts.len());
+    for (context, transitions) in contexts {
+        let context: Vec<String> = context.iter().map(|id| id.to_string()).collect();
+        let counts: Vec<String> = transitions.counts.iter().map(|(id, count)| format!("{id}:{count}")).collect();
+        let _ = writeln!(output, "{} : {}", context.join(" "), counts.join(" "));
+    }
+    output
+}
+
+///
+/// Reads a model saved with save.
+///
+/// result: The model or an error with the line that could not be read.
+///
+pub fn load(text: &str) -> Result<Model, MarkovError> {
+    let mut lines = Lines { lines: text.lines(), line: 0 };
+    if lines.header("markov")? != VERSION as usize {
+        return Err(lines.error("Unsupported version"));
+    }
+    let order = lines.header("order")?;
+    if order == 0 || order > MAX_ORDER {
+        return Err(lines.error(&MarkovError::InvalidOrder { order }.to_string()));
+    }
+    let kind = match lines.next()?.strip_prefix("tokens ") {
+        Some("words") => TokenKind::Words,
+        Some("bytes") => TokenKind::Bytes,
+        _ => return Err(lines.error("Expected tokens words or tokens bytes")),
+    };
+    let mut model = Model::new(order, kind).expect("Order is checked");
+    let vocabulary_size = lines.header("vocabulary")?;
+    for id in 1..=vocabulary_size {
+        let token = unescape(lines.next()?).ok_or_else(|| lines.error("Invalid escape"))?;
+        if model.intern(&token) as usize != id {
+            return Err(lines.error("Duplicate token"));
+        }
+    }
+    let transition_count = lines.header("transitions")?;
+    for _ in 0..transition_count {
+        let line = lines.next()?;
+        let (context, counts) = line.split_once(" : ").ok_or_else(|| lines.error("Expected context : counts"))?;
+        let context: Vec<u32> = context.split(' ').map(|id| lines.id(id, vocabulary_size)).collect::<Result<_, _>>()?;
+        if context.len() != order {
+            return Err(lines.error("Context length does not match the order"));
+        }
+        for pair in counts.split(' ') {
+            let (id, count) = pair.split_once(':').ok_or_else(|| lines.error("Expected id:count"))?;
+            let count: u64 = count.parse().ok().filter(|&count| count > 0).ok_or_else(|| lines.error("Invalid count"))?;
+            model.add_transition(context.clone(), lines.id(id, vocabulary_size)?, count);
+        }
+    }
+    Ok(model)
+}
+
+///
+/// Lines struct keeping track of the current line number for errors.
+///
+struct Lines<'a> {
+    lines: std::str::Lines<'a>,
+    line: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn error(&self, message: &str) -> MarkovError {
+        MarkovError::InvalidModel { line: self.line, message: message.to_string() }
+    }
+
+    fn next(&mut self) -> Result<&'a str, MarkovError> {
+        self.line += 1;
+        self.lines.next().ok_or_else(|| self.error("Unexpected end of model"))
+    }
+
+    ///
+    /// Reads a line with the name followed by a number.
+    ///
+    fn header(&mut self, name: &str) -> Result<usize, MarkovError> {
+        let line = self.next()?;
+        line.strip_prefix(name).and_then(|rest| rest.strip_prefix(' ')).and_then(|number| number.parse().ok()).ok_or_else(|| self.error(&format!("Expected {name} followed by a number")))
+    }
+
+    fn id(&self, text: &str, vocabulary_size: usize) -> Result<u32, MarkovError> {
+        text.parse().ok().filter(|&id: &u32| id as usize <= vocabulary_size).ok_or_else(|| self.error(&format!("Invalid token id {text:?}")))
+    }
+}
+
+///
+/// Escapes backslashes and bytes outside printable ASCII as \xHH. Spaces are
+/// escaped too, so tokens can not start or end with invisible whitespace.
+///
+fn escape(token: &[u8]) -> String {
+    token.iter().map(|&byte| if byte.is_ascii_graphic() && byte != b'\\' { (byte as char).to_string() } else { format!("\\x{byte:02x}") }).collect()
+}
+
+fn unescape(text: &str) -> Option<Vec<u8>> {
+    let bytes = text.as_bytes();
+    let mut token = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'\\' {
+            let hex = text.get(index + 2..index + 4).filter(|_| bytes.get(index + 1) == Some(&b'x'))?;
+            token.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 4;
+        } else {
+            token.push(bytes[index]);
+            index += 1;
+        }
+    }
+    Some(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save() {
+        let mut model = Model::new(1, TokenKind::Words).unwrap();
+        model.train(b"the cat\\ sat");
+        let expected = "markov 1\norder 1\ntokens words\nvocabulary 3\nthe\ncat\\x5c\nsat\ntransitions 4\n0 : 1:1\n1 : 2:1\n2 : 3:1\n3 : 0:1\n";
+        assert_eq!(save(&model), expected);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut model = Model::new(3, TokenKind::Bytes).unwrap();
+        model.train("blåbærsyltetøy \\ med fløte\n".as_bytes());
+        model.train(b"\x00\xff binary");
+        assert_eq!(load(&save(&model)), Ok(model));
+    }
+
+    #[test]
+    fn test_load_errors() {
+        assert_eq!(load("markov 2"), Err(MarkovError::InvalidModel { line: 1, message: "Unsupported version".to_string() }));
+        assert_eq!(load("markov 1\norder 0"), Err(MarkovError::InvalidModel { line: 2, message: "Invalid order 0, must be between 1 and 16".to_string() }));
+        let text = "markov 1\norder 1\ntokens words\nvocabulary 1\na\ntransitions 1\n0 : 2:1\n";
+        assert_eq!(load(text), Err(MarkovError::InvalidModel { line: 7, message: "Invalid token id \"2\"".to_string() }));
+        assert_eq!(load("markov 1\norder 1\ntokens words\nvocabulary 2\na\n"), Err(MarkovError::InvalidModel { line: 6, message: "Unexpected end of model".to_string() }));
+    }
+}
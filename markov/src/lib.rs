@@ -0,0 +1,90 @@
+///
+/// Markov chain text generator.
+/// Description: https://en.wikipedia.org/wiki/Markov_chain#Markov_text_generators
+///
+/// The text is split into words or bytes. For every sequence of order tokens
+/// the model counts which tokens follow it. Text is generated by repeatedly
+/// sampling the next token weighted by the counts for the last order tokens.
+/// The start and end of each training text are marked with a boundary token,
+/// so generated text starts like a training text and stops where one ends.
+///
+pub mod format;
+pub mod model;
+
+use std::fmt;
+
+///
+/// MarkovError enum to represent errors when building and loading models.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkovError {
+    // The order must be between 1 and MAX_ORDER.
+    InvalidOrder { order: usize },
+    // A line in a saved model that could not be read. Line numbers start at 1.
+    InvalidModel { line: usize, message: String },
+}
+
+impl fmt::Display for MarkovError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarkovError::InvalidOrder { order } => write!(f, "Invalid order {order}, must be between 1 and {}", model::MAX_ORDER),
+            MarkovError::InvalidModel { line, message } => write!(f, "Invalid model at line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for MarkovError {}
+
+///
+/// TokenKind enum for how text is split into tokens.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    // Words separated by whitespace. Generated words are joined with a space.
+    Words,
+    // Single bytes, which keeps the whitespace and punctuation of the text.
+    Bytes,
+}
+
+impl TokenKind {
+    ///
+    /// Splits the text into tokens.
+    ///
+    pub fn tokenize<'a>(&self, text: &'a [u8]) -> Vec<&'a [u8]> {
+        match self {
+            TokenKind::Words => text.split(|byte| byte.is_ascii_whitespace()).filter(|word| !word.is_empty()).collect(),
+            TokenKind::Bytes => text.chunks(1).collect(),
+        }
+    }
+
+    ///
+    /// Joins generated tokens into text.
+    ///
+    pub fn join(&self, tokens: &[&[u8]]) -> Vec<u8> {
+        match self {
+            TokenKind::Words => tokens.join(&b' '),
+            TokenKind::Bytes => tokens.concat(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TokenKind::Words => "words",
+            TokenKind::Bytes => "bytes",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let words: Vec<&[u8]> = vec![b"the", b"cat,", b"sat"];
+        assert_eq!(TokenKind::Words.tokenize(b" the  cat,\nsat "), words);
+        assert_eq!(TokenKind::Words.join(&words), b"the cat, sat");
+        assert_eq!(TokenKind::Bytes.tokenize(b"ab").len(), 2);
+        assert_eq!(TokenKind::Bytes.join(&TokenKind::Bytes.tokenize(b"a b\n")), b"a b\n");
+    }
+}
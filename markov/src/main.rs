@@ -0,0 +1,79 @@
+mod args;
+
+use std::fs;
+use std::io::{Read, Write};
+
+use args::{Args, Tokens};
+use clap::Parser;
+use markov::model::Model;
+use markov::{TokenKind, format};
+use prng::Xoshiro256StarStar;
+
+/**
+ * Markov chain text generator.
+ * Trains a model on text files or loads a saved model, optionally saves it,
+ * and prints generated texts.
+ */
+fn main() {
+    let args = Args::parse();
+    let model = match &args.load_model {
+        Some(path) => fs::read_to_string(path).map_err(|err| format!("Failed to read model {path}: {err}")).and_then(|text| format::load(&text).map_err(|err| err.to_string())),
+        None => train(&args),
+    };
+    let model = match model {
+        Ok(model) => model,
+        Err(err) => {
+            eprintln!("{err}");
+            return;
+        }
+    };
+    if let Some(path) = &args.save_model
+        && let Err(err) = fs::write(path, format::save(&model))
+    {
+        eprintln!("Failed to save model {path}: {err}");
+        return;
+    }
+    let mut rng = match args.seed {
+        Some(seed) => Xoshiro256StarStar::seed_from_u64(seed),
+        None => Xoshiro256StarStar::from_entropy(),
+    };
+    let mut stdout = std::io::stdout().lock();
+    for _ in 0..args.count {
+        let text = model.generate(&mut rng, args.max_tokens);
+        let _ = stdout.write_all(&text);
+        if !text.ends_with(b"\n") {
+            let _ = stdout.write_all(b"\n");
+        }
+    }
+}
+
+/**
+ * Trains a model on the input files, or stdin if there are none.
+ *
+ * # Arguments
+ * * `args`: The command line arguments with the files, order and token kind.
+ */
+fn train(args: &Args) -> Result<Model, String> {
+    let kind = match args.tokens {
+        Tokens::Words => TokenKind::Words,
+        Tokens::Bytes => TokenKind::Bytes,
+    };
+    let mut model = Model::new(args.order, kind).map_err(|err| err.to_string())?;
+    let mut texts = Vec::new();
+    if args.input_file.is_empty() {
+        let mut text = Vec::new();
+        std::io::stdin().read_to_end(&mut text).map_err(|err| format!("Failed to read stdin: {err}"))?;
+        texts.push(text);
+    }
+    for path in &args.input_file {
+        texts.push(fs::read(path).map_err(|err| format!("Failed to read {path}: {err}"))?);
+    }
+    for text in &texts {
+        if args.lines {
+            text.split(|&byte| byte == b'\n').for_each(|line| model.train(line));
+        } else {
+            model.train(text);
+        }
+    }
+    Ok(model)
+}
@@ -0,0 +1,263 @@
+//!
+//! The model with the transition counts, training and generation.
+//!
+
+use std::collections::HashMap;
+
+use prng::Rng;
+
+use crate::{MarkovError, TokenKind};
+
+///
+/// Maximum order. Higher orders mostly repeat the training text.
+///
+pub const MAX_ORDER: usize = 16;
+
+///
+/// Id of the boundary token marking the start and end of a text.
+///
+pub const BOUNDARY: u32 = 0;
+
+///
+/// Transitions struct with the tokens seen after a context and how many times.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transitions {
+    // Token ids with their counts, in the order they were first seen.
+    pub counts: Vec<(u32, u64)>,
+    // Sum of the counts.
+    pub total: u64,
+}
+
+impl Transitions {
+    pub fn add(&mut self, token: u32, count: u64) {
+        match self.counts.iter_mut().find(|(existing, _)| *existing == token) {
+            Some((_, existing)) => *existing += count,
+            None => self.counts.push((token, count)),
+        }
+        self.total += count;
+    }
+
+    ///
+    /// Samples a token with probability proportional to its count.
+    ///
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> u32 {
+        let mut remaining = rng.gen_range(0..self.total);
+        for &(token, count) in &self.counts {
+            if remaining < count {
+                return token;
+            }
+            remaining -= count;
+        }
+        unreachable!("Total is the sum of the counts")
+    }
+}
+
+///
+/// Model struct with the vocabulary and the transitions for each context of order tokens.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Model {
+    order: usize,
+    kind: TokenKind,
+    // Token bytes by id. Id 0 is the boundary and has no bytes.
+    vocabulary: Vec<Vec<u8>>,
+    ids: HashMap<Vec<u8>, u32>,
+    transitions: HashMap<Vec<u32>, Transitions>,
+}
+
+impl Model {
+    ///
+    /// Creates an empty model.
+    ///
+    /// order: Number of previous tokens the next token depends on.
+    /// kind: How text is split into tokens.
+    ///
+    pub fn new(order: usize, kind: TokenKind) -> Result<Model, MarkovError> {
+        if order == 0 || order > MAX_ORDER {
+            return Err(MarkovError::InvalidOrder { order });
+        }
+        Ok(Model { order, kind, vocabulary: vec![Vec::new()], ids: HashMap::new(), transitions: HashMap::new() })
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    pub fn kind(&self) -> TokenKind {
+        self.kind
+    }
+
+    ///
+    /// Returns the number of distinct tokens, not counting the boundary.
+    ///
+    pub fn vocabulary_size(&self) -> usize {
+        self.vocabulary.len() - 1
+    }
+
+    ///
+    /// Returns the number of distinct contexts seen.
+    ///
+    pub fn context_count(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn token(&self, id: u32) -> &[u8] {
+        &self.vocabulary[id as usize]
+    }
+
+    pub fn vocabulary(&self) -> &[Vec<u8>] {
+        &self.vocabulary
+    }
+
+    pub fn transitions(&self) -> &HashMap<Vec<u32>, Transitions> {
+        &self.transitions
+    }
+
+    ///
+    /// Returns the id of the token, adding it to the vocabulary if it is new.
+    ///
+    pub fn intern(&mut self, token: &[u8]) -> u32 {
+        if let Some(&id) = self.ids.get(token) {
+            return id;
+        }
+        let id = self.vocabulary.len() as u32;
+        self.vocabulary.push(token.to_vec());
+        self.ids.insert(token.to_vec(), id);
+        id
+    }
+
+    ///
+    /// Adds the count for the token following the context.
+    ///
+    pub fn add_transition(&mut self, context: Vec<u32>, token: u32, count: u64) {
+        self.transitions.entry(context).or_default().add(token, count);
+    }
+
+    ///
+    /// Counts the transitions in the text. Texts without tokens are ignored.
+    ///
+    pub fn train(&mut self, text: &[u8]) {
+        let tokens = self.kind.tokenize(text);
+        if tokens.is_empty() {
+            return;
+        }
+        let mut sequence = vec![BOUNDARY; self.order];
+        sequence.extend(tokens.iter().map(|token| self.intern(token)));
+        sequence.push(BOUNDARY);
+        for window in sequence.windows(self.order + 1) {
+            self.add_transition(window[..self.order].to_vec(), window[self.order], 1);
+        }
+    }
+
+    ///
+    /// Returns the probabilities of the tokens following the context, highest
+    /// first. The end boundary is returned as an empty token.
+    ///
+    /// context: The previous tokens. Only the last order tokens are used, and
+    /// missing tokens at the start are taken to be boundaries.
+    ///
+    pub fn probabilities(&self, context: &[&[u8]]) -> Vec<(&[u8], f64)> {
+        let mut ids = vec![BOUNDARY; self.order.saturating_sub(context.len())];
+        for token in &context[context.len().saturating_sub(self.order)..] {
+            match self.ids.get(*token) {
+                Some(&id) => ids.push(id),
+                None => return Vec::new(),
+            }
+        }
+        let Some(transitions) = self.transitions.get(&ids) else { return Vec::new() };
+        let mut probabilities: Vec<(&[u8], f64)> = transitions.counts.iter().map(|&(id, count)| (self.token(id), count as f64 / transitions.total as f64)).collect();
+        probabilities.sort_by(|first, second| second.1.total_cmp(&first.1));
+        probabilities
+    }
+
+    ///
+    /// Generates token ids from the start boundary until the end boundary.
+    ///
+    /// max_tokens: Maximum number of tokens to generate.
+    ///
+    pub fn generate_ids<R: Rng>(&self, rng: &mut R, max_tokens: usize) -> Vec<u32> {
+        let mut context = vec![BOUNDARY; self.order];
+        let mut generated = Vec::new();
+        while generated.len() < max_tokens {
+            // Every context reached was seen in training, except in an empty model.
+            let Some(transitions) = self.transitions.get(&context) else { break };
+            let token = transitions.sample(rng);
+            if token == BOUNDARY {
+                break;
+            }
+            generated.push(token);
+            context.remove(0);
+            context.push(token);
+        }
+        generated
+    }
+
+    ///
+    /// Generates a text.
+    ///
+    /// max_tokens: Maximum number of words or bytes to generate.
+    ///
+    pub fn generate<R: Rng>(&self, rng: &mut R, max_tokens: usize) -> Vec<u8> {
+        let tokens: Vec<&[u8]> = self.generate_ids(rng, max_tokens).into_iter().map(|id| self.token(id)).collect();
+        self.kind.join(&tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prng::Xoshiro256StarStar;
+
+    #[test]
+    fn test_invalid_order() {
+        assert_eq!(Model::new(0, TokenKind::Words), Err(MarkovError::InvalidOrder { order: 0 }));
+        assert_eq!(Model::new(MAX_ORDER + 1, TokenKind::Bytes), Err(MarkovError::InvalidOrder { order: MAX_ORDER + 1 }));
+    }
+
+    #[test]
+    fn test_train_counts() {
+        let mut model = Model::new(1, TokenKind::Words).unwrap();
+        model.train(b"a b a c a b");
+        assert_eq!(model.vocabulary_size(), 3);
+        let probabilities = model.probabilities(&[b"a"]);
+        assert_eq!(probabilities, vec![(&b"b"[..], 2.0 / 3.0), (&b"c"[..], 1.0 / 3.0)]);
+        // The text starts with a, and b is followed by a or the end.
+        assert_eq!(model.probabilities(&[]), vec![(&b"a"[..], 1.0)]);
+        assert_eq!(model.probabilities(&[b"b"]), vec![(&b"a"[..], 0.5), (&b""[..], 0.5)]);
+        assert_eq!(model.probabilities(&[b"x"]), vec![]);
+    }
+
+    #[test]
+    fn test_generation_follows_training() {
+        let mut model = Model::new(2, TokenKind::Bytes).unwrap();
+        model.train(b"abcabd");
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1749);
+        for _ in 0..50 {
+            let text = model.generate(&mut rng, 100);
+            // Every generated text starts like the training text and ends like it.
+            assert!(text.starts_with(b"ab") && text.ends_with(b"abd"), "{}", String::from_utf8_lossy(&text));
+            for window in text.windows(3) {
+                assert!(b"abcabd".windows(3).any(|seen| seen == window));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sampling_is_weighted() {
+        let mut transitions = Transitions::default();
+        transitions.add(1, 1);
+        transitions.add(2, 3);
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7);
+        let twos = (0..10_000).filter(|_| transitions.sample(&mut rng) == 2).count();
+        assert!((7_000..8_000).contains(&twos), "{twos}");
+    }
+
+    #[test]
+    fn test_max_tokens() {
+        let mut model = Model::new(1, TokenKind::Words).unwrap();
+        model.train(b"a a a a a a a a a a a a");
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        assert!(model.generate_ids(&mut rng, 3).len() <= 3);
+    }
+}
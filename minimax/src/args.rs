@@ -0,0 +1,39 @@
+use clap::{Parser, ValueEnum};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Game to play
+    #[arg(short, long, value_enum, default_value_t = GameKind::ConnectFour)]
+    pub game: GameKind,
+
+    /// Which player the human controls. Use none to let the engine play both sides
+    #[arg(short = 'u', long, value_enum, default_value_t = Human::First)]
+    pub human: Human,
+
+    /// Maximum search depth in plies
+    #[arg(short, long, default_value_t = 12)]
+    pub depth: usize,
+
+    /// Time limit per engine move in milliseconds
+    #[arg(short, long)]
+    pub time_ms: Option<u64>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameKind {
+    /// Tic-tac-toe on a 3x3 board
+    Tictactoe,
+    /// Connect Four on a 7x6 board
+    ConnectFour,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Human {
+    /// Human moves first
+    First,
+    /// Human moves second
+    Second,
+    /// Engine plays both sides
+    None,
+}
@@ -0,0 +1,282 @@
+use std::fmt;
+
+use crate::{Game, Outcome, Player};
+
+///
+/// Number of columns on the board.
+///
+pub const WIDTH: usize = 7;
+
+///
+/// Number of rows on the board.
+///
+pub const HEIGHT: usize = 6;
+
+///
+/// Columns in the order they are searched. Center columns are usually better.
+///
+const MOVE_ORDER: [usize; WIDTH] = [3, 2, 4, 1, 5, 0, 6];
+
+///
+/// Scores for a window of four cells containing only stones of one player.
+/// Indexed by the number of stones in the window.
+///
+const WINDOW_SCORES: [i32; 4] = [0, 1, 10, 50];
+
+///
+/// ConnectFour struct representing a 7x6 board as bitboards.
+/// Description: https://github.com/denkspuren/BitboardC4/blob/master/BitboardDesign.md
+///
+/// Each column uses 7 bits, 6 for the cells from the bottom and one empty
+/// sentinel bit on top so alignments do not wrap between columns.
+/// `position` holds the stones of the player to move and `mask` all stones.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectFour {
+    position: u64,
+    mask: u64,
+    moves: usize,
+}
+
+impl Default for ConnectFour {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConnectFour {
+    ///
+    /// Creates an empty board with the first player to move.
+    ///
+    pub fn new() -> ConnectFour {
+        ConnectFour { position: 0, mask: 0, moves: 0 }
+    }
+
+    ///
+    /// Creates a board by playing the moves from the start position.
+    ///
+    /// moves: Columns 0-6 in the order they are played.
+    ///
+    /// result: The board or None if a move is illegal.
+    ///
+    pub fn from_moves(moves: &[usize]) -> Option<ConnectFour> {
+        let mut game = ConnectFour::new();
+        for &column in moves {
+            if column >= WIDTH || !game.can_play(column) || game.outcome().is_some() {
+                return None;
+            }
+            game.play(column);
+        }
+        Some(game)
+    }
+
+    ///
+    /// Returns true if the column is not full.
+    ///
+    pub fn can_play(&self, column: usize) -> bool {
+        self.mask & Self::top_mask(column) == 0
+    }
+
+    ///
+    /// Returns the player with a stone in the cell. Row 0 is the bottom row.
+    ///
+    pub fn cell(&self, column: usize, row: usize) -> Option<Player> {
+        let bit = Self::cell_mask(column, row);
+        if self.mask & bit == 0 {
+            None
+        } else if self.position & bit != 0 {
+            Some(self.current_player())
+        } else {
+            Some(self.current_player().opponent())
+        }
+    }
+
+    ///
+    /// Returns the bit of the cell.
+    ///
+    fn cell_mask(column: usize, row: usize) -> u64 {
+        1 << (column * (HEIGHT + 1) + row)
+    }
+
+    ///
+    /// Returns the bit of the top cell in the column.
+    ///
+    fn top_mask(column: usize) -> u64 {
+        Self::cell_mask(column, HEIGHT - 1)
+    }
+
+    ///
+    /// Returns the bit of the bottom cell in the column.
+    ///
+    fn bottom_mask(column: usize) -> u64 {
+        Self::cell_mask(column, 0)
+    }
+
+    ///
+    /// Returns true if the stones contain four in a row in any direction.
+    /// Shifting by 1 is vertical, by 7 horizontal, and by 6 and 8 the diagonals.
+    ///
+    fn has_alignment(stones: u64) -> bool {
+        [1, HEIGHT as u32, HEIGHT as u32 + 1, HEIGHT as u32 + 2].iter().any(|&shift| {
+            let pairs = stones & (stones >> shift);
+            pairs & (pairs >> (2 * shift)) != 0
+        })
+    }
+
+    ///
+    /// Scores all windows of four cells for the player to move.
+    ///
+    fn score_windows(&self) -> i32 {
+        let mut score = 0;
+        let directions: [(i32, i32); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+        for column in 0..WIDTH as i32 {
+            for row in 0..HEIGHT as i32 {
+                for (column_step, row_step) in directions {
+                    let end_column = column + 3 * column_step;
+                    let end_row = row + 3 * row_step;
+                    if !(0..WIDTH as i32).contains(&end_column) || !(0..HEIGHT as i32).contains(&end_row) {
+                        continue;
+                    }
+                    let (mut own, mut other) = (0, 0);
+                    for step in 0..4 {
+                        match self.cell((column + step * column_step) as usize, (row + step * row_step) as usize) {
+                            Some(player) if player == self.current_player() => own += 1,
+                            Some(_) => other += 1,
+                            None => {}
+                        }
+                    }
+                    if other == 0 {
+                        score += WINDOW_SCORES[own];
+                    } else if own == 0 {
+                        score -= WINDOW_SCORES[other];
+                    }
+                }
+            }
+        }
+        score
+    }
+}
+
+impl Game for ConnectFour {
+    type Move = usize;
+
+    fn current_player(&self) -> Player {
+        if self.moves.is_multiple_of(2) { Player::First } else { Player::Second }
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        if self.outcome().is_some() {
+            return Vec::new();
+        }
+        MOVE_ORDER.iter().copied().filter(|&column| self.can_play(column)).collect()
+    }
+
+    ///
+    /// Plays in the column. Adding the bottom bit to the mask makes the carry
+    /// move up to the first empty cell of the column.
+    ///
+    fn play(&mut self, column: usize) {
+        self.position ^= self.mask;
+        self.mask |= self.mask + Self::bottom_mask(column);
+        self.moves += 1;
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        // The stones not belonging to the player to move were placed by the last mover.
+        if Self::has_alignment(self.position ^ self.mask) {
+            Some(Outcome::Win(self.current_player().opponent()))
+        } else if self.moves == WIDTH * HEIGHT {
+            Some(Outcome::Draw)
+        } else {
+            None
+        }
+    }
+
+    fn evaluate(&self) -> i32 {
+        self.score_windows()
+    }
+
+    ///
+    /// position + mask is unique for every position since it adds one bit above
+    /// the top stone of each column to the stones of the player to move.
+    ///
+    fn key(&self) -> u64 {
+        self.position + self.mask
+    }
+
+    ///
+    /// Parses a column number 1-7.
+    ///
+    fn parse_move(&self, text: &str) -> Option<usize> {
+        let column = text.trim().parse::<usize>().ok()?.checked_sub(1)?;
+        if column < WIDTH && self.can_play(column) && self.outcome().is_none() { Some(column) } else { None }
+    }
+}
+
+impl fmt::Display for ConnectFour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in (0..HEIGHT).rev() {
+            let line: String = (0..WIDTH)
+                .map(|column| match self.cell(column, row) {
+                    Some(Player::First) => 'X',
+                    Some(Player::Second) => 'O',
+                    None => '.',
+                })
+                .map(|symbol| format!(" {symbol}"))
+                .collect();
+            writeln!(f, "|{line} |")?;
+        }
+        let numbers: String = (1..=WIDTH).map(|column| format!(" {column}")).collect();
+        writeln!(f, " {numbers}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vertical_win() {
+        let game = ConnectFour::from_moves(&[0, 1, 0, 1, 0, 1, 0]).unwrap();
+        assert_eq!(game.outcome(), Some(Outcome::Win(Player::First)));
+    }
+
+    #[test]
+    fn test_horizontal_win() {
+        let game = ConnectFour::from_moves(&[0, 0, 1, 1, 2, 2, 3]).unwrap();
+        assert_eq!(game.outcome(), Some(Outcome::Win(Player::First)));
+    }
+
+    #[test]
+    fn test_diagonal_win() {
+        let game = ConnectFour::from_moves(&[0, 1, 1, 2, 2, 3, 2, 3, 3, 6, 3]).unwrap();
+        assert_eq!(game.outcome(), Some(Outcome::Win(Player::First)));
+    }
+
+    #[test]
+    fn test_no_wrap_between_columns() {
+        // X at the top of column 0 and the bottom of column 1 are consecutive bits
+        // without the sentinel, but not aligned on the board.
+        let game = ConnectFour::from_moves(&[1, 0, 1, 0, 0, 0, 0, 6, 0]).unwrap();
+        assert_eq!(game.cell(0, 5), Some(Player::First));
+        assert_eq!(game.cell(1, 0), Some(Player::First));
+        assert_eq!(game.outcome(), None);
+    }
+
+    #[test]
+    fn test_full_column() {
+        let game = ConnectFour::from_moves(&[0, 0, 0, 0, 0, 0]).unwrap();
+        assert!(!game.can_play(0));
+        assert!(!game.legal_moves().contains(&0));
+        assert_eq!(game.parse_move("1"), None);
+        assert_eq!(game.parse_move("2"), Some(1));
+    }
+
+    #[test]
+    fn test_cells() {
+        let game = ConnectFour::from_moves(&[3, 3]).unwrap();
+        assert_eq!(game.cell(3, 0), Some(Player::First));
+        assert_eq!(game.cell(3, 1), Some(Player::Second));
+        assert_eq!(game.cell(3, 2), None);
+    }
+}
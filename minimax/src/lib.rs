@@ -0,0 +1,524 @@
+///
+/// Game tree search using minimax.
+/// Description: https://en.wikipedia.org/wiki/Minimax
+///
+/// The engine searches in negamax form, where the score is always seen from the
+/// player to move, with alpha-beta pruning, iterative deepening and a
+/// transposition table. The games implement the Game trait.
+///
+pub mod connect_four;
+pub mod tictactoe;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+///
+/// Score of a won position. Wins found closer to the root get a higher score
+/// so the engine prefers the fastest win and the slowest loss.
+///
+pub const WIN_SCORE: i32 = 1_000_000;
+
+///
+/// Scores above this value (or below the negative value) are forced wins (or losses).
+///
+const WIN_THRESHOLD: i32 = WIN_SCORE - 1_000;
+
+///
+/// Score used as infinity for the alpha-beta window.
+///
+const INFINITY: i32 = i32::MAX - 1;
+
+///
+/// Default maximum number of entries in the transposition table.
+///
+const DEFAULT_TABLE_ENTRIES: usize = 1 << 20;
+
+///
+/// Player enum for two player games.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Player {
+    // The player making the first move.
+    First,
+    // The player making the second move.
+    Second,
+}
+
+impl Player {
+    ///
+    /// Returns the other player.
+    ///
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::First => Player::Second,
+            Player::Second => Player::First,
+        }
+    }
+}
+
+///
+/// Outcome of a finished game.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win(Player),
+    Draw,
+}
+
+///
+/// Game is the interface the engine uses to search a two player, zero-sum game
+/// with perfect information.
+///
+pub trait Game: Clone + fmt::Display {
+    type Move: Copy + PartialEq + fmt::Debug;
+
+    ///
+    /// Returns the player to move.
+    ///
+    fn current_player(&self) -> Player;
+
+    ///
+    /// Returns the legal moves. The engine searches them in this order, so
+    /// returning the most promising moves first makes pruning more effective.
+    ///
+    fn legal_moves(&self) -> Vec<Self::Move>;
+
+    ///
+    /// Plays the move for the current player. The move must be legal.
+    ///
+    fn play(&mut self, game_move: Self::Move);
+
+    ///
+    /// Returns the outcome if the game is finished.
+    ///
+    fn outcome(&self) -> Option<Outcome>;
+
+    ///
+    /// Heuristic score of an unfinished position, seen from the player to move.
+    /// Must stay well below WIN_SCORE in absolute value.
+    ///
+    fn evaluate(&self) -> i32;
+
+    ///
+    /// Key uniquely identifying the position, used by the transposition table.
+    ///
+    fn key(&self) -> u64;
+
+    ///
+    /// Parses a move entered by a human. Returns None if the text is not a legal move.
+    ///
+    fn parse_move(&self, text: &str) -> Option<Self::Move>;
+}
+
+///
+/// Bound enum telling how a stored score relates to the real score of the position.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    // The score is exact.
+    Exact,
+    // The real score is at least the stored score (beta cutoff).
+    Lower,
+    // The real score is at most the stored score (no move raised alpha).
+    Upper,
+}
+
+///
+/// Entry in the transposition table.
+///
+#[derive(Debug, Clone, Copy)]
+struct Entry<M> {
+    // Remaining depth the score was searched to.
+    depth: usize,
+    // Score relative to the stored position.
+    score: i32,
+    bound: Bound,
+    // Best move found, searched first when the position is seen again.
+    best_move: Option<M>,
+}
+
+///
+/// Result of a search.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchResult<M> {
+    // The best move found. None if the game is already finished.
+    pub best_move: Option<M>,
+    // Score of the best move seen from the player to move.
+    pub score: i32,
+    // Deepest completed iteration.
+    pub depth: usize,
+    // Number of positions visited in all iterations.
+    pub nodes: u64,
+}
+
+impl<M> SearchResult<M> {
+    ///
+    /// Returns the number of moves (plies) to a forced win or loss, if one was found.
+    ///
+    pub fn plies_to_end(&self) -> Option<i32> {
+        if self.score.abs() > WIN_THRESHOLD { Some(WIN_SCORE - self.score.abs()) } else { None }
+    }
+}
+
+///
+/// Engine using negamax with alpha-beta pruning, iterative deepening and
+/// a transposition table.
+///
+pub struct Engine<G: Game> {
+    table: HashMap<u64, Entry<G::Move>>,
+    max_table_entries: usize,
+    nodes: u64,
+    deadline: Option<Instant>,
+    stopped: bool,
+}
+
+impl<G: Game> Default for Engine<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G: Game> Engine<G> {
+    ///
+    /// Creates a new engine with the default transposition table size.
+    ///
+    pub fn new() -> Engine<G> {
+        Self::with_table_entries(DEFAULT_TABLE_ENTRIES)
+    }
+
+    ///
+    /// Creates a new engine limiting the transposition table to the number of entries.
+    /// The table is cleared when it is full. 0 disables the table.
+    ///
+    pub fn with_table_entries(max_table_entries: usize) -> Engine<G> {
+        Engine { table: HashMap::new(), max_table_entries, nodes: 0, deadline: None, stopped: false }
+    }
+
+    ///
+    /// Removes all entries from the transposition table.
+    ///
+    pub fn clear(&mut self) {
+        self.table.clear();
+    }
+
+    ///
+    /// Searches the position using iterative deepening. Each iteration searches one ply
+    /// deeper and the transposition table makes the earlier iterations improve the
+    /// move ordering of the later ones.
+    ///
+    /// game: The position to search.
+    /// max_depth: Maximum number of plies to search.
+    /// time_limit: Stop searching when the time is up. The result from the last completed
+    ///             iteration is returned. Depth 1 is always completed.
+    ///
+    /// result: The best move and its score.
+    ///
+    pub fn search(&mut self, game: &G, max_depth: usize, time_limit: Option<Duration>) -> SearchResult<G::Move> {
+        self.nodes = 0;
+        self.stopped = false;
+        self.deadline = None;
+        let mut result = SearchResult { best_move: None, score: 0, depth: 0, nodes: 0 };
+        if game.outcome().is_some() {
+            result.score = terminal_score(game, 0);
+            return result;
+        }
+        for depth in 1..=max_depth.max(1) {
+            // The deadline only applies after the first iteration so a move is always found.
+            if depth == 2 {
+                self.deadline = time_limit.map(|limit| Instant::now() + limit);
+            }
+            let (best_move, score) = self.search_root(game, depth);
+            if self.stopped {
+                break;
+            }
+            result = SearchResult { best_move, score, depth, nodes: self.nodes };
+            if score.abs() > WIN_THRESHOLD {
+                break;
+            }
+        }
+        result.nodes = self.nodes;
+        result
+    }
+
+    ///
+    /// Searches all moves in the root position.
+    ///
+    /// result: The best move and its score.
+    ///
+    fn search_root(&mut self, game: &G, depth: usize) -> (Option<G::Move>, i32) {
+        let mut alpha = -INFINITY;
+        let mut best_move = None;
+        for game_move in self.ordered_moves(game) {
+            let mut child = game.clone();
+            child.play(game_move);
+            let score = -self.negamax(&child, depth - 1, 1, -INFINITY, -alpha);
+            if self.stopped {
+                return (best_move, alpha);
+            }
+            if score > alpha || best_move.is_none() {
+                alpha = score;
+                best_move = Some(game_move);
+            }
+        }
+        self.store(game.key(), Entry { depth, score: alpha, bound: Bound::Exact, best_move }, 0);
+        (best_move, alpha)
+    }
+
+    ///
+    /// Negamax search with alpha-beta pruning.
+    ///
+    /// game: The position to search.
+    /// depth: Remaining plies to search.
+    /// ply: Distance from the root, used to prefer faster wins.
+    /// alpha: Lower bound of the window.
+    /// beta: Upper bound of the window.
+    ///
+    /// result: Score of the position from the player to move.
+    ///
+    fn negamax(&mut self, game: &G, depth: usize, ply: i32, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(4096) && self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            self.stopped = true;
+        }
+        if self.stopped {
+            return 0;
+        }
+        if game.outcome().is_some() {
+            return terminal_score(game, ply);
+        }
+        if depth == 0 {
+            return game.evaluate();
+        }
+        let key = game.key();
+        let original_alpha = alpha;
+        let mut table_move = None;
+        if let Some(entry) = self.table.get(&key) {
+            table_move = entry.best_move;
+            if entry.depth >= depth {
+                let score = Self::score_from_table(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        for game_move in Self::order_with(game.legal_moves(), table_move) {
+            let mut child = game.clone();
+            child.play(game_move);
+            let score = -self.negamax(&child, depth - 1, ply + 1, -beta, -alpha);
+            if score > best_score {
+                best_score = score;
+                best_move = Some(game_move);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        if !self.stopped {
+            let bound = if best_score <= original_alpha {
+                Bound::Upper
+            } else if best_score >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            self.store(key, Entry { depth, score: best_score, bound, best_move }, ply);
+        }
+        best_score
+    }
+
+    ///
+    /// Returns the legal moves with the move from the transposition table first.
+    ///
+    fn ordered_moves(&self, game: &G) -> Vec<G::Move> {
+        let table_move = self.table.get(&game.key()).and_then(|entry| entry.best_move);
+        Self::order_with(game.legal_moves(), table_move)
+    }
+
+    ///
+    /// Moves the first move to the front of the list.
+    ///
+    fn order_with(mut moves: Vec<G::Move>, first: Option<G::Move>) -> Vec<G::Move> {
+        if let Some(first) = first
+            && let Some(position) = moves.iter().position(|&game_move| game_move == first)
+        {
+            moves[..=position].rotate_right(1);
+        }
+        moves
+    }
+
+    ///
+    /// Stores the entry, converting win scores to be relative to the stored position.
+    ///
+    fn store(&mut self, key: u64, mut entry: Entry<G::Move>, ply: i32) {
+        if self.max_table_entries == 0 {
+            return;
+        }
+        if self.table.len() >= self.max_table_entries {
+            self.table.clear();
+        }
+        entry.score = Self::score_to_table(entry.score, ply);
+        self.table.insert(key, entry);
+    }
+
+    ///
+    /// Win scores depend on the distance from the root. In the table they are stored
+    /// as the distance from the position itself.
+    ///
+    fn score_to_table(score: i32, ply: i32) -> i32 {
+        if score > WIN_THRESHOLD {
+            score + ply
+        } else if score < -WIN_THRESHOLD {
+            score - ply
+        } else {
+            score
+        }
+    }
+
+    ///
+    /// Reverses score_to_table.
+    ///
+    fn score_from_table(score: i32, ply: i32) -> i32 {
+        if score > WIN_THRESHOLD {
+            score - ply
+        } else if score < -WIN_THRESHOLD {
+            score + ply
+        } else {
+            score
+        }
+    }
+}
+
+///
+/// Score of a finished game seen from the player to move.
+///
+fn terminal_score<G: Game>(game: &G, ply: i32) -> i32 {
+    match game.outcome() {
+        Some(Outcome::Win(player)) if player == game.current_player() => WIN_SCORE - ply,
+        Some(Outcome::Win(_)) => -(WIN_SCORE - ply),
+        _ => 0,
+    }
+}
+
+///
+/// Plain minimax without pruning or transposition table, in negamax form.
+/// Visits every node of the tree, so it is only useful for small games and
+/// as a reference for testing the engine.
+///
+/// game: The position to search.
+/// depth: Number of plies to search.
+///
+/// result: Score of the position from the player to move.
+///
+pub fn minimax<G: Game>(game: &G, depth: usize) -> i32 {
+    minimax_ply(game, depth, 0)
+}
+
+///
+/// Recursive part of minimax keeping track of the distance from the root.
+///
+fn minimax_ply<G: Game>(game: &G, depth: usize, ply: i32) -> i32 {
+    if game.outcome().is_some() {
+        return terminal_score(game, ply);
+    }
+    if depth == 0 {
+        return game.evaluate();
+    }
+    game.legal_moves()
+        .into_iter()
+        .map(|game_move| {
+            let mut child = game.clone();
+            child.play(game_move);
+            -minimax_ply(&child, depth - 1, ply + 1)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connect_four::ConnectFour;
+    use crate::tictactoe::TicTacToe;
+
+    #[test]
+    fn test_tictactoe_is_draw() {
+        let game = TicTacToe::new();
+        let mut engine = Engine::new();
+        let result = engine.search(&game, 9, None);
+        assert_eq!(result.score, 0);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn test_alpha_beta_matches_minimax() {
+        let mut game = TicTacToe::new();
+        game.play(0);
+        game.play(4);
+        game.play(8);
+        let mut engine = Engine::new();
+        let result = engine.search(&game, 9, None);
+        assert_eq!(result.score, minimax(&game, 9));
+    }
+
+    #[test]
+    fn test_finds_fastest_win() {
+        // X has two ways to win, one immediate.
+        let game = TicTacToe::from_moves(&[0, 3, 1, 4]);
+        let mut engine = Engine::new();
+        let result = engine.search(&game, 9, None);
+        assert_eq!(result.best_move, Some(2));
+        assert_eq!(result.plies_to_end(), Some(1));
+    }
+
+    #[test]
+    fn test_blocks_loss() {
+        // O must block the column 0, 3, 6.
+        let game = TicTacToe::from_moves(&[0, 4, 3]);
+        let mut engine = Engine::new();
+        let result = engine.search(&game, 9, None);
+        assert_eq!(result.best_move, Some(6));
+    }
+
+    #[test]
+    fn test_connect_four_immediate_win() {
+        let game = ConnectFour::from_moves(&[3, 0, 3, 0, 3, 0]).unwrap();
+        let mut engine = Engine::new();
+        let result = engine.search(&game, 6, None);
+        assert_eq!(result.best_move, Some(3));
+        assert_eq!(result.plies_to_end(), Some(1));
+    }
+
+    #[test]
+    fn test_connect_four_blocks_threat() {
+        let game = ConnectFour::from_moves(&[0, 3, 0, 3, 6, 3]).unwrap();
+        let mut engine = Engine::new();
+        let result = engine.search(&game, 6, None);
+        assert_eq!(result.best_move, Some(3));
+    }
+
+    #[test]
+    fn test_time_limit_returns_move() {
+        let game = ConnectFour::new();
+        let mut engine = Engine::new();
+        let result = engine.search(&game, 42, Some(Duration::from_millis(50)));
+        assert!(result.best_move.is_some());
+        assert!(result.depth >= 1 && result.depth < 42);
+    }
+
+    #[test]
+    fn test_transposition_table_reduces_nodes() {
+        let game = ConnectFour::new();
+        let with_table = Engine::new().search(&game, 8, None);
+        let without_table = Engine::with_table_entries(0).search(&game, 8, None);
+        assert_eq!(with_table.score, without_table.score);
+        assert!(with_table.nodes < without_table.nodes);
+    }
+}
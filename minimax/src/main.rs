@@ -0,0 +1,85 @@
+mod args;
+
+use std::io::BufRead;
+use std::time::Duration;
+
+use args::{Args, GameKind, Human};
+use clap::Parser;
+use minimax::connect_four::ConnectFour;
+use minimax::tictactoe::TicTacToe;
+use minimax::{Engine, Game, Outcome, Player};
+
+/**
+ * Plays tic-tac-toe or Connect Four against the minimax engine in the terminal.
+ */
+fn main() {
+    let args = Args::parse();
+    let time_limit = args.time_ms.map(Duration::from_millis);
+    match args.game {
+        GameKind::Tictactoe => play(TicTacToe::new(), args.human, args.depth, time_limit),
+        GameKind::ConnectFour => play(ConnectFour::new(), args.human, args.depth, time_limit),
+    }
+}
+
+/**
+ * Runs the game loop until the game is finished or stdin is closed.
+ *
+ * # Arguments
+ * * `game`: The start position.
+ * * `human`: The player controlled from stdin.
+ * * `depth`: Maximum search depth for the engine.
+ * * `time_limit`: Time limit per engine move.
+ */
+fn play<G: Game>(mut game: G, human: Human, depth: usize, time_limit: Option<Duration>) {
+    let mut engine: Engine<G> = Engine::new();
+    let mut lines = std::io::stdin().lock().lines();
+    while game.outcome().is_none() {
+        println!("{game}");
+        let game_move = if is_human(human, game.current_player()) {
+            match read_move(&game, &mut lines) {
+                Some(game_move) => game_move,
+                None => return,
+            }
+        } else {
+            let result = engine.search(&game, depth, time_limit);
+            let game_move = result.best_move.expect("Unfinished game must have a move");
+            let score = match result.plies_to_end() {
+                Some(plies) if result.score > 0 => format!("win in {plies}"),
+                Some(plies) => format!("loss in {plies}"),
+                None => result.score.to_string(),
+            };
+            println!("Engine plays {game_move:?} (score: {score}, depth: {}, nodes: {})", result.depth, result.nodes);
+            game_move
+        };
+        game.play(game_move);
+    }
+    println!("{game}");
+    match game.outcome() {
+        Some(Outcome::Win(player)) => println!("{player:?} player wins"),
+        _ => println!("Draw"),
+    }
+}
+
+/**
+ * Returns true if the player is controlled by the human.
+ */
+fn is_human(human: Human, player: Player) -> bool {
+    matches!((human, player), (Human::First, Player::First) | (Human::Second, Player::Second))
+}
+
+/**
+ * Reads lines from stdin until a legal move is entered.
+ *
+ * # Returns
+ * The move, or None if stdin was closed.
+ */
+fn read_move<G: Game>(game: &G, lines: &mut impl Iterator<Item = std::io::Result<String>>) -> Option<G::Move> {
+    loop {
+        println!("Your move:");
+        let line = lines.next()?.ok()?;
+        match game.parse_move(&line) {
+            Some(game_move) => return Some(game_move),
+            None => eprintln!("Illegal move: {}", line.trim()),
+        }
+    }
+}
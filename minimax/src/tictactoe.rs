@@ -0,0 +1,174 @@
+use std::fmt;
+
+use crate::{Game, Outcome, Player};
+
+///
+/// The eight lines of three cells that win the game.
+///
+const LINES: [[usize; 3]; 8] = [[0, 1, 2], [3, 4, 5], [6, 7, 8], [0, 3, 6], [1, 4, 7], [2, 5, 8], [0, 4, 8], [2, 4, 6]];
+
+///
+/// Cells in the order they are searched. Center and corners first.
+///
+const MOVE_ORDER: [usize; 9] = [4, 0, 2, 6, 8, 1, 3, 5, 7];
+
+///
+/// TicTacToe struct representing a 3x3 board. Cells are numbered 0-8 in
+/// row-major order. The first player is X and the second player is O.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TicTacToe {
+    cells: [Option<Player>; 9],
+    to_move: Player,
+}
+
+impl Default for TicTacToe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TicTacToe {
+    ///
+    /// Creates an empty board with X to move.
+    ///
+    pub fn new() -> TicTacToe {
+        TicTacToe { cells: [None; 9], to_move: Player::First }
+    }
+
+    ///
+    /// Creates a board by playing the moves from the start position.
+    ///
+    /// moves: Cells 0-8 in the order they are played. Must be legal.
+    ///
+    pub fn from_moves(moves: &[usize]) -> TicTacToe {
+        let mut game = TicTacToe::new();
+        for &cell in moves {
+            game.play(cell);
+        }
+        game
+    }
+
+    ///
+    /// Returns the player occupying the cell.
+    ///
+    pub fn cell(&self, cell: usize) -> Option<Player> {
+        self.cells[cell]
+    }
+
+    ///
+    /// Returns the player owning a complete line.
+    ///
+    fn winner(&self) -> Option<Player> {
+        LINES.iter().find_map(|line| match (self.cells[line[0]], self.cells[line[1]], self.cells[line[2]]) {
+            (Some(a), Some(b), Some(c)) if a == b && b == c => Some(a),
+            _ => None,
+        })
+    }
+}
+
+impl Game for TicTacToe {
+    type Move = usize;
+
+    fn current_player(&self) -> Player {
+        self.to_move
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        if self.winner().is_some() {
+            return Vec::new();
+        }
+        MOVE_ORDER.iter().copied().filter(|&cell| self.cells[cell].is_none()).collect()
+    }
+
+    fn play(&mut self, cell: usize) {
+        self.cells[cell] = Some(self.to_move);
+        self.to_move = self.to_move.opponent();
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        match self.winner() {
+            Some(player) => Some(Outcome::Win(player)),
+            None if self.cells.iter().all(Option::is_some) => Some(Outcome::Draw),
+            None => None,
+        }
+    }
+
+    ///
+    /// Counts the lines still open for each player. The game is small enough to be
+    /// searched to the end, so this is only used with a limited depth.
+    ///
+    fn evaluate(&self) -> i32 {
+        let open_lines = |player: Player| LINES.iter().filter(|line| line.iter().all(|&cell| self.cells[cell] != Some(player.opponent()))).count() as i32;
+        open_lines(self.to_move) - open_lines(self.to_move.opponent())
+    }
+
+    ///
+    /// The board encoded as a base 3 number. The player to move follows from
+    /// the number of stones, so it does not need to be part of the key.
+    ///
+    fn key(&self) -> u64 {
+        self.cells.iter().fold(0, |key, cell| {
+            key * 3
+                + match cell {
+                    None => 0,
+                    Some(Player::First) => 1,
+                    Some(Player::Second) => 2,
+                }
+        })
+    }
+
+    ///
+    /// Parses a cell number 1-9, counted row by row from the top left.
+    ///
+    fn parse_move(&self, text: &str) -> Option<usize> {
+        let cell = text.trim().parse::<usize>().ok()?.checked_sub(1)?;
+        if cell < 9 && self.legal_moves().contains(&cell) { Some(cell) } else { None }
+    }
+}
+
+impl fmt::Display for TicTacToe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..3 {
+            if row > 0 {
+                writeln!(f, "---+---+---")?;
+            }
+            let cells: Vec<String> = (0..3)
+                .map(|column| match self.cells[row * 3 + column] {
+                    Some(Player::First) => " X ".to_string(),
+                    Some(Player::Second) => " O ".to_string(),
+                    None => format!(" {} ", row * 3 + column + 1),
+                })
+                .collect();
+            writeln!(f, "{}", cells.join("|"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_win_detection() {
+        let game = TicTacToe::from_moves(&[0, 3, 1, 4, 2]);
+        assert_eq!(game.outcome(), Some(Outcome::Win(Player::First)));
+        assert!(game.legal_moves().is_empty());
+    }
+
+    #[test]
+    fn test_draw_detection() {
+        let game = TicTacToe::from_moves(&[0, 1, 2, 4, 3, 5, 7, 6, 8]);
+        assert_eq!(game.outcome(), Some(Outcome::Draw));
+    }
+
+    #[test]
+    fn test_parse_move() {
+        let game = TicTacToe::from_moves(&[4]);
+        assert_eq!(game.parse_move("1"), Some(0));
+        assert_eq!(game.parse_move("5"), None);
+        assert_eq!(game.parse_move("10"), None);
+        assert_eq!(game.parse_move("x"), None);
+    }
+}
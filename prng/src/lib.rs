@@ -0,0 +1,201 @@
+use std::ops::Range;
+
+///
+/// Rng is the common interface for the pseudo random number generators in this crate.
+/// Only next_u64 has to be implemented, the other methods are derived from it.
+///
+/// The generators are deterministic and not suitable for cryptographic use. They are
+/// intended for simulations, puzzle generation and sampling where reproducible
+/// results from a seed are wanted.
+///
+pub trait Rng {
+    ///
+    /// Returns the next 64 bit value from the generator.
+    ///
+    fn next_u64(&mut self) -> u64;
+
+    ///
+    /// Returns the next 32 bit value. The upper half of next_u64 is used since
+    /// it has the best statistical quality for the generators in this crate.
+    ///
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    ///
+    /// Returns a float uniformly distributed in [0, 1).
+    /// The 53 upper bits are used to fill the mantissa.
+    ///
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    ///
+    /// Returns a value uniformly distributed in the range.
+    /// Uses rejection sampling to avoid modulo bias.
+    ///
+    /// range: The half open range to sample from. Must not be empty.
+    ///
+    /// result: A value in the range.
+    ///
+    fn gen_range(&mut self, range: Range<u64>) -> u64 {
+        assert!(range.start < range.end, "Range must not be empty");
+        let span = range.end - range.start;
+        let zone = u64::MAX - (u64::MAX % span);
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return range.start + value % span;
+            }
+        }
+    }
+
+    ///
+    /// Returns an index in 0..len. Convenience wrapper around gen_range.
+    ///
+    fn gen_index(&mut self, len: usize) -> usize {
+        self.gen_range(0..len as u64) as usize
+    }
+
+    ///
+    /// Returns true with the given probability.
+    ///
+    fn gen_bool(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+
+    ///
+    /// Shuffles the slice in place using the Fisher-Yates algorithm.
+    ///
+    fn shuffle<T>(&mut self, data: &mut [T]) {
+        for idx in (1..data.len()).rev() {
+            let other = self.gen_index(idx + 1);
+            data.swap(idx, other);
+        }
+    }
+}
+
+///
+/// SplitMix64 generator. Very small and fast, mainly used for seeding
+/// other generators from a single 64 bit value.
+///
+/// Description: https://prng.di.unimi.it/splitmix64.c
+///
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    ///
+    /// Creates a new generator from the seed.
+    ///
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+}
+
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+///
+/// Xoshiro256** generator. General purpose generator with a 256 bit state.
+///
+/// Description: https://prng.di.unimi.it/xoshiro256starstar.c
+///
+#[derive(Debug, Clone)]
+pub struct Xoshiro256StarStar {
+    state: [u64; 4],
+}
+
+impl Xoshiro256StarStar {
+    ///
+    /// Creates a new generator where the state is filled using SplitMix64.
+    /// This is the seeding recommended by the authors.
+    ///
+    pub fn seed_from_u64(seed: u64) -> Xoshiro256StarStar {
+        let mut seeder = SplitMix64::new(seed);
+        let state = [seeder.next_u64(), seeder.next_u64(), seeder.next_u64(), seeder.next_u64()];
+        Xoshiro256StarStar { state }
+    }
+
+    ///
+    /// Creates a new generator seeded from the current time and process id.
+    /// Useful when reproducibility is not needed.
+    ///
+    pub fn from_entropy() -> Xoshiro256StarStar {
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|duration| duration.as_nanos() as u64).unwrap_or(0);
+        Xoshiro256StarStar::seed_from_u64(nanos ^ ((std::process::id() as u64) << 32))
+    }
+}
+
+impl Rng for Xoshiro256StarStar {
+    fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let temp = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= temp;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_splitmix64_reference() {
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(rng.next_u64(), 0xe220a8397b1dcdaf);
+        assert_eq!(rng.next_u64(), 0x6e789e6aa1b965f4);
+        assert_eq!(rng.next_u64(), 0x06c45d188009454f);
+    }
+
+    #[test]
+    fn test_xoshiro256starstar_reference() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(0);
+        assert_eq!(rng.next_u64(), 11091344671253066420);
+        assert_eq!(rng.next_u64(), 13793997310169335082);
+        assert_eq!(rng.next_u64(), 1900383378846508768);
+    }
+
+    #[test]
+    fn test_gen_range_bounds() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(42);
+        for _ in 0..1000 {
+            let value = rng.gen_range(10..17);
+            assert!((10..17).contains(&value));
+        }
+    }
+
+    #[test]
+    fn test_next_f64_bounds() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(42);
+        for _ in 0..1000 {
+            let value = rng.next_f64();
+            assert!((0.0..1.0).contains(&value));
+        }
+    }
+
+    #[test]
+    fn test_shuffle_is_permutation() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7);
+        let mut data: Vec<u32> = (0..50).collect();
+        rng.shuffle(&mut data);
+        let mut sorted = data.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..50).collect::<Vec<u32>>());
+        assert_ne!(data, sorted);
+    }
+}
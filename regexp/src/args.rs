@@ -0,0 +1,41 @@
+use clap::{Parser, ValueEnum};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Regular expression to search for
+    #[arg(short, long)]
+    pub pattern: String,
+
+    /// File to search. Reads stdin if not specified
+    #[arg(short, long)]
+    pub input_file: Option<String>,
+
+    /// Engine used for searching
+    #[arg(short, long, value_enum, default_value_t = Engine::Nfa)]
+    pub engine: Engine,
+
+    /// Print the number of matching lines instead of the lines
+    #[arg(short, long)]
+    pub count: bool,
+
+    /// Print the lines that do not match
+    #[arg(short = 'v', long)]
+    pub invert: bool,
+
+    /// Print only the matched parts of the lines
+    #[arg(short, long)]
+    pub only_matching: bool,
+
+    /// Print the number of NFA and DFA states to stderr
+    #[arg(short, long)]
+    pub statistics: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Engine {
+    /// Pike VM simulating the NFA, leftmost first matches
+    Nfa,
+    /// Compiled DFA, leftmost longest matches
+    Dfa,
+}
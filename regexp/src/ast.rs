@@ -0,0 +1,172 @@
+//!
+//! Syntax tree and character classes for parsed patterns.
+//!
+
+///
+/// CharClass struct with a set of characters as sorted, non overlapping and
+/// non adjacent inclusive ranges.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CharClass {
+    ranges: Vec<(char, char)>,
+}
+
+impl CharClass {
+    ///
+    /// Creates a class from ranges in any order. Overlapping and adjacent ranges are merged.
+    ///
+    pub fn new(mut ranges: Vec<(char, char)>) -> CharClass {
+        ranges.sort();
+        let mut merged: Vec<(char, char)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some((_, last_end)) if (start as u32) <= *last_end as u32 + 1 => *last_end = (*last_end).max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        CharClass { ranges: merged }
+    }
+
+    pub fn single(character: char) -> CharClass {
+        CharClass { ranges: vec![(character, character)] }
+    }
+
+    ///
+    /// Returns the class matching every character.
+    ///
+    pub fn any() -> CharClass {
+        CharClass { ranges: vec![('\0', char::MAX)] }
+    }
+
+    ///
+    /// Returns the class used for ., every character except newline.
+    ///
+    pub fn any_except_newline() -> CharClass {
+        CharClass::single('\n').negate()
+    }
+
+    ///
+    /// Returns the ASCII digits, \d.
+    ///
+    pub fn digit() -> CharClass {
+        CharClass::new(vec![('0', '9')])
+    }
+
+    ///
+    /// Returns the ASCII word characters, \w.
+    ///
+    pub fn word() -> CharClass {
+        CharClass::new(vec![('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')])
+    }
+
+    ///
+    /// Returns the ASCII whitespace, \s.
+    ///
+    pub fn space() -> CharClass {
+        CharClass::new(vec![('\t', '\r'), (' ', ' ')])
+    }
+
+    pub fn ranges(&self) -> &[(char, char)] {
+        &self.ranges
+    }
+
+    pub fn contains(&self, character: char) -> bool {
+        let index = self.ranges.partition_point(|&(_, end)| end < character);
+        self.ranges.get(index).is_some_and(|&(start, _)| start <= character)
+    }
+
+    ///
+    /// Returns the characters not in the class. The surrogate code points are
+    /// skipped since they are not valid characters.
+    ///
+    pub fn negate(&self) -> CharClass {
+        let mut ranges = Vec::new();
+        let mut next = Some('\0');
+        for &(start, end) in &self.ranges {
+            if let Some(from) = next.filter(|&from| from < start) {
+                ranges.push((from, previous_char(start)));
+            }
+            next = next_char(end);
+        }
+        if let Some(from) = next {
+            ranges.push((from, char::MAX));
+        }
+        CharClass { ranges }
+    }
+
+    pub fn union(&self, other: &CharClass) -> CharClass {
+        CharClass::new(self.ranges.iter().chain(other.ranges.iter()).copied().collect())
+    }
+}
+
+///
+/// Returns the character after the character, skipping the surrogates.
+///
+pub fn next_char(character: char) -> Option<char> {
+    match character {
+        '\u{D7FF}' => Some('\u{E000}'),
+        char::MAX => None,
+        _ => char::from_u32(character as u32 + 1),
+    }
+}
+
+///
+/// Returns the character before the character, skipping the surrogates. Must not be called with '\0'.
+///
+fn previous_char(character: char) -> char {
+    match character {
+        '\u{E000}' => '\u{D7FF}',
+        _ => char::from_u32(character as u32 - 1).expect("Not a surrogate"),
+    }
+}
+
+///
+/// Assertion enum for the positions matched by anchors.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Assertion {
+    // ^, the start of the text.
+    Start,
+    // $, the end of the text.
+    End,
+}
+
+///
+/// Ast enum for the syntax tree of a pattern.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ast {
+    // Matches the empty string.
+    Empty,
+    // Matches one character in the class. Literals are classes with one character.
+    Class(CharClass),
+    // Matches the empty string at a position.
+    Assertion(Assertion),
+    // Matches the parts one after another.
+    Concat(Vec<Ast>),
+    // Matches one of the alternatives, preferring the first.
+    Alternate(Vec<Ast>),
+    // Matches the expression between min and max times. Greedy repetitions prefer more matches.
+    Repeat { ast: Box<Ast>, min: u32, max: Option<u32>, greedy: bool },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_ranges() {
+        let class = CharClass::new(vec![('d', 'f'), ('a', 'c'), ('x', 'z'), ('y', 'y')]);
+        assert_eq!(class.ranges(), &[('a', 'f'), ('x', 'z')]);
+        assert!(class.contains('e'));
+        assert!(!class.contains('g'));
+    }
+
+    #[test]
+    fn test_negate() {
+        let class = CharClass::new(vec![('b', 'c')]).negate();
+        assert_eq!(class.ranges(), &[('\0', 'a'), ('d', char::MAX)]);
+        assert_eq!(class.negate(), CharClass::new(vec![('b', 'c')]));
+        assert_eq!(CharClass::any().negate().ranges(), &[]);
+    }
+}
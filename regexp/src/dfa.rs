@@ -0,0 +1,323 @@
+//!
+//! Compilation of the automaton into a deterministic finite automaton with the
+//! subset construction. Each DFA state is the set of NFA states the simulation
+//! can be in, so matching only needs one table lookup per character.
+//! Description: https://en.wikipedia.org/wiki/Powerset_construction
+//!
+//! Characters are grouped into classes that no pattern class tells apart, so the
+//! transition table has one column per class instead of one per character.
+//! The number of states can grow exponentially with the pattern, so compilation
+//! stops with an error after a limit.
+//!
+
+use std::collections::HashMap;
+
+use crate::ast::{Assertion, next_char};
+use crate::nfa::{Nfa, State};
+use crate::{Match, RegexError};
+
+///
+/// Default maximum number of DFA states.
+///
+pub const DEFAULT_MAX_STATES: usize = 10_000;
+
+///
+/// The state without any NFA states, which can never match.
+///
+const DEAD: usize = 0;
+
+///
+/// Dfa struct with the transition table.
+///
+#[derive(Debug, Clone)]
+pub struct Dfa {
+    // Start of each character class, sorted. The first is always 0.
+    boundaries: Vec<u32>,
+    // Transitions for each state, one per character class.
+    transitions: Vec<Vec<usize>>,
+    // True for the states containing the match state.
+    accepting: Vec<bool>,
+    // True for the states that match if the text ends here, which is when $ assertions hold.
+    accepting_at_end: Vec<bool>,
+    // Anchored start states at the start of the text, where ^ holds, and elsewhere.
+    start_text: usize,
+    start_inside: usize,
+    // Unanchored start state at the start of the text. Every state reached from it
+    // also contains the anchored start, so a match can begin at any position.
+    start_search: usize,
+    // True if the empty text matches, where both ^ and $ hold at the same position.
+    matches_empty_text: bool,
+}
+
+impl Dfa {
+    ///
+    /// Compiles the automaton.
+    ///
+    /// nfa: The automaton to compile.
+    /// max_states: Maximum number of DFA states.
+    ///
+    /// result: The DFA or an error if it needs more than max_states states.
+    ///
+    pub fn new(nfa: &Nfa, max_states: usize) -> Result<Dfa, RegexError> {
+        let boundaries = boundaries(nfa);
+        let mut builder = Builder { max_states, sets: Vec::new(), ids: HashMap::new() };
+        let dead = builder.add(Vec::new(), false)?;
+        debug_assert_eq!(dead, DEAD);
+        let start_text = builder.add(closure(nfa, &[nfa.start()], true, false), false)?;
+        let start_inside = builder.add(closure(nfa, &[nfa.start()], false, false), false)?;
+        let start_search = builder.add(closure(nfa, &[nfa.start()], true, false), true)?;
+        let mut transitions: Vec<Vec<usize>> = Vec::new();
+        // New states are added to the end of the list while the transitions are computed.
+        while transitions.len() < builder.sets.len() {
+            let (set, search) = builder.sets[transitions.len()].clone();
+            let mut row = Vec::with_capacity(boundaries.len());
+            for (class, &boundary) in boundaries.iter().enumerate() {
+                let target = match representative(boundary, boundaries.get(class + 1).copied()) {
+                    Some(character) => {
+                        let mut targets: Vec<usize> = set
+                            .iter()
+                            .filter_map(|&state| match nfa.state(state) {
+                                State::Class { class, next } if class.contains(character) => Some(*next),
+                                _ => None,
+                            })
+                            .collect();
+                        if search {
+                            targets.push(nfa.start());
+                        }
+                        builder.add(closure(nfa, &targets, false, false), search)?
+                    }
+                    None => DEAD,
+                };
+                row.push(target);
+            }
+            transitions.push(row);
+        }
+        let accepting = builder.sets.iter().map(|(set, _)| set.iter().any(|&state| *nfa.state(state) == State::Match)).collect();
+        let accepting_at_end = builder.sets.iter().map(|(set, _)| closure(nfa, set, false, true).iter().any(|&state| *nfa.state(state) == State::Match)).collect();
+        let matches_empty_text = closure(nfa, &[nfa.start()], true, true).iter().any(|&state| *nfa.state(state) == State::Match);
+        Ok(Dfa { boundaries, transitions, accepting, accepting_at_end, start_text, start_inside, start_search, matches_empty_text })
+    }
+
+    ///
+    /// Returns the number of states.
+    ///
+    pub fn state_count(&self) -> usize {
+        self.transitions.len()
+    }
+
+    ///
+    /// Returns the number of character classes.
+    ///
+    pub fn class_count(&self) -> usize {
+        self.boundaries.len()
+    }
+
+    fn next(&self, state: usize, character: char) -> usize {
+        let class = self.boundaries.partition_point(|&boundary| boundary <= character as u32) - 1;
+        self.transitions[state][class]
+    }
+
+    fn accepts(&self, state: usize, at_end: bool) -> bool {
+        self.accepting[state] || (at_end && self.accepting_at_end[state])
+    }
+
+    ///
+    /// Returns true if the pattern matches anywhere in the text.
+    ///
+    pub fn is_match(&self, text: &str) -> bool {
+        if text.is_empty() {
+            return self.matches_empty_text;
+        }
+        let mut state = self.start_search;
+        for character in text.chars() {
+            if self.accepting[state] {
+                return true;
+            }
+            state = self.next(state, character);
+        }
+        self.accepts(state, true)
+    }
+
+    ///
+    /// Finds the leftmost longest match. This is the POSIX rule, unlike the
+    /// leftmost first rule used by the Pike VM, so alternations and lazy
+    /// repetitions can give longer matches. Each start position is tried in turn
+    /// until the DFA dies, so the worst case is quadratic in the text length.
+    ///
+    /// result: The match or None if there is no match.
+    ///
+    pub fn find(&self, text: &str) -> Option<Match> {
+        let mut starts = text.char_indices().map(|(position, _)| position).chain(std::iter::once(text.len()));
+        starts.find_map(|start| self.longest_at(text, start).map(|end| Match { start, end }))
+    }
+
+    ///
+    /// Returns the end of the longest match starting at the position.
+    ///
+    fn longest_at(&self, text: &str, start: usize) -> Option<usize> {
+        if text.is_empty() {
+            return self.matches_empty_text.then_some(0);
+        }
+        let mut state = if start == 0 { self.start_text } else { self.start_inside };
+        let mut end = None;
+        for (offset, character) in text[start..].char_indices() {
+            if self.accepting[state] {
+                end = Some(start + offset);
+            }
+            state = self.next(state, character);
+            if state == DEAD {
+                return end;
+            }
+        }
+        if self.accepts(state, true) { Some(text.len()) } else { end }
+    }
+}
+
+///
+/// Builder struct with the DFA states found so far. Each state is a set of NFA
+/// states and whether it is part of the unanchored search.
+///
+struct Builder {
+    max_states: usize,
+    sets: Vec<(Vec<usize>, bool)>,
+    ids: HashMap<(Vec<usize>, bool), usize>,
+}
+
+impl Builder {
+    ///
+    /// Returns the id of the state, adding it if it is new.
+    ///
+    fn add(&mut self, set: Vec<usize>, search: bool) -> Result<usize, RegexError> {
+        let key = (set, search);
+        if let Some(&id) = self.ids.get(&key) {
+            return Ok(id);
+        }
+        if self.sets.len() >= self.max_states {
+            return Err(RegexError::TooManyStates { states: self.max_states });
+        }
+        self.sets.push(key.clone());
+        self.ids.insert(key, self.sets.len() - 1);
+        Ok(self.sets.len() - 1)
+    }
+}
+
+///
+/// Returns the sorted set of states reachable from the states without consuming
+/// a character. Only consuming states, match states and end assertions are kept,
+/// since the others do not change what the set matches.
+///
+/// at_start: Whether ^ assertions hold.
+/// at_end: Whether $ assertions hold.
+///
+fn closure(nfa: &Nfa, states: &[usize], at_start: bool, at_end: bool) -> Vec<usize> {
+    let mut seen = vec![false; nfa.states().len()];
+    let mut set = Vec::new();
+    let mut stack: Vec<usize> = states.to_vec();
+    while let Some(state) = stack.pop() {
+        if seen[state] {
+            continue;
+        }
+        seen[state] = true;
+        match nfa.state(state) {
+            State::Split { first, second } => stack.extend([*first, *second]),
+            State::Empty { next } => stack.push(*next),
+            State::Assertion { assertion: Assertion::Start, next } => {
+                if at_start {
+                    stack.push(*next);
+                }
+            }
+            State::Assertion { assertion: Assertion::End, next } => {
+                if at_end {
+                    stack.push(*next);
+                } else {
+                    set.push(state);
+                }
+            }
+            State::Class { .. } | State::Match => set.push(state),
+        }
+    }
+    set.sort_unstable();
+    set
+}
+
+///
+/// Returns the boundaries between the character classes. Characters between two
+/// boundaries are in the same pattern classes.
+///
+fn boundaries(nfa: &Nfa) -> Vec<u32> {
+    let mut boundaries = vec![0];
+    for state in nfa.states() {
+        if let State::Class { class, .. } = state {
+            for &(start, end) in class.ranges() {
+                boundaries.push(start as u32);
+                boundaries.push(end as u32 + 1);
+            }
+        }
+    }
+    boundaries.sort_unstable();
+    boundaries.dedup();
+    boundaries.retain(|&boundary| boundary <= char::MAX as u32);
+    boundaries
+}
+
+///
+/// Returns a character between the boundaries, or None if there are only surrogates between them.
+///
+fn representative(start: u32, end: Option<u32>) -> Option<char> {
+    let character = char::from_u32(start).or_else(|| next_char('\u{D7FF}'))?;
+    end.is_none_or(|end| (character as u32) < end).then_some(character)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    fn dfa(pattern: &str) -> Dfa {
+        Dfa::new(&Nfa::new(&parse(pattern).unwrap()).unwrap(), DEFAULT_MAX_STATES).unwrap()
+    }
+
+    fn find(pattern: &str, text: &str) -> Option<(usize, usize)> {
+        dfa(pattern).find(text).map(|found| (found.start, found.end))
+    }
+
+    #[test]
+    fn test_leftmost_longest() {
+        assert_eq!(find("a|ab", "xab"), Some((1, 3)));
+        assert_eq!(find("a+?", "baaab"), Some((1, 4)));
+        assert_eq!(find("x*", "abc"), Some((0, 0)));
+        assert_eq!(find("c", "abd"), None);
+    }
+
+    #[test]
+    fn test_anchors() {
+        assert_eq!(find("^a", "aa"), Some((0, 1)));
+        assert_eq!(find("^b", "ab"), None);
+        assert_eq!(find("a$", "aa"), Some((1, 2)));
+        assert_eq!(find("a$|ab", "ab"), Some((0, 2)));
+        assert!(dfa("^$").is_match(""));
+        assert!(!dfa("a^").is_match("aa"));
+        assert!(dfa("$^").is_match(""));
+    }
+
+    #[test]
+    fn test_is_match() {
+        let dfa = dfa("a(b|c)*d");
+        assert!(dfa.is_match("xxabcbcd"));
+        assert!(!dfa.is_match("xxabcbc"));
+    }
+
+    #[test]
+    fn test_classes_are_grouped() {
+        // [0-9] and the rest of the characters.
+        assert_eq!(dfa("[0-9]+").class_count(), 3);
+        assert_eq!(dfa("ø").find("blåbærsøt").map(|found| found.start), Some(9));
+    }
+
+    #[test]
+    fn test_state_limit() {
+        // The DFA has to remember the last 12 characters to know if the 12th from the end was an a.
+        let nfa = Nfa::new(&parse("a[ab]{11}$").unwrap()).unwrap();
+        assert_eq!(Dfa::new(&nfa, 1000).err(), Some(RegexError::TooManyStates { states: 1000 }));
+    }
+}
@@ -0,0 +1,210 @@
+///
+/// Regular expression engine.
+/// Description: https://swtch.com/~rsc/regexp/regexp1.html
+///
+/// The pattern is parsed into a syntax tree, which is compiled into a Thompson
+/// NFA. The NFA is either simulated directly with the Pike VM, which finds the
+/// same leftmost first matches as Perl style engines in linear time, or compiled
+/// into a DFA with the subset construction, which finds leftmost longest matches
+/// with one table lookup per character.
+///
+pub mod ast;
+pub mod dfa;
+pub mod nfa;
+mod parser;
+pub mod pikevm;
+
+use std::fmt;
+
+use nfa::Nfa;
+pub use parser::parse;
+
+///
+/// RegexError enum to represent errors when compiling patterns.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegexError {
+    // The pattern ended in the middle of an expression.
+    UnexpectedEnd,
+    // A character that is not allowed at the position. Position is the character index.
+    UnexpectedCharacter { character: char, position: usize },
+    // A ( without a matching ) or a ) without a matching (.
+    UnbalancedParenthesis { position: usize },
+    // A repetition without an expression to repeat, or with invalid counts.
+    InvalidRepetition { character: char, position: usize },
+    // An unterminated class, a class with a range in the wrong order, or a nested class.
+    InvalidClass { position: usize },
+    // An escape of a character without a meaning.
+    InvalidEscape { character: char, position: usize },
+    // The NFA would have more states than the limit.
+    TooLarge { states: usize },
+    // The DFA would have more states than the limit.
+    TooManyStates { states: usize },
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegexError::UnexpectedEnd => write!(f, "Unexpected end of pattern"),
+            RegexError::UnexpectedCharacter { character, position } => write!(f, "Unexpected character {character:?} at position {position}"),
+            RegexError::UnbalancedParenthesis { position } => write!(f, "Unbalanced parenthesis at position {position}"),
+            RegexError::InvalidRepetition { character, position } => write!(f, "Invalid repetition {character:?} at position {position}"),
+            RegexError::InvalidClass { position } => write!(f, "Invalid class at position {position}"),
+            RegexError::InvalidEscape { character, position } => write!(f, "Invalid escape \\{character} at position {position}"),
+            RegexError::TooLarge { states } => write!(f, "Pattern needs more than {states} NFA states"),
+            RegexError::TooManyStates { states } => write!(f, "Pattern needs more than {states} DFA states"),
+        }
+    }
+}
+
+impl std::error::Error for RegexError {}
+
+///
+/// Match struct with the byte positions of a match in the text.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Match {
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+///
+/// Regex struct with a compiled pattern. Searches use the Pike VM.
+///
+#[derive(Debug, Clone)]
+pub struct Regex {
+    nfa: Nfa,
+}
+
+impl Regex {
+    ///
+    /// Parses and compiles the pattern. See parse for the supported syntax.
+    ///
+    pub fn new(pattern: &str) -> Result<Regex, RegexError> {
+        Ok(Regex { nfa: Nfa::new(&parse(pattern)?)? })
+    }
+
+    pub fn nfa(&self) -> &Nfa {
+        &self.nfa
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        pikevm::is_match(&self.nfa, text)
+    }
+
+    ///
+    /// Finds the leftmost first match.
+    ///
+    pub fn find(&self, text: &str) -> Option<Match> {
+        pikevm::find_at(&self.nfa, text, 0)
+    }
+
+    ///
+    /// Finds the successive non overlapping matches. An empty match directly
+    /// after the previous match is skipped, so a* gives one match for aa and
+    /// not an extra empty match at the end.
+    ///
+    pub fn find_all(&self, text: &str) -> Vec<Match> {
+        let mut matches: Vec<Match> = Vec::new();
+        let mut from = 0;
+        while from <= text.len() {
+            let Some(found) = pikevm::find_at(&self.nfa, text, from) else { break };
+            if found.is_empty() && matches.last().is_some_and(|last| last.end == found.end) {
+                // Retry one character later.
+                match text[found.end..].chars().next() {
+                    Some(character) => from = found.end + character.len_utf8(),
+                    None => break,
+                }
+                continue;
+            }
+            from = found.end;
+            matches.push(found);
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dfa::{DEFAULT_MAX_STATES, Dfa};
+    use prng::{Rng, Xoshiro256StarStar};
+
+    #[test]
+    fn test_find_all() {
+        let regex = Regex::new("a*").unwrap();
+        let matches: Vec<(usize, usize)> = regex.find_all("baab").iter().map(|found| (found.start, found.end)).collect();
+        assert_eq!(matches, vec![(0, 0), (1, 3), (4, 4)]);
+    }
+
+    ///
+    /// Returns a random pattern over a small alphabet, so generated texts often match.
+    ///
+    fn generate_pattern(rng: &mut Xoshiro256StarStar, depth: u32) -> String {
+        let choice = if depth == 0 { rng.gen_index(8) } else { rng.gen_index(13) };
+        match choice {
+            0..=2 => ["a", "b", "c"][rng.gen_index(3)].to_string(),
+            3 => ".".to_string(),
+            4 => ["[ab]", "[^a]", "[a-c1]", r"\d", r"\w", r"\s", r"\D"][rng.gen_index(7)].to_string(),
+            5 => ["^", "$"][rng.gen_index(2)].to_string(),
+            6 => "()".to_string(),
+            7 => "b".to_string(),
+            8 | 9 => (0..2 + rng.gen_index(2)).map(|_| generate_pattern(rng, depth - 1)).collect(),
+            10 => format!("(?:{}|{})", generate_pattern(rng, depth - 1), generate_pattern(rng, depth - 1)),
+            _ => {
+                let operator = ["*", "+", "?", "{2}", "{1,3}", "{0,}", "{2,}"][rng.gen_index(7)];
+                let lazy = if rng.gen_bool(0.3) { "?" } else { "" };
+                format!("({}){operator}{lazy}", generate_pattern(rng, depth - 1))
+            }
+        }
+    }
+
+    fn generate_text(rng: &mut Xoshiro256StarStar) -> String {
+        let alphabet = ['a', 'b', 'c', '1', ' ', '\n'];
+        (0..rng.gen_index(12)).map(|_| alphabet[rng.gen_index(alphabet.len())]).collect()
+    }
+
+    ///
+    /// Compares the engines with the regex crate on generated patterns and texts.
+    /// The texts are ASCII, where the Unicode classes of the regex crate match the
+    /// same characters as the ASCII classes here. The DFA finds leftmost longest
+    /// matches, so it must start where the regex crate does and end at or after it.
+    ///
+    #[test]
+    fn test_differential() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1747);
+        for _ in 0..2000 {
+            let pattern = generate_pattern(&mut rng, 3);
+            let regex = Regex::new(&pattern).unwrap();
+            let reference = regex::Regex::new(&pattern).unwrap();
+            let dfa = Dfa::new(regex.nfa(), DEFAULT_MAX_STATES).unwrap();
+            for _ in 0..20 {
+                let text = generate_text(&mut rng);
+                let expected = reference.find(&text).map(|found| (found.start(), found.end()));
+                assert_eq!(regex.find(&text).map(|found| (found.start, found.end)), expected, "Pike VM {pattern:?} on {text:?}");
+                assert_eq!(regex.is_match(&text), expected.is_some(), "Pike VM {pattern:?} on {text:?}");
+                assert_eq!(dfa.is_match(&text), expected.is_some(), "DFA {pattern:?} on {text:?}");
+                let longest = dfa.find(&text).map(|found| (found.start, found.end));
+                assert_eq!(longest.map(|(start, _)| start), expected.map(|(start, _)| start), "DFA {pattern:?} on {text:?}");
+                assert!(longest.zip(expected).is_none_or(|((_, end), (_, expected_end))| end >= expected_end), "DFA {pattern:?} on {text:?}");
+                let all: Vec<(usize, usize)> = regex.find_all(&text).iter().map(|found| (found.start, found.end)).collect();
+                let expected_all: Vec<(usize, usize)> = reference.find_iter(&text).map(|found| (found.start(), found.end())).collect();
+                assert_eq!(all, expected_all, "Pike VM {pattern:?} on {text:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_errors_as_regex_crate() {
+        for pattern in ["(a", "a)", "*", "[b-a]", "a{2,1}", r"\q", "[a"] {
+            assert!(Regex::new(pattern).is_err(), "{pattern}");
+            assert!(regex::Regex::new(pattern).is_err(), "{pattern}");
+        }
+    }
+}
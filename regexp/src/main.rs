@@ -0,0 +1,110 @@
+mod args;
+
+use std::fs;
+use std::io::Read;
+
+use args::{Args, Engine};
+use clap::Parser;
+use regexp::dfa::{DEFAULT_MAX_STATES, Dfa};
+use regexp::{Match, Regex};
+
+/**
+ * Searches the lines of a file or stdin for a regular expression, like grep.
+ */
+fn main() {
+    let args = Args::parse();
+    let regex = match Regex::new(&args.pattern) {
+        Ok(regex) => regex,
+        Err(err) => {
+            eprintln!("{err}");
+            return;
+        }
+    };
+    let dfa = match args.engine {
+        Engine::Nfa => None,
+        Engine::Dfa => match Dfa::new(regex.nfa(), DEFAULT_MAX_STATES) {
+            Ok(dfa) => Some(dfa),
+            Err(err) => {
+                eprintln!("{err}");
+                return;
+            }
+        },
+    };
+    if args.statistics {
+        eprintln!("NFA states: {}", regex.nfa().states().len());
+        if let Some(dfa) = &dfa {
+            eprintln!("DFA states: {}, character classes: {}", dfa.state_count(), dfa.class_count());
+        }
+    }
+    let text = match read_input(&args.input_file) {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("Failed to read input: {err}");
+            return;
+        }
+    };
+    let mut count = 0;
+    for line in text.lines() {
+        let matches = find_matches(&regex, dfa.as_ref(), line, args.only_matching);
+        if matches.is_empty() == args.invert {
+            count += 1;
+            if args.count {
+                continue;
+            }
+            if args.only_matching && !args.invert {
+                matches.iter().filter(|found| !found.is_empty()).for_each(|found| println!("{}", &line[found.start..found.end]));
+            } else {
+                println!("{line}");
+            }
+        }
+    }
+    if args.count {
+        println!("{count}");
+    }
+}
+
+/**
+ * Reads the whole input file, or stdin if no file is given.
+ */
+fn read_input(input_file: &Option<String>) -> std::io::Result<String> {
+    match input_file {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            Ok(text)
+        }
+    }
+}
+
+/**
+ * Finds the matches in the line. Only the first match is found unless all are needed.
+ * The DFA finds leftmost longest matches, so later matches start after the end of the previous.
+ *
+ * # Arguments
+ * * `regex`: The compiled pattern.
+ * * `dfa`: The DFA to use instead of the Pike VM.
+ * * `line`: The line to search.
+ * * `all`: Find all matches instead of only the first.
+ */
+fn find_matches(regex: &Regex, dfa: Option<&Dfa>, line: &str, all: bool) -> Vec<Match> {
+    match (dfa, all) {
+        (None, true) => regex.find_all(line),
+        (None, false) => regex.find(line).into_iter().collect(),
+        (Some(dfa), false) => dfa.find(line).into_iter().collect(),
+        (Some(dfa), true) => {
+            let mut matches = Vec::new();
+            let mut from = 0;
+            while let Some(found) = dfa.find(&line[from..]) {
+                let found = Match { start: from + found.start, end: from + found.end };
+                matches.push(found);
+                match line[found.end..].chars().next() {
+                    _ if !found.is_empty() => from = found.end,
+                    Some(character) => from = found.end + character.len_utf8(),
+                    None => break,
+                }
+            }
+            matches
+        }
+    }
+}
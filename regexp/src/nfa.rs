@@ -0,0 +1,163 @@
+//!
+//! Thompson construction of a nondeterministic finite automaton from a syntax tree.
+//! Description: https://en.wikipedia.org/wiki/Thompson%27s_construction
+//!
+
+use crate::RegexError;
+use crate::ast::{Assertion, Ast, CharClass};
+
+///
+/// Maximum number of states in an automaton. Counted repetitions copy their
+/// expression, so small patterns like (a{1000}){1000} can get very large.
+///
+pub const MAX_STATES: usize = 100_000;
+
+///
+/// State enum for the states of the automaton. Next states are indices into the state list.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum State {
+    // Consumes one character in the class.
+    Class { class: CharClass, next: usize },
+    // Continues in both states without consuming. The first has priority.
+    Split { first: usize, second: usize },
+    // Continues without consuming when the assertion holds at the position.
+    Assertion { assertion: Assertion, next: usize },
+    // Continues without consuming.
+    Empty { next: usize },
+    // The pattern has matched.
+    Match,
+}
+
+///
+/// Nfa struct with the states of the automaton.
+///
+#[derive(Debug, Clone)]
+pub struct Nfa {
+    states: Vec<State>,
+    start: usize,
+}
+
+impl Nfa {
+    ///
+    /// Builds the automaton for the syntax tree.
+    ///
+    /// result: The automaton or an error if it would have more than MAX_STATES states.
+    ///
+    pub fn new(ast: &Ast) -> Result<Nfa, RegexError> {
+        let mut builder = Builder { states: vec![State::Match] };
+        let start = builder.compile(ast, 0)?;
+        Ok(Nfa { states: builder.states, start })
+    }
+
+    pub fn states(&self) -> &[State] {
+        &self.states
+    }
+
+    pub fn state(&self, index: usize) -> &State {
+        &self.states[index]
+    }
+
+    ///
+    /// Returns the start state. The automaton is anchored, it only matches at the position it is started.
+    ///
+    pub fn start(&self) -> usize {
+        self.start
+    }
+}
+
+///
+/// Builder struct for the automaton. Expressions are compiled back to front,
+/// so the state following an expression is always known when it is compiled.
+///
+struct Builder {
+    states: Vec<State>,
+}
+
+impl Builder {
+    fn push(&mut self, state: State) -> Result<usize, RegexError> {
+        if self.states.len() >= MAX_STATES {
+            return Err(RegexError::TooLarge { states: MAX_STATES });
+        }
+        self.states.push(state);
+        Ok(self.states.len() - 1)
+    }
+
+    ///
+    /// Compiles the expression.
+    ///
+    /// next: The state to continue in after the expression has matched.
+    ///
+    /// result: The start state of the expression.
+    ///
+    fn compile(&mut self, ast: &Ast, next: usize) -> Result<usize, RegexError> {
+        match ast {
+            Ast::Empty => Ok(next),
+            Ast::Class(class) => self.push(State::Class { class: class.clone(), next }),
+            Ast::Assertion(assertion) => self.push(State::Assertion { assertion: *assertion, next }),
+            Ast::Concat(parts) => parts.iter().rev().try_fold(next, |next, part| self.compile(part, next)),
+            Ast::Alternate(alternatives) => {
+                let (last, rest) = alternatives.split_last().expect("Alternation has alternatives");
+                let mut start = self.compile(last, next)?;
+                for alternative in rest.iter().rev() {
+                    let first = self.compile(alternative, next)?;
+                    start = self.push(State::Split { first, second: start })?;
+                }
+                Ok(start)
+            }
+            Ast::Repeat { ast, min, max, greedy } => {
+                let (mut start, copies) = match max {
+                    None => {
+                        // Compiled as x+ with the loop after the expression, and a* as (a+)?. An
+                        // empty iteration then reaches the exit of the loop before trying another
+                        // iteration, which gives the same matches as backtracking engines.
+                        let repeat = self.push(State::Empty { next })?;
+                        let body = self.compile(ast, repeat)?;
+                        self.states[repeat] = self.split(body, next, *greedy);
+                        if *min == 0 { (self.push(self.split(body, next, *greedy))?, 0) } else { (body, min - 1) }
+                    }
+                    Some(max) => {
+                        let mut start = next;
+                        for _ in *min..*max {
+                            let body = self.compile(ast, start)?;
+                            start = self.push(self.split(body, next, *greedy))?;
+                        }
+                        (start, *min)
+                    }
+                };
+                for _ in 0..copies {
+                    start = self.compile(ast, start)?;
+                }
+                Ok(start)
+            }
+        }
+    }
+
+    ///
+    /// Returns a split preferring the body if greedy and skipping it otherwise.
+    ///
+    fn split(&self, body: usize, skip: usize, greedy: bool) -> State {
+        if greedy { State::Split { first: body, second: skip } } else { State::Split { first: skip, second: body } }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_state_count() {
+        // Match, a, b and the split for the alternation.
+        assert_eq!(Nfa::new(&parse("a|b").unwrap()).unwrap().states().len(), 4);
+        // Match, a, the loop split and the split skipping the loop.
+        assert_eq!(Nfa::new(&parse("a*").unwrap()).unwrap().states().len(), 4);
+        // Match, two optional copies with splits and two mandatory copies.
+        assert_eq!(Nfa::new(&parse("a{2,4}").unwrap()).unwrap().states().len(), 7);
+    }
+
+    #[test]
+    fn test_too_large() {
+        assert_eq!(Nfa::new(&parse("(a{1000}){1000}").unwrap()).err(), Some(RegexError::TooLarge { states: MAX_STATES }));
+    }
+}
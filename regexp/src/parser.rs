@@ -0,0 +1,280 @@
+use crate::RegexError;
+use crate::ast::{Assertion, Ast, CharClass};
+
+///
+/// Maximum count allowed in a counted repetition like a{2,5}.
+///
+pub const MAX_REPEAT: u32 = 1000;
+
+///
+/// Parses a pattern into a syntax tree.
+///
+/// Supported syntax: literals, . (any character except newline), classes like
+/// [a-z] and [^0-9], the escapes \d \w \s \D \W \S \n \t \r and escaped
+/// punctuation, groups (a) and (?:a), alternation a|b, the repetitions
+/// * + ? {n} {n,} {n,m} with lazy variants ending in ?, and the anchors ^ and $.
+///
+/// result: The syntax tree or an error with the position of the problem.
+///
+pub fn parse(pattern: &str) -> Result<Ast, RegexError> {
+    let mut parser = Parser { chars: pattern.chars().collect(), index: 0 };
+    let ast = parser.alternation()?;
+    match parser.peek() {
+        Some(')') => Err(RegexError::UnbalancedParenthesis { position: parser.index }),
+        Some(character) => Err(RegexError::UnexpectedCharacter { character, position: parser.index }),
+        None => Ok(ast),
+    }
+}
+
+///
+/// Parser struct for a recursive descent parser over the characters of the pattern.
+///
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Result<char, RegexError> {
+        let character = self.peek().ok_or(RegexError::UnexpectedEnd)?;
+        self.index += 1;
+        Ok(character)
+    }
+
+    fn consume(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    ///
+    /// alternation := concat ( | concat )*
+    ///
+    fn alternation(&mut self) -> Result<Ast, RegexError> {
+        let mut alternatives = vec![self.concat()?];
+        while self.consume('|') {
+            alternatives.push(self.concat()?);
+        }
+        Ok(if alternatives.len() == 1 { alternatives.remove(0) } else { Ast::Alternate(alternatives) })
+    }
+
+    ///
+    /// concat := repeat*
+    ///
+    fn concat(&mut self) -> Result<Ast, RegexError> {
+        let mut parts = Vec::new();
+        while let Some(character) = self.peek() {
+            if character == '|' || character == ')' {
+                break;
+            }
+            parts.push(self.repeat()?);
+        }
+        Ok(match parts.len() {
+            0 => Ast::Empty,
+            1 => parts.remove(0),
+            _ => Ast::Concat(parts),
+        })
+    }
+
+    ///
+    /// repeat := atom quantifier?
+    ///
+    fn repeat(&mut self) -> Result<Ast, RegexError> {
+        let ast = self.atom()?;
+        let position = self.index;
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                self.index += 1;
+                self.counted(position)?
+            }
+            _ => return Ok(ast),
+        };
+        // Skips the operator, or the closing brace of a counted repetition.
+        self.index += 1;
+        let greedy = !self.consume('?');
+        if let Some(character @ ('*' | '+' | '?' | '{')) = self.peek() {
+            return Err(RegexError::InvalidRepetition { character, position: self.index });
+        }
+        Ok(Ast::Repeat { ast: Box::new(ast), min, max, greedy })
+    }
+
+    ///
+    /// Parses the inside of {n}, {n,} or {n,m} up to the closing brace.
+    ///
+    /// position: The position of the opening brace, used for errors.
+    ///
+    fn counted(&mut self, position: usize) -> Result<(u32, Option<u32>), RegexError> {
+        let invalid = RegexError::InvalidRepetition { character: '{', position };
+        let min = self.number().ok_or(invalid.clone())?;
+        let max = if self.consume(',') { if self.peek() == Some('}') { None } else { Some(self.number().ok_or(invalid.clone())?) } } else { Some(min) };
+        if self.peek() != Some('}') || max.is_some_and(|max| max < min) || min.max(max.unwrap_or(0)) > MAX_REPEAT {
+            return Err(invalid);
+        }
+        Ok((min, max))
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        let start = self.index;
+        while self.peek().is_some_and(|character| character.is_ascii_digit()) {
+            self.index += 1;
+        }
+        self.chars[start..self.index].iter().collect::<String>().parse().ok()
+    }
+
+    ///
+    /// atom := ( alternation ) | (?: alternation ) | [ class ] | . | ^ | $ | \ escape | literal
+    ///
+    fn atom(&mut self) -> Result<Ast, RegexError> {
+        let position = self.index;
+        match self.next()? {
+            '(' => {
+                if self.consume('?') && !self.consume(':') {
+                    return Err(RegexError::UnexpectedCharacter { character: '?', position: position + 1 });
+                }
+                let ast = self.alternation()?;
+                if !self.consume(')') {
+                    return Err(RegexError::UnbalancedParenthesis { position });
+                }
+                Ok(ast)
+            }
+            '[' => Ok(Ast::Class(self.class(position)?)),
+            '.' => Ok(Ast::Class(CharClass::any_except_newline())),
+            '^' => Ok(Ast::Assertion(Assertion::Start)),
+            '$' => Ok(Ast::Assertion(Assertion::End)),
+            '\\' => Ok(Ast::Class(self.escape()?)),
+            character @ ('*' | '+' | '?' | '{') => Err(RegexError::InvalidRepetition { character, position }),
+            character => Ok(Ast::Class(CharClass::single(character))),
+        }
+    }
+
+    ///
+    /// Parses a class after the opening bracket. A ] first in the class is a
+    /// literal, as is - first or last.
+    ///
+    /// position: The position of the opening bracket, used for errors.
+    ///
+    fn class(&mut self, position: usize) -> Result<CharClass, RegexError> {
+        let negated = self.consume('^');
+        let mut class = CharClass::new(Vec::new());
+        let mut first = true;
+        loop {
+            let item_position = self.index;
+            let start = match self.next().map_err(|_| RegexError::InvalidClass { position })? {
+                ']' if !first => break,
+                '[' => return Err(RegexError::InvalidClass { position: item_position }),
+                '\\' => {
+                    let escaped = self.escape()?;
+                    match escaped.ranges() {
+                        [(start, end)] if start == end => *start,
+                        _ => {
+                            class = class.union(&escaped);
+                            first = false;
+                            continue;
+                        }
+                    }
+                }
+                character => character,
+            };
+            first = false;
+            let end = if self.peek() == Some('-') && self.chars.get(self.index + 1).is_some_and(|&next| next != ']') {
+                self.index += 1;
+                match self.next()? {
+                    '\\' => match self.escape()?.ranges() {
+                        [(start, end)] if start == end => *start,
+                        _ => return Err(RegexError::InvalidClass { position: item_position }),
+                    },
+                    '[' => return Err(RegexError::InvalidClass { position: item_position }),
+                    character => character,
+                }
+            } else {
+                start
+            };
+            if end < start {
+                return Err(RegexError::InvalidClass { position: item_position });
+            }
+            class = class.union(&CharClass::new(vec![(start, end)]));
+        }
+        Ok(if negated { class.negate() } else { class })
+    }
+
+    ///
+    /// Parses the character after a backslash.
+    ///
+    fn escape(&mut self) -> Result<CharClass, RegexError> {
+        let position = self.index;
+        Ok(match self.next()? {
+            'd' => CharClass::digit(),
+            'D' => CharClass::digit().negate(),
+            'w' => CharClass::word(),
+            'W' => CharClass::word().negate(),
+            's' => CharClass::space(),
+            'S' => CharClass::space().negate(),
+            'n' => CharClass::single('\n'),
+            't' => CharClass::single('\t'),
+            'r' => CharClass::single('\r'),
+            character if character.is_ascii_punctuation() => CharClass::single(character),
+            character => return Err(RegexError::InvalidEscape { character, position }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literal(character: char) -> Ast {
+        Ast::Class(CharClass::single(character))
+    }
+
+    #[test]
+    fn test_precedence() {
+        let ast = parse("ab|c*").unwrap();
+        let expected = Ast::Alternate(vec![Ast::Concat(vec![literal('a'), literal('b')]), Ast::Repeat { ast: Box::new(literal('c')), min: 0, max: None, greedy: true }]);
+        assert_eq!(ast, expected);
+    }
+
+    #[test]
+    fn test_counted_repetition() {
+        assert_eq!(parse("a{2,5}?").unwrap(), Ast::Repeat { ast: Box::new(literal('a')), min: 2, max: Some(5), greedy: false });
+        assert_eq!(parse("a{3}").unwrap(), Ast::Repeat { ast: Box::new(literal('a')), min: 3, max: Some(3), greedy: true });
+        assert_eq!(parse("a{3,}").unwrap(), Ast::Repeat { ast: Box::new(literal('a')), min: 3, max: None, greedy: true });
+    }
+
+    #[test]
+    fn test_classes() {
+        assert_eq!(parse("[a-c_]").unwrap(), Ast::Class(CharClass::new(vec![('a', 'c'), ('_', '_')])));
+        assert_eq!(parse("[]-]").unwrap(), Ast::Class(CharClass::new(vec![(']', ']'), ('-', '-')])));
+        assert_eq!(parse(r"[\d.]").unwrap(), Ast::Class(CharClass::new(vec![('0', '9'), ('.', '.')])));
+        assert_eq!(parse("[^a]").unwrap(), Ast::Class(CharClass::single('a').negate()));
+    }
+
+    #[test]
+    fn test_groups() {
+        assert_eq!(parse("(?:a)").unwrap(), literal('a'));
+        assert_eq!(parse("()").unwrap(), Ast::Empty);
+        assert_eq!(parse("a|").unwrap(), Ast::Alternate(vec![literal('a'), Ast::Empty]));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse("(a"), Err(RegexError::UnbalancedParenthesis { position: 0 }));
+        assert_eq!(parse("a)"), Err(RegexError::UnbalancedParenthesis { position: 1 }));
+        assert_eq!(parse("*a"), Err(RegexError::InvalidRepetition { character: '*', position: 0 }));
+        assert_eq!(parse("a**"), Err(RegexError::InvalidRepetition { character: '*', position: 2 }));
+        assert_eq!(parse("a{3,2}"), Err(RegexError::InvalidRepetition { character: '{', position: 1 }));
+        assert_eq!(parse("[z-a]"), Err(RegexError::InvalidClass { position: 1 }));
+        assert_eq!(parse("[ab"), Err(RegexError::InvalidClass { position: 0 }));
+        assert_eq!(parse(r"\q"), Err(RegexError::InvalidEscape { character: 'q', position: 1 }));
+        assert_eq!(parse("a\\"), Err(RegexError::UnexpectedEnd));
+    }
+}
@@ -0,0 +1,165 @@
+//!
+//! NFA simulation with the Pike VM. All threads run in lockstep over the text, one
+//! character at a time, so the time is linear in the text length times the number
+//! of states. Threads are kept in priority order, which gives the same leftmost
+//! first matches as a backtracking engine.
+//! Description: https://swtch.com/~rsc/regexp/regexp2.html
+//!
+
+use crate::Match;
+use crate::ast::Assertion;
+use crate::nfa::{Nfa, State};
+
+///
+/// Threads struct with the threads at one position in priority order. Each thread
+/// has its state and the position where its match started.
+///
+struct Threads {
+    threads: Vec<(usize, usize)>,
+    seen: Vec<bool>,
+}
+
+impl Threads {
+    fn new(states: usize) -> Threads {
+        Threads { threads: Vec::new(), seen: vec![false; states] }
+    }
+
+    fn clear(&mut self) {
+        self.threads.clear();
+        self.seen.fill(false);
+    }
+
+    ///
+    /// Adds the thread and follows the transitions that do not consume a
+    /// character. Only the first thread to reach a state is kept, since it has
+    /// the highest priority.
+    ///
+    fn add(&mut self, nfa: &Nfa, state: usize, start: usize, position: usize, text: &str) {
+        let mut stack = vec![state];
+        while let Some(state) = stack.pop() {
+            if self.seen[state] {
+                continue;
+            }
+            self.seen[state] = true;
+            match nfa.state(state) {
+                State::Split { first, second } => {
+                    stack.push(*second);
+                    stack.push(*first);
+                }
+                State::Empty { next } => stack.push(*next),
+                State::Assertion { assertion, next } => {
+                    let holds = match assertion {
+                        Assertion::Start => position == 0,
+                        Assertion::End => position == text.len(),
+                    };
+                    if holds {
+                        stack.push(*next);
+                    }
+                }
+                State::Class { .. } | State::Match => self.threads.push((state, start)),
+            }
+        }
+    }
+}
+
+///
+/// Finds the leftmost first match starting at or after the position.
+///
+/// nfa: The automaton.
+/// text: The text to search. Anchors are relative to the whole text.
+/// from: Byte position to start searching from. Must be at a character boundary.
+///
+/// result: The match or None if there is no match.
+///
+pub fn find_at(nfa: &Nfa, text: &str, from: usize) -> Option<Match> {
+    search(nfa, text, from, false)
+}
+
+///
+/// Returns true if the pattern matches anywhere in the text. Stops at the first
+/// match found instead of finding where the leftmost first match ends.
+///
+pub fn is_match(nfa: &Nfa, text: &str) -> bool {
+    search(nfa, text, 0, true).is_some()
+}
+
+fn search(nfa: &Nfa, text: &str, from: usize, earliest: bool) -> Option<Match> {
+    let mut current = Threads::new(nfa.states().len());
+    let mut next = Threads::new(nfa.states().len());
+    let mut matched = None;
+    let mut position = from;
+    loop {
+        if matched.is_none() {
+            current.add(nfa, nfa.start(), position, position, text);
+        }
+        if current.threads.is_empty() && matched.is_some() {
+            break;
+        }
+        let character = text[position..].chars().next();
+        let after = position + character.map_or(0, char::len_utf8);
+        for &(state, start) in &current.threads {
+            match nfa.state(state) {
+                State::Class { class, next: target } => {
+                    if character.is_some_and(|character| class.contains(character)) {
+                        next.add(nfa, *target, start, after, text);
+                    }
+                }
+                State::Match => {
+                    matched = Some(Match { start, end: position });
+                    // Threads after this one have lower priority and are dropped.
+                    break;
+                }
+                _ => unreachable!("Only consuming and match states are added"),
+            }
+        }
+        if (earliest && matched.is_some()) || character.is_none() {
+            break;
+        }
+        std::mem::swap(&mut current, &mut next);
+        next.clear();
+        position = after;
+    }
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    fn find(pattern: &str, text: &str) -> Option<(usize, usize)> {
+        let nfa = Nfa::new(&parse(pattern).unwrap()).unwrap();
+        find_at(&nfa, text, 0).map(|found| (found.start, found.end))
+    }
+
+    #[test]
+    fn test_leftmost_first() {
+        assert_eq!(find("a|ab", "xab"), Some((1, 2)));
+        assert_eq!(find("ab|a", "xab"), Some((1, 3)));
+        assert_eq!(find("a+", "baaab"), Some((1, 4)));
+        assert_eq!(find("a+?", "baaab"), Some((1, 2)));
+        assert_eq!(find("x*", "abc"), Some((0, 0)));
+        assert_eq!(find("c", "abd"), None);
+    }
+
+    #[test]
+    fn test_anchors() {
+        assert_eq!(find("^a", "aa"), Some((0, 1)));
+        assert_eq!(find("^b", "ab"), None);
+        assert_eq!(find("a$", "aa"), Some((1, 2)));
+        assert_eq!(find("^$", ""), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_byte_offsets() {
+        assert_eq!(find("ø+", "blåbærsøt"), Some((9, 11)));
+        assert_eq!(find("b.r", "blåbærsøt"), Some((4, 8)));
+    }
+
+    #[test]
+    fn test_is_match() {
+        let nfa = Nfa::new(&parse("a(b|c)*d").unwrap()).unwrap();
+        assert!(is_match(&nfa, "xxabcbcd"));
+        assert!(!is_match(&nfa, "xxabcbc"));
+    }
+}
@@ -0,0 +1,182 @@
+///
+/// Visualization utilities shared by the simulation crates.
+///
+/// The crate contains an RGB image buffer, encoders for PNG and the Netpbm
+/// formats written from scratch, and helpers for drawing cell grids in the
+/// terminal.
+///
+pub mod netpbm;
+pub mod png;
+pub mod terminal;
+
+use std::io;
+use std::path::Path;
+
+///
+/// RGB color.
+///
+pub type Color = [u8; 3];
+
+pub const BLACK: Color = [0, 0, 0];
+pub const WHITE: Color = [255, 255, 255];
+
+///
+/// Image struct containing RGB pixels in row-major order.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    ///
+    /// Creates a new image filled with the background color.
+    ///
+    pub fn new(width: usize, height: usize, background: Color) -> Image {
+        Image { width, height, pixels: vec![background; width * height] }
+    }
+
+    ///
+    /// Creates an image where each cell of a grid is drawn as a square of scale x scale pixels.
+    ///
+    /// width: Number of cells horizontally.
+    /// height: Number of cells vertically.
+    /// scale: Size of a cell in pixels. Values below 1 are treated as 1.
+    /// color_of: Returns the color of the cell at (x, y).
+    ///
+    pub fn from_cells(width: usize, height: usize, scale: usize, color_of: impl Fn(usize, usize) -> Color) -> Image {
+        let scale = scale.max(1);
+        let mut image = Image::new(width * scale, height * scale, BLACK);
+        for y in 0..height {
+            for x in 0..width {
+                image.fill_rect(x * scale, y * scale, scale, scale, color_of(x, y));
+            }
+        }
+        image
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    ///
+    /// Returns the color of the pixel. Panics if the pixel is outside the image.
+    ///
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    ///
+    /// Sets the color of the pixel. Pixels outside the image are ignored.
+    ///
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    ///
+    /// Fills a rectangle. The parts outside the image are ignored.
+    ///
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color) {
+        for row in y..(y + height).min(self.height) {
+            for column in x..(x + width).min(self.width) {
+                self.pixels[row * self.width + column] = color;
+            }
+        }
+    }
+
+    ///
+    /// Returns the rows of the image.
+    ///
+    pub fn rows(&self) -> impl Iterator<Item = &[Color]> {
+        self.pixels.chunks(self.width.max(1)).take(self.height)
+    }
+
+    ///
+    /// Writes the image to the file. The format is chosen from the extension:
+    /// .ppm and .pgm use Netpbm, everything else PNG.
+    ///
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let bytes = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("ppm") => netpbm::encode_ppm(self),
+            Some("pgm") => netpbm::encode_pgm(self),
+            _ => png::encode(self),
+        };
+        std::fs::write(path, bytes)
+    }
+}
+
+///
+/// Returns a palette of distinct colors. The first color is black, the second white,
+/// and the rest are spread around the hue circle. Used for multi-state cells.
+///
+/// count: Number of colors.
+///
+pub fn palette(count: usize) -> Vec<Color> {
+    (0..count)
+        .map(|idx| match idx {
+            0 => BLACK,
+            1 => WHITE,
+            _ => hue_to_rgb((idx - 2) as f64 / (count - 2) as f64),
+        })
+        .collect()
+}
+
+///
+/// Converts a hue in [0, 1) with full saturation and value to RGB.
+///
+fn hue_to_rgb(hue: f64) -> Color {
+    let sector = hue * 6.0;
+    let fraction = sector - sector.floor();
+    let rising = (fraction * 255.0) as u8;
+    let falling = 255 - rising;
+    match sector as usize % 6 {
+        0 => [255, rising, 0],
+        1 => [falling, 255, 0],
+        2 => [0, 255, rising],
+        3 => [0, falling, 255],
+        4 => [rising, 0, 255],
+        _ => [255, 0, falling],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_cells_scales() {
+        let image = Image::from_cells(2, 1, 3, |x, _| if x == 0 { WHITE } else { BLACK });
+        assert_eq!(image.width(), 6);
+        assert_eq!(image.height(), 3);
+        assert_eq!(image.pixel(2, 2), WHITE);
+        assert_eq!(image.pixel(3, 0), BLACK);
+    }
+
+    #[test]
+    fn test_set_pixel_outside_is_ignored() {
+        let mut image = Image::new(2, 2, BLACK);
+        image.set_pixel(5, 5, WHITE);
+        image.fill_rect(1, 1, 10, 10, WHITE);
+        assert_eq!(image.pixel(0, 0), BLACK);
+        assert_eq!(image.pixel(1, 1), WHITE);
+    }
+
+    #[test]
+    fn test_palette() {
+        let colors = palette(5);
+        assert_eq!(colors.len(), 5);
+        assert_eq!(colors[0], BLACK);
+        assert_eq!(colors[1], WHITE);
+        assert_eq!(colors[2], [255, 0, 0]);
+        assert_ne!(colors[3], colors[4]);
+    }
+}
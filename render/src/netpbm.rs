@@ -0,0 +1,48 @@
+//! Netpbm encoders. The formats are uncompressed and very simple, which makes
+//! them useful for quick inspection and for piping into other tools.
+//! Description: https://netpbm.sourceforge.net/doc/
+
+use crate::Image;
+
+///
+/// Encodes the image as binary PPM (P6) with full RGB.
+///
+pub fn encode_ppm(image: &Image) -> Vec<u8> {
+    let mut bytes = format!("P6\n{} {}\n255\n", image.width(), image.height()).into_bytes();
+    for row in image.rows() {
+        row.iter().for_each(|pixel| bytes.extend_from_slice(pixel));
+    }
+    bytes
+}
+
+///
+/// Encodes the image as binary PGM (P5). Colors are converted to gray using
+/// the ITU-R BT.601 luma weights.
+///
+pub fn encode_pgm(image: &Image) -> Vec<u8> {
+    let mut bytes = format!("P5\n{} {}\n255\n", image.width(), image.height()).into_bytes();
+    for row in image.rows() {
+        bytes.extend(row.iter().map(|&[red, green, blue]| ((299 * red as u32 + 587 * green as u32 + 114 * blue as u32) / 1000) as u8));
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BLACK, WHITE};
+
+    #[test]
+    fn test_encode_ppm() {
+        let mut image = Image::new(2, 1, BLACK);
+        image.set_pixel(1, 0, [1, 2, 3]);
+        assert_eq!(encode_ppm(&image), b"P6\n2 1\n255\n\x00\x00\x00\x01\x02\x03".to_vec());
+    }
+
+    #[test]
+    fn test_encode_pgm() {
+        let mut image = Image::new(2, 1, BLACK);
+        image.set_pixel(1, 0, WHITE);
+        assert_eq!(encode_pgm(&image), b"P5\n2 1\n255\n\x00\xff".to_vec());
+    }
+}
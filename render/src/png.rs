@@ -0,0 +1,310 @@
+//! PNG encoder for 8 bit RGB images.
+//! Description: https://www.w3.org/TR/png/
+//!
+//! The image data is compressed with a small deflate implementation using
+//! LZ77 with hash chains and the fixed Huffman codes from RFC 1951.
+
+use crate::Image;
+
+///
+/// Every PNG file starts with these bytes.
+///
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+///
+/// Largest window size allowed by deflate.
+///
+const WINDOW_SIZE: usize = 32768;
+
+///
+/// Shortest and longest match deflate can encode.
+///
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+///
+/// Number of earlier positions tried for each match. Higher values compress better but slower.
+///
+const MAX_CHAIN: usize = 64;
+
+///
+/// Base length and extra bits for the length symbols 257-285.
+///
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+
+///
+/// Base distance and extra bits for the distance symbols 0-29.
+///
+const DISTANCE_BASE: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+///
+/// Encodes the image as a PNG file.
+///
+/// image: The image to encode.
+///
+/// result: The bytes of the PNG file.
+///
+pub fn encode(image: &Image) -> Vec<u8> {
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(image.width() as u32).to_be_bytes());
+    header.extend_from_slice(&(image.height() as u32).to_be_bytes());
+    // Bit depth 8, color type 2 (RGB), default compression, filter and no interlace.
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    // Each scanline starts with the filter type. Filter 0 keeps the bytes as they are.
+    let mut raw = Vec::with_capacity(image.height() * (image.width() * 3 + 1));
+    for row in image.rows() {
+        raw.push(0);
+        row.iter().for_each(|pixel| raw.extend_from_slice(pixel));
+    }
+
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_compress(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+///
+/// Writes a chunk with length, type, data and CRC.
+///
+fn write_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+///
+/// CRC-32 as used by PNG and zlib, polynomial 0xEDB88320 (reflected).
+///
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+///
+/// Adler-32 checksum used at the end of a zlib stream.
+///
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+///
+/// Compresses the data into a zlib stream with a single fixed Huffman deflate block.
+///
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    // CMF 0x78: deflate with 32K window. FLG 0x01 makes the header a multiple of 31.
+    let mut output = vec![0x78, 0x01];
+    let mut writer = BitWriter::new(output);
+    // BFINAL = 1, BTYPE = 01 (fixed Huffman codes).
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+    deflate_fixed(data, &mut writer);
+    output = writer.finish();
+    output.extend_from_slice(&adler32(data).to_be_bytes());
+    output
+}
+
+///
+/// Writes the data as LZ77 literals and matches using the fixed Huffman codes.
+///
+fn deflate_fixed(data: &[u8], writer: &mut BitWriter) {
+    const HASH_SIZE: usize = 1 << 15;
+    let mut head = vec![usize::MAX; HASH_SIZE];
+    let mut previous = vec![usize::MAX; data.len()];
+    let hash = |position: usize| -> usize {
+        let value = (data[position] as usize) << 16 | (data[position + 1] as usize) << 8 | data[position + 2] as usize;
+        (value.wrapping_mul(2_654_435_761) >> 8) & (HASH_SIZE - 1)
+    };
+    let insert = |position: usize, head: &mut Vec<usize>, previous: &mut Vec<usize>| {
+        if position + MIN_MATCH <= data.len() {
+            let key = hash(position);
+            previous[position] = head[key];
+            head[key] = position;
+        }
+    };
+
+    let mut position = 0;
+    while position < data.len() {
+        let (length, distance) = longest_match(data, position, &head, &previous, hash);
+        if length >= MIN_MATCH {
+            write_length(writer, length);
+            write_distance(writer, distance);
+            for offset in 0..length {
+                insert(position + offset, &mut head, &mut previous);
+            }
+            position += length;
+        } else {
+            write_literal(writer, data[position] as u16);
+            insert(position, &mut head, &mut previous);
+            position += 1;
+        }
+    }
+    // End of block.
+    write_literal(writer, 256);
+}
+
+///
+/// Finds the longest earlier match for the bytes at the position by following the hash chain.
+///
+/// result: The length and distance of the match. Length 0 if no match was found.
+///
+fn longest_match(data: &[u8], position: usize, head: &[usize], previous: &[usize], hash: impl Fn(usize) -> usize) -> (usize, usize) {
+    if position + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+    let max_length = MAX_MATCH.min(data.len() - position);
+    let mut candidate = head[hash(position)];
+    let (mut best_length, mut best_distance) = (0, 0);
+    let mut chain = 0;
+    while candidate != usize::MAX && position - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+        let length = data[candidate..].iter().zip(&data[position..position + max_length]).take_while(|(a, b)| a == b).count();
+        if length > best_length {
+            best_length = length;
+            best_distance = position - candidate;
+            if length == max_length {
+                break;
+            }
+        }
+        candidate = previous[candidate];
+        chain += 1;
+    }
+    (best_length, best_distance)
+}
+
+///
+/// Writes a literal or the end of block symbol (256) with the fixed Huffman code.
+///
+fn write_literal(writer: &mut BitWriter, symbol: u16) {
+    match symbol {
+        0..=143 => writer.write_huffman(0x30 + symbol as u32, 8),
+        144..=255 => writer.write_huffman(0x190 + (symbol as u32 - 144), 9),
+        256..=279 => writer.write_huffman(symbol as u32 - 256, 7),
+        _ => writer.write_huffman(0xc0 + (symbol as u32 - 280), 8),
+    }
+}
+
+///
+/// Writes the length symbol and its extra bits.
+///
+fn write_length(writer: &mut BitWriter, length: usize) {
+    let index = LENGTH_BASE.iter().rposition(|&base| base as usize <= length).expect("Length is at least 3");
+    write_literal(writer, 257 + index as u16);
+    writer.write_bits((length - LENGTH_BASE[index] as usize) as u32, LENGTH_EXTRA[index] as u32);
+}
+
+///
+/// Writes the distance symbol with the fixed 5 bit code and its extra bits.
+///
+fn write_distance(writer: &mut BitWriter, distance: usize) {
+    let index = DISTANCE_BASE.iter().rposition(|&base| base as usize <= distance).expect("Distance is at least 1");
+    writer.write_huffman(index as u32, 5);
+    writer.write_bits((distance - DISTANCE_BASE[index] as usize) as u32, DISTANCE_EXTRA[index] as u32);
+}
+
+///
+/// BitWriter packs bits into bytes starting with the least significant bit, as deflate requires.
+///
+struct BitWriter {
+    output: Vec<u8>,
+    buffer: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn new(output: Vec<u8>) -> BitWriter {
+        BitWriter { output, buffer: 0, count: 0 }
+    }
+
+    ///
+    /// Writes the lowest bits of the value, least significant bit first.
+    ///
+    fn write_bits(&mut self, value: u32, bits: u32) {
+        self.buffer |= (value as u64) << self.count;
+        self.count += bits;
+        while self.count >= 8 {
+            self.output.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    ///
+    /// Writes a Huffman code. Huffman codes are stored most significant bit first.
+    ///
+    fn write_huffman(&mut self, code: u32, bits: u32) {
+        self.write_bits(code.reverse_bits() >> (32 - bits), bits);
+    }
+
+    ///
+    /// Flushes the remaining bits, padding the last byte with zeros.
+    ///
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.output.push(self.buffer as u8);
+        }
+        self.output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BLACK, WHITE};
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_zlib_known_output() {
+        // An empty fixed Huffman block, and a block with a single literal, as zlib decodes them.
+        assert_eq!(zlib_compress(b""), vec![0x78, 0x01, 0x03, 0x00, 0x00, 0x00, 0x00, 0x01]);
+        assert_eq!(zlib_compress(b"a"), vec![0x78, 0x01, 0x4b, 0x04, 0x00, 0x00, 0x62, 0x00, 0x62]);
+    }
+
+    #[test]
+    fn test_zlib_compresses_repetition() {
+        let data = vec![7u8; 10_000];
+        let compressed = zlib_compress(&data);
+        assert!(compressed.len() < 200);
+    }
+
+    #[test]
+    fn test_png_structure() {
+        let image = Image::from_cells(4, 4, 2, |x, y| if (x + y) % 2 == 0 { WHITE } else { BLACK });
+        let png = encode(&image);
+        assert_eq!(&png[..8], &SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(u32::from_be_bytes([png[16], png[17], png[18], png[19]]), 8);
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+    }
+}
@@ -0,0 +1,75 @@
+//! Helpers for drawing cell grids in the terminal.
+//!
+//! Two cell rows are drawn per text line using the Unicode half block
+//! characters, so the cells look roughly square in most fonts.
+
+///
+/// ANSI sequence clearing the screen and moving the cursor to the top left.
+///
+pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+///
+/// ANSI sequence moving the cursor to the top left without clearing, which flickers less
+/// when the new frame has the same size.
+///
+pub const CURSOR_HOME: &str = "\x1b[H";
+
+///
+/// Draws a grid of on/off cells using half blocks.
+///
+/// width: Number of cells horizontally.
+/// height: Number of cells vertically.
+/// is_on: Returns true if the cell at (x, y) is set.
+///
+/// result: The text with one line per two cell rows, each line ending with a newline.
+///
+pub fn render_cells(width: usize, height: usize, is_on: impl Fn(usize, usize) -> bool) -> String {
+    let mut text = String::with_capacity((width + 1) * height.div_ceil(2));
+    for y in (0..height).step_by(2) {
+        for x in 0..width {
+            let top = is_on(x, y);
+            let bottom = y + 1 < height && is_on(x, y + 1);
+            text.push(match (top, bottom) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            });
+        }
+        text.push('\n');
+    }
+    text
+}
+
+///
+/// Draws a grid of symbols with one character per cell. Used for multi-state cells
+/// where the half block drawing can not show the state.
+///
+/// symbol_of: Returns the character for the cell at (x, y).
+///
+pub fn render_symbols(width: usize, height: usize, symbol_of: impl Fn(usize, usize) -> char) -> String {
+    let mut text = String::with_capacity((width + 1) * height);
+    for y in 0..height {
+        text.extend((0..width).map(|x| symbol_of(x, y)));
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_cells() {
+        let cells = [[true, false], [true, true], [false, true]];
+        let text = render_cells(2, 3, |x, y| cells[y][x]);
+        assert_eq!(text, "█▄\n ▀\n");
+    }
+
+    #[test]
+    fn test_render_symbols() {
+        let text = render_symbols(3, 2, |x, y| if x == y { '#' } else { '.' });
+        assert_eq!(text, "#..\n.#.\n");
+    }
+}
@@ -0,0 +1,37 @@
+use clap::{Parser, ValueEnum};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// File with puzzles to solve. Reads from stdin if not specified
+    #[arg(short, long)]
+    pub input_file: Option<String>,
+
+    /// Algorithm used to solve the puzzles
+    #[arg(short, long, value_enum, default_value_t = Algorithm::Propagation)]
+    pub algorithm: Algorithm,
+
+    /// Generate the specified number of puzzles instead of solving
+    #[arg(short, long)]
+    pub generate: Option<usize>,
+
+    /// Number of clues to keep in generated puzzles
+    #[arg(short, long, default_value_t = 0)]
+    pub clues: usize,
+
+    /// Seed for the puzzle generator
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// Print puzzles and solutions on a single line
+    #[arg(short, long)]
+    pub line: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Constraint propagation with backtracking
+    Propagation,
+    /// Algorithm X with dancing links
+    Dlx,
+}
@@ -0,0 +1,291 @@
+use crate::solver::SudokuSolver;
+use crate::{CELLS, SIZE, Sudoku, box_of};
+
+///
+/// Number of constraint columns. Each cell must be filled, and each digit must
+/// appear once in every row, column and box: 4 * 81 columns.
+///
+const COLUMNS: usize = 4 * CELLS;
+
+///
+/// Number of candidate rows. One row for each digit in each cell.
+///
+const ROWS: usize = CELLS * SIZE;
+
+///
+/// Index of the root header node.
+///
+const ROOT: usize = 0;
+
+///
+/// Solver using Knuth's Algorithm X with dancing links.
+/// Description: https://en.wikipedia.org/wiki/Dancing_Links
+///
+/// The sudoku is reduced to an exact cover problem where each candidate
+/// (cell, digit) is a row covering four constraint columns. The links are
+/// stored in flat vectors indexed by node id instead of pointers. Node 0 is
+/// the root, nodes 1..=324 are the column headers and the rest are the
+/// candidate nodes, four per row.
+///
+#[derive(Default)]
+pub struct DlxSolver {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    // Column header of each node.
+    column: Vec<usize>,
+    // Candidate row of each node.
+    row: Vec<usize>,
+    // Number of nodes in each column, indexed by header node.
+    size: Vec<usize>,
+    // First node of each candidate row.
+    row_start: Vec<usize>,
+    // Candidate rows in the current partial solution.
+    selected: Vec<usize>,
+}
+
+impl DlxSolver {
+    ///
+    /// Creates a new solver.
+    ///
+    pub fn new() -> DlxSolver {
+        DlxSolver::default()
+    }
+
+    ///
+    /// Returns the four constraint columns (0 based) covered by placing the digit in the cell.
+    ///
+    /// digit: 0 based digit.
+    ///
+    fn constraints(cell: usize, digit: usize) -> [usize; 4] {
+        let row = cell / SIZE;
+        let column = cell % SIZE;
+        [cell, CELLS + row * SIZE + digit, 2 * CELLS + column * SIZE + digit, 3 * CELLS + box_of(cell) * SIZE + digit]
+    }
+
+    ///
+    /// Builds the full linked matrix with all 729 candidate rows.
+    ///
+    fn build(&mut self) {
+        let nodes = 1 + COLUMNS + ROWS * 4;
+        self.left = vec![0; nodes];
+        self.right = vec![0; nodes];
+        self.up = (0..nodes).collect();
+        self.down = (0..nodes).collect();
+        self.column = (0..nodes).collect();
+        self.row = vec![0; nodes];
+        self.size = vec![0; 1 + COLUMNS];
+        self.row_start = vec![0; ROWS];
+        self.selected.clear();
+        for header in 0..=COLUMNS {
+            self.left[header] = if header == 0 { COLUMNS } else { header - 1 };
+            self.right[header] = if header == COLUMNS { 0 } else { header + 1 };
+        }
+        let mut node = COLUMNS + 1;
+        for candidate in 0..ROWS {
+            let constraints = Self::constraints(candidate / SIZE, candidate % SIZE);
+            self.row_start[candidate] = node;
+            for (idx, constraint) in constraints.iter().enumerate() {
+                let header = constraint + 1;
+                // Insert the node at the bottom of the column.
+                self.column[node] = header;
+                self.row[node] = candidate;
+                self.up[node] = self.up[header];
+                self.down[node] = header;
+                self.down[self.up[header]] = node;
+                self.up[header] = node;
+                self.size[header] += 1;
+                // Link the four nodes of the row in a circle.
+                self.left[node] = if idx == 0 { node + 3 } else { node - 1 };
+                self.right[node] = if idx == 3 { node - 3 } else { node + 1 };
+                node += 1;
+            }
+        }
+    }
+
+    ///
+    /// Removes the column from the header list and all rows in it from the other columns.
+    ///
+    fn cover(&mut self, header: usize) {
+        self.right[self.left[header]] = self.right[header];
+        self.left[self.right[header]] = self.left[header];
+        let mut row_node = self.down[header];
+        while row_node != header {
+            let mut node = self.right[row_node];
+            while node != row_node {
+                self.down[self.up[node]] = self.down[node];
+                self.up[self.down[node]] = self.up[node];
+                self.size[self.column[node]] -= 1;
+                node = self.right[node];
+            }
+            row_node = self.down[row_node];
+        }
+    }
+
+    ///
+    /// Reverses cover. Must be called in the exact reverse order of cover.
+    ///
+    fn uncover(&mut self, header: usize) {
+        let mut row_node = self.up[header];
+        while row_node != header {
+            let mut node = self.left[row_node];
+            while node != row_node {
+                self.size[self.column[node]] += 1;
+                self.down[self.up[node]] = node;
+                self.up[self.down[node]] = node;
+                node = self.left[node];
+            }
+            row_node = self.up[row_node];
+        }
+        self.right[self.left[header]] = header;
+        self.left[self.right[header]] = header;
+    }
+
+    ///
+    /// Returns true if the column header is still linked in the header list.
+    ///
+    fn is_active(&self, header: usize) -> bool {
+        self.right[self.left[header]] == header
+    }
+
+    ///
+    /// Selects the candidate row permanently, used for the given digits.
+    ///
+    /// result: false if one of the columns was already covered by an earlier given.
+    ///
+    fn select_given(&mut self, candidate: usize) -> bool {
+        let start = self.row_start[candidate];
+        let mut node = start;
+        loop {
+            if !self.is_active(self.column[node]) {
+                return false;
+            }
+            self.cover(self.column[node]);
+            node = self.right[node];
+            if node == start {
+                break;
+            }
+        }
+        self.selected.push(candidate);
+        true
+    }
+
+    ///
+    /// Algorithm X. Chooses the column with the fewest rows and tries each row in it.
+    ///
+    /// solutions: Solutions found so far.
+    /// limit: Stop when this many solutions have been found.
+    ///
+    fn search(&mut self, solutions: &mut Vec<Sudoku>, limit: usize) {
+        if self.right[ROOT] == ROOT {
+            solutions.push(self.current_solution());
+            return;
+        }
+        let mut header = self.right[ROOT];
+        let mut best = header;
+        while header != ROOT {
+            if self.size[header] < self.size[best] {
+                best = header;
+            }
+            header = self.right[header];
+        }
+        if self.size[best] == 0 {
+            return;
+        }
+        self.cover(best);
+        let mut row_node = self.down[best];
+        while row_node != best && solutions.len() < limit {
+            self.selected.push(self.row[row_node]);
+            let mut node = self.right[row_node];
+            while node != row_node {
+                self.cover(self.column[node]);
+                node = self.right[node];
+            }
+            self.search(solutions, limit);
+            let mut node = self.left[row_node];
+            while node != row_node {
+                self.uncover(self.column[node]);
+                node = self.left[node];
+            }
+            self.selected.pop();
+            row_node = self.down[row_node];
+        }
+        self.uncover(best);
+    }
+
+    ///
+    /// Converts the selected candidate rows into a grid.
+    ///
+    fn current_solution(&self) -> Sudoku {
+        let mut sudoku = Sudoku::empty();
+        for candidate in &self.selected {
+            let cell = candidate / SIZE;
+            sudoku.set(cell / SIZE, cell % SIZE, (candidate % SIZE + 1) as u8);
+        }
+        sudoku
+    }
+
+    ///
+    /// Finds up to limit solutions of the puzzle.
+    ///
+    fn solutions(&mut self, puzzle: &Sudoku, limit: usize) -> Vec<Sudoku> {
+        self.build();
+        let mut solutions = Vec::new();
+        for (cell, &value) in puzzle.cells().iter().enumerate() {
+            if value != 0 && !self.select_given(cell * SIZE + value as usize - 1) {
+                return solutions;
+            }
+        }
+        self.search(&mut solutions, limit);
+        solutions
+    }
+}
+
+impl SudokuSolver for DlxSolver {
+    fn solve(&mut self, puzzle: &Sudoku) -> Option<Sudoku> {
+        self.solutions(puzzle, 1).pop()
+    }
+
+    fn count_solutions(&mut self, puzzle: &Sudoku, limit: usize) -> usize {
+        self.solutions(puzzle, limit).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Solver;
+
+    const HARD: &str = "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..";
+    const HARD_SOLUTION: &str = "812753649943682175675491283154237896369845721287169534521974368438526917796318452";
+
+    #[test]
+    fn test_solve_hard() {
+        let puzzle: Sudoku = HARD.parse().unwrap();
+        let solution = DlxSolver::new().solve(&puzzle).unwrap();
+        assert_eq!(solution.to_line(), HARD_SOLUTION);
+    }
+
+    #[test]
+    fn test_matches_propagation_solver() {
+        let puzzles = [
+            "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......",
+            "52...6.........7.13...........4..8..6......5...........418.........3..2...87.....",
+            "6.....8.3.4.7.................5.4.7.3..2.....1.6.......2.....5.....8.6......1....",
+        ];
+        for text in puzzles {
+            let puzzle: Sudoku = text.parse().unwrap();
+            let expected = Solver::new().solve(&puzzle).unwrap();
+            let solution = DlxSolver::new().solve(&puzzle).unwrap();
+            assert_eq!(expected, solution);
+        }
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let mut solver = DlxSolver::new();
+        assert_eq!(solver.count_solutions(&HARD.parse().unwrap(), 2), 1);
+        assert_eq!(solver.count_solutions(&Sudoku::empty(), 3), 3);
+    }
+}
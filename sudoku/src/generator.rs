@@ -0,0 +1,123 @@
+use prng::{Rng, Xoshiro256StarStar};
+
+use crate::solver::{Solver, SudokuSolver};
+use crate::{BOX_SIZE, CELLS, SIZE, Sudoku};
+
+///
+/// Lowest number of clues a sudoku with a unique solution can have.
+///
+pub const MIN_CLUES: usize = 17;
+
+///
+/// Generator creating random puzzles with a unique solution.
+///
+/// A random complete grid is created first. Cells are then removed one at a
+/// time in random order, and a removal is kept only if the puzzle still has
+/// exactly one solution.
+///
+pub struct Generator<R: Rng = Xoshiro256StarStar> {
+    rng: R,
+}
+
+impl Generator<Xoshiro256StarStar> {
+    ///
+    /// Creates a generator with a reproducible sequence of puzzles for the seed.
+    ///
+    pub fn from_seed(seed: u64) -> Generator<Xoshiro256StarStar> {
+        Generator::new(Xoshiro256StarStar::seed_from_u64(seed))
+    }
+}
+
+impl<R: Rng> Generator<R> {
+    ///
+    /// Creates a generator using the random number generator.
+    ///
+    pub fn new(rng: R) -> Generator<R> {
+        Generator { rng }
+    }
+
+    ///
+    /// Creates a random complete grid.
+    ///
+    /// The three boxes on the diagonal do not share any unit, so they are filled
+    /// with random permutations first. The rest is solved with a shuffled digit order.
+    ///
+    pub fn full_grid(&mut self) -> Sudoku {
+        let mut grid = Sudoku::empty();
+        for diagonal in 0..BOX_SIZE {
+            let mut digits: [u8; SIZE] = std::array::from_fn(|idx| idx as u8 + 1);
+            self.rng.shuffle(&mut digits);
+            for (idx, digit) in digits.iter().enumerate() {
+                grid.set(diagonal * BOX_SIZE + idx / BOX_SIZE, diagonal * BOX_SIZE + idx % BOX_SIZE, *digit);
+            }
+        }
+        let mut digit_order: [u8; SIZE] = std::array::from_fn(|idx| idx as u8 + 1);
+        self.rng.shuffle(&mut digit_order);
+        Solver::with_digit_order(digit_order).solve(&grid).expect("Diagonal boxes can always be completed")
+    }
+
+    ///
+    /// Creates a puzzle with a unique solution.
+    ///
+    /// target_clues: Stop removing cells when the puzzle has this many clues. Values below
+    ///               17 remove as many cells as possible while keeping the solution unique.
+    ///
+    /// result: The puzzle and its solution.
+    ///
+    pub fn generate(&mut self, target_clues: usize) -> (Sudoku, Sudoku) {
+        let solution = self.full_grid();
+        let mut puzzle = solution;
+        let mut cells: Vec<usize> = (0..CELLS).collect();
+        self.rng.shuffle(&mut cells);
+        let mut solver = Solver::new();
+        for cell in cells {
+            if puzzle.clues() <= target_clues.max(MIN_CLUES) {
+                break;
+            }
+            let (row, column) = (cell / SIZE, cell % SIZE);
+            let value = puzzle.get(row, column);
+            puzzle.set(row, column, 0);
+            if solver.count_solutions(&puzzle, 2) != 1 {
+                puzzle.set(row, column, value);
+            }
+        }
+        (puzzle, solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dlx::DlxSolver;
+
+    #[test]
+    fn test_full_grid_is_solved() {
+        let mut generator = Generator::from_seed(1);
+        let grid = generator.full_grid();
+        assert!(grid.is_solved());
+        assert_ne!(grid, generator.full_grid());
+    }
+
+    #[test]
+    fn test_generate_unique() {
+        let mut generator = Generator::from_seed(2);
+        let (puzzle, solution) = generator.generate(0);
+        assert!(puzzle.clues() < 40);
+        assert!(solution.is_completion_of(&puzzle));
+        assert_eq!(DlxSolver::new().count_solutions(&puzzle, 2), 1);
+    }
+
+    #[test]
+    fn test_generate_target_clues() {
+        let mut generator = Generator::from_seed(3);
+        let (puzzle, _) = generator.generate(50);
+        assert_eq!(puzzle.clues(), 50);
+    }
+
+    #[test]
+    fn test_generate_reproducible() {
+        let first = Generator::from_seed(4).generate(30);
+        let second = Generator::from_seed(4).generate(30);
+        assert_eq!(first, second);
+    }
+}
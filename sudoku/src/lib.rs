@@ -0,0 +1,342 @@
+///
+/// Sudoku solver and generator.
+/// Description: https://en.wikipedia.org/wiki/Sudoku_solving_algorithms
+///
+/// Two solvers are provided. The default solver uses constraint propagation
+/// (naked and hidden singles) and falls back to backtracking on the cell with
+/// the fewest candidates. The second solver reduces the puzzle to an exact
+/// cover problem and solves it with Knuth's Algorithm X using dancing links.
+///
+pub mod dlx;
+pub mod generator;
+pub mod solver;
+
+use std::fmt;
+use std::str::FromStr;
+
+///
+/// Side length of the grid and number of digits.
+///
+pub const SIZE: usize = 9;
+
+///
+/// Side length of a box.
+///
+pub const BOX_SIZE: usize = 3;
+
+///
+/// Total number of cells in the grid.
+///
+pub const CELLS: usize = SIZE * SIZE;
+
+///
+/// SudokuError enum to represent errors when reading a puzzle.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SudokuError {
+    // The puzzle did not contain exactly 81 cells.
+    InvalidLength { length: usize },
+    // The puzzle contained a character that is not a digit or an empty marker.
+    InvalidCharacter { character: char },
+    // The given digits break the sudoku rules, for example two equal digits in a row.
+    Conflict { cell: usize },
+}
+
+impl fmt::Display for SudokuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SudokuError::InvalidLength { length } => write!(f, "Expected {CELLS} cells, found {length}"),
+            SudokuError::InvalidCharacter { character } => write!(f, "Invalid character {character:?}"),
+            SudokuError::Conflict { cell } => write!(f, "Conflicting digit at row {} column {}", cell / SIZE + 1, cell % SIZE + 1),
+        }
+    }
+}
+
+impl std::error::Error for SudokuError {}
+
+///
+/// Sudoku struct containing the 81 cells of the grid in row-major order.
+/// A value of 0 marks an empty cell, 1 to 9 are digits.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Sudoku {
+    cells: [u8; CELLS],
+}
+
+impl Sudoku {
+    ///
+    /// Creates a new Sudoku from the cells.
+    ///
+    /// cells: 81 values in row-major order. 0 is empty, 1-9 are digits.
+    ///
+    /// result: The Sudoku or an error if a value is larger than 9 or
+    ///         the digits conflict with each other.
+    ///
+    pub fn new(cells: [u8; CELLS]) -> Result<Sudoku, SudokuError> {
+        if let Some(position) = cells.iter().position(|&value| value as usize > SIZE) {
+            return Err(SudokuError::InvalidCharacter { character: char::from(b'0' + cells[position] % 10) });
+        }
+        let sudoku = Sudoku { cells };
+        match sudoku.find_conflict() {
+            Some(cell) => Err(SudokuError::Conflict { cell }),
+            None => Ok(sudoku),
+        }
+    }
+
+    ///
+    /// Creates an empty grid.
+    ///
+    pub fn empty() -> Sudoku {
+        Sudoku { cells: [0; CELLS] }
+    }
+
+    ///
+    /// Parses every puzzle in the text. Cells are read in order, ignoring whitespace
+    /// and the separator characters '|', '-' and '+', and every 81 cells form a puzzle.
+    /// This accepts both the one-line-per-puzzle format and the 9 line grid format.
+    ///
+    /// text: The text to parse.
+    ///
+    /// result: The puzzles in the order they appear.
+    ///
+    pub fn parse_all(text: &str) -> Result<Vec<Sudoku>, SudokuError> {
+        let cells = Self::parse_cells(text)?;
+        if cells.is_empty() || cells.len() % CELLS != 0 {
+            return Err(SudokuError::InvalidLength { length: cells.len() % CELLS });
+        }
+        cells
+            .chunks(CELLS)
+            .map(|chunk| {
+                let mut grid = [0u8; CELLS];
+                grid.copy_from_slice(chunk);
+                Sudoku::new(grid)
+            })
+            .collect()
+    }
+
+    ///
+    /// Reads the cell values from the text. Digits 1-9 are values, '0', '.' and '_'
+    /// are empty cells.
+    ///
+    fn parse_cells(text: &str) -> Result<Vec<u8>, SudokuError> {
+        let mut cells = Vec::with_capacity(CELLS);
+        for character in text.chars() {
+            match character {
+                '1'..='9' => cells.push(character as u8 - b'0'),
+                '0' | '.' | '_' => cells.push(0),
+                '|' | '-' | '+' => {}
+                character if character.is_whitespace() => {}
+                character => return Err(SudokuError::InvalidCharacter { character }),
+            }
+        }
+        Ok(cells)
+    }
+
+    ///
+    /// Returns the value of the cell. 0 is empty.
+    ///
+    pub fn get(&self, row: usize, column: usize) -> u8 {
+        self.cells[row * SIZE + column]
+    }
+
+    ///
+    /// Sets the value of a cell. No validation is done.
+    ///
+    pub fn set(&mut self, row: usize, column: usize, value: u8) {
+        self.cells[row * SIZE + column] = value;
+    }
+
+    ///
+    /// Returns all cells in row-major order.
+    ///
+    pub fn cells(&self) -> &[u8; CELLS] {
+        &self.cells
+    }
+
+    ///
+    /// Number of cells containing a digit.
+    ///
+    pub fn clues(&self) -> usize {
+        self.cells.iter().filter(|&&value| value != 0).count()
+    }
+
+    ///
+    /// Returns true if every cell has a digit and no rule is broken.
+    ///
+    pub fn is_solved(&self) -> bool {
+        self.clues() == CELLS && self.find_conflict().is_none()
+    }
+
+    ///
+    /// Returns true if the other grid keeps every digit given in this grid.
+    /// Used to check that a solution belongs to a puzzle.
+    ///
+    pub fn is_completion_of(&self, puzzle: &Sudoku) -> bool {
+        self.cells.iter().zip(puzzle.cells.iter()).all(|(&value, &given)| given == 0 || value == given)
+    }
+
+    ///
+    /// Formats the grid on a single line with '.' for empty cells.
+    ///
+    pub fn to_line(&self) -> String {
+        self.cells.iter().map(|&value| if value == 0 { '.' } else { char::from(b'0' + value) }).collect()
+    }
+
+    ///
+    /// Finds the first cell whose digit also appears in the same row, column or box.
+    ///
+    fn find_conflict(&self) -> Option<usize> {
+        (0..CELLS).find(|&cell| {
+            let value = self.cells[cell];
+            value != 0 && peers(cell).any(|peer| self.cells[peer] == value)
+        })
+    }
+}
+
+impl FromStr for Sudoku {
+    type Err = SudokuError;
+
+    ///
+    /// Parses a single puzzle. See parse_all for the accepted format.
+    ///
+    fn from_str(text: &str) -> Result<Sudoku, SudokuError> {
+        let cells = Self::parse_cells(text)?;
+        if cells.len() != CELLS {
+            return Err(SudokuError::InvalidLength { length: cells.len() });
+        }
+        let mut grid = [0u8; CELLS];
+        grid.copy_from_slice(&cells);
+        Sudoku::new(grid)
+    }
+}
+
+impl fmt::Display for Sudoku {
+    ///
+    /// Formats the grid as 9 lines with separators between the boxes.
+    ///
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..SIZE {
+            if row > 0 && row % BOX_SIZE == 0 {
+                writeln!(f, "------+-------+------")?;
+            }
+            for column in 0..SIZE {
+                if column > 0 && column % BOX_SIZE == 0 {
+                    write!(f, "| ")?;
+                }
+                match self.get(row, column) {
+                    0 => write!(f, ".")?,
+                    value => write!(f, "{value}")?,
+                }
+                if column + 1 < SIZE {
+                    write!(f, " ")?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// Returns the index of the box containing the cell.
+///
+pub fn box_of(cell: usize) -> usize {
+    (cell / SIZE / BOX_SIZE) * BOX_SIZE + (cell % SIZE) / BOX_SIZE
+}
+
+///
+/// Returns the cells sharing a row, column or box with the cell, excluding the cell itself.
+/// A cell has 20 peers.
+///
+pub fn peers(cell: usize) -> impl Iterator<Item = usize> {
+    let row = cell / SIZE;
+    let column = cell % SIZE;
+    let cell_box = box_of(cell);
+    (0..CELLS).filter(move |&other| other != cell && (other / SIZE == row || other % SIZE == column || box_of(other) == cell_box))
+}
+
+///
+/// Returns the 27 units of the grid (9 rows, 9 columns and 9 boxes), each a list of 9 cells.
+///
+pub fn units() -> Vec<[usize; SIZE]> {
+    let mut units = Vec::with_capacity(3 * SIZE);
+    for row in 0..SIZE {
+        units.push(std::array::from_fn(|column| row * SIZE + column));
+    }
+    for column in 0..SIZE {
+        units.push(std::array::from_fn(|row| row * SIZE + column));
+    }
+    for cell_box in 0..SIZE {
+        let top = (cell_box / BOX_SIZE) * BOX_SIZE;
+        let left = (cell_box % BOX_SIZE) * BOX_SIZE;
+        units.push(std::array::from_fn(|idx| (top + idx / BOX_SIZE) * SIZE + left + idx % BOX_SIZE));
+    }
+    units
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EASY: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    #[test]
+    fn test_parse_line() {
+        let sudoku: Sudoku = EASY.parse().unwrap();
+        assert_eq!(sudoku.get(0, 0), 5);
+        assert_eq!(sudoku.get(0, 2), 0);
+        assert_eq!(sudoku.get(8, 8), 9);
+        assert_eq!(sudoku.clues(), 30);
+        assert_eq!(sudoku.to_line(), EASY);
+    }
+
+    #[test]
+    fn test_parse_grid_roundtrip() {
+        let sudoku: Sudoku = EASY.parse().unwrap();
+        let text = sudoku.to_string();
+        let parsed: Sudoku = text.parse().unwrap();
+        assert_eq!(sudoku, parsed);
+    }
+
+    #[test]
+    fn test_parse_all() {
+        let text = format!("{EASY}\n\n{EASY}\n");
+        let puzzles = Sudoku::parse_all(&text).unwrap();
+        assert_eq!(puzzles.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let text = std::fs::read_to_string("testdata/puzzles.txt").unwrap();
+        let puzzles = Sudoku::parse_all(&text).unwrap();
+        assert_eq!(puzzles.len(), 3);
+        assert_eq!(puzzles[0].to_line(), EASY);
+    }
+
+    #[test]
+    fn test_parse_invalid_length() {
+        assert_eq!("123".parse::<Sudoku>(), Err(SudokuError::InvalidLength { length: 3 }));
+    }
+
+    #[test]
+    fn test_parse_invalid_character() {
+        let text = EASY.replacen('.', "x", 1);
+        assert_eq!(text.parse::<Sudoku>(), Err(SudokuError::InvalidCharacter { character: 'x' }));
+    }
+
+    #[test]
+    fn test_parse_conflict() {
+        let text = format!("55{}", &EASY[2..]);
+        assert_eq!(text.parse::<Sudoku>(), Err(SudokuError::Conflict { cell: 0 }));
+    }
+
+    #[test]
+    fn test_peers_and_units() {
+        assert_eq!(peers(0).count(), 20);
+        assert_eq!(peers(40).count(), 20);
+        let units = units();
+        assert_eq!(units.len(), 27);
+        assert_eq!(units[18], [0, 1, 2, 9, 10, 11, 18, 19, 20]);
+        assert_eq!(units[26], [60, 61, 62, 69, 70, 71, 78, 79, 80]);
+    }
+}
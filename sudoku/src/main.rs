@@ -0,0 +1,110 @@
+mod args;
+
+use std::{fs, io::Read};
+
+use args::{Algorithm, Args};
+use clap::Parser;
+use prng::Xoshiro256StarStar;
+use sudoku::Sudoku;
+use sudoku::dlx::DlxSolver;
+use sudoku::generator::Generator;
+use sudoku::solver::{Solver, SudokuSolver};
+
+/**
+ * Sudoku solver and generator.
+ * Reads puzzles from a file or stdin and prints the solutions, or generates
+ * new puzzles with a unique solution.
+ */
+fn main() {
+    let args = Args::parse();
+    match args.generate {
+        Some(count) => generate(count, args.clues, args.seed, args.line),
+        None => solve(args.input_file, args.algorithm, args.line),
+    }
+}
+
+/**
+ * Reads the puzzles and prints the solution for each of them.
+ *
+ * # Arguments
+ * * `input_file`: The file to read puzzles from, stdin if None.
+ * * `algorithm`: The solver to use.
+ * * `line`: Print the solutions on a single line.
+ */
+fn solve(input_file: Option<String>, algorithm: Algorithm, line: bool) {
+    let text = match read_input(input_file) {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("Failed to read input: {err}");
+            return;
+        }
+    };
+    let puzzles = match Sudoku::parse_all(&text) {
+        Ok(puzzles) => puzzles,
+        Err(err) => {
+            eprintln!("Failed to parse puzzles: {err}");
+            return;
+        }
+    };
+    let mut solver: Box<dyn SudokuSolver> = match algorithm {
+        Algorithm::Propagation => Box::new(Solver::new()),
+        Algorithm::Dlx => Box::new(DlxSolver::new()),
+    };
+    for (index, puzzle) in puzzles.iter().enumerate() {
+        match solver.solve(puzzle) {
+            Some(solution) => print_grid(&solution, line),
+            None => println!("Puzzle {}: No solution", index + 1),
+        }
+        if !line && index + 1 < puzzles.len() {
+            println!();
+        }
+    }
+}
+
+/**
+ * Generates puzzles with a unique solution and prints them.
+ *
+ * # Arguments
+ * * `count`: Number of puzzles to generate.
+ * * `clues`: Number of clues to keep. 0 removes as many as possible.
+ * * `seed`: Seed for reproducible puzzles. Seeded from the clock if None.
+ * * `line`: Print the puzzles on a single line.
+ */
+fn generate(count: usize, clues: usize, seed: Option<u64>, line: bool) {
+    let mut generator = match seed {
+        Some(seed) => Generator::from_seed(seed),
+        None => Generator::new(Xoshiro256StarStar::from_entropy()),
+    };
+    for index in 0..count {
+        let (puzzle, _) = generator.generate(clues);
+        print_grid(&puzzle, line);
+        if !line && index + 1 < count {
+            println!();
+        }
+    }
+}
+
+/**
+ * Prints the grid either as a 9 line grid or on a single line.
+ */
+fn print_grid(sudoku: &Sudoku, line: bool) {
+    if line {
+        println!("{}", sudoku.to_line());
+    } else {
+        print!("{sudoku}");
+    }
+}
+
+/**
+ * Reads the whole input file, or stdin if no file is specified.
+ */
+fn read_input(input_file: Option<String>) -> std::io::Result<String> {
+    match input_file {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            Ok(text)
+        }
+    }
+}
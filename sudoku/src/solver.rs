@@ -0,0 +1,284 @@
+use crate::{CELLS, SIZE, Sudoku, peers, units};
+
+///
+/// Bitmask with a bit set for each digit 1-9. Bit 0 is unused.
+///
+const ALL_CANDIDATES: u16 = 0b11_1111_1110;
+
+///
+/// SudokuSolver is the common interface for the solvers in this crate.
+///
+pub trait SudokuSolver {
+    ///
+    /// Solves the puzzle.
+    ///
+    /// puzzle: The puzzle to solve.
+    ///
+    /// result: The first solution found, or None if the puzzle has no solution.
+    ///
+    fn solve(&mut self, puzzle: &Sudoku) -> Option<Sudoku>;
+
+    ///
+    /// Counts the solutions of the puzzle, stopping when the limit is reached.
+    /// Use a limit of 2 to check if a puzzle has a unique solution.
+    ///
+    /// puzzle: The puzzle to count solutions for.
+    /// limit: The maximum number of solutions to search for.
+    ///
+    /// result: The number of solutions found, at most limit.
+    ///
+    fn count_solutions(&mut self, puzzle: &Sudoku, limit: usize) -> usize;
+}
+
+///
+/// Statistics collected while solving.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Statistics {
+    // Number of cells resolved because only one candidate was left.
+    pub naked_singles: usize,
+    // Number of cells resolved because a digit had only one place left in a unit.
+    pub hidden_singles: usize,
+    // Number of guesses made by the backtracking search.
+    pub guesses: usize,
+}
+
+///
+/// Solver using constraint propagation and backtracking.
+///
+/// Each cell keeps a bitmask of candidate digits. When a candidate is eliminated
+/// two rules are applied until nothing changes:
+/// * Naked single: If a cell has one candidate left it is removed from all peers.
+/// * Hidden single: If a digit has one place left in a unit it is assigned there.
+///
+/// If the grid is not solved by propagation the cell with the fewest candidates
+/// is chosen and each candidate is tried in turn.
+///
+pub struct Solver {
+    // Peers of each cell.
+    peers: Vec<Vec<usize>>,
+    // Units containing each cell. Every cell is in three units.
+    cell_units: Vec<Vec<[usize; SIZE]>>,
+    // The order in which digits are tried when guessing.
+    digit_order: [u8; SIZE],
+    // Statistics for the last solve.
+    statistics: Statistics,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Solver {
+    ///
+    /// Creates a new solver trying digits in ascending order.
+    ///
+    pub fn new() -> Solver {
+        Self::with_digit_order([1, 2, 3, 4, 5, 6, 7, 8, 9])
+    }
+
+    ///
+    /// Creates a new solver trying digits in the specified order when guessing.
+    /// The generator uses a shuffled order to produce random grids.
+    ///
+    /// digit_order: A permutation of the digits 1-9.
+    ///
+    pub fn with_digit_order(digit_order: [u8; SIZE]) -> Solver {
+        let all_units = units();
+        let peers = (0..CELLS).map(|cell| peers(cell).collect()).collect();
+        let cell_units = (0..CELLS).map(|cell| all_units.iter().filter(|unit| unit.contains(&cell)).copied().collect()).collect();
+        Solver { peers, cell_units, digit_order, statistics: Statistics::default() }
+    }
+
+    ///
+    /// Returns the statistics for the last call to solve or count_solutions.
+    ///
+    pub fn statistics(&self) -> Statistics {
+        self.statistics
+    }
+
+    ///
+    /// Creates the candidate grid for the puzzle by assigning all given digits.
+    ///
+    /// result: The candidates or None if the givens contradict each other.
+    ///
+    fn initial_candidates(&mut self, puzzle: &Sudoku) -> Option<[u16; CELLS]> {
+        let mut candidates = [ALL_CANDIDATES; CELLS];
+        for (cell, &value) in puzzle.cells().iter().enumerate() {
+            if value != 0 && !self.assign(&mut candidates, cell, value) {
+                return None;
+            }
+        }
+        Some(candidates)
+    }
+
+    ///
+    /// Assigns the digit to the cell by eliminating all other candidates.
+    ///
+    /// result: false if a contradiction was found.
+    ///
+    fn assign(&mut self, candidates: &mut [u16; CELLS], cell: usize, digit: u8) -> bool {
+        let others = candidates[cell] & !(1 << digit);
+        (1..=SIZE as u8).filter(|other| others & (1 << other) != 0).all(|other| self.eliminate(candidates, cell, other))
+    }
+
+    ///
+    /// Eliminates the digit from the candidates of the cell and propagates
+    /// naked and hidden singles.
+    ///
+    /// result: false if a contradiction was found.
+    ///
+    fn eliminate(&mut self, candidates: &mut [u16; CELLS], cell: usize, digit: u8) -> bool {
+        let bit = 1 << digit;
+        if candidates[cell] & bit == 0 {
+            return true;
+        }
+        candidates[cell] &= !bit;
+        let remaining = candidates[cell];
+        if remaining == 0 {
+            return false;
+        }
+        // Naked single: the last candidate can not appear in any peer.
+        if remaining.count_ones() == 1 {
+            self.statistics.naked_singles += 1;
+            let last = remaining.trailing_zeros() as u8;
+            for idx in 0..self.peers[cell].len() {
+                let peer = self.peers[cell][idx];
+                if !self.eliminate(candidates, peer, last) {
+                    return false;
+                }
+            }
+        }
+        // Hidden single: if the digit has one place left in a unit it must go there.
+        for unit_idx in 0..self.cell_units[cell].len() {
+            let unit = self.cell_units[cell][unit_idx];
+            let mut places = unit.iter().filter(|&&other| candidates[other] & bit != 0);
+            match (places.next(), places.next()) {
+                (None, _) => return false,
+                (Some(&place), None) if candidates[place] != bit => {
+                    self.statistics.hidden_singles += 1;
+                    if !self.assign(candidates, place, digit) {
+                        return false;
+                    }
+                }
+                _ => {}
+            }
+        }
+        true
+    }
+
+    ///
+    /// Backtracking search on the cell with the fewest candidates.
+    ///
+    /// candidates: The current candidate grid after propagation.
+    /// solutions: Solutions found so far.
+    /// limit: Stop when this many solutions have been found.
+    ///
+    fn search(&mut self, candidates: [u16; CELLS], solutions: &mut Vec<Sudoku>, limit: usize) {
+        if solutions.len() >= limit {
+            return;
+        }
+        let cell = (0..CELLS).filter(|&cell| candidates[cell].count_ones() > 1).min_by_key(|&cell| candidates[cell].count_ones());
+        let cell = match cell {
+            Some(cell) => cell,
+            None => {
+                solutions.push(Self::to_sudoku(&candidates));
+                return;
+            }
+        };
+        for digit in self.digit_order {
+            if candidates[cell] & (1 << digit) == 0 {
+                continue;
+            }
+            self.statistics.guesses += 1;
+            let mut next = candidates;
+            if self.assign(&mut next, cell, digit) {
+                self.search(next, solutions, limit);
+                if solutions.len() >= limit {
+                    return;
+                }
+            }
+        }
+    }
+
+    ///
+    /// Converts a fully resolved candidate grid into a Sudoku.
+    ///
+    fn to_sudoku(candidates: &[u16; CELLS]) -> Sudoku {
+        let mut sudoku = Sudoku::empty();
+        for (cell, &mask) in candidates.iter().enumerate() {
+            sudoku.set(cell / SIZE, cell % SIZE, mask.trailing_zeros() as u8);
+        }
+        sudoku
+    }
+
+    ///
+    /// Finds up to limit solutions of the puzzle.
+    ///
+    fn solutions(&mut self, puzzle: &Sudoku, limit: usize) -> Vec<Sudoku> {
+        self.statistics = Statistics::default();
+        let mut solutions = Vec::new();
+        if let Some(candidates) = self.initial_candidates(puzzle) {
+            self.search(candidates, &mut solutions, limit);
+        }
+        solutions
+    }
+}
+
+impl SudokuSolver for Solver {
+    fn solve(&mut self, puzzle: &Sudoku) -> Option<Sudoku> {
+        self.solutions(puzzle, 1).pop()
+    }
+
+    fn count_solutions(&mut self, puzzle: &Sudoku, limit: usize) -> usize {
+        self.solutions(puzzle, limit).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EASY: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const EASY_SOLUTION: &str = "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+    const HARD: &str = "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..";
+    const HARD_SOLUTION: &str = "812753649943682175675491283154237896369845721287169534521974368438526917796318452";
+
+    #[test]
+    fn test_solve_easy_by_propagation() {
+        let puzzle: Sudoku = EASY.parse().unwrap();
+        let mut solver = Solver::new();
+        let solution = solver.solve(&puzzle).unwrap();
+        assert_eq!(solution.to_line(), EASY_SOLUTION);
+        assert_eq!(solver.statistics().guesses, 0);
+    }
+
+    #[test]
+    fn test_solve_hard_with_backtracking() {
+        let puzzle: Sudoku = HARD.parse().unwrap();
+        let mut solver = Solver::new();
+        let solution = solver.solve(&puzzle).unwrap();
+        assert_eq!(solution.to_line(), HARD_SOLUTION);
+        assert!(solution.is_solved());
+        assert!(solution.is_completion_of(&puzzle));
+        assert!(solver.statistics().guesses > 0);
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        // Valid givens, but the top left cell has no candidate left.
+        let puzzle: Sudoku = ".123456....................7........8........9...................................".parse().unwrap();
+        let mut solver = Solver::new();
+        assert_eq!(solver.solve(&puzzle), None);
+        assert_eq!(solver.count_solutions(&puzzle, 2), 0);
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let mut solver = Solver::new();
+        assert_eq!(solver.count_solutions(&EASY.parse().unwrap(), 2), 1);
+        assert_eq!(solver.count_solutions(&Sudoku::empty(), 5), 5);
+    }
+}
@@ -0,0 +1,33 @@
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Rule as turn letters for ants, for example RL, or in Golly notation, for example {{{1,2,0},{0,8,0}}}
+    #[arg(short, long, default_value = "RL")]
+    pub rule: String,
+
+    /// Maximum number of steps
+    #[arg(short, long, default_value_t = 11_000)]
+    pub steps: u64,
+
+    /// Stop when the turmite builds a highway
+    #[arg(long)]
+    pub stop_on_highway: bool,
+
+    /// Largest width of the grid shown in the terminal
+    #[arg(short, long, default_value_t = 80)]
+    pub width: usize,
+
+    /// Largest height of the grid shown in the terminal
+    #[arg(long, default_value_t = 48)]
+    pub height: usize,
+
+    /// Write the grid to an image file instead of the terminal. PNG, or PPM/PGM by extension
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// Size of a cell in pixels in the image
+    #[arg(long, default_value_t = 4)]
+    pub scale: usize,
+}
@@ -0,0 +1,302 @@
+///
+/// Langton's ant and turmites.
+/// Description: https://en.wikipedia.org/wiki/Turmite
+///
+/// A turmite walks on an unbounded grid of colored cells. In each step it reads
+/// the color of its cell, and the transition for its state and that color tells
+/// which color to write, how to turn and which state to go to. Then it moves one
+/// cell forward. Langton's ant is the turmite with one state and two colors that
+/// turns right on white and left on black.
+///
+pub mod rule;
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use rule::Rule;
+
+///
+/// Number of steps between the checks for a highway.
+///
+const HIGHWAY_CHECK_INTERVAL: u64 = 1000;
+
+///
+/// Longest highway period that is detected.
+///
+const MAX_HIGHWAY_PERIOD: usize = 1000;
+
+///
+/// Number of repetitions of the period needed before a highway is reported.
+///
+const HIGHWAY_REPETITIONS: usize = 4;
+
+///
+/// Number of recent steps kept for the highway detection.
+///
+const HISTORY_LENGTH: usize = HIGHWAY_REPETITIONS * MAX_HIGHWAY_PERIOD;
+
+///
+/// TurmiteError enum to represent errors when reading a rule.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TurmiteError {
+    // The rule is not in the letter or the Golly notation.
+    InvalidRule { rule: String },
+    // The transition table is inconsistent.
+    InvalidTable { message: String },
+}
+
+impl fmt::Display for TurmiteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TurmiteError::InvalidRule { rule } => write!(f, "Invalid rule {rule:?}"),
+            TurmiteError::InvalidTable { message } => write!(f, "Invalid transition table: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for TurmiteError {}
+
+///
+/// Direction enum in clockwise order. The y axis points down.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    ///
+    /// Returns the direction after turning the number of quarter turns clockwise.
+    ///
+    pub fn turned(&self, quarter_turns: u8) -> Direction {
+        Direction::ALL[(*self as usize + quarter_turns as usize) % 4]
+    }
+
+    ///
+    /// Returns the change of the coordinates when moving one cell forward.
+    ///
+    pub fn offset(&self) -> (i64, i64) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+        }
+    }
+}
+
+///
+/// Highway struct describing a turmite that repeats the same moves forever
+/// while moving away from the rest of the pattern.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Highway {
+    // Number of steps in one repetition.
+    pub period: usize,
+    // Movement of the turmite during one repetition.
+    pub displacement: (i64, i64),
+    // Step at which the highway was detected.
+    pub detected_at: u64,
+}
+
+///
+/// Turmite struct containing the turmite and the grid it walks on.
+///
+/// Only cells with a color other than 0 are stored, so the grid is unbounded and
+/// the memory grows with the visited area.
+///
+#[derive(Debug, Clone)]
+pub struct Turmite {
+    rule: Rule,
+    cells: HashMap<(i64, i64), u8>,
+    position: (i64, i64),
+    direction: Direction,
+    state: usize,
+    steps: u64,
+    // Smallest rectangle containing every visited cell, as (min x, min y, max x, max y).
+    bounds: (i64, i64, i64, i64),
+    // Recent (state, color, direction) and positions, used to detect highways.
+    history: VecDeque<(usize, u8, Direction, (i64, i64))>,
+    highway: Option<Highway>,
+}
+
+impl Turmite {
+    ///
+    /// Creates a turmite at (0, 0) facing up in state 0 on an empty grid.
+    ///
+    pub fn new(rule: Rule) -> Turmite {
+        Turmite { rule, cells: HashMap::new(), position: (0, 0), direction: Direction::Up, state: 0, steps: 0, bounds: (0, 0, 0, 0), history: VecDeque::with_capacity(HISTORY_LENGTH), highway: None }
+    }
+
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    pub fn position(&self) -> (i64, i64) {
+        self.position
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn state(&self) -> usize {
+        self.state
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn bounds(&self) -> (i64, i64, i64, i64) {
+        self.bounds
+    }
+
+    ///
+    /// Returns the highway if one has been detected.
+    ///
+    pub fn highway(&self) -> Option<Highway> {
+        self.highway
+    }
+
+    ///
+    /// Returns the color of the cell.
+    ///
+    pub fn color(&self, x: i64, y: i64) -> u8 {
+        self.cells.get(&(x, y)).copied().unwrap_or(0)
+    }
+
+    ///
+    /// Returns the number of cells with a color other than 0.
+    ///
+    pub fn colored_cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    ///
+    /// Makes one step: read, write, turn, move.
+    ///
+    pub fn step(&mut self) {
+        let color = self.color(self.position.0, self.position.1);
+        let transition = self.rule.transition(self.state, color);
+        if transition.write == 0 {
+            self.cells.remove(&self.position);
+        } else {
+            self.cells.insert(self.position, transition.write);
+        }
+        if self.history.len() == HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+        self.history.push_back((self.state, color, self.direction, self.position));
+
+        self.direction = self.direction.turned(transition.turn.quarter_turns());
+        self.state = transition.next_state;
+        let (dx, dy) = self.direction.offset();
+        self.position = (self.position.0 + dx, self.position.1 + dy);
+        let (x, y) = self.position;
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+        self.bounds = (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y));
+        self.steps += 1;
+
+        if self.highway.is_none() && self.steps.is_multiple_of(HIGHWAY_CHECK_INTERVAL) {
+            self.highway = self.find_highway();
+        }
+    }
+
+    ///
+    /// Runs until the step limit is reached, or a highway is found if stop_on_highway is set.
+    ///
+    /// max_steps: Total number of steps, including the steps already made.
+    ///
+    pub fn run(&mut self, max_steps: u64, stop_on_highway: bool) {
+        while self.steps < max_steps && !(stop_on_highway && self.highway.is_some()) {
+            self.step();
+        }
+    }
+
+    ///
+    /// Looks for the shortest period where the recent steps repeat with the same
+    /// state, color and direction, and the turmite moves the same distance in
+    /// each repetition. A turmite repeating its steps while moving away only
+    /// meets cells it has not visited, so it keeps repeating forever.
+    ///
+    fn find_highway(&self) -> Option<Highway> {
+        let length = self.history.len();
+        (1..=MAX_HIGHWAY_PERIOD).filter(|period| period * HIGHWAY_REPETITIONS <= length).find_map(|period| {
+            let window = period * HIGHWAY_REPETITIONS;
+            let start = length - window;
+            let repeats = (start..length - period).all(|index| {
+                let (state, color, direction, _) = self.history[index];
+                let (next_state, next_color, next_direction, _) = self.history[index + period];
+                (state, color, direction) == (next_state, next_color, next_direction)
+            });
+            let (_, _, _, (first_x, first_y)) = self.history[start];
+            let (_, _, _, (second_x, second_y)) = self.history[start + period];
+            let displacement = (second_x - first_x, second_y - first_y);
+            (repeats && displacement != (0, 0)).then_some(Highway { period, displacement, detected_at: self.steps })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_steps() {
+        let mut ant = Turmite::new(Rule::langtons_ant());
+        ant.step();
+        assert_eq!(ant.position(), (1, 0));
+        assert_eq!(ant.direction(), Direction::Right);
+        (0..3).for_each(|_| ant.step());
+        // The ant walked a square clockwise and is back at the start.
+        assert_eq!(ant.position(), (0, 0));
+        assert_eq!(ant.colored_cells(), 4);
+        ant.step();
+        // The start cell is black now, so the ant turns left and clears it.
+        assert_eq!(ant.direction(), Direction::Left);
+        assert_eq!(ant.color(0, 0), 0);
+    }
+
+    #[test]
+    fn test_langtons_ant_highway() {
+        let mut ant = Turmite::new(Rule::langtons_ant());
+        ant.run(12_000, true);
+        let highway = ant.highway().expect("Langton's ant builds a highway after about 10000 steps");
+        assert_eq!(highway.period, 104);
+        assert_eq!((highway.displacement.0.abs(), highway.displacement.1.abs()), (2, 2));
+        assert!(highway.detected_at > 9_900);
+    }
+
+    #[test]
+    fn test_notations_give_same_result() {
+        let mut letters = Turmite::new("RL".parse().unwrap());
+        let mut golly = Turmite::new("{{{1,2,0},{0,8,0}}}".parse().unwrap());
+        letters.run(500, false);
+        golly.run(500, false);
+        assert_eq!(letters.position(), golly.position());
+        assert_eq!(letters.cells, golly.cells);
+    }
+
+    #[test]
+    fn test_symmetric_ant_has_no_highway() {
+        // LLRR grows a symmetric pattern forever without a highway.
+        let mut ant = Turmite::new("LLRR".parse().unwrap());
+        ant.run(20_000, true);
+        assert_eq!(ant.highway(), None);
+        assert_eq!(ant.steps(), 20_000);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut ant = Turmite::new(Rule::langtons_ant());
+        ant.run(3, false);
+        assert_eq!(ant.bounds(), (0, 0, 1, 1));
+    }
+}
@@ -0,0 +1,81 @@
+mod args;
+
+use args::Args;
+use clap::Parser;
+use render::terminal::render_symbols;
+use render::{Color, Image, palette};
+use turmite::rule::Rule;
+use turmite::{Direction, Turmite};
+
+///
+/// Symbols for the colors in the terminal.
+///
+const SYMBOLS: &[char] = &[' ', '█', '▓', '▒', '░', '#', '%', '*', '+', '=', '-', ':', '.'];
+
+///
+/// Color of the turmite in images.
+///
+const TURMITE_COLOR: Color = [255, 0, 0];
+
+/**
+ * Langton's ant and turmites.
+ * Runs the turmite for a number of steps and shows the grid in the terminal
+ * or writes it to an image.
+ */
+fn main() {
+    let args = Args::parse();
+    let rule = match args.rule.parse::<Rule>() {
+        Ok(rule) => rule,
+        Err(err) => {
+            eprintln!("{err}");
+            return;
+        }
+    };
+    let mut turmite = Turmite::new(rule);
+    turmite.run(args.steps, args.stop_on_highway);
+
+    println!("Rule: {}, steps: {}, colored cells: {}", turmite.rule(), turmite.steps(), turmite.colored_cells());
+    if let Some(highway) = turmite.highway() {
+        println!("Highway detected at step {} with period {} and displacement {:?}", highway.detected_at, highway.period, highway.displacement);
+    }
+
+    let (min_x, min_y, max_x, max_y) = turmite.bounds();
+    let (width, height) = ((max_x - min_x + 1) as usize, (max_y - min_y + 1) as usize);
+    match args.output {
+        Some(output) => {
+            let colors = palette(turmite.rule().colors());
+            let image = Image::from_cells(width, height, args.scale, |x, y| {
+                let (cell_x, cell_y) = (min_x + x as i64, min_y + y as i64);
+                if (cell_x, cell_y) == turmite.position() { TURMITE_COLOR } else { colors[turmite.color(cell_x, cell_y) as usize] }
+            });
+            if let Err(err) = image.save(&output) {
+                eprintln!("Failed to write image {output}: {err}");
+            }
+        }
+        None => {
+            // Show the area around the turmite if the grid is larger than the terminal.
+            let (view_width, view_height) = (width.min(args.width), height.min(args.height));
+            let left = (turmite.position().0 - view_width as i64 / 2).clamp(min_x, max_x + 1 - view_width as i64);
+            let top = (turmite.position().1 - view_height as i64 / 2).clamp(min_y, max_y + 1 - view_height as i64);
+            print!(
+                "{}",
+                render_symbols(view_width, view_height, |x, y| {
+                    let (cell_x, cell_y) = (left + x as i64, top + y as i64);
+                    if (cell_x, cell_y) == turmite.position() { direction_symbol(turmite.direction()) } else { SYMBOLS[turmite.color(cell_x, cell_y) as usize % SYMBOLS.len()] }
+                })
+            );
+        }
+    }
+}
+
+/**
+ * Returns the arrow showing the direction of the turmite.
+ */
+fn direction_symbol(direction: Direction) -> char {
+    match direction {
+        Direction::Up => '^',
+        Direction::Right => '>',
+        Direction::Down => 'v',
+        Direction::Left => '<',
+    }
+}
@@ -0,0 +1,263 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::TurmiteError;
+
+///
+/// Turn enum for the change of direction after reading a cell.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Turn {
+    None,
+    Right,
+    UTurn,
+    Left,
+}
+
+impl Turn {
+    ///
+    /// Returns the number of quarter turns clockwise.
+    ///
+    pub fn quarter_turns(&self) -> u8 {
+        match self {
+            Turn::None => 0,
+            Turn::Right => 1,
+            Turn::UTurn => 2,
+            Turn::Left => 3,
+        }
+    }
+
+    ///
+    /// Returns the turn for a letter of the ant notation.
+    ///
+    fn from_letter(letter: char) -> Option<Turn> {
+        match letter.to_ascii_uppercase() {
+            'N' => Some(Turn::None),
+            'R' => Some(Turn::Right),
+            'U' => Some(Turn::UTurn),
+            'L' => Some(Turn::Left),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Returns the turn for a code of the Golly notation, where 1 is no turn,
+    /// 2 right, 4 u-turn and 8 left.
+    ///
+    fn from_code(code: u32) -> Option<Turn> {
+        match code {
+            1 => Some(Turn::None),
+            2 => Some(Turn::Right),
+            4 => Some(Turn::UTurn),
+            8 => Some(Turn::Left),
+            _ => None,
+        }
+    }
+
+    fn code(&self) -> u32 {
+        1 << self.quarter_turns()
+    }
+}
+
+///
+/// Transition struct describing what the turmite does in a state when reading a color.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    // Color written to the cell.
+    pub write: u8,
+    // Turn made before moving forward.
+    pub turn: Turn,
+    // State after the move.
+    pub next_state: usize,
+}
+
+///
+/// Rule struct with the transition table of a turmite, indexed by state and color.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    table: Vec<Vec<Transition>>,
+}
+
+impl Rule {
+    ///
+    /// Creates a rule from the transition table.
+    ///
+    /// table: One row per state with one transition per color. All rows must have
+    ///        the same length, and the written colors and next states must exist.
+    ///
+    /// result: The rule or an error if the table is inconsistent.
+    ///
+    pub fn new(table: Vec<Vec<Transition>>) -> Result<Rule, TurmiteError> {
+        let colors = table.first().map_or(0, |row| row.len());
+        if colors < 2 || colors > u8::MAX as usize {
+            return Err(TurmiteError::InvalidTable { message: format!("Expected 2-255 colors, found {colors}") });
+        }
+        for (state, row) in table.iter().enumerate() {
+            if row.len() != colors {
+                return Err(TurmiteError::InvalidTable { message: format!("State {state} has {} colors, expected {colors}", row.len()) });
+            }
+            if let Some(transition) = row.iter().find(|transition| transition.write as usize >= colors || transition.next_state >= table.len()) {
+                return Err(TurmiteError::InvalidTable { message: format!("State {state} has an invalid transition {transition:?}") });
+            }
+        }
+        Ok(Rule { table })
+    }
+
+    ///
+    /// Langton's ant: turn right on color 0, left on color 1, and flip the color.
+    ///
+    pub fn langtons_ant() -> Rule {
+        "RL".parse().expect("Valid rule")
+    }
+
+    pub fn states(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn colors(&self) -> usize {
+        self.table[0].len()
+    }
+
+    ///
+    /// Returns the transition for the state and the color.
+    ///
+    pub fn transition(&self, state: usize, color: u8) -> Transition {
+        self.table[state][color as usize]
+    }
+}
+
+impl FromStr for Rule {
+    type Err = TurmiteError;
+
+    ///
+    /// Parses a rule in one of two notations.
+    ///
+    /// Ants with a single state are written as one turn letter per color, for example
+    /// RL for Langton's ant or LLRR. The ant writes the next color, so the colors cycle.
+    ///
+    /// General turmites use the Golly notation with a list of states, each a list of
+    /// {color to write, turn, next state} per color, for example {{{1,2,0},{0,8,0}}}.
+    /// Turns are 1 for none, 2 right, 4 u-turn and 8 left.
+    ///
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        if text.starts_with('{') { parse_golly(text) } else { parse_letters(text) }
+    }
+}
+
+impl fmt::Display for Rule {
+    ///
+    /// Writes the rule with the letter notation if possible, otherwise the Golly notation.
+    ///
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let colors = self.colors();
+        let is_ant = self.states() == 1 && self.table[0].iter().enumerate().all(|(color, transition)| transition.write as usize == (color + 1) % colors);
+        if is_ant {
+            return self.table[0].iter().try_for_each(|transition| write!(f, "{}", ['N', 'R', 'U', 'L'][transition.turn.quarter_turns() as usize]));
+        }
+        let states: Vec<String> = self
+            .table
+            .iter()
+            .map(|row| {
+                let transitions: Vec<String> = row.iter().map(|transition| format!("{{{},{},{}}}", transition.write, transition.turn.code(), transition.next_state)).collect();
+                format!("{{{}}}", transitions.join(","))
+            })
+            .collect();
+        write!(f, "{{{}}}", states.join(","))
+    }
+}
+
+///
+/// Parses the letter notation of ants.
+///
+fn parse_letters(text: &str) -> Result<Rule, TurmiteError> {
+    let turns = text.chars().map(Turn::from_letter).collect::<Option<Vec<Turn>>>().ok_or_else(|| TurmiteError::InvalidRule { rule: text.to_string() })?;
+    let colors = turns.len();
+    let row = turns.into_iter().enumerate().map(|(color, turn)| Transition { write: ((color + 1) % colors) as u8, turn, next_state: 0 }).collect();
+    Rule::new(vec![row])
+}
+
+///
+/// Parses the Golly notation of turmites.
+///
+fn parse_golly(text: &str) -> Result<Rule, TurmiteError> {
+    let invalid = || TurmiteError::InvalidRule { rule: text.to_string() };
+    // Numbers are read in order, and the nesting depth tells where a state starts and ends.
+    let mut table: Vec<Vec<Transition>> = Vec::new();
+    let mut numbers: Vec<u32> = Vec::new();
+    let mut depth = 0;
+    let mut number = String::new();
+    for character in text.chars() {
+        if character.is_ascii_digit() {
+            number.push(character);
+            continue;
+        }
+        if !number.is_empty() {
+            numbers.push(number.parse().map_err(|_| invalid())?);
+            number.clear();
+        }
+        match character {
+            '{' => {
+                depth += 1;
+                if depth == 2 {
+                    table.push(Vec::new());
+                }
+            }
+            '}' => {
+                if depth == 3 {
+                    let [write, turn, next_state] = numbers[..] else { return Err(invalid()) };
+                    let transition = Transition { write: u8::try_from(write).map_err(|_| invalid())?, turn: Turn::from_code(turn).ok_or_else(invalid)?, next_state: next_state as usize };
+                    table.last_mut().ok_or_else(invalid)?.push(transition);
+                    numbers.clear();
+                }
+                depth -= 1;
+                if depth < 0 {
+                    return Err(invalid());
+                }
+            }
+            ',' => {}
+            character if character.is_whitespace() => {}
+            _ => return Err(invalid()),
+        }
+    }
+    if depth != 0 || !numbers.is_empty() || !number.is_empty() {
+        return Err(invalid());
+    }
+    Rule::new(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_letters() {
+        let rule: Rule = "LLRR".parse().unwrap();
+        assert_eq!(rule.states(), 1);
+        assert_eq!(rule.colors(), 4);
+        assert_eq!(rule.transition(0, 3), Transition { write: 0, turn: Turn::Right, next_state: 0 });
+        assert_eq!(rule.to_string(), "LLRR");
+    }
+
+    #[test]
+    fn test_parse_golly() {
+        let rule: Rule = "{{{1, 2, 0}, {0, 8, 0}}}".parse().unwrap();
+        assert_eq!(rule, Rule::langtons_ant());
+        let fibonacci: Rule = "{{{1,8,1},{1,8,1}},{{1,2,1},{0,1,0}}}".parse().unwrap();
+        assert_eq!(fibonacci.states(), 2);
+        assert_eq!(fibonacci.transition(1, 1), Transition { write: 0, turn: Turn::None, next_state: 0 });
+        assert_eq!(fibonacci.to_string(), "{{{1,8,1},{1,8,1}},{{1,2,1},{0,1,0}}}");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("RX".parse::<Rule>().is_err());
+        assert!("R".parse::<Rule>().is_err());
+        assert!("{{{1,3,0},{0,8,0}}}".parse::<Rule>().is_err());
+        assert!("{{{1,2,1},{0,8,0}}}".parse::<Rule>().is_err());
+        assert!("{{{1,2,0},{0,8,0}}".parse::<Rule>().is_err());
+        assert!("{{{1,2},{0,8,0}}}".parse::<Rule>().is_err());
+    }
+}